@@ -0,0 +1,178 @@
+use rocket::Request;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::{options, timing};
+
+///
+/// Longest error detail sent in an alert
+///
+pub static MAX_DETAIL: usize = 512;
+
+struct Sent {
+    at: Instant,
+    suppressed: u64
+}
+
+///
+/// Notify the configured webhooks of requests that fail with a server error
+///
+/// Alerts are keyed by the method, path & reason of the error. Once an alert
+/// is sent, the same error is suppressed for the rest of the window and the
+/// number suppressed is reported with the next alert for it
+///
+pub struct Alerts {
+    targets: Vec<String>,
+    window: Duration,
+    sent: Mutex<HashMap<String, Sent>>
+}
+
+impl Alerts {
+    pub fn new(alerts: &options::Alerts) -> Self {
+        Alerts {
+            targets: alerts.targets.clone().unwrap_or_default(),
+            window: Duration::from_secs(u64::from(alerts.window())),
+            sent: Mutex::new(HashMap::new())
+        }
+    }
+
+    ///
+    /// Send an alert for a server error returned by a request, unless the same
+    /// error has already been alerted on within the window
+    ///
+    pub fn notify(&self, request: &Request, code: u16, reason: &str, detail: &str) {
+        if self.targets.is_empty() {
+            return;
+        }
+
+        let key = format!("{} {} {}", request.method().as_str(), request.uri().path(), reason);
+
+        let suppressed = match self.due(&key, Instant::now()) {
+            Some(suppressed) => suppressed,
+            None => { return; }
+        };
+
+        let body = json!({
+            "type": "error",
+            "code": code,
+            "status": rocket::http::Status::from_code(code).map(|status| status.reason).unwrap_or(""),
+            "reason": reason,
+            "detail": sanitize(detail),
+            "route": timing::describe(request.method().as_str(), request.uri().path(), request.uri().query()),
+            "request_id": request.headers().get_one("X-Request-Id"),
+            "suppressed": suppressed
+        }).to_string();
+
+        let targets = self.targets.clone();
+
+        //Alerts are sent in the background so the failed request is not held up
+        thread::spawn(move || {
+            let client = match reqwest::Client::builder().timeout(Duration::from_secs(10)).build() {
+                Ok(client) => client,
+                Err(err) => {
+                    println!("WARN: Failed to create alert client: {:?}", err);
+                    return;
+                }
+            };
+
+            for target in targets {
+                match client.post(target.as_str())
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .header("X-Hecate-Event", "alert")
+                    .body(body.clone())
+                    .send() {
+                    Ok(_) => (),
+                    Err(err) => println!("WARN: Failed to send alert to {}: {:?}", target, err)
+                };
+            }
+        });
+    }
+
+    ///
+    /// Whether an alert with the given key is due, returning the number of
+    /// alerts for it that were suppressed since the last one was sent
+    ///
+    fn due(&self, key: &str, now: Instant) -> Option<u64> {
+        let mut sent = match self.sent.lock() {
+            Ok(sent) => sent,
+            Err(_) => { return None; }
+        };
+
+        let window = self.window;
+        let suppressed = match sent.get_mut(key) {
+            Some(ref mut last) if now.duration_since(last.at) < window => {
+                last.suppressed += 1;
+                return None;
+            },
+            Some(last) => last.suppressed,
+            None => 0
+        };
+
+        //Forget the alerts whose window has passed so the map cannot grow without bound
+        sent.retain(|_, last| now.duration_since(last.at) < window);
+        sent.insert(String::from(key), Sent {
+            at: now,
+            suppressed: 0
+        });
+
+        Some(suppressed)
+    }
+}
+
+///
+/// Reduce the full error to a single line of printable characters that is no
+/// longer than `MAX_DETAIL`
+///
+fn sanitize(detail: &str) -> String {
+    let line: String = detail.lines()
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect();
+
+    let line = line.trim();
+
+    match line.char_indices().nth(MAX_DETAIL) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => String::from(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alerts(window: u32) -> Alerts {
+        Alerts::new(&options::Alerts {
+            targets: Some(vec![String::from("http://localhost/hook")]),
+            window: Some(window)
+        })
+    }
+
+    #[test]
+    fn alerts_due() {
+        let alerts = alerts(60);
+        let start = Instant::now();
+
+        assert_eq!(alerts.due("GET /api/meta Database Error", start), Some(0));
+        assert_eq!(alerts.due("GET /api/meta Database Error", start + Duration::from_secs(1)), None);
+        assert_eq!(alerts.due("GET /api/meta Database Error", start + Duration::from_secs(59)), None);
+
+        //A different error is alerted on independently
+        assert_eq!(alerts.due("GET /api/styles Database Error", start + Duration::from_secs(2)), Some(0));
+
+        assert_eq!(alerts.due("GET /api/meta Database Error", start + Duration::from_secs(60)), Some(2));
+        assert_eq!(alerts.due("GET /api/meta Database Error", start + Duration::from_secs(61)), None);
+    }
+
+    #[test]
+    fn sanitize_detail() {
+        assert_eq!(sanitize("relation \"meta\" does not exist"), "relation \"meta\" does not exist");
+        assert_eq!(sanitize("ERROR: failed\nDETAIL: Key (email)=(a@b.com)"), "ERROR: failed");
+        assert_eq!(sanitize("tab\tseparated\u{7}"), "tabseparated");
+        assert_eq!(sanitize(&"a".repeat(MAX_DETAIL + 10)), format!("{}...", "a".repeat(MAX_DETAIL)));
+        assert_eq!(sanitize(""), "");
+    }
+}
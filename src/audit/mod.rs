@@ -0,0 +1,159 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Request, Response, State};
+use crate::err::HecateError;
+use crate::auth::{Denied, Impersonated};
+use crate::DbReadWrite;
+use crate::proxy;
+
+///
+/// Record an action in the audit log
+///
+/// `impersonator` is the admin acting on behalf of `uid` when the action was
+/// performed through an impersonation token
+///
+pub fn log(conn: &impl postgres::GenericConnection, uid: Option<i64>, impersonator: Option<i64>, action: &str, detail: serde_json::Value) -> Result<bool, HecateError> {
+    match conn.execute("
+        INSERT INTO audit (uid, impersonator, action, detail, created)
+            VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                NOW()
+            )
+    ", &[&uid, &impersonator, &action, &detail]) {
+        Ok(_) => Ok(true),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Audit every request that was authenticated with an impersonation token
+///
+pub struct Impersonation;
+
+impl Fairing for Impersonation {
+    fn info(&self) -> Info {
+        Info {
+            name: "Impersonation Audit",
+            kind: Kind::Response
+        }
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        let (uid, impersonator) = match request.local_cache(|| Impersonated::new()).get() {
+            Some(session) => session,
+            None => { return; }
+        };
+
+        let detail = json!({
+            "method": request.method().as_str(),
+            "uri": request.uri().to_string(),
+            "status": response.status().code,
+            "ip": proxy::Client::from(request).ip.map(|ip| ip.to_string())
+        });
+
+        let conn = match request.guard::<State<DbReadWrite>>().succeeded() {
+            Some(conn) => match conn.get() {
+                Ok(conn) => conn,
+                Err(_) => {
+                    println!("WARN: Failed to audit impersonated request: {}", detail);
+                    return;
+                }
+            },
+            None => { return; }
+        };
+
+        match log(&*conn, Some(uid), Some(impersonator), "impersonated", detail) {
+            Ok(_) => (),
+            Err(err) => println!("WARN: Failed to audit impersonated request: {}", err.to_string())
+        };
+    }
+}
+
+///
+/// Audit every request that was denied by the auth rules
+///
+/// Only the path of the request is recorded as the query string can hold
+/// credentials, ie: the password of `GET /api/user/create`
+///
+pub struct Denials;
+
+impl Fairing for Denials {
+    fn info(&self) -> Info {
+        Info {
+            name: "Auth Denial Audit",
+            kind: Kind::Response
+        }
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        let (required, uid) = match request.local_cache(|| Denied::new()).get() {
+            Some(denied) => denied,
+            None => { return; }
+        };
+
+        //A denial the endpoint recovered from is not a denied request
+        if response.status().code != 401 && response.status().code != 403 {
+            return;
+        }
+
+        let detail = json!({
+            "scope": required,
+            "method": request.method().as_str(),
+            "uri": request.uri().path(),
+            "status": response.status().code,
+            "ip": proxy::Client::from(request).ip.map(|ip| ip.to_string())
+        });
+
+        let conn = match request.guard::<State<DbReadWrite>>().succeeded() {
+            Some(conn) => match conn.get() {
+                Ok(conn) => conn,
+                Err(_) => {
+                    println!("WARN: Failed to audit denied request: {}", detail);
+                    return;
+                }
+            },
+            None => { return; }
+        };
+
+        match log(&*conn, uid, None, "denied", detail) {
+            Ok(_) => (),
+            Err(err) => println!("WARN: Failed to audit denied request: {}", err.to_string())
+        };
+    }
+}
+
+///
+/// Recently denied requests, newest first. `offset` is the id of the last denial
+/// of the previous page
+///
+pub fn denials(conn: &impl postgres::GenericConnection, offset: Option<i64>, limit: Option<i64>) -> Result<serde_json::Value, HecateError> {
+    match conn.query("
+        SELECT
+            COALESCE(json_agg(row_to_json(row)), '[]'::JSON)
+        FROM (
+            SELECT
+                audit.id,
+                audit.uid,
+                users.username,
+                audit.detail->'scope' AS scope,
+                audit.detail->>'method' AS method,
+                audit.detail->>'uri' AS uri,
+                (audit.detail->>'status')::INTEGER AS status,
+                to_char(audit.created, 'YYYY-MM-DD HH24:MI:SS') AS created
+            FROM
+                audit
+                    LEFT JOIN users ON audit.uid = users.id
+            WHERE
+                audit.action = 'denied'
+                AND ($1::BIGINT IS NULL OR audit.id < $1)
+            ORDER BY
+                audit.id DESC
+            LIMIT $2::BIGINT
+        ) row;
+    ", &[&offset, &limit]) {
+        Ok(rows) => Ok(rows.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
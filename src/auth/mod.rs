@@ -1,8 +1,17 @@
 use rocket::request::{self, FromRequest};
-use rocket::http::Status;
-use rocket::{Request, Outcome};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Cookie, Status};
+use rocket::{Request, Response, Outcome, State};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
 
 use crate::err::HecateError;
+use crate::timing::Timings;
+use crate::{options, proxy, tenant};
+use crate::DbReadWrite;
 
 fn not_authed() -> HecateError {
     HecateError::new(401, String::from("You must be logged in to access this resource"), None)
@@ -68,6 +77,68 @@ fn is_auth(scope_type: &str, scope: &Option<String>) -> Result<bool, String> {
     }
 }
 
+///
+/// Recursively replace the values of the defaults with those of the overlay
+///
+fn overlay(defaults: &mut serde_json::Value, partial: &serde_json::Value, scope_type: Option<&str>) -> Result<bool, String> {
+    let partial = match partial.as_object() {
+        Some(partial) => partial,
+        None => {
+            return Err(match scope_type {
+                None => String::from("Auth Config Error: An auth overlay must be a JSON object"),
+                Some(scope_type) => format!("Auth Config Error: '{}' must be an object or null", scope_type)
+            });
+        }
+    };
+
+    for (key, value) in partial {
+        let name = match scope_type {
+            None => key.clone(),
+            Some(scope_type) => format!("{}::{}", scope_type, key)
+        };
+
+        match defaults.get_mut(key) {
+            None => { return Err(format!("Auth Config Error: '{}' is not a known auth category or behaviour", name)); },
+            Some(default) => {
+                if default.is_object() && !value.is_null() {
+                    overlay(default, value, Some(&*name))?;
+                } else {
+                    *default = value.clone();
+                }
+            }
+        };
+    }
+
+    Ok(true)
+}
+
+///
+/// Return the bound name of a `user@bound:<name>` scope
+///
+pub fn bound_scope(scope: &str) -> Option<&str> {
+    if scope.starts_with("user@bound:") && scope.len() > 11 {
+        Some(&scope[11..])
+    } else {
+        None
+    }
+}
+
+///
+/// Allows a category to be null, user, admin, or user@bound:<name>
+///
+/// A bound scope is met by any logged in user, the caller is responsible for
+/// ensuring the features being written are within the named bound
+///
+fn is_auth_bound(scope_type: &str, scope: &Option<String>) -> Result<bool, String> {
+    match scope {
+        &Some(ref scope_str) if bound_scope(scope_str).is_some() => Ok(true),
+        &Some(ref scope_str) if scope_str.starts_with("user@bound:") => {
+            Err(format!("Auth Config Error: '{}' must name a bound, ie: 'user@bound:<name>'", scope_type))
+        },
+        _ => is_auth(scope_type, scope)
+    }
+}
+
 pub trait ValidAuth {
     fn is_valid(&self) -> Result<bool, String>;
 }
@@ -249,6 +320,7 @@ impl ValidAuth for AuthMVT {
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct AuthUser {
     pub info: Option<String>,
+    pub update: Option<String>,
     pub list: Option<String>,
     pub create: Option<String>,
     pub create_session: Option<String>
@@ -258,6 +330,7 @@ impl AuthUser {
     fn new() -> Self {
         AuthUser {
             info: Some(String::from("self")),
+            update: Some(String::from("self")),
             list: Some(String::from("user")),
             create: Some(String::from("public")),
             create_session: Some(String::from("self"))
@@ -272,6 +345,7 @@ impl ValidAuth for AuthUser {
 
         is_self("user::create_session", &self.create_session)?;
         is_self("user::info", &self.info)?;
+        is_self("user::update", &self.update)?;
 
         Ok(true)
     }
@@ -345,7 +419,8 @@ pub struct AuthFeature {
     pub force: Option<String>,
     pub create: Option<String>,
     pub get: Option<String>,
-    pub history: Option<String>
+    pub history: Option<String>,
+    pub purge: Option<String>
 }
 
 impl AuthFeature {
@@ -354,17 +429,19 @@ impl AuthFeature {
             force: Some(String::from("none")),
             create: Some(String::from("user")),
             get: Some(String::from("public")),
-            history: Some(String::from("public"))
+            history: Some(String::from("public")),
+            purge: Some(String::from("admin"))
         }
     }
 }
 
 impl ValidAuth for AuthFeature {
     fn is_valid(&self) -> Result<bool, String> {
-        is_auth("feature::create", &self.create)?;
+        is_auth_bound("feature::create", &self.create)?;
         is_auth("feature::force", &self.force)?;
         is_all("feature::get", &self.get)?;
         is_all("feature::history", &self.history)?;
+        is_auth("feature::purge", &self.purge)?;
 
         Ok(true)
     }
@@ -424,6 +501,33 @@ impl ValidAuth for AuthOSM {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AuthIndex {
+    pub list: Option<String>,
+    pub create: Option<String>,
+    pub delete: Option<String>
+}
+
+impl AuthIndex {
+    fn new() -> Self {
+        AuthIndex {
+            list: Some(String::from("public")),
+            create: Some(String::from("admin")),
+            delete: Some(String::from("admin"))
+        }
+    }
+}
+
+impl ValidAuth for AuthIndex {
+    fn is_valid(&self) -> Result<bool, String> {
+        is_all("index::list", &self.list)?;
+        is_auth("index::create", &self.create)?;
+        is_auth("index::delete", &self.delete)?;
+
+        Ok(true)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct CustomAuth {
     pub server: Option<String>,
@@ -439,7 +543,9 @@ pub struct CustomAuth {
     pub delta: Option<AuthDelta>,
     pub bounds: Option<AuthBounds>,
     pub clone: Option<AuthClone>,
-    pub osm: Option<AuthOSM>
+    pub osm: Option<AuthOSM>,
+    pub index: Option<AuthIndex>,
+    pub require_auth: Option<bool>
 }
 
 impl ValidAuth for CustomAuth {
@@ -496,49 +602,98 @@ impl ValidAuth for CustomAuth {
             Some(ref osm) => { osm.is_valid()?; }
         };
 
+        match &self.index {
+            None => (),
+            Some(ref index) => { index.is_valid()?; }
+        };
+
         Ok(true)
     }
 }
 
+///
+/// The access level stored on a user account, levels are ordered so that a
+/// higher level meets the requirements of any lower level
+///
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub enum AccessLevel {
+    User,
+    Admin
+}
+
+impl AccessLevel {
+    ///
+    /// Parse the access column of the users table, where NULL is a regular user
+    ///
+    /// Unrecognized values are an error rather than being treated as a regular
+    /// user so that a misconfigured account does not silently lose access
+    ///
+    pub fn from_db(access: &Option<String>) -> Result<Self, HecateError> {
+        match access {
+            None => Ok(AccessLevel::User),
+            Some(access) => match access.as_str() {
+                "user" => Ok(AccessLevel::User),
+                "admin" => Ok(AccessLevel::Admin),
+                _ => Err(HecateError::new(500, String::from("Auth Config Error: Unrecognized user access level"), Some(format!("Auth Config Error: Unrecognized user access level '{}'", access))))
+            }
+        }
+    }
+}
+
 ///
 /// Determines whether the current auth state meets or exceeds the
 /// requirements of an endpoint
 ///
 fn auth_met(required: &Option<String>, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+    //Routes exempted by the server config skip the rules, unless they are disabled
+    //or require an admin or the user the request acts on
+    if auth.exempt {
+        match required.as_ref().map(|req| req.as_str()) {
+            None | Some("admin") | Some("self") => (),
+            Some(_) => { return Ok(true); }
+        };
+    }
+
     auth.validate(conn)?;
 
     match required {
         None => Err(not_authed()),
-        Some(req) => match req.as_ref() {
-            "public" => Ok(true),
-            "admin" => {
-                if auth.uid.is_none() || auth.access.is_none() {
-                    return Err(not_authed());
-                } else if auth.access == Some(String::from("admin")) {
-                    return Ok(true);
-                } else {
-                    return Err(not_authed());
-                }
-            },
-            "user" => {
-                if auth.uid.is_some() {
-                    return Ok(true);
-                } else {
-                    return Err(not_authed());
-                }
-            },
-            "self" => {
-                //Note: This ensures the user is validated,
-                //it is up to the parent caller to ensure
-                //the UID of 'self' matches the requested resource
+        Some(req) if req == "public" => Ok(true),
+        Some(req) => {
+            if req == "admin" && auth.impersonator.is_some() {
+                return Err(HecateError::new(403, String::from("Impersonated sessions cannot perform admin operations"), None));
+            }
 
-                if auth.uid.is_some() {
-                    return Ok(true);
-                } else {
-                    return Err(not_authed());
-                }
-            },
-            _ => Err(not_authed())
+            let level = match auth.access_level()? {
+                None => { return Err(not_authed()); },
+                Some(level) => level
+            };
+
+            match req.as_ref() {
+                "admin" => {
+                    if level < AccessLevel::Admin {
+                        Err(not_authed())
+                    } else if !auth.admin.permits(auth.ip) {
+                        Err(HecateError::new(403, String::from("Admin operations are not permitted from this network"), None))
+                    } else {
+                        Ok(true)
+                    }
+                },
+                "user" => Ok(true),
+                "self" => {
+                    //Note: This ensures the user is validated,
+                    //it is up to the parent caller to ensure
+                    //the UID of 'self' matches the requested resource
+                    Ok(true)
+                },
+                req if bound_scope(req).is_some() => {
+                    //Note: This ensures the user is validated,
+                    //it is up to the parent caller to ensure
+                    //the feature is within the bound
+                    Ok(true)
+                },
+                _ => Err(not_authed())
+            }
         }
     }
 }
@@ -559,7 +714,9 @@ impl CustomAuth {
             delta: Some(AuthDelta::new()),
             bounds: Some(AuthBounds::new()),
             clone: Some(AuthClone::new()),
-            osm: Some(AuthOSM::new())
+            osm: Some(AuthOSM::new()),
+            index: Some(AuthIndex::new()),
+            require_auth: Some(false)
         }
     }
 
@@ -569,285 +726,679 @@ impl CustomAuth {
         json_auth
     }
 
+    ///
+    /// Build a config from a partial config overlaid onto the defaults, so that
+    /// only the categories & behaviours that differ from the defaults need to be
+    /// given. A `null` category or behaviour disables it as in a full config
+    ///
+    /// Unknown categories & behaviours are rejected as a typo would otherwise
+    /// silently leave the default in place
+    ///
+    pub fn overlay(partial: &serde_json::Value) -> Result<Self, String> {
+        let mut auth = CustomAuth::new().to_json();
+
+        overlay(&mut auth, partial, None)?;
+
+        match serde_json::from_value(auth) {
+            Ok(auth) => Ok(auth),
+            Err(err) => Err(format!("Auth Config Error: {}", err))
+        }
+    }
+
+
+    ///
+    /// Determines whether the current auth state meets the requirements of an
+    /// endpoint, treating `public` endpoints as `user` when `require_auth` is set
+    ///
+    fn met(&self, required: &Option<String>, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+        let required = match required {
+            Some(req) if req == "public" && self.require_auth == Some(true) => Some(String::from("user")),
+            _ => required.clone()
+        };
+
+        match auth_met(&required, auth, conn) {
+            Ok(met) => Ok(met),
+            Err(err) => {
+                auth.denied.set(&required, auth.uid);
+                Err(err)
+            }
+        }
+    }
 
     pub fn is_admin(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
-        auth_met(&Some(String::from("admin")), auth, conn)
+        self.met(&Some(String::from("admin")), auth, conn)
+    }
+
+    pub fn is_user(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+        self.met(&Some(String::from("user")), auth, conn)
     }
 
     pub fn allows_server(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
-        auth_met(&self.server, auth, conn)
+        self.met(&self.server, auth, conn)
     }
 
     pub fn allows_webhooks_list(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.webhooks {
-            None => Err(not_authed()),
-            Some(webhooks) => auth_met(&webhooks.list, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(webhooks) => self.met(&webhooks.list, auth, conn)
         }
     }
 
     pub fn allows_webhooks_delete(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.webhooks {
-            None => Err(not_authed()),
-            Some(webhooks) => auth_met(&webhooks.delete, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(webhooks) => self.met(&webhooks.delete, auth, conn)
         }
     }
 
     pub fn allows_webhooks_update(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.webhooks {
-            None => Err(not_authed()),
-            Some(webhooks) => auth_met(&webhooks.update, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(webhooks) => self.met(&webhooks.update, auth, conn)
         }
     }
 
     pub fn allows_meta_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.meta {
-            None => Err(not_authed()),
-            Some(meta) => auth_met(&meta.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(meta) => self.met(&meta.get, auth, conn)
         }
     }
 
     pub fn allows_meta_list(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.meta {
-            None => Err(not_authed()),
-            Some(meta) => auth_met(&meta.list, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(meta) => self.met(&meta.list, auth, conn)
         }
     }
 
     pub fn allows_meta_set(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.meta {
-            None => Err(not_authed()),
-            Some(meta) => auth_met(&meta.set, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(meta) => self.met(&meta.set, auth, conn)
         }
     }
 
     pub fn allows_stats_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.stats {
-            None => Err(not_authed()),
-            Some(stats) => auth_met(&stats.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(stats) => self.met(&stats.get, auth, conn)
         }
     }
 
     pub fn allows_stats_bounds(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.stats {
-            None => Err(not_authed()),
-            Some(stats) => auth_met(&stats.bounds, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(stats) => self.met(&stats.bounds, auth, conn)
         }
     }
 
     pub fn allows_mvt_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.mvt {
-            None => Err(not_authed()),
-            Some(mvt) => auth_met(&mvt.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(mvt) => self.met(&mvt.get, auth, conn)
         }
     }
 
     pub fn allows_mvt_delete(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.mvt {
-            None => Err(not_authed()),
-            Some(mvt) => auth_met(&mvt.delete, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(mvt) => self.met(&mvt.delete, auth, conn)
         }
     }
 
     pub fn allows_mvt_regen(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.mvt {
-            None => Err(not_authed()),
-            Some(mvt) => auth_met(&mvt.regen, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(mvt) => self.met(&mvt.regen, auth, conn)
         }
     }
 
     pub fn allows_mvt_meta(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.mvt {
-            None => Err(not_authed()),
-            Some(mvt) => auth_met(&mvt.meta, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(mvt) => self.met(&mvt.meta, auth, conn)
         }
     }
 
     pub fn allows_user_list(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.user {
-            None => Err(not_authed()),
-            Some(user) => auth_met(&user.list, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(user) => self.met(&user.list, auth, conn)
         }
     }
 
     pub fn allows_user_create(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.user {
-            None => Err(not_authed()),
-            Some(user) => auth_met(&user.create, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(user) => self.met(&user.create, auth, conn)
         }
     }
 
     pub fn allows_user_info(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.user {
-            None => Err(not_authed()),
-            Some(user) => auth_met(&user.info, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(user) => self.met(&user.info, auth, conn)
+        }
+    }
+
+    pub fn allows_user_update(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+        match &self.user {
+            None => self.met(&None, auth, conn),
+            Some(user) => self.met(&user.update, auth, conn)
         }
     }
 
     pub fn allows_user_create_session(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.user {
-            None => Err(not_authed()),
-            Some(user) => auth_met(&user.create_session, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(user) => self.met(&user.create_session, auth, conn)
         }
     }
 
     pub fn allows_style_create(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.style {
-            None => Err(not_authed()),
-            Some(style) => auth_met(&style.create, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(style) => self.met(&style.create, auth, conn)
         }
     }
 
     pub fn allows_style_patch(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.style {
-            None => Err(not_authed()),
-            Some(style) => auth_met(&style.patch, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(style) => self.met(&style.patch, auth, conn)
         }
     }
 
     pub fn allows_style_set_public(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.style {
-            None => Err(not_authed()),
-            Some(style) => auth_met(&style.set_public, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(style) => self.met(&style.set_public, auth, conn)
         }
     }
 
     pub fn allows_style_set_private(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.style {
-            None => Err(not_authed()),
-            Some(style) => auth_met(&style.set_private, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(style) => self.met(&style.set_private, auth, conn)
         }
     }
 
     pub fn allows_style_delete(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.style {
-            None => Err(not_authed()),
-            Some(style) => auth_met(&style.delete, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(style) => self.met(&style.delete, auth, conn)
         }
     }
 
     pub fn allows_style_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.style {
-            None => Err(not_authed()),
-            Some(style) => auth_met(&style.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(style) => self.met(&style.get, auth, conn)
         }
     }
 
     pub fn allows_style_list(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.style {
-            None => Err(not_authed()),
-            Some(style) => auth_met(&style.list, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(style) => self.met(&style.list, auth, conn)
         }
     }
 
     pub fn allows_delta_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.delta {
-            None => Err(not_authed()),
-            Some(delta) => auth_met(&delta.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(delta) => self.met(&delta.get, auth, conn)
         }
     }
 
     pub fn allows_delta_list(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.delta {
-            None => Err(not_authed()),
-            Some(delta) => auth_met(&delta.list, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(delta) => self.met(&delta.list, auth, conn)
         }
     }
 
     pub fn allows_clone_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.clone {
-            None => Err(not_authed()),
-            Some(clone) => auth_met(&clone.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(clone) => self.met(&clone.get, auth, conn)
         }
     }
 
     pub fn allows_clone_query(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.clone {
-            None => Err(not_authed()),
-            Some(clone) => auth_met(&clone.query, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(clone) => self.met(&clone.query, auth, conn)
         }
     }
 
     pub fn allows_bounds_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.bounds {
-            None => Err(not_authed()),
-            Some(bounds) => auth_met(&bounds.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(bounds) => self.met(&bounds.get, auth, conn)
         }
     }
 
     pub fn allows_bounds_create(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.bounds {
-            None => Err(not_authed()),
-            Some(bounds) => auth_met(&bounds.create, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(bounds) => self.met(&bounds.create, auth, conn)
         }
     }
 
     pub fn allows_bounds_delete(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.bounds {
-            None => Err(not_authed()),
-            Some(bounds) => auth_met(&bounds.delete, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(bounds) => self.met(&bounds.delete, auth, conn)
         }
     }
 
     pub fn allows_bounds_list(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.bounds {
-            None => Err(not_authed()),
-            Some(bounds) => auth_met(&bounds.list, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(bounds) => self.met(&bounds.list, auth, conn)
         }
     }
 
     pub fn allows_feature_create(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.feature {
-            None => Err(not_authed()),
-            Some(feature) => auth_met(&feature.create, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(feature) => self.met(&feature.create, auth, conn)
+        }
+    }
+
+    ///
+    /// The bound that a user's feature writes must be within, admins are
+    /// never restricted to a bound
+    ///
+    pub fn feature_bound(&self, auth: &Auth) -> Option<String> {
+        if auth.has_admin_access() {
+            return None;
+        }
+
+        match &self.feature {
+            Some(AuthFeature { create: Some(ref create), .. }) => bound_scope(create).map(String::from),
+            _ => None
         }
     }
 
     pub fn allows_feature_force(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.feature {
-            None => Err(not_authed()),
-            Some(feature) => auth_met(&feature.force, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(feature) => self.met(&feature.force, auth, conn)
+        }
+    }
+
+    pub fn allows_feature_purge(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+        match &self.feature {
+            None => self.met(&None, auth, conn),
+            Some(feature) => self.met(&feature.purge, auth, conn)
         }
     }
 
     pub fn allows_feature_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.feature {
-            None => Err(not_authed()),
-            Some(feature) => auth_met(&feature.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(feature) => self.met(&feature.get, auth, conn)
         }
     }
 
     pub fn allows_feature_history(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.feature {
-            None => Err(not_authed()),
-            Some(feature) => auth_met(&feature.history, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(feature) => self.met(&feature.history, auth, conn)
         }
     }
 
     pub fn allows_schema_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.schema {
-            None => Err(not_authed()),
-            Some(schema) => auth_met(&schema.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(schema) => self.met(&schema.get, auth, conn)
         }
     }
 
     pub fn allows_auth_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.auth {
-            None => Err(not_authed()),
-            Some(a) => auth_met(&a.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(a) => self.met(&a.get, auth, conn)
         }
     }
 
     pub fn allows_osm_get(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.osm {
-            None => Err(not_authed()),
-            Some(osm) => auth_met(&osm.get, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(osm) => self.met(&osm.get, auth, conn)
         }
     }
 
     pub fn allows_osm_create(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
         match &self.osm {
-            None => Err(not_authed()),
-            Some(osm) => auth_met(&osm.create, auth, conn)
+            None => self.met(&None, auth, conn),
+            Some(osm) => self.met(&osm.create, auth, conn)
+        }
+    }
+
+    pub fn allows_index_list(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+        match &self.index {
+            None => self.met(&None, auth, conn),
+            Some(index) => self.met(&index.list, auth, conn)
+        }
+    }
+
+    pub fn allows_index_create(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+        match &self.index {
+            None => self.met(&None, auth, conn),
+            Some(index) => self.met(&index.create, auth, conn)
+        }
+    }
+
+    pub fn allows_index_delete(&self, auth: &mut Auth, conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+        match &self.index {
+            None => self.met(&None, auth, conn),
+            Some(index) => self.met(&index.delete, auth, conn)
+        }
+    }
+}
+
+///
+/// The user and admin of an impersonated session, shared through the request
+/// local cache so that the request can be audited once it has completed
+///
+#[derive(Debug, Clone)]
+pub struct Impersonated(Arc<Mutex<Option<(i64, i64)>>>);
+
+impl Impersonated {
+    pub fn new() -> Self {
+        Impersonated(Arc::new(Mutex::new(None)))
+    }
+
+    pub fn get(&self) -> Option<(i64, i64)> {
+        match self.0.lock() {
+            Ok(session) => *session,
+            Err(_) => None
+        }
+    }
+
+    fn set(&self, uid: i64, impersonator: i64) {
+        match self.0.lock() {
+            Ok(mut session) => { *session = Some((uid, impersonator)); },
+            Err(_) => ()
+        };
+    }
+}
+
+impl PartialEq for Impersonated {
+    fn eq(&self, other: &Impersonated) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+///
+/// The behaviour an endpoint required when the auth rules denied a request and
+/// the user that made it, if any, shared through the request local cache so that
+/// the denial can be audited once the request has completed
+///
+#[derive(Debug, Clone)]
+pub struct Denied(Arc<Mutex<Option<(Option<String>, Option<i64>)>>>);
+
+impl Denied {
+    pub fn new() -> Self {
+        Denied(Arc::new(Mutex::new(None)))
+    }
+
+    pub fn get(&self) -> Option<(Option<String>, Option<i64>)> {
+        match self.0.lock() {
+            Ok(denied) => denied.clone(),
+            Err(_) => None
+        }
+    }
+
+    fn set(&self, required: &Option<String>, uid: Option<i64>) {
+        match self.0.lock() {
+            Ok(mut denied) => { *denied = Some((required.clone(), uid)); },
+            Err(_) => ()
+        };
+    }
+}
+
+impl PartialEq for Denied {
+    fn eq(&self, other: &Denied) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+///
+/// The token a request was authenticated with, shared through the request
+/// local cache so that the use of the token can be recorded and a session
+/// token refreshed once the request has completed
+///
+#[derive(Debug, Clone)]
+pub struct SessionToken(Arc<Mutex<Option<String>>>);
+
+impl SessionToken {
+    pub fn new() -> Self {
+        SessionToken(Arc::new(Mutex::new(None)))
+    }
+
+    pub fn get(&self) -> Option<String> {
+        match self.0.lock() {
+            Ok(token) => token.clone(),
+            Err(_) => None
+        }
+    }
+
+    fn set(&self, token: String) {
+        match self.0.lock() {
+            Ok(mut session) => { *session = Some(token); },
+            Err(_) => ()
+        };
+    }
+}
+
+impl PartialEq for SessionToken {
+    fn eq(&self, other: &SessionToken) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+///
+/// Extend the expiry of a session token that is within the sliding window of
+/// expiring, up to the maximum lifetime of the token
+///
+/// Returns true if the expiry was extended. Impersonation tokens are never extended
+///
+pub fn refresh(conn: &impl postgres::GenericConnection, token: &String, session: &options::Session) -> Result<bool, HecateError> {
+    let window = session.window() as i32;
+    let lifetime = session.lifetime() as i32;
+    let max_lifetime = session.max_lifetime() as i32;
+
+    match conn.query("
+        UPDATE users_tokens
+            SET
+                expiry = LEAST(
+                    now() + ($2::INTEGER * INTERVAL '1 second'),
+                    created + ($3::INTEGER * INTERVAL '1 second')
+                )
+            WHERE
+                token = $1
+                AND kind = 'session'
+                AND impersonator IS NULL
+                AND now() < expiry
+                AND expiry < now() + ($4::INTEGER * INTERVAL '1 second')
+                AND expiry < LEAST(
+                    now() + ($2::INTEGER * INTERVAL '1 second'),
+                    created + ($3::INTEGER * INTERVAL '1 second')
+                )
+            RETURNING token
+    ", &[ &token, &lifetime, &max_lifetime, &window ]) {
+        Ok(res) => Ok(res.len() == 1),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Seconds between recording the use of a token, so that a burst of requests
+/// does not write to the token for every request
+///
+pub static TOUCH_INTERVAL: i32 = 60;
+
+///
+/// Seconds between each pass of the token reaper
+///
+pub static REAP_INTERVAL: u64 = 15;
+
+///
+/// Record that a token was used
+///
+/// Returns true if `last_used` was updated, which happens at most once every
+/// `TOUCH_INTERVAL` seconds for a given token
+///
+pub fn touch(conn: &impl postgres::GenericConnection, token: &String) -> Result<bool, HecateError> {
+    match conn.execute("
+        UPDATE users_tokens
+            SET
+                last_used = now()
+            WHERE
+                token = $1
+                AND (
+                    last_used IS NULL
+                    OR last_used < now() - ($2::INTEGER * INTERVAL '1 second')
+                )
+    ", &[ &token, &TOUCH_INTERVAL ]) {
+        Ok(updated) => Ok(updated > 0),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Delete every token that has not been used, or was never used since it was
+/// created, within the last `idle` seconds. Returns the number of tokens deleted
+///
+pub fn reap(conn: &impl postgres::GenericConnection, idle: &u32) -> Result<u64, HecateError> {
+    let idle = *idle as i32;
+
+    match conn.execute("
+        DELETE FROM users_tokens
+            WHERE
+                COALESCE(last_used, created) < now() - ($1::INTEGER * INTERVAL '1 second')
+    ", &[ &idle ]) {
+        Ok(deleted) => Ok(deleted),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Background thread deleting idle tokens every `REAP_INTERVAL` seconds
+///
+pub struct Reaper;
+
+impl Reaper {
+    pub fn start(pool: r2d2::Pool<r2d2_postgres::PostgresConnectionManager>, idle: u32) {
+        thread::Builder::new().name(String::from("Hecate Token Reaper")).spawn(move || {
+            loop {
+                thread::sleep(Duration::from_secs(REAP_INTERVAL));
+
+                let conn = match pool.get() {
+                    Ok(conn) => conn,
+                    Err(err) => {
+                        println!("WARN: Failed to reap idle tokens: {}", err);
+                        continue;
+                    }
+                };
+
+                match reap(&*conn, &idle) {
+                    Ok(0) => (),
+                    Ok(deleted) => println!("Reaped {} idle tokens", deleted),
+                    Err(err) => println!("WARN: Failed to reap idle tokens: {}", err.to_string())
+                };
+            }
+        }).unwrap();
+    }
+}
+
+///
+/// Record the use of the token used by each request, then slide the expiry of
+/// a session token when enabled, re-setting the session cookie whenever the
+/// expiry is extended
+///
+pub struct TokenRefresh;
+
+impl Fairing for TokenRefresh {
+    fn info(&self) -> Info {
+        Info {
+            name: "Session Token Refresh",
+            kind: Kind::Response
+        }
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        let token = match request.local_cache(|| SessionToken::new()).get() {
+            Some(token) => token,
+            None => { return; }
+        };
+
+        let session = match request.guard::<State<options::Options>>() {
+            Outcome::Success(options) => options.session(),
+            _ => { return; }
+        };
+
+        let conn = match request.guard::<State<DbReadWrite>>().succeeded() {
+            Some(conn) => match conn.get() {
+                Ok(conn) => conn,
+                Err(_) => {
+                    println!("WARN: Failed to refresh session token");
+                    return;
+                }
+            },
+            None => { return; }
+        };
+
+        if let Err(err) = touch(&*conn, &token) {
+            println!("WARN: Failed to record token use: {}", err.to_string());
+        }
+
+        if !session.is_sliding() {
+            return;
+        }
+
+        match refresh(&*conn, &token, &session) {
+            Ok(true) => {
+                response.adjoin_header(Cookie::build("session", token)
+                    .path("/")
+                    .http_only(true)
+                    .secure(proxy::Client::from(request).tls)
+                    .finish()
+                );
+            },
+            Ok(false) => (),
+            Err(err) => println!("WARN: Failed to refresh session token: {}", err.to_string())
+        };
+    }
+}
+
+///
+/// Session tokens are set as a cookie for the browser while API tokens are
+/// minted for programmatic use, each kind with its own expiry
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TokenKind {
+    Session,
+    Api
+}
+
+impl TokenKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenKind::Session => "session",
+            TokenKind::Api => "api"
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenKind::Session => "Session Token",
+            TokenKind::Api => "API Token"
         }
     }
 }
@@ -857,7 +1408,20 @@ pub struct Auth {
     pub uid: Option<i64>,
     pub access: Option<String>,
     pub token: Option<String>,
-    pub basic: Option<(String, String)>
+    pub basic: Option<(String, String)>,
+    pub impersonator: Option<i64>,
+    pub tenant: Option<i64>,
+    pub cross: Option<String>,
+    pub source: Option<String>,
+    pub exempt: bool,
+    pub case_fold: bool,
+    pub ip: Option<IpAddr>,
+    session: options::Session,
+    admin: options::Admin,
+    impersonated: Impersonated,
+    denied: Denied,
+    session_token: SessionToken,
+    timings: Timings
 }
 
 impl Auth {
@@ -866,10 +1430,54 @@ impl Auth {
             uid: None,
             access: None,
             token: None,
-            basic: None
+            basic: None,
+            impersonator: None,
+            tenant: None,
+            cross: None,
+            source: None,
+            exempt: false,
+            case_fold: false,
+            ip: None,
+            session: options::Session::new(),
+            admin: options::Admin::new(),
+            impersonated: Impersonated::new(),
+            denied: Denied::new(),
+            session_token: SessionToken::new(),
+            timings: Timings::new()
+        }
+    }
+
+    ///
+    /// Whether a validated user is an admin acting as themselves, ie: not
+    /// through an impersonation token, from a network admins are permitted from
+    ///
+    pub fn has_admin_access(&self) -> bool {
+        self.impersonator.is_none() && self.admin.permits(self.ip) && match self.access_level() {
+            Ok(Some(level)) => level >= AccessLevel::Admin,
+            _ => false
+        }
+    }
+
+    ///
+    /// The access level of a validated user, or None if the request is not
+    /// authenticated
+    ///
+    pub fn access_level(&self) -> Result<Option<AccessLevel>, HecateError> {
+        match self.uid {
+            None => Ok(None),
+            Some(_) => Ok(Some(AccessLevel::from_db(&self.access)?))
         }
     }
 
+    ///
+    /// The tenant scope of a validated request
+    ///
+    /// Admins acting as themselves can cross tenants by sending `X-Hecate-Tenant: *`
+    ///
+    pub fn scope(&self) -> Result<tenant::Scope, HecateError> {
+        tenant::Scope::new(self.tenant, &self.cross, self.has_admin_access())
+    }
+
     ///
     /// Remove user data from the Auth object
     ///
@@ -886,6 +1494,8 @@ impl Auth {
         }
         self.token = None;
         self.basic = None;
+        self.impersonator = None;
+        self.source = None;
     }
 
     ///
@@ -898,16 +1508,38 @@ impl Auth {
     /// Note: Once validated the token/basic auth used to validate the user will be set to null
     ///
     pub fn validate(&mut self, conn: &impl postgres::GenericConnection) -> Result<Option<i64>, HecateError> {
+        let start = Instant::now();
+        let validated = self.check(conn);
+
+        self.timings.record("auth", start.elapsed());
+        self.timings.set_admin(self.has_admin_access());
+
+        validated
+    }
+
+    fn check(&mut self, conn: &impl postgres::GenericConnection) -> Result<Option<i64>, HecateError> {
         if self.basic.is_some() {
-            match conn.query("
+            //A username matching exactly is preferred over one matching only once folded,
+            //as users created before case folding was enabled may differ only by case
+            let username = if self.case_fold {
+                "(username_folded = LOWER($1) OR username = $1)"
+            } else {
+                "username = $1"
+            };
+
+            match conn.query(&*format!("
                 SELECT
                     id,
-                    access
+                    access,
+                    tenant,
+                    password_reset
                 FROM users
                 WHERE
-                    username = $1
+                    {username}
                     AND password = crypt($2, password)
-            ", &[ &self.basic.as_ref().unwrap().0 , &self.basic.as_ref().unwrap().1 ]) {
+                ORDER BY username = $1 DESC
+                LIMIT 1
+            ", username = username), &[ &self.basic.as_ref().unwrap().0 , &self.basic.as_ref().unwrap().1 ]) {
                 Ok(res) => {
                     if res.len() != 1 {
                         return Err(not_authed());
@@ -915,8 +1547,15 @@ impl Auth {
 
                     let uid: i64 = res.get(0).get(0);
                     let access: Option<String> = res.get(0).get(1);
+                    let reset: bool = res.get(0).get(3);
+
+                    //A reset password only allows the user to choose a new password
+                    if reset {
+                        return Err(HecateError::new(403, String::from("Password Change Required - set a new password with POST /api/user/password"), None));
+                    }
 
                     self.secure(Some((uid, access)));
+                    self.tenant = res.get(0).get(2);
 
                     return Ok(Some(uid));
                 },
@@ -928,15 +1567,20 @@ impl Auth {
             match conn.query("
                 SELECT
                     users_tokens.uid,
-                    users.access
+                    users.access,
+                    users_tokens.impersonator,
+                    users.tenant,
+                    users_tokens.source
                 FROM
                     users_tokens,
                     users
                 WHERE
                     token = $1
-                    AND now() < expiry
+                    AND now() < expiry + (
+                        CASE WHEN users_tokens.kind = 'api' THEN $3::INTEGER ELSE $2::INTEGER END * INTERVAL '1 second'
+                    )
                     AND users_tokens.uid = users.id
-            ", &[ &self.token.as_ref().unwrap() ]) {
+            ", &[ &self.token.as_ref().unwrap(), &(self.session.grace(&TokenKind::Session) as i32), &(self.session.grace(&TokenKind::Api) as i32) ]) {
                 Ok(res) => {
                     if res.len() == 0 {
                         return Err(not_authed());
@@ -944,8 +1588,23 @@ impl Auth {
 
                     let uid: i64 = res.get(0).get(0);
                     let access: Option<String> = res.get(0).get(1);
+                    let impersonator: Option<i64> = res.get(0).get(2);
+
+                    let token = self.token.clone().unwrap();
 
                     self.secure(Some((uid, access)));
+                    self.tenant = res.get(0).get(3);
+                    self.source = res.get(0).get(4);
+
+                    self.session_token.set(token);
+
+                    match impersonator {
+                        None => (),
+                        Some(impersonator) => {
+                            self.impersonator = Some(impersonator);
+                            self.impersonated.set(uid, impersonator);
+                        }
+                    };
 
                     return Ok(Some(uid));
                 },
@@ -959,10 +1618,86 @@ impl Auth {
     }
 }
 
+///
+/// The auth rules in use by the server
+///
+/// Rules are replaced as a whole so that requests always see either the
+/// previous or the new rules and never a partially applied config
+///
+pub struct AuthContainer(RwLock<Arc<CustomAuth>>);
+
+impl AuthContainer {
+    pub fn new(auth: CustomAuth) -> Self {
+        AuthContainer(RwLock::new(Arc::new(auth)))
+    }
+
+    pub fn get(&self) -> Arc<CustomAuth> {
+        match self.0.read() {
+            Ok(auth) => auth.clone(),
+            Err(poisoned) => poisoned.into_inner().clone()
+        }
+    }
+
+    ///
+    /// Validate and swap in a new set of auth rules, leaving the current
+    /// rules in place if the new rules are invalid
+    ///
+    pub fn set(&self, auth: CustomAuth) -> Result<Arc<CustomAuth>, HecateError> {
+        match auth.is_valid() {
+            Ok(_) => (),
+            Err(err) => { return Err(HecateError::new(400, err, None)); }
+        };
+
+        let auth = Arc::new(auth);
+
+        match self.0.write() {
+            Ok(mut current) => { *current = auth.clone(); },
+            Err(poisoned) => { *poisoned.into_inner() = auth.clone(); }
+        };
+
+        Ok(auth)
+    }
+}
+
+///
+/// Request guard providing the auth rules in use when the request was received
+///
+pub struct Rules(Arc<CustomAuth>);
+
+impl Deref for Rules {
+    type Target = CustomAuth;
+
+    fn deref(&self) -> &CustomAuth {
+        &self.0
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Rules {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Rules, ()> {
+        match request.guard::<State<AuthContainer>>() {
+            Outcome::Success(container) => Outcome::Success(Rules(container.get())),
+            _ => Outcome::Failure((Status::InternalServerError, ()))
+        }
+    }
+}
+
 impl<'a, 'r> FromRequest<'a, 'r> for Auth {
     type Error = ();
     fn from_request(request: &'a Request<'r>) -> request::Outcome<Auth, ()> {
         let mut auth = Auth::new();
+        auth.impersonated = request.local_cache(|| Impersonated::new()).clone();
+        auth.denied = request.local_cache(|| Denied::new()).clone();
+        auth.session_token = request.local_cache(|| SessionToken::new()).clone();
+        auth.timings = request.local_cache(|| Timings::new()).clone();
+        auth.cross = request.headers().get_one("X-Hecate-Tenant").map(|value| String::from(value.trim()));
+        auth.exempt = request.local_cache(|| options::AuthExempt(false)).0;
+        auth.ip = proxy::Client::from(request).ip;
+        if let Outcome::Success(options) = request.guard::<State<options::Options>>() {
+            auth.case_fold = options.user().case_fold.unwrap_or(false);
+            auth.session = options.session();
+            auth.admin = options.admin();
+        }
 
         match request.cookies().get("session") {
             Some(token) => {
@@ -980,6 +1715,12 @@ impl<'a, 'r> FromRequest<'a, 'r> for Auth {
             return Outcome::Success(auth);
         }
 
+        if keys[0].starts_with("Bearer ") {
+            auth.token = Some(String::from(keys[0][7..].trim()));
+
+            return Outcome::Success(auth);
+        }
+
         let mut authtype = String::from(keys[0]);
         let auth_str = authtype.split_off(6);
 
@@ -1005,3 +1746,85 @@ impl<'a, 'r> FromRequest<'a, 'r> for Auth {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_level_from_db() {
+        assert_eq!(AccessLevel::from_db(&None).unwrap(), AccessLevel::User);
+        assert_eq!(AccessLevel::from_db(&Some(String::from("user"))).unwrap(), AccessLevel::User);
+        assert_eq!(AccessLevel::from_db(&Some(String::from("admin"))).unwrap(), AccessLevel::Admin);
+
+        for access in vec!["Admin", "superadmin", ""] {
+            assert_eq!(AccessLevel::from_db(&Some(String::from(access))).unwrap_err().as_json(), json!({
+                "code": 500,
+                "reason": "Auth Config Error: Unrecognized user access level",
+                "status": "Internal Server Error"
+            }));
+        }
+    }
+
+    #[test]
+    fn custom_auth_overlay() {
+        let auth = CustomAuth::overlay(&json!({
+            "feature": {
+                "create": "admin"
+            },
+            "schema": null
+        })).unwrap();
+
+        assert!(auth.is_valid().is_ok());
+        assert_eq!(auth.feature.as_ref().unwrap().create, Some(String::from("admin")));
+        assert_eq!(auth.feature.as_ref().unwrap().get, Some(String::from("public")));
+        assert_eq!(auth.feature.as_ref().unwrap().purge, Some(String::from("admin")));
+        assert_eq!(auth.schema, None);
+        assert_eq!(auth.style, CustomAuth::new().style);
+        assert_eq!(auth.server, Some(String::from("public")));
+
+        let mut expected = CustomAuth::new().to_json();
+        expected["feature"]["create"] = json!("admin");
+        expected["schema"] = json!(null);
+        assert_eq!(auth.to_json(), expected);
+
+        assert_eq!(CustomAuth::overlay(&json!({})).unwrap(), CustomAuth::new());
+    }
+
+    #[test]
+    fn custom_auth_overlay_invalid() {
+        assert_eq!(CustomAuth::overlay(&json!({ "featur": { "create": "admin" } })).unwrap_err(), "Auth Config Error: 'featur' is not a known auth category or behaviour");
+        assert_eq!(CustomAuth::overlay(&json!({ "feature": { "craete": "admin" } })).unwrap_err(), "Auth Config Error: 'feature::craete' is not a known auth category or behaviour");
+        assert_eq!(CustomAuth::overlay(&json!({ "feature": "admin" })).unwrap_err(), "Auth Config Error: 'feature' must be an object or null");
+        assert_eq!(CustomAuth::overlay(&json!([])).unwrap_err(), "Auth Config Error: An auth overlay must be a JSON object");
+    }
+
+    #[test]
+    fn access_level_order() {
+        assert!(AccessLevel::Admin > AccessLevel::User);
+        assert!(AccessLevel::Admin >= AccessLevel::Admin);
+        assert!(!(AccessLevel::User >= AccessLevel::Admin));
+    }
+
+    #[test]
+    fn admin_access() {
+        let mut auth = Auth::new();
+        assert!(!auth.has_admin_access());
+        assert_eq!(auth.access_level().unwrap(), None);
+
+        auth.uid = Some(1);
+        assert!(!auth.has_admin_access());
+        assert_eq!(auth.access_level().unwrap(), Some(AccessLevel::User));
+
+        auth.access = Some(String::from("admin"));
+        assert!(auth.has_admin_access());
+
+        auth.impersonator = Some(2);
+        assert!(!auth.has_admin_access());
+
+        auth.impersonator = None;
+        auth.access = Some(String::from("Admin"));
+        assert!(!auth.has_admin_access());
+        assert!(auth.access_level().is_err());
+    }
+}
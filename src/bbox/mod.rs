@@ -0,0 +1,164 @@
+use crate::err::HecateError;
+use crate::validate;
+
+///
+/// A validated WGS84 bounding box
+///
+/// A minX greater than maxX is treated as a box that crosses the
+/// antimeridian, ie: 170,-10,-170,10 covers 170 -> 180 and -180 -> -170
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bbox {
+    pub minx: f64,
+    pub miny: f64,
+    pub maxx: f64,
+    pub maxy: f64
+}
+
+impl Bbox {
+    pub fn new(bbox: &Vec<f64>) -> Result<Self, HecateError> {
+        validate::bbox(bbox)?;
+
+        Ok(Bbox {
+            minx: bbox[0],
+            miny: bbox[1],
+            maxx: bbox[2],
+            maxy: bbox[3]
+        })
+    }
+
+    ///
+    /// Parse a bbox from a minX,minY,maxX,maxY string as used by query params
+    ///
+    pub fn parse(bbox: &str) -> Result<Self, HecateError> {
+        match bbox.split(',').map(|s| s.trim().parse::<f64>()).collect::<Result<Vec<f64>, _>>() {
+            Ok(bbox) => Bbox::new(&bbox),
+            Err(_) => Err(HecateError::new(400, String::from("Invalid BBOX"), None))
+        }
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.minx > self.maxx
+    }
+
+    ///
+    /// Split the bbox at the antimeridian, returning one box for a normal
+    /// bbox or two boxes for one that crosses the antimeridian
+    ///
+    pub fn split(&self) -> Vec<Bbox> {
+        if self.crosses_antimeridian() {
+            vec![
+                Bbox { minx: self.minx, miny: self.miny, maxx: 180.0, maxy: self.maxy },
+                Bbox { minx: -180.0, miny: self.miny, maxx: self.maxx, maxy: self.maxy }
+            ]
+        } else {
+            vec![self.clone()]
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<f64> {
+        vec![self.minx, self.miny, self.maxx, self.maxy]
+    }
+
+    ///
+    /// Return an SQL clause matching any geometry in the given column that
+    /// intersects the bbox, pushing the coordinates onto the params list
+    ///
+    pub fn to_sql(&self, column: &str, params: &mut Vec<f64>) -> String {
+        self.to_buffered_sql(column, &None, params)
+    }
+
+    ///
+    /// As `to_sql`, expanding the bbox by `buffer` metres first when given
+    ///
+    /// The buffer is applied to the bbox as a geography so that the distance
+    /// is measured in metres regardless of latitude
+    ///
+    pub fn to_buffered_sql(&self, column: &str, buffer: &Option<f64>, params: &mut Vec<f64>) -> String {
+        let mut clauses: Vec<String> = Vec::new();
+
+        for bbox in self.split() {
+            let mut coords: Vec<String> = Vec::with_capacity(4);
+            for coord in bbox.to_vec() {
+                params.push(coord);
+                coords.push(format!("${}", params.len()));
+            }
+
+            let envelope = format!("ST_MakeEnvelope({}, 4326)", coords.join(", "));
+
+            match buffer {
+                None => clauses.push(format!("ST_Intersects({}, {})", column, envelope)),
+                Some(buffer) => {
+                    params.push(*buffer);
+                    clauses.push(format!("ST_Intersects({}, ST_Buffer({}::GEOGRAPHY, ${})::GEOMETRY)", column, envelope, params.len()));
+                }
+            };
+        }
+
+        format!("({})", clauses.join(" OR "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bbox() {
+        assert_eq!(Bbox::parse("-1,-2,3,4").unwrap(), Bbox { minx: -1.0, miny: -2.0, maxx: 3.0, maxy: 4.0 });
+        assert_eq!(Bbox::parse("-1, -2, 3, 4").unwrap(), Bbox { minx: -1.0, miny: -2.0, maxx: 3.0, maxy: 4.0 });
+    }
+
+    #[test]
+    fn parse_invalid_bbox() {
+        assert_eq!(Bbox::parse("-1,-2,3").unwrap_err().as_json(), json!({
+            "code": 400,
+            "reason": "Invalid BBOX",
+            "status": "Bad Request"
+        }));
+
+        assert_eq!(Bbox::parse("-1,-2,3,a").unwrap_err().as_json(), json!({
+            "code": 400,
+            "reason": "Invalid BBOX",
+            "status": "Bad Request"
+        }));
+
+        assert_eq!(Bbox::parse("-1,-91,3,4").unwrap_err().as_json(), json!({
+            "code": 400,
+            "reason": "BBOX minY value must be a number between -90 and 90",
+            "status": "Bad Request"
+        }));
+    }
+
+    #[test]
+    fn split_bbox() {
+        let bbox = Bbox::parse("-1,-2,3,4").unwrap();
+        assert!(!bbox.crosses_antimeridian());
+        assert_eq!(bbox.split(), vec![bbox.clone()]);
+
+        let bbox = Bbox::parse("170,-10,-170,10").unwrap();
+        assert!(bbox.crosses_antimeridian());
+        assert_eq!(bbox.split(), vec![
+            Bbox { minx: 170.0, miny: -10.0, maxx: 180.0, maxy: 10.0 },
+            Bbox { minx: -180.0, miny: -10.0, maxx: -170.0, maxy: 10.0 }
+        ]);
+    }
+
+    #[test]
+    fn bbox_sql() {
+        let mut params: Vec<f64> = Vec::new();
+        assert_eq!(Bbox::parse("-1,-2,3,4").unwrap().to_sql("geom", &mut params), "(ST_Intersects(geom, ST_MakeEnvelope($1, $2, $3, $4, 4326)))");
+        assert_eq!(params, vec![-1.0, -2.0, 3.0, 4.0]);
+
+        let mut params: Vec<f64> = Vec::new();
+        assert_eq!(Bbox::parse("170,-10,-170,10").unwrap().to_sql("geom", &mut params), "(ST_Intersects(geom, ST_MakeEnvelope($1, $2, $3, $4, 4326)) OR ST_Intersects(geom, ST_MakeEnvelope($5, $6, $7, $8, 4326)))");
+        assert_eq!(params, vec![170.0, -10.0, 180.0, 10.0, -180.0, -10.0, -170.0, 10.0]);
+    }
+
+    #[test]
+    fn bbox_buffered_sql() {
+        let mut params: Vec<f64> = Vec::new();
+        assert_eq!(Bbox::parse("-1,-2,3,4").unwrap().to_buffered_sql("geom", &Some(100.0), &mut params), "(ST_Intersects(geom, ST_Buffer(ST_MakeEnvelope($1, $2, $3, $4, 4326)::GEOGRAPHY, $5)::GEOMETRY))");
+        assert_eq!(params, vec![-1.0, -2.0, 3.0, 4.0, 100.0]);
+    }
+}
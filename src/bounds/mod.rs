@@ -1,5 +1,36 @@
 use crate::err::HecateError;
+use crate::gpkg;
 use crate::stream::PGStream;
+use crate::tenant::Scope;
+
+///
+/// Order in which bounds are returned from a metadata listing
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Sort {
+    Name,
+    Area
+}
+
+impl Sort {
+    pub fn parse(sort: &Option<String>) -> Result<Self, HecateError> {
+        match sort.as_ref().map(|sort| sort.as_str()) {
+            None | Some("name") => Ok(Sort::Name),
+            Some("area") => Ok(Sort::Area),
+            Some(_) => Err(HecateError::new(400, String::from("Invalid Sort - must be one of 'name' or 'area'"), None))
+        }
+    }
+
+    ///
+    /// The ORDER BY fragment for this sort, largest area first
+    ///
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            Sort::Name => "name",
+            Sort::Area => "area DESC, name"
+        }
+    }
+}
 
 pub fn set(conn: &impl postgres::GenericConnection, name: &String, feat: &serde_json::Value) -> Result<bool, HecateError> {
     match conn.execute("
@@ -14,6 +45,33 @@ pub fn set(conn: &impl postgres::GenericConnection, name: &String, feat: &serde_
     }
 }
 
+///
+/// Refresh the cached count of features intersecting a single bound
+/// or all bounds if no name is given
+///
+pub fn count(conn: &impl postgres::GenericConnection, name: &Option<String>) -> Result<bool, HecateError> {
+    match conn.execute("
+        UPDATE bounds
+            SET
+                features = (
+                    SELECT
+                        count(DISTINCT geo.id)
+                    FROM
+                        geo,
+                        ST_Subdivide(bounds.geom) AS subgeom
+                    WHERE
+                        ST_Intersects(geo.geom, subgeom)
+                ),
+                counted = NOW()
+            WHERE
+                $1::TEXT IS NULL
+                OR bounds.name = $1
+    ", &[ &name ]) {
+        Ok(_) => Ok(true),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
 pub fn delete(conn: &impl postgres::GenericConnection, name: &String) -> Result<bool, HecateError> {
     match conn.execute("
         DELETE FROM bounds WHERE name = $1
@@ -23,18 +81,13 @@ pub fn delete(conn: &impl postgres::GenericConnection, name: &String) -> Result<
     }
 }
 
-pub fn filter(conn: &impl postgres::GenericConnection, prefix: &String, limit: &Option<i16>) -> Result<Vec<String>, HecateError> {
-    let limit: i16 = match limit {
-        None => 100,
-        Some(limit) => if *limit > 100 { 100 } else { *limit }
-    };
-
+pub fn filter(conn: &impl postgres::GenericConnection, prefix: &String, limit: &Option<i64>) -> Result<Vec<String>, HecateError> {
     match conn.query("
         SELECT name
             FROM bounds
             WHERE name iLIKE $1||'%'
             ORDER BY name
-            LIMIT $2::SmallInt
+            LIMIT $2::BIGINT
     ", &[ &prefix, &limit ]) {
         Ok(rows) => {
             let mut names = Vec::<String>::new();
@@ -49,12 +102,12 @@ pub fn filter(conn: &impl postgres::GenericConnection, prefix: &String, limit: &
     }
 }
 
-pub fn list(conn: &impl postgres::GenericConnection, limit: &Option<i16>) -> Result<Vec<String>, HecateError> {
+pub fn list(conn: &impl postgres::GenericConnection, limit: &Option<i64>) -> Result<Vec<String>, HecateError> {
     match conn.query("
         SELECT name
         FROM bounds
         ORDER BY name
-        LIMIT $1::SmallInt
+        LIMIT $1::BIGINT
     ", &[ &limit ]) {
         Ok(rows) => {
             let mut names = Vec::<String>::new();
@@ -69,6 +122,86 @@ pub fn list(conn: &impl postgres::GenericConnection, limit: &Option<i16>) -> Res
     }
 }
 
+///
+/// List bounds along with their area in square metres and
+/// the cached number of features they contain
+///
+pub fn list_meta(conn: &impl postgres::GenericConnection, prefix: &Option<String>, sort: &Sort, limit: &Option<i64>) -> Result<Vec<serde_json::Value>, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            JSON_Build_Object(
+                'name', name,
+                'area', area,
+                'features', features,
+                'counted', counted
+            )
+        FROM (
+            SELECT
+                name,
+                ST_Area(geom::GEOGRAPHY) AS area,
+                COALESCE(features, 0) AS features,
+                to_char(counted, 'YYYY-MM-DD HH24:MI:SS') AS counted
+            FROM
+                bounds
+            WHERE
+                $1::TEXT IS NULL
+                OR name iLIKE $1||'%'
+        ) b
+        ORDER BY {}
+        LIMIT $2::BIGINT
+    ", sort.to_sql()), &[ &prefix, &limit ]) {
+        Ok(rows) => {
+            let mut list = Vec::<serde_json::Value>::new();
+
+            for row in rows.iter() {
+                list.push(row.get(0));
+            }
+
+            Ok(list)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Return the names of all bounds that intersect the given feature
+///
+pub fn feature(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<Vec<String>, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            bounds.name
+        FROM
+            geo
+                LEFT JOIN bounds
+                    ON ST_Intersects(geo.geom, bounds.geom)
+        WHERE
+            geo.id = $1
+            AND {scope}
+        ORDER BY
+            bounds.name
+    ", scope = tenant.to_sql("geo.tenant")), &[ &id ]) {
+        Ok(rows) => {
+            if rows.len() == 0 {
+                return Err(HecateError::new(404, String::from("Feature not found"), None));
+            }
+
+            let mut names = Vec::<String>::new();
+
+            for row in rows.iter() {
+                let name: Option<String> = row.get(0);
+
+                match name {
+                    Some(name) => names.push(name),
+                    None => ()
+                };
+            }
+
+            Ok(names)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
 pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, bounds: String) -> Result<PGStream, HecateError> {
     match PGStream::new(conn, String::from("next_bounds"), String::from(r#"
         DECLARE next_bounds CURSOR FOR
@@ -101,6 +234,193 @@ pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager
     }
 }
 
+///
+/// Features intersecting a bound, in the order of their ids. A feature that
+/// intersects several parts of the subdivided bound is only returned once
+///
+fn within(tenant: &Scope) -> String {
+    format!("
+        FROM
+            geo
+        WHERE
+            {scope}
+            AND geo.id IN (
+                SELECT
+                    geo.id
+                FROM
+                    geo,
+                    (
+                        SELECT
+                            ST_Subdivide(bounds.geom) AS subgeom
+                        FROM
+                            bounds
+                        WHERE
+                            name = $1
+                    ) AS b
+                WHERE
+                    ST_Intersects(geo.geom, b.subgeom)
+            )
+        ORDER BY
+            geo.id
+    ", scope = tenant.to_sql("geo.tenant"))
+}
+
+///
+/// Stream the features of a bound as Line-Delimited GeoJSON, reprojected to the given SRID
+///
+pub fn download(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, bounds: String, srid: i32, tenant: &Scope) -> Result<PGStream, HecateError> {
+    PGStream::new(conn, String::from("next_download"), format!(r#"
+        DECLARE next_download CURSOR FOR
+            SELECT
+                row_to_json(t)::TEXT
+            FROM (
+                SELECT
+                    geo.id AS id,
+                    geo.key AS key,
+                    'Feature' AS type,
+                    geo.version AS version,
+                    ST_AsGeoJSON(ST_Transform(geo.geom, $2))::JSON AS geometry,
+                    geo.props AS properties
+                {within}
+            ) t
+    "#, within = within(tenant)), &[&bounds, &srid])
+}
+
+///
+/// Write the features of a bound to a GeoPackage, reprojected to the given SRID
+///
+pub fn download_gpkg(conn: &impl postgres::GenericConnection, bounds: &String, srid: i32, tenant: &Scope) -> Result<std::fs::File, HecateError> {
+    let srs: Vec<gpkg::Srs> = match conn.query("
+        SELECT
+            srid,
+            auth_name,
+            auth_srid,
+            srtext
+        FROM
+            spatial_ref_sys
+        WHERE
+            srid = $1
+            OR srid = 4326
+        ORDER BY
+            srid = $1 DESC
+    ", &[&srid]) {
+        Ok(rows) => rows.iter().map(|row| gpkg::Srs {
+            srid: row.get(0),
+            organization: row.get(1),
+            code: row.get(2),
+            definition: row.get(3)
+        }).collect(),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    //The time of the latest delta, rather than the time of the download, so that
+    //the GeoPackage of unchanged data is the same file on every download
+    let last_change: String = match conn.query("
+        SELECT
+            COALESCE(to_char(MAX(created), 'YYYY-MM-DD\"T\"HH24:MI:SS.MS\"Z\"'), '1970-01-01T00:00:00.000Z')
+        FROM
+            deltas
+    ", &[]) {
+        Ok(rows) => rows.get(0).get(0),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    let gpkg = gpkg::GeoPackage::new(&srs, bounds, &last_change)?;
+
+    match fill(conn, &gpkg, bounds, srid, tenant) {
+        Ok(_) => gpkg.finish(),
+        Err(err) => {
+            gpkg.discard();
+            Err(err)
+        }
+    }
+}
+
+fn fill(conn: &impl postgres::GenericConnection, gpkg: &gpkg::GeoPackage, bounds: &String, srid: i32, tenant: &Scope) -> Result<(), HecateError> {
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.execute(&*format!("
+        DECLARE next_gpkg CURSOR FOR
+            SELECT
+                geo.id,
+                geo.key,
+                geo.version,
+                COALESCE(geo.props, '{}'::JSONB)::TEXT,
+                ST_AsBinary(ST_Transform(geo.geom, $2), 'NDR')
+            {within}
+    ", within = within(tenant)), &[&bounds, &srid]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    loop {
+        let rows = match trans.query("FETCH 1000 FROM next_gpkg", &[]) {
+            Ok(rows) => rows,
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+
+        if rows.len() == 0 {
+            break;
+        }
+
+        for row in rows.iter() {
+            gpkg.insert(row.get(0), row.get(1), row.get(2), row.get(3), row.get(4))?;
+        }
+    }
+
+    match trans.finish() {
+        Ok(_) => Ok(()),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// A validator of the download of a bound, that changes whenever the bytes of the
+/// download could, ie: a feature is edited or the bound is redrawn
+///
+/// The id & version of every feature in the bound are hashed, as an edit appended
+/// to an open batch changes features without creating a new delta
+///
+pub fn etag(conn: &impl postgres::GenericConnection, bounds: &String, format: &String, srid: i32, tenant: &Scope) -> Result<String, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            md5(concat_ws(':',
+                $1::TEXT,
+                $2::TEXT,
+                $3::TEXT,
+                $4::TEXT,
+                md5(ST_AsBinary(bounds.geom)),
+                (
+                    SELECT
+                        md5(COALESCE(string_agg(f.id || '.' || f.version, ',' ORDER BY f.id), ''))
+                    FROM (
+                        SELECT
+                            geo.id,
+                            geo.version
+                        {within}
+                    ) f
+                )
+            ))
+        FROM
+            bounds
+        WHERE
+            name = $1
+    ", within = within(tenant)), &[&bounds, &format, &srid.to_string(), &tenant.to_sql("geo.tenant")]) {
+        Ok(rows) => {
+            if rows.len() == 0 {
+                return Err(HecateError::new(404, String::from("bound not found"), None));
+            }
+
+            let etag: String = rows.get(0).get(0);
+            Ok(format!("\"{}\"", etag))
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
 pub fn meta(conn: &impl postgres::GenericConnection, name: String) -> Result<serde_json::Value, HecateError> {
     match conn.query("
         SELECT
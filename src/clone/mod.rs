@@ -1,8 +1,9 @@
 use crate::stream::PGStream;
 use crate::err::HecateError;
+use crate::tenant::Scope;
 
-pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>) -> Result<PGStream, HecateError> {
-    match PGStream::new(conn, String::from("next_clone"), String::from(r#"
+pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, tenant: &Scope) -> Result<PGStream, HecateError> {
+    match PGStream::new(conn, String::from("next_clone"), format!(r#"
         DECLARE next_clone CURSOR FOR
             SELECT
                 row_to_json(t)::TEXT
@@ -16,8 +17,10 @@ pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager
                     geo.props AS properties
                 FROM
                     geo
+                WHERE
+                    {scope}
             ) t
-    "#), &[]) {
+    "#, scope = tenant.to_sql("geo.tenant")), &[]) {
         Ok(stream) => Ok(stream),
         Err(err) =>  Err(err)
     }
@@ -0,0 +1,145 @@
+use rocket::request::{self, FromRequest};
+use rocket::{Request, Outcome};
+use crate::err::HecateError;
+use crate::feature;
+use crate::tenant::Scope;
+
+///
+/// The `If-Match` & `If-None-Match` headers of a feature write
+///
+/// `If-None-Match: *` only allows the write if the feature does not exist,
+/// `If-Match: <version>` only allows it if the feature is at the given version
+/// and `If-Match: *` only allows it if the feature exists
+///
+pub struct Precondition {
+    pub if_match: Option<String>,
+    pub if_none_match: Option<String>
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Precondition {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Precondition, ()> {
+        Outcome::Success(Precondition {
+            if_match: request.headers().get_one("If-Match").map(|value| String::from(value.trim())),
+            if_none_match: request.headers().get_one("If-None-Match").map(|value| String::from(value.trim()))
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Condition {
+    Absent,
+    Exists,
+    Version(i64)
+}
+
+impl Precondition {
+    ///
+    /// Parse the headers into the condition the feature must meet, if any
+    ///
+    pub fn condition(&self) -> Result<Option<Condition>, HecateError> {
+        match (&self.if_match, &self.if_none_match) {
+            (None, None) => Ok(None),
+            (Some(_), Some(_)) => Err(HecateError::new(400, String::from("If-Match and If-None-Match cannot be used together"), None)),
+            (None, Some(value)) => {
+                if value == "*" {
+                    Ok(Some(Condition::Absent))
+                } else {
+                    Err(HecateError::new(400, String::from("Invalid If-None-Match - only '*' is supported"), None))
+                }
+            },
+            (Some(value), None) => {
+                if value == "*" {
+                    return Ok(Some(Condition::Exists));
+                }
+
+                //Versions may be sent bare or as a quoted entity tag
+                match value.trim_start_matches("W/").trim_matches('"').parse::<i64>() {
+                    Ok(version) if version > 0 => Ok(Some(Condition::Version(version))),
+                    _ => Err(HecateError::new(400, String::from("Invalid If-Match - must be '*' or a feature version"), None))
+                }
+            }
+        }
+    }
+
+    ///
+    /// Ensure the feature meets the condition of the request, locking the current
+    /// version of the feature until the transaction ends
+    ///
+    /// The feature is looked up by its id, or by its key if it has no id. A feature
+    /// of another tenant is treated as not existing
+    ///
+    pub fn check(&self, trans: &postgres::transaction::Transaction, feat: &geojson::Feature, tenant: &Scope) -> Result<(), HecateError> {
+        let condition = match self.condition()? {
+            None => { return Ok(()); },
+            Some(condition) => condition
+        };
+
+        //A feature without an id or key cannot exist yet
+        let rows = match (feature::get_id(&feat), feature::get_key(&feat)?) {
+            (Ok(id), _) => trans.query(&*format!("SELECT version FROM geo WHERE id = $1 AND {} FOR UPDATE", tenant.to_sql("tenant")), &[&id]),
+            (Err(_), Some(key)) => trans.query(&*format!("SELECT version FROM geo WHERE key = $1 AND {} FOR UPDATE", tenant.to_sql("tenant")), &[&key]),
+            (Err(_), None) => { return condition.met(None); }
+        };
+
+        match rows {
+            Ok(rows) => condition.met(rows.iter().next().map(|row| row.get(0))),
+            Err(err) => Err(HecateError::from_db(err))
+        }
+    }
+}
+
+impl Condition {
+    ///
+    /// Whether a feature at the given version, `None` if it does not exist, meets the condition
+    ///
+    pub fn met(&self, current: Option<i64>) -> Result<(), HecateError> {
+        match (self, current) {
+            (Condition::Absent, Some(_)) => Err(HecateError::new(412, String::from("Precondition Failed - feature already exists"), None)),
+            (Condition::Absent, None) => Ok(()),
+            (_, None) => Err(HecateError::new(412, String::from("Precondition Failed - feature does not exist"), None)),
+            (Condition::Version(version), Some(current)) if *version != current => {
+                Err(HecateError::new(412, format!("Precondition Failed - feature is at version {}", current), None))
+            },
+            (_, Some(_)) => Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn precondition(if_match: Option<&str>, if_none_match: Option<&str>) -> Precondition {
+        Precondition {
+            if_match: if_match.map(String::from),
+            if_none_match: if_none_match.map(String::from)
+        }
+    }
+
+    #[test]
+    fn condition() {
+        assert_eq!(precondition(None, None).condition().unwrap(), None);
+        assert_eq!(precondition(None, Some("*")).condition().unwrap(), Some(Condition::Absent));
+        assert_eq!(precondition(Some("*"), None).condition().unwrap(), Some(Condition::Exists));
+        assert_eq!(precondition(Some("3"), None).condition().unwrap(), Some(Condition::Version(3)));
+        assert_eq!(precondition(Some("\"3\""), None).condition().unwrap(), Some(Condition::Version(3)));
+        assert_eq!(precondition(Some("W/\"3\""), None).condition().unwrap(), Some(Condition::Version(3)));
+
+        assert!(precondition(Some("0"), None).condition().is_err());
+        assert!(precondition(Some("abc"), None).condition().is_err());
+        assert!(precondition(None, Some("\"3\"")).condition().is_err());
+        assert!(precondition(Some("3"), Some("*")).condition().is_err());
+    }
+
+    #[test]
+    fn met() {
+        assert!(Condition::Absent.met(None).is_ok());
+        assert!(Condition::Absent.met(Some(1)).is_err());
+        assert!(Condition::Exists.met(Some(1)).is_ok());
+        assert!(Condition::Exists.met(None).is_err());
+        assert!(Condition::Version(2).met(Some(2)).is_ok());
+        assert!(Condition::Version(2).met(Some(3)).is_err());
+        assert!(Condition::Version(2).met(None).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::err::HecateError;
+use crate::options;
+
+///
+/// The batching window of a user in seconds, `None` if their edits are not batched
+///
+pub fn window(conn: &impl postgres::GenericConnection, uid: &i64, batch: &options::Batch) -> Result<Option<u32>, HecateError> {
+    match conn.query("
+        SELECT
+            CASE
+                WHEN jsonb_typeof(meta->'batch_window') = 'number' THEN (meta->>'batch_window')::BIGINT
+                ELSE NULL
+            END
+        FROM
+            users
+        WHERE
+            id = $1
+    ", &[&uid]) {
+        Ok(rows) => match rows.iter().next() {
+            None => Ok(batch.window(None)),
+            Some(row) => {
+                let user: Option<i64> = row.get(0);
+                Ok(batch.window(user.filter(|user| *user >= 0).map(|user| user as u32)))
+            }
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Validate the `batch_window` key of a user's meta, if it is set
+///
+pub fn validate(meta: &serde_json::Value) -> Result<(), HecateError> {
+    match meta.get("batch_window") {
+        None => Ok(()),
+        Some(window) => match window.as_u64() {
+            Some(window) if window <= options::MAX_BATCH as u64 => Ok(()),
+            _ => Err(HecateError::new(400, format!("User meta batch_window must be an integer between 0 and {}", options::MAX_BATCH), None))
+        }
+    }
+}
+
+///
+/// The delta the next edit of a user is added to, if their last delta is a
+/// batch that has not yet closed
+///
+/// A batch stays unfinalized while it is open, it is finalized by `close`
+/// once the window has passed
+///
+/// The delta is locked until the transaction ends so that concurrent edits
+/// of the user are added to it one at a time
+///
+pub fn current(trans: &postgres::transaction::Transaction, uid: &i64) -> Result<Option<i64>, HecateError> {
+    match trans.query("
+        SELECT
+            id,
+            COALESCE(batch_until > NOW()::TIMESTAMP AND NOT finalized, false)
+        FROM
+            deltas
+        WHERE
+            uid = $1
+        ORDER BY id DESC
+        LIMIT 1
+        FOR UPDATE
+    ", &[&uid]) {
+        Ok(rows) => match rows.iter().next() {
+            None => Ok(None),
+            Some(row) => {
+                let open: bool = row.get(1);
+
+                if open {
+                    Ok(Some(row.get(0)))
+                } else {
+                    Ok(None)
+                }
+            }
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Add the features of an edit to a batch, keeping every edit of a feature
+/// so that its history is preserved, and its message to that of the delta
+///
+pub fn append(id: &i64, trans: &postgres::transaction::Transaction, fc: &geojson::FeatureCollection, message: &String) -> Result<i64, HecateError> {
+    let fc_str = serde_json::to_string(&fc).unwrap();
+
+    match trans.execute("
+        UPDATE deltas
+            SET
+                features = jsonb_set(features, '{features}', (features->'features') || ($2::TEXT::JSONB->'features')),
+                affected = affected || $3,
+                props = jsonb_set(COALESCE(props, '{}'::JSONB), '{message}', to_jsonb(concat_ws('; ', props->>'message', $4::TEXT)))
+            WHERE
+                id = $1
+    ", &[&id, &fc_str, &super::affected(&fc), &message]) {
+        Ok(_) => Ok(*id),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Keep a batch open for `window` seconds from now
+///
+pub fn extend(id: &i64, trans: &postgres::transaction::Transaction, window: u32) -> Result<i64, HecateError> {
+    match trans.execute("
+        UPDATE deltas
+            SET batch_until = NOW()::TIMESTAMP + ($2::INTEGER * INTERVAL '1 second')
+            WHERE id = $1
+    ", &[&id, &(window as i32)]) {
+        Ok(_) => Ok(*id),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Finalize every batch whose window has passed, returning their ids so that
+/// the events of each are sent once the batch is complete
+///
+pub fn close(conn: &impl postgres::GenericConnection) -> Result<Vec<i64>, HecateError> {
+    match conn.query("
+        UPDATE deltas
+            SET finalized = true
+            WHERE
+                NOT finalized
+                AND batch_until <= NOW()::TIMESTAMP
+            RETURNING id
+    ", &[]) {
+        Ok(rows) => {
+            let mut ids: Vec<i64> = rows.iter().map(|row| row.get(0)).collect();
+            ids.sort();
+            Ok(ids)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_meta() {
+        assert!(validate(&json!({})).is_ok());
+        assert!(validate(&json!({ "batch_window": 0 })).is_ok());
+        assert!(validate(&json!({ "batch_window": 300 })).is_ok());
+
+        assert!(validate(&json!({ "batch_window": -1 })).is_err());
+        assert!(validate(&json!({ "batch_window": 1.5 })).is_err());
+        assert!(validate(&json!({ "batch_window": "300" })).is_err());
+        assert!(validate(&json!({ "batch_window": options::MAX_BATCH + 1 })).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+use rocket::request::{self, FromRequest};
+use rocket::{Request, Outcome};
+use crate::err::HecateError;
+use crate::tenant::Scope;
+
+///
+/// The numeric id parsed from a `Last-Event-ID` header, used by
+/// reconnecting clients to resume a stream from a known delta
+///
+pub struct LastEventId(pub Option<i64>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for LastEventId {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<LastEventId, ()> {
+        match request.headers().get_one("Last-Event-ID") {
+            Some(id) => match id.trim().parse() {
+                Ok(id) => Outcome::Success(LastEventId(Some(id))),
+                Err(_) => Outcome::Success(LastEventId(None))
+            },
+            None => Outcome::Success(LastEventId(None))
+        }
+    }
+}
+
+///
+/// A never ending Server-Sent Events stream of the finalized deltas of a tenant
+///
+/// Each read blocks until the worker publishes a new delta. After each
+/// event the reader signals `WouldBlock` so the event is flushed to the
+/// client immediately rather than sitting in the chunk buffer
+///
+pub struct DeltaEvents {
+    pool: r2d2::Pool<r2d2_postgres::PostgresConnectionManager>,
+    rx: crossbeam::Receiver<i64>,
+    bbox: Option<Vec<f64>>,
+    tenant: Scope,
+    backlog: VecDeque<i64>,
+    last: i64,
+    pending: Vec<u8>,
+    flush: bool
+}
+
+impl DeltaEvents {
+    pub fn new(
+        pool: r2d2::Pool<r2d2_postgres::PostgresConnectionManager>,
+        rx: crossbeam::Receiver<i64>,
+        bbox: Option<Vec<f64>>,
+        tenant: Scope,
+        last_event: Option<i64>
+    ) -> Result<Self, HecateError> {
+        //The receiver must already be subscribed before the backlog is read
+        //so that no delta can be finalized in between the two and be missed
+        let backlog: VecDeque<i64> = match last_event {
+            None => VecDeque::new(),
+            Some(last_event) => {
+                let conn = match pool.get() {
+                    Ok(conn) => conn,
+                    Err(_) => { return Err(HecateError::new(503, String::from("Could not connect to database"), None)); }
+                };
+
+                super::since(&*conn, &last_event, &tenant)?.into_iter().collect()
+            }
+        };
+
+        Ok(DeltaEvents {
+            pool: pool,
+            rx: rx,
+            bbox: bbox,
+            tenant: tenant,
+            backlog: backlog,
+            last: last_event.unwrap_or(0),
+            pending: Vec::new(),
+            flush: false
+        })
+    }
+
+    fn event(&mut self, delta_id: i64) -> std::io::Result<Vec<u8>> {
+        //Deltas already sent as part of the backlog can be published again by the worker
+        if delta_id <= self.last {
+            return Ok(Vec::new());
+        }
+
+        let conn = match self.pool.get() {
+            Ok(conn) => conn,
+            Err(err) => { return Err(Error::new(ErrorKind::Other, format!("{:?}", err))); }
+        };
+
+        let summary = match super::summary(&*conn, &delta_id, &self.bbox, &self.tenant) {
+            Ok(summary) => summary,
+            Err(err) => { return Err(Error::new(ErrorKind::Other, err.to_string())); }
+        };
+
+        self.last = delta_id;
+
+        match summary {
+            None => Ok(Vec::new()),
+            Some(summary) => Ok(format!("id: {}\nevent: delta\ndata: {}\n\n", delta_id, summary).into_bytes())
+        }
+    }
+}
+
+impl std::io::Read for DeltaEvents {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.flush {
+            self.flush = false;
+            return Err(Error::new(ErrorKind::WouldBlock, "flush event"));
+        }
+
+        while self.pending.len() == 0 {
+            let delta_id = match self.backlog.pop_front() {
+                Some(delta_id) => delta_id,
+                None => match self.rx.recv_timeout(Duration::from_secs(15)) {
+                    Ok(delta_id) => delta_id,
+                    Err(crossbeam::channel::RecvTimeoutError::Timeout) => {
+                        //Comment lines are ignored by clients but detect closed connections
+                        self.pending = b":keep-alive\n\n".to_vec();
+                        break;
+                    },
+                    Err(crossbeam::channel::RecvTimeoutError::Disconnected) => {
+                        return Ok(0);
+                    }
+                }
+            };
+
+            self.pending = self.event(delta_id)?;
+        }
+
+        let mut len = std::cmp::min(buf.len(), self.pending.len());
+
+        //A completely full buffer is written out without a flush, so
+        //hold back the last byte of an event that would exactly fill it
+        if len == buf.len() && len == self.pending.len() && len > 1 {
+            len = len - 1;
+        }
+
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+
+        if self.pending.len() == 0 && len < buf.len() {
+            self.flush = true;
+        }
+
+        Ok(len)
+    }
+}
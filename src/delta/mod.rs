@@ -1,7 +1,12 @@
+pub mod batch;
+pub mod events;
+
 use postgres;
 use geo::prelude::*;
 use std::collections::HashMap;
 use crate::err::HecateError;
+use crate::validate;
+use crate::tenant::Scope;
 use serde_json::Value;
 
 pub struct Delta {
@@ -38,8 +43,8 @@ impl Delta {
 }
 
 ///Get the history of a particular feature
-pub fn history(conn: &impl postgres::GenericConnection, feat_id: &i64) -> Result<serde_json::Value, HecateError> {
-    match conn.query("
+pub fn history(conn: &impl postgres::GenericConnection, feat_id: &i64, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    match conn.query(&*format!("
         SELECT json_agg(row_to_json(t))
         FROM (
             SELECT
@@ -53,30 +58,174 @@ pub fn history(conn: &impl postgres::GenericConnection, feat_id: &i64) -> Result
             WHERE
                 affected @> ARRAY[$1]::BIGINT[]
                 AND users.id = deltas.uid
+                AND {scope}
             ORDER BY id DESC
         ) t
         WHERE
             (feat->>'id')::BIGINT = $1;
-    ", &[&feat_id]) {
+    ", scope = tenant.to_sql("deltas.tenant")), &[&feat_id]) {
         Ok(res) => {
-            if res.len() == 0 {
-                return Err(HecateError::new(400, String::from("Could not find history for given id"), None))
-            }
+            //A feature without any history visible to the tenant aggregates to NULL
+            let history: Option<serde_json::Value> = match res.iter().next() {
+                Some(row) => row.get(0),
+                None => None
+            };
 
-            let history: serde_json::Value = res.get(0).get(0);
-            Ok(history)
+            match history {
+                Some(history) => Ok(history),
+                None => Err(HecateError::new(400, String::from("Could not find history for given id"), None))
+            }
         },
         Err(err) => Err(HecateError::from_db(err))
     }
 }
 
+///
+/// Maximum number of features whose history can be requested at once
+///
+pub const MAX_TIMELINE_IDS: usize = 1000;
+
+///
+/// Get the versions of many features as a single list, oldest first
+///
+/// Versions are returned from the deltas after `after`. A page always ends on a
+/// delta boundary so that the id of the last delta is a complete cursor, a page
+/// may therefore hold more than `limit` versions when its last delta wrote
+/// several of the features
+///
+pub fn timeline(conn: &impl postgres::GenericConnection, feat_ids: &Vec<i64>, after: Option<i64>, limit: Option<i64>, tenant: &Scope) -> Result<Value, HecateError> {
+    if feat_ids.len() == 0 || feat_ids.len() > MAX_TIMELINE_IDS {
+        return Err(HecateError::new(400, format!("Invalid Feature History - between 1 and {} ids must be given", MAX_TIMELINE_IDS), None));
+    }
+
+    let boundary = match limit {
+        None => String::from("TRUE"),
+        Some(limit) => format!("
+            versions.id <= COALESCE((
+                SELECT id FROM versions ORDER BY id, (feat->>'id')::BIGINT OFFSET {} LIMIT 1
+            ), versions.id)
+        ", limit - 1)
+    };
+
+    match conn.query(&*format!("
+        WITH versions AS (
+            SELECT
+                deltas.id,
+                deltas.uid,
+                users.username,
+                to_json(deltas.created) AS created,
+                deltas.props->>'message' AS message,
+                feat
+            FROM
+                deltas,
+                users,
+                JSONB_Array_Elements(deltas.features->'features') AS feat
+            WHERE
+                deltas.affected && $1::BIGINT[]
+                AND (feat->>'id')::BIGINT = ANY($1::BIGINT[])
+                AND deltas.id > COALESCE($2::BIGINT, 0)
+                AND users.id = deltas.uid
+                AND {scope}
+        )
+        SELECT COALESCE(json_agg(row_to_json(t) ORDER BY t.id, (t.feat->>'id')::BIGINT), '[]'::JSON)
+        FROM (
+            SELECT
+                versions.id,
+                versions.uid,
+                versions.username,
+                versions.created,
+                versions.message,
+                versions.feat
+            FROM
+                versions
+            WHERE
+                {boundary}
+        ) t
+    ", scope = tenant.to_sql("deltas.tenant"), boundary = boundary), &[&feat_ids, &after]) {
+        Ok(res) => Ok(res.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Map each current property of a feature to the delta that last set its value
+///
+/// The history of the feature is walked from oldest to newest, a property is
+/// attributed to a version whenever its value differs from the previous version
+///
+pub fn blame(conn: &impl postgres::GenericConnection, feat_id: &i64, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    let rows = match conn.query(&*format!("
+        SELECT
+            deltas.id,
+            deltas.uid,
+            users.username,
+            to_json(deltas.created),
+            feat->'properties'
+        FROM
+            deltas,
+            users,
+            JSONB_Array_Elements(deltas.features->'features') AS feat
+        WHERE
+            deltas.affected @> ARRAY[$1]::BIGINT[]
+            AND users.id = deltas.uid
+            AND (feat->>'id')::BIGINT = $1
+            AND {scope}
+        ORDER BY deltas.id ASC
+    ", scope = tenant.to_sql("deltas.tenant")), &[&feat_id]) {
+        Ok(rows) => rows,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    if rows.len() == 0 {
+        return Err(HecateError::new(404, String::from("Could not find history for given id"), None));
+    }
+
+    //The current value of each property along with the version that set it
+    let mut blame: HashMap<String, (serde_json::Value, serde_json::Value)> = HashMap::new();
+
+    for row in rows.iter() {
+        let delta: i64 = row.get(0);
+        let uid: i64 = row.get(1);
+        let username: String = row.get(2);
+        let created: serde_json::Value = row.get(3);
+        let props: Option<serde_json::Value> = row.get(4);
+
+        let props = match props {
+            Some(serde_json::Value::Object(props)) => props,
+            _ => serde_json::Map::new()
+        };
+
+        blame.retain(|key, _| props.contains_key(key));
+
+        for (key, value) in props {
+            match blame.get(&key) {
+                Some((current, _)) if *current == value => { continue; },
+                _ => ()
+            };
+
+            blame.insert(key, (value, json!({
+                "delta": delta,
+                "uid": uid,
+                "username": username,
+                "timestamp": created
+            })));
+        }
+    }
+
+    Ok(json!(blame.into_iter().map(|(key, (_, version))| (key, version)).collect::<serde_json::Map<String, serde_json::Value>>()))
+}
+
+///
+/// Open a delta for the user, stamped with the tenant of the user
+///
 pub fn open(trans: &postgres::transaction::Transaction, props: &HashMap<String, Option<String>>, uid: &i64) -> Result<i64, HecateError> {
     match trans.query("
-        INSERT INTO deltas (id, created, props, uid) VALUES (
+        INSERT INTO deltas (id, created, props, uid, tenant) VALUES (
             nextval('deltas_id_seq'),
             current_timestamp,
             to_json($1::HSTORE),
-            $2
+            $2,
+            (SELECT tenant FROM users WHERE id = $2)
         ) RETURNING id;
     ", &[&props, &uid]) {
         Err(err) => Err(HecateError::from_db(err)),
@@ -85,17 +234,51 @@ pub fn open(trans: &postgres::transaction::Transaction, props: &HashMap<String,
 
 }
 
+///
+/// The tenants whose data a delta changed, the tenant of the delta & of every
+/// feature it affected, as an admin crossing tenants can edit any of them
+///
+pub fn tenants(conn: &impl postgres::GenericConnection, id: &i64) -> Result<Vec<Option<i64>>, HecateError> {
+    match conn.query("
+        SELECT DISTINCT
+            tenant
+        FROM (
+            SELECT
+                tenant
+            FROM
+                deltas
+            WHERE
+                id = $1
+
+            UNION ALL
+
+            SELECT
+                geo.tenant
+            FROM
+                geo,
+                deltas
+            WHERE
+                deltas.id = $1
+                AND geo.id = ANY(deltas.affected)
+        ) t
+    ", &[&id]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => Ok(res.iter().map(|row| row.get(0)).collect())
+    }
+}
+
 pub fn create(trans: &postgres::transaction::Transaction, fc: &geojson::FeatureCollection, props: &HashMap<String, Option<String>>, uid: &i64) -> Result<i64, HecateError> {
     let fc_str = serde_json::to_string(&fc).unwrap();
 
     match trans.query("
-        INSERT INTO deltas (id, created, features, uid, props, affected) VALUES (
+        INSERT INTO deltas (id, created, features, uid, props, affected, tenant) VALUES (
             nextval('deltas_id_seq'),
             current_timestamp,
             $1::TEXT::JSON,
             $2,
             to_json($3::HSTORE),
-            $4
+            $4,
+            (SELECT tenant FROM users WHERE id = $2)
         ) RETURNING id;
     ", &[&fc_str, &uid, &props, &affected(&fc)]) {
         Err(err) => Err(HecateError::from_db(err)),
@@ -103,18 +286,27 @@ pub fn create(trans: &postgres::transaction::Transaction, fc: &geojson::FeatureC
     }
 }
 
-pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chrono::NaiveDateTime>, end: Option<chrono::NaiveDateTime>, limit: Option<i64>) -> Result<serde_json::Value, HecateError> {
-    match conn.query("
+///
+/// Fields that can be requested when listing deltas
+///
+pub const LIST_FIELDS: &[(&str, &str)] = &[
+    ("id", "deltas.id"),
+    ("uid", "deltas.uid"),
+    ("username", "users.username"),
+    ("created", "deltas.created"),
+    ("props", "deltas.props")
+];
+
+pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chrono::NaiveDateTime>, end: Option<chrono::NaiveDateTime>, limit: Option<i64>, fields: &Option<String>, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    let fields = validate::fields(fields, LIST_FIELDS)?;
+
+    match conn.query(&*format!("
         SELECT COALESCE(array_to_json(Array_Agg(djson.delta)), '[]')::JSON
         FROM (
             SELECT row_to_json(d) as delta
             FROM (
                 SELECT
-                    deltas.id,
-                    deltas.uid,
-                    users.username,
-                    deltas.created,
-                    deltas.props
+                    {fields}
                 FROM
                     deltas,
                     users
@@ -134,11 +326,12 @@ pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chron
                         AND $2::TIMESTAMP IS NOT NULL
                         AND deltas.created > $2::TIMESTAMP
                     ))
-                ORDER BY id DESC
+                    AND {scope}
+                ORDER BY deltas.id DESC
                 LIMIT $3
             ) d
         ) djson;
-    ", &[&start, &end, &limit]) {
+    ", fields = fields, scope = tenant.to_sql("deltas.tenant")), &[&start, &end, &limit]) {
         Err(err) => Err(HecateError::from_db(err)),
         Ok(res) => {
             let d_json: serde_json::Value = res.get(0).get(0);
@@ -147,45 +340,33 @@ pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chron
     }
 }
 
-pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i64>, limit: Option<i64>) -> Result<serde_json::Value, HecateError> {
+pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i64>, limit: Option<i64>, fields: &Option<String>, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    let fields = validate::fields(fields, LIST_FIELDS)?;
+
     let offset = match offset {
         None => String::from("Infinity"),
         Some(offset) => offset.to_string()
     };
 
-    let limit = match limit {
-        None => Some(20),
-        Some(limit) => {
-            if limit > 100 {
-                Some(100)
-            } else {
-                Some(limit)
-            }
-        }
-    };
-
-    match conn.query("
+    match conn.query(&*format!("
         SELECT COALESCE(array_to_json(Array_Agg(djson.delta)), '[]')::JSON
         FROM (
             SELECT row_to_json(d) as delta
             FROM (
                 SELECT
-                    deltas.id,
-                    deltas.uid,
-                    users.username,
-                    deltas.created,
-                    deltas.props
+                    {fields}
                 FROM
                     deltas,
                     users
                 WHERE
                     deltas.uid = users.id
                     AND deltas.id < $1::TEXT::FLOAT8
-                ORDER BY id DESC
+                    AND {scope}
+                ORDER BY deltas.id DESC
                 LIMIT $2
             ) d
         ) djson;
-    ", &[&offset, &limit]) {
+    ", fields = fields, scope = tenant.to_sql("deltas.tenant")), &[&offset, &limit]) {
         Err(err) => Err(HecateError::from_db(err)),
         Ok(res) => {
             let d_json: serde_json::Value = res.get(0).get(0);
@@ -194,6 +375,31 @@ pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i6
     }
 }
 
+///
+/// The offset that returns the page of deltas newer than the given delta,
+/// `None` if there are no newer deltas
+///
+pub fn prev_offset(conn: &impl postgres::GenericConnection, id: &i64, limit: Option<i64>, tenant: &Scope) -> Result<Option<i64>, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            MAX(d.id) + 1
+        FROM (
+            SELECT
+                id
+            FROM
+                deltas
+            WHERE
+                id > $1
+                AND {scope}
+            ORDER BY id ASC
+            LIMIT $2
+        ) d
+    ", scope = tenant.to_sql("tenant")), &[&id, &limit]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => Ok(res.get(0).get(0))
+    }
+}
+
 pub fn tiles(conn: &impl postgres::GenericConnection, id: &i64, min_zoom: u8, max_zoom: u8) -> Result<Vec<(i32, i32, u8)>, HecateError> {
     match conn.query("
         SELECT
@@ -246,8 +452,41 @@ pub fn tiles(conn: &impl postgres::GenericConnection, id: &i64, min_zoom: u8, ma
 
 }
 
-pub fn get_json(conn: &impl postgres::GenericConnection, id: &i64) -> Result<serde_json::Value, HecateError> {
-    match conn.query("
+///
+/// Find the delta that produced a given version of a feature
+///
+/// Every create, modify or restore of a feature produces a new version, a create
+/// producing version 1 and any other action the version after the one it was applied to
+///
+pub fn version(conn: &impl postgres::GenericConnection, feat_id: &i64, version: &i64, tenant: &Scope) -> Result<i64, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            deltas.id
+        FROM
+            deltas,
+            JSONB_Array_Elements(deltas.features->'features') AS feat
+        WHERE
+            deltas.affected @> ARRAY[$1]::BIGINT[]
+            AND (feat->>'id')::BIGINT = $1
+            AND feat->>'action' != 'delete'
+            AND COALESCE((feat->>'version')::BIGINT, 0) + 1 = $2
+            AND {scope}
+        ORDER BY deltas.id DESC
+        LIMIT 1
+    ", scope = tenant.to_sql("deltas.tenant")), &[&feat_id, &version]) {
+        Ok(rows) => {
+            if rows.len() == 0 {
+                return Err(HecateError::new(404, String::from("No delta found for the given feature version"), None));
+            }
+
+            Ok(rows.get(0).get(0))
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+pub fn get_json(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    match conn.query(&*format!("
         SELECT COALESCE(row_to_json(d), 'false'::JSON)
         FROM (
             SELECT
@@ -265,16 +504,124 @@ pub fn get_json(conn: &impl postgres::GenericConnection, id: &i64) -> Result<ser
             WHERE
                 deltas.uid = users.id
                 AND deltas.id = $1
+                AND {scope}
         ) d
-    ", &[&id]) {
+    ", scope = tenant.to_sql("deltas.tenant")), &[&id]) {
         Err(err) => Err(HecateError::from_db(err)),
         Ok(res) => {
+            if res.len() == 0 {
+                return Err(HecateError::new(404, String::from("Delta Not Found"), None));
+            }
+
             let d_json: serde_json::Value = res.get(0).get(0);
             Ok(d_json)
         }
     }
 }
 
+///
+/// Return the metadata (without features) of a finalized delta
+///
+/// If a bbox is given, None is returned unless at least one feature
+/// geometry in the delta intersects it. A bbox with minX greater than maxX
+/// crosses the antimeridian
+///
+pub fn summary(conn: &impl postgres::GenericConnection, id: &i64, bbox: &Option<Vec<f64>>, tenant: &Scope) -> Result<Option<serde_json::Value>, HecateError> {
+    match conn.query(&*format!("
+        SELECT row_to_json(d)
+        FROM (
+            SELECT
+                deltas.id,
+                deltas.uid,
+                users.username,
+                deltas.created,
+                deltas.props
+            FROM
+                deltas,
+                users
+            WHERE
+                deltas.uid = users.id
+                AND deltas.id = $1
+                AND deltas.finalized = true
+                AND {scope}
+                AND (
+                    $2::FLOAT8[] IS NULL
+                    OR EXISTS (
+                        SELECT 1
+                        FROM JSON_Array_Elements((deltas.features -> 'features')::JSON) AS feat
+                        WHERE
+                            JSON_TypeOf(feat->'geometry') = 'object'
+                            AND ST_Intersects(
+                                ST_SetSRID(ST_GeomFromGeoJSON(feat->>'geometry'), 4326),
+                                CASE
+                                    WHEN $2[1] > $2[3] THEN ST_Collect(
+                                        ST_MakeEnvelope($2[1], $2[2], 180, $2[4], 4326),
+                                        ST_MakeEnvelope(-180, $2[2], $2[3], $2[4], 4326)
+                                    )
+                                    ELSE ST_MakeEnvelope($2[1], $2[2], $2[3], $2[4], 4326)
+                                END
+                            )
+                    )
+                )
+        ) d
+    ", scope = tenant.to_sql("deltas.tenant")), &[&id, &bbox]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => {
+            if res.len() == 0 {
+                return Ok(None);
+            }
+
+            let d_json: serde_json::Value = res.get(0).get(0);
+            Ok(Some(d_json))
+        }
+    }
+}
+
+///
+/// Return the id and action of each feature in a delta
+///
+pub fn actions(conn: &impl postgres::GenericConnection, id: &i64) -> Result<Vec<(serde_json::Value, String)>, HecateError> {
+    match conn.query("
+        SELECT
+            COALESCE(feat->'id', 'null'::JSONB),
+            COALESCE(feat->>'action', 'create')
+        FROM
+            deltas,
+            JSONB_Array_Elements(deltas.features->'features') AS feat
+        WHERE
+            deltas.id = $1
+    ", &[&id]) {
+        Ok(rows) => Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect()),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Return the ids of all finalized deltas created after the given delta id
+///
+pub fn since(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<Vec<i64>, HecateError> {
+    match conn.query(&*format!("
+        SELECT id
+        FROM deltas
+        WHERE
+            id > $1
+            AND finalized = true
+            AND {scope}
+        ORDER BY id ASC
+    ", scope = tenant.to_sql("tenant")), &[&id]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => {
+            let mut ids: Vec<i64> = Vec::with_capacity(res.len());
+
+            for row in res.iter() {
+                ids.push(row.get(0));
+            }
+
+            Ok(ids)
+        }
+    }
+}
+
 pub fn modify_props(id: &i64, trans: &postgres::transaction::Transaction, props: &HashMap<String, Option<String>>, uid: &i64) -> Result<i64, HecateError> {
     match trans.query("
         UPDATE deltas
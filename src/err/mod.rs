@@ -1,9 +1,21 @@
-#[derive(PartialEq, Debug)]
+///
+/// The SQLSTATEs of constraint violations, which are caused by the data of a
+/// request rather than by the server, along with the status, the `error` code
+/// & the default reason they are returned with
+///
+static CONSTRAINTS: [(&str, u16, &str, &str); 3] = [
+    ("23505", 409, "CONFLICT", "Conflict - a record with the same value already exists"),
+    ("23503", 409, "REFERENCE", "Conflict - the record references a record that does not exist or is still referenced"),
+    ("23514", 400, "CHECK", "Invalid Value - the record failed a validity check")
+];
+
+#[derive(PartialEq, Debug, Clone)]
 pub struct HecateError {
     code: u16,
     custom_json: Option<serde_json::Value>,
     safe_error: String,
-    full_error: String
+    full_error: String,
+    headers: Vec<(String, String)>
 }
 
 impl HecateError {
@@ -17,7 +29,8 @@ impl HecateError {
             code: code,
             custom_json: None,
             safe_error: safe_error,
-            full_error: full_error
+            full_error: full_error,
+            headers: Vec::new()
         }
     }
 
@@ -31,29 +44,86 @@ impl HecateError {
             code: code,
             custom_json: Some(json),
             safe_error: safe_error,
-            full_error: full_error
+            full_error: full_error,
+            headers: Vec::new()
         }
     }
 
     pub fn from_db(error: postgres::error::Error) -> Self {
         println!("Database Error: {:?}", &error);
 
+        if let Some(err) = HecateError::constraint(&error, None) {
+            return err;
+        }
+
         match error.as_db() {
             Some(db_err) => HecateError {
                 code: 500,
                 custom_json: None,
                 safe_error: String::from("Database Error"),
-                full_error: format!("{}", db_err)
+                full_error: format!("{}", db_err),
+                headers: Vec::new()
             },
             None => HecateError {
                 code: 500,
                 custom_json: None,
                 safe_error: String::from("Database Error"),
-                full_error: format!("{}", error)
+                full_error: format!("{}", error),
+                headers: Vec::new()
             }
         }
     }
 
+    ///
+    /// As `from_db` but with a reason describing the record in conflict,
+    /// ie: `User/Email Exists`, if the error is a constraint violation
+    ///
+    pub fn from_constraint(error: postgres::error::Error, reason: &str) -> Self {
+        match HecateError::constraint(&error, Some(reason)) {
+            Some(err) => err,
+            None => HecateError::from_db(error)
+        }
+    }
+
+    fn constraint(error: &postgres::error::Error, reason: Option<&str>) -> Option<Self> {
+        let state = match error.as_db() {
+            Some(db_err) => db_err.code.code(),
+            None => { return None; }
+        };
+
+        let (code, name, default) = match CONSTRAINTS.iter().find(|constraint| constraint.0 == state) {
+            Some(constraint) => (constraint.1, constraint.2, constraint.3),
+            None => { return None; }
+        };
+
+        Some(HecateError::violation(code, name, reason.unwrap_or(default), Some(format!("{}", error))))
+    }
+
+    ///
+    /// A `409` for a record that would conflict with an existing record, in the
+    /// same form as a unique constraint violation
+    ///
+    pub fn conflict(reason: &str) -> Self {
+        HecateError::violation(409, "CONFLICT", reason, None)
+    }
+
+    fn violation(code: u16, name: &str, reason: &str, full_error: Option<String>) -> Self {
+        HecateError::from_json(code, json!({
+            "code": code,
+            "status": rocket::http::Status::from_code(code).unwrap().reason,
+            "error": name,
+            "reason": reason
+        }), String::from(reason), full_error)
+    }
+
+    ///
+    /// Add an HTTP header to be sent along with the error response
+    ///
+    pub fn with_header(mut self, name: &str, value: String) -> Self {
+        self.headers.push((String::from(name), value));
+        self
+    }
+
     pub fn as_json(self) -> serde_json::Value {
         match self.custom_json {
             Some(custom_json) => custom_json,
@@ -77,21 +147,35 @@ impl ToString for HecateError {
 }
 
 use std::io::Cursor;
+use rocket::State;
 use rocket::request::Request;
 use rocket::response::{self, Response, Responder};
 use rocket::http::ContentType;
 
 impl <'r> Responder<'r> for HecateError {
-    fn respond_to(self, _: &Request) -> response::Result<'r> {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
         let status = rocket::http::Status::from_code(self.code).unwrap();
+        let headers = self.headers.clone();
+
+        if self.code >= 500 {
+            if let Some(alerts) = request.guard::<State<crate::alert::Alerts>>().succeeded() {
+                alerts.notify(request, self.code, &self.safe_error, &self.full_error);
+            }
+        }
         let body = self.as_json().to_string();
 
         println!("HecateError: {:?}", &body);
 
-        Ok(Response::build()
+        let mut response = Response::build()
             .status(status)
             .sized_body(Cursor::new(body))
             .header(ContentType::new("application", "json"))
-            .finalize())
+            .finalize();
+
+        for (name, value) in headers {
+            response.set_raw_header(name, value);
+        }
+
+        Ok(response)
     }
 }
@@ -0,0 +1,170 @@
+use crate::err::HecateError;
+use crate::{index, filter};
+use crate::tenant::Scope;
+
+///
+/// A request to delete many features in a single delta
+///
+/// Features are selected either by an explicit list of ids & versions or by
+/// a filter, in which case the current version of each matching feature is used
+///
+#[derive(Deserialize, Debug)]
+pub struct BatchDelete {
+    pub message: String,
+    pub features: Option<Vec<Target>>,
+    pub filter: Option<String>,
+    pub partial: Option<bool>,
+    pub force: Option<bool>
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct Target {
+    pub id: i64,
+    pub version: Option<i64>
+}
+
+///
+/// The outcome of a batch delete, the result of each requested id along with
+/// the features that were deleted, ready to be stored in a delta
+///
+pub struct Batch {
+    pub results: Vec<serde_json::Value>,
+    pub deleted: geojson::FeatureCollection
+}
+
+impl Batch {
+    pub fn failed(&self) -> bool {
+        self.results.iter().any(|result| result["status"] != "deleted")
+    }
+}
+
+impl BatchDelete {
+    ///
+    /// Resolve the features to delete, locking any features of the tenant selected by a filter
+    ///
+    pub fn targets(&self, trans: &postgres::transaction::Transaction, tenant: &Scope) -> Result<Vec<Target>, HecateError> {
+        match (&self.features, &self.filter) {
+            (Some(_), Some(_)) | (None, None) => {
+                Err(HecateError::new(400, String::from("Exactly one of features or filter must be provided"), None))
+            },
+            (Some(features), None) => {
+                if !self.force.unwrap_or(false) && features.iter().any(|target| target.version.is_none()) {
+                    return Err(HecateError::new(400, String::from("Each feature must have a version unless force is set"), None));
+                }
+
+                Ok(features.clone())
+            },
+            (None, Some(query)) => {
+                let expr = filter::parse(query)?;
+                let indexed = index::indexed(trans)?;
+
+                let mut params: Vec<serde_json::Value> = Vec::new();
+                let clause = expr.to_sql(&indexed, &mut params)?;
+
+                let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+                match trans.query(&*format!("
+                    SELECT
+                        id,
+                        version
+                    FROM geo
+                    WHERE
+                        {clause}
+                        AND {scope}
+                    ORDER BY id
+                    FOR UPDATE
+                ", clause = clause, scope = tenant.to_sql("tenant")), &params) {
+                    Ok(rows) => Ok(rows.iter().map(|row| Target {
+                        id: row.get(0),
+                        version: Some(row.get(1))
+                    }).collect()),
+                    Err(err) => Err(HecateError::from_db(err))
+                }
+            }
+        }
+    }
+}
+
+///
+/// Delete each target within its own savepoint so that a single failure does
+/// not abort the remaining deletes, the caller decides whether to commit the
+/// successful deletes or roll back the whole batch
+///
+pub fn delete(trans: &postgres::transaction::Transaction, targets: &Vec<Target>, bound: &Option<String>, tenant: &Scope) -> Result<Batch, HecateError> {
+    let mut batch = Batch {
+        results: Vec::with_capacity(targets.len()),
+        deleted: geojson::FeatureCollection {
+            bbox: None,
+            features: Vec::new(),
+            foreign_members: None
+        }
+    };
+
+    for target in targets {
+        let savepoint = match trans.savepoint("batch_delete") {
+            Ok(savepoint) => savepoint,
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+
+        match delete_one(&savepoint, target, bound, tenant) {
+            Ok(feat) => {
+                match savepoint.commit() {
+                    Ok(_) => (),
+                    Err(err) => { return Err(HecateError::from_db(err)); }
+                };
+
+                batch.results.push(json!({
+                    "id": target.id,
+                    "status": "deleted"
+                }));
+                batch.deleted.features.push(feat);
+            },
+            Err(err) => {
+                savepoint.set_rollback();
+                match savepoint.finish() {
+                    Ok(_) => (),
+                    Err(err) => { return Err(HecateError::from_db(err)); }
+                };
+
+                let err = err.as_json();
+                let reason = match err.get("message") {
+                    Some(message) => message.clone(),
+                    None => err["reason"].clone()
+                };
+
+                batch.results.push(json!({
+                    "id": target.id,
+                    "status": "failed",
+                    "reason": reason
+                }));
+            }
+        };
+    }
+
+    Ok(batch)
+}
+
+fn delete_one(trans: &postgres::transaction::Transaction, target: &Target, bound: &Option<String>, tenant: &Scope) -> Result<geojson::Feature, HecateError> {
+    let mut feat = match super::get(trans, &target.id, tenant) {
+        Ok(feat) => feat,
+        Err(ref err) if err.to_string() == "Not Found" => {
+            return Err(HecateError::new(404, String::from("Feature Not Found"), None));
+        },
+        Err(err) => { return Err(err); }
+    };
+
+    let mut members = feat.foreign_members.unwrap_or(serde_json::Map::new());
+
+    match target.version {
+        None => (),
+        Some(version) => { members.insert(String::from("version"), json!(version)); }
+    };
+    members.insert(String::from("action"), json!("delete"));
+
+    feat.foreign_members = Some(members);
+
+    super::stored_within_bound(trans, &feat, &super::Action::Delete, bound)?;
+    super::delete(trans, &feat)?;
+
+    Ok(feat)
+}
@@ -0,0 +1,78 @@
+use crate::bbox::Bbox;
+use crate::err::HecateError;
+use crate::mvt;
+use crate::tenant::Scope;
+
+///
+/// Number of grid cells points are clustered into along each side of a tile
+///
+pub static CELLS: f64 = 8.0;
+
+///
+/// Size in degrees of the grid cells points are clustered into at a zoom
+///
+pub fn cell_size(zoom: u8) -> f64 {
+    360.0 / (2_f64.powi(zoom as i32) * CELLS)
+}
+
+///
+/// Cluster the point features within a bbox, returning a FeatureCollection
+/// with a point at the centroid of each cluster and the number of features in it
+///
+/// Points are clustered on a grid that halves in size with each zoom, a
+/// request is rejected if the bbox is covered by more tiles at the zoom than
+/// a tile cover request may return
+///
+pub fn get(conn: &impl postgres::GenericConnection, bbox: &Bbox, zoom: u8, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    mvt::cover::bbox(&bbox, zoom, zoom)?;
+
+    let mut params: Vec<f64> = Vec::new();
+    let clause = bbox.to_sql("geom", &mut params);
+
+    params.push(cell_size(zoom));
+    let size = format!("${}", params.len());
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+    match conn.query(&*format!("
+        SELECT
+            json_build_object(
+                'type', 'FeatureCollection',
+                'features', COALESCE(json_agg(json_build_object(
+                    'type', 'Feature',
+                    'properties', json_build_object(
+                        'count', clusters.count
+                    ),
+                    'geometry', ST_AsGeoJSON(clusters.geom)::JSON
+                ) ORDER BY clusters.count DESC, ST_X(clusters.geom), ST_Y(clusters.geom)), '[]'::JSON)
+            )
+        FROM (
+            SELECT
+                COUNT(*) AS count,
+                ST_Centroid(ST_Collect(geom)) AS geom
+            FROM
+                geo
+            WHERE
+                GeometryType(geom) = 'POINT'
+                AND {clause}
+                AND {scope}
+            GROUP BY
+                ST_SnapToGrid(geom, {size})
+        ) clusters
+    ", clause = clause, size = size, scope = tenant.to_sql("tenant")), &params) {
+        Ok(res) => Ok(res.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_sizes() {
+        assert_eq!(cell_size(0), 45.0);
+        assert_eq!(cell_size(1), 22.5);
+        assert_eq!(cell_size(4), 2.8125);
+    }
+}
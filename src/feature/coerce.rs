@@ -0,0 +1,169 @@
+use serde_json::Value;
+use crate::err::HecateError;
+use crate::options;
+
+///
+/// Number of significant digits a decimal string can have and still be
+/// represented exactly as a JSON number
+///
+pub static MAX_DIGITS: usize = 15;
+
+///
+/// Convert the string property values of a feature being created, modified or
+/// restored to the type the schema declares for their key, ie: `"height": "12"`
+/// becomes `"height": 12` if `height` is declared as an `integer`
+///
+/// Only top level keys are coerced. A value is left as is if the schema allows
+/// it to be a string, and the write is rejected if it does not convert to exactly
+/// one value of the declared types without losing data
+///
+pub fn props(feat: &mut geojson::Feature, schema: &Option<Value>, policy: &options::Properties) -> Result<bool, HecateError> {
+    if !policy.is_coerce() || super::get_action(&feat)? == super::Action::Delete {
+        return Ok(true);
+    }
+
+    let declared = match schema {
+        Some(schema) => match schema.get("properties").and_then(|props| props.as_object()) {
+            Some(declared) => declared,
+            None => { return Ok(true); }
+        },
+        None => { return Ok(true); }
+    };
+
+    let mut coerced: Vec<(String, Value)> = Vec::new();
+
+    if let Some(ref props) = feat.properties {
+        for (key, value) in props.iter() {
+            let value = match value.as_str() {
+                Some(value) => value,
+                None => { continue; }
+            };
+
+            let types = match declared.get(key) {
+                Some(declared) => types(declared),
+                None => { continue; }
+            };
+
+            if types.len() == 0 || types.iter().any(|t| t == "string") {
+                continue;
+            }
+
+            match coerce(value, &types) {
+                Some(value) => coerced.push((key.clone(), value)),
+                None => {
+                    return Err(super::import_error(&feat, &*format!("Property '{}' cannot be coerced to {}", key, types.join(" or "))));
+                }
+            }
+        }
+    }
+
+    if let Some(ref mut props) = feat.properties {
+        for (key, value) in coerced {
+            props.insert(key, value);
+        }
+    }
+
+    Ok(true)
+}
+
+fn types(declared: &Value) -> Vec<String> {
+    match declared.get("type") {
+        Some(Value::String(t)) => vec![t.clone()],
+        Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).map(String::from).collect(),
+        _ => Vec::new()
+    }
+}
+
+///
+/// The value a string converts to, `None` if it converts to none of the types
+/// or to a different value for different types
+///
+pub fn coerce(value: &str, types: &Vec<String>) -> Option<Value> {
+    let values: Vec<Value> = types.iter().filter_map(|t| match t.as_str() {
+        "integer" => integer(value),
+        "number" => integer(value).or_else(|| number(value)),
+        "boolean" => boolean(value),
+        _ => None
+    }).collect();
+
+    match values.first() {
+        Some(first) if values.iter().all(|value| value == first) => Some(first.clone()),
+        _ => None
+    }
+}
+
+///
+/// Integers must round trip exactly, so that ie: `012` or `+12` are not coerced
+///
+fn integer(value: &str) -> Option<Value> {
+    match value.parse::<i64>() {
+        Ok(int) if int.to_string() == value => Some(Value::from(int)),
+        _ => None
+    }
+}
+
+fn number(value: &str) -> Option<Value> {
+    let mantissa = value.split(|c| c == 'e' || c == 'E').next().unwrap_or("");
+
+    let digits = mantissa.trim_start_matches(|c| c == '-' || c == '+' || c == '0' || c == '.').chars().filter(|c| c.is_ascii_digit()).count();
+
+    if digits > MAX_DIGITS {
+        return None;
+    }
+
+    match value.parse::<f64>() {
+        Ok(num) if num.is_finite() => serde_json::Number::from_f64(num).map(Value::Number),
+        _ => None
+    }
+}
+
+fn boolean(value: &str) -> Option<Value> {
+    match value {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(types: &[&str]) -> Vec<String> {
+        types.iter().map(|t| String::from(*t)).collect()
+    }
+
+    #[test]
+    fn coerce_integer() {
+        assert_eq!(coerce("12", &types(&["integer"])), Some(json!(12)));
+        assert_eq!(coerce("-12", &types(&["integer"])), Some(json!(-12)));
+        assert_eq!(coerce("abc", &types(&["integer"])), None);
+        assert_eq!(coerce("012", &types(&["integer"])), None);
+        assert_eq!(coerce("12.5", &types(&["integer"])), None);
+        assert_eq!(coerce("99999999999999999999", &types(&["integer"])), None);
+    }
+
+    #[test]
+    fn coerce_number() {
+        assert_eq!(coerce("12", &types(&["number"])), Some(json!(12)));
+        assert_eq!(coerce("12.5", &types(&["number"])), Some(json!(12.5)));
+        assert_eq!(coerce("0.001", &types(&["number"])), Some(json!(0.001)));
+        assert_eq!(coerce("NaN", &types(&["number"])), None);
+        assert_eq!(coerce("inf", &types(&["number"])), None);
+        assert_eq!(coerce("0.10000000000000000001", &types(&["number"])), None);
+    }
+
+    #[test]
+    fn coerce_boolean() {
+        assert_eq!(coerce("true", &types(&["boolean"])), Some(json!(true)));
+        assert_eq!(coerce("false", &types(&["boolean", "null"])), Some(json!(false)));
+        assert_eq!(coerce("yes", &types(&["boolean"])), None);
+    }
+
+    #[test]
+    fn coerce_ambiguous() {
+        assert_eq!(coerce("12", &types(&["integer", "number"])), Some(json!(12)));
+        assert_eq!(coerce("true", &types(&["integer", "boolean"])), Some(json!(true)));
+        assert_eq!(coerce("12", &types(&["object"])), None);
+    }
+}
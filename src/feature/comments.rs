@@ -0,0 +1,170 @@
+use crate::err::HecateError;
+use crate::tenant::Scope;
+use super::exists;
+
+///
+/// Maximum length of a single comment in characters
+///
+pub static MAX_LENGTH: usize = 4096;
+
+///
+/// Comments are notes left on a feature by reviewers, stored outside of the
+/// feature so that commenting does not create a delta or a new version of it
+///
+pub fn validate(comment: &str) -> Result<(), HecateError> {
+    let length = comment.trim().chars().count();
+
+    if length == 0 || length > MAX_LENGTH {
+        return Err(HecateError::new(400, format!("Invalid Comment - must be between 1 and {} characters", MAX_LENGTH), None));
+    }
+
+    Ok(())
+}
+
+const COMMENT_JSON: &str = "
+    json_build_object(
+        'id', feature_comments.id,
+        'uid', feature_comments.uid,
+        'username', users.username,
+        'comment', feature_comments.comment,
+        'created', feature_comments.created,
+        'resolved', feature_comments.resolved,
+        'resolved_by', feature_comments.resolved_by
+    )
+";
+
+///
+/// Return a single comment of a feature
+///
+fn get(conn: &impl postgres::GenericConnection, id: &i64, comment: &i64) -> Result<serde_json::Value, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            {comment}
+        FROM
+            feature_comments
+                LEFT JOIN users ON feature_comments.uid = users.id
+        WHERE
+            feature_comments.feature = $1
+            AND feature_comments.id = $2
+    ", comment = COMMENT_JSON), &[&id, &comment]) {
+        Ok(rows) => match rows.iter().next() {
+            None => Err(HecateError::new(404, String::from("Comment Not Found"), None)),
+            Some(row) => Ok(row.get(0))
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Return the comments of a feature, oldest first
+///
+pub fn list(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    exists(conn, id, tenant)?;
+
+    match conn.query(&*format!("
+        SELECT
+            COALESCE(json_agg({comment} ORDER BY feature_comments.id), '[]'::JSON)
+        FROM
+            feature_comments
+                LEFT JOIN users ON feature_comments.uid = users.id
+        WHERE
+            feature_comments.feature = $1
+    ", comment = COMMENT_JSON), &[&id]) {
+        Ok(rows) => Ok(rows.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Add a comment to a feature, returning the new comment
+///
+pub fn create(conn: &impl postgres::GenericConnection, id: &i64, uid: &i64, comment: &str, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    validate(comment)?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    exists(&trans, id, tenant)?;
+
+    let comment_id: i64 = match trans.query("
+        INSERT INTO feature_comments (feature, uid, comment, created)
+            VALUES ($1, $2, $3, NOW())
+            RETURNING id
+    ", &[&id, &uid, &comment]) {
+        Ok(rows) => rows.get(0).get(0),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    let comment = get(&trans, id, &comment_id)?;
+
+    match trans.commit() {
+        Ok(_) => Ok(comment),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Mark a comment as resolved by the given user, or as unresolved if no user is given
+///
+pub fn resolve(conn: &impl postgres::GenericConnection, id: &i64, comment: &i64, uid: Option<i64>, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    exists(conn, id, tenant)?;
+
+    match conn.execute("
+        UPDATE feature_comments
+            SET
+                resolved = CASE WHEN $3::BIGINT IS NULL THEN NULL ELSE NOW() END,
+                resolved_by = $3
+            WHERE
+                feature = $1
+                AND id = $2
+    ", &[&id, &comment, &uid]) {
+        Ok(0) => Err(HecateError::new(404, String::from("Comment Not Found"), None)),
+        Ok(_) => get(conn, id, comment),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Delete a comment, only its author or an admin may do so
+///
+pub fn delete(conn: &impl postgres::GenericConnection, id: &i64, comment: &i64, uid: &i64, admin: bool, tenant: &Scope) -> Result<bool, HecateError> {
+    exists(conn, id, tenant)?;
+
+    let author: i64 = match conn.query("
+        SELECT uid FROM feature_comments WHERE feature = $1 AND id = $2
+    ", &[&id, &comment]) {
+        Ok(rows) => match rows.iter().next() {
+            None => { return Err(HecateError::new(404, String::from("Comment Not Found"), None)); },
+            Some(row) => row.get(0)
+        },
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    if author != *uid && !admin {
+        return Err(HecateError::new(403, String::from("Only the author of a comment or an admin can delete it"), None));
+    }
+
+    match conn.execute("
+        DELETE FROM feature_comments WHERE feature = $1 AND id = $2
+    ", &[&id, &comment]) {
+        Ok(_) => Ok(true),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_comments() {
+        assert!(validate("Is this building still here?").is_ok());
+        assert!(validate(&"a".repeat(MAX_LENGTH)).is_ok());
+
+        assert!(validate("").is_err());
+        assert!(validate("  \n").is_err());
+        assert!(validate(&"a".repeat(MAX_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::err::HecateError;
+use crate::tenant::Scope;
+
+///
+/// A request to merge feature `b` into feature `a` in a single delta
+///
+/// The geometry of `a` becomes the union of both geometries, the properties of
+/// both features are combined with conflicts resolved by the given strategy and
+/// `b` is deleted
+///
+#[derive(Deserialize, Debug)]
+pub struct Merge {
+    pub message: String,
+    pub a: i64,
+    pub b: i64,
+    pub strategy: Option<String>
+}
+
+///
+/// Which feature wins when both features have a property with the same key
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Strategy {
+    PreferA,
+    PreferB,
+    Newest
+}
+
+impl Strategy {
+    pub fn parse(strategy: &Option<String>) -> Result<Self, HecateError> {
+        match strategy.as_ref().map(|strategy| strategy.as_str()) {
+            None | Some("prefer-a") => Ok(Strategy::PreferA),
+            Some("prefer-b") => Ok(Strategy::PreferB),
+            Some("newest") => Ok(Strategy::Newest),
+            Some(_) => Err(HecateError::new(400, String::from("Invalid Strategy - must be one of 'prefer-a', 'prefer-b' or 'newest'"), None))
+        }
+    }
+}
+
+///
+/// The features to store in the merge delta, the modify of `a` and the delete of `b`
+///
+pub struct Merged {
+    pub modify: geojson::Feature,
+    pub delete: geojson::Feature
+}
+
+impl Merge {
+    ///
+    /// Lock both features and build the modify & delete that make up the merge
+    ///
+    /// With the `newest` strategy the feature most recently changed by a delta
+    /// wins conflicts, `a` is preferred if both were last changed together
+    ///
+    pub fn features(&self, trans: &postgres::transaction::Transaction, tenant: &Scope) -> Result<Merged, HecateError> {
+        if self.a == self.b {
+            return Err(HecateError::new(400, String::from("Cannot merge a feature into itself"), None));
+        }
+
+        let strategy = Strategy::parse(&self.strategy)?;
+
+        let rows = match trans.query(&*format!("
+            SELECT
+                a.key,
+                a.version,
+                COALESCE(a.props, '{{}}'::JSONB),
+                b.key,
+                b.version,
+                COALESCE(b.props, '{{}}'::JSONB),
+                ST_AsGeoJSON(b.geom)::JSON,
+                ST_AsGeoJSON(ST_Union(a.geom, b.geom))::JSON,
+                (SELECT MAX(id) FROM deltas WHERE affected @> ARRAY[a.id]::BIGINT[]),
+                (SELECT MAX(id) FROM deltas WHERE affected @> ARRAY[b.id]::BIGINT[])
+            FROM
+                geo a,
+                geo b
+            WHERE
+                a.id = $1
+                AND b.id = $2
+                AND {a_scope}
+                AND {b_scope}
+            FOR UPDATE OF a, b
+        ", a_scope = tenant.to_sql("a.tenant"), b_scope = tenant.to_sql("b.tenant")), &[&self.a, &self.b]) {
+            Ok(rows) => rows,
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+
+        if rows.len() != 1 {
+            return Err(HecateError::new(404, String::from("Feature Not Found"), None));
+        }
+
+        let row = rows.get(0);
+
+        let a_key: Option<String> = row.get(0);
+        let a_version: i64 = row.get(1);
+        let a_props: serde_json::Value = row.get(2);
+        let b_key: Option<String> = row.get(3);
+        let b_version: i64 = row.get(4);
+        let b_props: serde_json::Value = row.get(5);
+        let b_geom: serde_json::Value = row.get(6);
+        let union: serde_json::Value = row.get(7);
+        let a_delta: Option<i64> = row.get(8);
+        let b_delta: Option<i64> = row.get(9);
+
+        let prefer_a = match strategy {
+            Strategy::PreferA => true,
+            Strategy::PreferB => false,
+            Strategy::Newest => a_delta >= b_delta
+        };
+
+        let (mut props, winner) = if prefer_a {
+            (b_props.clone(), a_props)
+        } else {
+            (a_props, b_props.clone())
+        };
+
+        match (props.as_object_mut(), winner) {
+            (Some(props), serde_json::Value::Object(winner)) => {
+                for (key, value) in winner {
+                    props.insert(key, value);
+                }
+            },
+            _ => { return Err(HecateError::new(500, String::from("Feature properties must be an object"), None)); }
+        };
+
+        Ok(Merged {
+            modify: super::from_value(json!({
+                "id": self.a,
+                "key": a_key,
+                "type": "Feature",
+                "action": "modify",
+                "version": a_version,
+                "properties": props,
+                "geometry": union
+            }))?,
+            delete: super::from_value(json!({
+                "id": self.b,
+                "key": b_key,
+                "type": "Feature",
+                "action": "delete",
+                "version": b_version,
+                "properties": b_props,
+                "geometry": b_geom
+            }))?
+        })
+    }
+}
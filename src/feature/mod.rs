@@ -1,6 +1,218 @@
+pub mod revalidate;
+pub mod batch;
+pub mod merge;
+pub mod split;
+pub mod rollback;
+pub mod coerce;
+pub mod tags;
+pub mod cluster;
+pub mod comments;
+pub mod replace;
+pub mod related;
+pub mod provenance;
+pub mod transform;
+
 use crate::stream::PGStream;
 use crate::err::HecateError;
 use crate::validate;
+use crate::{index, filter, options};
+use crate::tenant::Scope;
+use crate::bbox::Bbox;
+
+///
+/// The SRID that feature geometries are stored in
+///
+pub static SRID: i32 = 4326;
+
+///
+/// The GeoJSON geometry types accepted by the feature endpoints
+///
+pub static GEOMETRY_TYPES: [&str; 7] = [
+    "Point",
+    "MultiPoint",
+    "LineString",
+    "MultiLineString",
+    "Polygon",
+    "MultiPolygon",
+    "GeometryCollection"
+];
+
+///
+/// The GeoJSON geometry types that features are stored as, single part
+/// geometries are never stored when they are promoted to their multi part type
+///
+pub fn geometry_types(geometry: &options::Geometry) -> Vec<&'static str> {
+    GEOMETRY_TYPES.iter()
+        .filter(|kind| geometry.multi != Some(true) || kind.starts_with("Multi") || **kind == "GeometryCollection")
+        .cloned()
+        .collect()
+}
+
+///
+/// The encoding used for geometries in feature responses
+///
+/// WKT & EWKT geometries are returned as a `wkt` or `ewkt` string member on
+/// the feature, with the GeoJSON `geometry` left null
+///
+/// `Centroid` & `PointOnSurface` replace the geometry with a GeoJSON point
+/// representing it, the latter always lying on the geometry
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum GeometryFormat {
+    GeoJSON,
+    Wkt,
+    Ewkt,
+    Centroid,
+    PointOnSurface
+}
+
+impl GeometryFormat {
+    pub fn parse(format: &Option<String>) -> Result<Self, HecateError> {
+        match format.as_ref().map(|format| format.as_str()) {
+            None | Some("geojson") => Ok(GeometryFormat::GeoJSON),
+            Some("wkt") => Ok(GeometryFormat::Wkt),
+            Some("ewkt") => Ok(GeometryFormat::Ewkt),
+            Some("centroid") => Ok(GeometryFormat::Centroid),
+            Some("point_on_surface") => Ok(GeometryFormat::PointOnSurface),
+            Some(_) => Err(HecateError::new(400, String::from("Invalid Geometry - must be one of 'geojson', 'wkt', 'ewkt', 'centroid' or 'point_on_surface'"), None))
+        }
+    }
+
+    ///
+    /// The name of the format as accepted by `parse`
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeometryFormat::GeoJSON => "geojson",
+            GeometryFormat::Wkt => "wkt",
+            GeometryFormat::Ewkt => "ewkt",
+            GeometryFormat::Centroid => "centroid",
+            GeometryFormat::PointOnSurface => "point_on_surface"
+        }
+    }
+
+    ///
+    /// The select list fragment that returns the geometry in this format
+    ///
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            GeometryFormat::GeoJSON => "ST_AsGeoJSON(geom)::JSON AS geometry",
+            GeometryFormat::Wkt => "NULL::JSON AS geometry, ST_AsText(geom) AS wkt",
+            GeometryFormat::Ewkt => "NULL::JSON AS geometry, ST_AsEWKT(geom) AS ewkt",
+            GeometryFormat::Centroid => "ST_AsGeoJSON(ST_Centroid(geom))::JSON AS geometry",
+            GeometryFormat::PointOnSurface => "ST_AsGeoJSON(ST_PointOnSurface(geom))::JSON AS geometry"
+        }
+    }
+}
+
+///
+/// The key a feature query is sorted by
+///
+/// `created` is the time of the delta that created the feature and `modified`
+/// the time of the delta that wrote its current version
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum OrderKey {
+    Id,
+    Created,
+    Modified
+}
+
+///
+/// A deterministic ordering of a feature query along with the keyset cursor
+/// to resume from, ties are broken by feature id
+///
+/// The cursor is the id of the last feature of the previous page, only
+/// features sorted after it are returned
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Order {
+    pub key: OrderKey,
+    pub desc: bool,
+    pub after: Option<i64>
+}
+
+impl Order {
+    ///
+    /// Parse an order of the form `<key>` or `<key>:<asc|desc>`, features are
+    /// ordered by id when only a cursor is given
+    ///
+    pub fn parse(order: &Option<String>, after: &Option<i64>) -> Result<Option<Self>, HecateError> {
+        let order = match order {
+            None => match after {
+                None => { return Ok(None); },
+                Some(_) => "id"
+            },
+            Some(order) => order.as_str()
+        };
+
+        let mut parts = order.splitn(2, ':');
+
+        let key = match parts.next() {
+            Some("id") => OrderKey::Id,
+            Some("created") => OrderKey::Created,
+            Some("modified") => OrderKey::Modified,
+            _ => { return Err(HecateError::new(400, String::from("Invalid Order - must be one of 'id', 'created' or 'modified'"), None)); }
+        };
+
+        let desc = match parts.next() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(_) => { return Err(HecateError::new(400, String::from("Invalid Order - direction must be one of 'asc' or 'desc'"), None)); }
+        };
+
+        Ok(Some(Order {
+            key: key,
+            desc: desc,
+            after: *after
+        }))
+    }
+
+    fn key_sql(&self) -> &'static str {
+        match self.key {
+            OrderKey::Id => "geo.id",
+            OrderKey::Created => "COALESCE((SELECT deltas.created FROM deltas WHERE deltas.id = geo.deltas[1]), 'epoch'::TIMESTAMP)",
+            OrderKey::Modified => "COALESCE((SELECT deltas.created FROM deltas WHERE deltas.id = geo.deltas[array_length(geo.deltas, 1)]), 'epoch'::TIMESTAMP)"
+        }
+    }
+
+    ///
+    /// The condition limiting a query to the features after the cursor, `true` if there is none
+    ///
+    pub fn after_sql(&self) -> String {
+        let cmp = if self.desc { "<" } else { ">" };
+
+        match (self.after, self.key) {
+            (None, _) => String::from("true"),
+            (Some(after), OrderKey::Id) => format!("geo.id {} {}", cmp, after),
+            (Some(after), _) => format!(
+                "({key}, geo.id) {cmp} ((SELECT {key} FROM geo WHERE geo.id = {after}), {after})",
+                key = self.key_sql(),
+                cmp = cmp,
+                after = after
+            )
+        }
+    }
+
+    pub fn to_sql(&self) -> String {
+        let dir = if self.desc { "DESC" } else { "ASC" };
+
+        match self.key {
+            OrderKey::Id => format!("ORDER BY geo.id {}", dir),
+            _ => format!("ORDER BY {key} {dir}, geo.id {dir}", key = self.key_sql(), dir = dir)
+        }
+    }
+}
+
+///
+/// The WHERE condition & ORDER BY clause of an optionally ordered feature stream
+///
+fn order_sql(order: &Option<Order>) -> (String, String) {
+    match order {
+        None => (String::from("true"), String::new()),
+        Some(order) => (order.after_sql(), order.to_sql())
+    }
+}
 
 #[derive(PartialEq, Debug)]
 pub enum Action {
@@ -25,6 +237,88 @@ pub fn import_error(feat: &geojson::Feature, error: &str) -> HecateError {
     }), String::from("Import Error"), None)
 }
 
+///
+/// Ensure a user creating `creates` new features will not exceed the create
+/// quota, counting the features created in their deltas within the window
+///
+pub fn check_quota(conn: &impl postgres::GenericConnection, uid: &i64, quota: &options::Quota, creates: i64) -> Result<bool, HecateError> {
+    let limit = match quota.create {
+        None => { return Ok(true); },
+        Some(limit) => limit
+    };
+
+    if creates == 0 {
+        return Ok(true);
+    }
+
+    let window = quota.window() as i32;
+
+    let used: i64 = match conn.query("
+        SELECT
+            count(*)
+        FROM
+            deltas,
+            JSON_Array_Elements((deltas.features -> 'features')::JSON) AS feat
+        WHERE
+            deltas.uid = $1
+            AND (deltas.finalized = true OR deltas.batch_until IS NOT NULL)
+            AND deltas.created > NOW()::TIMESTAMP - ($2::INTEGER * INTERVAL '1 second')
+            AND feat->>'action' = 'create'
+    ", &[&uid, &window]) {
+        Ok(res) => res.get(0).get(0),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    if used + creates > limit {
+        return Err(HecateError::from_json(429, json!({
+            "code": 429,
+            "status": "Too Many Requests",
+            "error": "QUOTA_EXCEEDED",
+            "reason": format!("Quota Exceeded - users can create {} features every {} seconds", limit, window),
+            "quota": limit,
+            "used": used
+        }), String::from("Quota Exceeded"), None));
+    }
+
+    Ok(true)
+}
+
+///
+/// Ensure the feature exists & is visible to the tenant, locking it until the
+/// transaction ends so that nothing is attached to a feature being deleted
+///
+pub fn exists(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<(), HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            id
+        FROM
+            geo
+        WHERE
+            id = $1
+            AND {scope}
+        FOR SHARE
+    ", scope = tenant.to_sql("tenant")), &[&id]) {
+        Ok(rows) => {
+            if rows.len() == 0 {
+                Err(HecateError::new(404, String::from("Feature Not Found"), None))
+            } else {
+                Ok(())
+            }
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Return the number of features in a collection that are being created
+///
+pub fn creates(fc: &geojson::FeatureCollection) -> i64 {
+    fc.features.iter().filter(|feat| match get_action(feat) {
+        Ok(Action::Create) => true,
+        _ => false
+    }).count() as i64
+}
+
 ///
 /// Check if the feature has the force: true flag set and if so
 /// validate that it meets the force:true acceptions
@@ -156,6 +450,226 @@ pub fn get_geom_str(feat: &geojson::Feature) -> Result<String, HecateError> {
     }
 }
 
+///
+/// Ensure the geometry of a feature is within the given bound, used to
+/// restrict users with a `user@bound:<name>` scope to their bound
+///
+pub fn within_bound(trans: &postgres::transaction::Transaction, feat: &geojson::Feature, bound: &Option<String>) -> Result<bool, HecateError> {
+    let bound = match bound {
+        None => { return Ok(true); },
+        Some(bound) => bound
+    };
+
+    let geom_str = get_geom_str(&feat)?;
+
+    match trans.query("
+        SELECT
+            ST_Within(ST_SetSRID(ST_GeomFromGeoJSON($1), 4326), bounds.geom)
+        FROM
+            bounds
+        WHERE
+            bounds.name = $2
+    ", &[&geom_str, &bound]) {
+        Ok(res) => {
+            let within: Option<bool> = match res.len() {
+                0 => None,
+                _ => res.get(0).get(0)
+            };
+
+            match within {
+                Some(true) => Ok(true),
+                _ => Err(outside_bound(&feat, &bound))
+            }
+        },
+        Err(err) => match err.as_db() {
+            Some(e) => Err(import_error(&feat, e.message.as_str())),
+            _ => Err(import_error(&feat, "Generic Error"))
+        }
+    }
+}
+
+///
+/// Ensure the stored geometry of a feature being modified, deleted or restored is
+/// within the given bound, so that a user restricted to a bound cannot edit or pull
+/// in features from outside of it
+///
+/// A deleted feature is checked against the last geometry in its history. A feature
+/// that does not exist is left for the action itself to reject
+///
+pub fn stored_within_bound(trans: &postgres::transaction::Transaction, feat: &geojson::Feature, action: &Action, bound: &Option<String>) -> Result<bool, HecateError> {
+    let bound = match bound {
+        None => { return Ok(true); },
+        Some(bound) => bound
+    };
+
+    let id = get_id(&feat)?;
+
+    let stored = match action {
+        Action::Create => { return Ok(true); },
+        Action::Restore => "
+            SELECT
+                ST_SetSRID(ST_GeomFromGeoJSON(feat->>'geometry'), 4326) AS geom
+            FROM (
+                SELECT
+                    deltas.id,
+                    JSON_Array_Elements((deltas.features -> 'features')::JSON) AS feat
+                FROM
+                    deltas
+                WHERE
+                    affected @> ARRAY[$1]::BIGINT[]
+            ) f
+            WHERE
+                (feat->>'id')::BIGINT = $1
+                AND json_typeof(feat->'geometry') = 'object'
+            ORDER BY id DESC
+            LIMIT 1
+        ",
+        Action::Modify | Action::Delete => "
+            SELECT
+                geom
+            FROM
+                geo
+            WHERE
+                id = $1
+        "
+    };
+
+    match trans.query(&*format!("
+        SELECT
+            (SELECT ST_Within(stored.geom, bounds.geom) FROM bounds WHERE bounds.name = $2)
+        FROM ({stored}) stored
+    ", stored = stored), &[&id, &bound]) {
+        Ok(res) => match res.iter().next() {
+            None => Ok(true),
+            Some(row) => match row.get::<_, Option<bool>>(0) {
+                Some(true) => Ok(true),
+                _ => Err(outside_bound(&feat, &bound))
+            }
+        },
+        Err(err) => match err.as_db() {
+            Some(e) => Err(import_error(&feat, e.message.as_str())),
+            _ => Err(import_error(&feat, "Generic Error"))
+        }
+    }
+}
+
+fn outside_bound(feat: &geojson::Feature, bound: &str) -> HecateError {
+    HecateError::from_json(403, json!({
+        "id": &feat.id,
+        "message": format!("Feature must be within bound '{}'", bound),
+        "feature": &feat
+    }), String::from("Feature Outside Bound"), None)
+}
+
+///
+/// Snap a GeoJSON geometry string to the configured storage grid, returning
+/// the geometry unchanged if no grid is configured
+///
+pub fn snap_geom(trans: &postgres::transaction::Transaction, feat: &geojson::Feature, geom_str: String, geometry: &options::Geometry) -> Result<String, HecateError> {
+    let grid = match geometry.grid {
+        None => { return Ok(geom_str); },
+        Some(grid) => grid
+    };
+
+    match trans.query("
+        SELECT
+            CASE
+                WHEN ST_IsEmpty(snapped.geom) THEN NULL
+                ELSE ST_AsGeoJSON(snapped.geom, 15)
+            END
+        FROM (
+            SELECT ST_SnapToGrid(ST_SetSRID(ST_GeomFromGeoJSON($1), 4326), $2) AS geom
+        ) snapped
+    ", &[&geom_str, &grid]) {
+        Ok(res) => match res.get(0).get(0) {
+            Some(geom) => Ok(geom),
+            None => Err(import_error(&feat, "Geometry collapsed when snapped to the storage grid"))
+        },
+        Err(err) => match err.as_db() {
+            Some(e) => Err(import_error(&feat, e.message.as_str())),
+            _ => Err(import_error(&feat, "Generic Error"))
+        }
+    }
+}
+
+///
+/// Promote a single part GeoJSON geometry string to its multi part type, returning
+/// the geometry unchanged if promotion is not enabled or it is already multi part
+///
+pub fn multi_geom(trans: &postgres::transaction::Transaction, feat: &geojson::Feature, geom_str: String, geometry: &options::Geometry) -> Result<String, HecateError> {
+    if geometry.multi != Some(true) {
+        return Ok(geom_str);
+    }
+
+    match trans.query("
+        SELECT ST_AsGeoJSON(ST_Multi(ST_SetSRID(ST_GeomFromGeoJSON($1), 4326)), 15)
+    ", &[&geom_str]) {
+        Ok(res) => Ok(res.get(0).get(0)),
+        Err(err) => match err.as_db() {
+            Some(e) => Err(import_error(&feat, e.message.as_str())),
+            _ => Err(import_error(&feat, "Generic Error"))
+        }
+    }
+}
+
+///
+/// Ensure an input SRID is a spatial reference system known to PostGIS
+///
+pub fn check_srid(conn: &impl postgres::GenericConnection, srid: &i32) -> Result<(), HecateError> {
+    if *srid == SRID {
+        return Ok(());
+    }
+
+    match conn.query("
+        SELECT 1 FROM spatial_ref_sys WHERE srid = $1
+    ", &[&srid]) {
+        Ok(rows) => {
+            if rows.len() == 0 {
+                Err(HecateError::new(400, format!("Invalid SRID - {} is not a known spatial reference system", srid), None))
+            } else {
+                Ok(())
+            }
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Reproject the geometry of a feature from the given SRID to the storage SRID
+/// so that it is validated, stored & recorded in history in storage coordinates
+///
+pub fn reproject(conn: &impl postgres::GenericConnection, feat: &mut geojson::Feature, srid: &i32) -> Result<(), HecateError> {
+    if *srid == SRID {
+        return Ok(());
+    }
+
+    let geom_str = match feat.geometry {
+        None => { return Ok(()); },
+        Some(ref geom) => match serde_json::to_string(geom) {
+            Ok(geom) => geom,
+            Err(_) => { return Err(import_error(&feat, "Failed to stringify geometry")); }
+        }
+    };
+
+    let geom: String = match conn.query("
+        SELECT ST_AsGeoJSON(ST_Transform(ST_SetSRID(ST_GeomFromGeoJSON($1), $2), $3), 15)
+    ", &[&geom_str, &srid, &SRID]) {
+        Ok(res) => res.get(0).get(0),
+        Err(err) => match err.as_db() {
+            Some(e) => { return Err(import_error(&feat, e.message.as_str())); },
+            _ => { return Err(import_error(&feat, "Generic Error")); }
+        }
+    };
+
+    match geom.parse::<geojson::GeoJson>() {
+        Ok(geojson::GeoJson::Geometry(geom)) => {
+            feat.geometry = Some(geom);
+            Ok(())
+        },
+        _ => Err(import_error(&feat, "Failed to reproject geometry"))
+    }
+}
+
 pub fn get_id(feat: &geojson::Feature) -> Result<i64, HecateError> {
     match feat.id {
         None => { return Err(import_error(&feat, "ID Required")); },
@@ -172,6 +686,13 @@ pub fn get_id(feat: &geojson::Feature) -> Result<i64, HecateError> {
     }
 }
 
+///
+/// The ids of a list of features, features without an integer id are skipped
+///
+pub fn ids(feats: &Vec<geojson::Feature>) -> Vec<i64> {
+    feats.iter().filter_map(|feat| get_id(feat).ok()).collect()
+}
+
 pub fn get_action(feat: &geojson::Feature) -> Result<Action, HecateError> {
     match feat.foreign_members {
         None => { return Err(import_error(&feat, "Action Required")); },
@@ -191,6 +712,36 @@ pub fn get_action(feat: &geojson::Feature) -> Result<Action, HecateError> {
     }
 }
 
+///
+/// Apply the server's property policy to a feature that is being created,
+/// modified, or restored, removing or rejecting any key that is not allowed
+///
+pub fn clean_props(feat: &mut geojson::Feature, policy: &options::Properties) -> Result<bool, HecateError> {
+    if get_action(&feat)? == Action::Delete {
+        return Ok(true);
+    }
+
+    let denied: Vec<String> = match feat.properties {
+        None => { return Ok(true); },
+        Some(ref props) => props.keys().filter(|key| !policy.is_allowed(key)).map(|key| key.clone()).collect()
+    };
+
+    if denied.len() == 0 {
+        return Ok(true);
+    } else if !policy.is_strip() {
+        return Err(import_error(&feat, &*format!("Property '{}' is not allowed", denied[0])));
+    }
+
+    match feat.properties {
+        None => (),
+        Some(ref mut props) => for key in denied {
+            props.remove(&key);
+        }
+    };
+
+    Ok(true)
+}
+
 pub fn get_key(feat: &geojson::Feature) -> Result<Option<String>, HecateError> {
     match feat.foreign_members {
         None => Ok(None),
@@ -210,9 +761,22 @@ pub fn get_key(feat: &geojson::Feature) -> Result<Option<String>, HecateError> {
     }
 }
 
-pub fn action(trans: &postgres::transaction::Transaction, schema_json: &Option<serde_json::value::Value>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
+pub fn action(trans: &postgres::transaction::Transaction, schema_json: &Option<serde_json::value::Value>, geometry: &options::Geometry, bound: &Option<String>, feat: &geojson::Feature, delta: &Option<i64>, tenant: &Scope) -> Result<Response, HecateError> {
     let action = get_action(&feat)?;
 
+    match action {
+        Action::Delete => (),
+        _ => { within_bound(&trans, &feat, &bound)?; }
+    };
+
+    match action {
+        Action::Create => (),
+        _ => {
+            within_tenant(&trans, &feat, &action, &tenant)?;
+            stored_within_bound(&trans, &feat, &action, &bound)?;
+        }
+    };
+
     let mut scope = valico::json_schema::Scope::new();
     let schema = match schema_json {
         &Some(ref schema) => {
@@ -225,16 +789,55 @@ pub fn action(trans: &postgres::transaction::Transaction, schema_json: &Option<s
     };
 
     let res = match action {
-        Action::Create => create(&trans, &schema, &feat, &delta)?,
-        Action::Modify => modify(&trans, &schema, &feat, &delta)?,
-        Action::Restore => restore(&trans, &schema, &feat, &delta)?,
+        Action::Create => create(&trans, &schema, &geometry, &feat, &delta, &tenant)?,
+        Action::Modify => modify(&trans, &schema, &geometry, &feat, &delta)?,
+        Action::Restore => restore(&trans, &schema, &geometry, &feat, &delta)?,
         Action::Delete => delete(&trans, &feat)?
     };
 
     Ok(res)
 }
 
-pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
+///
+/// Ensure a feature being modified, deleted or restored belongs to the tenant of
+/// the request
+///
+/// A feature of another tenant is reported as not found so that its existence
+/// is not revealed. A deleted feature belongs to the tenant of the delta that
+/// created it
+///
+pub fn within_tenant(trans: &postgres::transaction::Transaction, feat: &geojson::Feature, action: &Action, tenant: &Scope) -> Result<bool, HecateError> {
+    let id = get_id(&feat)?;
+
+    let rows = match action {
+        Action::Restore => trans.query(&*format!("
+            SELECT
+                {scope}
+            FROM deltas
+            WHERE
+                affected @> ARRAY[$1]::BIGINT[]
+            ORDER BY id ASC
+            LIMIT 1
+        ", scope = tenant.to_sql("tenant")), &[&id]),
+        _ => trans.query(&*format!("
+            SELECT
+                {scope}
+            FROM geo
+            WHERE
+                id = $1
+        ", scope = tenant.to_sql("tenant")), &[&id])
+    };
+
+    match rows {
+        Ok(rows) => match rows.iter().next() {
+            Some(row) if !row.get::<_, bool>(0) => Err(import_error(&feat, "Feature Not Found")),
+            _ => Ok(true)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, geometry: &options::Geometry, feat: &geojson::Feature, delta: &Option<i64>, tenant: &Scope) -> Result<Response, HecateError> {
     if get_version(&feat).is_ok() {
         return Err(import_error(&feat, "Cannot have Version"));
     }
@@ -253,7 +856,8 @@ pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico
 
     if !valid { return Err(import_error(&feat, "Failed to Match Schema")) };
 
-    let geom_str = get_geom_str(&feat)?;
+    let geom_str = snap_geom(&trans, &feat, get_geom_str(&feat)?, &geometry)?;
+    let geom_str = multi_geom(&trans, &feat, geom_str, &geometry)?;
 
     let props_str = match serde_json::to_string(&props) {
         Ok(props) => props,
@@ -267,15 +871,19 @@ pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico
         Ok(id) => Some(id)
     };
 
+    let stamp = tenant.stamp();
+
     if is_force(&feat)? == true {
-        match trans.query("
-            INSERT INTO geo (version, geom, props, deltas, key)
+        //A key held by a feature of another tenant is treated as a duplicate rather than overwritten
+        match trans.query(&*format!("
+            INSERT INTO geo (version, geom, props, deltas, key, tenant)
                 VALUES (
                     1,
                     ST_SetSRID(ST_GeomFromGeoJSON($1), 4326),
                     $2::TEXT::JSON,
                     array[COALESCE($3, currval('deltas_id_seq')::BIGINT)],
-                    $4
+                    $4,
+                    $5
                 )
                 ON CONFLICT (key) DO UPDATE
                     SET
@@ -283,8 +891,11 @@ pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico
                         geom = ST_SetSRID(ST_GeomFromGeoJSON($1), 4326),
                         props = $2::TEXT::JSON,
                         deltas = array_append(geo.deltas, COALESCE($3, currval('deltas_id_seq')::BIGINT))
+                    WHERE
+                        {scope}
                 RETURNING id;
-        ", &[&geom_str, &props_str, &delta, &key]) {
+        ", scope = tenant.to_sql("geo.tenant")), &[&geom_str, &props_str, &delta, &key, &stamp]) {
+            Ok(ref res) if res.len() == 0 => Err(import_error(&feat, "Duplicate Key Value")),
             Ok(res) => Ok(Response {
                 old: id,
                 new: Some(res.get(0).get(0)),
@@ -301,15 +912,16 @@ pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico
         }
     } else {
         match trans.query("
-            INSERT INTO geo (version, geom, props, deltas, key)
+            INSERT INTO geo (version, geom, props, deltas, key, tenant)
                 VALUES (
                     1,
                     ST_SetSRID(ST_GeomFromGeoJSON($1), 4326),
                     $2::TEXT::JSON,
                     array[COALESCE($3, currval('deltas_id_seq')::BIGINT)],
-                    $4
+                    $4,
+                    $5
                 ) RETURNING id;
-        ", &[&geom_str, &props_str, &delta, &key]) {
+        ", &[&geom_str, &props_str, &delta, &key, &stamp]) {
             Ok(res) => Ok(Response {
                 old: id,
                 new: Some(res.get(0).get(0)),
@@ -331,7 +943,7 @@ pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico
     }
 }
 
-pub fn modify(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
+pub fn modify(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, geometry: &options::Geometry, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
     let props = match feat.properties {
         None => { return Err(import_error(&feat, "Properties Required")); },
         Some(ref props) => props
@@ -350,7 +962,8 @@ pub fn modify(trans: &postgres::transaction::Transaction, schema: &Option<valico
     let version = get_version(&feat)?;
     let key = get_key(&feat)?;
 
-    let geom_str = get_geom_str(&feat)?;
+    let geom_str = snap_geom(&trans, &feat, get_geom_str(&feat)?, &geometry)?;
+    let geom_str = multi_geom(&trans, &feat, geom_str, &geometry)?;
 
     let props_str = match serde_json::to_string(&props) {
         Ok(props) => props,
@@ -405,8 +1018,8 @@ pub fn delete(trans: &postgres::transaction::Transaction, feat: &geojson::Featur
     }
 }
 
-pub fn query_by_key(conn: &impl postgres::GenericConnection, key: &String) -> Result<serde_json::value::Value, HecateError> {
-    match conn.query("
+pub fn query_by_key(conn: &impl postgres::GenericConnection, key: &String, tenant: &Scope) -> Result<serde_json::value::Value, HecateError> {
+    match conn.query(&*format!("
         SELECT
             row_to_json(f)::JSON AS feature
         FROM (
@@ -418,9 +1031,11 @@ pub fn query_by_key(conn: &impl postgres::GenericConnection, key: &String) -> Re
                 ST_AsGeoJSON(geom)::JSON AS geometry,
                 props AS properties
             FROM geo
-            WHERE key = $1
+            WHERE
+                key = $1
+                AND {scope}
         ) f;
-    ", &[&key]) {
+    ", scope = tenant.to_sql("tenant")), &[&key]) {
         Ok(res) => {
             if res.len() != 1 { return Err(HecateError::new(404, String::from("Feature not found"), None)); }
 
@@ -431,10 +1046,10 @@ pub fn query_by_key(conn: &impl postgres::GenericConnection, key: &String) -> Re
     }
 }
 
-pub fn query_by_point(conn: &impl postgres::GenericConnection, point: &String) -> Result<Vec<serde_json::value::Value>, HecateError> {
+pub fn query_by_point(conn: &impl postgres::GenericConnection, point: &String, tenant: &Scope) -> Result<Vec<serde_json::value::Value>, HecateError> {
     let (lng, lat) = validate::point(point)?;
 
-    match conn.query("
+    match conn.query(&*format!("
         SELECT
             row_to_json(f)::JSON AS feature
         FROM (
@@ -448,10 +1063,11 @@ pub fn query_by_point(conn: &impl postgres::GenericConnection, point: &String) -
             FROM geo
             WHERE
                 ST_DWithin(ST_SetSRID(ST_MakePoint($1, $2), 4326), geo.geom, 0.00005)
+                AND {scope}
             ORDER BY
                 ST_Distance(ST_SetSRID(ST_MakePoint($1, $2), 4326), geo.geom) DESC
         ) f
-    ", &[&lng, &lat]) {
+    ", scope = tenant.to_sql("geo.tenant")), &[&lng, &lat]) {
         Ok(results) => {
             if results.len() == 0 {
                 return Err(HecateError::new(404, String::from("Feature not found"), None));
@@ -470,8 +1086,25 @@ pub fn query_by_point(conn: &impl postgres::GenericConnection, point: &String) -
     }
 }
 
-pub fn get(conn: &impl postgres::GenericConnection, id: &i64) -> Result<geojson::Feature, HecateError> {
-    match conn.query("
+///
+/// Parse a feature that was assembled as JSON by the server
+///
+pub fn from_value(feat: serde_json::Value) -> Result<geojson::Feature, HecateError> {
+    match feat.to_string().parse::<geojson::GeoJson>() {
+        Ok(geojson::GeoJson::Feature(feat)) => Ok(feat),
+        _ => Err(HecateError::new(500, String::from("Failed to build feature"), None))
+    }
+}
+
+pub fn get(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<geojson::Feature, HecateError> {
+    get_format(conn, id, &GeometryFormat::GeoJSON, tenant)
+}
+
+///
+/// Get a single feature, encoding its geometry in the given format
+///
+pub fn get_format(conn: &impl postgres::GenericConnection, id: &i64, format: &GeometryFormat, tenant: &Scope) -> Result<geojson::Feature, HecateError> {
+    match conn.query(&*format!("
         SELECT
             row_to_json(f)::TEXT AS feature
         FROM (
@@ -480,14 +1113,26 @@ pub fn get(conn: &impl postgres::GenericConnection, id: &i64) -> Result<geojson:
                 key AS key,
                 'Feature' AS type,
                 version AS version,
-                ST_AsGeoJSON(geom)::JSON AS geometry,
+                {geometry},
                 props AS properties
             FROM geo
-            WHERE id = $1
+            WHERE
+                id = $1
+                AND {scope}
         ) f;
-    ", &[&id]) {
+    ", geometry = format.to_sql(), scope = tenant.to_sql("tenant")), &[&id]) {
         Ok(res) => {
-            if res.len() != 1 { return Err(HecateError::new(404, String::from("Not Found"), None)); }
+            if res.len() != 1 {
+                return match deleted(conn, id, tenant)? {
+                    Some(delta) => Err(HecateError::from_json(410, json!({
+                        "code": 410,
+                        "status": "Gone",
+                        "reason": "Feature Deleted",
+                        "delta": delta
+                    }), String::from("Feature Deleted"), None).with_header("X-Hecate-Delta", delta.to_string())),
+                    None => Err(HecateError::new(404, String::from("Not Found"), None))
+                };
+            }
 
             let feat: postgres::rows::Row = res.get(0);
             let feat: String = feat.get(0);
@@ -505,7 +1150,142 @@ pub fn get(conn: &impl postgres::GenericConnection, id: &i64) -> Result<geojson:
     }
 }
 
-pub fn restore(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
+///
+/// Return the id of the delta that deleted a feature if the last change to the
+/// feature in history was a delete
+///
+/// A feature whose history has been purged is indistinguishable from one that
+/// never existed
+///
+pub fn deleted(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<Option<i64>, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            deltas.id,
+            feat->>'action'
+        FROM
+            deltas,
+            JSONB_Array_Elements(deltas.features->'features') AS feat
+        WHERE
+            deltas.affected @> ARRAY[$1]::BIGINT[]
+            AND (feat->>'id')::BIGINT = $1
+            AND {scope}
+        ORDER BY deltas.id DESC
+        LIMIT 1
+    ", scope = tenant.to_sql("deltas.tenant")), &[&id]) {
+        Ok(rows) => match rows.iter().next() {
+            Some(row) => {
+                let action: Option<String> = row.get(1);
+
+                if action == Some(String::from("delete")) {
+                    Ok(Some(row.get(0)))
+                } else {
+                    Ok(None)
+                }
+            },
+            None => Ok(None)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Permanently remove the history of features that were deleted before the retention window
+///
+/// Deleted features are removed from the geo table immediately but remain restorable
+/// through their history in the deltas table. Once every delta touching a deleted feature
+/// is older than the retention window the feature is stripped from those deltas, and any
+/// delta left without features is removed entirely. Running a purge again is a noop
+/// until further features age out of the window.
+///
+pub fn purge(conn: &impl postgres::GenericConnection, retention: &u32) -> Result<serde_json::Value, HecateError> {
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    let ids: Vec<i64> = match trans.query("
+        SELECT
+            affected.id
+        FROM
+            deltas,
+            UNNEST(deltas.affected) AS affected(id)
+        WHERE
+            NOT EXISTS (
+                SELECT 1 FROM geo WHERE geo.id = affected.id
+            )
+        GROUP BY affected.id
+        HAVING
+            BOOL_AND(deltas.finalized)
+            AND MAX(deltas.created) < NOW() - ($1::INTEGER * INTERVAL '1 day')
+        ORDER BY affected.id
+    ", &[&(*retention as i32)]) {
+        Ok(rows) => rows.iter().map(|row| row.get(0)).collect(),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    //Deltas which no longer reference any feature are orphaned and removed entirely
+    let (modified, orphaned): (Vec<i64>, Vec<i64>) = match trans.query("
+        UPDATE deltas
+            SET
+                affected = ARRAY(
+                    SELECT a.id
+                    FROM UNNEST(deltas.affected) WITH ORDINALITY AS a(id, n)
+                    WHERE a.id <> ALL($1::BIGINT[])
+                    ORDER BY a.n
+                ),
+                features = JSONB_SET(deltas.features, '{features}', (
+                    SELECT COALESCE(JSONB_AGG(f.feat ORDER BY f.n), '[]'::JSONB)
+                    FROM JSONB_ARRAY_ELEMENTS(deltas.features -> 'features') WITH ORDINALITY AS f(feat, n)
+                    WHERE COALESCE((f.feat->>'id')::BIGINT <> ALL($1::BIGINT[]), true)
+                ))
+            WHERE
+                deltas.affected && $1::BIGINT[]
+            RETURNING
+                id,
+                CARDINALITY(affected) = 0
+    ", &[&ids]) {
+        Ok(rows) => {
+            let mut modified = Vec::new();
+            let mut orphaned = Vec::new();
+
+            for row in rows.iter() {
+                let id: i64 = row.get(0);
+                let is_orphaned: bool = row.get(1);
+
+                if is_orphaned {
+                    orphaned.push(id);
+                } else {
+                    modified.push(id);
+                }
+            }
+
+            (modified, orphaned)
+        },
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.execute("
+        DELETE FROM deltas
+            WHERE id = ANY($1::BIGINT[])
+    ", &[&orphaned]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.commit() {
+        Ok(_) => Ok(json!({
+            "retention": retention,
+            "features": ids,
+            "deltas": {
+                "modified": modified,
+                "removed": orphaned
+            }
+        })),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+pub fn restore(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, geometry: &options::Geometry, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
     let props = match feat.properties {
         None => { return Err(import_error(&feat, "Properties Required")); },
         Some(ref props) => props
@@ -524,7 +1304,8 @@ pub fn restore(trans: &postgres::transaction::Transaction, schema: &Option<valic
     let version = get_version(&feat)?;
     let key = get_key(&feat)?;
 
-    let geom_str = get_geom_str(&feat)?;
+    let geom_str = snap_geom(&trans, &feat, get_geom_str(&feat)?, &geometry)?;
+    let geom_str = multi_geom(&trans, &feat, geom_str, &geometry)?;
 
     let props_str = match serde_json::to_string(&props) {
         Ok(props) => props,
@@ -574,14 +1355,15 @@ pub fn restore(trans: &postgres::transaction::Transaction, schema: &Option<valic
 
             //Create Delta History Array
             match trans.query("
-                INSERT INTO geo (id, version, geom, props, deltas, key)
+                INSERT INTO geo (id, version, geom, props, deltas, key, tenant)
                     VALUES (
                         $1::BIGINT,
                         $2::BIGINT + 1,
                         ST_SetSRID(ST_GeomFromGeoJSON($3), 4326),
                         $4::TEXT::JSON,
                         array_append($5::BIGINT[], COALESCE($6, currval('deltas_id_seq')::BIGINT)),
-                        $7
+                        $7,
+                        (SELECT tenant FROM deltas WHERE id = ($5::BIGINT[])[1])
                     );
             ", &[&id, &prev_version, &geom_str, &props_str, &affected, &delta, &key]) {
                 Ok(_) => Ok(Response {
@@ -611,10 +1393,62 @@ pub fn restore(trans: &postgres::transaction::Transaction, schema: &Option<valic
     }
 }
 
-pub fn get_point_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, point: &String) -> Result<PGStream, HecateError> {
+///
+/// The LIMIT clause of a feature stream, no clause if unlimited
+///
+fn limit_sql(limit: &Option<i64>) -> String {
+    match limit {
+        Some(limit) => format!("LIMIT {}", limit),
+        None => String::new()
+    }
+}
+
+///
+/// The select list fragment that returns the properties of a feature, an empty
+/// object when only the geometry is requested so that properties are never read
+///
+fn properties_sql(column: &str, geometry_only: bool) -> String {
+    if geometry_only {
+        String::from("'{}'::JSON AS properties")
+    } else {
+        format!("{} AS properties", column)
+    }
+}
+
+///
+/// The where clause fragment matching features at the point given by `$1, $2`,
+/// or within `$3` metres of it when a buffer is given
+///
+/// The buffer is applied to the point as a geography so that the distance
+/// is measured in metres regardless of latitude
+///
+fn point_sql(buffer: &Option<f64>) -> &'static str {
+    match buffer {
+        None => "ST_DWithin(ST_SetSRID(ST_MakePoint($1, $2), 4326), geo.geom, 0.00005)",
+        Some(_) => "ST_Intersects(geo.geom, ST_Buffer(ST_SetSRID(ST_MakePoint($1, $2), 4326)::GEOGRAPHY, $3)::GEOMETRY)"
+    }
+}
+
+///
+/// The params of the clause returned by `point_sql`
+///
+fn point_params(lng: &f64, lat: &f64, buffer: &Option<f64>) -> Vec<f64> {
+    let mut params = vec![*lng, *lat];
+
+    if let Some(buffer) = buffer {
+        params.push(*buffer);
+    }
+
+    params
+}
+
+pub fn get_point_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, point: &String, buffer: &Option<f64>, format: &GeometryFormat, geometry_only: bool, limit: &Option<i64>, tenant: &Scope) -> Result<PGStream, HecateError> {
     let (lng, lat) = validate::point(point)?;
+    let params = point_params(&lng, &lat, buffer);
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
 
-    Ok(PGStream::new(conn, String::from("next_features"), String::from(r#"
+    Ok(PGStream::new(conn, String::from("next_features"), format!(r#"
         DECLARE next_features CURSOR FOR
             SELECT
                 row_to_json(f)::TEXT AS feature
@@ -624,21 +1458,27 @@ pub fn get_point_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConn
                     key AS key,
                     'Feature' AS type,
                     version AS version,
-                    ST_AsGeoJSON(geom)::JSON AS geometry,
-                    props AS properties
+                    {geometry},
+                    {properties}
                 FROM geo
                 WHERE
-                    ST_DWithin(ST_SetSRID(ST_MakePoint($1, $2), 4326), geo.geom, 0.00005)
+                    {clause}
+                    AND {scope}
                 ORDER BY
                     ST_Distance(ST_SetSRID(ST_MakePoint($1, $2), 4326), geo.geom) DESC
+                {limit}
             ) f;
-    "#), &[&lng, &lat])?)
+    "#, clause = point_sql(buffer), geometry = format.to_sql(), properties = properties_sql("props", geometry_only), limit = limit_sql(limit), scope = tenant.to_sql("geo.tenant")), &params)?)
 }
 
-pub fn get_bbox_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, bbox: &Vec<f64>) -> Result<PGStream, HecateError> {
-    validate::bbox(bbox)?;
+pub fn get_bbox_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, bbox: &Bbox, buffer: &Option<f64>, format: &GeometryFormat, geometry_only: bool, order: &Option<Order>, limit: &Option<i64>, tenant: &Scope) -> Result<PGStream, HecateError> {
+    let mut params: Vec<f64> = Vec::new();
+    let clause = bbox.to_buffered_sql("geom", buffer, &mut params);
+    let (after, order) = order_sql(order);
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
 
-    Ok(PGStream::new(conn, String::from("next_features"), String::from(r#"
+    Ok(PGStream::new(conn, String::from("next_features"), format!(r#"
         DECLARE next_features CURSOR FOR
             SELECT
                 row_to_json(f)::TEXT AS feature
@@ -648,20 +1488,168 @@ pub fn get_bbox_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConne
                     key AS key,
                     'Feature' AS type,
                     version AS version,
-                    ST_AsGeoJSON(geom)::JSON AS geometry,
-                    props AS properties
+                    {geometry},
+                    {properties}
                 FROM geo
                 WHERE
-                    ST_Intersects(geom, ST_MakeEnvelope($1, $2, $3, $4, 4326))
-                    OR ST_Within(geom, ST_MakeEnvelope($1, $2, $3, $4, 4326))
+                    {clause}
+                    AND {after}
+                    AND {scope}
+                {order}
+                {limit}
             ) f;
-    "#), &[&bbox[0], &bbox[1], &bbox[2], &bbox[3]])?)
+    "#, clause = clause, after = after, order = order, geometry = format.to_sql(), properties = properties_sql("props", geometry_only), limit = limit_sql(limit), scope = tenant.to_sql("tenant")), &params)?)
 }
 
-pub fn get_bbox(conn: &impl postgres::GenericConnection, bbox: Vec<f64>) -> Result<geojson::FeatureCollection, HecateError> {
-    validate::bbox(&bbox)?;
+///
+/// Stream all features matching a filter
+///
+pub fn get_filter_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, filter: &filter::Expr, format: &GeometryFormat, geometry_only: bool, order: &Option<Order>, limit: &Option<i64>, tenant: &Scope) -> Result<PGStream, HecateError> {
+    let indexed = index::indexed(&*conn)?;
 
-    match conn.query("
+    let mut params: Vec<serde_json::Value> = Vec::new();
+    let clause = filter.to_sql(&indexed, &mut params)?;
+    let (after, order) = order_sql(order);
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+    Ok(PGStream::new(conn, String::from("next_features"), format!(r#"
+        DECLARE next_features CURSOR FOR
+            SELECT
+                row_to_json(f)::TEXT AS feature
+            FROM (
+                SELECT
+                    id AS id,
+                    key AS key,
+                    'Feature' AS type,
+                    version AS version,
+                    {geometry},
+                    {properties}
+                FROM geo
+                WHERE
+                    {clause}
+                    AND {after}
+                    AND {scope}
+                {order}
+                {limit}
+            ) f;
+    "#, clause = clause, after = after, order = order, geometry = format.to_sql(), properties = properties_sql("props", geometry_only), limit = limit_sql(limit), scope = tenant.to_sql("tenant")), &params)?)
+}
+
+///
+/// Stream all features whose latest version was written at or after the given
+/// time, followed by a tombstone for each feature deleted since then
+///
+/// The time of a version is the creation time of the delta that wrote it
+///
+pub fn get_modified_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, since: &chrono::NaiveDateTime, format: &GeometryFormat, geometry_only: bool, limit: &Option<i64>, tenant: &Scope) -> Result<PGStream, HecateError> {
+    Ok(PGStream::new(conn, String::from("next_features"), format!(r#"
+        DECLARE next_features CURSOR FOR
+            SELECT
+                row_to_json(f)::TEXT AS feature
+            FROM (
+                SELECT
+                    geo.id AS id,
+                    geo.key AS key,
+                    'Feature' AS type,
+                    geo.version AS version,
+                    {geometry},
+                    {properties}
+                FROM
+                    geo,
+                    deltas
+                WHERE
+                    deltas.id = geo.deltas[array_length(geo.deltas, 1)]
+                    AND deltas.created >= $1
+                    AND {geo_scope}
+                ORDER BY geo.id
+            ) f
+            UNION ALL
+            SELECT
+                tombstone::TEXT AS feature
+            FROM (
+                SELECT DISTINCT ON ((feat->>'id')::BIGINT)
+                    JSON_Build_Object(
+                        'id', (feat->>'id')::BIGINT,
+                        'key', feat->>'key',
+                        'type', 'Feature',
+                        'action', 'delete',
+                        'version', (feat->>'version')::BIGINT,
+                        'geometry', NULL,
+                        'properties', NULL
+                    ) AS tombstone
+                FROM
+                    deltas,
+                    JSONB_Array_Elements(deltas.features->'features') AS feat
+                WHERE
+                    deltas.created >= $1
+                    AND deltas.finalized = true
+                    AND feat->>'action' = 'delete'
+                    AND {deltas_scope}
+                    AND NOT EXISTS (
+                        SELECT 1 FROM geo WHERE geo.id = (feat->>'id')::BIGINT
+                    )
+                ORDER BY
+                    (feat->>'id')::BIGINT,
+                    deltas.id DESC
+            ) t
+            {limit};
+    "#, geometry = format.to_sql(), properties = properties_sql("geo.props", geometry_only), limit = limit_sql(limit), geo_scope = tenant.to_sql("geo.tenant"), deltas_scope = tenant.to_sql("deltas.tenant")), &[since])?)
+}
+
+///
+/// Count all features matching a filter without returning them
+///
+pub fn count(conn: &impl postgres::GenericConnection, filter: &filter::Expr, tenant: &Scope) -> Result<i64, HecateError> {
+    let indexed = index::indexed(conn)?;
+
+    let mut params: Vec<serde_json::Value> = Vec::new();
+    let clause = filter.to_sql(&indexed, &mut params)?;
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+    match conn.query(&*format!("
+        SELECT
+            count(*)
+        FROM geo
+        WHERE
+            {clause}
+            AND {scope}
+    ", clause = clause, scope = tenant.to_sql("tenant")), &params) {
+        Ok(res) => Ok(res.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Count all features near a point without returning them
+///
+pub fn count_point(conn: &impl postgres::GenericConnection, point: &String, buffer: &Option<f64>, tenant: &Scope) -> Result<i64, HecateError> {
+    let (lng, lat) = validate::point(point)?;
+    let params = point_params(&lng, &lat, buffer);
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+    match conn.query(&*format!("
+        SELECT
+            count(*)
+        FROM geo
+        WHERE
+            {clause}
+            AND {scope}
+    ", clause = point_sql(buffer), scope = tenant.to_sql("geo.tenant")), &params) {
+        Ok(res) => Ok(res.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+pub fn get_bbox(conn: &impl postgres::GenericConnection, bbox: &Bbox, tenant: &Scope) -> Result<geojson::FeatureCollection, HecateError> {
+    let mut params: Vec<f64> = Vec::new();
+    let clause = bbox.to_sql("geom", &mut params);
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+    match conn.query(&*format!("
         SELECT
             row_to_json(f)::TEXT AS feature
         FROM (
@@ -674,10 +1662,10 @@ pub fn get_bbox(conn: &impl postgres::GenericConnection, bbox: Vec<f64>) -> Resu
                 props AS properties
             FROM geo
             WHERE
-                ST_Intersects(geom, ST_MakeEnvelope($1, $2, $3, $4, 4326))
-                OR ST_Within(geom, ST_MakeEnvelope($1, $2, $3, $4, 4326))
+                {clause}
+                AND {scope}
         ) f;
-    ", &[&bbox[0], &bbox[1], &bbox[2], &bbox[3]]) {
+    ", clause = clause, scope = tenant.to_sql("tenant")), &params) {
         Ok(res) => {
             let mut fc = geojson::FeatureCollection {
                 bbox: None,
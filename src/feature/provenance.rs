@@ -0,0 +1,162 @@
+use crate::err::HecateError;
+use crate::{auth, options};
+use super::{get_action, get_id, Action};
+
+///
+/// Maximum length of the source name of an API token
+///
+pub static MAX_SOURCE: usize = 64;
+
+///
+/// Validate the source name given to an API token
+///
+pub fn validate_source(source: &str) -> Result<(), HecateError> {
+    if source.trim().len() == 0 || source.chars().count() > MAX_SOURCE || source.chars().any(|c| c.is_control()) {
+        return Err(HecateError::new(400, format!("Invalid Source - must be a single line of between 1 and {} characters", MAX_SOURCE), None));
+    }
+
+    Ok(())
+}
+
+///
+/// Stamps the features of a request with the source they came from, so that
+/// the origin of each feature is known once data from several sources is merged
+///
+/// Only admins may set or change the property themselves, anyone else has it
+/// set for them on create & kept as it was on modify
+///
+pub struct Provenance {
+    key: Option<String>,
+    source: String,
+    admin: bool
+}
+
+impl Provenance {
+    ///
+    /// The provenance of a validated request, the source is the name given to the
+    /// API token the request was made with, or otherwise the username
+    ///
+    pub fn new(conn: &impl postgres::GenericConnection, auth: &auth::Auth, provenance: &options::Provenance) -> Result<Self, HecateError> {
+        let source = match (&provenance.key, &auth.source, &auth.uid) {
+            (None, _, _) | (_, _, None) => String::new(),
+            (Some(_), Some(source), _) => source.clone(),
+            (Some(_), None, Some(uid)) => match conn.query("
+                SELECT username FROM users WHERE id = $1
+            ", &[&uid]) {
+                Ok(rows) => match rows.iter().next() {
+                    Some(row) => row.get(0),
+                    None => { return Err(HecateError::new(401, String::from("You must be logged in to access this resource"), None)); }
+                },
+                Err(err) => { return Err(HecateError::from_db(err)); }
+            }
+        };
+
+        Ok(Provenance {
+            key: provenance.key.clone(),
+            source: source,
+            admin: auth.has_admin_access()
+        })
+    }
+
+    ///
+    /// Set the provenance property of a feature being created or modified
+    ///
+    pub fn stamp(&self, conn: &impl postgres::GenericConnection, feat: &mut geojson::Feature) -> Result<(), HecateError> {
+        let key = match self.key {
+            None => { return Ok(()); },
+            Some(ref key) => key
+        };
+
+        let expected = match get_action(&feat)? {
+            Action::Create => json!(self.source),
+            Action::Modify => match conn.query("
+                SELECT props->($2::TEXT) FROM geo WHERE id = $1
+            ", &[&get_id(&feat)?, &key]) {
+                Ok(rows) => match rows.iter().next() {
+                    Some(row) => row.get::<_, Option<serde_json::Value>>(0).unwrap_or(serde_json::Value::Null),
+                    None => serde_json::Value::Null
+                },
+                Err(err) => { return Err(HecateError::from_db(err)); }
+            },
+            Action::Delete | Action::Restore => { return Ok(()); }
+        };
+
+        self.set(key, feat, expected)
+    }
+
+    fn set(&self, key: &str, feat: &mut geojson::Feature, expected: serde_json::Value) -> Result<(), HecateError> {
+        let props = feat.properties.get_or_insert_with(serde_json::Map::new);
+
+        match props.get(key) {
+            Some(_) if self.admin => Ok(()),
+            Some(value) if *value != expected => {
+                Err(HecateError::new(403, format!("Only admins may set the '{}' property", key), None))
+            },
+            _ => {
+                if expected.is_null() {
+                    props.remove(key);
+                } else {
+                    props.insert(String::from(key), expected);
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature(props: serde_json::Value) -> geojson::Feature {
+        super::super::from_value(json!({
+            "type": "Feature",
+            "action": "create",
+            "properties": props,
+            "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+        })).unwrap()
+    }
+
+    fn provenance(admin: bool) -> Provenance {
+        Provenance {
+            key: Some(String::from("_source")),
+            source: String::from("ingalls"),
+            admin: admin
+        }
+    }
+
+    #[test]
+    fn validate_sources() {
+        assert!(validate_source("osm-import").is_ok());
+        assert!(validate_source("City Open Data").is_ok());
+
+        assert!(validate_source("").is_err());
+        assert!(validate_source(" ").is_err());
+        assert!(validate_source("osm\nimport").is_err());
+        assert!(validate_source(&"a".repeat(MAX_SOURCE + 1)).is_err());
+    }
+
+    #[test]
+    fn stamp_props() {
+        let mut feat = feature(json!({ "building": "yes" }));
+        provenance(false).set("_source", &mut feat, json!("ingalls")).unwrap();
+        assert_eq!(feat.properties.unwrap().get("_source"), Some(&json!("ingalls")));
+
+        //Setting the source a user would be stamped with is allowed
+        let mut feat = feature(json!({ "_source": "ingalls" }));
+        assert!(provenance(false).set("_source", &mut feat, json!("ingalls")).is_ok());
+
+        let mut feat = feature(json!({ "_source": "city" }));
+        assert!(provenance(false).set("_source", &mut feat, json!("ingalls")).is_err());
+
+        let mut feat = feature(json!({ "_source": "city" }));
+        provenance(true).set("_source", &mut feat, json!("ingalls")).unwrap();
+        assert_eq!(feat.properties.unwrap().get("_source"), Some(&json!("city")));
+
+        //A feature that was never stamped is left unstamped on modify
+        let mut feat = feature(json!({ "building": "yes" }));
+        provenance(false).set("_source", &mut feat, serde_json::Value::Null).unwrap();
+        assert_eq!(feat.properties.unwrap().get("_source"), None);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::err::HecateError;
+use crate::{filter, index};
+use crate::tenant::Scope;
+use super::exists;
+
+///
+/// Maximum number of related features returned by a single request
+///
+pub static MAX_RELATED: i64 = 1000;
+
+///
+/// Which side of a foreign key property the related features are on
+///
+/// `Incoming` features have the property set to the id of the feature while
+/// the `Outgoing` feature is the one whose id the property of the feature is set to
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Direction {
+    Incoming,
+    Outgoing
+}
+
+impl Direction {
+    pub fn parse(direction: &Option<String>) -> Result<Self, HecateError> {
+        match direction.as_ref().map(|direction| direction.as_str()) {
+            None | Some("incoming") => Ok(Direction::Incoming),
+            Some("outgoing") => Ok(Direction::Outgoing),
+            Some(_) => Err(HecateError::new(400, String::from("Invalid Direction - must be one of 'incoming' or 'outgoing'"), None))
+        }
+    }
+}
+
+///
+/// The filter matching features whose property references the given id, as
+/// either a number or a string
+///
+pub fn references(key: &str, id: &i64) -> Result<filter::Expr, HecateError> {
+    index::path(key)?;
+
+    Ok(filter::Expr::Or(
+        Box::new(filter::Expr::Cmp(String::from(key), filter::Op::Eq, json!(id))),
+        Box::new(filter::Expr::Cmp(String::from(key), filter::Op::Eq, json!(id.to_string())))
+    ))
+}
+
+///
+/// Parse the id referenced by the value of a foreign key property
+///
+pub fn referenced(value: &serde_json::Value) -> Option<i64> {
+    match value {
+        serde_json::Value::Number(id) => id.as_i64(),
+        serde_json::Value::String(id) => id.parse().ok(),
+        _ => None
+    }
+}
+
+///
+/// Return a FeatureCollection of the features related to a feature through
+/// the foreign key property `key`
+///
+pub fn get(conn: &impl postgres::GenericConnection, id: &i64, key: &str, direction: &Direction, limit: &i64, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    exists(conn, id, tenant)?;
+
+    let keys = index::path(key)?;
+
+    let mut params: Vec<serde_json::Value> = Vec::new();
+
+    let clause = match direction {
+        Direction::Incoming => references(key, id)?.to_sql(&index::indexed(conn)?, &mut params)?,
+        Direction::Outgoing => {
+            let value: Option<serde_json::Value> = match conn.query("
+                SELECT props #> $2::TEXT[] FROM geo WHERE id = $1
+            ", &[&id, &keys]) {
+                Ok(rows) => match rows.iter().next() {
+                    Some(row) => row.get(0),
+                    None => None
+                },
+                Err(err) => { return Err(HecateError::from_db(err)); }
+            };
+
+            match value.as_ref().and_then(referenced) {
+                None => {
+                    return Ok(json!({
+                        "type": "FeatureCollection",
+                        "features": []
+                    }));
+                },
+                Some(parent) => {
+                    params.push(json!(parent));
+                    format!("id = (${}::JSONB #>> '{{}}')::BIGINT", params.len())
+                }
+            }
+        }
+    };
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+    match conn.query(&*format!("
+        SELECT
+            json_build_object(
+                'type', 'FeatureCollection',
+                'features', COALESCE(json_agg(row_to_json(f) ORDER BY f.id), '[]'::JSON)
+            )
+        FROM (
+            SELECT
+                id AS id,
+                key AS key,
+                'Feature' AS type,
+                version AS version,
+                ST_AsGeoJSON(geom)::JSON AS geometry,
+                props AS properties
+            FROM geo
+            WHERE
+                {clause}
+                AND {scope}
+            ORDER BY id
+            LIMIT {limit}
+        ) f
+    ", clause = clause, scope = tenant.to_sql("tenant"), limit = limit), &params) {
+        Ok(rows) => Ok(rows.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_direction() {
+        assert_eq!(Direction::parse(&None).unwrap(), Direction::Incoming);
+        assert_eq!(Direction::parse(&Some(String::from("incoming"))).unwrap(), Direction::Incoming);
+        assert_eq!(Direction::parse(&Some(String::from("outgoing"))).unwrap(), Direction::Outgoing);
+        assert!(Direction::parse(&Some(String::from("children"))).is_err());
+    }
+
+    #[test]
+    fn referenced_ids() {
+        assert_eq!(referenced(&json!(12)), Some(12));
+        assert_eq!(referenced(&json!("12")), Some(12));
+        assert_eq!(referenced(&json!("twelve")), None);
+        assert_eq!(referenced(&json!(1.5)), None);
+        assert_eq!(referenced(&json!(null)), None);
+    }
+}
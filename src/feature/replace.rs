@@ -0,0 +1,162 @@
+use crate::err::HecateError;
+use crate::{bbox, filter, index};
+use crate::tenant::Scope;
+
+///
+/// A request to replace a property value across every feature that has it,
+/// ie: correcting `residental` to `residential`, in a single delta
+///
+/// The features changed can be narrowed to those intersecting a `bbox` and/or
+/// matching a `filter`
+///
+#[derive(Deserialize, Debug)]
+pub struct Replace {
+    pub message: String,
+    pub key: String,
+    pub value: serde_json::Value,
+    pub replacement: serde_json::Value,
+    pub bbox: Option<String>,
+    pub filter: Option<String>
+}
+
+impl Replace {
+    ///
+    /// The filter selecting the features whose property is replaced
+    ///
+    pub fn expr(&self) -> Result<filter::Expr, HecateError> {
+        index::path(&self.key)?;
+
+        if self.value == self.replacement {
+            return Err(HecateError::new(400, String::from("Invalid Replace - value and replacement must differ"), None));
+        }
+
+        let mut expr = filter::Expr::Cmp(self.key.clone(), filter::Op::Eq, self.value.clone());
+
+        if let Some(ref bbox) = self.bbox {
+            let bbox = bbox::Bbox::parse(bbox)?;
+            expr = filter::Expr::And(Box::new(expr), Box::new(filter::Expr::Intersects(bbox.to_vec())));
+        }
+
+        if let Some(ref query) = self.filter {
+            expr = filter::Expr::And(Box::new(expr), Box::new(filter::parse(query)?));
+        }
+
+        Ok(expr)
+    }
+
+    ///
+    /// Lock the matching features of the tenant and build the modify of each
+    /// with the property replaced
+    ///
+    pub fn features(&self, trans: &postgres::transaction::Transaction, tenant: &Scope) -> Result<Vec<geojson::Feature>, HecateError> {
+        let expr = self.expr()?;
+        let keys = index::path(&self.key)?;
+        let indexed = index::indexed(trans)?;
+
+        let mut params: Vec<serde_json::Value> = Vec::new();
+        let clause = expr.to_sql(&indexed, &mut params)?;
+
+        let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+        let rows = match trans.query(&*format!("
+            SELECT
+                id,
+                key,
+                version,
+                props,
+                ST_AsGeoJSON(geom)::JSON
+            FROM geo
+            WHERE
+                {clause}
+                AND {scope}
+            ORDER BY id
+            FOR UPDATE
+        ", clause = clause, scope = tenant.to_sql("tenant")), &params) {
+            Ok(rows) => rows,
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+
+        let mut features: Vec<geojson::Feature> = Vec::with_capacity(rows.len());
+
+        for row in rows.iter() {
+            let id: i64 = row.get(0);
+            let key: Option<String> = row.get(1);
+            let version: i64 = row.get(2);
+            let mut props: serde_json::Value = row.get(3);
+            let geom: serde_json::Value = row.get(4);
+
+            replace(&mut props, &keys, &self.replacement);
+
+            features.push(super::from_value(json!({
+                "id": id,
+                "key": key,
+                "type": "Feature",
+                "action": "modify",
+                "version": version,
+                "properties": props,
+                "geometry": geom
+            }))?);
+        }
+
+        Ok(features)
+    }
+}
+
+///
+/// Set the property at the given path, returning false if the path does not exist
+///
+pub fn replace(props: &mut serde_json::Value, keys: &Vec<String>, replacement: &serde_json::Value) -> bool {
+    match props.pointer_mut(&format!("/{}", keys.join("/"))) {
+        Some(value) => {
+            *value = replacement.clone();
+            true
+        },
+        None => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(key: &str, value: serde_json::Value, replacement: serde_json::Value) -> Replace {
+        Replace {
+            message: String::from("Fix typo"),
+            key: String::from(key),
+            value: value,
+            replacement: replacement,
+            bbox: None,
+            filter: None
+        }
+    }
+
+    #[test]
+    fn replace_expr() {
+        assert_eq!(request("building", json!("residental"), json!("residential")).expr().unwrap(), filter::Expr::Cmp(String::from("building"), filter::Op::Eq, json!("residental")));
+
+        let mut scoped = request("building", json!("residental"), json!("residential"));
+        scoped.bbox = Some(String::from("-1,-1,1,1"));
+        scoped.filter = Some(String::from("name = 'a'"));
+        assert_eq!(scoped.expr().unwrap(), filter::Expr::And(
+            Box::new(filter::Expr::And(
+                Box::new(filter::Expr::Cmp(String::from("building"), filter::Op::Eq, json!("residental"))),
+                Box::new(filter::Expr::Intersects(vec![-1.0, -1.0, 1.0, 1.0]))
+            )),
+            Box::new(filter::Expr::Cmp(String::from("name"), filter::Op::Eq, json!("a")))
+        ));
+
+        assert!(request("building", json!("yes"), json!("yes")).expr().is_err());
+        assert!(request("build ing", json!("yes"), json!("no")).expr().is_err());
+    }
+
+    #[test]
+    fn replace_props() {
+        let mut props = json!({ "building": "residental", "address": { "street": "Main St" } });
+
+        assert!(replace(&mut props, &vec![String::from("building")], &json!("residential")));
+        assert!(replace(&mut props, &vec![String::from("address"), String::from("street")], &json!("Main Street")));
+        assert!(!replace(&mut props, &vec![String::from("name")], &json!("a")));
+
+        assert_eq!(props, json!({ "building": "residential", "address": { "street": "Main Street" } }));
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::{BufRead, BufReader, Lines, Read};
+use crate::stream::PGStream;
+use crate::err::HecateError;
+use crate::bbox::Bbox;
+use crate::tenant::Scope;
+
+///
+/// Number of stored features that are validated against a single
+/// compilation of the schema
+///
+const BATCH: usize = 1000;
+
+///
+/// A stream of Line-Delimited JSON reports, one for each stored feature that
+/// fails the current geometry or schema validation
+///
+/// Features are only read, nothing is written back to the database
+///
+pub struct Revalidate {
+    features: Lines<BufReader<PGStream>>,
+    schema: Option<serde_json::Value>,
+    pending: Vec<u8>,
+    eot: bool
+}
+
+impl Revalidate {
+    pub fn new(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, schema: &Option<serde_json::Value>, bbox: &Option<Bbox>, bound: &Option<String>, tenant: &Scope) -> Result<Self, HecateError> {
+        let mut params: Vec<Box<dyn postgres::types::ToSql>> = Vec::new();
+        let mut clauses: Vec<String> = vec![tenant.to_sql("tenant")];
+
+        match bbox {
+            None => (),
+            Some(bbox) => {
+                let mut coords: Vec<f64> = Vec::new();
+                clauses.push(bbox.to_sql("geom", &mut coords));
+
+                for coord in coords {
+                    params.push(Box::new(coord));
+                }
+            }
+        };
+
+        match bound {
+            None => (),
+            Some(bound) => {
+                match conn.query("
+                    SELECT 1 FROM bounds WHERE name = $1
+                ", &[&bound]) {
+                    Ok(res) => if res.len() == 0 {
+                        return Err(HecateError::new(404, String::from("Bound Not Found"), None));
+                    },
+                    Err(err) => { return Err(HecateError::from_db(err)); }
+                };
+
+                params.push(Box::new(bound.clone()));
+                clauses.push(format!("ST_Intersects(geom, (SELECT bounds.geom FROM bounds WHERE bounds.name = ${}))", params.len()));
+            }
+        };
+
+        let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| &**param).collect();
+
+        let features = PGStream::new(conn, String::from("next_features"), format!(r#"
+            DECLARE next_features CURSOR FOR
+                SELECT
+                    row_to_json(f)::TEXT AS feature
+                FROM (
+                    SELECT
+                        id AS id,
+                        key AS key,
+                        'Feature' AS type,
+                        version AS version,
+                        ST_AsGeoJSON(geom)::JSON AS geometry,
+                        props AS properties,
+                        CASE
+                            WHEN ST_IsValid(geom) THEN NULL
+                            ELSE ST_IsValidReason(geom)
+                        END AS invalid
+                    FROM geo
+                    WHERE
+                        {clause}
+                    ORDER BY id
+                ) f;
+        "#, clause = clauses.join(" AND ")), &params)?;
+
+        Ok(Revalidate {
+            features: BufReader::new(features).lines(),
+            schema: schema.clone(),
+            pending: Vec::new(),
+            eot: false
+        })
+    }
+
+    ///
+    /// Validate the next batch of features, returning false once all
+    /// features have been read
+    ///
+    fn next_batch(&mut self) -> std::io::Result<bool> {
+        let mut scope = valico::json_schema::Scope::new();
+        let schema = match self.schema {
+            None => None,
+            Some(ref schema) => match scope.compile_and_return(schema.clone(), false) {
+                Ok(schema) => Some(schema),
+                Err(_) => { return Err(std::io::Error::new(std::io::ErrorKind::Other, "Schema Error")); }
+            }
+        };
+
+        let mut read = 0;
+        while read < BATCH {
+            let line = match self.features.next() {
+                None => { return Ok(false); },
+                Some(line) => line?
+            };
+
+            //The underlying feature stream terminates with an EOT
+            let line = line.trim_end_matches('\u{4}');
+            if line.len() == 0 {
+                continue;
+            }
+
+            read += 1;
+
+            let feat: serde_json::Value = match serde_json::from_str(line) {
+                Ok(feat) => feat,
+                Err(_) => { continue; }
+            };
+
+            let mut reasons: Vec<serde_json::Value> = Vec::new();
+
+            match feat.get("invalid") {
+                Some(serde_json::Value::String(reason)) => reasons.push(json!(format!("Invalid Geometry - {}", reason))),
+                _ => ()
+            };
+
+            match feat.to_string().parse::<geojson::GeoJson>() {
+                Ok(geojson::GeoJson::Feature(feature)) => {
+                    match feature.properties {
+                        None => reasons.push(json!("Properties Required")),
+                        Some(ref props) => match schema {
+                            Some(ref schema) if !schema.validate(&json!(props)).is_valid() => reasons.push(json!("Failed to Match Schema")),
+                            _ => ()
+                        }
+                    };
+
+                    match super::get_geom_str(&feature) {
+                        Ok(_) => (),
+                        Err(err) => reasons.push(err.as_json()["message"].clone())
+                    };
+                },
+                _ => reasons.push(json!("Invalid Feature"))
+            };
+
+            if reasons.len() > 0 {
+                self.pending.append(&mut json!({
+                    "id": feat["id"],
+                    "key": feat["key"],
+                    "version": feat["version"],
+                    "reasons": reasons
+                }).to_string().into_bytes());
+                self.pending.push(0x0A);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl Read for Revalidate {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pending.len() < buf.len() && !self.eot {
+            if !self.next_batch()? {
+                self.pending.push(0x04); //Write EOT Character To Stream
+                self.eot = true;
+            }
+        }
+
+        let len = std::cmp::min(buf.len(), self.pending.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+
+        Ok(len)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::err::HecateError;
+use crate::tenant::Scope;
+
+///
+/// A request to restore a feature to the properties & geometry of an earlier version
+///
+/// `current` is the version the client expects the feature to be at, so that an
+/// edit made since the client last saw the feature is not silently reverted
+///
+#[derive(Deserialize, Debug)]
+pub struct Rollback {
+    pub message: String,
+    pub current: i64
+}
+
+impl Rollback {
+    ///
+    /// Lock the feature and build the modify that returns it to the given version
+    ///
+    /// The modify carries a `restored_from` member referencing the version it
+    /// was restored from so that the rollback is visible in history
+    ///
+    pub fn feature(&self, trans: &postgres::transaction::Transaction, id: &i64, version: &i64, tenant: &Scope) -> Result<geojson::Feature, HecateError> {
+        let rows = match trans.query(&*format!("
+            SELECT
+                key,
+                version
+            FROM
+                geo
+            WHERE
+                id = $1
+                AND {scope}
+            FOR UPDATE
+        ", scope = tenant.to_sql("tenant")), &[&id]) {
+            Ok(rows) => rows,
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+
+        if rows.len() != 1 {
+            return Err(HecateError::new(404, String::from("Feature Not Found"), None));
+        }
+
+        let key: Option<String> = rows.get(0).get(0);
+        let current: i64 = rows.get(0).get(1);
+
+        if current != self.current {
+            return Err(HecateError::new(409, format!("Version Conflict - feature is at version {}", current), None));
+        }
+
+        if *version < 1 || *version >= current {
+            return Err(HecateError::new(400, String::from("Invalid Version - must be an earlier version of the feature"), None));
+        }
+
+        //The version written by a delta is one greater than the version it was applied to
+        let rows = match trans.query("
+            SELECT
+                feat->'properties',
+                feat->'geometry'
+            FROM
+                deltas,
+                JSONB_Array_Elements(deltas.features->'features') AS feat
+            WHERE
+                deltas.affected @> ARRAY[$1]::BIGINT[]
+                AND (feat->>'id')::BIGINT = $1
+                AND feat->>'action' != 'delete'
+                AND COALESCE((feat->>'version')::BIGINT, 0) + 1 = $2
+            ORDER BY deltas.id DESC
+            LIMIT 1
+        ", &[&id, &version]) {
+            Ok(rows) => rows,
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+
+        if rows.len() != 1 {
+            return Err(HecateError::new(404, String::from("Version Not Found"), None));
+        }
+
+        let props: serde_json::Value = rows.get(0).get(0);
+        let geom: serde_json::Value = rows.get(0).get(1);
+
+        super::from_value(json!({
+            "id": id,
+            "key": key,
+            "type": "Feature",
+            "action": "modify",
+            "version": current,
+            "restored_from": version,
+            "properties": props,
+            "geometry": geom
+        }))
+    }
+}
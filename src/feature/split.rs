@@ -0,0 +1,106 @@
+use crate::err::HecateError;
+use crate::tenant::Scope;
+
+///
+/// A request to split a feature into multiple features along a splitter geometry
+///
+#[derive(Deserialize, Debug)]
+pub struct Split {
+    pub message: String,
+    pub splitter: serde_json::Value
+}
+
+///
+/// The features to store in the split delta, the delete of the original
+/// feature followed by the create of each child
+///
+pub struct Splitted {
+    pub delete: geojson::Feature,
+    pub children: Vec<geojson::Feature>
+}
+
+impl Split {
+    ///
+    /// Lock the feature and split its geometry with `ST_Split`
+    ///
+    /// Each child receives a copy of the original properties along with a `split_from`
+    /// member referencing the original feature so the lineage is kept in history
+    ///
+    pub fn features(&self, trans: &postgres::transaction::Transaction, id: &i64, tenant: &Scope) -> Result<Splitted, HecateError> {
+        let splitter = match self.splitter.get("type") {
+            Some(_) => self.splitter.to_string(),
+            None => { return Err(HecateError::new(400, String::from("Invalid Splitter - must be a GeoJSON geometry"), None)); }
+        };
+
+        let rows = match trans.query(&*format!("
+            SELECT
+                geo.key,
+                geo.version,
+                COALESCE(geo.props, '{{}}'::JSONB),
+                ST_AsGeoJSON(geo.geom)::JSON,
+                (
+                    SELECT
+                        JSON_Agg(ST_AsGeoJSON(part.geom)::JSON ORDER BY part.path)
+                    FROM
+                        ST_Dump(ST_Split(geo.geom, ST_SetSRID(ST_GeomFromGeoJSON($2::TEXT), 4326))) AS part
+                )
+            FROM
+                geo
+            WHERE
+                geo.id = $1
+                AND {scope}
+            FOR UPDATE
+        ", scope = tenant.to_sql("geo.tenant")), &[&id, &splitter]) {
+            Ok(rows) => rows,
+            Err(err) => match err.as_db() {
+                Some(e) => { return Err(HecateError::new(400, format!("Invalid Splitter - {}", e.message), None)); },
+                None => { return Err(HecateError::from_db(err)); }
+            }
+        };
+
+        if rows.len() != 1 {
+            return Err(HecateError::new(404, String::from("Feature Not Found"), None));
+        }
+
+        let row = rows.get(0);
+
+        let key: Option<String> = row.get(0);
+        let version: i64 = row.get(1);
+        let props: serde_json::Value = row.get(2);
+        let geom: serde_json::Value = row.get(3);
+        let parts: Option<serde_json::Value> = row.get(4);
+
+        let parts = match parts {
+            Some(serde_json::Value::Array(parts)) => parts,
+            _ => Vec::new()
+        };
+
+        if parts.len() < 2 {
+            return Err(HecateError::new(400, String::from("Splitter does not split the feature"), None));
+        }
+
+        let mut children = Vec::with_capacity(parts.len());
+        for part in parts {
+            children.push(super::from_value(json!({
+                "type": "Feature",
+                "action": "create",
+                "split_from": id,
+                "properties": props,
+                "geometry": part
+            }))?);
+        }
+
+        Ok(Splitted {
+            delete: super::from_value(json!({
+                "id": id,
+                "key": key,
+                "type": "Feature",
+                "action": "delete",
+                "version": version,
+                "properties": props,
+                "geometry": geom
+            }))?,
+            children: children
+        })
+    }
+}
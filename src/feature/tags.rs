@@ -0,0 +1,124 @@
+use crate::err::HecateError;
+use crate::tenant::Scope;
+use super::exists;
+
+///
+/// Maximum length of a single tag
+///
+pub static MAX_LENGTH: usize = 64;
+
+///
+/// Tags are labels used to organise features, such as `needs-review`, that
+/// are stored outside of the feature itself
+///
+/// Unlike properties, adding or removing a tag does not create a delta or a
+/// new version of the feature
+///
+pub fn validate(tag: &str) -> Result<(), HecateError> {
+    if tag.len() == 0 || tag.len() > MAX_LENGTH {
+        return Err(HecateError::new(400, format!("Invalid Tag - must be between 1 and {} characters", MAX_LENGTH), None));
+    }
+
+    if !tag.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == ':' || c == '-') {
+        return Err(HecateError::new(400, String::from("Invalid Tag - may only contain a-z, 0-9, _, :, and -"), None));
+    }
+
+    Ok(())
+}
+
+///
+/// Return the tags of a feature in alphabetical order
+///
+pub fn list(conn: &impl postgres::GenericConnection, id: &i64, tenant: &Scope) -> Result<Vec<String>, HecateError> {
+    exists(conn, id, tenant)?;
+
+    match conn.query("
+        SELECT
+            tag
+        FROM
+            feature_tags
+        WHERE
+            id = $1
+        ORDER BY tag
+    ", &[&id]) {
+        Ok(rows) => Ok(rows.iter().map(|row| row.get(0)).collect()),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Add a tag to a feature, adding a tag the feature already has is a no-op
+///
+pub fn add(conn: &impl postgres::GenericConnection, id: &i64, tag: &str, uid: &i64, tenant: &Scope) -> Result<Vec<String>, HecateError> {
+    validate(tag)?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    exists(&trans, id, tenant)?;
+
+    match trans.execute("
+        INSERT INTO feature_tags (id, tag, uid, created)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (id, tag) DO NOTHING
+    ", &[&id, &tag, &uid]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    let tags = list(&trans, id, tenant)?;
+
+    match trans.commit() {
+        Ok(_) => Ok(tags),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Remove a tag from a feature, removing a tag the feature does not have is a no-op
+///
+pub fn remove(conn: &impl postgres::GenericConnection, id: &i64, tag: &str, tenant: &Scope) -> Result<Vec<String>, HecateError> {
+    validate(tag)?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    exists(&trans, id, tenant)?;
+
+    match trans.execute("
+        DELETE FROM feature_tags
+            WHERE
+                id = $1
+                AND tag = $2
+    ", &[&id, &tag]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    let tags = list(&trans, id, tenant)?;
+
+    match trans.commit() {
+        Ok(_) => Ok(tags),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_tags() {
+        assert!(validate("needs-review").is_ok());
+        assert!(validate("source:survey_2019").is_ok());
+
+        assert!(validate("").is_err());
+        assert!(validate("Needs-Review").is_err());
+        assert!(validate("needs review").is_err());
+        assert!(validate(&"a".repeat(MAX_LENGTH + 1)).is_err());
+    }
+}
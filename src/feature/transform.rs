@@ -0,0 +1,160 @@
+use crate::err::HecateError;
+use crate::options;
+use serde_json::Value;
+use super::{get_action, Action};
+
+///
+/// Apply the configured transform rules to the properties of a feature being
+/// created or modified, in the order the rules are given
+///
+/// Only top level keys are transformed. The string operations leave values that
+/// are not strings as they are, so that a rule never rejects a write itself
+///
+pub fn props(feat: &mut geojson::Feature, transform: &options::Transform) -> Result<(), HecateError> {
+    let rules = match transform.rules {
+        Some(ref rules) if rules.len() > 0 => rules,
+        _ => { return Ok(()); }
+    };
+
+    match get_action(&feat)? {
+        Action::Create | Action::Modify => (),
+        Action::Delete | Action::Restore => { return Ok(()); }
+    };
+
+    let props = feat.properties.get_or_insert_with(serde_json::Map::new);
+
+    for rule in rules {
+        let field = match rule.field {
+            Some(ref field) => field,
+            None => continue
+        };
+
+        let value = match rule.op.as_ref().map(|op| op.as_str()) {
+            Some("lowercase") => string(props.get(field), |value| value.to_lowercase()),
+            Some("uppercase") => string(props.get(field), |value| value.to_uppercase()),
+            Some("trim") => string(props.get(field), |value| String::from(value.trim())),
+            Some("default") => match props.get(field) {
+                None | Some(Value::Null) => rule.value.clone(),
+                Some(_) => None
+            },
+            Some("copy") => source(&props, &rule.source).cloned(),
+            Some("length") => match source(&props, &rule.source) {
+                Some(Value::String(value)) => Some(json!(value.chars().count())),
+                _ => None
+            },
+            _ => { return Err(HecateError::new(500, String::from("Invalid Transform Rule"), rule.op.clone())); }
+        };
+
+        if let Some(value) = value {
+            props.insert(field.clone(), value);
+        }
+    }
+
+    Ok(())
+}
+
+fn string(value: Option<&Value>, op: impl Fn(&str) -> String) -> Option<Value> {
+    match value {
+        Some(Value::String(value)) => Some(Value::String(op(value))),
+        _ => None
+    }
+}
+
+fn source<'a>(props: &'a serde_json::Map<String, Value>, source: &Option<String>) -> Option<&'a Value> {
+    match source {
+        Some(source) => props.get(source),
+        None => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature(action: &str, props: Value) -> geojson::Feature {
+        super::super::from_value(json!({
+            "id": 1,
+            "version": 1,
+            "type": "Feature",
+            "action": action,
+            "properties": props,
+            "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+        })).unwrap()
+    }
+
+    fn rule(field: &str, op: &str, source: Option<&str>, value: Option<Value>) -> options::TransformRule {
+        options::TransformRule {
+            field: Some(String::from(field)),
+            op: Some(String::from(op)),
+            source: source.map(String::from),
+            value: value
+        }
+    }
+
+    fn transform(rules: Vec<options::TransformRule>) -> options::Transform {
+        options::Transform {
+            rules: Some(rules)
+        }
+    }
+
+    #[test]
+    fn transform_strings() {
+        let mut feat = feature("create", json!({
+            "highway": "Primary",
+            "name": "  Main St ",
+            "ref": "a1",
+            "lanes": 2
+        }));
+
+        props(&mut feat, &transform(vec![
+            rule("highway", "lowercase", None, None),
+            rule("name", "trim", None, None),
+            rule("ref", "uppercase", None, None),
+            rule("lanes", "lowercase", None, None),
+            rule("surface", "lowercase", None, None)
+        ])).unwrap();
+
+        assert_eq!(json!(feat.properties.unwrap()), json!({
+            "highway": "primary",
+            "name": "Main St",
+            "ref": "A1",
+            "lanes": 2
+        }));
+    }
+
+    #[test]
+    fn transform_derived() {
+        let mut feat = feature("modify", json!({
+            "name": "Main St",
+            "source": null,
+            "status": "open"
+        }));
+
+        props(&mut feat, &transform(vec![
+            rule("name_length", "length", Some("name"), None),
+            rule("display", "copy", Some("name"), None),
+            rule("source", "default", None, Some(json!("survey"))),
+            rule("status", "default", None, Some(json!("planned"))),
+            rule("missing", "copy", Some("nothing"), None)
+        ])).unwrap();
+
+        assert_eq!(json!(feat.properties.unwrap()), json!({
+            "name": "Main St",
+            "name_length": 7,
+            "display": "Main St",
+            "source": "survey",
+            "status": "open"
+        }));
+    }
+
+    #[test]
+    fn transform_actions() {
+        let mut feat = feature("delete", json!({ "highway": "Primary" }));
+        props(&mut feat, &transform(vec![rule("highway", "lowercase", None, None)])).unwrap();
+        assert_eq!(json!(feat.properties.unwrap()), json!({ "highway": "Primary" }));
+
+        let mut feat = feature("create", json!({ "highway": "Primary" }));
+        props(&mut feat, &options::Transform::new()).unwrap();
+        assert_eq!(json!(feat.properties.unwrap()), json!({ "highway": "Primary" }));
+    }
+}
@@ -0,0 +1,532 @@
+use std::collections::HashMap;
+use crate::err::HecateError;
+use crate::{index, validate};
+use crate::bbox::Bbox;
+use crate::feature::tags;
+
+///
+/// Filters are limited in size so that a single request cannot exhaust the
+/// stack of the recursive descent parser or produce an enormous query
+///
+const MAX_LENGTH: usize = 4096;
+const MAX_DEPTH: usize = 32;
+
+fn invalid(reason: &str) -> HecateError {
+    HecateError::new(400, format!("Invalid Filter - {}", reason), None)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Word(String),
+    Str(String),
+    Num(serde_json::Number),
+    Open,
+    Close,
+    Comma,
+    Eq,
+    NotEq,
+    Lt,
+    Gt
+}
+
+fn tokenize(filter: &str) -> Result<Vec<Token>, HecateError> {
+    let chars: Vec<char> = filter.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            i += 1;
+        } else if c == '=' {
+            tokens.push(Token::Eq);
+            i += 1;
+        } else if c == '<' {
+            tokens.push(Token::Lt);
+            i += 1;
+        } else if c == '>' {
+            tokens.push(Token::Gt);
+            i += 1;
+        } else if c == '!' && i + 1 < chars.len() && chars[i + 1] == '=' {
+            tokens.push(Token::NotEq);
+            i += 2;
+        } else if c == '\'' {
+            //Strings are single quoted, with '' as an escaped quote
+            let mut value = String::new();
+            i += 1;
+
+            loop {
+                if i >= chars.len() {
+                    return Err(invalid("unterminated string"));
+                } else if chars[i] == '\'' && i + 1 < chars.len() && chars[i + 1] == '\'' {
+                    value.push('\'');
+                    i += 2;
+                } else if chars[i] == '\'' {
+                    i += 1;
+                    break;
+                } else {
+                    value.push(chars[i]);
+                    i += 1;
+                }
+            }
+
+            tokens.push(Token::Str(value));
+        } else if c.is_ascii_digit() || (c == '-' && i + 1 < chars.len() && chars[i + 1].is_ascii_digit()) {
+            let start = i;
+            i += 1;
+
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == 'e' || chars[i] == 'E' || ((chars[i] == '-' || chars[i] == '+') && (chars[i - 1] == 'e' || chars[i - 1] == 'E'))) {
+                i += 1;
+            }
+
+            let num: String = chars[start..i].iter().collect();
+
+            match serde_json::from_str::<serde_json::Number>(&num) {
+                Ok(num) => tokens.push(Token::Num(num)),
+                Err(_) => { return Err(invalid(&format!("invalid number '{}'", num))); }
+            };
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == ':' || chars[i] == '-' || chars[i] == '.') {
+                i += 1;
+            }
+
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            return Err(invalid(&format!("unexpected character '{}'", c)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Op {
+    Eq,
+    NotEq,
+    Lt,
+    Gt
+}
+
+///
+/// The parsed form of a filter
+///
+/// Property paths are validated as they are parsed and values are only ever
+/// bound as query parameters so a successfully parsed filter is always safe
+/// to compile
+///
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cmp(String, Op, serde_json::Value),
+    In(String, Vec<serde_json::Value>),
+    Like(String, String),
+    Intersects(Vec<f64>),
+    Tagged(String)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) => word.to_uppercase() == keyword,
+            _ => false
+        }
+    }
+
+    fn expect(&mut self, expected: Token, reason: &str) -> Result<(), HecateError> {
+        match self.next() {
+            Some(ref token) if *token == expected => Ok(()),
+            _ => Err(invalid(reason))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, HecateError> {
+        let mut expr = self.and()?;
+
+        while self.is_keyword("OR") {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, HecateError> {
+        let mut expr = self.not()?;
+
+        while self.is_keyword("AND") {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, HecateError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(invalid("filter is nested too deeply"));
+        }
+
+        let expr = if self.is_keyword("NOT") {
+            self.pos += 1;
+            Expr::Not(Box::new(self.not()?))
+        } else {
+            self.primary()?
+        };
+
+        self.depth -= 1;
+
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, HecateError> {
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let expr = self.or()?;
+            self.expect(Token::Close, "expected ')'")?;
+            return Ok(expr);
+        }
+
+        if self.is_keyword("INTERSECTS") {
+            self.pos += 1;
+            self.expect(Token::Open, "INTERSECTS must be followed by (minX, minY, maxX, maxY)")?;
+
+            let mut bbox: Vec<f64> = Vec::with_capacity(4);
+            loop {
+                match self.next() {
+                    Some(Token::Num(num)) => match num.as_f64() {
+                        Some(num) => bbox.push(num),
+                        None => { return Err(invalid("INTERSECTS coordinates must be numeric")); }
+                    },
+                    _ => { return Err(invalid("INTERSECTS coordinates must be numeric")); }
+                };
+
+                match self.next() {
+                    Some(Token::Comma) => (),
+                    Some(Token::Close) => break,
+                    _ => { return Err(invalid("INTERSECTS must be followed by (minX, minY, maxX, maxY)")); }
+                };
+            }
+
+            validate::bbox(&bbox)?;
+
+            return Ok(Expr::Intersects(bbox));
+        }
+
+        if self.is_keyword("TAGGED") {
+            self.pos += 1;
+            self.expect(Token::Open, "TAGGED must be followed by ('<tag>')")?;
+
+            let tag = match self.next() {
+                Some(Token::Str(tag)) => tag,
+                _ => { return Err(invalid("TAGGED must be followed by ('<tag>')")); }
+            };
+
+            self.expect(Token::Close, "TAGGED must be followed by ('<tag>')")?;
+
+            tags::validate(&tag)?;
+
+            return Ok(Expr::Tagged(tag));
+        }
+
+        let property = match self.next() {
+            Some(Token::Word(word)) => {
+                index::path(&word)?;
+                word
+            },
+            _ => { return Err(invalid("expected a property")); }
+        };
+
+        if self.is_keyword("IN") {
+            self.pos += 1;
+            self.expect(Token::Open, "IN must be followed by a list of values")?;
+
+            let mut values: Vec<serde_json::Value> = Vec::new();
+            loop {
+                values.push(self.value()?);
+
+                match self.next() {
+                    Some(Token::Comma) => (),
+                    Some(Token::Close) => break,
+                    _ => { return Err(invalid("IN must be followed by a list of values")); }
+                };
+            }
+
+            return Ok(Expr::In(property, values));
+        }
+
+        if self.is_keyword("LIKE") {
+            self.pos += 1;
+
+            return match self.next() {
+                Some(Token::Str(pattern)) => Ok(Expr::Like(property, pattern)),
+                _ => Err(invalid("LIKE must be followed by a string"))
+            };
+        }
+
+        let op = match self.next() {
+            Some(Token::Eq) => Op::Eq,
+            Some(Token::NotEq) => Op::NotEq,
+            Some(Token::Lt) => Op::Lt,
+            Some(Token::Gt) => Op::Gt,
+            _ => { return Err(invalid(&format!("expected an operator after '{}'", property))); }
+        };
+
+        let value = self.value()?;
+
+        if (op == Op::Lt || op == Op::Gt) && !(value.is_number() || value.is_string()) {
+            return Err(invalid("< and > can only compare numbers or strings"));
+        }
+
+        Ok(Expr::Cmp(property, op, value))
+    }
+
+    fn value(&mut self) -> Result<serde_json::Value, HecateError> {
+        match self.next() {
+            Some(Token::Str(value)) => Ok(serde_json::Value::String(value)),
+            Some(Token::Num(value)) => Ok(serde_json::Value::Number(value)),
+            Some(Token::Word(ref word)) if word.to_uppercase() == "TRUE" => Ok(serde_json::Value::Bool(true)),
+            Some(Token::Word(ref word)) if word.to_uppercase() == "FALSE" => Ok(serde_json::Value::Bool(false)),
+            Some(Token::Word(ref word)) if word.to_uppercase() == "NULL" => Ok(serde_json::Value::Null),
+            _ => Err(invalid("expected a string, number, true, false, or null"))
+        }
+    }
+}
+
+///
+/// Parse a filter such as `building.levels > 3 AND INTERSECTS(-1, -1, 1, 1)`
+///
+pub fn parse(filter: &str) -> Result<Expr, HecateError> {
+    if filter.len() > MAX_LENGTH {
+        return Err(invalid("filter is too long"));
+    }
+
+    let mut parser = Parser {
+        tokens: tokenize(filter)?,
+        pos: 0,
+        depth: 0
+    };
+
+    if parser.tokens.len() == 0 {
+        return Err(invalid("filter is empty"));
+    }
+
+    let expr = parser.or()?;
+
+    match parser.peek() {
+        None => Ok(expr),
+        Some(_) => Err(invalid("unexpected input after end of filter"))
+    }
+}
+
+///
+/// Parse a property equality filter in the form `<property path>=<value>`
+///
+/// The value is parsed as JSON when possible so that `levels=3` matches the
+/// number 3, otherwise it is treated as a string
+///
+pub fn prop(filter: &str) -> Result<Expr, HecateError> {
+    let mut split = filter.splitn(2, '=');
+
+    let property = match split.next() {
+        Some(property) => String::from(property),
+        None => { return Err(HecateError::new(400, String::from("Invalid Property Filter - must be in the form <property>=<value>"), None)); }
+    };
+
+    let value = match split.next() {
+        Some(value) => match serde_json::from_str(value) {
+            Ok(value) => value,
+            Err(_) => serde_json::Value::String(String::from(value))
+        },
+        None => { return Err(HecateError::new(400, String::from("Invalid Property Filter - must be in the form <property>=<value>"), None)); }
+    };
+
+    index::path(&property)?;
+
+    Ok(Expr::Cmp(property, Op::Eq, value))
+}
+
+impl Expr {
+    ///
+    /// Compile the filter to a SQL clause against the geo table, appending
+    /// every value to params and referencing it as a numbered parameter
+    ///
+    pub fn to_sql(&self, indexed: &HashMap<String, Vec<String>>, params: &mut Vec<serde_json::Value>) -> Result<String, HecateError> {
+        match self {
+            Expr::And(left, right) => Ok(format!("({} AND {})", left.to_sql(indexed, params)?, right.to_sql(indexed, params)?)),
+            Expr::Or(left, right) => Ok(format!("({} OR {})", left.to_sql(indexed, params)?, right.to_sql(indexed, params)?)),
+            Expr::Not(expr) => Ok(format!("(NOT {})", expr.to_sql(indexed, params)?)),
+            Expr::Cmp(property, op, value) => {
+                let keys = index::path(property)?;
+                params.push(value.clone());
+                let param = params.len();
+
+                match op {
+                    Op::Eq => Ok(index::equals(&keys, indexed.get(property), param)),
+                    Op::NotEq => Ok(format!("{} <> ${}::JSONB", index::expression(&keys), param)),
+                    //JSONB orders values of different types by type, so both sides must be of the same type
+                    Op::Lt => Ok(format!("(JSONB_TYPEOF({expr}) = JSONB_TYPEOF(${param}::JSONB) AND {expr} < ${param}::JSONB)", expr = index::expression(&keys), param = param)),
+                    Op::Gt => Ok(format!("(JSONB_TYPEOF({expr}) = JSONB_TYPEOF(${param}::JSONB) AND {expr} > ${param}::JSONB)", expr = index::expression(&keys), param = param))
+                }
+            },
+            Expr::In(property, values) => {
+                let keys = index::path(property)?;
+
+                let mut list: Vec<String> = Vec::with_capacity(values.len());
+                for value in values {
+                    params.push(value.clone());
+                    list.push(format!("${}::JSONB", params.len()));
+                }
+
+                Ok(format!("{} IN ({})", index::expression(&keys), list.join(", ")))
+            },
+            Expr::Like(property, pattern) => {
+                let keys = index::path(property)?;
+                params.push(serde_json::Value::String(pattern.clone()));
+
+                Ok(format!("(props #>> '{{{}}}'::TEXT[]) LIKE (${}::JSONB #>> '{{}}')", keys.join(","), params.len()))
+            },
+            Expr::Intersects(bbox) => {
+                let mut clauses: Vec<String> = Vec::new();
+
+                for bbox in Bbox::new(bbox)?.split() {
+                    let mut coords: Vec<String> = Vec::with_capacity(4);
+                    for coord in bbox.to_vec() {
+                        params.push(json!(coord));
+                        coords.push(format!("(${}::JSONB #>> '{{}}')::FLOAT8", params.len()));
+                    }
+
+                    clauses.push(format!("ST_Intersects(geom, ST_MakeEnvelope({}, 4326))", coords.join(", ")));
+                }
+
+                if clauses.len() == 1 {
+                    Ok(clauses.remove(0))
+                } else {
+                    Ok(format!("({})", clauses.join(" OR ")))
+                }
+            },
+            Expr::Tagged(tag) => {
+                params.push(serde_json::Value::String(tag.clone()));
+
+                Ok(format!("EXISTS (SELECT 1 FROM feature_tags WHERE feature_tags.id = geo.id AND feature_tags.tag = (${}::JSONB #>> '{{}}'))", params.len()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(filter: &str) -> (String, Vec<serde_json::Value>) {
+        let mut params = Vec::new();
+        let sql = parse(filter).unwrap().to_sql(&HashMap::new(), &mut params).unwrap();
+        (sql, params)
+    }
+
+    fn reason(filter: &str) -> serde_json::Value {
+        parse(filter).err().unwrap().as_json()["reason"].clone()
+    }
+
+    #[test]
+    fn parse_comparisons() {
+        assert_eq!(parse("building.levels = 3").unwrap(), Expr::Cmp(String::from("building.levels"), Op::Eq, json!(3)));
+        assert_eq!(parse("name != 'Main St'").unwrap(), Expr::Cmp(String::from("name"), Op::NotEq, json!("Main St")));
+        assert_eq!(parse("height < -1.5").unwrap(), Expr::Cmp(String::from("height"), Op::Lt, json!(-1.5)));
+        assert_eq!(parse("height > 1e3").unwrap(), Expr::Cmp(String::from("height"), Op::Gt, json!(1000.0)));
+        assert_eq!(parse("shop = true").unwrap(), Expr::Cmp(String::from("shop"), Op::Eq, json!(true)));
+        assert_eq!(parse("addr:street = 'O''Connell'").unwrap(), Expr::Cmp(String::from("addr:street"), Op::Eq, json!("O'Connell")));
+    }
+
+    #[test]
+    fn parse_in_like_intersects() {
+        assert_eq!(parse("shop IN ('bakery', 'cafe', 1)").unwrap(), Expr::In(String::from("shop"), vec![json!("bakery"), json!("cafe"), json!(1)]));
+        assert_eq!(parse("name like 'Main%'").unwrap(), Expr::Like(String::from("name"), String::from("Main%")));
+        assert_eq!(parse("INTERSECTS(-1, -1, 1, 1)").unwrap(), Expr::Intersects(vec![-1.0, -1.0, 1.0, 1.0]));
+        assert_eq!(parse("tagged('needs-review')").unwrap(), Expr::Tagged(String::from("needs-review")));
+    }
+
+    #[test]
+    fn parse_precedence() {
+        assert_eq!(parse("a = 1 OR b = 2 AND NOT c = 3").unwrap(), Expr::Or(
+            Box::new(Expr::Cmp(String::from("a"), Op::Eq, json!(1))),
+            Box::new(Expr::And(
+                Box::new(Expr::Cmp(String::from("b"), Op::Eq, json!(2))),
+                Box::new(Expr::Not(Box::new(Expr::Cmp(String::from("c"), Op::Eq, json!(3)))))
+            ))
+        ));
+
+        assert_eq!(parse("(a = 1 OR b = 2) AND c = 3").unwrap(), Expr::And(
+            Box::new(Expr::Or(
+                Box::new(Expr::Cmp(String::from("a"), Op::Eq, json!(1))),
+                Box::new(Expr::Cmp(String::from("b"), Op::Eq, json!(2)))
+            )),
+            Box::new(Expr::Cmp(String::from("c"), Op::Eq, json!(3)))
+        ));
+    }
+
+    #[test]
+    fn compile_parameterized() {
+        let (sql, params) = compile("name = 'x''); DROP TABLE geo; --' AND levels IN (1, 2)");
+
+        assert_eq!(sql, "((props #> '{name}'::TEXT[]) = $1::JSONB AND (props #> '{levels}'::TEXT[]) IN ($2::JSONB, $3::JSONB))");
+        assert_eq!(params, vec![json!("x'); DROP TABLE geo; --"), json!(1), json!(2)]);
+
+        let (sql, params) = compile("name LIKE 'Main%' OR INTERSECTS(0, 0, 1, 1)");
+        assert_eq!(sql, "((props #>> '{name}'::TEXT[]) LIKE ($1::JSONB #>> '{}') OR ST_Intersects(geom, ST_MakeEnvelope(($2::JSONB #>> '{}')::FLOAT8, ($3::JSONB #>> '{}')::FLOAT8, ($4::JSONB #>> '{}')::FLOAT8, ($5::JSONB #>> '{}')::FLOAT8, 4326)))");
+        assert_eq!(params.len(), 5);
+
+        let (sql, params) = compile("INTERSECTS(170, 0, -170, 1)");
+        assert_eq!(sql, "(ST_Intersects(geom, ST_MakeEnvelope(($1::JSONB #>> '{}')::FLOAT8, ($2::JSONB #>> '{}')::FLOAT8, ($3::JSONB #>> '{}')::FLOAT8, ($4::JSONB #>> '{}')::FLOAT8, 4326)) OR ST_Intersects(geom, ST_MakeEnvelope(($5::JSONB #>> '{}')::FLOAT8, ($6::JSONB #>> '{}')::FLOAT8, ($7::JSONB #>> '{}')::FLOAT8, ($8::JSONB #>> '{}')::FLOAT8, 4326)))");
+        assert_eq!(params, vec![json!(170.0), json!(0.0), json!(180.0), json!(1.0), json!(-180.0), json!(0.0), json!(-170.0), json!(1.0)]);
+
+        let (sql, params) = compile("NOT TAGGED('needs-review')");
+        assert_eq!(sql, "(NOT EXISTS (SELECT 1 FROM feature_tags WHERE feature_tags.id = geo.id AND feature_tags.tag = ($1::JSONB #>> '{}')))");
+        assert_eq!(params, vec![json!("needs-review")]);
+    }
+
+    #[test]
+    fn reject_malicious() {
+        assert_eq!(reason("name = 1; DROP TABLE geo"), json!("Invalid Filter - unexpected character ';'"));
+        assert_eq!(reason("name = 'a' -- comment"), json!("Invalid Filter - unexpected input after end of filter"));
+        assert_eq!(reason("name = 'unterminated"), json!("Invalid Filter - unterminated string"));
+        assert_eq!(reason("props = 1 OR 1 = 1"), json!("Invalid Filter - expected a property"));
+        assert_eq!(reason("name = other_column"), json!("Invalid Filter - expected a string, number, true, false, or null"));
+        assert_eq!(reason("a..b = 1"), json!("Invalid Property - keys may only contain a-z, 0-9, _, :, and -"));
+        assert_eq!(reason("name = \"quoted\""), json!("Invalid Filter - unexpected character '\"'"));
+        assert_eq!(reason("INTERSECTS(0, 0, 1)"), json!("Invalid BBOX"));
+        assert_eq!(reason("TAGGED(review)"), json!("Invalid Filter - TAGGED must be followed by ('<tag>')"));
+        assert_eq!(reason("TAGGED('a b')"), json!("Invalid Tag - may only contain a-z, 0-9, _, :, and -"));
+        assert_eq!(reason(&format!("{}a = 1{}", "(".repeat(64), ")".repeat(64))), json!("Invalid Filter - filter is nested too deeply"));
+    }
+}
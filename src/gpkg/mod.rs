@@ -0,0 +1,227 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use rusqlite::types::ToSql;
+use crate::err::HecateError;
+
+///
+/// Table the features are written to
+///
+pub static TABLE: &str = "features";
+
+///
+/// A spatial reference system, as described by the `spatial_ref_sys` table of PostGIS
+///
+pub struct Srs {
+    pub srid: i32,
+    pub organization: String,
+    pub code: i32,
+    pub definition: String
+}
+
+///
+/// A GeoPackage being written to a temporary file as features are fetched,
+/// so that a download never holds the whole dataset in memory
+///
+/// Properties are stored as a JSON string in the `properties` column as the
+/// keys of the features are not known until every feature has been written
+///
+pub struct GeoPackage {
+    conn: rusqlite::Connection,
+    path: PathBuf,
+    srid: i32
+}
+
+impl GeoPackage {
+    ///
+    /// Create an empty GeoPackage whose features are in the first of the given
+    /// reference systems. EPSG:4326 must always be among them
+    ///
+    /// `last_change` is recorded as the time the contents last changed, in the
+    /// `%Y-%m-%dT%H:%M:%fZ` format of the GeoPackage spec
+    ///
+    pub fn new(srs: &Vec<Srs>, identifier: &str, last_change: &str) -> Result<Self, HecateError> {
+        let srid = match srs.first() {
+            Some(srs) => srs.srid,
+            None => { return Err(HecateError::new(500, String::from("Failed to write GeoPackage"), None)); }
+        };
+
+        let path = std::env::temp_dir().join(format!("hecate-{}-{}.gpkg", std::process::id(), rand::random::<u64>()));
+
+        let conn = match rusqlite::Connection::open(&path) {
+            Ok(conn) => conn,
+            Err(err) => { return Err(error(err)); }
+        };
+
+        let gpkg = GeoPackage {
+            conn: conn,
+            path: path,
+            srid: srid
+        };
+
+        match gpkg.create(srs, identifier, last_change) {
+            Ok(_) => (),
+            Err(err) => {
+                gpkg.discard();
+                return Err(err);
+            }
+        };
+
+        Ok(gpkg)
+    }
+
+    fn create(&self, srs: &Vec<Srs>, identifier: &str, last_change: &str) -> Result<(), HecateError> {
+        match self.conn.execute_batch(&*format!("
+            PRAGMA application_id = 1196444487;
+            PRAGMA user_version = 10200;
+
+            CREATE TABLE gpkg_spatial_ref_sys (
+                srs_name TEXT NOT NULL,
+                srs_id INTEGER NOT NULL PRIMARY KEY,
+                organization TEXT NOT NULL,
+                organization_coordsys_id INTEGER NOT NULL,
+                definition TEXT NOT NULL,
+                description TEXT
+            );
+
+            CREATE TABLE gpkg_contents (
+                table_name TEXT NOT NULL PRIMARY KEY,
+                data_type TEXT NOT NULL,
+                identifier TEXT UNIQUE,
+                description TEXT DEFAULT '',
+                last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+                min_x DOUBLE,
+                min_y DOUBLE,
+                max_x DOUBLE,
+                max_y DOUBLE,
+                srs_id INTEGER REFERENCES gpkg_spatial_ref_sys(srs_id)
+            );
+
+            CREATE TABLE gpkg_geometry_columns (
+                table_name TEXT NOT NULL REFERENCES gpkg_contents(table_name),
+                column_name TEXT NOT NULL,
+                geometry_type_name TEXT NOT NULL,
+                srs_id INTEGER NOT NULL REFERENCES gpkg_spatial_ref_sys(srs_id),
+                z TINYINT NOT NULL,
+                m TINYINT NOT NULL,
+                PRIMARY KEY (table_name, column_name)
+            );
+
+            CREATE TABLE {table} (
+                fid INTEGER PRIMARY KEY,
+                geom GEOMETRY,
+                key TEXT,
+                version INTEGER,
+                properties TEXT
+            );
+
+            INSERT INTO gpkg_spatial_ref_sys VALUES
+                ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined', NULL),
+                ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined', NULL);
+        ", table = TABLE)) {
+            Ok(_) => (),
+            Err(err) => { return Err(error(err)); }
+        };
+
+        for srs in srs {
+            match self.conn.execute("
+                INSERT INTO gpkg_spatial_ref_sys (srs_name, srs_id, organization, organization_coordsys_id, definition)
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+            ", &[&format!("{}:{}", srs.organization, srs.code) as &ToSql, &srs.srid, &srs.organization, &srs.code, &srs.definition]) {
+                Ok(_) => (),
+                Err(err) => { return Err(error(err)); }
+            };
+        }
+
+        match self.conn.execute("
+            INSERT INTO gpkg_contents (table_name, data_type, identifier, last_change, srs_id) VALUES (?1, 'features', ?2, ?3, ?4)
+        ", &[&TABLE as &ToSql, &identifier, &last_change, &self.srid]) {
+            Ok(_) => (),
+            Err(err) => { return Err(error(err)); }
+        };
+
+        match self.conn.execute_batch(&*format!("
+            INSERT INTO gpkg_geometry_columns VALUES ('{table}', 'geom', 'GEOMETRY', {srid}, 2, 2);
+
+            BEGIN;
+        ", table = TABLE, srid = self.srid)) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(error(err))
+        }
+    }
+
+    pub fn insert(&self, id: i64, key: Option<String>, version: i64, properties: String, wkb: Vec<u8>) -> Result<(), HecateError> {
+        match self.conn.execute(&*format!("
+            INSERT INTO {} (fid, geom, key, version, properties) VALUES (?1, ?2, ?3, ?4, ?5)
+        ", TABLE), &[&id as &ToSql, &blob(self.srid, &wkb), &key, &version, &properties]) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(error(err))
+        }
+    }
+
+    ///
+    /// Commit the features and return the finished file for reading
+    ///
+    /// The file is unlinked once it is opened, so that it is removed from disk
+    /// as soon as the download completes or is abandoned
+    ///
+    pub fn finish(self) -> Result<File, HecateError> {
+        match self.conn.execute_batch("COMMIT;") {
+            Ok(_) => (),
+            Err(err) => { return Err(error(err)); }
+        };
+
+        let path = self.path.clone();
+
+        match self.conn.close() {
+            Ok(_) => (),
+            Err((_, err)) => { return Err(error(err)); }
+        };
+
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) => { return Err(HecateError::new(500, String::from("Failed to write GeoPackage"), Some(err.to_string()))); }
+        };
+
+        fs::remove_file(&path).ok();
+
+        Ok(file)
+    }
+
+    ///
+    /// Remove a GeoPackage that could not be completed
+    ///
+    pub fn discard(self) {
+        let path = self.path.clone();
+
+        drop(self.conn);
+
+        fs::remove_file(&path).ok();
+    }
+}
+
+///
+/// Wrap little endian WKB in a GeoPackage geometry header without an envelope
+///
+pub fn blob(srid: i32, wkb: &Vec<u8>) -> Vec<u8> {
+    let mut blob: Vec<u8> = vec![0x47, 0x50, 0x00, 0x01];
+    blob.extend_from_slice(&srid.to_le_bytes());
+    blob.extend_from_slice(&wkb);
+    blob
+}
+
+fn error(err: rusqlite::Error) -> HecateError {
+    HecateError::new(500, String::from("Failed to write GeoPackage"), Some(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_header() {
+        let wkb: Vec<u8> = vec![0x01, 0x01, 0x00, 0x00, 0x00];
+
+        assert_eq!(blob(4326, &wkb), vec![0x47, 0x50, 0x00, 0x01, 0xE6, 0x10, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(blob(3857, &wkb)[4..8], [0x11, 0x0F, 0x00, 0x00]);
+    }
+}
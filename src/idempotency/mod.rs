@@ -0,0 +1,157 @@
+use rocket::request::{self, FromRequest};
+use rocket::{Request, Outcome};
+use crate::err::HecateError;
+
+///
+/// Number of hours a key is remembered, a key reused after this window
+/// is treated as a new request
+///
+pub static RETENTION: i32 = 24;
+
+///
+/// Maximum length of a key, longer keys are rejected
+///
+pub static MAX_LENGTH: usize = 255;
+
+///
+/// The value of an `Idempotency-Key` header, allowing a client to safely
+/// retry a write whose response was lost
+///
+pub struct Key(pub Option<String>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Key {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Key, ()> {
+        match request.headers().get_one("Idempotency-Key") {
+            Some(key) => Outcome::Success(Key(Some(String::from(key.trim())))),
+            None => Outcome::Success(Key(None))
+        }
+    }
+}
+
+///
+/// The result of a write made with an idempotency key
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Record {
+    pub delta: i64,
+    pub ids: Vec<i64>,
+    pub replayed: bool
+}
+
+impl Key {
+    ///
+    /// Ensure the key is usable, returning it if one was given
+    ///
+    pub fn validate(&self) -> Result<Option<&String>, HecateError> {
+        match self.0 {
+            None => Ok(None),
+            Some(ref key) if key.len() == 0 || key.len() > MAX_LENGTH => {
+                Err(HecateError::new(400, format!("Invalid Idempotency-Key - must be between 1 and {} characters", MAX_LENGTH), None))
+            },
+            Some(ref key) => Ok(Some(key))
+        }
+    }
+
+    ///
+    /// Return the result of a previous write by the user with the same key
+    /// if it is still within the retention window
+    ///
+    pub fn get(&self, conn: &impl postgres::GenericConnection, uid: &i64) -> Result<Option<Record>, HecateError> {
+        let key = match self.validate()? {
+            None => { return Ok(None); },
+            Some(key) => key
+        };
+
+        match conn.query("
+            SELECT
+                delta,
+                ids
+            FROM
+                idempotency
+            WHERE
+                key = $1
+                AND uid = $2
+                AND created > NOW() - ($3::INTEGER * INTERVAL '1 hour')
+        ", &[&key, &uid, &RETENTION]) {
+            Ok(rows) => match rows.iter().next() {
+                None => Ok(None),
+                Some(row) => Ok(Some(Record {
+                    delta: row.get(0),
+                    ids: row.get(1),
+                    replayed: true
+                }))
+            },
+            Err(err) => Err(HecateError::from_db(err))
+        }
+    }
+
+    ///
+    /// Remember the result of a write, must be called within the transaction
+    /// of the write so that the key is only stored if the write is committed
+    ///
+    /// A concurrent request with the same key is rejected as only the first
+    /// request to commit is remembered
+    ///
+    pub fn set(&self, trans: &postgres::transaction::Transaction, uid: &i64, delta: &i64, ids: Vec<i64>) -> Result<Option<Record>, HecateError> {
+        let key = match self.validate()? {
+            None => { return Ok(None); },
+            Some(key) => key
+        };
+
+        if let Err(err) = trans.execute("
+            DELETE FROM idempotency
+                WHERE
+                    uid = $1
+                    AND created <= NOW() - ($2::INTEGER * INTERVAL '1 hour')
+        ", &[&uid, &RETENTION]) {
+            return Err(HecateError::from_db(err));
+        }
+
+        match trans.execute("
+            INSERT INTO idempotency (key, uid, delta, ids, created)
+                VALUES ($1, $2, $3, $4, NOW())
+                ON CONFLICT (key, uid) DO NOTHING
+        ", &[&key, &uid, &delta, &ids]) {
+            Ok(0) => Err(HecateError::new(409, String::from("A request with this Idempotency-Key is already in progress"), None)),
+            Ok(_) => Ok(Some(Record {
+                delta: *delta,
+                ids: ids,
+                replayed: false
+            })),
+            Err(err) => Err(HecateError::from_db(err))
+        }
+    }
+}
+
+///
+/// Wrap a response to report the delta & features of an idempotent write
+///
+pub struct Idempotent<R>(pub R, pub Option<Record>);
+
+impl<'r, R: rocket::response::Responder<'r>> rocket::response::Responder<'r> for Idempotent<R> {
+    fn respond_to(self, request: &rocket::Request) -> rocket::response::Result<'r> {
+        let mut response = self.0.respond_to(request)?;
+
+        if let Some(record) = self.1 {
+            response.set_raw_header("X-Hecate-Delta", record.delta.to_string());
+            response.set_raw_header("X-Hecate-Features", record.ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(","));
+            response.set_raw_header("Idempotent-Replayed", record.replayed.to_string());
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        assert_eq!(Key(None).validate().unwrap(), None);
+        assert_eq!(Key(Some(String::from("abc"))).validate().unwrap(), Some(&String::from("abc")));
+        assert!(Key(Some(String::new())).validate().is_err());
+        assert!(Key(Some("a".repeat(MAX_LENGTH + 1))).validate().is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use postgres;
+use std::collections::HashMap;
+use crate::err::HecateError;
+
+#[derive(Serialize, Deserialize)]
+pub struct Index {
+    id: Option<i64>,
+    property: String,
+    kind: String
+}
+
+impl Index {
+    pub fn new(id: i64, property: String, kind: String) -> Self {
+        Index {
+            id: Some(id),
+            property: property,
+            kind: kind
+        }
+    }
+}
+
+///
+/// Split a dot separated property path into its keys
+///
+/// Paths are written directly into index DDL and the matching filter
+/// expressions (Postgres will only use an expression index when the query
+/// repeats the expression verbatim) so keys are restricted to a conservative
+/// character set to prevent SQL injection
+///
+pub fn path(property: &str) -> Result<Vec<String>, HecateError> {
+    let keys: Vec<String> = property.split('.').map(|key| String::from(key)).collect();
+
+    for key in &keys {
+        if key.len() == 0 || key.len() > 64 || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':' || c == '-') {
+            return Err(HecateError::new(400, String::from("Invalid Property - keys may only contain a-z, 0-9, _, :, and -"), None));
+        }
+    }
+
+    Ok(keys)
+}
+
+///
+/// Return the SQL expression selecting the given property path as JSONB
+///
+pub fn expression(keys: &Vec<String>) -> String {
+    format!("(props #> '{{{}}}'::TEXT[])", keys.join(","))
+}
+
+pub fn is_valid_kind(kind: &String) -> bool {
+    kind == "gin" || kind == "expression"
+}
+
+pub fn list(conn: &impl postgres::GenericConnection) -> Result<Vec<Index>, HecateError> {
+    match conn.query("
+        SELECT
+            id,
+            property,
+            kind
+        FROM
+            indexes
+        ORDER BY
+            id
+    ", &[]) {
+        Ok(results) => {
+            let mut indexes: Vec<Index> = Vec::with_capacity(results.len());
+
+            for result in results.iter() {
+                indexes.push(Index::new(result.get(0), result.get(1), result.get(2)));
+            }
+
+            Ok(indexes)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Return a map of every indexed property path to the kinds of index on it
+///
+pub fn indexed(conn: &impl postgres::GenericConnection) -> Result<HashMap<String, Vec<String>>, HecateError> {
+    let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+
+    for index in list(conn)? {
+        paths.entry(index.property).or_insert(Vec::new()).push(index.kind);
+    }
+
+    Ok(paths)
+}
+
+pub fn create(conn: &impl postgres::GenericConnection, index: serde_json::Value) -> Result<Index, HecateError> {
+    let mut index: Index = match serde_json::from_value(index) {
+        Ok(index) => index,
+        Err(err) => { return Err(HecateError::new(400, String::from("Invalid index JSON"), Some(err.to_string()))); }
+    };
+
+    if !is_valid_kind(&index.kind) {
+        return Err(HecateError::new(400, String::from("Invalid Index Kind - must be one of 'gin' or 'expression'"), None));
+    }
+
+    let keys = path(&index.property)?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    let id: i64 = match trans.query("
+        INSERT INTO indexes (property, kind, created)
+            VALUES (
+                $1,
+                $2,
+                NOW()
+            )
+            ON CONFLICT DO NOTHING
+            RETURNING id
+    ", &[&index.property, &index.kind]) {
+        Ok(results) => {
+            if results.len() == 0 {
+                return Err(HecateError::new(400, String::from("Index Already Exists"), None));
+            }
+
+            results.get(0).get(0)
+        },
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    //The index name is derived from the registry id so no user input is used as an identifier
+    let ddl = match index.kind.as_str() {
+        "gin" => format!("CREATE INDEX geo_props_idx_{} ON geo USING GIN ({})", id, expression(&keys)),
+        _ => format!("CREATE INDEX geo_props_idx_{} ON geo ({})", id, expression(&keys))
+    };
+
+    match trans.execute(&*ddl, &[]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.commit() {
+        Ok(_) => {
+            index.id = Some(id);
+
+            Ok(index)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+pub fn delete(conn: &impl postgres::GenericConnection, id: i64) -> Result<bool, HecateError> {
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.execute("
+        DELETE FROM indexes
+        WHERE id = $1
+    ", &[&id]) {
+        Ok(0) => { return Err(HecateError::new(404, String::from("Index Not Found"), None)); },
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.execute(&*format!("DROP INDEX IF EXISTS geo_props_idx_{}", id), &[]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.commit() {
+        Ok(_) => Ok(true),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Return a SQL clause testing the given property path for equality with a JSONB parameter
+///
+/// The clause always repeats the exact expression of any index on the
+/// path. A GIN index cannot answer equality so a containment check, which
+/// equality implies, is added alongside to allow the planner to use it
+///
+pub fn equals(keys: &Vec<String>, kinds: Option<&Vec<String>>, param: usize) -> String {
+    let expr = expression(keys);
+
+    match kinds {
+        Some(kinds) if kinds.contains(&String::from("gin")) && !kinds.contains(&String::from("expression")) => {
+            format!("({expr} @> ${param}::JSONB AND {expr} = ${param}::JSONB)", expr = expr, param = param)
+        },
+        _ => format!("{} = ${}::JSONB", expr, param)
+    }
+}
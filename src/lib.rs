@@ -9,6 +9,7 @@ pub static POSTGIS: f64 = 2.4;
 #[macro_use] extern crate rocket;
 
 pub mod validate;
+pub mod bbox;
 pub mod err;
 pub mod meta;
 pub mod stats;
@@ -16,6 +17,7 @@ pub mod delta;
 pub mod mvt;
 pub mod feature;
 pub mod bounds;
+pub mod gpkg;
 pub mod clone;
 pub mod stream;
 pub mod style;
@@ -24,8 +26,25 @@ pub mod user;
 pub mod auth;
 pub mod worker;
 pub mod webhooks;
+pub mod options;
+pub mod index;
+pub mod filter;
+pub mod topojson;
+pub mod audit;
+pub mod limit;
+pub mod timing;
+pub mod proxy;
+pub mod retry;
+pub mod idempotency;
+pub mod condition;
+pub mod tenant;
+pub mod routing;
+pub mod snapshot;
+pub mod alert;
+pub mod range;
 
 use auth::ValidAuth;
+use options::ValidOptions;
 use err::HecateError;
 
 //Postgres Connection Pooling
@@ -34,7 +53,7 @@ use r2d2_postgres::{PostgresConnectionManager, TlsMode};
 
 use rand::prelude::*;
 
-use std::io::{Cursor, Read};
+use std::io::{BufReader, Cursor, Read};
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use rocket::http::Status as HTTPStatus;
@@ -67,7 +86,8 @@ pub fn start(
     port: Option<u16>,
     workers: Option<u16>,
     schema: Option<serde_json::value::Value>,
-    auth: Option<auth::CustomAuth>
+    auth: Option<auth::CustomAuth>,
+    options: Option<options::Options>
 ) {
     env_logger::init();
 
@@ -86,8 +106,23 @@ pub fn start(
         }
     };
 
-    let db_replica: DbReplica = DbReplica::new(Some(database.replica.iter().map(|db| init_pool(&db)).collect()));
-    let db_sandbox: DbSandbox = DbSandbox::new(Some(database.sandbox.iter().map(|db| init_pool(&db)).collect()));
+    let options: options::Options = match options {
+        None => options::Options::new(),
+        Some(options) => {
+            match options.is_valid() {
+                Err(err_msg) => {
+                    println!("ERROR: {}", err_msg);
+                    std::process::exit(1);
+                },
+                Ok(_) => ()
+            };
+
+            options
+        }
+    };
+
+    let db_replica: DbReplica = DbReplica::new(Some(database.replica.iter().map(|db| init_pool(&db, &options.database())).collect()));
+    let db_sandbox: DbSandbox = DbSandbox::new(Some(database.sandbox.iter().map(|db| init_pool(&db, &options.database())).collect()));
 
     let limits = Limits::new()
         .limit("json", 20971520)
@@ -112,15 +147,36 @@ pub fn start(
         _ => ()
     };
 
-    let worker = worker::Worker::new(database.main.clone());
+    let flights = mvt::Flights::new();
+
+    let worker = worker::Worker::new(database.main.clone(), options.database(), options.tiles(), flights.clone());
+
+    let db_readwrite = DbReadWrite::new(init_pool(&database.main, &options.database()));
 
-    rocket::custom(config)
-        .manage(DbReadWrite::new(init_pool(&database.main)))
+    if let Some(idle) = options.session().idle {
+        auth::Reaper::start(db_readwrite.pool(), idle);
+    }
+
+    let rocket = rocket::custom(config)
+        .manage(db_readwrite)
         .manage(db_replica)
         .manage(db_sandbox)
         .manage(schema)
-        .manage(auth_rules)
+        .manage(auth::AuthContainer::new(auth_rules))
         .manage(worker)
+        .attach(options.routing())
+        .attach(options.headers())
+        .attach(options.attribution())
+        .attach(options.exempt())
+        .attach(audit::Impersonation)
+        .attach(audit::Denials)
+        .attach(auth::TokenRefresh)
+        .attach(timing::ServerTiming::new(&options.timing()))
+        .manage(limit::Limiter::new(&options.limits()))
+        .manage(flights)
+        .manage(stats::SummaryCache::new())
+        .manage(alert::Alerts::new(&options.alerts()))
+        .manage(options)
         .mount("/", routes![
             index
         ])
@@ -130,25 +186,41 @@ pub fn start(
         ])
         .mount("/api", routes![
             server,
+            server_info,
             meta_list,
             meta_get,
             meta_delete,
             meta_set,
             schema_get,
             auth_get,
+            auth_config_get,
+            auth_config_set,
+            auth_denials,
             stats_get,
             stats_regen,
+            stats_numeric,
+            stats_values,
+            stats_summary,
             mvt_get,
             mvt_meta,
+            mvt_debug,
+            mvt_cover,
             mvt_wipe,
             mvt_regen,
             users,
             user_self,
+            user_styles,
+            user_active,
             user_info,
+            user_update,
             user_create,
             user_set_admin,
             user_delete_admin,
+            user_impersonate,
+            user_password_reset,
+            user_password_change,
             user_create_session,
+            user_create_token,
             user_delete_session,
             style_create,
             style_patch,
@@ -156,19 +228,50 @@ pub fn start(
             style_private,
             style_delete,
             style_get,
+            style_default,
+            style_set_default,
+            style_delete_default,
             style_list_public,
             style_list_user,
             delta,
             delta_list,
+            delta_stream,
             feature_action,
+            feature_split,
+            feature_restore,
+            feature_set_geometry,
             features_action,
+            features_delete,
+            features_merge,
+            features_replace,
             feature_get,
             feature_query,
             feature_get_history,
+            features_history,
+            feature_get_bounds,
+            feature_get_related,
+            feature_get_blame,
+            feature_get_version_delta,
+            feature_tags,
+            feature_tag_add,
+            feature_tag_remove,
+            feature_comments,
+            feature_comment_create,
+            feature_comment_delete,
+            feature_comment_resolve,
+            feature_comment_unresolve,
+            data_purge,
             features_query,
+            features_count,
+            features_cluster,
+            features_revalidate,
+            index_list,
+            index_create,
+            index_delete,
             bounds,
             bounds_stats,
             bounds_meta,
+            bounds_download,
             bounds_get,
             bounds_set,
             bounds_delete,
@@ -177,8 +280,13 @@ pub fn start(
             webhooks_delete,
             webhooks_update,
             webhooks_create,
+            webhooks_ping,
+            webhooks_backfill,
+            webhooks_backfill_get,
             clone_get,
             clone_query,
+            snapshot_get,
+            snapshot_restore,
             osm_capabilities,
             osm_06capabilities,
             osm_user,
@@ -187,10 +295,16 @@ pub fn start(
             osm_changeset_modify,
             osm_changeset_upload,
             osm_changeset_close
-        ])
+        ]);
+
+    let routes = routing::Routes::new(rocket.routes());
+
+    rocket
+        .manage(routes)
         .register(catchers![
            not_authorized,
            not_found,
+           internal_error,
         ]).launch();
 }
 
@@ -198,10 +312,23 @@ pub fn start(
 pub type PostgresPool = Pool<PostgresConnectionManager>;
 pub type PostgresPooledConnection = PooledConnection<PostgresConnectionManager>;
 
-fn init_pool(database: &str) -> r2d2::Pool<r2d2_postgres::PostgresConnectionManager> {
+///
+/// Set the search_path of each pooled connection as it is established so that
+/// every query resolves tables in the configured schema
+///
+#[derive(Debug)]
+struct SearchPath(options::Database);
+
+impl r2d2::CustomizeConnection<postgres::Connection, postgres::Error> for SearchPath {
+    fn on_acquire(&self, conn: &mut postgres::Connection) -> Result<(), postgres::Error> {
+        self.0.search_path(conn)
+    }
+}
+
+fn init_pool(database: &str, options: &options::Database) -> r2d2::Pool<r2d2_postgres::PostgresConnectionManager> {
     //Create Postgres Connection Pool
     let manager = ::r2d2_postgres::PostgresConnectionManager::new(format!("postgres://{}", database), TlsMode::None).unwrap();
-    match r2d2::Pool::builder().max_size(15).build(manager) {
+    match r2d2::Pool::builder().max_size(15).connection_customizer(Box::new(SearchPath(options.clone()))).build(manager) {
         Ok(pool) => pool,
         Err(_) => {
             println!("ERROR: Failed to connect to database");
@@ -216,6 +343,22 @@ impl DbReplica {
         DbReplica(database)
     }
 
+    ///
+    /// Return a handle to one of the replica pools for long lived
+    /// consumers that need to check out connections over time
+    ///
+    fn pool(&self) -> Result<r2d2::Pool<r2d2_postgres::PostgresConnectionManager>, HecateError> {
+        match self.0 {
+            None => Err(HecateError::new(503, String::from("No Database Replica Connection"), None)),
+            Some(ref db_replica) => {
+                let mut rng = thread_rng();
+                let db_replica_it = rng.gen_range(0, db_replica.len());
+
+                Ok(db_replica.get(db_replica_it).unwrap().clone())
+            }
+        }
+    }
+
     fn get(&self) -> Result<r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, HecateError> {
         match self.0 {
             None => Err(HecateError::new(503, String::from("No Database Replica Connection"), None)),
@@ -260,6 +403,13 @@ impl DbReadWrite {
         DbReadWrite(database)
     }
 
+    ///
+    /// Return a handle to the pool for work that outlives the request
+    ///
+    fn pool(&self) -> r2d2::Pool<r2d2_postgres::PostgresConnectionManager> {
+        self.0.clone()
+    }
+
     fn get(&self) -> Result<r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, HecateError> {
         match self.0.get() {
             Ok(conn) => Ok(conn),
@@ -271,7 +421,114 @@ impl DbReadWrite {
 #[derive(FromForm, Debug)]
 struct Filter {
     filter: Option<String>,
-    limit: Option<i16>
+    limit: Option<i64>,
+    meta: Option<bool>,
+    sort: Option<String>,
+    envelope: Option<bool>
+}
+
+#[derive(FromForm, Debug)]
+struct UserFilter {
+    filter: Option<String>,
+    limit: Option<i64>,
+    fields: Option<String>,
+    envelope: Option<bool>
+}
+
+#[derive(FromForm, Debug)]
+struct StyleList {
+    limit: Option<i64>,
+    envelope: Option<bool>
+}
+
+///
+/// A list response, echoing the effective limit of the list in the `X-Hecate-Limit` header
+///
+struct Listed<R>(R, Option<i64>);
+
+impl<'r, R: rocket::response::Responder<'r>> rocket::response::Responder<'r> for Listed<R> {
+    fn respond_to(self, request: &rocket::Request) -> rocket::response::Result<'r> {
+        let mut response = self.0.respond_to(request)?;
+
+        if let Some(limit) = self.1 {
+            response.set_raw_header("X-Hecate-Limit", limit.to_string());
+        }
+
+        Ok(response)
+    }
+}
+
+impl Listed<Json<serde_json::Value>> {
+    ///
+    /// A list without cursors, wrapped in an envelope if requested
+    ///
+    fn new(data: serde_json::Value, limit: Option<i64>, wrap: Option<bool>) -> Self {
+        if wrap == Some(true) {
+            Listed(Json(envelope(data, limit, true, None, None)), limit)
+        } else {
+            Listed(Json(data), limit)
+        }
+    }
+}
+
+///
+/// Wrap a list in an envelope describing the page it is from for `?envelope=true`
+///
+/// The total is only known when the page starts at the beginning of the list
+/// and is not full, as no additional query is made to count the list
+///
+fn envelope(data: serde_json::Value, limit: Option<i64>, first: bool, next: Option<i64>, prev: Option<i64>) -> serde_json::Value {
+    let len = data.as_array().map(|data| data.len() as i64).unwrap_or(0);
+
+    let total = match limit {
+        Some(limit) if len >= limit => None,
+        _ if first => Some(len),
+        _ => None
+    };
+
+    json!({
+        "data": data,
+        "meta": {
+            "total": total,
+            "limit": limit,
+            "next": next,
+            "prev": prev
+        }
+    })
+}
+
+///
+/// The id of the last item of a full page, the cursor of the next page
+///
+fn next_cursor(data: &serde_json::Value, limit: Option<i64>) -> Option<i64> {
+    let data = data.as_array()?;
+
+    match limit {
+        Some(limit) if data.len() as i64 >= limit => data.last()?.get("id")?.as_i64(),
+        _ => None
+    }
+}
+
+///
+/// Resolve the effective limit of a list request from the requested limit, the
+/// configured list options and the built in default & maximum of the endpoint
+///
+fn list_limit(options: &options::Options, requested: Option<i64>, default: Option<i64>, max: Option<i64>) -> Result<Option<i64>, HecateError> {
+    match requested {
+        Some(requested) if requested < 1 => Err(HecateError::new(400, String::from("Invalid Limit - must be a positive integer"), None)),
+        _ => Ok(options.list().limit(requested, default, max))
+    }
+}
+
+///
+/// Reject a request for an output format that is disabled on this server
+///
+fn allows_format(options: &options::Options, format: &str) -> Result<(), HecateError> {
+    if options.formats().allows(format) {
+        Ok(())
+    } else {
+        Err(HecateError::new(406, format!("Format Not Acceptable - '{}' is disabled on this server", format), None))
+    }
 }
 
 #[catch(401)]
@@ -280,8 +537,13 @@ fn not_authorized() -> HecateError {
 }
 
 #[catch(404)]
-fn not_found() -> HecateError {
-    HecateError::new(404, String::from("Resource Not Found"), None)
+fn not_found(request: &rocket::Request) -> HecateError {
+    routing::unmatched(request)
+}
+
+#[catch(500)]
+fn internal_error() -> HecateError {
+    HecateError::new(500, String::from("Internal Server Error"), None)
 }
 
 
@@ -292,7 +554,7 @@ fn index() -> &'static str { "Hello World!" }
 fn server(
     mut auth: auth::Auth,
     conn: State<DbReplica>,
-    auth_rules: State<auth::CustomAuth>
+    auth_rules: auth::Rules
 ) -> Result<Json<serde_json::Value>, HecateError> {
     auth_rules.allows_server(&mut auth, &*conn.get()?)?;
 
@@ -301,11 +563,27 @@ fn server(
     })))
 }
 
+#[get("/server")]
+fn server_info(
+    mut auth: auth::Auth,
+    conn: State<DbReplica>,
+    auth_rules: auth::Rules
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_server(&mut auth, &*conn)?;
+
+    Ok(Json(json!({
+        "version": VERSION,
+        "read_only": false,
+        "notice": meta::get(&*conn, &String::from(meta::NOTICE))?
+    })))
+}
+
 #[get("/meta")]
 fn meta_list(
     mut auth: auth::Auth,
     conn: State<DbReplica>,
-    auth_rules: State<auth::CustomAuth>
+    auth_rules: auth::Rules
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_meta_list(&mut auth, &*conn)?;
@@ -317,7 +595,7 @@ fn meta_list(
 fn meta_get(
     mut auth: auth::Auth,
     conn: State<DbReplica>,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     worker: State<worker::Worker>,
     key: String
 ) -> Result<Json<serde_json::Value>, HecateError> {
@@ -333,7 +611,7 @@ fn meta_get(
 fn meta_delete(
     mut auth: auth::Auth,
     conn: State<DbReadWrite>,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     worker: State<worker::Worker>,
     key: String
 ) -> Result<Json<serde_json::Value>, HecateError> {
@@ -349,7 +627,7 @@ fn meta_delete(
 fn meta_set(
     mut auth: auth::Auth,
     conn: State<DbReadWrite>,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     worker: State<worker::Worker>,
     key: String,
     body: Json<serde_json::Value>
@@ -372,43 +650,116 @@ fn staticsrv(file: PathBuf) -> Option<NamedFile> {
     NamedFile::open(Path::new("web/dist/").join(file)).ok()
 }
 
+///
+/// Respond with a tile, a tile without features is returned as a 204 if
+/// configured to do so
+///
+fn mvt_response(tile: Vec<u8>, tiles: &options::Tiles) -> Response<'static> {
+    let mut mvt_response = Response::new();
+
+    if tile.len() == 0 && tiles.is_no_content() {
+        mvt_response.set_status(HTTPStatus::NoContent);
+        return mvt_response;
+    }
+
+    mvt_response.set_status(HTTPStatus::Ok);
+    mvt_response.set_sized_body(Cursor::new(tile));
+    mvt_response.set_raw_header("Content-Type", "application/x-protobuf");
+    mvt_response
+}
+
 #[get("/tiles/<z>/<x>/<y>")]
 fn mvt_get(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    flights: State<mvt::Flights>,
     z: u8, x: u32, y: u32
 ) -> Result<Response<'static>, HecateError> {
     let conn = conn.get()?;
 
     auth_rules.allows_mvt_get(&mut auth, &*conn)?;
 
-    if z > 17 { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
-
-    let tile = mvt::get(&*conn, z, x, y, false)?;
+    if !options.tiles().is_zoom(z) { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
 
-    let c = Cursor::new(tile);
+    let tile = mvt::get(&*conn, &flights, z, x, y, false, &options.tiles(), &auth.scope()?)?;
 
-    let mut mvt_response = Response::new();
-    mvt_response.set_status(HTTPStatus::Ok);
-    mvt_response.set_sized_body(c);
-    mvt_response.set_raw_header("Content-Type", "application/x-protobuf");
-    Ok(mvt_response)
+    Ok(mvt_response(tile, &options.tiles()))
 }
 
 #[get("/tiles/<z>/<x>/<y>/meta")]
 fn mvt_meta(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
     z: u8, x: u32, y: u32
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_mvt_meta(&mut auth, &*conn)?;
 
-    if z > 17 { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
+    if !options.tiles().is_zoom(z) { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
+
+    Ok(Json(mvt::meta(&*conn, z, x, y, &auth.scope()?)?))
+}
+
+#[derive(FromForm, Debug)]
+struct TileCover {
+    bbox: Option<String>,
+    feature: Option<i64>,
+    minzoom: Option<u8>,
+    maxzoom: Option<u8>
+}
+
+#[get("/tiles/cover?<opts..>")]
+fn mvt_cover(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    opts: Form<TileCover>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_mvt_get(&mut auth, &*conn)?;
+
+    let maxzoom = match opts.maxzoom {
+        Some(maxzoom) => maxzoom,
+        None => { return Err(HecateError::new(400, String::from("maxzoom param required"), None)); }
+    };
+    let minzoom = opts.minzoom.unwrap_or(0);
+
+    let tiles = match (&opts.bbox, &opts.feature) {
+        (Some(bbox), None) => mvt::cover::bbox(&bbox::Bbox::parse(bbox)?, minzoom, maxzoom)?,
+        (None, Some(id)) => mvt::cover::feature(&*conn, id, minzoom, maxzoom, &auth.scope()?)?,
+        (Some(_), Some(_)) => { return Err(HecateError::new(400, String::from("bbox and feature params cannot be used together"), None)); },
+        (None, None) => { return Err(HecateError::new(400, String::from("bbox or feature param required"), None)); }
+    };
+
+    Ok(Json(json!(tiles)))
+}
+
+#[derive(FromForm, Debug)]
+struct TileDebug {
+    coordinates: Option<String>
+}
+
+#[get("/tiles/<z>/<x>/<y>/debug?<opts..>")]
+fn mvt_debug(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    z: u8, x: u32, y: u32,
+    opts: Form<TileDebug>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_mvt_get(&mut auth, &*conn)?;
 
-    Ok(Json(mvt::meta(&*conn, z, x, y)?))
+    if !options.tiles().is_zoom(z) { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
+
+    let coordinates = mvt::Coordinates::parse(&opts.coordinates)?;
+
+    Ok(Json(mvt::debug(&*conn, &z, &x, &y, coordinates, &options.tiles(), &auth.scope()?)?))
 }
 
 
@@ -416,7 +767,7 @@ fn mvt_meta(
 fn mvt_wipe(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
+    auth_rules: auth::Rules
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_mvt_delete(&mut auth, &*conn)?;
@@ -428,23 +779,22 @@ fn mvt_wipe(
 fn mvt_regen(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    limiter: State<limit::Limiter>,
+    options: State<options::Options>,
+    flights: State<mvt::Flights>,
     z: u8, x: u32, y: u32
 ) -> Result<Response<'static>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_mvt_regen(&mut auth, &*conn)?;
 
-    if z > 17 { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
+    let _permit = limiter.acquire(limit::Route::Regen, &auth)?;
 
-    let tile = mvt::get(&*conn, z, x, y, true)?;
+    if !options.tiles().is_zoom(z) { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
 
-    let c = Cursor::new(tile);
+    let tile = mvt::get(&*conn, &flights, z, x, y, true, &options.tiles(), &auth.scope()?)?;
 
-    let mut mvt_response = Response::new();
-    mvt_response.set_status(HTTPStatus::Ok);
-    mvt_response.set_sized_body(c);
-    mvt_response.set_raw_header("Content-Type", "application/x-protobuf");
-    Ok(mvt_response)
+    Ok(mvt_response(tile, &options.tiles()))
 }
 
 #[derive(FromForm, Debug)]
@@ -457,21 +807,79 @@ struct User {
 #[derive(FromForm, Debug)]
 struct Map {
     bbox: Option<String>,
-    point: Option<String>
+    point: Option<String>,
+    buffer: Option<f64>,
+    prop: Option<String>,
+    filter: Option<String>,
+    tag: Option<String>,
+    format: Option<String>,
+    geometry: Option<String>,
+    geometry_only: Option<bool>,
+    quantization: Option<u32>,
+    modified_since: Option<String>,
+    order: Option<String>,
+    after: Option<i64>,
+    limit: Option<i64>,
+    envelope: Option<bool>
+}
+
+impl Map {
+    ///
+    /// Combine the bbox, prop, and filter params into a single filter that
+    /// all given params must match
+    ///
+    fn to_filter(&self) -> Result<filter::Expr, HecateError> {
+        let mut exprs: Vec<filter::Expr> = Vec::new();
+
+        match self.bbox {
+            None => (),
+            Some(ref bbox) => exprs.push(filter::Expr::Intersects(bbox::Bbox::parse(bbox)?.to_vec()))
+        };
+
+        match self.prop {
+            None => (),
+            Some(ref prop) => exprs.push(filter::prop(prop)?)
+        };
+
+        match self.filter {
+            None => (),
+            Some(ref query) => exprs.push(filter::parse(query)?)
+        };
+
+        match self.tag {
+            None => (),
+            Some(ref tag) => {
+                feature::tags::validate(tag)?;
+                exprs.push(filter::Expr::Tagged(tag.clone()));
+            }
+        };
+
+        if exprs.len() == 0 {
+            return Err(HecateError::new(400, String::from("key, point, prop, filter, or tag param must be used"), None));
+        }
+
+        let mut expr = exprs.remove(0);
+        for next in exprs {
+            expr = filter::Expr::And(Box::new(expr), Box::new(next));
+        }
+
+        Ok(expr)
+    }
 }
 
 #[get("/user/create?<user..>")]
 fn user_create(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     worker: State<worker::Worker>,
+    options: State<options::Options>,
     user: Form<User>
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_user_create(&mut auth, &*conn)?;
 
-    user::create(&*conn, &user.username, &user.password, &user.email)?;
+    user::create(&*conn, &options.user(), &user.username, &user.password, &user.email)?;
 
     worker.queue(worker::Task::new(worker::TaskType::User(user.username.clone())));
 
@@ -481,15 +889,18 @@ fn user_create(
 #[get("/users?<filter..>")]
 fn users(conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    filter: Form<Filter>
-) -> Result<Json<serde_json::Value>, HecateError> {
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    filter: Form<UserFilter>
+) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_user_list(&mut auth, &*conn)?;
 
+    let limit = list_limit(&options, filter.limit, Some(100), Some(100))?;
+
     match &filter.filter {
-        Some(search) => Ok(Json(json!(user::filter(&*conn, &search, &filter.limit)?))),
-        None => Ok(Json(json!(user::list(&*conn, &filter.limit)?)))
+        Some(search) => Ok(Listed::new(json!(user::filter(&*conn, &search, &limit, &filter.fields)?), limit, filter.envelope)),
+        None => Ok(Listed::new(json!(user::list(&*conn, &limit, &filter.fields)?), limit, filter.envelope))
     }
 }
 
@@ -497,21 +908,70 @@ fn users(conn: State<DbReplica>,
 fn user_info(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
-    auth_rules.is_admin(&mut auth, &*conn)?;
+    auth_rules.allows_user_info(&mut auth, &*conn)?;
+
+    //Only admins can view the profile of another user
+    if auth.uid != Some(id) {
+        auth_rules.is_admin(&mut auth, &*conn)?;
+    }
 
     Ok(Json(user::info(&*conn, &id)?))
 }
 
+#[patch("/user/<id>", format="application/json", data="<body>")]
+fn user_update(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_user_update(&mut auth, &*conn)?;
+
+    //Only admins can update the profile of another user
+    if auth.uid != Some(id) {
+        auth_rules.is_admin(&mut auth, &*conn)?;
+    }
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let profile: serde_json::Value = match serde_json::from_str(&*body_str) {
+        Ok(profile) => profile,
+        Err(_) => { return Err(HecateError::new(400, String::from("Invalid User JSON"), None)); }
+    };
+
+    Ok(Json(user::update(&*conn, &id, profile)?))
+}
+
 #[put("/user/<id>/admin")]
 fn user_set_admin(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -525,7 +985,7 @@ fn user_set_admin(
 fn user_delete_admin(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -535,93 +995,46 @@ fn user_delete_admin(
     Ok(Json(json!(user::delete_admin(&*conn, &id)?)))
 }
 
-#[get("/user/info")]
-fn user_self(
-    conn: State<DbReplica>,
-    mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
-) -> Result<Json<serde_json::Value>, HecateError> {
-    let conn = conn.get()?;
-    auth_rules.allows_user_info(&mut auth, &*conn)?;
-
-    let uid = auth.uid.unwrap();
-
-    Ok(Json(user::info(&*conn, &uid)?))
-}
-
-#[get("/user/session")]
-fn user_create_session(
+#[post("/user/<id>/impersonate")]
+fn user_impersonate(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    mut cookies: Cookies
+    auth_rules: auth::Rules,
+    id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
-    auth_rules.allows_user_create_session(&mut auth, &*conn)?;
+    auth_rules.is_admin(&mut auth, &*conn)?;
 
-    let uid = auth.uid.unwrap();
+    let admin = auth.uid.unwrap();
 
-    let token = user::create_token(&*conn, &uid)?;
+    let token = user::create_impersonation_token(&*conn, &id, &admin)?;
 
-    cookies.add(Cookie::build("session", token)
-        .path("/")
-        .http_only(true)
-        .finish()
-    );
+    audit::log(&*conn, Some(id), Some(admin), "impersonate", json!({}))?;
 
-    Ok(Json(json!(uid)))
+    Ok(Json(json!({
+        "uid": id,
+        "token": token
+    })))
 }
 
-#[delete("/user/session")]
-fn user_delete_session(
-    conn: State<DbReadWrite>,
-    auth: auth::Auth,
-    mut cookies: Cookies
-) -> Result<Json<serde_json::Value>, HecateError> {
-    // there is no auth check here for deleting tokens, the web interface should
-    // always be able to de-authenticate to prevent errors
-
-    let token = match cookies.get("session") {
-        Some(session) => Some(String::from(session.value())),
-        None => None
-    };
-
-    cookies.remove(Cookie::build("session", String::from(""))
-        .path("/")
-        .http_only(true)
-        .finish()
-    );
-
-    match token {
-        Some(token) => {
-            let uid = match auth.uid {
-                Some(uid) => uid,
-                None => { return Ok(Json(json!(true))); }
-            };
-
-            match user::destroy_token(&*conn.get()?, &uid, &token) {
-                _ => {
-                    Ok(Json(json!(true)))
-                }
-            }
-        },
-        None => Ok(Json(json!(true)))
-    }
+#[derive(Deserialize, Debug)]
+struct PasswordReset {
+    password: Option<String>
 }
 
-#[post("/style", format="application/json", data="<body>")]
-fn style_create(
+#[post("/user/<id>/password/reset", data="<body>")]
+fn user_password_reset(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    worker: State<worker::Worker>,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    id: i64,
     body: Data
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
-    auth_rules.allows_style_create(&mut auth, &*conn)?;
-    let uid = auth.uid.unwrap();
+    auth_rules.is_admin(&mut auth, &*conn)?;
 
     let body_str: String;
     {
@@ -642,8 +1055,247 @@ fn style_create(
         }
     }
 
-    let style_id = style::create(&*conn, &uid, &body_str)?;
-    worker.queue(worker::Task::new(worker::TaskType::Style(style_id)));
+    let reset = if body_str.trim().is_empty() {
+        PasswordReset { password: None }
+    } else {
+        match serde_json::from_str(&body_str) {
+            Ok(reset) => reset,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid Password Reset JSON"), None)); }
+        }
+    };
+
+    let temporary = user::reset_password(&*conn, &options.user(), &id, &reset.password)?;
+
+    audit::log(&*conn, Some(id), auth.uid, "password_reset", json!({}))?;
+
+    Ok(Json(match temporary {
+        None => json!({ "id": id }),
+        Some(password) => json!({ "id": id, "password": password })
+    }))
+}
+
+#[derive(Deserialize, Debug)]
+struct PasswordChange {
+    password: String
+}
+
+#[post("/user/password", format="application/json", data="<body>")]
+fn user_password_change(
+    conn: State<DbReadWrite>,
+    auth: auth::Auth,
+    options: State<options::Options>,
+    body: Json<PasswordChange>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    //The current password is checked here rather than by the auth rules, which
+    //reject the credentials of a user whose password has been reset
+    let (username, current) = match auth.basic {
+        Some(ref basic) => basic,
+        None => { return Err(HecateError::new(401, String::from("You must be logged in to access this resource"), None)); }
+    };
+
+    Ok(Json(json!(user::change_password(&*conn, &options.user(), username, current, &body.password)?)))
+}
+
+#[get("/user/info")]
+fn user_self(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_user_info(&mut auth, &*conn)?;
+
+    let uid = auth.uid.unwrap();
+
+    Ok(Json(user::info(&*conn, &uid)?))
+}
+
+#[derive(FromForm, Debug)]
+struct UserActive {
+    window: Option<i32>
+}
+
+#[get("/user/active?<opts..>")]
+fn user_active(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    opts: Form<UserActive>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.is_admin(&mut auth, &*conn)?;
+
+    let window = opts.window.unwrap_or(user::ACTIVE_WINDOW);
+
+    if window < 1 {
+        return Err(HecateError::new(400, String::from("Invalid window - must be a number of seconds greater than 0"), None));
+    }
+
+    Ok(Json(user::active(&*conn, &window)?))
+}
+
+#[get("/user/styles?<opts..>")]
+fn user_styles(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    opts: Form<StyleList>
+) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_user_info(&mut auth, &*conn)?;
+
+    let uid = match auth.uid {
+        Some(uid) => uid,
+        None => { return Err(HecateError::new(401, String::from("You must be logged in to access this resource"), None)); }
+    };
+
+    let limit = list_limit(&options, opts.limit, None, None)?;
+
+    Ok(Listed::new(json!(style::list_user(&*conn, &uid, &limit)?), limit, opts.envelope))
+}
+
+#[get("/user/session")]
+fn user_create_session(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    client: proxy::Client,
+    mut cookies: Cookies
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_user_create_session(&mut auth, &*conn)?;
+
+    if auth.impersonator.is_some() {
+        return Err(HecateError::new(403, String::from("Impersonated sessions cannot create new sessions"), None));
+    }
+
+    let uid = auth.uid.unwrap();
+
+    let token = user::create_token(&*conn, &uid, &auth::TokenKind::Session, &options.session(), &None)?;
+
+    cookies.add(Cookie::build("session", token)
+        .path("/")
+        .http_only(true)
+        .secure(client.tls)
+        .finish()
+    );
+
+    Ok(Json(json!(uid)))
+}
+
+#[derive(FromForm, Debug)]
+struct TokenOptions {
+    source: Option<String>
+}
+
+#[post("/user/token?<opts..>")]
+fn user_create_token(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    opts: Form<TokenOptions>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_user_create_session(&mut auth, &*conn)?;
+
+    if auth.impersonator.is_some() {
+        return Err(HecateError::new(403, String::from("Impersonated sessions cannot create new tokens"), None));
+    }
+
+    if let Some(ref source) = opts.source {
+        feature::provenance::validate_source(source)?;
+    }
+
+    let uid = auth.uid.unwrap();
+    let session = options.session();
+
+    let token = user::create_token(&*conn, &uid, &auth::TokenKind::Api, &session, &opts.source)?;
+
+    Ok(Json(json!({
+        "token": token,
+        "expiry": session.expiry(&auth::TokenKind::Api)
+    })))
+}
+
+#[delete("/user/session")]
+fn user_delete_session(
+    conn: State<DbReadWrite>,
+    auth: auth::Auth,
+    mut cookies: Cookies
+) -> Result<Json<serde_json::Value>, HecateError> {
+    // there is no auth check here for deleting tokens, the web interface should
+    // always be able to de-authenticate to prevent errors
+
+    let token = match cookies.get("session") {
+        Some(session) => Some(String::from(session.value())),
+        None => None
+    };
+
+    cookies.remove(Cookie::build("session", String::from(""))
+        .path("/")
+        .http_only(true)
+        .finish()
+    );
+
+    match token {
+        Some(token) => {
+            let uid = match auth.uid {
+                Some(uid) => uid,
+                None => { return Ok(Json(json!(true))); }
+            };
+
+            match user::destroy_token(&*conn.get()?, &uid, &token) {
+                _ => {
+                    Ok(Json(json!(true)))
+                }
+            }
+        },
+        None => Ok(Json(json!(true)))
+    }
+}
+
+#[post("/style", format="application/json", data="<body>")]
+fn style_create(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    worker: State<worker::Worker>,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_style_create(&mut auth, &*conn)?;
+    let uid = auth.uid.unwrap();
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let style_id = style::create(&*conn, &uid, &body_str)?;
+    worker.queue(worker::Task::new(worker::TaskType::Style(style_id)));
 
     Ok(Json(json!(style_id)))
 }
@@ -652,7 +1304,7 @@ fn style_create(
 fn style_public(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -667,7 +1319,7 @@ fn style_public(
 fn style_private(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -682,7 +1334,7 @@ fn style_private(
 fn style_patch(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     worker: State<worker::Worker>,
     id: i64,
     body: Data
@@ -716,22 +1368,30 @@ fn style_patch(
     Ok(Json(json!(style::update(&*conn, &uid, &id, &body_str)?)))
 }
 
-#[delete("/style/<id>")]
+#[derive(FromForm, Debug)]
+struct StyleDelete {
+    force: Option<bool>
+}
+
+#[delete("/style/<id>?<opts..>")]
 fn style_delete(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     worker: State<worker::Worker>,
-    id: i64
+    id: i64,
+    opts: Form<StyleDelete>
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
     auth_rules.allows_style_delete(&mut auth, &*conn)?;
     let uid = auth.uid.unwrap();
 
+    let deleted = style::delete(&*conn, &uid, &id, opts.force.unwrap_or(false))?;
+
     worker.queue(worker::Task::new(worker::TaskType::Style(id)));
 
-    Ok(Json(json!(style::delete(&*conn, &uid, &id)?)))
+    Ok(Json(json!(deleted)))
 }
 
 
@@ -739,50 +1399,106 @@ fn style_delete(
 fn style_get(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
     auth_rules.allows_style_get(&mut auth, &*conn)?;
 
-    Ok(Json(json!(style::get(&*conn, &auth.uid, &id)?)))
+    Ok(Json(json!(style::get(&*conn, &auth.uid, auth.has_admin_access(), &id, &auth.scope()?)?)))
 }
 
-#[get("/styles")]
-fn style_list_public(
+#[get("/style/default")]
+fn style_default(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
+    auth_rules: auth::Rules
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_style_get(&mut auth, &*conn)?;
+
+    Ok(Json(json!(style::get_default(&*conn, &auth.uid, auth.has_admin_access(), &auth.scope()?)?)))
+}
+
+#[post("/style/<id>/default")]
+fn style_set_default(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    worker: State<worker::Worker>,
+    id: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.is_admin(&mut auth, &*conn)?;
+
+    let set = style::set_default(&*conn, &id, &auth.scope()?)?;
+
+    worker.queue(worker::Task::new(worker::TaskType::Meta));
+
+    Ok(Json(json!(set)))
+}
+
+#[delete("/style/default")]
+fn style_delete_default(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    worker: State<worker::Worker>
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
+    auth_rules.is_admin(&mut auth, &*conn)?;
+
+    worker.queue(worker::Task::new(worker::TaskType::Meta));
+
+    Ok(Json(json!(meta::delete(&*conn, &String::from(style::DEFAULT))?)))
+}
+
+#[get("/styles?<opts..>")]
+fn style_list_public(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    opts: Form<StyleList>
+) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
+    let conn = conn.get()?;
+
     auth_rules.allows_style_list(&mut auth, &*conn)?;
 
-    Ok(Json(json!(style::list_public(&*conn)?)))
+    let limit = list_limit(&options, opts.limit, None, None)?;
+
+    Ok(Listed::new(json!(style::list_public(&*conn, &limit, &auth.scope()?)?), limit, opts.envelope))
 }
 
-#[get("/styles/<user>")]
+#[get("/styles/<user>?<opts..>")]
 fn style_list_user(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    user: i64
-) -> Result<Json<serde_json::Value>, HecateError> {
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    user: i64,
+    opts: Form<StyleList>
+) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
     let conn = conn.get()?;
 
     auth_rules.allows_style_list(&mut auth, &*conn)?;
 
+    let limit = list_limit(&options, opts.limit, None, None)?;
+
     match auth.uid {
         Some(uid) => {
-            if uid == user {
-                Ok(Json(json!(style::list_user(&*conn, &user)?)))
+            if uid == user || auth.has_admin_access() {
+                Ok(Listed::new(json!(style::list_user(&*conn, &user, &limit)?), limit, opts.envelope))
             } else {
-                Ok(Json(json!(style::list_user_public(&*conn, &user)?)))
+                Ok(Listed::new(json!(style::list_user_public(&*conn, &user, &limit, &auth.scope()?)?), limit, opts.envelope))
             }
         },
         _ => {
-            Ok(Json(json!(style::list_user_public(&*conn, &user)?)))
+            Ok(Listed::new(json!(style::list_user_public(&*conn, &user, &limit, &auth.scope()?)?), limit, opts.envelope))
         }
     }
 }
@@ -792,22 +1508,30 @@ struct DeltaList {
     offset: Option<i64>,
     limit: Option<i64>,
     start: Option<String>,
-    end: Option<String>
+    end: Option<String>,
+    fields: Option<String>,
+    envelope: Option<bool>
 }
 
 #[get("/deltas?<opts..>")]
 fn delta_list(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
     opts: Form<DeltaList>
-) ->  Result<Json<serde_json::Value>, HecateError> {
+) ->  Result<Listed<Json<serde_json::Value>>, HecateError> {
     let conn = conn.get()?;
 
     auth_rules.allows_delta_list(&mut auth, &*conn)?;
+    let tenant = auth.scope()?;
 
     if opts.offset.is_none() && opts.limit.is_none() && opts.start.is_none() && opts.end.is_none() {
-        Ok(Json(delta::list_by_offset(&*conn, None, None)?))
+        let limit = list_limit(&options, None, Some(20), Some(100))?;
+
+        let list = delta::list_by_offset(&*conn, None, limit, &opts.fields, &tenant)?;
+
+        delta_page(&*conn, list, None, limit, opts.envelope, &tenant)
     } else if opts.offset.is_some() && (opts.start.is_some() || opts.end.is_some()) {
         return Err(HecateError::new(400, String::from("Offset cannot be used with start or end"), None));
     } else if opts.start.is_some() || opts.end.is_some() {
@@ -831,25 +1555,95 @@ fn delta_list(
             }
         };
 
-        Ok(Json(delta::list_by_date(&*conn, start, end, opts.limit)?))
+        let limit = list_limit(&options, opts.limit, None, None)?;
+
+        Ok(Listed::new(delta::list_by_date(&*conn, start, end, limit, &opts.fields, &tenant)?, limit, opts.envelope))
     } else if opts.offset.is_some() || opts.limit.is_some() {
-        Ok(Json(delta::list_by_offset(&*conn, opts.offset, opts.limit)?))
+        let limit = list_limit(&options, opts.limit, Some(20), Some(100))?;
+
+        let list = delta::list_by_offset(&*conn, opts.offset, limit, &opts.fields, &tenant)?;
+
+        delta_page(&*conn, list, opts.offset, limit, opts.envelope, &tenant)
     } else {
         return Err(HecateError::new(400, String::from("Invalid Query Params"), None));
     }
 }
 
+///
+/// A page of deltas listed by offset, the `next` & `prev` cursors of the envelope
+/// are the offsets of the adjoining pages
+///
+fn delta_page(conn: &impl postgres::GenericConnection, list: serde_json::Value, offset: Option<i64>, limit: Option<i64>, wrap: Option<bool>, tenant: &tenant::Scope) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
+    if wrap != Some(true) {
+        return Ok(Listed(Json(list), limit));
+    }
+
+    let prev = match offset {
+        None => None,
+        Some(offset) => {
+            let newest = match list.get(0).and_then(|delta| delta.get("id")).and_then(|id| id.as_i64()) {
+                Some(id) => id,
+                None => offset - 1
+            };
+
+            delta::prev_offset(conn, &newest, limit, tenant)?
+        }
+    };
+
+    let next = next_cursor(&list, limit);
+
+    Ok(Listed(Json(envelope(list, limit, offset.is_none(), next, prev)), limit))
+}
+
+#[derive(FromForm, Debug)]
+struct DeltaStream {
+    bbox: Option<String>
+}
+
+#[get("/delta/stream?<opts..>")]
+fn delta_stream(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    worker: State<worker::Worker>,
+    last_event: delta::events::LastEventId,
+    opts: Form<DeltaStream>
+) -> Result<Response<'static>, HecateError> {
+    auth_rules.allows_delta_list(&mut auth, &*conn.get()?)?;
+
+    let bbox: Option<Vec<f64>> = match &opts.bbox {
+        None => None,
+        Some(bbox) => Some(bbox::Bbox::parse(bbox)?.to_vec())
+    };
+
+    let events = delta::events::DeltaEvents::new(conn.pool()?, worker.subscribe(), bbox, auth.scope()?, last_event.0)?;
+
+    let mut stream_response = Response::new();
+    stream_response.set_status(HTTPStatus::Ok);
+    stream_response.set_raw_header("Content-Type", "text/event-stream");
+    stream_response.set_raw_header("Cache-Control", "no-cache");
+    stream_response.set_chunked_body(events, 4096);
+    Ok(stream_response)
+}
+
 #[get("/delta/<id>")]
 fn delta(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
     id: i64
 ) ->  Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_delta_get(&mut auth, &*conn)?;
 
-    Ok(Json(delta::get_json(&*conn, &id)?))
+    let mut delta = delta::get_json(&*conn, &id, &auth.scope()?)?;
+
+    if let Some(features) = delta.get_mut("features") {
+        options.attribution().collection(features);
+    }
+
+    Ok(Json(delta))
 }
 
 #[get("/data/bounds?<filter..>")]
@@ -857,24 +1651,48 @@ fn bounds(
     conn: State<DbReplica>,
     mut auth:
     auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
     filter: Form<Filter>
-) -> Result<Json<serde_json::Value>, HecateError> {
+) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
     let conn = conn.get()?;
 
     auth_rules.allows_bounds_list(&mut auth, &*conn)?;
 
-    match &filter.filter {
-        Some(search) => Ok(Json(json!(bounds::filter(&*conn, &search, &filter.limit)?))),
-        None => Ok(Json(json!(bounds::list(&*conn, &filter.limit)?)))
+    if filter.meta == Some(true) || filter.sort.is_some() {
+        let limit = list_limit(&options, filter.limit, None, None)?;
+
+        let sort = bounds::Sort::parse(&filter.sort)?;
+        let list = bounds::list_meta(&*conn, &filter.filter, &sort, &limit)?;
+
+        if filter.meta == Some(true) {
+            return Ok(Listed::new(json!(list), limit, filter.envelope));
+        }
+
+        let names: Vec<serde_json::Value> = list.into_iter().map(|bound| bound["name"].clone()).collect();
+
+        return Ok(Listed::new(json!(names), limit, filter.envelope));
     }
-}
+
+    match &filter.filter {
+        Some(search) => {
+            let limit = list_limit(&options, filter.limit, Some(100), Some(100))?;
+
+            Ok(Listed::new(json!(bounds::filter(&*conn, &search, &limit)?), limit, filter.envelope))
+        },
+        None => {
+            let limit = list_limit(&options, filter.limit, None, None)?;
+
+            Ok(Listed::new(json!(bounds::list(&*conn, &limit)?), limit, filter.envelope))
+        }
+    }
+}
 
 #[get("/data/bounds/<bounds>")]
 fn bounds_get(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     bounds: String
 ) -> Result<Stream<stream::PGStream>, HecateError> {
     let conn = conn.get()?;
@@ -888,7 +1706,7 @@ fn bounds_get(
 fn bounds_set(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     bounds: String,
     body: Data
 ) -> Result<Json<serde_json::Value>, HecateError> {
@@ -922,14 +1740,16 @@ fn bounds_set(
         }
     };
 
-    Ok(Json(json!(bounds::set(&*conn, &bounds, &geom)?)))
+    bounds::set(&*conn, &bounds, &geom)?;
+
+    Ok(Json(json!(bounds::count(&*conn, &Some(bounds))?)))
 }
 
 #[delete("/data/bounds/<bounds>")]
 fn bounds_delete(
     conn: State<DbReadWrite>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     bounds: String
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -943,7 +1763,7 @@ fn bounds_delete(
 fn webhooks_list(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
+    auth_rules: auth::Rules
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
@@ -959,7 +1779,7 @@ fn webhooks_list(
 fn webhooks_get(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -976,7 +1796,7 @@ fn webhooks_get(
 fn webhooks_delete(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<bool>, HecateError> {
     let conn = conn.get()?;
@@ -990,7 +1810,7 @@ fn webhooks_delete(
 fn webhooks_create(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     body: Data
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -1029,11 +1849,87 @@ fn webhooks_create(
     }
 }
 
+#[post("/webhooks/<id>/ping")]
+fn webhooks_ping(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_webhooks_update(&mut auth, &*conn)?;
+
+    match serde_json::to_value(webhooks::ping(&*conn, id)?) {
+        Ok(delivery) => Ok(Json(delivery)),
+        Err(_) => Err(HecateError::new(500, String::from("Internal Server Error"), None))
+    }
+}
+
+#[derive(FromForm, Debug)]
+struct Backfill {
+    since: i64
+}
+
+#[post("/webhooks/<id>/backfill?<opts..>")]
+fn webhooks_backfill(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    opts: Form<Backfill>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let pool = conn.pool();
+    let conn = conn.get()?;
+
+    auth_rules.allows_webhooks_update(&mut auth, &*conn)?;
+
+    let backfill = webhooks::backfill::start(&*conn, id, opts.since)?;
+
+    let response = match serde_json::to_value(&backfill) {
+        Ok(backfill) => backfill,
+        Err(_) => { return Err(HecateError::new(500, String::from("Internal Server Error"), None)); }
+    };
+
+    std::thread::spawn(move || {
+        let conn = match pool.get() {
+            Ok(conn) => conn,
+            Err(err) => {
+                println!("ERROR: Failed to start backfill of webhook {}: {}", id, err);
+                return;
+            }
+        };
+
+        if let Err(err) = webhooks::backfill::run(&*conn, backfill) {
+            println!("ERROR: Backfill of webhook {} failed: {}", id, err.to_string());
+        }
+    });
+
+    Ok(Json(response))
+}
+
+#[get("/webhooks/<id>/backfill")]
+fn webhooks_backfill_get(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_webhooks_list(&mut auth, &*conn)?;
+
+    match serde_json::to_value(webhooks::backfill::get(&*conn, id)?) {
+        Ok(backfill) => Ok(Json(backfill)),
+        Err(_) => Err(HecateError::new(500, String::from("Internal Server Error"), None))
+    }
+}
+
 #[post("/webhooks/<id>", format="application/json", data="<body>")]
 fn webhooks_update(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     body: Data,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
@@ -1077,7 +1973,7 @@ fn webhooks_update(
 fn bounds_stats(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     bounds: String
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -1091,7 +1987,7 @@ fn bounds_stats(
 fn bounds_meta(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     bounds: String
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
@@ -1101,6 +1997,95 @@ fn bounds_meta(
     Ok(Json(bounds::meta(&*conn, bounds)?))
 }
 
+#[derive(FromForm, Debug)]
+struct BoundsDownload {
+    format: Option<String>,
+    srid: Option<i32>
+}
+
+#[get("/data/bounds/<bounds>/download?<opts..>")]
+fn bounds_download(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    limiter: State<limit::Limiter>,
+    options: State<options::Options>,
+    requested: range::Range,
+    bounds: String,
+    opts: Form<BoundsDownload>
+) -> Result<Response<'static>, HecateError> {
+    let format = opts.format.clone().unwrap_or(String::from("geojson"));
+    let srid = opts.srid.unwrap_or(feature::SRID);
+    let tenant = auth.scope()?;
+
+    let etag = {
+        let conn = conn.get()?;
+
+        auth_rules.allows_clone_get(&mut auth, &*conn)?;
+        auth_rules.allows_bounds_get(&mut auth, &*conn)?;
+
+        if format != "geojson" && format != "gpkg" {
+            return Err(HecateError::new(400, String::from("Invalid Format - must be one of 'geojson' or 'gpkg'"), None));
+        }
+
+        allows_format(&options, &format)?;
+        feature::check_srid(&*conn, &srid)?;
+
+        //Also ensures the bound exists so that an unknown bound is not downloaded as an empty file
+        bounds::etag(&*conn, &bounds, &format, srid, &tenant)?
+    };
+
+    let permit = limiter.acquire(limit::Route::Clone, &auth)?;
+
+    let filename = bounds.replace(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'), "_");
+
+    let mut response = Response::new();
+    response.set_status(HTTPStatus::Ok);
+    response.set_raw_header("Accept-Ranges", "bytes");
+    response.set_raw_header("ETag", etag.clone());
+
+    if format == "gpkg" {
+        response.set_raw_header("Content-Type", "application/geopackage+sqlite3");
+        response.set_raw_header("Content-Disposition", format!("attachment; filename=\"{}.gpkg\"", filename));
+    } else {
+        response.set_raw_header("Content-Disposition", format!("attachment; filename=\"{}.geojson\"", filename));
+    }
+
+    //A range of an export that has changed since the client started downloading it
+    //would not join up with the bytes it already has, so the whole export is returned
+    if !requested.matches(&etag) {
+        if format == "gpkg" {
+            response.set_chunked_body(limit::Limited::new(bounds::download_gpkg(&*conn.get()?, &bounds, srid, &tenant)?, permit), 4096);
+        } else {
+            response.set_chunked_body(limit::Limited::new(bounds::download(conn.get()?, bounds, srid, &tenant)?, permit), 4096);
+        }
+
+        return Ok(response);
+    }
+
+    //A range of a streamed export can only be read once the whole export has been written,
+    //features are exported in the order of their ids so that the bytes are the same as
+    //those of any earlier download with the same ETag
+    let file = if format == "gpkg" {
+        bounds::download_gpkg(&*conn.get()?, &bounds, srid, &tenant)?
+    } else {
+        range::materialize(bounds::download(conn.get()?, bounds, srid, &tenant)?)?
+    };
+
+    match requested.bytes(range::length(&file)?)? {
+        None => {
+            response.set_sized_body(limit::Limited::new(file, permit));
+        },
+        Some(bytes) => {
+            response.set_status(HTTPStatus::PartialContent);
+            response.set_raw_header("Content-Range", bytes.content_range());
+            response.set_sized_body(limit::Limited::new(range::Slice::new(file, &bytes)?, permit));
+        }
+    };
+
+    Ok(response)
+}
+
 #[derive(FromForm, Debug)]
 struct CloneQuery {
     query: String,
@@ -1112,118 +2097,331 @@ fn clone_query(
     sandbox_conn: State<DbSandbox>,
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    limiter: State<limit::Limiter>,
     cquery: Form<CloneQuery>
-) -> Result<Stream<stream::PGStream>, HecateError> {
+) -> Result<Stream<limit::Limited<stream::PGStream>>, HecateError> {
     auth_rules.allows_clone_query(&mut auth, &*conn.get()?)?;
 
-    Ok(Stream::from(clone::query(sandbox_conn.get()?, &cquery.query, &cquery.limit)?))
+    let permit = limiter.acquire(limit::Route::Clone, &auth)?;
+
+    Ok(Stream::from(limit::Limited::new(clone::query(sandbox_conn.get()?, &cquery.query, &cquery.limit)?, permit)))
+}
+
+#[derive(FromForm, Debug)]
+struct CloneFormat {
+    format: Option<String>,
+    quantization: Option<u32>
 }
 
-#[get("/data/clone")]
+#[get("/data/clone?<opts..>")]
 fn clone_get(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
-) -> Result<Stream<stream::PGStream>, HecateError> {
+    auth_rules: auth::Rules,
+    limiter: State<limit::Limiter>,
+    options: State<options::Options>,
+    opts: Form<CloneFormat>
+) -> Result<Response<'static>, HecateError> {
     auth_rules.allows_clone_get(&mut auth, &*conn.get()?)?;
 
-    Ok(Stream::from(clone::get(conn.get()?)?))
+    allows_format(&options, opts.format.as_ref().map(|format| format.as_str()).unwrap_or("geojson"))?;
+
+    let tenant = auth.scope()?;
+
+    let permit = limiter.acquire(limit::Route::Clone, &auth)?;
+
+    features_response(limit::Limited::new(clone::get(conn.get()?, &tenant)?, permit), &opts.format, &opts.quantization)
+}
+
+#[get("/data/snapshot")]
+fn snapshot_get(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    limiter: State<limit::Limiter>
+) -> Result<Response<'static>, HecateError> {
+    auth_rules.is_admin(&mut auth, &*conn.get()?)?;
+
+    let permit = limiter.acquire(limit::Route::Clone, &auth)?;
+
+    let mut snapshot_response = Response::new();
+    snapshot_response.set_status(HTTPStatus::Ok);
+    snapshot_response.set_chunked_body(limit::Limited::new(snapshot::get(conn.get()?)?, permit), 4096);
+    Ok(snapshot_response)
+}
+
+#[derive(FromForm, Debug)]
+struct Restore {
+    force: Option<bool>
+}
+
+#[post("/data/restore?<opts..>", data="<body>")]
+fn snapshot_restore(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    limiter: State<limit::Limiter>,
+    opts: Form<Restore>,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.is_admin(&mut auth, &*conn)?;
+
+    let _permit = limiter.acquire(limit::Route::Import, &auth)?;
+
+    Ok(Json(snapshot::restore(&*conn, BufReader::new(body.open()), opts.force.unwrap_or(false))?))
+}
+
+///
+/// Stream features as Line-Delimited GeoJSON, or collect them into a single
+/// TopoJSON topology when requested
+///
+fn features_response(features: impl Read + 'static, format: &Option<String>, quantization: &Option<u32>) -> Result<Response<'static>, HecateError> {
+    let format = match format {
+        None => "geojson",
+        Some(format) => format.as_str()
+    };
+
+    match format {
+        "geojson" => {
+            let mut stream_response = Response::new();
+            stream_response.set_status(HTTPStatus::Ok);
+            stream_response.set_chunked_body(features, 4096);
+            Ok(stream_response)
+        },
+        "topojson" => {
+            let topology = topojson::from_stream(features, quantization)?;
+
+            let mut topo_response = Response::new();
+            topo_response.set_status(HTTPStatus::Ok);
+            topo_response.set_sized_body(Cursor::new(topology.to_string()));
+            topo_response.set_raw_header("Content-Type", "application/json");
+            Ok(topo_response)
+        },
+        _ => Err(HecateError::new(400, String::from("Invalid Format - must be one of 'geojson' or 'topojson'"), None))
+    }
 }
 
 #[get("/data/features?<map..>")]
 fn features_query(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    timings: timing::Timings,
+    options: State<options::Options>,
     map: Form<Map>
-) -> Result<Stream<stream::PGStream>, HecateError> {
+) -> Result<Response<'static>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    let tenant = auth.scope()?;
 
-    if map.bbox.is_some() && map.point.is_some() {
-        Err(HecateError::new(400, String::from("key and point params cannot be used together"), None))
+    let geometry = feature::GeometryFormat::parse(&map.geometry)?;
+    let order = feature::Order::parse(&map.order, &map.after)?;
+    let limit = list_limit(&options, map.limit, None, None)?;
+    let geometry_only = map.geometry_only.unwrap_or(false);
+    let buffer = validate::buffer(&map.buffer)?;
+
+    allows_format(&options, map.format.as_ref().map(|format| format.as_str()).unwrap_or("geojson"))?;
+    allows_format(&options, geometry.name())?;
+
+    if geometry != feature::GeometryFormat::GeoJSON && map.format.as_ref().map(|format| format.as_str()) == Some("topojson") {
+        return Err(HecateError::new(400, String::from("geometry param cannot be used with topojson format"), None));
+    }
+
+    let features = if map.modified_since.is_some() {
+        if map.bbox.is_some() || map.point.is_some() || map.prop.is_some() || map.filter.is_some() || map.tag.is_some() {
+            return Err(HecateError::new(400, String::from("modified_since cannot be used with bbox, point, prop, filter, or tag params"), None));
+        }
+
+        if map.format.as_ref().map(|format| format.as_str()) == Some("topojson") {
+            return Err(HecateError::new(400, String::from("modified_since cannot be used with topojson format"), None));
+        }
+
+        if order.is_some() {
+            return Err(HecateError::new(400, String::from("modified_since cannot be used with order or after params"), None));
+        }
+
+        if buffer.is_some() {
+            return Err(HecateError::new(400, String::from("buffer param can only be used with bbox or point params"), None));
+        }
+
+        let since: chrono::NaiveDateTime = match map.modified_since.as_ref().unwrap().parse() {
+            Ok(since) => since,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid modified_since Timestamp"), None)); }
+        };
+
+        timings.time("db", || feature::get_modified_stream(conn, &since, &geometry, geometry_only, &limit, &tenant))?
+    } else if map.bbox.is_some() && map.point.is_some() {
+        return Err(HecateError::new(400, String::from("key and point params cannot be used together"), None));
+    } else if map.prop.is_some() || map.filter.is_some() || map.tag.is_some() {
+        if map.point.is_some() {
+            return Err(HecateError::new(400, String::from("prop/filter/tag and point params cannot be used together"), None));
+        }
+
+        if buffer.is_some() {
+            return Err(HecateError::new(400, String::from("buffer param can only be used with bbox or point params"), None));
+        }
+
+        let expr = map.to_filter()?;
+
+        timings.time("db", || feature::get_filter_stream(conn, &expr, &geometry, geometry_only, &order, &limit, &tenant))?
     } else if map.bbox.is_some() {
-        let bbox: Vec<f64> = map.bbox.as_ref().unwrap().split(',').map(|s| s.parse().unwrap()).collect();
-        Ok(Stream::from(feature::get_bbox_stream(conn, &bbox)?))
+        let bbox = bbox::Bbox::parse(map.bbox.as_ref().unwrap())?;
+        timings.time("db", || feature::get_bbox_stream(conn, &bbox, &buffer, &geometry, geometry_only, &order, &limit, &tenant))?
     } else if map.point.is_some() {
-        Ok(Stream::from(feature::get_point_stream(conn, &map.point.as_ref().unwrap())?))
+        if order.is_some() {
+            return Err(HecateError::new(400, String::from("point param cannot be used with order or after params"), None));
+        }
+
+        timings.time("db", || feature::get_point_stream(conn, &map.point.as_ref().unwrap(), &buffer, &geometry, geometry_only, &limit, &tenant))?
     } else {
-        Err(HecateError::new(400, String::from("key or point param must be used"), None))
+        return Err(HecateError::new(400, String::from("key, point, prop, filter, tag, or modified_since param must be used"), None));
+    };
+
+    let mut response = if map.envelope == Some(true) {
+        if map.format.as_ref().map(|format| format.as_str()).unwrap_or("geojson") != "geojson" {
+            return Err(HecateError::new(400, String::from("envelope param can only be used with geojson format"), None));
+        }
+
+        let data = json!(timings.time("serialize", || stream::collect(features))?);
+
+        //Only an ordered list has a cursor, keyset paging cannot move backwards
+        let next = match order {
+            Some(_) => next_cursor(&data, limit),
+            None => None
+        };
+
+        let mut envelope_response = Response::new();
+        envelope_response.set_status(HTTPStatus::Ok);
+        envelope_response.set_sized_body(Cursor::new(envelope(data, limit, map.after.is_none(), next, None).to_string()));
+        envelope_response.set_raw_header("Content-Type", "application/json");
+        envelope_response
+    } else {
+        timings.time("serialize", || features_response(features, &map.format, &map.quantization))?
+    };
+
+    if let Some(limit) = limit {
+        response.set_raw_header("X-Hecate-Limit", limit.to_string());
     }
 
+    Ok(response)
 }
 
-#[get("/schema")]
-fn schema_get(
+#[get("/data/features/count?<map..>")]
+fn features_count(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    schema: State<Option<serde_json::value::Value>>
+    auth_rules: auth::Rules,
+    timings: timing::Timings,
+    map: Form<Map>
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    let tenant = auth.scope()?;
+    let buffer = validate::buffer(&map.buffer)?;
 
-    auth_rules.allows_schema_get(&mut auth, &*conn)?;
+    let count = if map.bbox.is_some() && map.point.is_some() {
+        return Err(HecateError::new(400, String::from("key and point params cannot be used together"), None));
+    } else if map.point.is_some() {
+        if map.prop.is_some() || map.filter.is_some() || map.tag.is_some() {
+            return Err(HecateError::new(400, String::from("prop/filter/tag and point params cannot be used together"), None));
+        }
 
-    match schema.inner() {
-        Some(ref s) => Ok(Json(json!(s))),
-        None => Err(HecateError::new(404, String::from("No schema Validation Enforced"), None))
-    }
+        timings.time("db", || feature::count_point(&*conn, &map.point.as_ref().unwrap(), &buffer, &tenant))?
+    } else if buffer.is_some() {
+        return Err(HecateError::new(400, String::from("buffer param can only be used with the point param when counting"), None));
+    } else {
+        let expr = map.to_filter()?;
+
+        timings.time("db", || feature::count(&*conn, &expr, &tenant))?
+    };
+
+    Ok(Json(json!({
+        "count": count
+    })))
 }
 
-#[get("/auth")]
-fn auth_get(
+#[derive(FromForm, Debug)]
+struct Cluster {
+    bbox: Option<String>,
+    zoom: Option<u8>
+}
+
+#[get("/data/cluster?<opts..>")]
+fn features_cluster(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
+    auth_rules: auth::Rules,
+    timings: timing::Timings,
+    opts: Form<Cluster>
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    let tenant = auth.scope()?;
 
-    auth_rules.allows_auth_get(&mut auth, &*conn)?;
+    let (bbox, zoom) = match (&opts.bbox, opts.zoom) {
+        (Some(bbox), Some(zoom)) => (bbox::Bbox::parse(bbox)?, zoom),
+        _ => { return Err(HecateError::new(400, String::from("bbox and zoom params must be used"), None)); }
+    };
 
-    Ok(Json(auth_rules.to_json()))
+    Ok(Json(timings.time("db", || feature::cluster::get(&*conn, &bbox, zoom, &tenant))?))
 }
 
-#[get("/data/stats")]
-fn stats_get(
-    conn: State<DbReadWrite>,
+#[derive(FromForm, Debug)]
+struct Revalidate {
+    bbox: Option<String>,
+    bound: Option<String>
+}
+
+#[get("/data/features/revalidate?<opts..>")]
+fn features_revalidate(
+    conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
-) -> Result<Json<serde_json::Value>, HecateError> {
+    auth_rules: auth::Rules,
+    schema: State<Option<serde_json::value::Value>>,
+    opts: Form<Revalidate>
+) -> Result<Stream<feature::revalidate::Revalidate>, HecateError> {
     let conn = conn.get()?;
+    auth_rules.is_admin(&mut auth, &*conn)?;
 
-    auth_rules.allows_stats_get(&mut auth, &*conn)?;
+    let bbox = match opts.bbox {
+        None => None,
+        Some(ref bbox) => Some(bbox::Bbox::parse(bbox)?)
+    };
 
-    Ok(Json(stats::get_json(&*conn)?))
+    let tenant = auth.scope()?;
+
+    Ok(Stream::from(feature::revalidate::Revalidate::new(conn, schema.inner(), &bbox, &opts.bound, &tenant)?))
 }
 
-#[get("/data/stats/regen")]
-fn stats_regen(
-    conn: State<DbReadWrite>,
+#[get("/indexes")]
+fn index_list(
+    conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
+    auth_rules: auth::Rules
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
-    auth_rules.allows_stats_get(&mut auth, &*conn)?;
+    auth_rules.allows_index_list(&mut auth, &*conn)?;
 
-    Ok(Json(json!(stats::regen(&*conn)?)))
+    match serde_json::to_value(index::list(&*conn)?) {
+        Ok(indexes) => Ok(Json(indexes)),
+        Err(_) => Err(HecateError::new(500, String::from("Internal Server Error"), None))
+    }
 }
 
-#[post("/data/features", format="application/json", data="<body>")]
-fn features_action(
-    mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+#[post("/index", format="application/json", data="<body>")]
+fn index_create(
     conn: State<DbReadWrite>,
-    worker: State<worker::Worker>,
-    schema: State<Option<serde_json::value::Value>>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
     body: Data
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
-    auth_rules.allows_feature_create(&mut auth, &*conn)?;
-
-    let uid = auth.uid.unwrap();
+    auth_rules.allows_index_create(&mut auth, &*conn)?;
 
     let body_str: String;
     {
@@ -1244,101 +2442,1268 @@ fn features_action(
         }
     }
 
-    let mut fc = match body_str.parse::<GeoJson>() {
-        Err(_) => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON Feature"), None)); },
-        Ok(geo) => match geo {
-            GeoJson::FeatureCollection(fc) => fc,
-            _ => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON FeatureCollection"), None)); }
+    let index: serde_json::Value = match serde_json::from_str(&*body_str) {
+        Ok(index) => index,
+        Err(_) => {
+            return Err(HecateError::new(400, String::from("Invalid index JSON"), None));
         }
     };
 
-    let delta_message = match fc.foreign_members {
-        None => { return Err(HecateError::new(400, String::from("FeatureCollection Must have message property for delta"), None)); }
-        Some(ref members) => match members.get("message") {
-            Some(message) => match message.as_str() {
-                Some(message) => String::from(message),
-                None => { return Err(HecateError::new(400, String::from("FeatureCollection Must have message property for delta"), None)); }
-            },
-            None => { return Err(HecateError::new(400, String::from("FeatureCollection Must have message property for delta"), None)); }
-        }
-    };
+    match serde_json::to_value(index::create(&*conn, index)?) {
+        Ok(index) => Ok(Json(index)),
+        Err(_) => { return Err(HecateError::new(500, String::from("Failed to return index ID"), None)); }
+    }
+}
 
-    let trans = match conn.transaction() {
-        Ok(trans) => trans,
-        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
-    };
+#[delete("/index/<id>")]
+fn index_delete(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64
+) -> Result<Json<bool>, HecateError> {
+    let conn = conn.get()?;
 
-    let mut map: HashMap<String, Option<String>> = HashMap::new();
-    map.insert(String::from("message"), Some(delta_message));
+    auth_rules.allows_index_delete(&mut auth, &*conn)?;
 
-    let delta_id = match delta::open(&trans, &map, &uid) {
-        Ok(id) => id,
-        Err(err) => {
-            trans.set_rollback();
-            trans.finish().unwrap();
-            return Err(err);
-        }
-    };
+    Ok(Json(index::delete(&*conn, id)?))
+}
 
-    for feat in &mut fc.features {
-        match feature::is_force(&feat) {
-            Err(err) => {
+#[derive(FromForm, Debug)]
+struct SchemaOpts {
+    meta: Option<bool>
+}
+
+#[get("/schema?<opts..>")]
+fn schema_get(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    schema: State<Option<serde_json::value::Value>>,
+    options: State<options::Options>,
+    opts: Form<SchemaOpts>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_schema_get(&mut auth, &*conn)?;
+
+    let mut schema = match schema.inner() {
+        Some(ref s) => json!(s),
+        None => { return Err(HecateError::new(404, String::from("No schema Validation Enforced"), None)); }
+    };
+
+    if opts.meta != Some(true) {
+        return Ok(Json(schema));
+    }
+
+    let properties = options.properties();
+
+    //Validators ignore unknown keywords, so the metadata is added without the schema losing its meaning
+    match schema.as_object_mut() {
+        Some(schema) => {
+            schema.insert(String::from("x-hecate"), json!({
+                "geometry": {
+                    "types": feature::geometry_types(&options.geometry()),
+                    "srid": feature::SRID
+                },
+                "properties": {
+                    "mode": match properties.is_strip() {
+                        true => "strip",
+                        false => "reject"
+                    },
+                    "allow": properties.allow,
+                    "deny": properties.deny
+                }
+            }));
+        },
+        None => { return Err(HecateError::new(500, String::from("Schema must be a JSON object"), None)); }
+    };
+
+    Ok(Json(schema))
+}
+
+#[get("/auth")]
+fn auth_get(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_auth_get(&mut auth, &*conn)?;
+
+    Ok(Json(auth_rules.to_json()))
+}
+
+#[get("/auth/config")]
+fn auth_config_get(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.is_admin(&mut auth, &*conn)?;
+
+    Ok(Json(auth_rules.to_json()))
+}
+
+#[put("/auth/config", format="application/json", data="<body>")]
+fn auth_config_set(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    auth_container: State<auth::AuthContainer>,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.is_admin(&mut auth, &*conn)?;
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let new_rules: auth::CustomAuth = match serde_json::from_str(&*body_str) {
+        Ok(new_rules) => new_rules,
+        Err(err) => { return Err(HecateError::new(400, String::from("Invalid Auth Config JSON"), Some(err.to_string()))); }
+    };
+
+    Ok(Json(auth_container.set(new_rules)?.to_json()))
+}
+
+#[derive(FromForm, Debug)]
+struct DenialList {
+    offset: Option<i64>,
+    limit: Option<i64>,
+    envelope: Option<bool>
+}
+
+#[get("/auth/denials?<opts..>")]
+fn auth_denials(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    opts: Form<DenialList>
+) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.is_admin(&mut auth, &*conn)?;
+
+    let limit = list_limit(&options, opts.limit, Some(100), Some(1000))?;
+
+    let list = audit::denials(&*conn, opts.offset, limit)?;
+
+    if opts.envelope != Some(true) {
+        return Ok(Listed(Json(list), limit));
+    }
+
+    let next = next_cursor(&list, limit);
+
+    Ok(Listed(Json(envelope(list, limit, opts.offset.is_none(), next, None)), limit))
+}
+
+#[get("/data/stats")]
+fn stats_get(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_stats_get(&mut auth, &*conn)?;
+
+    Ok(Json(stats::get_json(&*conn, &auth.scope()?)?))
+}
+
+#[get("/data/stats/regen")]
+fn stats_regen(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    limiter: State<limit::Limiter>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_stats_get(&mut auth, &*conn)?;
+
+    let _permit = limiter.acquire(limit::Route::Regen, &auth)?;
+
+    bounds::count(&*conn, &None)?;
+
+    Ok(Json(json!(stats::regen(&*conn)?)))
+}
+
+#[derive(FromForm, Debug)]
+struct NumericStats {
+    property: Option<String>
+}
+
+#[get("/data/stats/numeric?<opts..>")]
+fn stats_numeric(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    opts: Form<NumericStats>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_stats_get(&mut auth, &*conn)?;
+
+    let property = match opts.property {
+        Some(ref property) if property.len() > 0 => property,
+        _ => { return Err(HecateError::new(400, String::from("property param required"), None)); }
+    };
+
+    Ok(Json(stats::numeric(&*conn, property, &auth.scope()?)?))
+}
+
+#[derive(FromForm, Debug)]
+struct PropertyValues {
+    bbox: Option<String>,
+    limit: Option<i64>
+}
+
+#[get("/data/properties/<key>/values?<opts..>")]
+fn stats_values(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    key: String,
+    opts: Form<PropertyValues>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_stats_get(&mut auth, &*conn)?;
+
+    let bbox = match opts.bbox {
+        None => None,
+        Some(ref bbox) => Some(bbox::Bbox::parse(bbox)?)
+    };
+
+    let limit = list_limit(&options, opts.limit, Some(stats::MAX_VALUES), Some(stats::MAX_VALUES))?.unwrap_or(stats::MAX_VALUES);
+
+    Ok(Json(stats::values(&*conn, &key, &bbox, &limit, &auth.scope()?)?))
+}
+
+#[get("/stats/summary")]
+fn stats_summary(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    cache: State<stats::SummaryCache>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_stats_get(&mut auth, &*conn)?;
+
+    Ok(Json(cache.get(&*conn)?))
+}
+
+#[derive(FromForm, Debug)]
+struct Input {
+    input_srid: Option<i32>
+}
+
+#[post("/data/features?<opts..>", format="application/json", data="<body>")]
+fn features_action(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    worker: State<worker::Worker>,
+    schema: State<Option<serde_json::value::Value>>,
+    options: State<options::Options>,
+    limiter: State<limit::Limiter>,
+    key: idempotency::Key,
+    opts: Form<Input>,
+    body: Data
+) -> Result<idempotency::Idempotent<Json<serde_json::Value>>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_feature_create(&mut auth, &*conn)?;
+
+    let _permit = limiter.acquire(limit::Route::Import, &auth)?;
+
+    let uid = auth.uid.unwrap();
+
+    if let Some(record) = key.get(&*conn, &uid)? {
+        return Ok(idempotency::Idempotent(Json(json!(true)), Some(record)));
+    }
+
+    let input_srid = opts.input_srid.unwrap_or(feature::SRID);
+    feature::check_srid(&*conn, &input_srid)?;
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let mut fc = match body_str.parse::<GeoJson>() {
+        Err(_) => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON Feature"), None)); },
+        Ok(geo) => match geo {
+            GeoJson::FeatureCollection(fc) => fc,
+            _ => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON FeatureCollection"), None)); }
+        }
+    };
+
+    for feat in &mut fc.features {
+        feature::reproject(&*conn, feat, &input_srid)?;
+    }
+
+    if !auth.has_admin_access() {
+        feature::check_quota(&*conn, &uid, &options.quota(), feature::creates(&fc))?;
+    }
+
+    let delta_message = match fc.foreign_members {
+        None => { return Err(HecateError::new(400, String::from("FeatureCollection Must have message property for delta"), None)); }
+        Some(ref members) => match members.get("message") {
+            Some(message) => match message.as_str() {
+                Some(message) => String::from(message),
+                None => { return Err(HecateError::new(400, String::from("FeatureCollection Must have message property for delta"), None)); }
+            },
+            None => { return Err(HecateError::new(400, String::from("FeatureCollection Must have message property for delta"), None)); }
+        }
+    };
+
+    let tenant = auth.scope()?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
+    };
+
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    map.insert(String::from("message"), Some(delta_message));
+
+    let delta_id = match delta::open(&trans, &map, &uid) {
+        Ok(id) => id,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    let properties = options.properties();
+    let transform = options.transform();
+    let geometry = options.geometry();
+    let bound = auth_rules.feature_bound(&auth);
+
+    let provenance = match feature::provenance::Provenance::new(&trans, &auth, &options.provenance()) {
+        Ok(provenance) => provenance,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    for feat in &mut fc.features {
+        match feature::is_force(&feat) {
+            Err(err) => {
                 return Err(err);
             },
-            Ok(force) => {
-                if force {
-                    auth_rules.allows_feature_force(&mut auth, &*conn)?;
-                }
-            }
+            Ok(force) => {
+                if force {
+                    auth_rules.allows_feature_force(&mut auth, &*conn)?;
+                }
+            }
+        };
+
+        match feature::clean_props(feat, &properties) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            },
+            Ok(_) => ()
+        };
+
+        match feature::coerce::props(feat, schema.inner(), &properties) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            },
+            Ok(_) => ()
+        };
+
+        match feature::transform::props(feat, &transform) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            },
+            Ok(_) => ()
+        };
+
+        match provenance.stamp(&trans, feat) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            },
+            Ok(_) => ()
+        };
+
+        match feature::action(&trans, &schema.inner(), &geometry, &bound, &feat, &None, &tenant) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            },
+            Ok(res) => {
+                if res.new.is_some() {
+                    feat.id = Some(geojson::feature::Id::Number(serde_json::Number::from(res.new.unwrap())))
+                }
+            }
+        };
+    }
+
+    match delta::modify(&delta_id, &trans, &fc, &uid) {
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        },
+        _ => ()
+    };
+
+    let record = match key.set(&trans, &uid, &delta_id, feature::ids(&fc.features)) {
+        Ok(record) => record,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    match delta::finalize(&delta_id, &trans) {
+        Ok(_) => {
+            if trans.commit().is_err() {
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+            }
+
+            worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+
+            if options.database().is_analyze(fc.features.len()) {
+                worker.queue(worker::Task::new(worker::TaskType::Analyze));
+            }
+
+            Ok(idempotency::Idempotent(Json(json!(true)), record))
+        },
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            Err(err)
+        }
+    }
+}
+
+#[post("/data/features/delete", format="application/json", data="<body>")]
+fn features_delete(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    worker: State<worker::Worker>,
+    limiter: State<limit::Limiter>,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_feature_create(&mut auth, &*conn)?;
+
+    let _permit = limiter.acquire(limit::Route::Import, &auth)?;
+
+    let uid = auth.uid.unwrap();
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let batch: feature::batch::BatchDelete = match serde_json::from_str(&body_str) {
+        Ok(batch) => batch,
+        Err(err) => { return Err(HecateError::new(400, format!("Invalid Batch Delete - {}", err), None)); }
+    };
+
+    if batch.force == Some(true) {
+        auth_rules.allows_feature_force(&mut auth, &*conn)?;
+    }
+
+    let tenant = auth.scope()?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
+    };
+
+    let targets = match batch.targets(&trans, &tenant) {
+        Ok(targets) => targets,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    map.insert(String::from("message"), Some(batch.message.clone()));
+
+    let delta_id = match delta::open(&trans, &map, &uid) {
+        Ok(id) => id,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    let deleted = match feature::batch::delete(&trans, &targets, &auth_rules.feature_bound(&auth), &tenant) {
+        Ok(deleted) => deleted,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    if deleted.failed() && !batch.partial.unwrap_or(false) {
+        trans.set_rollback();
+        trans.finish().unwrap();
+
+        return Err(HecateError::from_json(400, json!({
+            "code": 400,
+            "status": "Bad Request",
+            "reason": "Batch Delete Failed - no features were deleted",
+            "results": deleted.results
+        }), String::from("Batch Delete Failed"), None));
+    }
+
+    if deleted.deleted.features.len() == 0 {
+        trans.set_rollback();
+        trans.finish().unwrap();
+
+        return Ok(Json(json!({
+            "delta": null,
+            "results": deleted.results
+        })));
+    }
+
+    match delta::modify(&delta_id, &trans, &deleted.deleted, &uid) {
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        },
+        _ => ()
+    };
+
+    match delta::finalize(&delta_id, &trans) {
+        Ok(_) => {
+            if trans.commit().is_err() {
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+            }
+
+            worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+
+            Ok(Json(json!({
+                "delta": delta_id,
+                "results": deleted.results
+            })))
+        },
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            Err(err)
+        }
+    }
+}
+
+#[post("/data/features/merge", format="application/json", data="<body>")]
+fn features_merge(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    schema: State<Option<serde_json::value::Value>>,
+    worker: State<worker::Worker>,
+    options: State<options::Options>,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_feature_force(&mut auth, &*conn)?;
+
+    let uid = auth.uid.unwrap();
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let merge: feature::merge::Merge = match serde_json::from_str(&body_str) {
+        Ok(merge) => merge,
+        Err(err) => { return Err(HecateError::new(400, format!("Invalid Merge - {}", err), None)); }
+    };
+
+    let tenant = auth.scope()?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
+    };
+
+    let merged = match merge.features(&trans, &tenant) {
+        Ok(merged) => merged,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    map.insert(String::from("message"), Some(merge.message.clone()));
+
+    let delta_id = match delta::open(&trans, &map, &uid) {
+        Ok(id) => id,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    let fc = geojson::FeatureCollection {
+        bbox: None,
+        features: vec![ merged.modify, merged.delete ],
+        foreign_members: None,
+    };
+
+    for feat in fc.features.iter() {
+        match feature::action(&trans, schema.inner(), &options.geometry(), &auth_rules.feature_bound(&auth), feat, &None, &tenant) {
+            Ok(_) => (),
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        };
+    }
+
+    match delta::modify(&delta_id, &trans, &fc, &uid) {
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        },
+        _ => ()
+    }
+
+    match delta::finalize(&delta_id, &trans) {
+        Ok(_) => {
+            if trans.commit().is_err() {
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+            }
+
+            worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+
+            Ok(Json(json!({
+                "delta": delta_id,
+                "id": merge.a
+            })))
+        },
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            Err(err)
+        }
+    }
+}
+
+#[post("/data/features/replace", format="application/json", data="<body>")]
+fn features_replace(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    schema: State<Option<serde_json::value::Value>>,
+    worker: State<worker::Worker>,
+    limiter: State<limit::Limiter>,
+    options: State<options::Options>,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_feature_force(&mut auth, &*conn)?;
+
+    let _permit = limiter.acquire(limit::Route::Import, &auth)?;
+
+    let uid = auth.uid.unwrap();
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let replace: feature::replace::Replace = match serde_json::from_str(&body_str) {
+        Ok(replace) => replace,
+        Err(err) => { return Err(HecateError::new(400, format!("Invalid Replace - {}", err), None)); }
+    };
+
+    let tenant = auth.scope()?;
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
+    };
+
+    let features = match replace.features(&trans, &tenant) {
+        Ok(features) => features,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    if features.len() == 0 {
+        trans.set_rollback();
+        trans.finish().unwrap();
+
+        return Ok(Json(json!({
+            "delta": null,
+            "count": 0
+        })));
+    }
+
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    map.insert(String::from("message"), Some(replace.message.clone()));
+
+    let delta_id = match delta::open(&trans, &map, &uid) {
+        Ok(id) => id,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    let fc = geojson::FeatureCollection {
+        bbox: None,
+        features: features,
+        foreign_members: None,
+    };
+
+    for feat in fc.features.iter() {
+        match feature::action(&trans, schema.inner(), &options.geometry(), &auth_rules.feature_bound(&auth), feat, &Some(delta_id), &tenant) {
+            Ok(_) => (),
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        };
+    }
+
+    match delta::modify(&delta_id, &trans, &fc, &uid) {
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        },
+        _ => ()
+    }
+
+    match delta::finalize(&delta_id, &trans) {
+        Ok(_) => {
+            if trans.commit().is_err() {
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+            }
+
+            worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+
+            if options.database().is_analyze(fc.features.len()) {
+                worker.queue(worker::Task::new(worker::TaskType::Analyze));
+            }
+
+            Ok(Json(json!({
+                "delta": delta_id,
+                "count": fc.features.len()
+            })))
+        },
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            Err(err)
+        }
+    }
+}
+
+#[get("/0.6/map?<map..>")]
+fn osm_map(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    map: Form<Map>
+) -> Result<String, status::Custom<String>> {
+    let conn = conn.get().unwrap();
+
+    match auth_rules.allows_osm_get(&mut auth, &*conn) {
+        Ok(_) => (),
+        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
+    };
+
+    let query = match map.bbox {
+        None => { return Err(status::Custom(HTTPStatus::BadRequest, String::from("Invalid BBOX"))); },
+        Some(ref bbox) => match bbox::Bbox::parse(bbox) {
+            Ok(bbox) => bbox,
+            Err(err) => { return Err(status::Custom(HTTPStatus::BadRequest, err.as_json().to_string())); }
+        }
+    };
+
+    let tenant = match auth.scope() {
+        Ok(tenant) => tenant,
+        Err(err) => { return Err(status::Custom(HTTPStatus::Forbidden, err.as_json().to_string())); }
+    };
+
+    let fc = match feature::get_bbox(&*conn, &query, &tenant) {
+        Ok(features) => features,
+        Err(err) => { return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string())) }
+    };
+
+    let xml_str = match osm::from_features(&fc) {
+        Ok(xml_str) => xml_str,
+        Err(err) => { return Err(status::Custom(HTTPStatus::ExpectationFailed, err.to_string())) }
+    };
+
+    Ok(xml_str)
+}
+
+#[put("/0.6/changeset/create", data="<body>")]
+fn osm_changeset_create(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    body: Data
+) -> Result<String, status::Custom<String>> {
+    let conn = conn.get().unwrap();
+
+    match auth_rules.allows_osm_get(&mut auth, &*conn) {
+        Ok(_) => (),
+        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
+    };
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(status::Custom(HTTPStatus::BadRequest, String::from("Invalid JSON - Non-UTF8"))); }
+        }
+    }
+
+    let uid = auth.uid.unwrap();
+
+    let map = match osm::to_delta(&body_str) {
+        Ok(map) => map,
+        Err(err) => { return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string())); }
+    };
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(_) => { return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to open transaction"))); }
+    };
+
+    let delta_id = match delta::open(&trans, &map, &uid) {
+        Ok(id) => id,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string()));
+        }
+    };
+
+    if trans.commit().is_err() {
+        return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to commit transaction")));
+    }
+
+    Ok(delta_id.to_string())
+}
+
+#[put("/0.6/changeset/<id>/close")]
+fn osm_changeset_close(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    id: i64
+) -> Result<String, status::Custom<String>> {
+    let conn = conn.get().unwrap();
+
+    match auth_rules.allows_osm_get(&mut auth, &*conn) {
+        Ok(_) => (),
+        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
+    };
+
+    Ok(id.to_string())
+}
+
+#[put("/0.6/changeset/<delta_id>", data="<body>")]
+fn osm_changeset_modify(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    delta_id: i64,
+    body: Data
+) -> Result<Response<'static>, status::Custom<String>> {
+    let conn = conn.get().unwrap();
+
+    match auth_rules.allows_osm_get(&mut auth, &*conn) {
+        Ok(_) => (),
+        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
+    };
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = String::from_utf8(body_vec).unwrap();
+    }
+
+    let uid = auth.uid.unwrap();
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(_) => { return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to open transaction"))); }
+    };
+
+    match delta::is_open(&delta_id, &trans) {
+        Ok(true) => (),
+        _ => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+
+            let mut conflict_response = Response::new();
+            conflict_response.set_status(HTTPStatus::Conflict);
+            conflict_response.set_sized_body(Cursor::new(format!("The changeset {} was closed at previously", &delta_id)));
+            conflict_response.set_raw_header("Error", format!("The changeset {} was closed at previously", &delta_id));
+            return Ok(conflict_response);
+        }
+    }
+
+    let map = match osm::to_delta(&body_str) {
+        Ok(map) => map,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string()));
+        }
+    };
+
+    let delta_id = match delta::modify_props(&delta_id, &trans, &map, &uid) {
+        Ok(id) => id,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string()));
+        }
+    };
+
+    if trans.commit().is_err() {
+        return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to commit transaction")));
+    }
+
+    Err(status::Custom(HTTPStatus::Ok, delta_id.to_string()))
+}
+
+#[post("/0.6/changeset/<delta_id>/upload", data="<body>")]
+fn osm_changeset_upload(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    schema: State<Option<serde_json::value::Value>>,
+    worker: State<worker::Worker>,
+    options: State<options::Options>,
+    delta_id: i64,
+    body: Data
+) -> Result<Response<'static>, status::Custom<String>> {
+    let conn = conn.get().unwrap();
+
+    match auth_rules.allows_osm_get(&mut auth, &*conn) {
+        Ok(_) => (),
+        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
+    };
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = String::from_utf8(body_vec).unwrap();
+    }
+
+    let uid = auth.uid.unwrap();
+
+    let tenant = match auth.scope() {
+        Ok(tenant) => tenant,
+        Err(err) => { return Err(status::Custom(HTTPStatus::Forbidden, err.as_json().to_string())); }
+    };
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(_) => { return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to open transaction"))); }
+    };
+
+    match delta::is_open(&delta_id, &trans) {
+        Ok(true) => (),
+        _ => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+
+            let mut conflict_response = Response::new();
+            conflict_response.set_status(HTTPStatus::Conflict);
+            conflict_response.set_sized_body(Cursor::new(format!("The changeset {} was closed at previously", &delta_id)));
+            conflict_response.set_raw_header("Error", format!("The changeset {} was closed at previously", &delta_id));
+            return Ok(conflict_response);
+        }
+    }
+
+    let (mut fc, tree) = match osm::to_features(&body_str) {
+        Ok(fctree) => fctree,
+        Err(err) => { return Err(status::Custom(HTTPStatus::ExpectationFailed, err.to_string())); }
+    };
+
+    if !auth.has_admin_access() {
+        match feature::check_quota(&trans, &uid, &options.quota(), feature::creates(&fc)) {
+            Ok(_) => (),
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(status::Custom(HTTPStatus::TooManyRequests, err.as_json().to_string()));
+            }
+        };
+    }
+
+    let mut ids: HashMap<i64, feature::Response> = HashMap::new();
+
+    let properties = options.properties();
+    let transform = options.transform();
+    let geometry = options.geometry();
+    let bound = auth_rules.feature_bound(&auth);
+
+    let provenance = match feature::provenance::Provenance::new(&trans, &auth, &options.provenance()) {
+        Ok(provenance) => provenance,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(status::Custom(HTTPStatus::InternalServerError, err.as_json().to_string()));
+        }
+    };
+
+    for feat in &mut fc.features {
+        match feature::get_action(&feat) {
+            Ok(action) => {
+                if action == feature::Action::Create {
+                    feature::del_version(feat);
+                }
+            },
+            _ => ()
+        }
+
+        match feature::clean_props(feat, &properties) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string()));
+            },
+            Ok(_) => ()
+        };
+
+        match feature::coerce::props(feat, schema.inner(), &properties) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string()));
+            },
+            Ok(_) => ()
         };
 
-        match feature::action(&trans, &schema.inner(), &feat, &None) {
+        match feature::transform::props(feat, &transform) {
             Err(err) => {
                 trans.set_rollback();
                 trans.finish().unwrap();
-                return Err(err);
+                return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string()));
             },
-            Ok(res) => {
-                if res.new.is_some() {
-                    feat.id = Some(geojson::feature::Id::Number(serde_json::Number::from(res.new.unwrap())))
+            Ok(_) => ()
+        };
+
+        match provenance.stamp(&trans, feat) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string()));
+            },
+            Ok(_) => ()
+        };
+
+        let feat_res = match feature::action(&trans, &schema.inner(), &geometry, &bound, &feat, &Some(delta_id), &tenant) {
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string()));
+            },
+            Ok(feat_res) => {
+                if feat_res.old.unwrap_or(0) < 0 {
+                    feat.id = Some(geojson::feature::Id::Number(serde_json::Number::from(feat_res.new.unwrap())));
                 }
+
+                feat_res
             }
         };
+
+        ids.insert(feat_res.old.unwrap(), feat_res);
     }
 
-    match delta::modify(&delta_id, &trans, &fc, &uid) {
-        Err(err) => {
+    let diffres = match osm::to_diffresult(ids, tree) {
+        Err(_) => {
             trans.set_rollback();
             trans.finish().unwrap();
-            return Err(err);
+            return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Could not format diffResult XML")));
         },
-        _ => ()
+        Ok(diffres) => diffres
     };
 
+    match delta::modify(&delta_id, &trans, &fc, &uid) {
+        Ok (_) => (),
+        Err(_) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Could not create delta")));
+        }
+    }
+
     match delta::finalize(&delta_id, &trans) {
-        Ok(_) => {
+        Ok (_) => {
             if trans.commit().is_err() {
-                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+                return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to commit transaction")));
             }
 
             worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
 
-            Ok(Json(json!(true)))
+            Err(status::Custom(HTTPStatus::Ok, diffres))
         },
-        Err(err) => {
+        Err(_) => {
             trans.set_rollback();
             trans.finish().unwrap();
-            Err(err)
+            Err(status::Custom(HTTPStatus::InternalServerError, String::from("Could not close delta")))
         }
     }
 }
 
-#[get("/0.6/map?<map..>")]
-fn osm_map(
+#[get("/capabilities")]
+fn osm_capabilities(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    map: Form<Map>
+    auth_rules: auth::Rules
 ) -> Result<String, status::Custom<String>> {
     let conn = conn.get().unwrap();
 
@@ -1347,27 +3712,52 @@ fn osm_map(
         Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
     };
 
-    let query: Vec<f64> = map.bbox.as_ref().unwrap().split(',').map(|s| s.parse().unwrap()).collect();
+    Ok(String::from("
+        <osm version=\"0.6\" generator=\"Hecate Server\">
+            <api>
+                <version minimum=\"0.6\" maximum=\"0.6\"/>
+                <area maximum=\"0.25\"/>
+                <waynodes maximum=\"2000\"/>
+                <changesets maximum_elements=\"10000\"/>
+                <timeout seconds=\"300\"/>
+                <status database=\"online\" api=\"online\"/>
+            </api>
+        </osm>
+    "))
+}
 
-    let fc = match feature::get_bbox(&*conn, query) {
-        Ok(features) => features,
-        Err(err) => { return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string())) }
-    };
+#[get("/0.6/capabilities")]
+fn osm_06capabilities(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules
+) -> Result<String, status::Custom<String>> {
+    let conn = conn.get().unwrap();
 
-    let xml_str = match osm::from_features(&fc) {
-        Ok(xml_str) => xml_str,
-        Err(err) => { return Err(status::Custom(HTTPStatus::ExpectationFailed, err.to_string())) }
+    match auth_rules.allows_osm_get(&mut auth, &*conn) {
+        Ok(_) => (),
+        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
     };
 
-    Ok(xml_str)
+    Ok(String::from("
+        <osm version=\"0.6\" generator=\"Hecate Server\">
+            <api>
+                <version minimum=\"0.6\" maximum=\"0.6\"/>
+                <area maximum=\"0.25\"/>
+                <waynodes maximum=\"2000\"/>
+                <changesets maximum_elements=\"10000\"/>
+                <timeout seconds=\"300\"/>
+                <status database=\"online\" api=\"online\"/>
+            </api>
+        </osm>
+    "))
 }
 
-#[put("/0.6/changeset/create", data="<body>")]
-fn osm_changeset_create(
+#[get("/0.6/user/details")]
+fn osm_user(
+    conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    conn: State<DbReadWrite>,
-    body: Data
+    auth_rules: auth::Rules
 ) -> Result<String, status::Custom<String>> {
     let conn = conn.get().unwrap();
 
@@ -1376,6 +3766,48 @@ fn osm_changeset_create(
         Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
     };
 
+    Ok(String::from("
+        <osm version=\"0.6\" generator=\"Hecate Server\">
+            <user id=\"1\" display_name=\"user\" account_created=\"2010-06-18T12:34:58Z\">
+                <description></description>
+                <languages><lang>en-US</lang><lang>en</lang></languages>
+                <messages>
+                    <recieved county=\"0\" unread=\"0\"/>
+                    <send count=\"0\"/>
+                </messages>
+            </user>
+        </osm>
+    "))
+}
+
+#[post("/data/feature?<opts..>", format="application/json", data="<body>")]
+fn feature_action(
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    conn: State<DbReadWrite>,
+    schema: State<Option<serde_json::value::Value>>,
+    worker: State<worker::Worker>,
+    options: State<options::Options>,
+    key: idempotency::Key,
+    precondition: condition::Precondition,
+    opts: Form<Input>,
+    body: Data
+) -> Result<idempotency::Idempotent<Json<serde_json::Value>>, HecateError> {
+    let conn = conn.get()?;
+
+    auth_rules.allows_feature_create(&mut auth, &*conn)?;
+
+    let uid = auth.uid.unwrap();
+
+    precondition.condition()?;
+
+    if let Some(record) = key.get(&*conn, &uid)? {
+        return Ok(idempotency::Idempotent(Json(json!(true)), Some(record)));
+    }
+
+    let input_srid = opts.input_srid.unwrap_or(feature::SRID);
+    feature::check_srid(&*conn, &input_srid)?;
+
     let body_str: String;
     {
         let mut body_stream = body.open();
@@ -1389,70 +3821,183 @@ fn osm_changeset_create(
             body_vec.append(&mut buffer[..buffer_size].to_vec());
         }
 
-        body_str = match String::from_utf8(body_vec) {
-            Ok(body_str) => body_str,
-            Err(_) => { return Err(status::Custom(HTTPStatus::BadRequest, String::from("Invalid JSON - Non-UTF8"))); }
+        body_str = String::from_utf8(body_vec).unwrap();
+    }
+
+    let mut feat = match body_str.parse::<GeoJson>() {
+        Err(_) => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON Feature"), None)); }
+        Ok(geo) => match geo {
+            GeoJson::Feature(feat) => feat,
+            _ => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON Feature"), None)); }
         }
+    };
+
+    feature::reproject(&*conn, &mut feat, &input_srid)?;
+
+    if !auth.has_admin_access() && feature::get_action(&feat)? == feature::Action::Create {
+        feature::check_quota(&*conn, &uid, &options.quota(), 1)?;
     }
 
-    let uid = auth.uid.unwrap();
+    if feature::is_force(&feat)? {
+        auth_rules.allows_feature_force(&mut auth, &*conn)?;
+    };
 
-    let map = match osm::to_delta(&body_str) {
-        Ok(map) => map,
-        Err(err) => { return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string())); }
+    feature::clean_props(&mut feat, &options.properties())?;
+    feature::coerce::props(&mut feat, schema.inner(), &options.properties())?;
+    feature::transform::props(&mut feat, &options.transform())?;
+    feature::provenance::Provenance::new(&*conn, &auth, &options.provenance())?.stamp(&*conn, &mut feat)?;
+
+    let delta_message = match feat.foreign_members {
+        None => { return Err(HecateError::new(400, String::from("Feature Must have message property for delta"), None)); }
+        Some(ref members) => match members.get("message") {
+            Some(message) => match message.as_str() {
+                Some(message) => String::from(message),
+                None => { return Err(HecateError::new(400, String::from("Feature Must have message property for delta"), None)); }
+            },
+            None => { return Err(HecateError::new(400, String::from("Feature Must have message property for delta"), None)); }
+        }
     };
 
+    let tenant = auth.scope()?;
+
+    let window = delta::batch::window(&*conn, &uid, &options.batch())?;
+
     let trans = match conn.transaction() {
         Ok(trans) => trans,
-        Err(_) => { return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to open transaction"))); }
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
     };
 
-    let delta_id = match delta::open(&trans, &map, &uid) {
-        Ok(id) => id,
+    let batch = match window {
+        None => None,
+        Some(_) => match delta::batch::current(&trans, &uid) {
+            Ok(batch) => batch,
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        }
+    };
+
+    let delta_id = match batch {
+        Some(id) => id,
+        None => {
+            let mut map: HashMap<String, Option<String>> = HashMap::new();
+            map.insert(String::from("message"), Some(delta_message.clone()));
+            match delta::open(&trans, &map, &uid) {
+                Ok(id) => id,
+                Err(err) => {
+                    trans.set_rollback();
+                    trans.finish().unwrap();
+                    return Err(err);
+                }
+            }
+        }
+    };
+
+    if let Err(err) = precondition.check(&trans, &feat, &tenant) {
+        trans.set_rollback();
+        trans.finish().unwrap();
+        return Err(err);
+    }
+
+    match feature::action(&trans, schema.inner(), &options.geometry(), &auth_rules.feature_bound(&auth), &feat, &Some(delta_id), &tenant) {
+        Ok(res) => {
+            if res.new.is_some() {
+                feat.id = Some(geojson::feature::Id::Number(serde_json::Number::from(res.new.unwrap())));
+            }
+        },
         Err(err) => {
             trans.set_rollback();
             trans.finish().unwrap();
-            return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string()));
+            return Err(err);
         }
+    }
+
+    let fc = geojson::FeatureCollection {
+        bbox: None,
+        features: vec![ feat ],
+        foreign_members: None,
     };
 
-    if trans.commit().is_err() {
-        return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to commit transaction")));
+    let written = match batch {
+        Some(_) => delta::batch::append(&delta_id, &trans, &fc, &delta_message),
+        None => delta::modify(&delta_id, &trans, &fc, &uid)
+    };
+
+    if let Err(err) = written {
+        trans.set_rollback();
+        trans.finish().unwrap();
+        return Err(err);
     }
 
-    Ok(delta_id.to_string())
+    let record = match key.set(&trans, &uid, &delta_id, feature::ids(&fc.features)) {
+        Ok(record) => record,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
+    //A batch is left open for further edits, the worker finalizes it & sends its events once it closes
+    let closed = match window {
+        Some(window) => delta::batch::extend(&delta_id, &trans, window),
+        None => delta::finalize(&delta_id, &trans)
+    };
+
+    match closed {
+        Ok(_) => {
+            if trans.commit().is_err() {
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+            }
+
+            if window.is_none() {
+                worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+            }
+
+            Ok(idempotency::Idempotent(Json(json!(true)), record))
+        },
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            Err(err)
+        }
+    }
 }
 
-#[put("/0.6/changeset/<id>/close")]
-fn osm_changeset_close(
+#[derive(FromForm, Debug)]
+struct GeometryUpdate {
+    version: Option<i64>,
+    message: Option<String>
+}
+
+#[put("/data/feature/<id>/geometry?<opts..>", format="application/json", data="<body>")]
+fn feature_set_geometry(
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     conn: State<DbReadWrite>,
-    id: i64
-) -> Result<String, status::Custom<String>> {
-    let conn = conn.get().unwrap();
+    schema: State<Option<serde_json::value::Value>>,
+    worker: State<worker::Worker>,
+    options: State<options::Options>,
+    id: i64,
+    opts: Form<GeometryUpdate>,
+    body: Data
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
 
-    match auth_rules.allows_osm_get(&mut auth, &*conn) {
-        Ok(_) => (),
-        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
-    };
+    auth_rules.allows_feature_create(&mut auth, &*conn)?;
 
-    Ok(id.to_string())
-}
+    let uid = auth.uid.unwrap();
 
-#[put("/0.6/changeset/<delta_id>", data="<body>")]
-fn osm_changeset_modify(
-    mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    conn: State<DbReadWrite>,
-    delta_id: i64,
-    body: Data
-) -> Result<Response<'static>, status::Custom<String>> {
-    let conn = conn.get().unwrap();
+    let version = match opts.version {
+        Some(version) => version,
+        None => { return Err(HecateError::new(400, String::from("version param is required"), None)); }
+    };
 
-    match auth_rules.allows_osm_get(&mut auth, &*conn) {
-        Ok(_) => (),
-        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
+    let delta_message = match opts.message {
+        Some(ref message) => message.clone(),
+        None => { return Err(HecateError::new(400, String::from("message param is required"), None)); }
     };
 
     let body_str: String;
@@ -1468,71 +4013,111 @@ fn osm_changeset_modify(
             body_vec.append(&mut buffer[..buffer_size].to_vec());
         }
 
-        body_str = String::from_utf8(body_vec).unwrap();
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
     }
 
-    let uid = auth.uid.unwrap();
+    let geom = match body_str.parse::<GeoJson>() {
+        Ok(GeoJson::Geometry(geom)) => geom,
+        _ => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON Geometry"), None)); }
+    };
+
+    let tenant = auth.scope()?;
 
     let trans = match conn.transaction() {
         Ok(trans) => trans,
-        Err(_) => { return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to open transaction"))); }
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
     };
 
-    match delta::is_open(&delta_id, &trans) {
-        Ok(true) => (),
-        _ => {
+    //Properties & key are carried over from the current version of the feature
+    let mut feat = match feature::get(&trans, &id, &tenant) {
+        Ok(feat) => feat,
+        Err(err) => {
             trans.set_rollback();
             trans.finish().unwrap();
-
-            let mut conflict_response = Response::new();
-            conflict_response.set_status(HTTPStatus::Conflict);
-            conflict_response.set_sized_body(Cursor::new(format!("The changeset {} was closed at previously", &delta_id)));
-            conflict_response.set_raw_header("Error", format!("The changeset {} was closed at previously", &delta_id));
-            return Ok(conflict_response);
+            return Err(err);
         }
-    }
+    };
 
-    let map = match osm::to_delta(&body_str) {
-        Ok(map) => map,
+    feat.geometry = Some(geom);
+
+    let mut members = feat.foreign_members.unwrap_or(serde_json::Map::new());
+    members.insert(String::from("action"), json!("modify"));
+    members.insert(String::from("version"), json!(version));
+    feat.foreign_members = Some(members);
+
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    map.insert(String::from("message"), Some(delta_message));
+
+    let delta_id = match delta::open(&trans, &map, &uid) {
+        Ok(id) => id,
         Err(err) => {
             trans.set_rollback();
             trans.finish().unwrap();
-            return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string()));
+            return Err(err);
         }
     };
 
-    let delta_id = match delta::modify_props(&delta_id, &trans, &map, &uid) {
-        Ok(id) => id,
+    match feature::action(&trans, schema.inner(), &options.geometry(), &auth_rules.feature_bound(&auth), &feat, &None, &tenant) {
+        Ok(_) => (),
         Err(err) => {
             trans.set_rollback();
             trans.finish().unwrap();
-            return Err(status::Custom(HTTPStatus::InternalServerError, err.to_string()));
+            return Err(err);
         }
     };
 
-    if trans.commit().is_err() {
-        return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to commit transaction")));
+    let fc = geojson::FeatureCollection {
+        bbox: None,
+        features: vec![ feat ],
+        foreign_members: None,
+    };
+
+    match delta::modify(&delta_id, &trans, &fc, &uid) {
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        },
+        _ => ()
     }
 
-    Err(status::Custom(HTTPStatus::Ok, delta_id.to_string()))
+    match delta::finalize(&delta_id, &trans) {
+        Ok(_) => {
+            if trans.commit().is_err() {
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+            }
+
+            worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+
+            Ok(Json(json!(true)))
+        },
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            Err(err)
+        }
+    }
 }
 
-#[post("/0.6/changeset/<delta_id>/upload", data="<body>")]
-fn osm_changeset_upload(
+#[post("/data/feature/<id>/split", format="application/json", data="<body>")]
+fn feature_split(
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     conn: State<DbReadWrite>,
     schema: State<Option<serde_json::value::Value>>,
     worker: State<worker::Worker>,
-    delta_id: i64,
+    options: State<options::Options>,
+    id: i64,
     body: Data
-) -> Result<Response<'static>, status::Custom<String>> {
-    let conn = conn.get().unwrap();
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
 
-    match auth_rules.allows_osm_get(&mut auth, &*conn) {
-        Ok(_) => (),
-        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
-    };
+    auth_rules.allows_feature_force(&mut auth, &*conn)?;
+
+    let uid = auth.uid.unwrap();
 
     let body_str: String;
     {
@@ -1547,194 +4132,138 @@ fn osm_changeset_upload(
             body_vec.append(&mut buffer[..buffer_size].to_vec());
         }
 
-        body_str = String::from_utf8(body_vec).unwrap();
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
     }
 
-    let uid = auth.uid.unwrap();
+    let split: feature::split::Split = match serde_json::from_str(&body_str) {
+        Ok(split) => split,
+        Err(err) => { return Err(HecateError::new(400, format!("Invalid Split - {}", err), None)); }
+    };
+
+    let tenant = auth.scope()?;
 
     let trans = match conn.transaction() {
         Ok(trans) => trans,
-        Err(_) => { return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to open transaction"))); }
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
     };
 
-    match delta::is_open(&delta_id, &trans) {
-        Ok(true) => (),
-        _ => {
+    let splitted = match split.features(&trans, &id, &tenant) {
+        Ok(splitted) => splitted,
+        Err(err) => {
             trans.set_rollback();
             trans.finish().unwrap();
-
-            let mut conflict_response = Response::new();
-            conflict_response.set_status(HTTPStatus::Conflict);
-            conflict_response.set_sized_body(Cursor::new(format!("The changeset {} was closed at previously", &delta_id)));
-            conflict_response.set_raw_header("Error", format!("The changeset {} was closed at previously", &delta_id));
-            return Ok(conflict_response);
+            return Err(err);
         }
-    }
+    };
 
-    let (mut fc, tree) = match osm::to_features(&body_str) {
-        Ok(fctree) => fctree,
-        Err(err) => { return Err(status::Custom(HTTPStatus::ExpectationFailed, err.to_string())); }
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    map.insert(String::from("message"), Some(split.message.clone()));
+
+    let delta_id = match delta::open(&trans, &map, &uid) {
+        Ok(id) => id,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
     };
 
-    let mut ids: HashMap<i64, feature::Response> = HashMap::new();
+    let mut delete = splitted.delete;
 
-    for feat in &mut fc.features {
-        match feature::get_action(&feat) {
-            Ok(action) => {
-                if action == feature::Action::Create {
-                    feature::del_version(feat);
-                }
-            },
-            _ => ()
+    match feature::action(&trans, schema.inner(), &options.geometry(), &auth_rules.feature_bound(&auth), &delete, &None, &tenant) {
+        Ok(_) => (),
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
         }
+    };
+
+    let mut ids: Vec<i64> = Vec::with_capacity(splitted.children.len());
+    let mut children = Vec::with_capacity(splitted.children.len());
+    for mut child in splitted.children {
+        match feature::action(&trans, schema.inner(), &options.geometry(), &auth_rules.feature_bound(&auth), &child, &None, &tenant) {
+            Ok(res) => {
+                let child_id = res.new.unwrap();
 
-        let feat_res = match feature::action(&trans, &schema.inner(), &feat, &Some(delta_id)) {
+                child.id = Some(geojson::feature::Id::Number(serde_json::Number::from(child_id)));
+                ids.push(child_id);
+                children.push(child);
+            },
             Err(err) => {
                 trans.set_rollback();
                 trans.finish().unwrap();
-                return Err(status::Custom(HTTPStatus::ExpectationFailed, err.as_json().to_string()));
-            },
-            Ok(feat_res) => {
-                if feat_res.old.unwrap_or(0) < 0 {
-                    feat.id = Some(geojson::feature::Id::Number(serde_json::Number::from(feat_res.new.unwrap())));
-                }
-
-                feat_res
+                return Err(err);
             }
         };
+    }
 
-        ids.insert(feat_res.old.unwrap(), feat_res);
+    if let Some(ref mut members) = delete.foreign_members {
+        members.insert(String::from("split_into"), json!(ids));
     }
 
-    let diffres = match osm::to_diffresult(ids, tree) {
-        Err(_) => {
-            trans.set_rollback();
-            trans.finish().unwrap();
-            return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Could not format diffResult XML")));
-        },
-        Ok(diffres) => diffres
+    let mut features = vec![ delete ];
+    features.append(&mut children);
+
+    let fc = geojson::FeatureCollection {
+        bbox: None,
+        features: features,
+        foreign_members: None,
     };
 
     match delta::modify(&delta_id, &trans, &fc, &uid) {
-        Ok (_) => (),
-        Err(_) => {
+        Err(err) => {
             trans.set_rollback();
             trans.finish().unwrap();
-            return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Could not create delta")));
-        }
+            return Err(err);
+        },
+        _ => ()
     }
 
     match delta::finalize(&delta_id, &trans) {
-        Ok (_) => {
+        Ok(_) => {
             if trans.commit().is_err() {
-                return Err(status::Custom(HTTPStatus::InternalServerError, String::from("Failed to commit transaction")));
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
             }
 
             worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
 
-            Err(status::Custom(HTTPStatus::Ok, diffres))
-        },
-        Err(_) => {
-            trans.set_rollback();
-            trans.finish().unwrap();
-            Err(status::Custom(HTTPStatus::InternalServerError, String::from("Could not close delta")))
-        }
-    }
-}
-
-#[get("/capabilities")]
-fn osm_capabilities(
-    conn: State<DbReplica>,
-    mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
-) -> Result<String, status::Custom<String>> {
-    let conn = conn.get().unwrap();
-
-    match auth_rules.allows_osm_get(&mut auth, &*conn) {
-        Ok(_) => (),
-        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
-    };
-
-    Ok(String::from("
-        <osm version=\"0.6\" generator=\"Hecate Server\">
-            <api>
-                <version minimum=\"0.6\" maximum=\"0.6\"/>
-                <area maximum=\"0.25\"/>
-                <waynodes maximum=\"2000\"/>
-                <changesets maximum_elements=\"10000\"/>
-                <timeout seconds=\"300\"/>
-                <status database=\"online\" api=\"online\"/>
-            </api>
-        </osm>
-    "))
-}
-
-#[get("/0.6/capabilities")]
-fn osm_06capabilities(
-    conn: State<DbReplica>,
-    mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
-) -> Result<String, status::Custom<String>> {
-    let conn = conn.get().unwrap();
-
-    match auth_rules.allows_osm_get(&mut auth, &*conn) {
-        Ok(_) => (),
-        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
-    };
-
-    Ok(String::from("
-        <osm version=\"0.6\" generator=\"Hecate Server\">
-            <api>
-                <version minimum=\"0.6\" maximum=\"0.6\"/>
-                <area maximum=\"0.25\"/>
-                <waynodes maximum=\"2000\"/>
-                <changesets maximum_elements=\"10000\"/>
-                <timeout seconds=\"300\"/>
-                <status database=\"online\" api=\"online\"/>
-            </api>
-        </osm>
-    "))
-}
-
-#[get("/0.6/user/details")]
-fn osm_user(
-    conn: State<DbReplica>,
-    mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>
-) -> Result<String, status::Custom<String>> {
-    let conn = conn.get().unwrap();
-
-    match auth_rules.allows_osm_get(&mut auth, &*conn) {
-        Ok(_) => (),
-        Err(_) => { return Err(status::Custom(HTTPStatus::Unauthorized, String::from("Not Authorized"))); }
-    };
+            Ok(Json(json!({
+                "delta": delta_id,
+                "features": ids
+            })))
+        },
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            Err(err)
+        }
+    }
+}
 
-    Ok(String::from("
-        <osm version=\"0.6\" generator=\"Hecate Server\">
-            <user id=\"1\" display_name=\"user\" account_created=\"2010-06-18T12:34:58Z\">
-                <description></description>
-                <languages><lang>en-US</lang><lang>en</lang></languages>
-                <messages>
-                    <recieved county=\"0\" unread=\"0\"/>
-                    <send count=\"0\"/>
-                </messages>
-            </user>
-        </osm>
-    "))
+#[derive(FromForm, Debug)]
+struct RestoreVersion {
+    version: i64
 }
 
-#[post("/data/feature", format="application/json", data="<body>")]
-fn feature_action(
+#[post("/data/feature/<id>/restore?<opts..>", format="application/json", data="<body>")]
+fn feature_restore(
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     conn: State<DbReadWrite>,
     schema: State<Option<serde_json::value::Value>>,
     worker: State<worker::Worker>,
+    options: State<options::Options>,
+    id: i64,
+    opts: Form<RestoreVersion>,
     body: Data
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
 
-    auth_rules.allows_feature_create(&mut auth, &*conn)?;
+    auth_rules.allows_feature_force(&mut auth, &*conn)?;
 
     let uid = auth.uid.unwrap();
 
@@ -1751,39 +4280,36 @@ fn feature_action(
             body_vec.append(&mut buffer[..buffer_size].to_vec());
         }
 
-        body_str = String::from_utf8(body_vec).unwrap();
-    }
-
-    let mut feat = match body_str.parse::<GeoJson>() {
-        Err(_) => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON Feature"), None)); }
-        Ok(geo) => match geo {
-            GeoJson::Feature(feat) => feat,
-            _ => { return Err(HecateError::new(400, String::from("Body must be valid GeoJSON Feature"), None)); }
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
         }
-    };
+    }
 
-    if feature::is_force(&feat)? {
-        auth_rules.allows_feature_force(&mut auth, &*conn)?;
+    let rollback: feature::rollback::Rollback = match serde_json::from_str(&body_str) {
+        Ok(rollback) => rollback,
+        Err(err) => { return Err(HecateError::new(400, format!("Invalid Restore - {}", err), None)); }
     };
 
-    let delta_message = match feat.foreign_members {
-        None => { return Err(HecateError::new(400, String::from("Feature Must have message property for delta"), None)); }
-        Some(ref members) => match members.get("message") {
-            Some(message) => match message.as_str() {
-                Some(message) => String::from(message),
-                None => { return Err(HecateError::new(400, String::from("Feature Must have message property for delta"), None)); }
-            },
-            None => { return Err(HecateError::new(400, String::from("Feature Must have message property for delta"), None)); }
-        }
-    };
+    let tenant = auth.scope()?;
 
     let trans = match conn.transaction() {
         Ok(trans) => trans,
         Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
     };
 
+    let feat = match rollback.feature(&trans, &id, &opts.version, &tenant) {
+        Ok(feat) => feat,
+        Err(err) => {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+    };
+
     let mut map: HashMap<String, Option<String>> = HashMap::new();
-    map.insert(String::from("message"), Some(delta_message));
+    map.insert(String::from("message"), Some(rollback.message.clone()));
+
     let delta_id = match delta::open(&trans, &map, &uid) {
         Ok(id) => id,
         Err(err) => {
@@ -1793,18 +4319,14 @@ fn feature_action(
         }
     };
 
-    match feature::action(&trans, schema.inner(), &feat, &None) {
-        Ok(res) => {
-            if res.new.is_some() {
-                feat.id = Some(geojson::feature::Id::Number(serde_json::Number::from(res.new.unwrap())));
-            }
-        },
+    match feature::action(&trans, schema.inner(), &options.geometry(), &auth_rules.feature_bound(&auth), &feat, &None, &tenant) {
+        Ok(_) => (),
         Err(err) => {
             trans.set_rollback();
             trans.finish().unwrap();
             return Err(err);
         }
-    }
+    };
 
     let fc = geojson::FeatureCollection {
         bbox: None,
@@ -1829,7 +4351,10 @@ fn feature_action(
 
             worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
 
-            Ok(Json(json!(true)))
+            Ok(Json(json!({
+                "delta": delta_id,
+                "version": rollback.current + 1
+            })))
         },
         Err(err) => {
             trans.set_rollback();
@@ -1839,19 +4364,33 @@ fn feature_action(
     }
 }
 
-#[get("/data/feature/<id>")]
+#[derive(FromForm, Debug)]
+struct FeatureFormat {
+    geometry: Option<String>
+}
+
+#[get("/data/feature/<id>?<opts..>")]
 fn feature_get(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
-    id: i64
+    auth_rules: auth::Rules,
+    timings: timing::Timings,
+    options: State<options::Options>,
+    id: i64,
+    opts: Form<FeatureFormat>
 ) -> Result<Response<'static>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_feature_get(&mut auth, &*conn)?;
 
-    match feature::get(&*conn, &id) {
+    let geometry = feature::GeometryFormat::parse(&opts.geometry)?;
+
+    allows_format(&options, geometry.name())?;
+
+    let tenant = auth.scope()?;
+
+    match timings.time("db", || feature::get_format(&*conn, &id, &geometry, &tenant)) {
         Ok(feature) => {
-            let feature = geojson::GeoJson::from(feature).to_string();
+            let feature = timings.time("serialize", || geojson::GeoJson::from(feature).to_string());
 
             let mut response = Response::new();
 
@@ -1875,33 +4414,317 @@ struct FeatureQuery {
 fn feature_query(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
     fquery: Form<FeatureQuery>
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    let tenant = auth.scope()?;
 
     if fquery.key.is_some() && fquery.point.is_some() {
         Err(HecateError::new(400, String::from("key and point params cannot be used together"), None))
     } else if fquery.key.is_some() {
-        Ok(Json(feature::query_by_key(&*conn, &fquery.key.as_ref().unwrap())?))
+        Ok(Json(feature::query_by_key(&*conn, &fquery.key.as_ref().unwrap(), &tenant)?))
     } else if fquery.point.is_some() {
-        let mut results = feature::query_by_point(&*conn, &fquery.point.as_ref().unwrap())?;
+        let mut results = feature::query_by_point(&*conn, &fquery.point.as_ref().unwrap(), &tenant)?;
         Ok(Json(results.pop().unwrap()))
     } else {
         Err(HecateError::new(400, String::from("key or point param must be used"), None))
     }
 }
 
+#[post("/data/purge")]
+fn data_purge(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_purge(&mut auth, &*conn)?;
+
+    Ok(Json(feature::purge(&*conn, &options.purge().retention)?))
+}
+
 #[get("/data/feature/<id>/history")]
 fn feature_get_history(
     conn: State<DbReplica>,
     mut auth: auth::Auth,
-    auth_rules: State<auth::CustomAuth>,
+    auth_rules: auth::Rules,
+    id: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_history(&mut auth, &*conn)?;
+
+    Ok(Json(delta::history(&*conn, &id, &auth.scope()?)?))
+}
+
+#[derive(FromForm, Debug)]
+struct HistoryList {
+    after: Option<i64>,
+    limit: Option<i64>,
+    envelope: Option<bool>
+}
+
+#[derive(Deserialize, Debug)]
+struct HistoryRequest {
+    ids: Vec<i64>
+}
+
+#[post("/data/features/history?<opts..>", format="application/json", data="<body>")]
+fn features_history(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    opts: Form<HistoryList>,
+    body: Data
+) -> Result<Listed<Json<serde_json::Value>>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_history(&mut auth, &*conn)?;
+
+    let body_str: String;
+    {
+        let mut body_stream = body.open();
+        let mut body_vec = Vec::new();
+
+        let mut buffer = [0; 1024];
+        let mut buffer_size: usize = 1;
+
+        while buffer_size > 0 {
+            buffer_size = body_stream.read(&mut buffer[..]).unwrap_or(0);
+            body_vec.append(&mut buffer[..buffer_size].to_vec());
+        }
+
+        body_str = match String::from_utf8(body_vec) {
+            Ok(body_str) => body_str,
+            Err(_) => { return Err(HecateError::new(400, String::from("Invalid JSON - Non-UTF8"), None)); }
+        }
+    }
+
+    let history: HistoryRequest = match serde_json::from_str(&body_str) {
+        Ok(history) => history,
+        Err(err) => { return Err(HecateError::new(400, format!("Invalid Feature History - {}", err), None)); }
+    };
+
+    let limit = list_limit(&options, opts.limit, Some(100), Some(1000))?;
+
+    let list = delta::timeline(&*conn, &history.ids, opts.after, limit, &auth.scope()?)?;
+
+    if opts.envelope != Some(true) {
+        return Ok(Listed(Json(list), limit));
+    }
+
+    let next = next_cursor(&list, limit);
+
+    Ok(Listed(Json(envelope(list, limit, opts.after.is_none(), next, None)), limit))
+}
+
+#[get("/data/feature/<id>/blame")]
+fn feature_get_blame(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
     id: i64
 ) -> Result<Json<serde_json::Value>, HecateError> {
     let conn = conn.get()?;
     auth_rules.allows_feature_history(&mut auth, &*conn)?;
 
-    Ok(Json(delta::history(&*conn, &id)?))
+    Ok(Json(delta::blame(&*conn, &id, &auth.scope()?)?))
+}
+
+#[get("/data/feature/<id>/version/<version>/delta")]
+fn feature_get_version_delta(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    options: State<options::Options>,
+    id: i64,
+    version: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_delta_get(&mut auth, &*conn)?;
+
+    let tenant = auth.scope()?;
+
+    let delta_id = delta::version(&*conn, &id, &version, &tenant)?;
+
+    let mut delta = delta::get_json(&*conn, &delta_id, &tenant)?;
+
+    if let Some(features) = delta.get_mut("features") {
+        options.attribution().collection(features);
+    }
+
+    Ok(Json(delta))
+}
+
+#[get("/data/feature/<id>/tags")]
+fn feature_tags(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+
+    Ok(Json(json!(feature::tags::list(&*conn, &id, &auth.scope()?)?)))
+}
+
+#[post("/data/feature/<id>/tags/<tag>")]
+fn feature_tag_add(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    tag: String
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_create(&mut auth, &*conn)?;
+
+    let uid = auth.uid.unwrap();
+
+    Ok(Json(json!(feature::tags::add(&*conn, &id, &tag, &uid, &auth.scope()?)?)))
+}
+
+#[delete("/data/feature/<id>/tags/<tag>")]
+fn feature_tag_remove(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    tag: String
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_create(&mut auth, &*conn)?;
+
+    Ok(Json(json!(feature::tags::remove(&*conn, &id, &tag, &auth.scope()?)?)))
+}
+
+#[get("/data/feature/<id>/comments")]
+fn feature_comments(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+
+    Ok(Json(feature::comments::list(&*conn, &id, &auth.scope()?)?))
+}
+
+#[derive(Deserialize, Debug)]
+struct Comment {
+    comment: String
+}
+
+#[post("/data/feature/<id>/comments", format="application/json", data="<body>")]
+fn feature_comment_create(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    body: Json<Comment>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    auth_rules.is_user(&mut auth, &*conn)?;
+
+    let uid = auth.uid.unwrap();
+
+    Ok(Json(feature::comments::create(&*conn, &id, &uid, &body.comment, &auth.scope()?)?))
+}
+
+#[delete("/data/feature/<id>/comments/<comment>")]
+fn feature_comment_delete(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    comment: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    auth_rules.is_user(&mut auth, &*conn)?;
+
+    let uid = auth.uid.unwrap();
+
+    Ok(Json(json!(feature::comments::delete(&*conn, &id, &comment, &uid, auth.has_admin_access(), &auth.scope()?)?)))
+}
+
+#[post("/data/feature/<id>/comments/<comment>/resolve")]
+fn feature_comment_resolve(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    comment: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    auth_rules.is_user(&mut auth, &*conn)?;
+
+    Ok(Json(feature::comments::resolve(&*conn, &id, &comment, auth.uid, &auth.scope()?)?))
+}
+
+#[post("/data/feature/<id>/comments/<comment>/unresolve")]
+fn feature_comment_unresolve(
+    conn: State<DbReadWrite>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64,
+    comment: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    auth_rules.is_user(&mut auth, &*conn)?;
+
+    Ok(Json(feature::comments::resolve(&*conn, &id, &comment, None, &auth.scope()?)?))
+}
+
+#[derive(FromForm, Debug)]
+struct Related {
+    key: Option<String>,
+    direction: Option<String>,
+    limit: Option<i64>
+}
+
+#[get("/data/feature/<id>/related?<opts..>")]
+fn feature_get_related(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    timings: timing::Timings,
+    options: State<options::Options>,
+    id: i64,
+    opts: Form<Related>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    let tenant = auth.scope()?;
+
+    let key = match opts.key {
+        Some(ref key) => key,
+        None => { return Err(HecateError::new(400, String::from("key param must be used"), None)); }
+    };
+
+    let direction = feature::related::Direction::parse(&opts.direction)?;
+    let limit = list_limit(&options, opts.limit, Some(feature::related::MAX_RELATED), Some(feature::related::MAX_RELATED))?.unwrap_or(feature::related::MAX_RELATED);
+
+    Ok(Json(timings.time("db", || feature::related::get(&*conn, &id, key, &direction, &limit, &tenant))?))
+}
+
+#[get("/data/feature/<id>/bounds")]
+fn feature_get_bounds(
+    conn: State<DbReplica>,
+    mut auth: auth::Auth,
+    auth_rules: auth::Rules,
+    id: i64
+) -> Result<Json<serde_json::Value>, HecateError> {
+    let conn = conn.get()?;
+    auth_rules.allows_feature_get(&mut auth, &*conn)?;
+    auth_rules.allows_bounds_list(&mut auth, &*conn)?;
+
+    Ok(Json(json!(bounds::feature(&*conn, &id, &auth.scope()?)?)))
 }
@@ -0,0 +1,134 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::err::HecateError;
+use crate::{auth, options};
+
+///
+/// Default number of seconds a client is asked to wait before retrying
+///
+pub static RETRY_AFTER: u32 = 5;
+
+///
+/// The groups of expensive routes that can be limited
+///
+#[derive(Debug, PartialEq)]
+pub enum Route {
+    Clone,
+    Regen,
+    Import
+}
+
+///
+/// A count of the requests currently in flight against a limited route
+///
+struct Slot {
+    max: usize,
+    active: Arc<AtomicUsize>
+}
+
+///
+/// Concurrency limits for expensive routes, managed as rocket state
+///
+pub struct Limiter {
+    clone: Option<Slot>,
+    regen: Option<Slot>,
+    import: Option<Slot>,
+    retry_after: u32,
+    exempt_admin: bool
+}
+
+impl Limiter {
+    pub fn new(limits: &options::Limits) -> Self {
+        let slot = |max: Option<usize>| match max {
+            None => None,
+            Some(max) => Some(Slot {
+                max: max,
+                active: Arc::new(AtomicUsize::new(0))
+            })
+        };
+
+        Limiter {
+            clone: slot(limits.clone),
+            regen: slot(limits.regen),
+            import: slot(limits.import),
+            retry_after: limits.retry_after.unwrap_or(RETRY_AFTER),
+            exempt_admin: limits.exempt_admin.unwrap_or(false)
+        }
+    }
+
+    ///
+    /// Reserve a place for a request, the place is released when the
+    /// returned permit is dropped
+    ///
+    pub fn acquire(&self, route: Route, auth: &auth::Auth) -> Result<Permit, HecateError> {
+        let slot = match route {
+            Route::Clone => &self.clone,
+            Route::Regen => &self.regen,
+            Route::Import => &self.import
+        };
+
+        let slot = match slot {
+            None => { return Ok(Permit(None)); },
+            Some(slot) => slot
+        };
+
+        if self.exempt_admin && auth.has_admin_access() {
+            return Ok(Permit(None));
+        }
+
+        if slot.active.fetch_add(1, Ordering::SeqCst) >= slot.max {
+            slot.active.fetch_sub(1, Ordering::SeqCst);
+
+            return Err(HecateError::new(503, String::from("Too many concurrent requests - please retry later"), None)
+                .with_header("Retry-After", self.retry_after.to_string()));
+        }
+
+        Ok(Permit(Some(slot.active.clone())))
+    }
+}
+
+///
+/// A reserved place against a route limit
+///
+pub struct Permit(Option<Arc<AtomicUsize>>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        match self.0 {
+            None => (),
+            Some(ref active) => { active.fetch_sub(1, Ordering::SeqCst); }
+        };
+    }
+}
+
+///
+/// A response body that holds its permit until the body has been fully
+/// sent, so that streamed responses count against the limit for as long
+/// as they are using a database connection
+///
+pub struct Limited<R: Read> {
+    inner: R,
+    _permit: Permit
+}
+
+impl<R: Read> Limited<R> {
+    pub fn new(inner: R, permit: Permit) -> Self {
+        Limited {
+            inner: inner,
+            _permit: permit
+        }
+    }
+}
+
+impl<R: Read> Read for Limited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Read + Seek> Seek for Limited<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
@@ -7,7 +7,10 @@ use std::path::Path;
 use std::fs::File;
 use std::io::Read;
 use hecate::auth::CustomAuth;
+use hecate::options::Options;
+use hecate::retry::Backoff;
 use std::error::Error;
+use std::time::Duration;
 use clap::App;
 
 fn main() {
@@ -60,6 +63,44 @@ fn main() {
         None => None
     };
 
+    let auth: Option<CustomAuth> = match matched.value_of("auth_overlay") {
+        Some(overlay_path) => {
+            let mut overlay_file = match File::open(&Path::new(overlay_path)) {
+                Ok(file) => file,
+                Err(_) => panic!("Failed to open auth overlay file at: {}", overlay_path)
+            };
+
+            let mut overlay_str = String::new();
+
+            overlay_file.read_to_string(&mut overlay_str).unwrap();
+
+            match CustomAuth::overlay(&serde_json::from_str(&*overlay_str).unwrap()) {
+                Ok(auth) => Some(auth),
+                Err(err_msg) => {
+                    println!("ERROR: {}", err_msg);
+                    std::process::exit(1);
+                }
+            }
+        },
+        None => auth
+    };
+
+    let options: Option<Options> = match matched.value_of("config") {
+        Some(config_path) => {
+            let mut config_file = match File::open(&Path::new(config_path)) {
+                Ok(file) => file,
+                Err(_) => panic!("Failed to open config file at: {}", config_path)
+            };
+
+            let mut config_str = String::new();
+
+            config_file.read_to_string(&mut config_str).unwrap();
+
+            Some(serde_json::from_str(&*config_str).unwrap())
+        },
+        None => None
+    };
+
     let port: Option<u16> = match matched.value_of("port") {
         Some(port) => match port.parse() {
             Ok(port) => Some(port),
@@ -76,14 +117,24 @@ fn main() {
         None => None
     };
 
-    database_check(&database, false);
+    let database_timeout: u64 = match matched.value_of("database_timeout") {
+        Some(timeout) => match timeout.parse() {
+            Ok(timeout) => timeout,
+            _ => { panic!("database_timeout arg must be integer value") }
+        },
+        None => 0
+    };
+
+    let backoff = Backoff::new(Duration::from_secs(database_timeout));
+
+    database_check(&database, false, &backoff);
 
     for db_replica in &database_replica {
-        database_check(db_replica, true);
+        database_check(db_replica, true, &backoff);
     }
 
     for db_sandbox in &database_sandbox {
-        database_check(db_sandbox, true);
+        database_check(db_sandbox, true, &backoff);
     }
 
     hecate::start(
@@ -91,12 +142,15 @@ fn main() {
         port,
         workers,
         schema,
-        auth
+        auth,
+        options
     );
 }
 
-fn database_check(conn_str: &String, is_read: bool) {
-    match postgres::Connection::connect(format!("postgres://{}", conn_str), postgres::TlsMode::None) {
+fn database_check(conn_str: &String, is_read: bool, backoff: &Backoff) {
+    let connect = || postgres::Connection::connect(format!("postgres://{}", conn_str), postgres::TlsMode::None);
+
+    match backoff.retry(conn_str, connect) {
         Ok(conn) => {
             let conn_type = match is_read {
                 true => String::from("READ"),
@@ -1,5 +1,10 @@
 use crate::err::HecateError;
 
+///
+/// Reserved key of a notice, ie: planned maintenance, that clients should display to users
+///
+pub static NOTICE: &str = "server.notice";
+
 pub fn list(conn: &impl postgres::GenericConnection) -> Result<Vec<String>, HecateError> {
     match conn.query("
         SELECT key FROM meta ORDER BY key
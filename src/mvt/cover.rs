@@ -0,0 +1,180 @@
+use std::f64::consts::PI;
+use crate::bbox::Bbox;
+use crate::err::HecateError;
+use crate::tenant::Scope;
+use super::grid::{Grid, Extent};
+
+///
+/// Maximum number of tiles a single cover request can enumerate
+///
+pub static MAX_TILES: u64 = 10000;
+
+///
+/// Highest zoom that tiles are served at
+///
+pub static MAX_ZOOM: u8 = 17;
+
+///
+/// Latitude beyond which web mercator is undefined
+///
+static MAX_LAT: f64 = 85.0511287798066;
+
+///
+/// Project a WGS84 coordinate to web mercator
+///
+fn mercator(lon: f64, lat: f64) -> (f64, f64) {
+    let lat = lat.max(-MAX_LAT).min(MAX_LAT);
+
+    let x = lon * 20037508.342789244 / 180.0;
+    let y = ((90.0 + lat) * PI / 360.0).tan().ln() * 20037508.342789244 / PI;
+
+    (x, y)
+}
+
+///
+/// The min (inclusive) & max (exclusive) x & y of the XYZ tiles that cover
+/// each part of the bbox at the given zoom
+///
+fn ranges(grid: &Grid, bbox: &Bbox, z: u8) -> Vec<(u32, u32, u32, u32)> {
+    let (level_maxx, level_maxy) = grid.level_limit(z);
+
+    bbox.split().iter().map(|bbox| {
+        let (minx, miny) = mercator(bbox.minx, bbox.miny);
+        let (maxx, maxy) = mercator(bbox.maxx, bbox.maxy);
+
+        let limits = &grid.tile_limits(Extent { minx, miny, maxx, maxy }, 0)[z as usize];
+
+        //A bbox that lies on a tile edge, ie: a point, still falls within a tile
+        let minx = limits.minx.min(level_maxx - 1);
+        let miny = limits.miny.min(level_maxy - 1);
+        let maxx = limits.maxx.max(minx + 1);
+        let maxy = limits.maxy.max(miny + 1);
+
+        //The grid counts rows from the south, XYZ tiles count them from the north
+        (minx, maxx, level_maxy - maxy, level_maxy - miny)
+    }).collect()
+}
+
+///
+/// Return the `(z, x, y)` XYZ tiles covering the bbox at each zoom in the range,
+/// ordered by zoom, then x, then y
+///
+/// The request is rejected without enumerating any tiles if it would return
+/// more than `MAX_TILES` tiles
+///
+pub fn bbox(bbox: &Bbox, minzoom: u8, maxzoom: u8) -> Result<Vec<(u8, u32, u32)>, HecateError> {
+    if maxzoom > MAX_ZOOM {
+        return Err(HecateError::new(400, format!("Invalid Zoom - maxzoom must be at most {}", MAX_ZOOM), None));
+    } else if minzoom > maxzoom {
+        return Err(HecateError::new(400, String::from("Invalid Zoom - minzoom must not be greater than maxzoom"), None));
+    }
+
+    let grid = Grid::web_mercator();
+
+    let zooms: Vec<(u8, Vec<(u32, u32, u32, u32)>)> = (minzoom..=maxzoom).map(|z| (z, ranges(&grid, &bbox, z))).collect();
+
+    let total: u64 = zooms.iter().map(|(_, ranges)| {
+        ranges.iter().map(|(minx, maxx, miny, maxy)| (maxx - minx) as u64 * (maxy - miny) as u64).sum::<u64>()
+    }).sum();
+
+    if total > MAX_TILES {
+        return Err(HecateError::new(400, format!("Tile cover of {} tiles exceeds the limit of {} tiles", total, MAX_TILES), None));
+    }
+
+    let mut tiles: Vec<(u8, u32, u32)> = Vec::with_capacity(total as usize);
+
+    for (z, ranges) in zooms {
+        let mut zoom: Vec<(u8, u32, u32)> = Vec::new();
+
+        for (minx, maxx, miny, maxy) in ranges {
+            for x in minx..maxx {
+                for y in miny..maxy {
+                    zoom.push((z, x, y));
+                }
+            }
+        }
+
+        zoom.sort();
+        tiles.append(&mut zoom);
+    }
+
+    Ok(tiles)
+}
+
+///
+/// Return the tiles covering the bounding box of the current geometry of a feature
+///
+pub fn feature(conn: &impl postgres::GenericConnection, id: &i64, minzoom: u8, maxzoom: u8, tenant: &Scope) -> Result<Vec<(u8, u32, u32)>, HecateError> {
+    let extent = match conn.query(&*format!("
+        SELECT
+            ST_XMin(geom),
+            ST_YMin(geom),
+            ST_XMax(geom),
+            ST_YMax(geom)
+        FROM
+            geo
+        WHERE
+            id = $1
+            AND {scope}
+    ", scope = tenant.to_sql("tenant")), &[&id]) {
+        Ok(rows) => match rows.iter().next() {
+            None => { return Err(HecateError::new(404, String::from("Feature Not Found"), None)); },
+            Some(row) => vec![row.get(0), row.get(1), row.get(2), row.get(3)]
+        },
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    bbox(&Bbox::new(&extent)?, minzoom, maxzoom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cover_world() {
+        assert_eq!(bbox(&Bbox::parse("-180,-90,180,90").unwrap(), 0, 1).unwrap(), vec![
+            (0, 0, 0),
+            (1, 0, 0),
+            (1, 0, 1),
+            (1, 1, 0),
+            (1, 1, 1)
+        ]);
+    }
+
+    #[test]
+    fn cover_bbox() {
+        assert_eq!(bbox(&Bbox::parse("-77.12,38.79,-76.91,38.99").unwrap(), 10, 10).unwrap(), vec![
+            (10, 292, 391),
+            (10, 292, 392),
+            (10, 293, 391),
+            (10, 293, 392)
+        ]);
+    }
+
+    #[test]
+    fn cover_point() {
+        assert_eq!(bbox(&Bbox::parse("-77.03,38.9,-77.03,38.9").unwrap(), 14, 14).unwrap(), vec![
+            (14, 4686, 6267)
+        ]);
+
+        assert_eq!(bbox(&Bbox::parse("0,0,0,0").unwrap(), 1, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn cover_antimeridian() {
+        assert_eq!(bbox(&Bbox::parse("170,-10,-170,10").unwrap(), 2, 2).unwrap(), vec![
+            (2, 0, 1),
+            (2, 0, 2),
+            (2, 3, 1),
+            (2, 3, 2)
+        ]);
+    }
+
+    #[test]
+    fn cover_limits() {
+        assert!(bbox(&Bbox::parse("-1,-1,1,1").unwrap(), 2, 1).is_err());
+        assert!(bbox(&Bbox::parse("-1,-1,1,1").unwrap(), 0, 18).is_err());
+        assert!(bbox(&Bbox::parse("-180,-90,180,90").unwrap(), 0, 8).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use crate::err::HecateError;
+
+///
+/// A generation of a tile that other requests for the same tile can wait on
+///
+struct Flight {
+    result: Mutex<Option<Result<Vec<u8>, HecateError>>>,
+    done: Condvar
+}
+
+impl Flight {
+    fn new() -> Self {
+        Flight {
+            result: Mutex::new(None),
+            done: Condvar::new()
+        }
+    }
+
+    fn land(&self, result: Result<Vec<u8>, HecateError>) {
+        if let Ok(mut current) = self.result.lock() {
+            if current.is_none() {
+                *current = Some(result);
+            }
+        }
+
+        self.done.notify_all();
+    }
+
+    fn wait(&self) -> Result<Vec<u8>, HecateError> {
+        let mut result = match self.result.lock() {
+            Ok(result) => result,
+            Err(_) => { return Err(error()); }
+        };
+
+        while result.is_none() {
+            result = match self.done.wait(result) {
+                Ok(result) => result,
+                Err(_) => { return Err(error()); }
+            };
+        }
+
+        match *result {
+            Some(ref result) => result.clone(),
+            None => Err(error())
+        }
+    }
+}
+
+///
+/// The tiles currently being generated, managed as rocket state so that
+/// concurrent requests for the same uncached tile share a single generation
+/// rather than each running the same query
+///
+/// Clones share the same flights so that tiles regenerated by the worker are
+/// also shared with requests
+///
+#[derive(Clone)]
+pub struct Flights {
+    inflight: Arc<Mutex<HashMap<String, Arc<Flight>>>>
+}
+
+///
+/// Removes a flight once its generation has finished, failing any waiting
+/// requests if the generation ended without a result
+///
+struct Landing<'a> {
+    flights: &'a Flights,
+    tile: String,
+    flight: Arc<Flight>
+}
+
+impl<'a> Drop for Landing<'a> {
+    fn drop(&mut self) {
+        if let Ok(mut inflight) = self.flights.inflight.lock() {
+            let current = match inflight.get(&self.tile) {
+                Some(flight) => Arc::ptr_eq(flight, &self.flight),
+                None => false
+            };
+
+            if current {
+                inflight.remove(&self.tile);
+            }
+        }
+
+        self.flight.land(Err(error()));
+    }
+}
+
+impl Flights {
+    pub fn new() -> Self {
+        Flights {
+            inflight: Arc::new(Mutex::new(HashMap::new()))
+        }
+    }
+
+    ///
+    /// Generate a tile, or if the tile is already being generated wait for
+    /// and return the result of that generation
+    ///
+    /// Tiles are identified by their `tile_ref` so that the tiles of each tenant
+    /// are generated separately
+    ///
+    pub fn run<F>(&self, tile: String, generate: F) -> Result<Vec<u8>, HecateError>
+        where F: FnOnce() -> Result<Vec<u8>, HecateError>
+    {
+        self.fly(tile, false, generate)
+    }
+
+    ///
+    /// Generate a tile without joining a generation that is already in flight,
+    /// which may have read the features from before a change was committed
+    ///
+    /// A generation already in flight is waited on before this one starts, so
+    /// that it cannot overwrite the regenerated tile, while requests made during
+    /// the regeneration share its result
+    ///
+    pub fn rerun<F>(&self, tile: String, generate: F) -> Result<Vec<u8>, HecateError>
+        where F: FnOnce() -> Result<Vec<u8>, HecateError>
+    {
+        self.fly(tile, true, generate)
+    }
+
+    fn fly<F>(&self, tile: String, fresh: bool, generate: F) -> Result<Vec<u8>, HecateError>
+        where F: FnOnce() -> Result<Vec<u8>, HecateError>
+    {
+        let flight = loop {
+            let mut inflight = match self.inflight.lock() {
+                Ok(inflight) => inflight,
+                Err(_) => { return Err(error()); }
+            };
+
+            let existing = match inflight.get(&tile) {
+                Some(flight) => flight.clone(),
+                None => {
+                    let flight = Arc::new(Flight::new());
+                    inflight.insert(tile.clone(), flight.clone());
+                    break flight;
+                }
+            };
+
+            drop(inflight);
+
+            if !fresh {
+                return existing.wait();
+            }
+
+            //The outcome of the stale generation is not used, only that it has finished
+            let _ = existing.wait();
+
+            if let Ok(mut inflight) = self.inflight.lock() {
+                let landed = match inflight.get(&tile) {
+                    Some(flight) => Arc::ptr_eq(flight, &existing),
+                    None => false
+                };
+
+                if landed {
+                    inflight.remove(&tile);
+                }
+            }
+        };
+
+        let landing = Landing {
+            flights: self,
+            tile: tile,
+            flight: flight
+        };
+
+        let result = generate();
+
+        landing.flight.land(result.clone());
+
+        result
+    }
+}
+
+fn error() -> HecateError {
+    HecateError::new(500, String::from("Failed to generate tile"), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn concurrent_generation() {
+        let flights = Arc::new(Flights::new());
+        let generated = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(10));
+
+        let requests: Vec<thread::JoinHandle<Result<Vec<u8>, HecateError>>> = (0..10).map(|_| {
+            let flights = flights.clone();
+            let generated = generated.clone();
+            let barrier = barrier.clone();
+
+            thread::spawn(move || {
+                barrier.wait();
+
+                flights.run(String::from("14/4686/6267"), || {
+                    generated.fetch_add(1, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(500));
+                    Ok(vec![1, 2, 3])
+                })
+            })
+        }).collect();
+
+        for request in requests {
+            assert_eq!(request.join().unwrap(), Ok(vec![1, 2, 3]));
+        }
+
+        assert_eq!(generated.load(Ordering::SeqCst), 1);
+
+        //A finished generation is not reused
+        assert_eq!(flights.run(String::from("14/4686/6267"), || {
+            generated.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![4])
+        }), Ok(vec![4]));
+
+        assert_eq!(generated.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn regeneration() {
+        let flights = Arc::new(Flights::new());
+        let barrier = Arc::new(Barrier::new(2));
+
+        let stale = {
+            let flights = flights.clone();
+            let barrier = barrier.clone();
+
+            thread::spawn(move || {
+                flights.run(String::from("3/2/1"), || {
+                    barrier.wait();
+                    thread::sleep(Duration::from_millis(500));
+                    Ok(vec![1])
+                })
+            })
+        };
+
+        barrier.wait();
+
+        //A regeneration does not share the generation that was already in flight
+        assert_eq!(flights.rerun(String::from("3/2/1"), || Ok(vec![2])), Ok(vec![2]));
+        assert_eq!(stale.join().unwrap(), Ok(vec![1]));
+    }
+
+    #[test]
+    fn separate_tiles() {
+        let flights = Flights::new();
+
+        let tile = flights.run(String::from("1/0/0"), || {
+            flights.run(String::from("1/1/0"), || Ok(vec![2]))
+        });
+
+        assert_eq!(tile, Ok(vec![2]));
+        assert_eq!(flights.run(String::from("1/0/0"), || Ok(vec![1])), Ok(vec![1]));
+    }
+
+    #[test]
+    fn shared_error() {
+        let flights = Arc::new(Flights::new());
+        let barrier = Arc::new(Barrier::new(2));
+
+        let waiting = {
+            let flights = flights.clone();
+            let barrier = barrier.clone();
+
+            thread::spawn(move || {
+                barrier.wait();
+                thread::sleep(Duration::from_millis(100));
+                flights.run(String::from("2/1/1"), || Ok(vec![1]))
+            })
+        };
+
+        let result = flights.run(String::from("2/1/1"), || {
+            barrier.wait();
+            thread::sleep(Duration::from_millis(500));
+            Err(HecateError::new(500, String::from("Generation Failed"), None))
+        });
+
+        assert_eq!(result, Err(HecateError::new(500, String::from("Generation Failed"), None)));
+        assert_eq!(waiting.join().unwrap(), result);
+    }
+}
@@ -1,8 +1,39 @@
 #[cfg_attr(rustfmt, rustfmt_skip)]
 pub mod grid;
+pub mod cover;
+pub mod flight;
 
 use crate::err::HecateError;
+use crate::options;
+use crate::tenant::Scope;
 pub use self::grid::{Grid};
+pub use self::flight::{Flights};
+
+///
+/// A generated tile along with the number of invalid geometries that were
+/// repaired or skipped & the number of features dropped while generating it
+///
+pub struct Tile {
+    pub tile: Vec<u8>,
+    pub repaired: i64,
+    pub skipped: i64,
+    pub dropped: i64
+}
+
+///
+/// The reference a tile is cached & generated under, the tiles of each tenant
+/// contain different features so are kept apart
+///
+/// Untenanted tiles keep the plain `z/x/y` reference they had before tenants
+/// were introduced
+///
+pub fn tile_ref(z: u8, x: u32, y: u32, tenant: &Scope) -> String {
+    match tenant {
+        Scope::Tenant(None) => format!("{}/{}/{}", z, x, y),
+        Scope::Tenant(Some(tenant)) => format!("{}/{}/{}@{}", z, x, y, tenant),
+        Scope::All(_) => format!("{}/{}/{}@*", z, x, y)
+    }
+}
 
 pub fn db_get(conn: &impl postgres::GenericConnection, coord: String) -> Result<Option<Vec<u8>>, HecateError> {
     match conn.query("
@@ -23,46 +54,245 @@ pub fn db_get(conn: &impl postgres::GenericConnection, coord: String) -> Result<
     }
 }
 
-pub fn db_create(conn: &impl postgres::GenericConnection, z: &u8, x: &u32, y: &u32) -> Result<Vec<u8>, HecateError> {
-    let grid = Grid::web_mercator();
-    let bbox = grid.tile_extent(*z, *x, *y);
-
-    let mut limit: Option<i64> = None;
-    if *z < 10 {
-        limit = Some(10)
-    } else if *z < 14 {
-        limit = Some(100)
-    }
-
-    match conn.query("
-        SELECT
-            ST_AsMVT(q, 'data', 4096, 'geom')
-        FROM (
+///
+/// Selects the features of a tile as the simplified geometries that are encoded
+/// into the tile, in tile coordinates
+///
+/// When `max_features` is set every feature intersecting the tile is ranked so
+/// that those beyond the limit can be dropped & counted, otherwise the built in
+/// limit of the zoom is applied as the features are read
+///
+fn tile_features(tiles: &options::Tiles, tenant: &Scope) -> String {
+    let candidates = match tiles.max_features {
+        None => format!("
             SELECT
                 id,
-                ST_AsMVTGeom(geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326), 4096, 256, false) AS geom
+                geom,
+                1::BIGINT AS rank
             FROM
                 geo
             WHERE
                 ST_Intersects(geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326))
+                AND {scope}
             LIMIT $6
-        ) q
-    ", &[&bbox.minx, &bbox.miny, &bbox.maxx, &bbox.maxy, &grid.srid, &limit]) {
+        ", scope = tenant.to_sql("tenant")),
+        Some(_) => format!("
+            SELECT
+                id,
+                geom,
+                ROW_NUMBER() OVER (ORDER BY {order}) AS rank
+            FROM
+                geo
+            WHERE
+                ST_Intersects(geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326))
+                AND {scope}
+        ", order = tile_order(tiles), scope = tenant.to_sql("tenant"))
+    };
+
+    format!("
+    candidates AS ({candidates}),
+    features AS (
+        SELECT
+            id,
+            geom,
+            ST_IsValid(geom) AS valid
+        FROM
+            candidates
+        WHERE
+            $6::BIGINT IS NULL
+            OR rank <= $6
+    ),
+    tile AS (
+        SELECT
+            id,
+            ST_AsMVTGeom(
+                CASE WHEN valid THEN geom ELSE ST_MakeValid(geom) END,
+                ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326),
+                4096,
+                256,
+                false
+            ) AS geom
+        FROM
+            features
+        WHERE
+            valid OR $7
+    )
+    ", candidates = candidates)
+}
+
+///
+/// The order features are kept in when a tile has more than `max_features`,
+/// the highest values of the `priority` property first, otherwise a sample
+/// that is stable between generations of the tile
+///
+fn tile_order(tiles: &options::Tiles) -> &'static str {
+    match tiles.priority {
+        Some(_) => "props->($8::TEXT) DESC NULLS LAST, md5(id::TEXT)",
+        None => "md5(id::TEXT)"
+    }
+}
+
+///
+/// The `priority` param of the tile query, if the query uses it
+///
+fn tile_priority(tiles: &options::Tiles) -> Option<&String> {
+    match tiles.max_features {
+        Some(_) => tiles.priority.as_ref(),
+        None => None
+    }
+}
+
+///
+/// The maximum number of features encoded into a tile at the given zoom,
+/// `max_features` when configured or otherwise the built in limit
+///
+fn tile_limit(z: &u8, tiles: &options::Tiles) -> Option<i64> {
+    if tiles.max_features.is_some() {
+        tiles.max_features
+    } else if *z < 10 {
+        Some(10)
+    } else if *z < 14 {
+        Some(100)
+    } else {
+        None
+    }
+}
+
+pub fn db_create(conn: &impl postgres::GenericConnection, z: &u8, x: &u32, y: &u32, tiles: &options::Tiles, tenant: &Scope) -> Result<Tile, HecateError> {
+    let grid = Grid::web_mercator();
+    let bbox = grid.tile_extent(*z, *x, *y);
+
+    let limit = tile_limit(z, tiles);
+
+    let repair = tiles.is_repair();
+
+    let mut params: Vec<&dyn postgres::types::ToSql> = vec![&bbox.minx, &bbox.miny, &bbox.maxx, &bbox.maxy, &grid.srid, &limit, &repair];
+    if let Some(priority) = tile_priority(tiles) {
+        params.push(priority);
+    }
+
+    match conn.query(&*format!("
+        WITH {features}
+        SELECT
+            (
+                SELECT
+                    ST_AsMVT(q, 'data', 4096, 'geom')
+                FROM
+                    tile q
+            ),
+            (
+                SELECT count(*) FROM features WHERE NOT valid
+            ),
+            (
+                SELECT count(*) FROM candidates WHERE rank > $6
+            )
+    ", features = tile_features(tiles, tenant)), &params) {
         Ok(res) => {
             let tile: Vec<u8> = res.get(0).get(0);
-            Ok(tile)
+            let invalid: i64 = res.get(0).get(1);
+            let dropped: i64 = res.get(0).get(2);
+
+            if invalid > 0 {
+                println!("WARN: {} {} invalid geometries in tile {}/{}/{}", if repair { "Repaired" } else { "Skipped" }, invalid, z, x, y);
+            }
+
+            if dropped > 0 {
+                println!("WARN: Dropped {} features beyond the limit of {} in tile {}/{}/{}", dropped, limit.unwrap_or(0), z, x, y);
+            }
+
+            Ok(Tile {
+                tile: tile,
+                repaired: if repair { invalid } else { 0 },
+                skipped: if repair { 0 } else { invalid },
+                dropped: dropped
+            })
         },
         Err(err) => Err(HecateError::from_db(err))
     }
 }
 
+///
+/// The coordinate space of the geometries returned by `debug`
+///
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Coordinates {
+    Tile,
+    Geographic
+}
+
+impl Coordinates {
+    pub fn parse(coordinates: &Option<String>) -> Result<Self, HecateError> {
+        match coordinates.as_ref().map(|coordinates| coordinates.as_str()) {
+            None | Some("geographic") => Ok(Coordinates::Geographic),
+            Some("tile") => Ok(Coordinates::Tile),
+            Some(_) => Err(HecateError::new(400, String::from("Invalid Coordinates - must be one of 'tile' or 'geographic'"), None))
+        }
+    }
+}
 
-pub fn db_cache(conn: &impl postgres::GenericConnection, coord: String, tile: &Vec<u8>) -> Result<(), HecateError> {
+///
+/// Return the features a tile would contain as a GeoJSON FeatureCollection
+///
+/// Geometries are the simplified geometries that are encoded into the tile,
+/// either in tile coordinates (0 - 4096 with the origin at the top left) or
+/// converted back to WGS84. Features whose geometry collapses when encoded are
+/// left out as they are not part of the tile
+///
+pub fn debug(conn: &impl postgres::GenericConnection, z: &u8, x: &u32, y: &u32, coordinates: Coordinates, tiles: &options::Tiles, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    let grid = Grid::web_mercator();
+    let bbox = grid.tile_extent(*z, *x, *y);
+
+    let limit = tile_limit(z, tiles);
+
+    let repair = tiles.is_repair();
+
+    let mut params: Vec<&dyn postgres::types::ToSql> = vec![&bbox.minx, &bbox.miny, &bbox.maxx, &bbox.maxy, &grid.srid, &limit, &repair];
+    if let Some(priority) = tile_priority(tiles) {
+        params.push(priority);
+    }
+
+    let geom = match coordinates {
+        Coordinates::Tile => "tile.geom",
+        Coordinates::Geographic => "ST_SetSRID(ST_Affine(
+            tile.geom,
+            (ST_XMax(bounds.geom) - ST_XMin(bounds.geom)) / 4096, 0,
+            0, (ST_YMin(bounds.geom) - ST_YMax(bounds.geom)) / 4096,
+            ST_XMin(bounds.geom), ST_YMax(bounds.geom)
+        ), 4326)"
+    };
+
+    match conn.query(&*format!("
+        WITH {features},
+        bounds AS (
+            SELECT ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326) AS geom
+        )
+        SELECT
+            JSON_Build_Object(
+                'type', 'FeatureCollection',
+                'features', COALESCE(JSON_Agg(JSON_Build_Object(
+                    'id', tile.id,
+                    'type', 'Feature',
+                    'properties', JSON_Build_Object('id', tile.id),
+                    'geometry', ST_AsGeoJSON({geom})::JSON
+                ) ORDER BY tile.id), '[]'::JSON)
+            )
+        FROM
+            tile,
+            bounds
+        WHERE
+            tile.geom IS NOT NULL
+    ", features = tile_features(tiles, tenant), geom = geom), &params) {
+        Ok(res) => Ok(res.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+pub fn db_cache(conn: &impl postgres::GenericConnection, coord: String, tile: &Tile) -> Result<(), HecateError> {
     match conn.query("
-        INSERT INTO tiles (ref, tile, created)
-            VALUES ($1, $2, NOW())
-                ON CONFLICT (ref) DO UPDATE SET tile = $2;
-    ", &[&coord, &tile]) {
+        INSERT INTO tiles (ref, tile, created, repaired, skipped, dropped)
+            VALUES ($1, $2, NOW(), $3, $4, $5)
+                ON CONFLICT (ref) DO UPDATE SET tile = $2, repaired = $3, skipped = $4, dropped = $5;
+    ", &[&coord, &tile.tile, &tile.repaired, &tile.skipped, &tile.dropped]) {
         Err(err) => Err(HecateError::from_db(err)),
         _ => Ok(())
     }
@@ -77,19 +307,22 @@ pub fn wipe(conn: &impl postgres::GenericConnection) -> Result<serde_json::Value
     }
 }
 
-pub fn meta(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32) -> Result<serde_json::Value, HecateError> {
+pub fn meta(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
     match conn.query("
         SELECT
             COALESCE(row_to_json(t), '{}'::JSON)
         FROM (
             SELECT
-                created AS created
+                created AS created,
+                repaired AS repaired,
+                skipped AS skipped,
+                dropped AS dropped
             FROM
                 tiles
             WHERE
                 ref = $1
         ) t;
-    ", &[&format!("{}/{}/{}", &z, &x, &y)]) {
+    ", &[&tile_ref(z, x, y, tenant)]) {
         Ok(rows) => {
             if rows.len() != 1 {
                 Err(HecateError::new(404, String::from("Metadata Not Found"), None))
@@ -102,17 +335,44 @@ pub fn meta(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32) -> R
     }
 }
 
-pub fn get(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32, regen: bool) -> Result<Vec<u8>, HecateError> {
-    if regen == false {
-        match db_get(conn, format!("{}/{}/{}", &z, &x, &y))? {
+///
+/// Return a tile from the cache, generating & caching it if it is not cached or
+/// a regeneration is requested
+///
+/// Concurrent requests for a tile that is being generated wait for & share the
+/// result of that generation. A regeneration never shares a generation that was
+/// already in flight, as it may have started before the change being regenerated
+///
+/// Tiles of admins crossing tenants are generated each time as the worker only
+/// regenerates the tiles of the tenant whose features changed
+///
+pub fn get(conn: &impl postgres::GenericConnection, flights: &Flights, z: u8, x: u32, y: u32, regen: bool, tiles: &options::Tiles, tenant: &Scope) -> Result<Vec<u8>, HecateError> {
+    let coord = tile_ref(z, x, y, tenant);
+    let cached = match tenant {
+        Scope::All(_) => false,
+        Scope::Tenant(_) => true
+    };
+
+    if regen == false && cached {
+        match db_get(conn, coord.clone())? {
             Some(tile) => { return Ok(tile); }
             _ => ()
         };
     }
 
-    let tile = db_create(conn, &z, &x, &y)?;
+    let generate = || {
+        let tile = db_create(conn, &z, &x, &y, tiles, tenant)?;
 
-    db_cache(conn, format!("{}/{}/{}", &z, &x, &y), &tile)?;
+        if cached {
+            db_cache(conn, coord.clone(), &tile)?;
+        }
 
-    Ok(tile)
+        Ok(tile.tile)
+    };
+
+    if regen {
+        flights.rerun(coord.clone(), generate)
+    } else {
+        flights.run(coord.clone(), generate)
+    }
 }
@@ -0,0 +1,2084 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::http::uri::Origin;
+use rocket::{Data, Request, Response, State};
+use regex::Regex;
+use std::io::Cursor;
+use std::net::IpAddr;
+use crate::{auth, mvt, proxy, routing};
+
+pub trait ValidOptions {
+    fn is_valid(&self) -> Result<bool, String>;
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct HeadersHsts {
+    pub max_age: u64,
+    pub include_subdomains: Option<bool>
+}
+
+impl ValidOptions for HeadersHsts {
+    fn is_valid(&self) -> Result<bool, String> {
+        if self.max_age == 0 {
+            return Err(String::from("Options Config Error: 'headers::hsts::max_age' must be greater than 0"));
+        }
+
+        Ok(true)
+    }
+}
+
+///
+/// Security related headers that are added to every response
+///
+/// All headers are disabled by default so that deployments which are not
+/// fronted by TLS are not surprised by browsers refusing plaintext
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Headers {
+    pub hsts: Option<HeadersHsts>,
+    pub nosniff: Option<bool>,
+    pub frame_options: Option<String>
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        Headers {
+            hsts: None,
+            nosniff: None,
+            frame_options: None
+        }
+    }
+}
+
+impl ValidOptions for Headers {
+    fn is_valid(&self) -> Result<bool, String> {
+        match &self.hsts {
+            None => (),
+            Some(ref hsts) => { hsts.is_valid()?; }
+        };
+
+        match &self.frame_options {
+            None => (),
+            Some(ref frame_options) => match frame_options as &str {
+                "DENY" => (),
+                "SAMEORIGIN" => (),
+                _ => { return Err(String::from("Options Config Error: 'headers::frame_options' must be one of 'DENY', 'SAMEORIGIN', or null")); }
+            }
+        };
+
+        Ok(true)
+    }
+}
+
+impl Fairing for Headers {
+    fn info(&self) -> Info {
+        Info {
+            name: "Security Headers",
+            kind: Kind::Response
+        }
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        match &self.hsts {
+            Some(ref hsts) if proxy::Client::from(request).tls => {
+                let value = match hsts.include_subdomains {
+                    Some(true) => format!("max-age={}; includeSubDomains", hsts.max_age),
+                    _ => format!("max-age={}", hsts.max_age)
+                };
+
+                response.set_header(Header::new("Strict-Transport-Security", value));
+            },
+            _ => ()
+        };
+
+        match self.nosniff {
+            Some(true) => { response.set_header(Header::new("X-Content-Type-Options", "nosniff")); },
+            _ => ()
+        };
+
+        match &self.frame_options {
+            Some(ref frame_options) => { response.set_header(Header::new("X-Frame-Options", frame_options.clone())); },
+            None => ()
+        };
+    }
+}
+
+///
+/// How long the history of a deleted feature is retained before it can be purged
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Purge {
+    pub retention: u32
+}
+
+impl Purge {
+    pub fn new() -> Self {
+        Purge {
+            retention: 30
+        }
+    }
+}
+
+impl ValidOptions for Purge {
+    fn is_valid(&self) -> Result<bool, String> {
+        Ok(true)
+    }
+}
+
+///
+/// Policy applied to new users when they are created
+///
+/// Every rule is disabled by default so that existing deployments continue
+/// to accept the same usernames and passwords
+///
+/// When `case_fold` is set, usernames that only differ by case collide on
+/// creation and logging in with Basic auth ignores the case of the username
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct User {
+    pub username: Option<String>,
+    pub reserved: Option<Vec<String>>,
+    pub entropy: Option<f64>,
+    pub case_fold: Option<bool>
+}
+
+impl User {
+    pub fn new() -> Self {
+        User {
+            username: None,
+            reserved: None,
+            entropy: None,
+            case_fold: None
+        }
+    }
+}
+
+impl ValidOptions for User {
+    fn is_valid(&self) -> Result<bool, String> {
+        match &self.username {
+            None => (),
+            Some(ref username) => match Regex::new(username) {
+                Ok(_) => (),
+                Err(_) => { return Err(String::from("Options Config Error: 'user::username' must be a valid regular expression")); }
+            }
+        };
+
+        match self.entropy {
+            Some(entropy) if entropy.is_nan() || entropy < 0.0 => {
+                return Err(String::from("Options Config Error: 'user::entropy' must be a positive number"));
+            },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Match a key against a glob pattern where `*` matches any sequence of
+/// characters and `?` matches any single character
+///
+pub fn glob(pattern: &str, key: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let key: Vec<char> = key.chars().collect();
+
+    let mut p = 0;
+    let mut k = 0;
+
+    //Position of the last * and the key position it was matched against
+    let mut star: Option<(usize, usize)> = None;
+
+    while k < key.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == key[k]) {
+            p += 1;
+            k += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, k));
+            p += 1;
+        } else {
+            match star {
+                Some((star_p, star_k)) => {
+                    //Let the last * absorb one more character and retry
+                    p = star_p + 1;
+                    k = star_k + 1;
+                    star = Some((star_p, star_k + 1));
+                },
+                None => { return false; }
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+///
+/// Property keys that may be stored on a feature when it is written
+///
+/// Keys are matched against glob patterns. A key is allowed if it matches
+/// any `allow` pattern (or `allow` is omitted) and does not match any `deny`
+/// pattern. Disallowed keys either cause the write to fail, or are removed
+/// when the `mode` is `strip`
+///
+/// When `coerce` is enabled, string values are converted to the type the
+/// schema declares for their key
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Properties {
+    pub mode: Option<String>,
+    pub allow: Option<Vec<String>>,
+    pub deny: Option<Vec<String>>,
+    pub coerce: Option<bool>
+}
+
+impl Properties {
+    pub fn new() -> Self {
+        Properties {
+            mode: None,
+            allow: None,
+            deny: None,
+            coerce: None
+        }
+    }
+
+    pub fn is_allowed(&self, key: &str) -> bool {
+        let allowed = match &self.allow {
+            None => true,
+            Some(allow) => allow.iter().any(|pattern| glob(pattern, key))
+        };
+
+        let denied = match &self.deny {
+            None => false,
+            Some(deny) => deny.iter().any(|pattern| glob(pattern, key))
+        };
+
+        allowed && !denied
+    }
+
+    pub fn is_strip(&self) -> bool {
+        self.mode == Some(String::from("strip"))
+    }
+
+    pub fn is_coerce(&self) -> bool {
+        self.coerce == Some(true)
+    }
+}
+
+impl ValidOptions for Properties {
+    fn is_valid(&self) -> Result<bool, String> {
+        match &self.mode {
+            None => (),
+            Some(ref mode) => match mode as &str {
+                "strip" => (),
+                "reject" => (),
+                _ => { return Err(String::from("Options Config Error: 'properties::mode' must be one of 'strip', 'reject', or null")); }
+            }
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Maximum number of concurrent requests to expensive endpoints
+///
+/// Each limit is disabled by default. Once a limit is reached further
+/// requests are rejected with a 503 and a `Retry-After` header instead of
+/// waiting on the connection pool
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Limits {
+    pub clone: Option<usize>,
+    pub regen: Option<usize>,
+    pub import: Option<usize>,
+    pub retry_after: Option<u32>,
+    pub exempt_admin: Option<bool>
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Limits {
+            clone: None,
+            regen: None,
+            import: None,
+            retry_after: None,
+            exempt_admin: None
+        }
+    }
+}
+
+impl ValidOptions for Limits {
+    fn is_valid(&self) -> Result<bool, String> {
+        for (name, limit) in vec![("clone", self.clone), ("regen", self.regen), ("import", self.import)] {
+            match limit {
+                Some(0) => { return Err(format!("Options Config Error: 'limits::{}' must be greater than 0", name)); },
+                _ => ()
+            };
+        }
+
+        Ok(true)
+    }
+}
+
+///
+/// Maximum number of features a non-admin user can create within a
+/// trailing window of `window` seconds
+///
+/// The quota is disabled by default
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Quota {
+    pub create: Option<i64>,
+    pub window: Option<u32>
+}
+
+impl Quota {
+    pub fn new() -> Self {
+        Quota {
+            create: None,
+            window: Some(86400)
+        }
+    }
+
+    pub fn window(&self) -> u32 {
+        self.window.unwrap_or(86400)
+    }
+}
+
+impl ValidOptions for Quota {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.create {
+            Some(create) if create < 0 => {
+                return Err(String::from("Options Config Error: 'quota::create' must be 0 or greater"));
+            },
+            _ => ()
+        };
+
+        match self.window {
+            Some(0) => { return Err(String::from("Options Config Error: 'quota::window' must be greater than 0")); },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// How feature geometries are stored
+///
+/// When a `grid` size is set, coordinates are snapped to a grid of that size
+/// in degrees as features are created, modified, or restored
+///
+/// When `multi` is set, single part geometries are promoted to their multi
+/// part type so that a dataset never mixes ie: `Polygon` & `MultiPolygon`
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Geometry {
+    pub grid: Option<f64>,
+    pub multi: Option<bool>
+}
+
+impl Geometry {
+    pub fn new() -> Self {
+        Geometry {
+            grid: None,
+            multi: None
+        }
+    }
+}
+
+impl ValidOptions for Geometry {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.grid {
+            Some(grid) if !(grid > 0.0) => {
+                return Err(String::from("Options Config Error: 'geometry::grid' must be greater than 0"));
+            },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// The property features are stamped with the source they were created by
+///
+/// The source is the name given to the API token the request was made with,
+/// or otherwise the username of the user. Disabled by default
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Provenance {
+    pub key: Option<String>
+}
+
+impl Provenance {
+    pub fn new() -> Self {
+        Provenance {
+            key: None
+        }
+    }
+}
+
+impl ValidOptions for Provenance {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.key {
+            Some(ref key) if key.len() == 0 || key.len() > 64 || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':' || c == '-') => {
+                Err(String::from("Options Config Error: 'provenance::key' must be a top level property key of a-z, 0-9, _, :, and -"))
+            },
+            _ => Ok(true)
+        }
+    }
+}
+
+///
+/// Operations a transform rule can apply to a property
+///
+pub static TRANSFORM_OPS: [&str; 6] = ["lowercase", "uppercase", "trim", "default", "copy", "length"];
+
+///
+/// Rules that normalize the properties of features as they are created or modified
+///
+/// Each rule applies its `op` to the top level property `field`, in order,
+/// once the properties have passed the `properties` policy and before the
+/// feature is validated against the schema and stored
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Transform {
+    pub rules: Option<Vec<TransformRule>>
+}
+
+impl Transform {
+    pub fn new() -> Self {
+        Transform {
+            rules: None
+        }
+    }
+}
+
+///
+/// A single transform, `copy` & `length` derive the field from the `source`
+/// property while `default` sets the field to `value` if it is missing
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TransformRule {
+    pub field: Option<String>,
+    pub op: Option<String>,
+    pub source: Option<String>,
+    pub value: Option<serde_json::Value>
+}
+
+impl ValidOptions for Transform {
+    fn is_valid(&self) -> Result<bool, String> {
+        let rules = match self.rules {
+            None => { return Ok(true); },
+            Some(ref rules) => rules
+        };
+
+        for (i, rule) in rules.iter().enumerate() {
+            match rule.field {
+                Some(ref field) if field.len() > 0 => (),
+                _ => { return Err(format!("Options Config Error: 'transform::rules[{}]::field' must be a property key", i)); }
+            };
+
+            let op = match rule.op {
+                Some(ref op) if TRANSFORM_OPS.contains(&op.as_str()) => op.as_str(),
+                _ => { return Err(format!("Options Config Error: 'transform::rules[{}]::op' must be one of '{}'", i, TRANSFORM_OPS.join("', '"))); }
+            };
+
+            match (op, &rule.source) {
+                ("copy", Some(source)) | ("length", Some(source)) if source.len() > 0 => (),
+                ("copy", _) | ("length", _) => { return Err(format!("Options Config Error: 'transform::rules[{}]::source' must be a property key for '{}'", i, op)); },
+                (_, Some(_)) => { return Err(format!("Options Config Error: 'transform::rules[{}]::source' can only be used with 'copy' or 'length'", i)); },
+                _ => ()
+            };
+
+            match (op, &rule.value) {
+                ("default", Some(_)) => (),
+                ("default", None) => { return Err(format!("Options Config Error: 'transform::rules[{}]::value' must be set for 'default'", i)); },
+                (_, Some(_)) => { return Err(format!("Options Config Error: 'transform::rules[{}]::value' can only be used with 'default'", i)); },
+                _ => ()
+            };
+        }
+
+        Ok(true)
+    }
+}
+
+///
+/// Webhooks that are notified when a request fails with a server error
+///
+/// Alerts for the same route & error are sent at most once per `window`
+/// seconds, so a single failing query cannot flood the targets
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Alerts {
+    pub targets: Option<Vec<String>>,
+    pub window: Option<u32>
+}
+
+impl Alerts {
+    pub fn new() -> Self {
+        Alerts {
+            targets: None,
+            window: Some(300)
+        }
+    }
+
+    pub fn window(&self) -> u32 {
+        self.window.unwrap_or(300)
+    }
+}
+
+impl ValidOptions for Alerts {
+    fn is_valid(&self) -> Result<bool, String> {
+        match &self.targets {
+            None => (),
+            Some(ref targets) => for target in targets {
+                if !(target.starts_with("http://") || target.starts_with("https://")) {
+                    return Err(format!("Options Config Error: 'alerts::targets' must be http or https URLs, '{}' is not", target));
+                }
+            }
+        };
+
+        match self.window {
+            Some(0) => { return Err(String::from("Options Config Error: 'alerts::window' must be greater than 0")); },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Networks that admin operations may be performed from
+///
+/// An admin whose client IP, as resolved through any trusted `proxy`, is
+/// within a `deny` range or outside of every `allow` range is treated as a
+/// regular user, so admin only endpoints are refused with a `403`
+///
+/// Both lists are unset by default, allowing admins from any network
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Admin {
+    pub allow: Option<Vec<String>>,
+    pub deny: Option<Vec<String>>
+}
+
+impl Admin {
+    pub fn new() -> Self {
+        Admin {
+            allow: None,
+            deny: None
+        }
+    }
+
+    ///
+    /// Whether admin operations are permitted from a client IP, an unknown IP
+    /// is only permitted when neither list is set
+    ///
+    pub fn permits(&self, ip: Option<IpAddr>) -> bool {
+        if self.allow.is_none() && self.deny.is_none() {
+            return true;
+        }
+
+        let ip = match ip {
+            Some(ip) => ip,
+            None => { return false; }
+        };
+
+        let within = |ranges: &Vec<String>| ranges.iter().any(|range| match proxy::Cidr::parse(range) {
+            Ok(range) => range.contains(&ip),
+            Err(_) => false
+        });
+
+        if let Some(ref deny) = self.deny {
+            if within(deny) {
+                return false;
+            }
+        }
+
+        match self.allow {
+            Some(ref allow) => within(allow),
+            None => true
+        }
+    }
+}
+
+impl ValidOptions for Admin {
+    fn is_valid(&self) -> Result<bool, String> {
+        for (name, ranges) in vec![("allow", &self.allow), ("deny", &self.deny)] {
+            if let Some(ranges) = ranges {
+                for range in ranges {
+                    if let Err(err) = proxy::Cidr::parse(range) {
+                        return Err(format!("Options Config Error: 'admin::{}' entry {}", name, err));
+                    }
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+///
+/// Maximum batching window in seconds, for the server or a single user
+///
+pub static MAX_BATCH: u32 = 86400;
+
+///
+/// Coalescing of a user's consecutive single feature edits into one delta
+///
+/// An edit made within `window` seconds of the user's previous edit is added
+/// to the delta of that edit rather than opening a new one, so that a delta
+/// closes once the user has been inactive for the window. Users can set their
+/// own window with a `batch_window` key in their meta, `0` disabling it
+///
+/// Disabled by default
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Batch {
+    pub window: Option<u32>
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Batch {
+            window: None
+        }
+    }
+
+    ///
+    /// The window of a user, given the `batch_window` of their meta if set
+    ///
+    pub fn window(&self, user: Option<u32>) -> Option<u32> {
+        match user.or(self.window) {
+            Some(0) | None => None,
+            Some(window) => Some(window)
+        }
+    }
+}
+
+impl ValidOptions for Batch {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.window {
+            Some(window) if window == 0 || window > MAX_BATCH => {
+                return Err(format!("Options Config Error: 'batch::window' must be between 1 and {}", MAX_BATCH));
+            },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Report how long authentication, database queries, and serialization took
+/// in a `Server-Timing` response header
+///
+/// Disabled by default, when `admin_only` is set the header is only sent to
+/// requests authenticated as an admin
+///
+/// Independently of the header, DB queries taking at least `slow_query`
+/// milliseconds are logged
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Timing {
+    pub enabled: Option<bool>,
+    pub admin_only: Option<bool>,
+    pub slow_query: Option<u64>
+}
+
+impl Timing {
+    pub fn new() -> Self {
+        Timing {
+            enabled: None,
+            admin_only: None,
+            slow_query: None
+        }
+    }
+}
+
+impl ValidOptions for Timing {
+    fn is_valid(&self) -> Result<bool, String> {
+        Ok(true)
+    }
+}
+
+///
+/// Reverse proxies that are trusted to report the client IP and scheme
+/// through the `X-Forwarded-For` and `X-Forwarded-Proto` headers
+///
+/// Both headers are ignored unless the request comes directly from one of the
+/// trusted IPs, so when `trusted` is omitted no proxy is trusted
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Proxy {
+    pub trusted: Option<Vec<String>>
+}
+
+impl Proxy {
+    pub fn new() -> Self {
+        Proxy {
+            trusted: None
+        }
+    }
+
+    pub fn trusted(&self) -> Option<Vec<IpAddr>> {
+        match &self.trusted {
+            None => None,
+            Some(trusted) => Some(trusted.iter().filter_map(|ip| ip.trim().parse().ok()).collect())
+        }
+    }
+}
+
+impl ValidOptions for Proxy {
+    fn is_valid(&self) -> Result<bool, String> {
+        match &self.trusted {
+            None => (),
+            Some(ref trusted) => for ip in trusted {
+                match ip.trim().parse::<IpAddr>() {
+                    Ok(_) => (),
+                    Err(_) => { return Err(format!("Options Config Error: 'proxy::trusted' entry '{}' must be an IP address", ip)); }
+                };
+            }
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Shortest idle period a token can be reaped after, as the last use of a
+/// token is only recorded once a minute
+///
+pub static MIN_IDLE: u32 = 300;
+
+///
+/// Expiry of session & API tokens, along with sliding expiry for session tokens
+///
+/// Session tokens set as a cookie expire `expiry` seconds after they are
+/// created and API tokens `api_expiry` seconds after. Each kind of token is
+/// still accepted for `grace` or `api_grace` seconds after it has expired
+///
+/// Sliding expiry is disabled by default so that tokens keep a fixed lifetime.
+/// When enabled, a session token that is used within `window` seconds of its
+/// expiry is extended to expire `lifetime` seconds from now, but never more
+/// than `max_lifetime` seconds after it was created
+///
+/// Tokens that have not been used for `idle` seconds are deleted whatever
+/// their expiry, reaping is disabled by default
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Session {
+    pub sliding: Option<bool>,
+    pub window: Option<u32>,
+    pub lifetime: Option<u32>,
+    pub max_lifetime: Option<u32>,
+    pub idle: Option<u32>,
+    pub expiry: Option<u32>,
+    pub grace: Option<u32>,
+    pub api_expiry: Option<u32>,
+    pub api_grace: Option<u32>
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session {
+            sliding: None,
+            window: Some(3600),
+            lifetime: Some(14400),
+            max_lifetime: Some(86400),
+            idle: None,
+            expiry: Some(14400),
+            grace: None,
+            api_expiry: Some(2592000),
+            api_grace: None
+        }
+    }
+
+    ///
+    /// Seconds after creation that a token of the given kind expires
+    ///
+    pub fn expiry(&self, kind: &auth::TokenKind) -> u32 {
+        match kind {
+            auth::TokenKind::Session => self.expiry.unwrap_or(14400),
+            auth::TokenKind::Api => self.api_expiry.unwrap_or(2592000)
+        }
+    }
+
+    ///
+    /// Seconds after expiry that a token of the given kind is still accepted
+    ///
+    pub fn grace(&self, kind: &auth::TokenKind) -> u32 {
+        match kind {
+            auth::TokenKind::Session => self.grace.unwrap_or(0),
+            auth::TokenKind::Api => self.api_grace.unwrap_or(0)
+        }
+    }
+
+    pub fn is_sliding(&self) -> bool {
+        self.sliding.unwrap_or(false)
+    }
+
+    pub fn window(&self) -> u32 {
+        self.window.unwrap_or(3600)
+    }
+
+    pub fn lifetime(&self) -> u32 {
+        self.lifetime.unwrap_or(14400)
+    }
+
+    pub fn max_lifetime(&self) -> u32 {
+        self.max_lifetime.unwrap_or(86400)
+    }
+}
+
+impl ValidOptions for Session {
+    fn is_valid(&self) -> Result<bool, String> {
+        for (name, value) in vec![("window", self.window), ("lifetime", self.lifetime), ("max_lifetime", self.max_lifetime), ("expiry", self.expiry), ("api_expiry", self.api_expiry)] {
+            match value {
+                Some(0) => { return Err(format!("Options Config Error: 'session::{}' must be greater than 0", name)); },
+                _ => ()
+            };
+        }
+
+        if self.lifetime() > self.max_lifetime() {
+            return Err(String::from("Options Config Error: 'session::lifetime' must not be greater than 'session::max_lifetime'"));
+        }
+
+        match self.idle {
+            Some(idle) if idle < MIN_IDLE => {
+                return Err(format!("Options Config Error: 'session::idle' must be at least {} seconds", MIN_IDLE));
+            },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// How vector tiles are generated
+///
+/// Invalid geometries that intersect a tile are repaired with `ST_MakeValid`
+/// by default so that a single bad feature cannot break the tile. With an
+/// `invalid` mode of `skip` they are left out of the tile instead
+///
+/// A tile without features is returned as an empty tile by default, with an
+/// `empty` mode of `no-content` a 204 is returned instead
+///
+/// Tiles are served from zoom 0 up to `mvt::cover::MAX_ZOOM`, `minzoom` &
+/// `maxzoom` narrow this range so that tiles no client needs are not generated
+///
+/// A tile contains at most `max_features` features when set, keeping those with
+/// the highest `priority` property or otherwise a sample of the features
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Tiles {
+    pub invalid: Option<String>,
+    pub empty: Option<String>,
+    pub minzoom: Option<u8>,
+    pub maxzoom: Option<u8>,
+    pub max_features: Option<i64>,
+    pub priority: Option<String>
+}
+
+impl Tiles {
+    pub fn new() -> Self {
+        Tiles {
+            invalid: None,
+            empty: None,
+            minzoom: None,
+            maxzoom: None,
+            max_features: None,
+            priority: None
+        }
+    }
+
+    ///
+    /// Whether tiles are served at the given zoom
+    ///
+    pub fn is_zoom(&self, z: u8) -> bool {
+        z >= self.minzoom.unwrap_or(0) && z <= self.maxzoom.unwrap_or(mvt::cover::MAX_ZOOM)
+    }
+
+    pub fn is_repair(&self) -> bool {
+        self.invalid != Some(String::from("skip"))
+    }
+
+    pub fn is_no_content(&self) -> bool {
+        self.empty == Some(String::from("no-content"))
+    }
+}
+
+impl ValidOptions for Tiles {
+    fn is_valid(&self) -> Result<bool, String> {
+        match &self.invalid {
+            None => (),
+            Some(ref invalid) => match invalid as &str {
+                "repair" => (),
+                "skip" => (),
+                _ => { return Err(String::from("Options Config Error: 'tiles::invalid' must be one of 'repair', 'skip', or null")); }
+            }
+        };
+
+        match &self.empty {
+            None => (),
+            Some(ref empty) => match empty as &str {
+                "tile" => (),
+                "no-content" => (),
+                _ => { return Err(String::from("Options Config Error: 'tiles::empty' must be one of 'tile', 'no-content', or null")); }
+            }
+        };
+
+        if self.maxzoom.unwrap_or(0) > mvt::cover::MAX_ZOOM {
+            return Err(format!("Options Config Error: 'tiles::maxzoom' must be at most {}", mvt::cover::MAX_ZOOM));
+        }
+
+        if self.minzoom.unwrap_or(0) > self.maxzoom.unwrap_or(mvt::cover::MAX_ZOOM) {
+            return Err(String::from("Options Config Error: 'tiles::minzoom' must not be greater than 'tiles::maxzoom'"));
+        }
+
+        match self.max_features {
+            Some(max_features) if max_features < 1 => {
+                return Err(String::from("Options Config Error: 'tiles::max_features' must be greater than 0"));
+            },
+            _ => ()
+        };
+
+        match self.priority {
+            Some(ref priority) if priority.len() == 0 || priority.len() > 64 || !priority.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':' || c == '-') => {
+                return Err(String::from("Options Config Error: 'tiles::priority' must be a top level property key of a-z, 0-9, _, :, and -"));
+            },
+            Some(_) if self.max_features.is_none() => {
+                return Err(String::from("Options Config Error: 'tiles::priority' requires 'tiles::max_features'"));
+            },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Default & maximum `limit` applied to list endpoints
+///
+/// An omitted `limit` uses the default and an oversized one is clamped to the
+/// maximum. Either value falls back to the built in value of each endpoint
+/// when not configured
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct List {
+    pub default: Option<i64>,
+    pub max: Option<i64>
+}
+
+impl List {
+    pub fn new() -> Self {
+        List {
+            default: None,
+            max: None
+        }
+    }
+
+    ///
+    /// The effective limit of a request given the endpoint's built in default & maximum
+    ///
+    pub fn limit(&self, requested: Option<i64>, default: Option<i64>, max: Option<i64>) -> Option<i64> {
+        let max = self.max.or(max);
+
+        let limit = match requested {
+            Some(requested) => Some(requested),
+            None => match self.default.or(default) {
+                Some(default) => Some(default),
+                None => max
+            }
+        };
+
+        match (limit, max) {
+            (Some(limit), Some(max)) if limit > max => Some(max),
+            (limit, _) => limit
+        }
+    }
+}
+
+impl ValidOptions for List {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.default {
+            Some(default) if default < 1 => { return Err(String::from("Options Config Error: 'list::default' must be a positive integer")); },
+            _ => ()
+        };
+
+        match self.max {
+            Some(max) if max < 1 => { return Err(String::from("Options Config Error: 'list::max' must be a positive integer")); },
+            _ => ()
+        };
+
+        match (self.default, self.max) {
+            (Some(default), Some(max)) if default > max => { return Err(String::from("Options Config Error: 'list::default' cannot be greater than 'list::max'")); },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Output formats that can be enabled or disabled
+///
+pub static FORMATS: [&str; 5] = ["geojson", "topojson", "wkt", "ewkt", "gpkg"];
+
+///
+/// Output formats that clients may request, allowing expensive formats to be
+/// disabled on a deployment. All formats are enabled by default
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Formats {
+    pub enabled: Option<Vec<String>>
+}
+
+impl Formats {
+    pub fn new() -> Self {
+        Formats {
+            enabled: None
+        }
+    }
+
+    ///
+    /// Whether a format may be requested. Unknown formats are allowed so that
+    /// they are rejected by the validation of the requested endpoint instead
+    ///
+    pub fn allows(&self, format: &str) -> bool {
+        match self.enabled {
+            None => true,
+            Some(ref enabled) => !FORMATS.contains(&format) || enabled.iter().any(|enabled| enabled == format)
+        }
+    }
+}
+
+impl ValidOptions for Formats {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.enabled {
+            None => (),
+            Some(ref enabled) => {
+                for format in enabled {
+                    if !FORMATS.contains(&format.as_str()) {
+                        return Err(format!("Options Config Error: 'formats::enabled' must only contain {}", FORMATS.iter().map(|format| format!("'{}'", format)).collect::<Vec<String>>().join(", ")));
+                    }
+                }
+
+                if !enabled.iter().any(|format| format == "geojson") {
+                    return Err(String::from("Options Config Error: 'formats::enabled' must contain 'geojson'"));
+                }
+            }
+        };
+
+        Ok(true)
+    }
+}
+
+///
+/// Path prefixes of the endpoints that read or write the features & deltas
+///
+pub static DATA: [&str; 5] = ["/api/data", "/api/delta", "/api/deltas", "/api/tiles", "/api/0.6"];
+
+///
+/// Route prefixes that manage users, sessions & server configuration
+///
+pub static ACCOUNT: [&str; 7] = ["/api/auth", "/api/user", "/api/users", "/api/webhooks", "/api/meta", "/api/style", "/api/styles"];
+
+///
+/// Attribution required by the license of the data, sent with every response
+/// of a data endpoint so that clients receive the notice automatically
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Attribution {
+    pub text: Option<String>,
+    pub license: Option<String>
+}
+
+impl Attribution {
+    pub fn new() -> Self {
+        Attribution {
+            text: None,
+            license: None
+        }
+    }
+
+    ///
+    /// Whether the path of a request is that of a data endpoint
+    ///
+    pub fn is_data(path: &str) -> bool {
+        DATA.iter().any(|data| path == *data || path.starts_with(&*format!("{}/", data)))
+    }
+
+    ///
+    /// Add the attribution as top level members of a GeoJSON FeatureCollection
+    ///
+    pub fn collection(&self, fc: &mut serde_json::Value) {
+        if fc["type"] != "FeatureCollection" {
+            return;
+        }
+
+        if let Some(ref text) = self.text {
+            fc["attribution"] = json!(text);
+        }
+
+        if let Some(ref license) = self.license {
+            fc["license"] = json!(license);
+        }
+    }
+}
+
+impl ValidOptions for Attribution {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.text {
+            Some(ref text) if text.trim().len() == 0 || !text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) => {
+                return Err(String::from("Options Config Error: 'attribution::text' must be a non-empty single line of ASCII text"));
+            },
+            _ => ()
+        };
+
+        match self.license {
+            Some(ref license) if !(license.starts_with("http://") || license.starts_with("https://")) || license.contains('>') => {
+                return Err(String::from("Options Config Error: 'attribution::license' must be an http(s) URL"));
+            },
+            _ => ()
+        };
+
+        Ok(true)
+    }
+}
+
+impl Fairing for Attribution {
+    fn info(&self) -> Info {
+        Info {
+            name: "Attribution",
+            kind: Kind::Response
+        }
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if !Attribution::is_data(request.uri().path()) {
+            return;
+        }
+
+        match &self.text {
+            Some(ref text) => { response.set_header(Header::new("X-Hecate-Attribution", text.clone())); },
+            None => ()
+        };
+
+        match &self.license {
+            Some(ref license) => { response.adjoin_header(Header::new("Link", format!("<{}>; rel=\"license\"", license))); },
+            None => ()
+        };
+    }
+}
+
+///
+/// The Postgres schema holding the Hecate tables, allowing multiple datasets
+/// to be served from separate schemas of a single database
+///
+/// `analyze` is the number of features a bulk import must write for the
+/// planner statistics of the features table to be refreshed once it commits
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Database {
+    pub schema: Option<String>,
+    pub analyze: Option<u64>
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Database {
+            schema: None,
+            analyze: None
+        }
+    }
+
+    pub fn is_analyze(&self, features: usize) -> bool {
+        match self.analyze {
+            None => false,
+            Some(threshold) => features as u64 >= threshold
+        }
+    }
+
+    ///
+    /// Resolve unqualified table references of a connection to the configured
+    /// schema. `public` is kept on the path as it usually holds the PostGIS
+    /// types & functions
+    ///
+    pub fn search_path(&self, conn: &impl postgres::GenericConnection) -> Result<(), postgres::Error> {
+        match self.schema {
+            None => Ok(()),
+            Some(ref schema) => conn.batch_execute(&format!("SET search_path TO \"{}\", public;", schema))
+        }
+    }
+}
+
+impl ValidOptions for Database {
+    fn is_valid(&self) -> Result<bool, String> {
+        match self.schema {
+            None => (),
+            Some(ref schema) => {
+                //Only plain identifiers are accepted as the schema is interpolated into SET search_path
+                if schema.len() > 63 || !Regex::new(r"^[a-z_][a-z0-9_]*$").unwrap().is_match(schema) {
+                    return Err(String::from("Options Config Error: 'database::schema' must be a lowercase identifier of up to 63 characters"));
+                }
+            }
+        };
+
+        if self.analyze == Some(0) {
+            return Err(String::from("Options Config Error: 'database::analyze' must be greater than 0"));
+        }
+
+        Ok(true)
+    }
+}
+
+///
+/// Whether the auth rules are skipped for a request, cached on the request by
+/// the `Exempt` fairing for the `Auth` guard to read
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct AuthExempt(pub bool);
+
+///
+/// Route prefixes that are exempt from the auth rules or from the same origin
+/// policy, ie: so that a load balancer can poll a health endpoint of a server
+/// whose auth rules are closed
+///
+/// A prefix matches the path it names and every path below it. The auth
+/// exemption only lifts the auth rules, endpoints that are disabled by a `null`
+/// rule, that require an admin or that need a user to act on remain so
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Exempt {
+    pub auth: Option<Vec<String>>,
+    pub cors: Option<Vec<String>>
+}
+
+impl Exempt {
+    pub fn new() -> Self {
+        Exempt {
+            auth: None,
+            cors: None
+        }
+    }
+
+    fn matches(prefixes: &Option<Vec<String>>, path: &str) -> bool {
+        match prefixes {
+            None => false,
+            Some(prefixes) => prefixes.iter().any(|prefix| path == prefix || path.starts_with(&*format!("{}/", prefix)))
+        }
+    }
+
+    pub fn is_auth(&self, path: &str) -> bool {
+        Exempt::matches(&self.auth, path)
+    }
+
+    pub fn is_cors(&self, path: &str) -> bool {
+        Exempt::matches(&self.cors, path)
+    }
+
+    fn valid_prefixes(prefixes: &Option<Vec<String>>, name: &str) -> Result<bool, String> {
+        if let Some(prefixes) = prefixes {
+            for prefix in prefixes {
+                if !prefix.starts_with('/') || prefix.ends_with('/') || prefix.contains(char::is_whitespace) {
+                    return Err(format!("Options Config Error: 'exempt::{}' prefix '{}' must start with '/' and not end with '/'", name, prefix));
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl ValidOptions for Exempt {
+    fn is_valid(&self) -> Result<bool, String> {
+        Exempt::valid_prefixes(&self.auth, "auth")?;
+        Exempt::valid_prefixes(&self.cors, "cors")?;
+
+        //A prefix that covers a data endpoint, ie: a typo of '/api' for '/api/server', would serve the data to anyone
+        if let Some(ref auth) = self.auth {
+            for prefix in auth {
+                if Attribution::is_data(prefix) || DATA.iter().any(|data| data.starts_with(&*format!("{}/", prefix))) {
+                    return Err(format!("Options Config Error: 'exempt::auth' prefix '{}' covers a data endpoint - exempting it would allow anyone to read & write features without logging in, regardless of the auth rules", prefix));
+                }
+
+                if ACCOUNT.iter().any(|route| prefix == route || prefix.starts_with(&*format!("{}/", route)) || route.starts_with(&*format!("{}/", prefix))) {
+                    return Err(format!("Options Config Error: 'exempt::auth' prefix '{}' covers an account or config endpoint - exempting it would allow anyone to manage users, sessions, webhooks, metadata & styles without logging in, regardless of the auth rules", prefix));
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl Fairing for Exempt {
+    fn info(&self) -> Info {
+        Info {
+            name: "Exempt Routes",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        let exempt = self.is_auth(request.uri().path());
+
+        request.local_cache(|| AuthExempt(exempt));
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if !self.is_cors(request.uri().path()) {
+            return;
+        }
+
+        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+
+        //Preflight requests match no route, answer them with the methods the path allows
+        if request.method() == Method::Options && response.status() == Status::MethodNotAllowed {
+            let allowed = response.headers().get_one("Allow").map(String::from);
+
+            response.set_status(Status::NoContent);
+            response.set_sized_body(Cursor::new(""));
+            response.remove_header("Content-Type");
+
+            if let Some(allowed) = allowed {
+                response.set_header(Header::new("Access-Control-Allow-Methods", allowed));
+            }
+
+            if let Some(headers) = request.headers().get_one("Access-Control-Request-Headers") {
+                response.set_header(Header::new("Access-Control-Allow-Headers", String::from(headers)));
+            }
+        }
+    }
+}
+
+///
+/// Normalization of request paths before they are routed, so that minor
+/// variations of a URL reach the intended route
+///
+/// When `case_insensitive` is set, the static segments of a path that matches
+/// no route are matched ignoring case, dynamic segments such as ids & names
+/// keep their case. When `trailing_slash` is set, trailing slashes are removed
+///
+/// Both are disabled by default so that routing stays exact
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Routing {
+    pub case_insensitive: Option<bool>,
+    pub trailing_slash: Option<bool>
+}
+
+impl Routing {
+    pub fn new() -> Self {
+        Routing {
+            case_insensitive: None,
+            trailing_slash: None
+        }
+    }
+
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive == Some(true)
+    }
+
+    pub fn is_trailing_slash(&self) -> bool {
+        self.trailing_slash == Some(true)
+    }
+
+    ///
+    /// The path a request is routed by, `None` if it is unchanged
+    ///
+    pub fn normalize(&self, path: &str, routes: Option<&routing::Routes>) -> Option<String> {
+        let mut normalized = String::from(path);
+
+        if self.is_trailing_slash() && normalized.ends_with('/') {
+            normalized = format!("/{}", normalized.trim_matches('/'));
+        }
+
+        if self.is_case_insensitive() {
+            if let Some(canonical) = routes.and_then(|routes| routes.canonical(&normalized)) {
+                normalized = canonical;
+            }
+        }
+
+        if normalized == path {
+            None
+        } else {
+            Some(normalized)
+        }
+    }
+}
+
+impl ValidOptions for Routing {
+    fn is_valid(&self) -> Result<bool, String> {
+        Ok(true)
+    }
+}
+
+impl Fairing for Routing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Route Normalization",
+            kind: Kind::Request
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        if !self.is_case_insensitive() && !self.is_trailing_slash() {
+            return;
+        }
+
+        let routes = request.guard::<State<routing::Routes>>().succeeded();
+
+        let path = match self.normalize(request.uri().path(), routes.as_ref().map(|routes| routes.inner())) {
+            None => { return; },
+            Some(path) => path
+        };
+
+        let uri = match request.uri().query() {
+            None => path,
+            Some(query) => format!("{}?{}", path, query)
+        };
+
+        if let Ok(uri) = Origin::parse_owned(uri) {
+            request.set_uri(uri);
+        }
+    }
+}
+
+///
+/// Server behaviour that can be customized by passing a JSON document to the
+/// `--config` flag. Any section that is omitted falls back to its default
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Options {
+    pub headers: Option<Headers>,
+    pub purge: Option<Purge>,
+    pub user: Option<User>,
+    pub properties: Option<Properties>,
+    pub limits: Option<Limits>,
+    pub quota: Option<Quota>,
+    pub timing: Option<Timing>,
+    pub geometry: Option<Geometry>,
+    pub proxy: Option<Proxy>,
+    pub session: Option<Session>,
+    pub tiles: Option<Tiles>,
+    pub list: Option<List>,
+    pub formats: Option<Formats>,
+    pub attribution: Option<Attribution>,
+    pub database: Option<Database>,
+    pub exempt: Option<Exempt>,
+    pub routing: Option<Routing>,
+    pub batch: Option<Batch>,
+    pub admin: Option<Admin>,
+    pub provenance: Option<Provenance>,
+    pub alerts: Option<Alerts>,
+    pub transform: Option<Transform>
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Options {
+            headers: Some(Headers::new()),
+            purge: Some(Purge::new()),
+            user: Some(User::new()),
+            properties: Some(Properties::new()),
+            limits: Some(Limits::new()),
+            quota: Some(Quota::new()),
+            timing: Some(Timing::new()),
+            geometry: Some(Geometry::new()),
+            proxy: Some(Proxy::new()),
+            session: Some(Session::new()),
+            tiles: Some(Tiles::new()),
+            list: Some(List::new()),
+            formats: Some(Formats::new()),
+            attribution: Some(Attribution::new()),
+            database: Some(Database::new()),
+            exempt: Some(Exempt::new()),
+            routing: Some(Routing::new()),
+            batch: Some(Batch::new()),
+            admin: Some(Admin::new()),
+            provenance: Some(Provenance::new()),
+            alerts: Some(Alerts::new()),
+            transform: Some(Transform::new())
+        }
+    }
+
+    pub fn headers(&self) -> Headers {
+        match &self.headers {
+            None => Headers::new(),
+            Some(headers) => headers.clone()
+        }
+    }
+
+    pub fn purge(&self) -> Purge {
+        match &self.purge {
+            None => Purge::new(),
+            Some(purge) => purge.clone()
+        }
+    }
+
+    pub fn user(&self) -> User {
+        match &self.user {
+            None => User::new(),
+            Some(user) => user.clone()
+        }
+    }
+
+    pub fn properties(&self) -> Properties {
+        match &self.properties {
+            None => Properties::new(),
+            Some(properties) => properties.clone()
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        match &self.limits {
+            None => Limits::new(),
+            Some(limits) => limits.clone()
+        }
+    }
+
+    pub fn quota(&self) -> Quota {
+        match &self.quota {
+            None => Quota::new(),
+            Some(quota) => quota.clone()
+        }
+    }
+
+    pub fn timing(&self) -> Timing {
+        match &self.timing {
+            None => Timing::new(),
+            Some(timing) => timing.clone()
+        }
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        match &self.geometry {
+            None => Geometry::new(),
+            Some(geometry) => geometry.clone()
+        }
+    }
+
+    pub fn proxy(&self) -> Proxy {
+        match &self.proxy {
+            None => Proxy::new(),
+            Some(proxy) => proxy.clone()
+        }
+    }
+
+    pub fn session(&self) -> Session {
+        match &self.session {
+            None => Session::new(),
+            Some(session) => session.clone()
+        }
+    }
+
+    pub fn tiles(&self) -> Tiles {
+        match &self.tiles {
+            None => Tiles::new(),
+            Some(tiles) => tiles.clone()
+        }
+    }
+
+    pub fn list(&self) -> List {
+        match &self.list {
+            None => List::new(),
+            Some(list) => list.clone()
+        }
+    }
+
+    pub fn formats(&self) -> Formats {
+        match &self.formats {
+            None => Formats::new(),
+            Some(formats) => formats.clone()
+        }
+    }
+
+    pub fn attribution(&self) -> Attribution {
+        match &self.attribution {
+            None => Attribution::new(),
+            Some(attribution) => attribution.clone()
+        }
+    }
+
+    pub fn database(&self) -> Database {
+        match &self.database {
+            None => Database::new(),
+            Some(database) => database.clone()
+        }
+    }
+
+    pub fn exempt(&self) -> Exempt {
+        match &self.exempt {
+            None => Exempt::new(),
+            Some(exempt) => exempt.clone()
+        }
+    }
+
+    pub fn routing(&self) -> Routing {
+        match &self.routing {
+            None => Routing::new(),
+            Some(routing) => routing.clone()
+        }
+    }
+
+    pub fn batch(&self) -> Batch {
+        match &self.batch {
+            None => Batch::new(),
+            Some(batch) => batch.clone()
+        }
+    }
+
+    pub fn admin(&self) -> Admin {
+        match &self.admin {
+            None => Admin::new(),
+            Some(admin) => admin.clone()
+        }
+    }
+
+    pub fn provenance(&self) -> Provenance {
+        match &self.provenance {
+            None => Provenance::new(),
+            Some(provenance) => provenance.clone()
+        }
+    }
+
+    pub fn alerts(&self) -> Alerts {
+        match &self.alerts {
+            None => Alerts::new(),
+            Some(alerts) => alerts.clone()
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        match &self.transform {
+            None => Transform::new(),
+            Some(transform) => transform.clone()
+        }
+    }
+}
+
+impl ValidOptions for Options {
+    fn is_valid(&self) -> Result<bool, String> {
+        match &self.headers {
+            None => (),
+            Some(ref headers) => { headers.is_valid()?; }
+        };
+
+        match &self.purge {
+            None => (),
+            Some(ref purge) => { purge.is_valid()?; }
+        };
+
+        match &self.user {
+            None => (),
+            Some(ref user) => { user.is_valid()?; }
+        };
+
+        match &self.properties {
+            None => (),
+            Some(ref properties) => { properties.is_valid()?; }
+        };
+
+        match &self.limits {
+            None => (),
+            Some(ref limits) => { limits.is_valid()?; }
+        };
+
+        match &self.quota {
+            None => (),
+            Some(ref quota) => { quota.is_valid()?; }
+        };
+
+        match &self.timing {
+            None => (),
+            Some(ref timing) => { timing.is_valid()?; }
+        };
+
+        match &self.geometry {
+            None => (),
+            Some(ref geometry) => { geometry.is_valid()?; }
+        };
+
+        match &self.proxy {
+            None => (),
+            Some(ref proxy) => { proxy.is_valid()?; }
+        };
+
+        match &self.session {
+            None => (),
+            Some(ref session) => { session.is_valid()?; }
+        };
+
+        match &self.tiles {
+            None => (),
+            Some(ref tiles) => { tiles.is_valid()?; }
+        };
+
+        match &self.list {
+            None => (),
+            Some(ref list) => { list.is_valid()?; }
+        };
+
+        match &self.formats {
+            None => (),
+            Some(ref formats) => { formats.is_valid()?; }
+        };
+
+        match &self.attribution {
+            None => (),
+            Some(ref attribution) => { attribution.is_valid()?; }
+        };
+
+        match &self.database {
+            None => (),
+            Some(ref database) => { database.is_valid()?; }
+        };
+
+        match &self.exempt {
+            None => (),
+            Some(ref exempt) => { exempt.is_valid()?; }
+        };
+
+        match &self.routing {
+            None => (),
+            Some(ref routing) => { routing.is_valid()?; }
+        };
+
+        match &self.batch {
+            None => (),
+            Some(ref batch) => { batch.is_valid()?; }
+        };
+
+        match &self.admin {
+            None => (),
+            Some(ref admin) => { admin.is_valid()?; }
+        };
+
+        match &self.provenance {
+            None => (),
+            Some(ref provenance) => { provenance.is_valid()?; }
+        };
+
+        match &self.alerts {
+            None => (),
+            Some(ref alerts) => { alerts.is_valid()?; }
+        };
+
+        match &self.transform {
+            None => (),
+            Some(ref transform) => { transform.is_valid()?; }
+        };
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match() {
+        assert!(glob("_debug", "_debug"));
+        assert!(!glob("_debug", "_debugger"));
+        assert!(glob("_*", "_debug"));
+        assert!(glob("*:internal", "hecate:internal"));
+        assert!(glob("a*b*c", "a_b_b_c"));
+        assert!(glob("name:??", "name:en"));
+        assert!(!glob("name:??", "name:eng"));
+        assert!(glob("*", ""));
+        assert!(!glob("_*", "debug"));
+    }
+
+    #[test]
+    fn list_limit() {
+        let list = List::new();
+        assert_eq!(list.limit(None, None, None), None);
+        assert_eq!(list.limit(None, Some(20), Some(100)), Some(20));
+        assert_eq!(list.limit(Some(1000), Some(20), Some(100)), Some(100));
+        assert_eq!(list.limit(None, None, Some(100)), Some(100));
+
+        let list = List {
+            default: Some(10),
+            max: Some(500)
+        };
+        assert_eq!(list.limit(None, None, None), Some(10));
+        assert_eq!(list.limit(None, Some(20), Some(100)), Some(10));
+        assert_eq!(list.limit(Some(200), Some(20), Some(100)), Some(200));
+        assert_eq!(list.limit(Some(10000000), None, None), Some(500));
+    }
+
+    #[test]
+    fn formats_allows() {
+        let formats = Formats::new();
+        assert!(formats.allows("topojson"));
+        assert!(formats.is_valid().is_ok());
+
+        let formats = Formats {
+            enabled: Some(vec![String::from("geojson"), String::from("wkt")])
+        };
+        assert!(formats.allows("geojson"));
+        assert!(formats.allows("wkt"));
+        assert!(!formats.allows("topojson"));
+        assert!(!formats.allows("ewkt"));
+        assert!(formats.allows("shapefile"));
+        assert!(formats.is_valid().is_ok());
+
+        assert!(Formats { enabled: Some(vec![String::from("wkt")]) }.is_valid().is_err());
+        assert!(Formats { enabled: Some(vec![String::from("geojson"), String::from("shapefile")]) }.is_valid().is_err());
+    }
+
+    #[test]
+    fn database_schema() {
+        assert!(Database::new().is_valid().is_ok());
+        assert!(Database { schema: Some(String::from("dataset_2")), analyze: None }.is_valid().is_ok());
+        assert!(Database { schema: Some(String::from("Dataset")), analyze: None }.is_valid().is_err());
+        assert!(Database { schema: Some(String::from("2dataset")), analyze: None }.is_valid().is_err());
+        assert!(Database { schema: Some(String::from(r#"public"; DROP TABLE geo; --"#)), analyze: None }.is_valid().is_err());
+        assert!(Database { schema: Some("a".repeat(64)), analyze: None }.is_valid().is_err());
+    }
+
+    #[test]
+    fn session_idle() {
+        let mut session = Session::new();
+        assert!(session.is_valid().is_ok());
+
+        session.idle = Some(MIN_IDLE - 1);
+        assert!(session.is_valid().is_err());
+
+        session.idle = Some(MIN_IDLE);
+        assert!(session.is_valid().is_ok());
+    }
+
+    #[test]
+    fn session_expiry() {
+        let mut session = Session::new();
+        assert_eq!(session.expiry(&auth::TokenKind::Session), 14400);
+        assert_eq!(session.expiry(&auth::TokenKind::Api), 2592000);
+        assert_eq!(session.grace(&auth::TokenKind::Session), 0);
+        assert_eq!(session.grace(&auth::TokenKind::Api), 0);
+
+        session.expiry = Some(600);
+        session.api_grace = Some(60);
+        assert_eq!(session.expiry(&auth::TokenKind::Session), 600);
+        assert_eq!(session.grace(&auth::TokenKind::Api), 60);
+        assert!(session.is_valid().is_ok());
+
+        session.api_expiry = Some(0);
+        assert!(session.is_valid().is_err());
+    }
+
+    #[test]
+    fn database_analyze() {
+        assert!(Database { schema: None, analyze: Some(0) }.is_valid().is_err());
+
+        let database = Database { schema: None, analyze: Some(1000) };
+        assert!(database.is_valid().is_ok());
+        assert!(!database.is_analyze(999));
+        assert!(database.is_analyze(1000));
+        assert!(!Database::new().is_analyze(1000000));
+    }
+
+    #[test]
+    fn attribution_collection() {
+        let attribution = Attribution {
+            text: Some(String::from("Hecate contributors, ODbL")),
+            license: Some(String::from("https://opendatacommons.org/licenses/odbl/"))
+        };
+
+        let mut fc = json!({ "type": "FeatureCollection", "features": [] });
+        attribution.collection(&mut fc);
+        assert_eq!(fc, json!({
+            "type": "FeatureCollection",
+            "features": [],
+            "attribution": "Hecate contributors, ODbL",
+            "license": "https://opendatacommons.org/licenses/odbl/"
+        }));
+
+        let mut feat = json!({ "type": "Feature" });
+        attribution.collection(&mut feat);
+        assert_eq!(feat, json!({ "type": "Feature" }));
+
+        assert!(Attribution::is_data("/api/data/features"));
+        assert!(Attribution::is_data("/api/deltas"));
+        assert!(!Attribution::is_data("/api/users"));
+    }
+
+    #[test]
+    fn tiles_zoom() {
+        assert!(Tiles::new().is_zoom(0));
+        assert!(Tiles::new().is_zoom(17));
+        assert!(!Tiles::new().is_zoom(18));
+
+        let tiles = Tiles { invalid: None, empty: None, minzoom: Some(2), maxzoom: Some(14), max_features: None, priority: None };
+        assert!(tiles.is_valid().is_ok());
+        assert!(!tiles.is_zoom(1));
+        assert!(tiles.is_zoom(2));
+        assert!(tiles.is_zoom(14));
+        assert!(!tiles.is_zoom(15));
+
+        assert!(Tiles { invalid: None, empty: None, minzoom: None, maxzoom: Some(18), max_features: None, priority: None }.is_valid().is_err());
+        assert!(Tiles { invalid: None, empty: None, minzoom: Some(10), maxzoom: Some(8), max_features: None, priority: None }.is_valid().is_err());
+        assert!(Tiles { invalid: None, empty: None, minzoom: Some(18), maxzoom: None, max_features: None, priority: None }.is_valid().is_err());
+    }
+
+    #[test]
+    fn tiles_max_features() {
+        let mut tiles = Tiles::new();
+        tiles.max_features = Some(1000);
+        assert!(tiles.is_valid().is_ok());
+
+        tiles.priority = Some(String::from("rank"));
+        assert!(tiles.is_valid().is_ok());
+
+        tiles.priority = Some(String::from("road.rank"));
+        assert!(tiles.is_valid().is_err());
+
+        tiles.max_features = Some(0);
+        tiles.priority = None;
+        assert!(tiles.is_valid().is_err());
+
+        tiles.max_features = None;
+        tiles.priority = Some(String::from("rank"));
+        assert!(tiles.is_valid().is_err());
+    }
+
+    #[test]
+    fn exempt() {
+        let exempt = Exempt {
+            auth: Some(vec![String::from("/api/schema")]),
+            cors: Some(vec![String::from("/api/tiles")])
+        };
+        assert!(exempt.is_valid().is_ok());
+
+        assert!(exempt.is_auth("/api/schema"));
+        assert!(exempt.is_auth("/api/schema/latest"));
+        assert!(!exempt.is_auth("/api/schemas"));
+        assert!(!exempt.is_auth("/api/tiles/1/0/0"));
+        assert!(exempt.is_cors("/api/tiles/1/0/0"));
+        assert!(!Exempt::new().is_auth("/api/schema"));
+
+        assert!(Exempt { auth: Some(vec![String::from("api/schema")]), cors: None }.is_valid().is_err());
+        assert!(Exempt { auth: None, cors: Some(vec![String::from("/api/tiles/")]) }.is_valid().is_err());
+
+        for prefix in &["/api", "/api/data", "/api/data/features", "/api/deltas"] {
+            assert_eq!(Exempt { auth: Some(vec![String::from(*prefix)]), cors: None }.is_valid().unwrap_err(), format!("Options Config Error: 'exempt::auth' prefix '{}' covers a data endpoint - exempting it would allow anyone to read & write features without logging in, regardless of the auth rules", prefix));
+        }
+
+        for prefix in &["/api/auth", "/api/auth/config", "/api/user", "/api/users", "/api/webhooks", "/api/meta/key", "/api/style", "/api/styles"] {
+            assert_eq!(Exempt { auth: Some(vec![String::from(*prefix)]), cors: None }.is_valid().unwrap_err(), format!("Options Config Error: 'exempt::auth' prefix '{}' covers an account or config endpoint - exempting it would allow anyone to manage users, sessions, webhooks, metadata & styles without logging in, regardless of the auth rules", prefix));
+        }
+
+        assert!(Exempt { auth: Some(vec![String::from("/api/metadata")]), cors: None }.is_valid().is_ok());
+    }
+
+    #[test]
+    fn routing() {
+        let strict = Routing::new();
+        assert_eq!(strict.normalize("/api/data/features/", None), None);
+        assert_eq!(strict.normalize("/API/DATA/features", None), None);
+
+        let slash = Routing { case_insensitive: None, trailing_slash: Some(true) };
+        assert_eq!(slash.normalize("/api/data/features/", None), Some(String::from("/api/data/features")));
+        assert_eq!(slash.normalize("/api/data/features//", None), Some(String::from("/api/data/features")));
+        assert_eq!(slash.normalize("/api/data/features", None), None);
+        assert_eq!(slash.normalize("/", None), None);
+    }
+
+    #[test]
+    fn admin_networks() {
+        let ip = |ip: &str| Some(ip.parse::<IpAddr>().unwrap());
+
+        let open = Admin::new();
+        assert!(open.permits(ip("203.0.113.9")));
+        assert!(open.permits(None));
+
+        let office = Admin {
+            allow: Some(vec![String::from("192.0.2.0/24")]),
+            deny: Some(vec![String::from("192.0.2.66")])
+        };
+        assert!(office.is_valid().is_ok());
+        assert!(office.permits(ip("192.0.2.1")));
+        assert!(!office.permits(ip("192.0.2.66")));
+        assert!(!office.permits(ip("203.0.113.9")));
+        assert!(!office.permits(None));
+
+        let denied = Admin {
+            allow: None,
+            deny: Some(vec![String::from("203.0.113.0/24")])
+        };
+        assert!(denied.permits(ip("192.0.2.1")));
+        assert!(!denied.permits(ip("203.0.113.9")));
+
+        assert!(Admin { allow: Some(vec![String::from("office")]), deny: None }.is_valid().is_err());
+    }
+
+    #[test]
+    fn batch() {
+        let disabled = Batch::new();
+        assert_eq!(disabled.window(None), None);
+        assert_eq!(disabled.window(Some(60)), Some(60));
+        assert!(disabled.is_valid().is_ok());
+
+        let batch = Batch { window: Some(300) };
+        assert_eq!(batch.window(None), Some(300));
+        assert_eq!(batch.window(Some(60)), Some(60));
+        assert_eq!(batch.window(Some(0)), None);
+        assert!(batch.is_valid().is_ok());
+
+        assert!(Batch { window: Some(0) }.is_valid().is_err());
+        assert!(Batch { window: Some(MAX_BATCH + 1) }.is_valid().is_err());
+    }
+
+    #[test]
+    fn provenance() {
+        assert!(Provenance::new().is_valid().is_ok());
+        assert!(Provenance { key: Some(String::from("_source")) }.is_valid().is_ok());
+
+        assert!(Provenance { key: Some(String::from("")) }.is_valid().is_err());
+        assert!(Provenance { key: Some(String::from("source.name")) }.is_valid().is_err());
+        assert!(Provenance { key: Some(String::from("data source")) }.is_valid().is_err());
+    }
+
+    #[test]
+    fn alerts() {
+        assert!(Alerts::new().is_valid().is_ok());
+        assert_eq!(Alerts::new().window(), 300);
+        assert!(Alerts { targets: Some(vec![String::from("https://example.com/hook")]), window: Some(60) }.is_valid().is_ok());
+
+        assert!(Alerts { targets: Some(vec![String::from("example.com/hook")]), window: None }.is_valid().is_err());
+        assert!(Alerts { targets: None, window: Some(0) }.is_valid().is_err());
+    }
+
+    fn rule(field: &str, op: &str, source: Option<&str>, value: Option<serde_json::Value>) -> TransformRule {
+        TransformRule {
+            field: Some(String::from(field)),
+            op: Some(String::from(op)),
+            source: source.map(String::from),
+            value: value
+        }
+    }
+
+    #[test]
+    fn transform() {
+        assert!(Transform::new().is_valid().is_ok());
+
+        assert!(Transform { rules: Some(vec![
+            rule("highway", "lowercase", None, None),
+            rule("name", "trim", None, None),
+            rule("name_length", "length", Some("name"), None),
+            rule("source", "default", None, Some(json!("survey")))
+        ]) }.is_valid().is_ok());
+
+        assert_eq!(Transform { rules: Some(vec![rule("name", "reverse", None, None)]) }.is_valid().err().unwrap(), "Options Config Error: 'transform::rules[0]::op' must be one of 'lowercase', 'uppercase', 'trim', 'default', 'copy', 'length'");
+        assert!(Transform { rules: Some(vec![rule("", "trim", None, None)]) }.is_valid().is_err());
+        assert!(Transform { rules: Some(vec![rule("name_length", "length", None, None)]) }.is_valid().is_err());
+        assert!(Transform { rules: Some(vec![rule("name", "trim", Some("ref"), None)]) }.is_valid().is_err());
+        assert!(Transform { rules: Some(vec![rule("source", "default", None, None)]) }.is_valid().is_err());
+        assert!(Transform { rules: Some(vec![rule("name", "lowercase", None, Some(json!("a")))]) }.is_valid().is_err());
+    }
+}
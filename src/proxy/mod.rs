@@ -0,0 +1,235 @@
+use rocket::request::{self, FromRequest};
+use rocket::{Request, State, Outcome};
+use std::net::IpAddr;
+use crate::options;
+
+///
+/// The client that made a request, as reported by any trusted reverse proxy
+/// in front of Hecate
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Client {
+    pub ip: Option<IpAddr>,
+    pub tls: bool
+}
+
+impl Client {
+    ///
+    /// Resolve the client of a request using the server's proxy options
+    ///
+    pub fn from(request: &Request) -> Client {
+        request.local_cache(|| {
+            let trusted = match request.guard::<State<options::Options>>() {
+                Outcome::Success(options) => options.proxy().trusted(),
+                _ => None
+            };
+
+            Client::resolve(
+                request.remote().map(|remote| remote.ip()),
+                request.headers().get_one("X-Forwarded-For"),
+                request.headers().get_one("X-Forwarded-Proto"),
+                &trusted
+            )
+        }).clone()
+    }
+
+    ///
+    /// Hecate does not terminate TLS itself, so a request is considered secure
+    /// when a proxy reports an https scheme
+    ///
+    /// The forwarded headers are only used when the peer is a trusted proxy, in
+    /// which case the client is the right most address in `X-Forwarded-For` that
+    /// is not itself a trusted proxy. Without a list of trusted proxies no peer is
+    /// trusted and the client is always the connecting peer
+    ///
+    pub fn resolve(remote: Option<IpAddr>, forwarded_for: Option<&str>, forwarded_proto: Option<&str>, trusted: &Option<Vec<IpAddr>>) -> Client {
+        let is_https = |proto: Option<&str>| match proto {
+            Some(proto) => match proto.split(',').next() {
+                Some(proto) => proto.trim().to_lowercase() == "https",
+                None => false
+            },
+            None => false
+        };
+
+        let trusted = match trusted {
+            None => {
+                return Client {
+                    ip: remote,
+                    tls: false
+                };
+            },
+            Some(trusted) => trusted
+        };
+
+        match remote {
+            Some(ref peer) if trusted.contains(peer) => (),
+            _ => {
+                return Client {
+                    ip: remote,
+                    tls: false
+                };
+            }
+        };
+
+        let forwarded: Vec<IpAddr> = match forwarded_for {
+            None => Vec::new(),
+            Some(forwarded_for) => forwarded_for.split(',').filter_map(|ip| ip.trim().parse().ok()).collect()
+        };
+
+        let ip = match forwarded.iter().rev().find(|ip| !trusted.contains(ip)) {
+            Some(ip) => Some(*ip),
+            None => match forwarded.first() {
+                Some(ip) => Some(*ip),
+                None => remote
+            }
+        };
+
+        Client {
+            ip: ip,
+            tls: is_https(forwarded_proto)
+        }
+    }
+}
+
+///
+/// A range of IP addresses in CIDR notation, ie: `10.0.0.0/8`, a bare address
+/// being a range of only that address
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8
+}
+
+impl Cidr {
+    pub fn parse(cidr: &str) -> Result<Self, String> {
+        let mut parts = cidr.trim().splitn(2, '/');
+
+        let network: IpAddr = match parts.next().unwrap_or("").parse() {
+            Ok(network) => network,
+            Err(_) => { return Err(format!("'{}' must be an IP address or CIDR range", cidr)); }
+        };
+
+        let max = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128
+        };
+
+        let prefix = match parts.next() {
+            None => max,
+            Some(prefix) => match prefix.parse::<u8>() {
+                Ok(prefix) if prefix <= max => prefix,
+                _ => { return Err(format!("'{}' must have a prefix length between 0 and {}", cidr, max)); }
+            }
+        };
+
+        Ok(Cidr {
+            network: network,
+            prefix: prefix
+        })
+    }
+
+    ///
+    /// Whether the range contains an address, IPv4 addresses mapped to IPv6
+    /// are treated as the IPv4 address
+    ///
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) if v6.segments()[0..6] == [0, 0, 0, 0, 0, 0xffff] => match v6.to_ipv4() {
+                Some(v4) => IpAddr::V4(v4),
+                None => *ip
+            },
+            _ => *ip
+        };
+
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = if self.prefix == 0 { 0 } else { !0u32 << (32 - self.prefix as u32) };
+                u32::from(network) & mask == u32::from(ip) & mask
+            },
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = if self.prefix == 0 { 0 } else { !0u128 << (128 - self.prefix as u32) };
+                u128::from(network) & mask == u128::from(ip) & mask
+            },
+            _ => false
+        }
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Client {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Client, ()> {
+        Outcome::Success(Client::from(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn resolve_untrusted() {
+        let trusted = Some(vec![ip("10.0.0.1")]);
+
+        assert_eq!(Client::resolve(Some(ip("203.0.113.9")), Some("198.51.100.1"), Some("https"), &trusted), Client {
+            ip: Some(ip("203.0.113.9")),
+            tls: false
+        });
+    }
+
+    #[test]
+    fn resolve_trusted() {
+        let trusted = Some(vec![ip("10.0.0.1"), ip("10.0.0.2")]);
+
+        assert_eq!(Client::resolve(Some(ip("10.0.0.1")), Some("192.0.2.7, 198.51.100.1, 10.0.0.2"), Some("https"), &trusted), Client {
+            ip: Some(ip("198.51.100.1")),
+            tls: true
+        });
+
+        assert_eq!(Client::resolve(Some(ip("10.0.0.1")), Some("10.0.0.2"), Some("http"), &trusted), Client {
+            ip: Some(ip("10.0.0.2")),
+            tls: false
+        });
+
+        assert_eq!(Client::resolve(Some(ip("10.0.0.1")), None, None, &trusted), Client {
+            ip: Some(ip("10.0.0.1")),
+            tls: false
+        });
+    }
+
+    #[test]
+    fn cidr() {
+        let office = Cidr::parse("192.0.2.0/24").unwrap();
+        assert!(office.contains(&ip("192.0.2.1")));
+        assert!(office.contains(&ip("192.0.2.255")));
+        assert!(office.contains(&ip("::ffff:192.0.2.9")));
+        assert!(!office.contains(&ip("192.0.3.1")));
+        assert!(!office.contains(&ip("2001:db8::1")));
+
+        assert!(Cidr::parse("203.0.113.9").unwrap().contains(&ip("203.0.113.9")));
+        assert!(!Cidr::parse("203.0.113.9").unwrap().contains(&ip("203.0.113.10")));
+        assert!(Cidr::parse("0.0.0.0/0").unwrap().contains(&ip("198.51.100.1")));
+        assert!(Cidr::parse("2001:db8::/32").unwrap().contains(&ip("2001:db8:1::1")));
+
+        assert!(Cidr::parse("192.0.2.0/33").is_err());
+        assert!(Cidr::parse("192.0.2.0/").is_err());
+        assert!(Cidr::parse("office").is_err());
+    }
+
+    #[test]
+    fn resolve_unconfigured() {
+        assert_eq!(Client::resolve(Some(ip("203.0.113.9")), Some("198.51.100.1"), Some("HTTPS"), &None), Client {
+            ip: Some(ip("203.0.113.9")),
+            tls: false
+        });
+
+        assert_eq!(Client::resolve(Some(ip("127.0.0.1")), None, Some("https"), &None), Client {
+            ip: Some(ip("127.0.0.1")),
+            tls: false
+        });
+    }
+}
@@ -0,0 +1,255 @@
+use rocket::request::{self, FromRequest};
+use rocket::{Request, Outcome};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use crate::err::HecateError;
+
+///
+/// The `Range` header of a download request
+///
+/// Only a single range of `bytes` is supported, a header with any other unit,
+/// several ranges or an invalid range is ignored and the whole body returned
+///
+/// A range is also ignored if the `If-Range` header does not match the current
+/// `ETag` of the download, so that a client resuming a download never joins
+/// bytes of two different exports
+///
+pub struct Range {
+    pub range: Option<String>,
+    pub if_range: Option<String>
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Range {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Range, ()> {
+        Outcome::Success(Range {
+            range: request.headers().get_one("Range").map(|value| String::from(value.trim())),
+            if_range: request.headers().get_one("If-Range").map(|value| String::from(value.trim()))
+        })
+    }
+}
+
+///
+/// An inclusive range of bytes within a body of `length` bytes
+///
+#[derive(Debug, PartialEq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+    pub length: u64
+}
+
+impl ByteRange {
+    ///
+    /// The value of the `Content-Range` header of the range
+    ///
+    pub fn content_range(&self) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, self.length)
+    }
+}
+
+impl Range {
+    ///
+    /// Whether a range should be returned at all, given the current `ETag` of the body
+    ///
+    pub fn matches(&self, etag: &str) -> bool {
+        match self.range {
+            None => false,
+            Some(_) => match self.if_range {
+                None => true,
+                Some(ref if_range) => if_range == etag
+            }
+        }
+    }
+
+    ///
+    /// The range of a body of the given length that was requested, `None` if
+    /// the whole body should be returned
+    ///
+    pub fn bytes(&self, length: u64) -> Result<Option<ByteRange>, HecateError> {
+        let spec = match self.range {
+            Some(ref range) if range.starts_with("bytes=") && !range.contains(',') => range[6..].trim(),
+            _ => { return Ok(None); }
+        };
+
+        let (start, end) = match spec.find('-') {
+            Some(dash) => (spec[..dash].trim(), spec[dash + 1..].trim()),
+            None => { return Ok(None); }
+        };
+
+        let (start, end) = match (start.parse::<u64>().ok(), end.parse::<u64>().ok()) {
+            //A suffix range of the last n bytes
+            (None, Some(suffix)) if start.is_empty() => {
+                if suffix == 0 || length == 0 {
+                    return Err(unsatisfiable(length));
+                }
+
+                (length.saturating_sub(suffix), length - 1)
+            },
+            (Some(start), None) if end.is_empty() => (start, length.saturating_sub(1)),
+            (Some(start), Some(end)) if start <= end => (start, std::cmp::min(end, length.saturating_sub(1))),
+            _ => { return Ok(None); }
+        };
+
+        if start >= length {
+            return Err(unsatisfiable(length));
+        }
+
+        Ok(Some(ByteRange {
+            start: start,
+            end: end,
+            length: length
+        }))
+    }
+}
+
+fn unsatisfiable(length: u64) -> HecateError {
+    HecateError::new(416, String::from("Range Not Satisfiable"), None)
+        .with_header("Content-Range", format!("bytes */{}", length))
+}
+
+///
+/// Write a streamed export to a temporary file so that its length is known &
+/// a range of it can be read
+///
+/// The file is unlinked once it is opened, so that it is removed from disk
+/// as soon as the download completes or is abandoned
+///
+pub fn materialize(mut body: impl Read) -> Result<File, HecateError> {
+    let path = std::env::temp_dir().join(format!("hecate-{}-{}.download", std::process::id(), rand::random::<u64>()));
+
+    let mut file = match OpenOptions::new().read(true).write(true).create_new(true).open(&path) {
+        Ok(file) => file,
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to prepare download"), Some(err.to_string()))); }
+    };
+
+    fs::remove_file(&path).ok();
+
+    match io::copy(&mut body, &mut file).and_then(|_| file.seek(SeekFrom::Start(0))) {
+        Ok(_) => Ok(file),
+        Err(err) => Err(HecateError::new(500, String::from("Failed to prepare download"), Some(err.to_string())))
+    }
+}
+
+///
+/// The length of a file being downloaded
+///
+pub fn length(file: &File) -> Result<u64, HecateError> {
+    match file.metadata() {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(err) => Err(HecateError::new(500, String::from("Failed to prepare download"), Some(err.to_string())))
+    }
+}
+
+///
+/// A range of bytes of a file, read & seeked as if it were the whole file
+///
+pub struct Slice {
+    file: File,
+    start: u64,
+    length: u64,
+    position: u64
+}
+
+impl Slice {
+    pub fn new(mut file: File, range: &ByteRange) -> Result<Self, HecateError> {
+        match file.seek(SeekFrom::Start(range.start)) {
+            Ok(_) => Ok(Slice {
+                file: file,
+                start: range.start,
+                length: range.end - range.start + 1,
+                position: 0
+            }),
+            Err(err) => Err(HecateError::new(500, String::from("Failed to prepare download"), Some(err.to_string())))
+        }
+    }
+}
+
+impl Read for Slice {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.length.saturating_sub(self.position);
+        if remaining == 0 {
+            return Ok(0);
+        }
+
+        let size = std::cmp::min(buf.len() as u64, remaining) as usize;
+        let read = self.file.read(&mut buf[..size])?;
+        self.position += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl Seek for Slice {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.length as i64 + offset,
+            SeekFrom::Current(offset) => self.position as i64 + offset
+        };
+
+        if position < 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot seek before the start of the range"));
+        }
+
+        self.file.seek(SeekFrom::Start(self.start + position as u64))?;
+        self.position = position as u64;
+
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(range: &str) -> Range {
+        Range {
+            range: Some(String::from(range)),
+            if_range: None
+        }
+    }
+
+    #[test]
+    fn range_bytes() {
+        assert_eq!(Range { range: None, if_range: None }.bytes(100).unwrap(), None);
+
+        assert_eq!(range("bytes=0-9").bytes(100).unwrap(), Some(ByteRange { start: 0, end: 9, length: 100 }));
+        assert_eq!(range("bytes=90-").bytes(100).unwrap(), Some(ByteRange { start: 90, end: 99, length: 100 }));
+        assert_eq!(range("bytes=-10").bytes(100).unwrap(), Some(ByteRange { start: 90, end: 99, length: 100 }));
+        assert_eq!(range("bytes=-500").bytes(100).unwrap(), Some(ByteRange { start: 0, end: 99, length: 100 }));
+        assert_eq!(range("bytes=50-500").bytes(100).unwrap(), Some(ByteRange { start: 50, end: 99, length: 100 }));
+        assert_eq!(range("bytes=50-500").bytes(100).unwrap().unwrap().content_range(), "bytes 50-99/100");
+
+        //Unsupported or invalid ranges return the whole body
+        assert_eq!(range("items=0-9").bytes(100).unwrap(), None);
+        assert_eq!(range("bytes=0-9,20-29").bytes(100).unwrap(), None);
+        assert_eq!(range("bytes=9-0").bytes(100).unwrap(), None);
+        assert_eq!(range("bytes=a-b").bytes(100).unwrap(), None);
+        assert_eq!(range("bytes=10").bytes(100).unwrap(), None);
+    }
+
+    #[test]
+    fn range_matches() {
+        assert!(range("bytes=0-9").matches("\"abc\""));
+        assert!(!Range { range: None, if_range: None }.matches("\"abc\""));
+
+        let mut resume = range("bytes=10-");
+        resume.if_range = Some(String::from("\"abc\""));
+        assert!(resume.matches("\"abc\""));
+        assert!(!resume.matches("\"def\""));
+    }
+
+    #[test]
+    fn range_unsatisfiable() {
+        for unsatisfiable in &["bytes=100-", "bytes=100-200", "bytes=-0"] {
+            assert_eq!(range(unsatisfiable).bytes(100).err().unwrap().as_json(), json!({
+                "code": 416,
+                "reason": "Range Not Satisfiable",
+                "status": "Range Not Satisfiable"
+            }), "not ok - {} is not satisfiable.", unsatisfiable);
+        }
+
+        assert!(range("bytes=0-").bytes(0).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::cmp;
+use std::fmt::Display;
+use std::thread;
+use std::time::{Duration, Instant};
+
+///
+/// Retry an operation with exponential backoff until it succeeds or the
+/// total timeout would be exceeded, ie: connecting to a database that is
+/// still starting up
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub timeout: Duration
+}
+
+impl Backoff {
+    pub fn new(timeout: Duration) -> Self {
+        Backoff {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(5),
+            timeout: timeout
+        }
+    }
+
+    ///
+    /// Attempt the operation, logging each failed attempt. The error of the
+    /// last attempt is returned once there is no time left for another one
+    ///
+    pub fn retry<T, E: Display>(&self, name: &str, mut attempt: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        let start = Instant::now();
+        let mut delay = self.initial;
+        let mut attempts: u32 = 1;
+
+        loop {
+            match attempt() {
+                Ok(res) => { return Ok(res); },
+                Err(err) => {
+                    if start.elapsed() + delay > self.timeout {
+                        if attempts > 1 {
+                            println!("ERROR: Giving up on {} after {} attempts", name, attempts);
+                        }
+
+                        return Err(err);
+                    }
+
+                    println!("WARN: Attempt {} on {} failed, retrying in {}ms: {}", attempts, name, delay.as_millis(), err);
+
+                    thread::sleep(delay);
+
+                    delay = cmp::min(delay * 2, self.max);
+                    attempts = attempts + 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backoff(timeout: u64) -> Backoff {
+        Backoff {
+            initial: Duration::from_millis(10),
+            max: Duration::from_millis(40),
+            timeout: Duration::from_millis(timeout)
+        }
+    }
+
+    #[test]
+    fn retry_succeeds_within_timeout() {
+        let mut attempts = 0;
+
+        let res: Result<u32, String> = backoff(1000).retry("test", || {
+            attempts = attempts + 1;
+
+            if attempts < 4 {
+                Err(String::from("not ready"))
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(res, Ok(4));
+    }
+
+    #[test]
+    fn retry_fails_after_timeout() {
+        let start = Instant::now();
+        let mut attempts = 0;
+
+        let res: Result<(), String> = backoff(100).retry("test", || {
+            attempts = attempts + 1;
+            Err(format!("not ready {}", attempts))
+        });
+
+        assert_eq!(res, Err(format!("not ready {}", attempts)));
+        assert!(attempts > 1);
+        assert!(start.elapsed() < Duration::from_millis(300));
+    }
+
+    #[test]
+    fn retry_without_timeout() {
+        let mut attempts = 0;
+
+        let res: Result<(), String> = backoff(0).retry("test", || {
+            attempts = attempts + 1;
+            Err(String::from("not ready"))
+        });
+
+        assert_eq!(res, Err(String::from("not ready")));
+        assert_eq!(attempts, 1);
+    }
+}
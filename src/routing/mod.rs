@@ -0,0 +1,210 @@
+use rocket::http::Method;
+use rocket::{Request, Route, State};
+use crate::err::HecateError;
+
+///
+/// The method & path of every mounted route, used to tell a request for a path
+/// that does not exist apart from a request using the wrong method for a path
+///
+pub struct Routes(Vec<(Method, Vec<String>)>);
+
+impl Routes {
+    pub fn new<'a>(routes: impl Iterator<Item = &'a Route>) -> Self {
+        Routes(routes.map(|route| (route.method, segments(route.uri.path()))).collect())
+    }
+
+    ///
+    /// The methods of the routes matching the path, in the order they were mounted
+    ///
+    pub fn allowed(&self, path: &str) -> Vec<Method> {
+        let path = segments(path);
+
+        let mut methods: Vec<Method> = Vec::new();
+
+        for (method, route) in self.0.iter() {
+            if matches(route, &path) && !methods.contains(method) {
+                methods.push(*method);
+            }
+        }
+
+        methods
+    }
+
+    ///
+    /// The path with each static segment in the case of the route it matches when
+    /// case is ignored, `None` if the path matches no route or already matches one
+    ///
+    /// A path that matches several routes is given the case of the route with the
+    /// most static segments, as rocket prefers static segments over dynamic ones
+    ///
+    pub fn canonical(&self, path: &str) -> Option<String> {
+        let parts = segments(path);
+
+        if self.0.iter().any(|(_, route)| matches(route, &parts)) {
+            return None;
+        }
+
+        let mut best: Option<(usize, Vec<String>)> = None;
+
+        for (_, route) in self.0.iter() {
+            if let Some(canonical) = canonical(route, &parts) {
+                let statics = route.iter().filter(|segment| !segment.starts_with('<')).count();
+
+                if best.as_ref().map_or(true, |best| statics > best.0) {
+                    best = Some((statics, canonical));
+                }
+            }
+        }
+
+        let (_, canonical) = best?;
+
+        let mut canonical = format!("/{}", canonical.join("/"));
+        if path.ends_with('/') && canonical != "/" {
+            canonical.push('/');
+        }
+
+        Some(canonical)
+    }
+}
+
+fn segments(path: &str) -> Vec<String> {
+    path.split('/').filter(|segment| !segment.is_empty()).map(String::from).collect()
+}
+
+///
+/// Whether the segments of a path match those of a route, a dynamic `<param>`
+/// matches any single segment and `<param..>` matches all remaining segments
+///
+fn matches(route: &Vec<String>, path: &Vec<String>) -> bool {
+    for (i, segment) in route.iter().enumerate() {
+        if segment.starts_with('<') && segment.ends_with("..>") {
+            return true;
+        }
+
+        match path.get(i) {
+            None => { return false; },
+            Some(part) => {
+                if !(segment.starts_with('<') && segment.ends_with('>')) && segment != part {
+                    return false;
+                }
+            }
+        };
+    }
+
+    route.len() == path.len()
+}
+
+///
+/// The segments of a path with the static segments of the route in their case,
+/// `None` if the path does not match the route when case is ignored
+///
+fn canonical(route: &Vec<String>, path: &Vec<String>) -> Option<Vec<String>> {
+    let mut canonical: Vec<String> = Vec::with_capacity(path.len());
+
+    for (i, segment) in route.iter().enumerate() {
+        if segment.starts_with('<') && segment.ends_with("..>") {
+            canonical.extend_from_slice(&path[i.min(path.len())..]);
+            return Some(canonical);
+        }
+
+        match path.get(i) {
+            None => { return None; },
+            Some(part) => {
+                if segment.starts_with('<') && segment.ends_with('>') {
+                    canonical.push(part.clone());
+                } else if segment.eq_ignore_ascii_case(part) {
+                    canonical.push(segment.clone());
+                } else {
+                    return None;
+                }
+            }
+        };
+    }
+
+    if route.len() == path.len() {
+        Some(canonical)
+    } else {
+        None
+    }
+}
+
+///
+/// The error for a request that matched no route, a `405` listing the allowed
+/// methods if the path exists under another method, a `404` otherwise
+///
+pub fn unmatched(request: &Request) -> HecateError {
+    let allowed = match request.guard::<State<Routes>>().succeeded() {
+        Some(routes) => routes.allowed(request.uri().path()),
+        None => Vec::new()
+    };
+
+    if allowed.len() == 0 || allowed.contains(&request.method()) {
+        return HecateError::new(404, String::from("Resource Not Found"), None);
+    }
+
+    let allowed: Vec<String> = allowed.iter().map(|method| method.as_str().to_string()).collect();
+
+    HecateError::from_json(405, json!({
+        "code": 405,
+        "status": "Method Not Allowed",
+        "reason": format!("Method Not Allowed - {} is not supported on this resource", request.method()),
+        "allowed": allowed
+    }), String::from("Method Not Allowed"), None).with_header("Allow", allowed.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes() -> Routes {
+        Routes(vec![
+            (Method::Get, segments("/api/data/feature/<id>")),
+            (Method::Post, segments("/api/data/feature")),
+            (Method::Get, segments("/api/data/feature")),
+            (Method::Get, segments("/admin/<file..>")),
+            (Method::Delete, segments("/api/style/<id>")),
+            (Method::Get, segments("/api/style/<id>"))
+        ])
+    }
+
+    #[test]
+    fn allowed() {
+        assert_eq!(routes().allowed("/api/data/feature/1"), vec![Method::Get]);
+        assert_eq!(routes().allowed("/api/data/feature"), vec![Method::Post, Method::Get]);
+        assert_eq!(routes().allowed("/api/data/feature/"), vec![Method::Post, Method::Get]);
+        assert_eq!(routes().allowed("/api/style/2"), vec![Method::Delete, Method::Get]);
+        assert_eq!(routes().allowed("/admin/index.html"), vec![Method::Get]);
+        assert_eq!(routes().allowed("/admin/js/app.js"), vec![Method::Get]);
+    }
+
+    #[test]
+    fn canonical_paths() {
+        let routes = Routes(vec![
+            (Method::Get, segments("/api/data/feature/<id>")),
+            (Method::Get, segments("/api/data/feature/<id>/history")),
+            (Method::Get, segments("/api/data/features")),
+            (Method::Get, segments("/api/data/features/count")),
+            (Method::Get, segments("/api/data/bounds/<bounds>")),
+            (Method::Get, segments("/admin/<file..>"))
+        ]);
+
+        assert_eq!(routes.canonical("/API/DATA/features"), Some(String::from("/api/data/features")));
+        assert_eq!(routes.canonical("/Api/Data/Features/"), Some(String::from("/api/data/features/")));
+        assert_eq!(routes.canonical("/API/data/Features/COUNT"), Some(String::from("/api/data/features/count")));
+        assert_eq!(routes.canonical("/API/Data/Feature/1/History"), Some(String::from("/api/data/feature/1/history")));
+        assert_eq!(routes.canonical("/API/data/bounds/DC"), Some(String::from("/api/data/bounds/DC")));
+        assert_eq!(routes.canonical("/ADMIN/js/App.js"), Some(String::from("/admin/js/App.js")));
+
+        //Paths that already match a route or that match none are left alone
+        assert_eq!(routes.canonical("/api/data/features"), None);
+        assert_eq!(routes.canonical("/api/data/bounds/DC"), None);
+        assert_eq!(routes.canonical("/API/DATA/nothing"), None);
+    }
+
+    #[test]
+    fn not_allowed() {
+        assert_eq!(routes().allowed("/api/data/feature/1/history"), Vec::<Method>::new());
+        assert_eq!(routes().allowed("/api/data"), Vec::<Method>::new());
+        assert_eq!(routes().allowed("/api/nothing"), Vec::<Method>::new());
+    }
+}
@@ -9,7 +9,34 @@ CREATE TABLE webhooks (
     id          BIGSERIAL,
     name        TEXT,
     actions     TEXT[],
-    url         TEXT
+    url         TEXT,
+    secret      TEXT,
+    mode        TEXT,
+    priority    BIGINT,
+    blocking    BOOLEAN
+);
+
+DROP TABLE IF EXISTS webhooks_deliveries;
+CREATE TABLE webhooks_deliveries (
+    id          BIGSERIAL,
+    webhook     BIGINT,
+    event       TEXT,
+    status      INTEGER,
+    latency     BIGINT,
+    error       TEXT,
+    created     TIMESTAMP
+);
+CREATE INDEX webhooks_deliveries_idx ON webhooks_deliveries(webhook);
+
+DROP TABLE IF EXISTS webhooks_backfills;
+CREATE TABLE webhooks_backfills (
+    webhook     BIGINT PRIMARY KEY,
+    since       BIGINT,
+    last        BIGINT,
+    status      TEXT,
+    error       TEXT,
+    created     TIMESTAMP,
+    updated     TIMESTAMP
 );
 
 DROP TABLE IF EXISTS meta;
@@ -22,7 +49,10 @@ DROP TABLE IF EXISTS tiles;
 CREATE TABLE tiles (
     created     TIMESTAMP,
     ref         TEXT UNIQUE,
-    tile        BYTEA
+    tile        BYTEA,
+    repaired    BIGINT DEFAULT 0,
+    skipped     BIGINT DEFAULT 0,
+    dropped     BIGINT DEFAULT 0
 );
 
 DROP TABLE IF EXISTS bounds;
@@ -30,42 +60,109 @@ CREATE TABLE bounds (
     id          BIGSERIAL,
     geom        GEOMETRY(MULTIPOLYGON, 4326),
     name        TEXT UNIQUE,
-    props       JSONB
+    props       JSONB,
+    features    BIGINT DEFAULT 0,
+    counted     TIMESTAMP
 );
 CREATE INDEX bounds_gist ON bounds USING GIST(geom);
 CREATE INDEX bounds_idx ON bounds(name);
 
+DROP TABLE IF EXISTS indexes;
+CREATE TABLE indexes (
+    id          BIGSERIAL,
+    property    TEXT,
+    kind        TEXT,
+    created     TIMESTAMP,
+    UNIQUE(property, kind)
+);
+
 DROP TABLE IF EXISTS users;
 CREATE TABLE users (
     id          BIGSERIAL,
     access      TEXT,
     username    TEXT UNIQUE,
+    username_folded TEXT,
     password    TEXT,
+    password_reset BOOLEAN NOT NULL DEFAULT FALSE,
     email       TEXT UNIQUE,
-    meta        JSONB
+    meta        JSONB,
+    tenant      BIGINT
 );
 
+CREATE INDEX users_username_folded_idx ON users(username_folded);
+
 DROP TABLE IF EXISTS users_tokens;
 CREATE TABLE users_tokens (
     name        TEXT,
     uid         BIGINT,
     token       TEXT,
-    expiry      TIMESTAMP
+    expiry      TIMESTAMP,
+    impersonator BIGINT,
+    created     TIMESTAMP DEFAULT now(),
+    last_used   TIMESTAMP,
+    kind        TEXT NOT NULL DEFAULT 'session',
+    source      TEXT
 );
 
+DROP TABLE IF EXISTS idempotency;
+CREATE TABLE idempotency (
+    key         TEXT NOT NULL,
+    uid         BIGINT NOT NULL,
+    delta       BIGINT,
+    ids         BIGINT[],
+    created     TIMESTAMP,
+    PRIMARY KEY (key, uid)
+);
+
+DROP TABLE IF EXISTS audit;
+CREATE TABLE audit (
+    id          BIGSERIAL,
+    uid         BIGINT,
+    impersonator BIGINT,
+    action      TEXT,
+    detail      JSONB,
+    created     TIMESTAMP
+);
+CREATE INDEX audit_idx ON audit(action, created);
+
 DROP TABLE IF EXISTS geo;
 DROP INDEX IF EXISTS geo_gist;
 DROP INDEX IF EXISTS geo_idx;
+DROP INDEX IF EXISTS geo_tenant_idx;
 CREATE TABLE geo (
     id          BIGSERIAL UNIQUE,
     key         TEXT UNIQUE,
     version     BIGINT,
     geom        GEOMETRY(GEOMETRY, 4326),
     props       JSONB,
-    deltas      BIGINT[]
+    deltas      BIGINT[],
+    tenant      BIGINT
 );
 CREATE INDEX geo_gist ON geo USING GIST(geom);
 CREATE INDEX geo_idx ON geo(id);
+CREATE INDEX geo_tenant_idx ON geo(tenant);
+
+DROP TABLE IF EXISTS feature_tags;
+CREATE TABLE feature_tags (
+    id          BIGINT NOT NULL,
+    tag         TEXT NOT NULL,
+    uid         BIGINT,
+    created     TIMESTAMP,
+    PRIMARY KEY (id, tag)
+);
+CREATE INDEX feature_tags_tag_idx ON feature_tags(tag);
+
+DROP TABLE IF EXISTS feature_comments;
+CREATE TABLE feature_comments (
+    id          BIGSERIAL PRIMARY KEY,
+    feature     BIGINT NOT NULL,
+    uid         BIGINT NOT NULL,
+    comment     TEXT NOT NULL,
+    created     TIMESTAMP NOT NULL,
+    resolved    TIMESTAMP,
+    resolved_by BIGINT
+);
+CREATE INDEX feature_comments_feature_idx ON feature_comments(feature, id);
 
 DROP TABLE IF EXISTS styles;
 CREATE TABLE styles (
@@ -73,7 +170,8 @@ CREATE TABLE styles (
     name        TEXT,
     style       JSONB,
     uid         BIGINT,
-    public      BOOLEAN
+    public      BOOLEAN,
+    tenant      BIGINT
 );
 
 DROP TABLE IF EXISTS deltas;
@@ -84,7 +182,9 @@ CREATE TABLE deltas (
     affected    BIGINT[],
     props       JSONB,
     uid         BIGINT,
-    finalized   BOOLEAN DEFAULT FALSE
+    finalized   BOOLEAN DEFAULT FALSE,
+    tenant      BIGINT,
+    batch_until TIMESTAMP
 );
 CREATE INDEX deltas_idx ON deltas(id);
 CREATE INDEX deltas_affected_idx on deltas USING GIN (affected);
@@ -0,0 +1,384 @@
+use std::io::BufRead;
+use crate::stream::PGStream;
+use crate::err::HecateError;
+
+///
+/// Version of the snapshot format, written in the header record of a snapshot
+///
+pub static VERSION: i64 = 1;
+
+///
+/// Stream the full dataset as line delimited JSON records, a `snapshot` header
+/// followed by every `meta`, `style`, `delta`, `feature`, `tag` and `comment` record
+///
+/// Records are written in the order they must be restored in, with every id
+/// preserved so that the history of each feature is kept intact
+///
+pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>) -> Result<PGStream, HecateError> {
+    PGStream::new(conn, String::from("next_snapshot"), format!(r#"
+        DECLARE next_snapshot CURSOR FOR
+            SELECT
+                record
+            FROM (
+                SELECT
+                    0 AS part,
+                    0 AS id,
+                    json_build_object(
+                        'type', 'snapshot',
+                        'version', {version}::BIGINT,
+                        'created', NOW()::TIMESTAMP
+                    )::TEXT AS record
+
+                UNION ALL
+
+                SELECT
+                    1 AS part,
+                    0 AS id,
+                    json_build_object(
+                        'type', 'meta',
+                        'key', key,
+                        'value', value
+                    )::TEXT AS record
+                FROM
+                    meta
+
+                UNION ALL
+
+                SELECT
+                    2 AS part,
+                    id AS id,
+                    json_build_object(
+                        'type', 'style',
+                        'id', id,
+                        'name', name,
+                        'style', style,
+                        'uid', uid,
+                        'public', public,
+                        'tenant', tenant
+                    )::TEXT AS record
+                FROM
+                    styles
+
+                UNION ALL
+
+                SELECT
+                    3 AS part,
+                    id AS id,
+                    json_build_object(
+                        'type', 'delta',
+                        'id', id,
+                        'created', created,
+                        'features', features,
+                        'affected', affected,
+                        'props', props,
+                        'uid', uid,
+                        'finalized', finalized,
+                        'tenant', tenant
+                    )::TEXT AS record
+                FROM
+                    deltas
+
+                UNION ALL
+
+                SELECT
+                    4 AS part,
+                    id AS id,
+                    json_build_object(
+                        'type', 'feature',
+                        'id', id,
+                        'key', key,
+                        'version', version,
+                        'geometry', ST_AsGeoJSON(geom)::JSON,
+                        'properties', props,
+                        'deltas', deltas,
+                        'tenant', tenant
+                    )::TEXT AS record
+                FROM
+                    geo
+
+                UNION ALL
+
+                SELECT
+                    5 AS part,
+                    id AS id,
+                    json_build_object(
+                        'type', 'tag',
+                        'id', id,
+                        'tag', tag,
+                        'uid', uid,
+                        'created', created
+                    )::TEXT AS record
+                FROM
+                    feature_tags
+
+                UNION ALL
+
+                SELECT
+                    6 AS part,
+                    id AS id,
+                    json_build_object(
+                        'type', 'comment',
+                        'id', id,
+                        'feature', feature,
+                        'uid', uid,
+                        'comment', comment,
+                        'created', created,
+                        'resolved', resolved,
+                        'resolved_by', resolved_by
+                    )::TEXT AS record
+                FROM
+                    feature_comments
+            ) t
+            ORDER BY part, id, record
+    "#, version = VERSION), &[])
+}
+
+///
+/// Whether the dataset has any features, deltas, styles, meta, tags or comments
+///
+pub fn is_empty(conn: &impl postgres::GenericConnection) -> Result<bool, HecateError> {
+    match conn.query("
+        SELECT
+            NOT (
+                EXISTS (SELECT 1 FROM geo)
+                OR EXISTS (SELECT 1 FROM deltas)
+                OR EXISTS (SELECT 1 FROM styles)
+                OR EXISTS (SELECT 1 FROM meta)
+                OR EXISTS (SELECT 1 FROM feature_tags)
+                OR EXISTS (SELECT 1 FROM feature_comments)
+            )
+    ", &[]) {
+        Ok(rows) => Ok(rows.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Read the type of a snapshot record, ensuring the header is the first record
+///
+pub fn record_type(record: &serde_json::Value, line: usize) -> Result<String, HecateError> {
+    let kind = match record.get("type").and_then(|kind| kind.as_str()) {
+        Some(kind) => String::from(kind),
+        None => { return Err(HecateError::new(400, format!("Invalid Snapshot - record on line {} must have a type", line), None)); }
+    };
+
+    if line == 1 {
+        if kind != "snapshot" {
+            return Err(HecateError::new(400, String::from("Invalid Snapshot - must start with a snapshot header"), None));
+        } else if record.get("version").and_then(|version| version.as_i64()) != Some(VERSION) {
+            return Err(HecateError::new(400, format!("Invalid Snapshot - only version {} snapshots are supported", VERSION), None));
+        }
+    } else if kind == "snapshot" {
+        return Err(HecateError::new(400, format!("Invalid Snapshot - unexpected header on line {}", line), None));
+    }
+
+    Ok(kind)
+}
+
+///
+/// Restore a snapshot created by `get` into the dataset, returning the number
+/// of each kind of record restored
+///
+/// The dataset must be empty unless `force` is set, in which case the existing
+/// features, deltas, styles, meta, tags and comments are removed before the snapshot is restored
+///
+pub fn restore(conn: &impl postgres::GenericConnection, snapshot: impl BufRead, force: bool) -> Result<serde_json::Value, HecateError> {
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    if !is_empty(&trans)? {
+        if !force {
+            return Err(HecateError::conflict("Dataset is not empty - use force=true to overwrite it"));
+        }
+
+        match trans.batch_execute("
+            DELETE FROM feature_tags;
+            DELETE FROM feature_comments;
+            DELETE FROM geo;
+            DELETE FROM deltas;
+            DELETE FROM styles;
+            DELETE FROM meta;
+        ") {
+            Ok(_) => (),
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+    }
+
+    let inserts = vec![
+        ("meta", "
+            INSERT INTO meta (key, value)
+                SELECT
+                    r->>'key',
+                    r->'value'
+                FROM (SELECT $1::TEXT::JSONB AS r) t
+        "),
+        ("style", "
+            INSERT INTO styles (id, name, style, uid, public, tenant)
+                SELECT
+                    (r->>'id')::BIGINT,
+                    r->>'name',
+                    r->'style',
+                    (r->>'uid')::BIGINT,
+                    (r->>'public')::BOOLEAN,
+                    (r->>'tenant')::BIGINT
+                FROM (SELECT $1::TEXT::JSONB AS r) t
+        "),
+        ("delta", "
+            INSERT INTO deltas (id, created, features, affected, props, uid, finalized, tenant)
+                SELECT
+                    (r->>'id')::BIGINT,
+                    (r->>'created')::TIMESTAMP,
+                    NULLIF(r->'features', 'null'::JSONB),
+                    CASE
+                        WHEN jsonb_typeof(r->'affected') = 'array' THEN ARRAY(SELECT jsonb_array_elements_text(r->'affected')::BIGINT)
+                        ELSE NULL
+                    END,
+                    NULLIF(r->'props', 'null'::JSONB),
+                    (r->>'uid')::BIGINT,
+                    COALESCE((r->>'finalized')::BOOLEAN, false),
+                    (r->>'tenant')::BIGINT
+                FROM (SELECT $1::TEXT::JSONB AS r) t
+        "),
+        ("feature", "
+            INSERT INTO geo (id, key, version, geom, props, deltas, tenant)
+                SELECT
+                    (r->>'id')::BIGINT,
+                    r->>'key',
+                    (r->>'version')::BIGINT,
+                    ST_SetSRID(ST_GeomFromGeoJSON(r->>'geometry'), 4326),
+                    r->'properties',
+                    CASE
+                        WHEN jsonb_typeof(r->'deltas') = 'array' THEN ARRAY(SELECT jsonb_array_elements_text(r->'deltas')::BIGINT)
+                        ELSE NULL
+                    END,
+                    (r->>'tenant')::BIGINT
+                FROM (SELECT $1::TEXT::JSONB AS r) t
+        "),
+        ("tag", "
+            INSERT INTO feature_tags (id, tag, uid, created)
+                SELECT
+                    (r->>'id')::BIGINT,
+                    r->>'tag',
+                    (r->>'uid')::BIGINT,
+                    (r->>'created')::TIMESTAMP
+                FROM (SELECT $1::TEXT::JSONB AS r) t
+        "),
+        ("comment", "
+            INSERT INTO feature_comments (id, feature, uid, comment, created, resolved, resolved_by)
+                SELECT
+                    (r->>'id')::BIGINT,
+                    (r->>'feature')::BIGINT,
+                    (r->>'uid')::BIGINT,
+                    r->>'comment',
+                    (r->>'created')::TIMESTAMP,
+                    (r->>'resolved')::TIMESTAMP,
+                    (r->>'resolved_by')::BIGINT
+                FROM (SELECT $1::TEXT::JSONB AS r) t
+        ")
+    ];
+
+    let mut statements = Vec::with_capacity(inserts.len());
+    for (kind, insert) in inserts {
+        match trans.prepare(insert) {
+            Ok(statement) => statements.push((kind, statement)),
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+    }
+
+    let mut counts = json!({
+        "meta": 0,
+        "styles": 0,
+        "deltas": 0,
+        "features": 0,
+        "tags": 0,
+        "comments": 0
+    });
+
+    let mut line_number = 0;
+    for line in snapshot.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => { return Err(HecateError::new(400, String::from("Invalid Snapshot - failed to read snapshot"), Some(err.to_string()))); }
+        };
+
+        //Skip the trailing EOT character of a streamed snapshot
+        let line = line.trim_matches('\u{4}');
+        if line.trim().len() == 0 {
+            continue;
+        }
+
+        line_number = line_number + 1;
+
+        let record: serde_json::Value = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(err) => { return Err(HecateError::new(400, format!("Invalid Snapshot - line {} is not valid JSON", line_number), Some(err.to_string()))); }
+        };
+
+        let kind = record_type(&record, line_number)?;
+
+        if kind == "snapshot" {
+            continue;
+        }
+
+        let statement = match statements.iter().find(|statement| statement.0 == kind) {
+            Some(statement) => &statement.1,
+            None => { return Err(HecateError::new(400, format!("Invalid Snapshot - unknown record type '{}' on line {}", kind, line_number), None)); }
+        };
+
+        match statement.execute(&[&line]) {
+            Ok(_) => (),
+            Err(err) => { return Err(HecateError::new(400, format!("Invalid Snapshot - {} on line {} could not be restored", kind, line_number), Some(err.to_string()))); }
+        };
+
+        let count = match kind.as_str() {
+            "meta" => "meta",
+            "style" => "styles",
+            "delta" => "deltas",
+            "tag" => "tags",
+            "comment" => "comments",
+            _ => "features"
+        };
+        counts[count] = json!(counts[count].as_i64().unwrap() + 1);
+    }
+
+    drop(statements);
+
+    if line_number == 0 {
+        return Err(HecateError::new(400, String::from("Invalid Snapshot - must start with a snapshot header"), None));
+    }
+
+    //Continue each id sequence after the restored ids & drop any tiles cached from before the restore
+    match trans.batch_execute("
+        SELECT setval(pg_get_serial_sequence('geo', 'id'), COALESCE((SELECT MAX(id) FROM geo), 0) + 1, false);
+        SELECT setval(pg_get_serial_sequence('deltas', 'id'), COALESCE((SELECT MAX(id) FROM deltas), 0) + 1, false);
+        SELECT setval(pg_get_serial_sequence('styles', 'id'), COALESCE((SELECT MAX(id) FROM styles), 0) + 1, false);
+        SELECT setval(pg_get_serial_sequence('feature_comments', 'id'), COALESCE((SELECT MAX(id) FROM feature_comments), 0) + 1, false);
+        DELETE FROM tiles;
+    ") {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.commit() {
+        Ok(_) => Ok(counts),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_types() {
+        assert_eq!(record_type(&json!({ "type": "snapshot", "version": VERSION }), 1).unwrap(), "snapshot");
+        assert_eq!(record_type(&json!({ "type": "feature", "id": 1 }), 2).unwrap(), "feature");
+
+        assert!(record_type(&json!({ "type": "feature", "id": 1 }), 1).is_err());
+        assert!(record_type(&json!({ "type": "snapshot", "version": VERSION + 1 }), 1).is_err());
+        assert!(record_type(&json!({ "type": "snapshot", "version": VERSION }), 2).is_err());
+        assert!(record_type(&json!({ "id": 1 }), 2).is_err());
+    }
+}
@@ -1,7 +1,92 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use crate::err::HecateError;
+use crate::index;
+use crate::bbox::Bbox;
+use crate::tenant::Scope;
 
-pub fn get_json(conn: &impl postgres::GenericConnection) -> Result<serde_json::Value, HecateError> {
-    match conn.query("
+///
+/// Number of seconds a calculated summary is served before being recalculated
+///
+pub static SUMMARY_TTL: u64 = 30;
+
+///
+/// Maximum number of distinct values of a property returned by a single request
+///
+pub static MAX_VALUES: i64 = 1000;
+
+///
+/// The most recently calculated summary, managed as rocket state so that
+/// frequent polling does not repeatedly scan the geo & deltas tables
+///
+pub struct SummaryCache {
+    ttl: Duration,
+    cached: Mutex<Option<(Instant, serde_json::Value)>>
+}
+
+impl SummaryCache {
+    pub fn new() -> Self {
+        SummaryCache {
+            ttl: Duration::from_secs(SUMMARY_TTL),
+            cached: Mutex::new(None)
+        }
+    }
+
+    ///
+    /// Return the cached summary if it is still fresh, otherwise recalculate it
+    ///
+    pub fn get(&self, conn: &impl postgres::GenericConnection) -> Result<serde_json::Value, HecateError> {
+        let mut cached = match self.cached.lock() {
+            Ok(cached) => cached,
+            Err(_) => { return Err(HecateError::new(500, String::from("Failed to lock stats summary"), None)); }
+        };
+
+        match *cached {
+            Some((calculated, ref summary)) if calculated.elapsed() < self.ttl => {
+                return Ok(summary.clone());
+            },
+            _ => ()
+        };
+
+        let summary_json = summary(conn)?;
+
+        *cached = Some((Instant::now(), summary_json.clone()));
+
+        Ok(summary_json)
+    }
+}
+
+///
+/// The extent & number of features stored in the server
+///
+/// The values are estimated from the `ANALYZE` stats of the geo table, unless
+/// the server holds the features of a tenant the request cannot see. The stats
+/// then only cover the features of the request's tenant & are calculated exactly
+///
+pub fn get_json(conn: &impl postgres::GenericConnection, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    let estimated = match tenant {
+        Scope::All(_) => true,
+        Scope::Tenant(Some(_)) => false,
+        Scope::Tenant(None) => match conn.query("
+            SELECT NOT EXISTS (SELECT 1 FROM geo WHERE tenant IS NOT NULL)
+        ", &[]) {
+            Ok(res) => res.get(0).get(0),
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        }
+    };
+
+    let (extent, total) = match estimated {
+        true => (
+            String::from("SELECT ST_EstimatedExtent('geo', 'geom') AS extent"),
+            String::from("SELECT pg_class.reltuples::bigint as total FROM pg_class WHERE oid = 'public.geo'::regclass")
+        ),
+        false => (
+            format!("SELECT ST_Extent(geom) AS extent FROM geo WHERE {}", tenant.to_sql("tenant")),
+            format!("SELECT COUNT(*)::BIGINT AS total FROM geo WHERE {}", tenant.to_sql("tenant"))
+        )
+    };
+
+    match conn.query(&*format!("
         SELECT COALESCE(row_to_json(d), 'false'::JSON)
         FROM (
             SELECT
@@ -22,20 +107,10 @@ pub fn get_json(conn: &impl postgres::GenericConnection) -> Result<serde_json::V
                     WHERE
                         relname = 'geo'
                 ) as time,
-                (
-                    SELECT
-                        ST_EstimatedExtent('geo', 'geom') AS extent
-                ) as extent,
-                (
-                    SELECT
-                        pg_class.reltuples::bigint as total
-                    FROM
-                        pg_class
-                    WHERE
-                        oid = 'public.geo'::regclass
-                ) as total
+                ({extent}) as extent,
+                ({total}) as total
         ) d;
-    ", &[]) {
+    ", extent = extent, total = total), &[]) {
         Err(err) => Err(HecateError::from_db(err)),
         Ok(res) => {
             let d_json: serde_json::Value = res.get(0).get(0);
@@ -52,3 +127,154 @@ pub fn regen(conn: &impl postgres::GenericConnection) -> Result<bool, HecateErro
         _ => Ok(true)
     }
 }
+
+///
+/// Totals of the features, deltas and authors stored in the server along with
+/// the size on disk of the database and its largest tables
+///
+/// Deleted features are those referenced by a delta that no longer exist in the
+/// geo table, features whose history has been purged are no longer counted
+///
+pub fn summary(conn: &impl postgres::GenericConnection) -> Result<serde_json::Value, HecateError> {
+    match conn.query("
+        SELECT
+            JSON_Build_Object(
+                'features', (SELECT COUNT(*) FROM geo),
+                'deltas', (SELECT COUNT(*) FROM deltas),
+                'deleted', (
+                    SELECT
+                        COUNT(DISTINCT affected.id)
+                    FROM
+                        deltas,
+                        UNNEST(deltas.affected) AS affected(id)
+                    WHERE
+                        NOT EXISTS (
+                            SELECT 1 FROM geo WHERE geo.id = affected.id
+                        )
+                ),
+                'authors', (SELECT COUNT(DISTINCT uid) FROM deltas),
+                'size', JSON_Build_Object(
+                    'database', pg_database_size(current_database()),
+                    'geo', pg_total_relation_size('geo'),
+                    'deltas', pg_total_relation_size('deltas')
+                ),
+                'calculated', to_char(NOW(), 'YYYY-MM-DD HH24:MI:SS')
+            )
+    ", &[]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => {
+            let summary: serde_json::Value = res.get(0).get(0);
+            Ok(summary)
+        }
+    }
+}
+
+///
+/// Summary statistics of a top level numeric property across the features
+///
+/// Only values stored as JSON numbers are aggregated, features where the
+/// property holds any other type are counted as `ignored` so that a few bad
+/// values cannot fail or skew the whole summary
+///
+pub fn numeric(conn: &impl postgres::GenericConnection, property: &String, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    match conn.query(&*format!("
+        SELECT
+            JSON_Build_Object(
+                'property', $1::TEXT,
+                'count', count,
+                'ignored', ignored,
+                'min', min,
+                'max', max,
+                'avg', avg,
+                'percentiles', JSON_Build_Object(
+                    'p25', percentiles[1],
+                    'p50', percentiles[2],
+                    'p75', percentiles[3],
+                    'p90', percentiles[4],
+                    'p99', percentiles[5]
+                )
+            )
+        FROM (
+            SELECT
+                COUNT(value) AS count,
+                COUNT(*) - COUNT(value) AS ignored,
+                MIN(value) AS min,
+                MAX(value) AS max,
+                AVG(value) AS avg,
+                percentile_cont(ARRAY[0.25, 0.5, 0.75, 0.9, 0.99]) WITHIN GROUP (ORDER BY value) AS percentiles
+            FROM (
+                SELECT
+                    CASE
+                        WHEN jsonb_typeof(props->$1) = 'number' THEN (props->>$1)::DOUBLE PRECISION
+                        ELSE NULL
+                    END AS value
+                FROM
+                    geo
+                WHERE
+                    props ? $1
+                    AND {scope}
+            ) v
+        ) s
+    ", scope = tenant.to_sql("tenant")), &[&property]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => {
+            let stats: serde_json::Value = res.get(0).get(0);
+            Ok(stats)
+        }
+    }
+}
+
+///
+/// The distinct values of a property across the features, with the number of
+/// features having each value, most frequent first
+///
+/// `distinct` is the number of distinct values before `limit` is applied, so
+/// that clients can tell whether the values they were sent are complete
+///
+pub fn values(conn: &impl postgres::GenericConnection, property: &str, bbox: &Option<Bbox>, limit: &i64, tenant: &Scope) -> Result<serde_json::Value, HecateError> {
+    let expression = index::expression(&index::path(property)?);
+
+    let mut params: Vec<f64> = Vec::new();
+    let clause = match bbox {
+        None => String::from("true"),
+        Some(bbox) => bbox.to_sql("geom", &mut params)
+    };
+
+    let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|param| param as &dyn postgres::types::ToSql).collect();
+
+    match conn.query(&*format!("
+        SELECT
+            COALESCE(MAX(v.total), 0),
+            COALESCE(JSON_Agg(JSON_Build_Object(
+                'value', v.value,
+                'count', v.count
+            ) ORDER BY v.count DESC, v.value), '[]'::JSON)
+        FROM (
+            SELECT
+                {expression} AS value,
+                COUNT(*) AS count,
+                COUNT(*) OVER () AS total
+            FROM
+                geo
+            WHERE
+                {expression} IS NOT NULL
+                AND {clause}
+                AND {scope}
+            GROUP BY {expression}
+            ORDER BY count DESC, value
+            LIMIT {limit}
+        ) v
+    ", expression = expression, clause = clause, scope = tenant.to_sql("tenant"), limit = limit), &params) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => {
+            let distinct: i64 = res.get(0).get(0);
+            let values: serde_json::Value = res.get(0).get(1);
+
+            Ok(json!({
+                "property": property,
+                "distinct": distinct,
+                "values": values
+            }))
+        }
+    }
+}
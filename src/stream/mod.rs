@@ -1,5 +1,5 @@
 use postgres::types::ToSql;
-use std::io::{Error, ErrorKind};
+use std::io::{Error, ErrorKind, Read};
 use crate::err::HecateError;
 
 use std::mem;
@@ -101,3 +101,30 @@ impl PGStream {
     }
 }
 
+
+///
+/// Read a line delimited feature stream into a list of features
+///
+pub fn collect(mut stream: impl Read) -> Result<Vec<serde_json::Value>, HecateError> {
+    let mut body = String::new();
+
+    match stream.read_to_string(&mut body) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to read features"), Some(err.to_string()))); }
+    };
+
+    let mut features: Vec<serde_json::Value> = Vec::new();
+
+    for line in body.trim_end_matches('\u{4}').lines() {
+        if line.trim().len() == 0 {
+            continue;
+        }
+
+        match serde_json::from_str(line) {
+            Ok(feature) => features.push(feature),
+            Err(err) => { return Err(HecateError::new(500, String::from("Failed to read features"), Some(err.to_string()))); }
+        };
+    }
+
+    Ok(features)
+}
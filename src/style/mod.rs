@@ -1,17 +1,27 @@
 use serde_json::Value;
 use crate::err::HecateError;
+use crate::meta;
+use crate::tenant::Scope;
+
+///
+/// Meta key of the default style of the server, stored as `{ "style": <id> }` so
+/// that, like any other meta reference, the default style cannot be deleted by accident
+///
+pub static DEFAULT: &str = "style.default";
 
 /// Creates a new GL JS Style under a given user account
 ///
-/// By default styles are private and can only be accessed by a single user
+/// By default styles are private and can only be accessed by a single user. The
+/// style belongs to the tenant of the user
 pub fn create(conn: &impl postgres::GenericConnection, uid: &i64, style: &String) -> Result<i64, HecateError> {
     match conn.query("
-        INSERT INTO styles (name, style, uid, public)
+        INSERT INTO styles (name, style, uid, public, tenant)
             VALUES (
                 COALESCE($1::TEXT::JSON->>'name', 'New Style')::TEXT,
                 COALESCE($1::TEXT::JSON->'style', '{}'::JSON),
                 $2,
-                false
+                false,
+                (SELECT tenant FROM users WHERE id = $2)
             )
             RETURNING id;
     ", &[&style, &uid]) {
@@ -24,9 +34,11 @@ pub fn create(conn: &impl postgres::GenericConnection, uid: &i64, style: &String
 }
 
 /// Get the style by id, if the style is public, the user need not be logged in,
-/// if the style is private ensure the owner is the requester
-pub fn get(conn: &impl postgres::GenericConnection, uid: &Option<i64>, style_id: &i64) -> Result<Value, HecateError> {
-    match conn.query("
+/// if the style is private ensure the owner or an admin is the requester
+///
+/// A private style is reported as not found to anyone else so that its existence is not leaked
+pub fn get(conn: &impl postgres::GenericConnection, uid: &Option<i64>, is_admin: bool, style_id: &i64, tenant: &Scope) -> Result<Value, HecateError> {
+    match conn.query(&*format!("
         SELECT
             row_to_json(t) as style
         FROM (
@@ -45,10 +57,12 @@ pub fn get(conn: &impl postgres::GenericConnection, uid: &Option<i64>, style_id:
                 AND (
                     styles.public IS true
                     OR styles.uid = $2
+                    OR $3
                 )
                 AND users.id = styles.uid
+                AND {scope}
         ) t
-    ", &[&style_id, &uid]) {
+    ", scope = tenant.to_sql("styles.tenant")), &[&style_id, &uid, &is_admin]) {
         Ok(rows) => {
             if rows.len() != 1 {
                 Err(HecateError::new(404, String::from("Style Not Found"), None))
@@ -62,6 +76,43 @@ pub fn get(conn: &impl postgres::GenericConnection, uid: &Option<i64>, style_id:
     }
 }
 
+/// Get the default style of the server, if one has been set
+pub fn get_default(conn: &impl postgres::GenericConnection, uid: &Option<i64>, is_admin: bool, tenant: &Scope) -> Result<Value, HecateError> {
+    match meta::get(conn, &String::from(DEFAULT))?.get("style").and_then(|style| style.as_i64()) {
+        Some(style_id) => get(conn, uid, is_admin, &style_id, tenant),
+        None => Err(HecateError::new(404, String::from("Default Style Not Set"), None))
+    }
+}
+
+/// Make a style the default style of the server
+///
+/// Only a public style can be the default so that it can be read by anyone able to read styles
+pub fn set_default(conn: &impl postgres::GenericConnection, style_id: &i64, tenant: &Scope) -> Result<bool, HecateError> {
+    let public: bool = match conn.query(&*format!("
+        SELECT
+            public
+        FROM
+            styles
+        WHERE
+            id = $1
+            AND {scope}
+    ", scope = tenant.to_sql("tenant")), &[&style_id]) {
+        Ok(rows) => match rows.iter().next() {
+            Some(row) => row.get(0),
+            None => { return Err(HecateError::new(404, String::from("Style Not Found"), None)); }
+        },
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    if !public {
+        return Err(HecateError::new(400, String::from("Default Style Must Be Public"), None));
+    }
+
+    meta::set(conn, &String::from(DEFAULT), &json!({
+        "style": style_id
+    }))
+}
+
 pub fn update(conn: &impl postgres::GenericConnection, uid: &i64, style_id: &i64, style: &String) -> Result<bool, HecateError> {
     match conn.execute("
         UPDATE styles
@@ -103,28 +154,105 @@ pub fn access(conn: &impl postgres::GenericConnection, uid: &i64, style_id: &i64
     }
 }
 
+///Return the server meta keys and usernames whose `style` value refers to the given style
+pub fn references(conn: &impl postgres::GenericConnection, style_id: &i64) -> Result<Value, HecateError> {
+    match conn.query("
+        SELECT
+            JSON_Build_Object(
+                'meta', COALESCE((
+                    SELECT JSON_Agg(meta.key ORDER BY meta.key)
+                    FROM meta
+                    WHERE meta.value->>'style' = $1::TEXT
+                ), '[]'::JSON),
+                'users', COALESCE((
+                    SELECT JSON_Agg(users.username ORDER BY users.username)
+                    FROM users
+                    WHERE users.meta->>'style' = $1::TEXT
+                ), '[]'::JSON)
+            )
+    ", &[&style_id]) {
+        Ok(rows) => Ok(rows.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
 ///Allow the owner of a given style to delete it
-pub fn delete(conn: &impl postgres::GenericConnection, uid: &i64, style_id: &i64) -> Result<bool, HecateError> {
-    match conn.execute("
-        DELETE
+///
+///A style that is referenced by the server meta or a user's meta will not be deleted
+///unless `force` is set, in which case the references are removed along with the style
+pub fn delete(conn: &impl postgres::GenericConnection, uid: &i64, style_id: &i64, force: bool) -> Result<bool, HecateError> {
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.query("
+        SELECT 1
             FROM styles
             WHERE
                 uid = $1
                 AND id = $2
+            FOR UPDATE
     ", &[&uid, &style_id]) {
-        Ok(deleted) => {
-            if deleted == 0 {
-                Err(HecateError::new(404, String::from("Style Not Found"), None))
-            } else {
-                Ok(true)
-            }
+        Ok(rows) => if rows.len() == 0 {
+            return Err(HecateError::new(404, String::from("Style Not Found"), None));
         },
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    let references = references(&trans, &style_id)?;
+
+    let referenced = references["meta"].as_array().map_or(false, |meta| meta.len() > 0)
+        || references["users"].as_array().map_or(false, |users| users.len() > 0);
+
+    if referenced && !force {
+        return Err(HecateError::from_json(409, json!({
+            "code": 409,
+            "status": "Conflict",
+            "reason": "Style Is Referenced - pass force=true to remove the references and delete",
+            "references": references
+        }), String::from("Style Is Referenced"), None));
+    }
+
+    if referenced {
+        match trans.execute("
+            UPDATE meta
+                SET value = value - 'style'
+                WHERE value->>'style' = $1::TEXT
+        ", &[&style_id]) {
+            Ok(_) => (),
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+
+        match trans.execute("
+            UPDATE users
+                SET meta = meta - 'style'
+                WHERE meta->>'style' = $1::TEXT
+        ", &[&style_id]) {
+            Ok(_) => (),
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+    }
+
+    match trans.execute("
+        DELETE
+            FROM styles
+            WHERE
+                uid = $1
+                AND id = $2
+    ", &[&uid, &style_id]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.commit() {
+        Ok(_) => Ok(true),
         Err(err) => Err(HecateError::from_db(err))
     }
 }
 
 ///Return a list of all styles (public and private) for a given user
-pub fn list_user(conn: &impl postgres::GenericConnection, uid: &i64) -> Result<Value, HecateError> {
+pub fn list_user(conn: &impl postgres::GenericConnection, uid: &i64, limit: &Option<i64>) -> Result<Value, HecateError> {
     match conn.query("
         SELECT
             COALESCE(JSON_Agg(row_to_json(t)), '[]'::JSON)
@@ -142,8 +270,9 @@ pub fn list_user(conn: &impl postgres::GenericConnection, uid: &i64) -> Result<V
                 uid = $1
                 AND users.id = uid
             ORDER BY styles.id
+            LIMIT $2::BIGINT
         ) t;
-    ", &[&uid]) {
+    ", &[&uid, &limit]) {
         Ok(rows) => {
             if rows.len() == 0 {
                 Err(HecateError::new(404, String::from("Style Not Found"), None))
@@ -157,8 +286,8 @@ pub fn list_user(conn: &impl postgres::GenericConnection, uid: &i64) -> Result<V
 }
 
 ///Return a list of public styles for a given user
-pub fn list_user_public(conn: &impl postgres::GenericConnection, uid: &i64) -> Result<Value, HecateError> {
-    match conn.query("
+pub fn list_user_public(conn: &impl postgres::GenericConnection, uid: &i64, limit: &Option<i64>, tenant: &Scope) -> Result<Value, HecateError> {
+    match conn.query(&*format!("
         SELECT
             COALESCE(JSON_Agg(row_to_json(t)), '[]'::JSON)
         FROM (
@@ -175,9 +304,11 @@ pub fn list_user_public(conn: &impl postgres::GenericConnection, uid: &i64) -> R
                 uid = $1
                 AND public IS TRUE
                 AND uid = users.id
+                AND {scope}
             ORDER BY styles.id
+            LIMIT $2::BIGINT
         ) t;
-    ", &[&uid]) {
+    ", scope = tenant.to_sql("styles.tenant")), &[&uid, &limit]) {
         Ok(rows) => {
             if rows.len() == 0 {
                 Err(HecateError::new(404, String::from("Style Not Found"), None))
@@ -190,8 +321,8 @@ pub fn list_user_public(conn: &impl postgres::GenericConnection, uid: &i64) -> R
     }
 }
 
-pub fn list_public(conn: &impl postgres::GenericConnection) -> Result<Value, HecateError> {
-    match conn.query("
+pub fn list_public(conn: &impl postgres::GenericConnection, limit: &Option<i64>, tenant: &Scope) -> Result<Value, HecateError> {
+    match conn.query(&*format!("
         SELECT
             COALESCE(JSON_Agg(row_to_json(t)), '[]'::JSON)
         FROM (
@@ -207,9 +338,11 @@ pub fn list_public(conn: &impl postgres::GenericConnection) -> Result<Value, Hec
             WHERE
                 public IS true
                 AND uid = users.id
+                AND {scope}
             ORDER BY styles.id
+            LIMIT $1::BIGINT
         ) t;
-    ", &[]) {
+    ", scope = tenant.to_sql("styles.tenant")), &[&limit]) {
         Ok(rows) => {
             if rows.len() == 0 {
                 Err(HecateError::new(404, String::from("Style Not Found"), None))
@@ -0,0 +1,97 @@
+use crate::err::HecateError;
+
+///
+/// The value of the `X-Hecate-Tenant` header that allows an admin to read &
+/// write the data of every tenant
+///
+pub static CROSS: &str = "*";
+
+///
+/// The rows a request can see & write
+///
+/// Users without a tenant share the untenanted data, so that a server that does
+/// not assign tenants behaves exactly as it did before tenants were introduced
+///
+#[derive(Debug, PartialEq, Clone)]
+pub enum Scope {
+    ///
+    /// An admin crossing tenants, new rows are stamped with the admin's own tenant
+    ///
+    All(Option<i64>),
+    Tenant(Option<i64>)
+}
+
+impl Scope {
+    ///
+    /// Build the scope of a request from the tenant of the user and the value of
+    /// the `X-Hecate-Tenant` header, if any
+    ///
+    pub fn new(tenant: Option<i64>, cross: &Option<String>, admin: bool) -> Result<Self, HecateError> {
+        match cross {
+            None => Ok(Scope::Tenant(tenant)),
+            Some(cross) if cross != CROSS => {
+                Err(HecateError::new(400, String::from("Invalid X-Hecate-Tenant - only '*' is supported"), None))
+            },
+            Some(_) if !admin => Err(HecateError::new(403, String::from("Only admins can cross tenants"), None)),
+            Some(_) => Ok(Scope::All(tenant))
+        }
+    }
+
+    ///
+    /// The tenant rows written by the request are stamped with
+    ///
+    pub fn stamp(&self) -> Option<i64> {
+        match self {
+            Scope::All(tenant) => *tenant,
+            Scope::Tenant(tenant) => *tenant
+        }
+    }
+
+    ///
+    /// SQL condition limiting the given tenant column to the rows of the scope
+    ///
+    /// The tenant is inlined as an integer literal so that the condition can be
+    /// added to queries without renumbering their parameters
+    ///
+    pub fn to_sql(&self, column: &str) -> String {
+        match self {
+            Scope::All(_) => String::from("TRUE"),
+            Scope::Tenant(None) => format!("{} IS NULL", column),
+            Scope::Tenant(Some(tenant)) => format!("{} = {}", column, tenant)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope() {
+        assert_eq!(Scope::new(Some(1), &None, false).unwrap(), Scope::Tenant(Some(1)));
+        assert_eq!(Scope::new(None, &None, true).unwrap(), Scope::Tenant(None));
+        assert_eq!(Scope::new(Some(1), &Some(String::from("*")), true).unwrap(), Scope::All(Some(1)));
+
+        assert_eq!(Scope::new(Some(1), &Some(String::from("*")), false).unwrap_err().as_json(), json!({
+            "code": 403,
+            "reason": "Only admins can cross tenants",
+            "status": "Forbidden"
+        }));
+
+        assert_eq!(Scope::new(Some(1), &Some(String::from("2")), true).unwrap_err().as_json(), json!({
+            "code": 400,
+            "reason": "Invalid X-Hecate-Tenant - only '*' is supported",
+            "status": "Bad Request"
+        }));
+    }
+
+    #[test]
+    fn to_sql() {
+        assert_eq!(Scope::All(Some(1)).to_sql("geo.tenant"), "TRUE");
+        assert_eq!(Scope::Tenant(None).to_sql("geo.tenant"), "geo.tenant IS NULL");
+        assert_eq!(Scope::Tenant(Some(3)).to_sql("tenant"), "tenant = 3");
+
+        assert_eq!(Scope::All(Some(2)).stamp(), Some(2));
+        assert_eq!(Scope::Tenant(None).stamp(), None);
+    }
+}
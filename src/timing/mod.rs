@@ -0,0 +1,306 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{self, FromRequest};
+use rocket::http::Header;
+use rocket::{Data, Request, Response, Outcome};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::options;
+
+///
+/// Query params whose values are never written to the log
+///
+static SECRETS: [&str; 3] = ["password", "token", "secret"];
+
+///
+/// Longest request description written to the log
+///
+static MAX_DESCRIPTION: usize = 512;
+
+#[derive(Debug)]
+struct Slow {
+    threshold: Duration,
+    request: String,
+    request_id: Option<String>
+}
+
+#[derive(Debug)]
+struct Metrics {
+    entries: Vec<(&'static str, Duration)>,
+    admin: bool,
+    slow: Option<Slow>
+}
+
+///
+/// Time spent in each stage of a request, shared through the request local
+/// cache so that it can be reported once the response is ready
+///
+#[derive(Debug, Clone)]
+pub struct Timings(Arc<Mutex<Metrics>>);
+
+impl Timings {
+    pub fn new() -> Self {
+        Timings(Arc::new(Mutex::new(Metrics {
+            entries: Vec::new(),
+            admin: false,
+            slow: None
+        })))
+    }
+
+    ///
+    /// Log every `db` measurement of the request that takes at least the threshold
+    ///
+    pub fn watch(&self, threshold: Duration, request: String, request_id: Option<String>) {
+        match self.0.lock() {
+            Ok(mut metrics) => {
+                metrics.slow = Some(Slow {
+                    threshold: threshold,
+                    request: request,
+                    request_id: request_id
+                });
+            },
+            Err(_) => ()
+        };
+    }
+
+    ///
+    /// The log line for a measurement if it is a slow query
+    ///
+    fn slow(&self, name: &'static str, elapsed: Duration) -> Option<String> {
+        if name != "db" {
+            return None;
+        }
+
+        match self.0.lock() {
+            Ok(metrics) => match metrics.slow {
+                Some(ref slow) if elapsed >= slow.threshold => Some(format!("WARN: Slow Query: {:.2}ms on {} (request {})",
+                    millis(elapsed),
+                    slow.request,
+                    slow.request_id.as_ref().map(|id| id.as_str()).unwrap_or("-")
+                )),
+                _ => None
+            },
+            Err(_) => None
+        }
+    }
+
+    ///
+    /// Add an elapsed duration to the named metric
+    ///
+    pub fn record(&self, name: &'static str, elapsed: Duration) {
+        match self.0.lock() {
+            Ok(mut metrics) => match metrics.entries.iter_mut().find(|entry| entry.0 == name) {
+                Some(entry) => { entry.1 += elapsed; },
+                None => { metrics.entries.push((name, elapsed)); }
+            },
+            Err(_) => ()
+        };
+    }
+
+    ///
+    /// Run the given function, adding the time it took to the named metric
+    ///
+    pub fn time<T>(&self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        if let Some(line) = self.slow(name, elapsed) {
+            println!("{}", line);
+        }
+
+        self.record(name, elapsed);
+
+        result
+    }
+
+    ///
+    /// Record whether the request was made by an admin
+    ///
+    pub fn set_admin(&self, admin: bool) {
+        match self.0.lock() {
+            Ok(mut metrics) => { metrics.admin = admin; },
+            Err(_) => ()
+        };
+    }
+
+    pub fn is_admin(&self) -> bool {
+        match self.0.lock() {
+            Ok(metrics) => metrics.admin,
+            Err(_) => false
+        }
+    }
+
+    ///
+    /// Format the recorded metrics as a Server-Timing header value
+    ///
+    pub fn to_header(&self, total: Duration) -> String {
+        let mut metrics: Vec<String> = match self.0.lock() {
+            Ok(metrics) => metrics.entries.iter().map(|entry| format!("{};dur={:.2}", entry.0, millis(entry.1))).collect(),
+            Err(_) => Vec::new()
+        };
+
+        metrics.push(format!("total;dur={:.2}", millis(total)));
+
+        metrics.join(", ")
+    }
+}
+
+impl PartialEq for Timings {
+    fn eq(&self, other: &Timings) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Timings {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Timings, ()> {
+        Outcome::Success(request.local_cache(|| Timings::new()).clone())
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
+
+///
+/// Describe a request for the log as its method, path & query, with the values
+/// of any secret params redacted
+///
+pub fn describe(method: &str, path: &str, query: Option<&str>) -> String {
+    let mut description = match query {
+        None => format!("{} {}", method, path),
+        Some(query) => {
+            let params: Vec<String> = query.split('&').map(|param| {
+                let name = param.splitn(2, '=').next().unwrap_or("");
+
+                if SECRETS.iter().any(|secret| name.to_lowercase().contains(secret)) {
+                    format!("{}=REDACTED", name)
+                } else {
+                    String::from(param)
+                }
+            }).collect();
+
+            format!("{} {}?{}", method, path, params.join("&"))
+        }
+    };
+
+    if description.len() > MAX_DESCRIPTION {
+        let mut end = MAX_DESCRIPTION;
+        while !description.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        description.truncate(end);
+        description.push_str("...");
+    }
+
+    description
+}
+
+///
+/// The time the request was received
+///
+struct Start(Instant);
+
+///
+/// Report request timings to clients in a Server-Timing header
+///
+/// The DB queries of a request that take at least `slow_query` milliseconds are
+/// logged along with the request and the `X-Request-Id` header of the request
+///
+pub struct ServerTiming {
+    enabled: bool,
+    admin_only: bool,
+    slow_query: Option<Duration>
+}
+
+impl ServerTiming {
+    pub fn new(timing: &options::Timing) -> Self {
+        ServerTiming {
+            enabled: timing.enabled.unwrap_or(false),
+            admin_only: timing.admin_only.unwrap_or(false),
+            slow_query: timing.slow_query.map(Duration::from_millis)
+        }
+    }
+}
+
+impl Fairing for ServerTiming {
+    fn info(&self) -> Info {
+        Info {
+            name: "Server Timing",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        if self.enabled {
+            request.local_cache(|| Start(Instant::now()));
+        }
+
+        if let Some(threshold) = self.slow_query {
+            let description = describe(request.method().as_str(), request.uri().path(), request.uri().query());
+            let request_id = request.headers().get_one("X-Request-Id").map(String::from);
+
+            request.local_cache(|| Timings::new()).watch(threshold, description, request_id);
+        }
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if !self.enabled {
+            return;
+        }
+
+        let start = request.local_cache(|| Start(Instant::now()));
+        let timings = request.local_cache(|| Timings::new());
+
+        if self.admin_only && !timings.is_admin() {
+            return;
+        }
+
+        response.set_header(Header::new("Server-Timing", timings.to_header(start.0.elapsed())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_header() {
+        let timings = Timings::new();
+
+        timings.record("auth", Duration::from_millis(2));
+        timings.record("db", Duration::from_millis(10));
+        timings.record("auth", Duration::from_millis(1));
+
+        assert_eq!(timings.to_header(Duration::from_micros(15500)), "auth;dur=3.00, db;dur=10.00, total;dur=15.50");
+    }
+
+    #[test]
+    fn timings_time() {
+        let timings = Timings::new();
+
+        assert_eq!(timings.time("serialize", || 1 + 1), 2);
+        assert!(timings.to_header(Duration::from_millis(1)).starts_with("serialize;dur="));
+    }
+
+    #[test]
+    fn timings_slow() {
+        let timings = Timings::new();
+        assert_eq!(timings.slow("db", Duration::from_secs(10)), None);
+
+        timings.watch(Duration::from_millis(100), String::from("GET /api/data/features?filter=a"), Some(String::from("abc")));
+
+        assert_eq!(timings.slow("db", Duration::from_millis(99)), None);
+        assert_eq!(timings.slow("serialize", Duration::from_millis(250)), None);
+        assert_eq!(timings.slow("db", Duration::from_millis(250)), Some(String::from("WARN: Slow Query: 250.00ms on GET /api/data/features?filter=a (request abc)")));
+    }
+
+    #[test]
+    fn describe_request() {
+        assert_eq!(describe("GET", "/api/data/features", None), "GET /api/data/features");
+        assert_eq!(describe("GET", "/api/user/create", Some("username=a&password=b&new_Password=c")), "GET /api/user/create?username=a&password=REDACTED&new_Password=REDACTED");
+        assert_eq!(describe("GET", "/api/user/token", Some("token")), "GET /api/user/token?token=REDACTED");
+        assert!(describe("GET", "/api/data/features", Some(&"a".repeat(1000))).ends_with("..."));
+    }
+}
@@ -0,0 +1,527 @@
+use std::collections::HashMap;
+use std::io::Read;
+use crate::err::HecateError;
+
+type Point = [f64; 2];
+
+///
+/// A point that can be hashed and compared exactly
+///
+type Key = (u64, u64);
+
+fn key(point: &Point) -> Key {
+    //Normalize -0.0 so that it is considered equal to 0.0
+    ((point[0] + 0.0).to_bits(), (point[1] + 0.0).to_bits())
+}
+
+///
+/// A geometry with each of its lines replaced by an index into the extracted lines
+///
+enum Geom {
+    Null,
+    Point(Point),
+    MultiPoint(Vec<Point>),
+    LineString(usize),
+    MultiLineString(Vec<usize>),
+    Polygon(Vec<usize>),
+    MultiPolygon(Vec<Vec<usize>>),
+    GeometryCollection(Vec<Geom>)
+}
+
+struct Line {
+    points: Vec<Point>,
+    ring: bool
+}
+
+struct Transform {
+    scale: Point,
+    translate: Point
+}
+
+impl Transform {
+    fn new(bbox: &[f64; 4], quantization: u32) -> Self {
+        let q = (quantization - 1) as f64;
+
+        let scale = |min: f64, max: f64| if max > min { (max - min) / q } else { 1.0 };
+
+        Transform {
+            scale: [scale(bbox[0], bbox[2]), scale(bbox[1], bbox[3])],
+            translate: [bbox[0], bbox[1]]
+        }
+    }
+
+    fn apply(&self, point: &Point) -> Point {
+        [
+            ((point[0] - self.translate[0]) / self.scale[0]).round(),
+            ((point[1] - self.translate[1]) / self.scale[1]).round()
+        ]
+    }
+}
+
+fn invalid(reason: &str) -> HecateError {
+    HecateError::new(500, String::from("Failed to generate TopoJSON"), Some(String::from(reason)))
+}
+
+fn position(coords: &serde_json::Value) -> Result<Point, HecateError> {
+    match coords.as_array() {
+        Some(coords) if coords.len() >= 2 => match (coords[0].as_f64(), coords[1].as_f64()) {
+            (Some(x), Some(y)) => Ok([x, y]),
+            _ => Err(invalid("position must be numeric"))
+        },
+        _ => Err(invalid("position must have at least two coordinates"))
+    }
+}
+
+fn positions(coords: &serde_json::Value) -> Result<Vec<Point>, HecateError> {
+    match coords.as_array() {
+        Some(coords) => coords.iter().map(|coord| position(coord)).collect(),
+        None => Err(invalid("expected an array of positions"))
+    }
+}
+
+fn list<'a>(coords: &'a serde_json::Value) -> Result<&'a Vec<serde_json::Value>, HecateError> {
+    match coords.as_array() {
+        Some(coords) => Ok(coords),
+        None => Err(invalid("expected an array"))
+    }
+}
+
+struct Builder {
+    lines: Vec<Line>,
+    bbox: Option<[f64; 4]>
+}
+
+impl Builder {
+    fn extend(&mut self, point: &Point) {
+        self.bbox = match self.bbox {
+            None => Some([point[0], point[1], point[0], point[1]]),
+            Some(bbox) => Some([
+                bbox[0].min(point[0]),
+                bbox[1].min(point[1]),
+                bbox[2].max(point[0]),
+                bbox[3].max(point[1])
+            ])
+        };
+    }
+
+    fn line(&mut self, coords: &serde_json::Value, ring: bool) -> Result<usize, HecateError> {
+        let points = positions(coords)?;
+
+        for point in &points {
+            self.extend(point);
+        }
+
+        self.lines.push(Line {
+            points: points,
+            ring: ring
+        });
+
+        Ok(self.lines.len() - 1)
+    }
+
+    fn geometry(&mut self, geom: &serde_json::Value) -> Result<Geom, HecateError> {
+        if geom.is_null() {
+            return Ok(Geom::Null);
+        }
+
+        let coords = &geom["coordinates"];
+
+        match geom["type"].as_str() {
+            Some("Point") => {
+                let point = position(coords)?;
+                self.extend(&point);
+                Ok(Geom::Point(point))
+            },
+            Some("MultiPoint") => {
+                let points = positions(coords)?;
+                for point in &points {
+                    self.extend(point);
+                }
+                Ok(Geom::MultiPoint(points))
+            },
+            Some("LineString") => Ok(Geom::LineString(self.line(coords, false)?)),
+            Some("MultiLineString") => {
+                let mut lines = Vec::new();
+                for line in list(coords)? {
+                    lines.push(self.line(line, false)?);
+                }
+                Ok(Geom::MultiLineString(lines))
+            },
+            Some("Polygon") => {
+                let mut rings = Vec::new();
+                for ring in list(coords)? {
+                    rings.push(self.line(ring, true)?);
+                }
+                Ok(Geom::Polygon(rings))
+            },
+            Some("MultiPolygon") => {
+                let mut polys = Vec::new();
+                for poly in list(coords)? {
+                    let mut rings = Vec::new();
+                    for ring in list(poly)? {
+                        rings.push(self.line(ring, true)?);
+                    }
+                    polys.push(rings);
+                }
+                Ok(Geom::MultiPolygon(polys))
+            },
+            Some("GeometryCollection") => {
+                let mut geoms = Vec::new();
+                for geom in list(&geom["geometries"])? {
+                    geoms.push(self.geometry(geom)?);
+                }
+                Ok(Geom::GeometryCollection(geoms))
+            },
+            _ => Err(invalid("unknown geometry type"))
+        }
+    }
+}
+
+///
+/// Find every point at which lines meet or diverge
+///
+/// A point is a junction if it is the end of an open line or if it is visited
+/// twice with a different pair of neighbours. Points along an edge shared by
+/// two lines always have the same neighbours so are not junctions
+///
+fn junctions(lines: &Vec<Line>) -> HashMap<Key, bool> {
+    let mut neighbours: HashMap<Key, (Key, Key)> = HashMap::new();
+    let mut junctions: HashMap<Key, bool> = HashMap::new();
+
+    for line in lines {
+        let points = &line.points;
+
+        if points.len() == 0 {
+            continue;
+        }
+
+        //A closed ring repeats its first point, which is not visited twice
+        let n = if line.ring && points.len() > 1 { points.len() - 1 } else { points.len() };
+
+        for i in 0..n {
+            let point = key(&points[i]);
+
+            let (prev, next) = if line.ring {
+                (key(&points[(i + n - 1) % n]), key(&points[(i + 1) % n]))
+            } else if i == 0 || i == n - 1 {
+                junctions.insert(point, true);
+                continue;
+            } else {
+                (key(&points[i - 1]), key(&points[i + 1]))
+            };
+
+            let pair = if prev < next { (prev, next) } else { (next, prev) };
+
+            match neighbours.get(&point) {
+                None => { neighbours.insert(point, pair); },
+                Some(seen) => if *seen != pair {
+                    junctions.insert(point, true);
+                }
+            };
+        }
+    }
+
+    junctions
+}
+
+///
+/// Split a line into arcs that start and end at junctions
+///
+fn cut(line: &Line, junctions: &HashMap<Key, bool>) -> Vec<Vec<Point>> {
+    let mut points = line.points.clone();
+
+    if points.len() < 2 {
+        return vec![points];
+    }
+
+    if line.ring {
+        let n = points.len() - 1;
+        points.truncate(n);
+
+        //Rings begin at a junction so that they are cut cleanly. Rings without
+        //junctions begin at their lowest point so that identical rings share an arc
+        let start = match (0..n).find(|i| junctions.contains_key(&key(&points[*i]))) {
+            Some(start) => start,
+            None => (0..n).min_by_key(|i| key(&points[*i])).unwrap()
+        };
+
+        points.rotate_left(start);
+        points.push(points[0]);
+    }
+
+    let mut arcs: Vec<Vec<Point>> = Vec::new();
+    let mut arc: Vec<Point> = vec![points[0]];
+
+    for i in 1..points.len() {
+        arc.push(points[i]);
+
+        if i < points.len() - 1 && junctions.contains_key(&key(&points[i])) {
+            arcs.push(arc);
+            arc = vec![points[i]];
+        }
+    }
+
+    arcs.push(arc);
+
+    arcs
+}
+
+fn encode(point: &Point, quantized: bool) -> serde_json::Value {
+    if quantized {
+        json!([point[0] as i64, point[1] as i64])
+    } else {
+        json!([point[0], point[1]])
+    }
+}
+
+fn output(geom: &Geom, line_arcs: &Vec<Vec<i64>>, transform: &Option<Transform>) -> serde_json::Value {
+    let quantize = |point: &Point| match transform {
+        Some(transform) => encode(&transform.apply(point), true),
+        None => encode(point, false)
+    };
+
+    match geom {
+        Geom::Null => json!({ "type": null }),
+        Geom::Point(point) => json!({
+            "type": "Point",
+            "coordinates": quantize(point)
+        }),
+        Geom::MultiPoint(points) => json!({
+            "type": "MultiPoint",
+            "coordinates": points.iter().map(|point| quantize(point)).collect::<Vec<serde_json::Value>>()
+        }),
+        Geom::LineString(line) => json!({
+            "type": "LineString",
+            "arcs": line_arcs[*line]
+        }),
+        Geom::MultiLineString(lines) => json!({
+            "type": "MultiLineString",
+            "arcs": lines.iter().map(|line| &line_arcs[*line]).collect::<Vec<&Vec<i64>>>()
+        }),
+        Geom::Polygon(rings) => json!({
+            "type": "Polygon",
+            "arcs": rings.iter().map(|ring| &line_arcs[*ring]).collect::<Vec<&Vec<i64>>>()
+        }),
+        Geom::MultiPolygon(polys) => json!({
+            "type": "MultiPolygon",
+            "arcs": polys.iter().map(|rings| {
+                rings.iter().map(|ring| &line_arcs[*ring]).collect::<Vec<&Vec<i64>>>()
+            }).collect::<Vec<Vec<&Vec<i64>>>>()
+        }),
+        Geom::GeometryCollection(geoms) => json!({
+            "type": "GeometryCollection",
+            "geometries": geoms.iter().map(|geom| output(geom, line_arcs, transform)).collect::<Vec<serde_json::Value>>()
+        })
+    }
+}
+
+///
+/// Convert a list of GeoJSON features into a TopoJSON topology
+///
+/// Lines and polygon rings are split into arcs at every junction and arcs
+/// that are shared between features are only stored once. When a quantization
+/// is given coordinates are snapped to a grid of that many points in each
+/// dimension and the arcs are delta encoded
+///
+pub fn topology(features: &Vec<serde_json::Value>, quantization: &Option<u32>) -> Result<serde_json::Value, HecateError> {
+    match quantization {
+        Some(quantization) if *quantization < 2 => {
+            return Err(HecateError::new(400, String::from("Invalid Quantization - must be at least 2"), None));
+        },
+        _ => ()
+    };
+
+    let mut builder = Builder {
+        lines: Vec::new(),
+        bbox: None
+    };
+
+    let mut geoms: Vec<Geom> = Vec::with_capacity(features.len());
+    for feature in features {
+        geoms.push(builder.geometry(&feature["geometry"])?);
+    }
+
+    let transform = match (quantization, builder.bbox) {
+        (Some(quantization), Some(bbox)) => Some(Transform::new(&bbox, *quantization)),
+        _ => None
+    };
+
+    let mut lines = builder.lines;
+
+    match transform {
+        None => (),
+        Some(ref transform) => for line in lines.iter_mut() {
+            let mut points: Vec<Point> = Vec::with_capacity(line.points.len());
+
+            //Snapping can collapse neighbouring points together
+            for point in &line.points {
+                let point = transform.apply(point);
+
+                if points.last() != Some(&point) {
+                    points.push(point);
+                }
+            }
+
+            line.points = points;
+        }
+    };
+
+    let junctions = junctions(&lines);
+
+    let mut arcs: Vec<Vec<Point>> = Vec::new();
+    let mut index: HashMap<Vec<Key>, i64> = HashMap::new();
+    let mut line_arcs: Vec<Vec<i64>> = Vec::with_capacity(lines.len());
+
+    for line in &lines {
+        let mut ids: Vec<i64> = Vec::new();
+
+        for arc in cut(line, &junctions) {
+            let forward: Vec<Key> = arc.iter().map(|point| key(point)).collect();
+            let mut reverse = forward.clone();
+            reverse.reverse();
+
+            let id = match (index.get(&forward), index.get(&reverse)) {
+                (Some(id), _) => *id,
+                (None, Some(id)) => !*id,
+                (None, None) => {
+                    let id = arcs.len() as i64;
+                    index.insert(forward, id);
+                    arcs.push(arc);
+                    id
+                }
+            };
+
+            ids.push(id);
+        }
+
+        line_arcs.push(ids);
+    }
+
+    let arcs: Vec<serde_json::Value> = arcs.iter().map(|arc| {
+        match transform {
+            None => serde_json::Value::Array(arc.iter().map(|point| encode(point, false)).collect()),
+            Some(_) => {
+                let mut prev: Point = [0.0, 0.0];
+
+                serde_json::Value::Array(arc.iter().map(|point| {
+                    let delta = [point[0] - prev[0], point[1] - prev[1]];
+                    prev = *point;
+                    encode(&delta, true)
+                }).collect())
+            }
+        }
+    }).collect();
+
+    let mut objects: Vec<serde_json::Value> = Vec::with_capacity(features.len());
+    for (feature, geom) in features.iter().zip(geoms.iter()) {
+        let mut object = output(geom, &line_arcs, &transform);
+
+        for field in &["id", "key", "version", "properties"] {
+            if !feature[field].is_null() {
+                object[field] = feature[field].clone();
+            }
+        }
+
+        objects.push(object);
+    }
+
+    let mut topology = json!({
+        "type": "Topology",
+        "objects": {
+            "features": {
+                "type": "GeometryCollection",
+                "geometries": objects
+            }
+        },
+        "arcs": arcs
+    });
+
+    match builder.bbox {
+        None => (),
+        Some(bbox) => { topology["bbox"] = json!(bbox); }
+    };
+
+    match transform {
+        None => (),
+        Some(transform) => {
+            topology["transform"] = json!({
+                "scale": transform.scale,
+                "translate": transform.translate
+            });
+        }
+    };
+
+    Ok(topology)
+}
+
+///
+/// Convert a line delimited GeoJSON stream into a TopoJSON topology
+///
+pub fn from_stream(stream: impl Read, quantization: &Option<u32>) -> Result<serde_json::Value, HecateError> {
+    let features = crate::stream::collect(stream)?;
+
+    topology(&features, quantization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(id: i64, x: f64) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "Feature",
+            "properties": {},
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[x, 0.0], [x + 1.0, 0.0], [x + 1.0, 1.0], [x, 1.0], [x, 0.0]]]
+            }
+        })
+    }
+
+    #[test]
+    fn shared_edge() {
+        let topology = topology(&vec![square(1, 0.0), square(2, 1.0)], &None).unwrap();
+
+        let arcs = topology["arcs"].as_array().unwrap();
+        assert_eq!(arcs.len(), 3);
+
+        let shared: Vec<&serde_json::Value> = arcs.iter().filter(|arc| {
+            let arc = arc.as_array().unwrap();
+            arc.len() == 2 && arc.contains(&json!([1.0, 0.0])) && arc.contains(&json!([1.0, 1.0]))
+        }).collect();
+        assert_eq!(shared.len(), 1);
+
+        let geoms = topology["objects"]["features"]["geometries"].as_array().unwrap();
+        let first: Vec<i64> = serde_json::from_value(geoms[0]["arcs"][0].clone()).unwrap();
+        let second: Vec<i64> = serde_json::from_value(geoms[1]["arcs"][0].clone()).unwrap();
+
+        //The shared arc is used forwards by one polygon and reversed by the other
+        assert!(first.iter().any(|id| second.contains(&!*id)));
+    }
+
+    #[test]
+    fn isolated_ring() {
+        let topology = topology(&vec![square(1, 0.0)], &None).unwrap();
+
+        assert_eq!(topology["arcs"], json!([[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]]));
+        assert_eq!(topology["objects"]["features"]["geometries"][0]["arcs"], json!([[0]]));
+        assert_eq!(topology["bbox"], json!([0.0, 0.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn quantized() {
+        let topology = topology(&vec![square(1, 0.0)], &Some(11)).unwrap();
+
+        assert_eq!(topology["transform"], json!({
+            "scale": [0.1, 0.1],
+            "translate": [0.0, 0.0]
+        }));
+        assert_eq!(topology["arcs"], json!([[[0, 0], [10, 0], [0, 10], [-10, 0], [0, -10]]]));
+    }
+
+    #[test]
+    fn invalid_quantization() {
+        assert!(topology(&vec![square(1, 0.0)], &Some(1)).is_err());
+    }
+}
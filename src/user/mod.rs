@@ -1,70 +1,206 @@
+use crate::auth;
+use crate::delta;
 use crate::err::HecateError;
+use crate::options;
+use crate::validate;
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use regex::Regex;
 
-pub fn create(conn: &impl postgres::GenericConnection, username: &String, password: &String, email: &String) -> Result<bool, HecateError> {
-    match conn.query("
-        INSERT INTO users (username, password, email, meta)
-            VALUES ($1, crypt($2, gen_salt('bf', 10)), $3, '{}'::JSONB);
-    ", &[ &username, &password, &email ]) {
-        Ok(_) => Ok(true),
-        Err(err) => {
-            if err.as_db().is_some() && err.as_db().unwrap().code.code() == "23505" {
-                Err(HecateError::new(400, String::from("User/Email Exists"), None))
-            } else {
-                Err(HecateError::from_db(err))
+///
+/// Length of the temporary passwords generated when a password is reset
+///
+pub static TEMPORARY_LENGTH: usize = 16;
+
+///
+/// Estimate the entropy of a password in bits from its length and the
+/// size of the character classes it draws from
+///
+pub fn entropy(password: &String) -> f64 {
+    let mut pool: u32 = 0;
+
+    if password.chars().any(|c| c.is_ascii_lowercase()) { pool += 26; }
+    if password.chars().any(|c| c.is_ascii_uppercase()) { pool += 26; }
+    if password.chars().any(|c| c.is_ascii_digit()) { pool += 10; }
+    if password.chars().any(|c| c.is_ascii_punctuation() || c == ' ') { pool += 33; }
+    if password.chars().any(|c| !c.is_ascii()) { pool += 100; }
+
+    if pool == 0 {
+        return 0.0;
+    }
+
+    password.chars().count() as f64 * (pool as f64).log2()
+}
+
+///
+/// Ensure a new password meets the entropy required by the server's user policy
+///
+pub fn is_strong(policy: &options::User, password: &String) -> Result<bool, HecateError> {
+    match policy.entropy {
+        Some(required) if entropy(password) < required => {
+            Err(HecateError::new(400, format!("Weak Password - must have an estimated entropy of at least {} bits", required), None))
+        },
+        _ => Ok(true)
+    }
+}
+
+///
+/// Ensure a new username and password are allowed by the server's user policy
+///
+pub fn is_allowed(policy: &options::User, username: &String, password: &String) -> Result<bool, HecateError> {
+    match &policy.reserved {
+        None => (),
+        Some(reserved) => {
+            if reserved.iter().any(|name| name.to_lowercase() == username.to_lowercase()) {
+                return Err(HecateError::new(400, String::from("Username Reserved"), None));
             }
         }
-    }
+    };
+
+    match &policy.username {
+        None => (),
+        Some(pattern) => match Regex::new(pattern) {
+            Ok(pattern) => if !pattern.is_match(username) {
+                return Err(HecateError::new(400, String::from("Invalid Username - contains characters or a length not allowed by this server"), None));
+            },
+            Err(err) => { return Err(HecateError::new(500, String::from("Internal Server Error"), Some(err.to_string()))); }
+        }
+    };
+
+    is_strong(policy, password)?;
+
+    Ok(true)
 }
 
-pub fn list(conn: &impl postgres::GenericConnection, limit: &Option<i16>) -> Result<serde_json::Value, HecateError> {
-    let limit: i16 = match limit {
-        None => 100,
-        Some(limit) => if *limit > 100 { 100 } else { *limit }
+///
+/// Create a user, the username is stored as given for display along with its
+/// lowercase form used to look it up when usernames are case folded
+///
+pub fn create(conn: &impl postgres::GenericConnection, policy: &options::User, username: &String, password: &String, email: &String) -> Result<bool, HecateError> {
+    is_allowed(policy, username, password)?;
+
+    let case_fold = policy.case_fold.unwrap_or(false);
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
     };
 
-    match conn.query("
+    //Serialize the creation of usernames that fold to the same value so that
+    //two of them cannot be created concurrently
+    if case_fold {
+        match trans.execute("SELECT pg_advisory_xact_lock(hashtext(LOWER($1)))", &[ &username ]) {
+            Ok(_) => (),
+            Err(err) => { return Err(HecateError::from_db(err)); }
+        };
+    }
+
+    match trans.query("
+        INSERT INTO users (username, username_folded, password, email, meta)
+            SELECT $1, LOWER($1), crypt($2, gen_salt('bf', 10)), $3, '{}'::JSONB
+            WHERE
+                NOT $4
+                OR NOT EXISTS (
+                    SELECT 1 FROM users WHERE username_folded = LOWER($1)
+                )
+            RETURNING id;
+    ", &[ &username, &password, &email, &case_fold ]) {
+        Ok(ref rows) if rows.len() == 0 => { return Err(HecateError::conflict("User/Email Exists")); },
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_constraint(err, "User/Email Exists")); }
+    };
+
+    match trans.commit() {
+        Ok(_) => Ok(true),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Fields that can be requested when listing users
+///
+pub const LIST_FIELDS: &[(&str, &str)] = &[
+    ("id", "id"),
+    ("access", "access"),
+    ("username", "username")
+];
+
+pub fn list(conn: &impl postgres::GenericConnection, limit: &Option<i64>, fields: &Option<String>) -> Result<serde_json::Value, HecateError> {
+    let fields = validate::fields(fields, LIST_FIELDS)?;
+
+    match conn.query(&*format!("
         SELECT 
             COALESCE(json_agg(row_to_json(row)), '[]'::JSON)
         FROM (
             SELECT
-                id,
-                access,
-                username
+                {fields}
             FROM
                 users
             ORDER BY
                 username
-            LIMIT $1::SmallInt
+            LIMIT $1::BIGINT
         ) row;
-    ", &[ &limit ]) {
+    ", fields = fields), &[ &limit ]) {
         Ok(rows) => Ok(rows.get(0).get(0)),
         Err(err) => Err(HecateError::from_db(err))
     }
 }
 
-pub fn filter(conn: &impl postgres::GenericConnection, filter: &String, limit: &Option<i16>) -> Result<serde_json::Value, HecateError> {
-    let limit: i16 = match limit {
-        None => 100,
-        Some(limit) => if *limit > 100 { 100 } else { *limit }
-    };
+///
+/// Default number of seconds within which a user must have used a token to be active
+///
+pub static ACTIVE_WINDOW: i32 = 900;
 
+///
+/// List the users that have used a token within the last `window` seconds,
+/// most recently active first
+///
+pub fn active(conn: &impl postgres::GenericConnection, window: &i32) -> Result<serde_json::Value, HecateError> {
     match conn.query("
+        SELECT
+            COALESCE(json_agg(row_to_json(row)), '[]'::JSON)
+        FROM (
+            SELECT
+                users.id,
+                users.username,
+                MAX(users_tokens.last_used) AS last_used
+            FROM
+                users,
+                users_tokens
+            WHERE
+                users_tokens.uid = users.id
+                AND users_tokens.last_used > now() - ($1::INTEGER * INTERVAL '1 second')
+            GROUP BY
+                users.id,
+                users.username
+            ORDER BY
+                last_used DESC,
+                users.id
+        ) row;
+    ", &[ &window ]) {
+        Ok(rows) => Ok(rows.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+pub fn filter(conn: &impl postgres::GenericConnection, filter: &String, limit: &Option<i64>, fields: &Option<String>) -> Result<serde_json::Value, HecateError> {
+    let fields = validate::fields(fields, LIST_FIELDS)?;
+
+    match conn.query(&*format!("
         SELECT 
             COALESCE(json_agg(row_to_json(row)), '[]'::JSON)
         FROM (
             SELECT
-                id,
-                access,
-                username
+                {fields}
             FROM
                 users
             WHERE
                 username ~ $1
             ORDER BY
                 username
-            LIMIT $2::SmallInt
+            LIMIT $2::BIGINT
         ) row;
-    ", &[ &filter, &limit ]) {
+    ", fields = fields), &[ &filter, &limit ]) {
         Ok(rows) => Ok(rows.get(0).get(0)),
         Err(err) => Err(HecateError::from_db(err))
     }
@@ -109,23 +245,223 @@ pub fn info(conn: &impl postgres::GenericConnection, uid: &i64) -> Result<serde_
             WHERE id = $1
         ) u
     ", &[ &uid ]) {
-        Ok(res) => Ok(res.get(0).get(0)),
+        Ok(res) => {
+            if res.len() == 0 {
+                return Err(HecateError::new(404, String::from("User Not Found"), None));
+            }
+
+            Ok(res.get(0).get(0))
+        },
         Err(err) => Err(HecateError::from_db(err))
     }
 }
 
-pub fn create_token(conn: &impl postgres::GenericConnection, uid: &i64) -> Result<String, HecateError> {
+///
+/// Maximum size in bytes of the serialized user meta object
+///
+pub static MAX_META: usize = 8192;
+
+///
+/// The user profile fields that can be updated by the user
+///
+#[derive(Deserialize, Debug)]
+pub struct Profile {
+    pub email: Option<String>,
+    pub meta: Option<serde_json::Value>
+}
+
+///
+/// Update the email and/or meta of a user, returning the updated user info
+///
+/// Fields that are omitted are left unchanged, a given meta object replaces
+/// the existing one
+///
+pub fn update(conn: &impl postgres::GenericConnection, uid: &i64, profile: serde_json::Value) -> Result<serde_json::Value, HecateError> {
+    let profile: Profile = match serde_json::from_value(profile) {
+        Ok(profile) => profile,
+        Err(err) => { return Err(HecateError::new(400, String::from("Invalid User JSON"), Some(err.to_string()))); }
+    };
+
+    match profile.email {
+        None => (),
+        Some(ref email) => validate::email(email)?
+    };
+
+    match profile.meta {
+        None => (),
+        Some(ref meta) => {
+            if !meta.is_object() {
+                return Err(HecateError::new(400, String::from("User meta must be a JSON Object"), None));
+            } else if meta.to_string().len() > MAX_META {
+                return Err(HecateError::new(400, format!("User meta cannot exceed {} bytes", MAX_META), None));
+            }
+
+            delta::batch::validate(meta)?;
+        }
+    };
+
+    match conn.execute("
+        UPDATE users
+            SET
+                email = COALESCE($2, email),
+                meta = COALESCE($3, meta)
+            WHERE
+                id = $1
+    ", &[ &uid, &profile.email, &profile.meta ]) {
+        Ok(0) => { return Err(HecateError::new(404, String::from("User Not Found"), None)); },
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_constraint(err, "User/Email Exists")); }
+    };
+
+    info(conn, uid)
+}
+
+///
+/// Reset the password of a user, returning the generated temporary password if
+/// no password is given
+///
+/// The user must change the password before their next login and every token
+/// of the user is revoked
+///
+pub fn reset_password(conn: &impl postgres::GenericConnection, policy: &options::User, uid: &i64, password: &Option<String>) -> Result<Option<String>, HecateError> {
+    let (password, temporary) = match password {
+        Some(password) => {
+            is_strong(policy, password)?;
+
+            (password.clone(), None)
+        },
+        None => {
+            let mut rng = rand::thread_rng();
+            let password: String = rng.sample_iter(&Alphanumeric).take(TEMPORARY_LENGTH).collect();
+
+            (password.clone(), Some(password))
+        }
+    };
+
+    let trans = match conn.transaction() {
+        Ok(trans) => trans,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.execute("
+        UPDATE users
+            SET
+                password = crypt($2, gen_salt('bf', 10)),
+                password_reset = TRUE
+            WHERE
+                id = $1
+    ", &[ &uid, &password ]) {
+        Ok(0) => { return Err(HecateError::new(404, String::from("User Not Found"), None)); },
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.execute("
+        DELETE FROM users_tokens WHERE uid = $1
+    ", &[ &uid ]) {
+        Ok(_) => (),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match trans.commit() {
+        Ok(_) => Ok(temporary),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Change the password of a user given their username & current password,
+/// clearing any reset that requires the password to be changed
+///
+pub fn change_password(conn: &impl postgres::GenericConnection, policy: &options::User, username: &String, current: &String, password: &String) -> Result<bool, HecateError> {
+    if password == current {
+        return Err(HecateError::new(400, String::from("Invalid Password - must differ from the current password"), None));
+    }
+
+    is_strong(policy, password)?;
+
+    let username_sql = if policy.case_fold.unwrap_or(false) {
+        "(username_folded = LOWER($1) OR username = $1)"
+    } else {
+        "username = $1"
+    };
+
+    match conn.execute(&*format!("
+        UPDATE users
+            SET
+                password = crypt($3, gen_salt('bf', 10)),
+                password_reset = FALSE
+            WHERE
+                id = (
+                    SELECT id
+                    FROM users
+                    WHERE
+                        {username}
+                        AND password = crypt($2, password)
+                    ORDER BY username = $1 DESC
+                    LIMIT 1
+                )
+    ", username = username_sql), &[ &username, &current, &password ]) {
+        Ok(0) => Err(HecateError::new(401, String::from("You must be logged in to access this resource"), None)),
+        Ok(_) => Ok(true),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Create a token of the given kind, expiring after the configured expiry of that kind
+///
+/// Features created with a token that has a `source` are stamped with it in
+/// place of the username when provenance is enabled
+///
+pub fn create_token(conn: &impl postgres::GenericConnection, uid: &i64, kind: &auth::TokenKind, session: &options::Session, source: &Option<String>) -> Result<String, HecateError> {
     match conn.query("
-        INSERT INTO users_tokens (name, uid, token, expiry)
+        INSERT INTO users_tokens (name, uid, token, expiry, kind, source)
             VALUES (
-                'Session Token',
+                $2,
                 $1,
                 md5(random()::TEXT),
-                now() + INTERVAL '4 hours'
+                now() + ($3::INTEGER * INTERVAL '1 second'),
+                $4,
+                $5
             )
             RETURNING token;
-    ", &[ &uid ]) {
+    ", &[ &uid, &kind.name(), &(session.expiry(kind) as i32), &kind.as_str(), &source ]) {
+        Ok(res) => {
+            let token: String = res.get(0).get(0);
+            Ok(token)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Create a short lived token allowing an admin to act as another user
+///
+/// Requests made with the token are authenticated as the target user but are
+/// flagged as impersonated, which prevents any admin operation from being
+/// performed with it
+///
+pub fn create_impersonation_token(conn: &impl postgres::GenericConnection, uid: &i64, impersonator: &i64) -> Result<String, HecateError> {
+    match conn.query("
+        INSERT INTO users_tokens (name, uid, token, expiry, impersonator)
+            SELECT
+                'Impersonation Token',
+                users.id,
+                md5(random()::TEXT),
+                now() + INTERVAL '30 minutes',
+                $2
+            FROM
+                users
+            WHERE
+                users.id = $1
+            RETURNING token;
+    ", &[ &uid, &impersonator ]) {
         Ok(res) => {
+            if res.len() == 0 {
+                return Err(HecateError::new(404, String::from("User Not Found"), None));
+            }
+
             let token: String = res.get(0).get(0);
             Ok(token)
         },
@@ -25,6 +25,12 @@ pub fn point(point: &String) -> Result<(f64, f64), HecateError> {
     Ok((lng, lat))
 }
 
+///
+/// Ensure a bbox has 4 in range coordinates
+///
+/// A minX greater than maxX is allowed as it describes a bbox that crosses
+/// the antimeridian
+///
 pub fn bbox(bbox: &Vec<f64>) -> Result<(), HecateError> {
     if bbox.len() != 4 {
         return Err(HecateError::new(400, String::from("Invalid BBOX"), None));
@@ -38,8 +44,6 @@ pub fn bbox(bbox: &Vec<f64>) -> Result<(), HecateError> {
         return Err(HecateError::new(400, String::from("BBOX maxX value must be a number between -180 and 180"), None));
     } else if bbox[3].is_nan() || bbox[3] < -90.0 || bbox[3] > 90.0 {
         return Err(HecateError::new(400, String::from("BBOX maxY value must be a number between -90 and 90"), None));
-    } else if bbox[0] > bbox[2] {
-        return Err(HecateError::new(400, String::from("BBOX minX value cannot be greater than maxX value"), None));
     } else if bbox[1] > bbox[3] {
         return Err(HecateError::new(400, String::from("BBOX minY value cannot be greater than maxY value"), None));
     }
@@ -47,6 +51,74 @@ pub fn bbox(bbox: &Vec<f64>) -> Result<(), HecateError> {
     Ok(())
 }
 
+///
+/// Maximum distance in metres a query geometry can be buffered by
+///
+pub static MAX_BUFFER: f64 = 50000.0;
+
+///
+/// Ensure a buffer distance is a positive number of metres no greater than `MAX_BUFFER`
+///
+pub fn buffer(buffer: &Option<f64>) -> Result<Option<f64>, HecateError> {
+    match buffer {
+        None => Ok(None),
+        Some(buffer) if buffer.is_finite() && *buffer > 0.0 && *buffer <= MAX_BUFFER => Ok(Some(*buffer)),
+        Some(_) => Err(HecateError::new(400, format!("Buffer must be a number of metres greater than 0 and no greater than {}", MAX_BUFFER), None))
+    }
+}
+
+///
+/// Perform a basic structural check of an email address
+///
+/// Deliverability can only be confirmed by sending an email, this simply
+/// ensures the value has a local part and a dotted domain
+///
+pub fn email(email: &String) -> Result<(), HecateError> {
+    let parts: Vec<&str> = email.split('@').collect();
+
+    if email.len() > 254 || email.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(HecateError::new(400, String::from("Invalid Email"), None));
+    } else if parts.len() != 2 || parts[0].len() == 0 {
+        return Err(HecateError::new(400, String::from("Invalid Email"), None));
+    } else if !parts[1].contains('.') || parts[1].starts_with('.') || parts[1].ends_with('.') || parts[1].contains("..") {
+        return Err(HecateError::new(400, String::from("Invalid Email"), None));
+    }
+
+    Ok(())
+}
+
+///
+/// Parse a comma separated list of field names into an SQL select list
+///
+/// `allowed` maps each field name that can be requested to the SQL expression
+/// that selects it. If no fields are given, every allowed field is selected
+///
+pub fn fields(fields: &Option<String>, allowed: &[(&str, &str)]) -> Result<String, HecateError> {
+    let names: Vec<&str> = match fields {
+        None => allowed.iter().map(|field| field.0).collect(),
+        Some(fields) => fields.split(',').map(|field| field.trim()).collect()
+    };
+
+    let mut select: Vec<String> = Vec::new();
+    for name in names {
+        match allowed.iter().find(|field| field.0 == name) {
+            None => {
+                let names: Vec<&str> = allowed.iter().map(|field| field.0).collect();
+                return Err(HecateError::new(400, format!("Invalid Field - '{}' must be one of {}", name, names.join(", ")), None));
+            },
+            Some((name, expr)) => {
+                let column = format!("{} AS {}", expr, name);
+
+                if !select.contains(&column) {
+                    select.push(column);
+                }
+            }
+        };
+    }
+
+    Ok(select.join(", "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,15 +254,8 @@ mod tests {
     }
 
     #[test]
-    fn invalid_bbox_minx_gt_maxx() {
-        match bbox(&[107.578125,-30.600094,56.162109,46.377254].to_vec()){
-            Ok(_) => (),
-            Err(err) =>  assert_eq!(err.as_json(),json!({
-                "code": 400,
-                "reason": "BBOX minX value cannot be greater than maxX value",
-                "status": "Bad Request"
-            }), "not ok - the minX value is greater than maxX."),
-        }
+    fn valid_bbox_antimeridian() {
+        assert_eq!(bbox(&[170.0,-30.600094,-170.0,46.377254].to_vec()).ok(),Some(()), "ok - bbox crossing the antimeridian is valid.");
     }
 
     #[test]
@@ -204,4 +269,65 @@ mod tests {
             }), "not ok - the minY value is greater than maxY."),
         }
     }
+
+    #[test]
+    fn valid_buffer() {
+        assert_eq!(buffer(&None).unwrap(), None);
+        assert_eq!(buffer(&Some(100.0)).unwrap(), Some(100.0));
+        assert_eq!(buffer(&Some(MAX_BUFFER)).unwrap(), Some(MAX_BUFFER));
+    }
+
+    #[test]
+    fn invalid_buffer() {
+        for invalid in &[0.0, -100.0, MAX_BUFFER + 1.0, std::f64::NAN, std::f64::INFINITY] {
+            assert_eq!(buffer(&Some(*invalid)).err().unwrap().as_json(), json!({
+                "code": 400,
+                "reason": "Buffer must be a number of metres greater than 0 and no greater than 50000",
+                "status": "Bad Request"
+            }), "not ok - {} is not a valid buffer.", invalid);
+        }
+    }
+
+    #[test]
+    fn valid_fields() {
+        let allowed = [("id", "users.id"), ("username", "users.username")];
+
+        assert_eq!(fields(&None, &allowed).unwrap(), "users.id AS id, users.username AS username");
+        assert_eq!(fields(&Some(String::from("username")), &allowed).unwrap(), "users.username AS username");
+        assert_eq!(fields(&Some(String::from("username, id,username")), &allowed).unwrap(), "users.username AS username, users.id AS id");
+    }
+
+    #[test]
+    fn invalid_fields() {
+        let allowed = [("id", "users.id"), ("username", "users.username")];
+
+        assert_eq!(fields(&Some(String::from("id,password")), &allowed).err().unwrap().as_json(), json!({
+            "code": 400,
+            "reason": "Invalid Field - 'password' must be one of id, username",
+            "status": "Bad Request"
+        }));
+
+        assert_eq!(fields(&Some(String::from("")), &allowed).err().unwrap().as_json(), json!({
+            "code": 400,
+            "reason": "Invalid Field - '' must be one of id, username",
+            "status": "Bad Request"
+        }));
+    }
+
+    #[test]
+    fn valid_email() {
+        assert_eq!(email(&String::from("ingalls@protonmail.com")).ok(), Some(()), "ok - email is valid.");
+        assert_eq!(email(&String::from("first.last+tag@mail.example.co")).ok(), Some(()), "ok - email is valid.");
+    }
+
+    #[test]
+    fn invalid_email() {
+        for invalid in &["ingalls", "@protonmail.com", "ingalls@protonmail", "ingalls@@protonmail.com", "ingalls@protonmail..com", "in galls@protonmail.com"] {
+            assert_eq!(email(&String::from(*invalid)).err().unwrap().as_json(), json!({
+                "code": 400,
+                "reason": "Invalid Email",
+                "status": "Bad Request"
+            }), "not ok - {} is not a valid email.", invalid);
+        }
+    }
 }
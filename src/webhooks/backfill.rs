@@ -0,0 +1,193 @@
+use std::thread;
+use std::time::Duration;
+use crate::{
+    retry,
+    err::HecateError
+};
+
+///
+/// Maximum number of deltas replayed per second so that a backfill does not
+/// overwhelm the subscriber or starve the server of connections
+///
+pub static RATE: u64 = 10;
+
+///
+/// Seconds a failing delivery is retried for before the backfill is stopped
+///
+pub static RETRY_TIMEOUT: u64 = 30;
+
+///
+/// Minutes without progress after which a running backfill is assumed to have
+/// been interrupted, ie: by a server restart, and can be resumed
+///
+pub static STALE: i32 = 5;
+
+///
+/// The progress of replaying past deltas to a webhook
+///
+/// `last` is the id of the last delta that was delivered, a backfill that
+/// failed or was interrupted resumes from it when restarted with the same `since`
+///
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Backfill {
+    pub webhook: i64,
+    pub since: i64,
+    pub last: i64,
+    pub status: String,
+    pub error: Option<String>
+}
+
+///
+/// Return the progress of the backfill of a webhook
+///
+pub fn get(conn: &impl postgres::GenericConnection, webhook: i64) -> Result<Backfill, HecateError> {
+    match conn.query("
+        SELECT
+            webhook,
+            since,
+            last,
+            status,
+            error
+        FROM
+            webhooks_backfills
+        WHERE
+            webhook = $1
+    ", &[&webhook]) {
+        Ok(rows) => match rows.iter().next() {
+            None => Err(HecateError::new(404, String::from("Backfill Not Found"), None)),
+            Some(row) => Ok(Backfill {
+                webhook: row.get(0),
+                since: row.get(1),
+                last: row.get(2),
+                status: row.get(3),
+                error: row.get(4)
+            })
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Claim the backfill of a webhook, resuming an unfinished backfill with the
+/// same `since` or starting a new one
+///
+pub fn start(conn: &impl postgres::GenericConnection, webhook: i64, since: i64) -> Result<Backfill, HecateError> {
+    let hook = super::get(conn, webhook)?;
+
+    if !hook.actions.iter().any(|action| action == "delta") {
+        return Err(HecateError::new(400, String::from("Webhook is not subscribed to delta events"), None));
+    }
+
+    if since < 0 {
+        return Err(HecateError::new(400, String::from("Invalid since - must be a delta id"), None));
+    }
+
+    match conn.query("
+        INSERT INTO webhooks_backfills (webhook, since, last, status, error, created, updated)
+            VALUES ($1, $2, $2, 'running', NULL, NOW(), NOW())
+            ON CONFLICT (webhook) DO UPDATE
+                SET
+                    last = CASE
+                        WHEN webhooks_backfills.since = $2 AND webhooks_backfills.status != 'complete' THEN webhooks_backfills.last
+                        ELSE $2
+                    END,
+                    since = $2,
+                    status = 'running',
+                    error = NULL,
+                    updated = NOW()
+                WHERE
+                    webhooks_backfills.status != 'running'
+                    OR webhooks_backfills.updated < NOW() - ($3::INTEGER * INTERVAL '1 minute')
+            RETURNING webhook, since, last, status, error
+    ", &[&webhook, &since, &STALE]) {
+        Ok(rows) => match rows.iter().next() {
+            None => Err(HecateError::new(409, String::from("A backfill is already running for this webhook"), None)),
+            Some(row) => Ok(Backfill {
+                webhook: row.get(0),
+                since: row.get(1),
+                last: row.get(2),
+                status: row.get(3),
+                error: row.get(4)
+            })
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+fn progress(conn: &impl postgres::GenericConnection, backfill: &Backfill) -> Result<(), HecateError> {
+    match conn.execute("
+        UPDATE webhooks_backfills
+            SET
+                last = $2,
+                status = $3,
+                error = $4,
+                updated = NOW()
+            WHERE
+                webhook = $1
+    ", &[&backfill.webhook, &backfill.last, &backfill.status, &backfill.error]) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+fn next_delta(conn: &impl postgres::GenericConnection, last: &i64) -> Result<Option<i64>, HecateError> {
+    match conn.query("
+        SELECT
+            id
+        FROM
+            deltas
+        WHERE
+            id > $1
+            AND finalized
+        ORDER BY id ASC
+        LIMIT 1
+    ", &[&last]) {
+        Ok(rows) => Ok(rows.iter().next().map(|row| row.get(0))),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Re-dispatch the events of every finalized delta after `last` to the webhook,
+/// recording each attempt in the delivery log
+///
+/// A delivery that still fails once retries are exhausted stops the backfill
+/// so that the subscriber never receives deltas out of order
+///
+pub fn run(conn: &impl postgres::GenericConnection, mut backfill: Backfill) -> Result<Backfill, HecateError> {
+    let hook = super::get(conn, backfill.webhook)?;
+    let backoff = retry::Backoff::new(Duration::from_secs(RETRY_TIMEOUT));
+
+    while let Some(delta) = next_delta(conn, &backfill.last)? {
+        for (event, body) in super::delta_events(conn, &delta, hook.is_feature_mode())? {
+            let delivered = backoff.retry("webhook backfill", || {
+                match super::deliver(conn, &hook, event, body.clone()) {
+                    Ok(ref delivery) if delivery.success => Ok(()),
+                    Ok(delivery) => Err(match delivery.error {
+                        Some(error) => error,
+                        None => format!("Status {}", delivery.status.unwrap_or(0))
+                    }),
+                    Err(err) => Err(err.to_string())
+                }
+            });
+
+            if let Err(err) = delivered {
+                backfill.status = String::from("failed");
+                backfill.error = Some(format!("Failed to deliver delta {}: {}", delta, err));
+                progress(conn, &backfill)?;
+
+                return Ok(backfill);
+            }
+        }
+
+        backfill.last = delta;
+        progress(conn, &backfill)?;
+
+        thread::sleep(Duration::from_millis(1000 / RATE));
+    }
+
+    backfill.status = String::from("complete");
+    progress(conn, &backfill)?;
+
+    Ok(backfill)
+}
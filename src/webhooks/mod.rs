@@ -1,27 +1,88 @@
+pub mod backfill;
+
 use postgres;
 use reqwest;
+use std::time::{Duration, Instant};
 use crate::{
+    delta,
     worker,
     err::HecateError
 };
 
+///
+/// The version of the shape of webhook payloads, included in every payload as
+/// `schema_version` so that subscribers can branch on it
+///
+/// Must be incremented whenever a member of any payload is removed, renamed or
+/// changes meaning. Adding a new member does not require a new version
+///
+pub const SCHEMA_VERSION: i64 = 1;
+
 #[derive(Serialize, Deserialize)]
 pub struct WebHook {
     id: Option<i64>,
     name: String,
     actions: Vec<String>,
-    url: String
+    url: String,
+    #[serde(default, skip_serializing)]
+    secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    blocking: Option<bool>
 }
 
 impl WebHook {
-    pub fn new(id: i64, name: String, actions: Vec<String>, url: String) -> Self {
+    pub fn new(id: i64, name: String, actions: Vec<String>, url: String, secret: Option<String>, mode: Option<String>, priority: Option<i64>, blocking: Option<bool>) -> Self {
         WebHook {
             id: Some(id),
             name: name,
             actions: actions,
-            url: url
+            url: url,
+            secret: secret,
+            mode: mode,
+            priority: priority,
+            blocking: blocking
         }
     }
+
+    ///
+    /// Webhooks are sent each event in descending order of priority, `0` by default
+    ///
+    pub fn priority(&self) -> i64 {
+        self.priority.unwrap_or(0)
+    }
+
+    ///
+    /// Whether a failed delivery to this webhook prevents the event from being
+    /// sent to webhooks of a lower priority
+    ///
+    pub fn is_blocking(&self) -> bool {
+        self.blocking.unwrap_or(false)
+    }
+
+    ///
+    /// Whether delta events are sent once for each feature in the delta
+    /// rather than once for the whole delta
+    ///
+    pub fn is_feature_mode(&self) -> bool {
+        self.mode == Some(String::from("feature"))
+    }
+}
+
+///
+/// The outcome of posting a single event to a webhook
+///
+#[derive(Serialize)]
+pub struct Delivery {
+    id: i64,
+    event: String,
+    success: bool,
+    status: Option<u16>,
+    latency: i64,
+    error: Option<String>
 }
 
 #[derive(Debug)]
@@ -47,16 +108,21 @@ pub fn list(conn: &impl postgres::GenericConnection, action: Action) -> Result<V
             id,
             name,
             actions,
-            url
+            url,
+            secret,
+            mode,
+            priority,
+            blocking
         FROM
             webhooks
         {action}
+        ORDER BY COALESCE(priority, 0) DESC, id
     ", action = &action).as_str(), &[]) {
         Ok(results) => {
             let mut hooks: Vec<WebHook> = Vec::with_capacity(results.len());
 
             for result in results.iter() {
-                hooks.push(WebHook::new(result.get(0), result.get(1), result.get(2), result.get(3)));
+                hooks.push(WebHook::new(result.get(0), result.get(1), result.get(2), result.get(3), result.get(4), result.get(5), result.get(6), result.get(7)));
             }
 
             Ok(hooks)
@@ -71,7 +137,11 @@ pub fn get(conn: &impl postgres::GenericConnection, id: i64) -> Result<WebHook,
             id,
             name,
             actions,
-            url
+            url,
+            secret,
+            mode,
+            priority,
+            blocking
         FROM
             webhooks
         WHERE
@@ -84,7 +154,7 @@ pub fn get(conn: &impl postgres::GenericConnection, id: i64) -> Result<WebHook,
 
             let result = results.get(0);
 
-            Ok(WebHook::new(result.get(0), result.get(1), result.get(2), result.get(3)))
+            Ok(WebHook::new(result.get(0), result.get(1), result.get(2), result.get(3), result.get(4), result.get(5), result.get(6), result.get(7)))
         },
         Err(err) => Err(HecateError::from_db(err))
     }
@@ -110,15 +180,23 @@ pub fn create(conn: &impl postgres::GenericConnection, name: serde_json::Value)
         return Err(HecateError::new(400, String::from("Invalid Action"), None));
     }
 
+    if !is_valid_mode(&webhook.mode) {
+        return Err(HecateError::new(400, String::from("Invalid Mode - must be one of 'delta' or 'feature'"), None));
+    }
+
     match conn.query("
-        INSERT INTO webhooks (name, actions, url)
+        INSERT INTO webhooks (name, actions, url, secret, mode, priority, blocking)
             VALUES (
                 $1,
                 $2,
-                $3
+                $3,
+                NULLIF($4::TEXT, ''),
+                $5,
+                $6,
+                $7
             )
             Returning id
-    ", &[&webhook.name, &webhook.actions, &webhook.url]) {
+    ", &[&webhook.name, &webhook.actions, &webhook.url, &webhook.secret, &webhook.mode, &webhook.priority, &webhook.blocking]) {
         Ok(results) => {
             let id = results.get(0).get(0);
 
@@ -140,16 +218,25 @@ pub fn update(conn: &impl postgres::GenericConnection, id: i64, name: serde_json
         return Err(HecateError::new(400, String::from("Invalid Action"), None));
     }
 
+    if !is_valid_mode(&webhook.mode) {
+        return Err(HecateError::new(400, String::from("Invalid Mode - must be one of 'delta' or 'feature'"), None));
+    }
+
     webhook.id = Some(id);
 
+    //The secret is write only, so it is kept unless a new one is given or it is cleared with an empty string
     match conn.execute("
          UPDATE webhooks
             SET
                 name = $1,
                 actions = $2,
-                url = $3
+                url = $3,
+                secret = CASE WHEN $5::TEXT IS NULL THEN secret ELSE NULLIF($5::TEXT, '') END,
+                mode = $6,
+                priority = $7,
+                blocking = $8
             WHERE id = $4
-    ", &[&webhook.name, &webhook.actions, &webhook.url, &id]) {
+    ", &[&webhook.name, &webhook.actions, &webhook.url, &id, &webhook.secret, &webhook.mode, &webhook.priority, &webhook.blocking]) {
         Ok(_) => Ok(webhook),
         Err(err) => Err(HecateError::from_db(err))
     }
@@ -170,58 +257,272 @@ pub fn is_valid_action(actions: &Vec<String>) -> bool {
     true
 }
 
+pub fn is_valid_mode(mode: &Option<String>) -> bool {
+    match mode {
+        None => true,
+        Some(mode) => mode == "delta" || mode == "feature"
+    }
+}
+
+///
+/// Return the hex encoded HMAC-SHA256 of a body using the webhook secret
+///
+pub fn sign(conn: &impl postgres::GenericConnection, body: &String, secret: &String) -> Result<String, HecateError> {
+    match conn.query("
+        SELECT encode(hmac($1::TEXT, $2::TEXT, 'sha256'), 'hex')
+    ", &[&body, &secret]) {
+        Ok(res) => Ok(res.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Post a single event to a webhook, recording the outcome in the delivery log
+///
+/// If the webhook has a secret the body is signed and the signature is sent in
+/// the `X-Hecate-Signature` header so that subscribers can verify its origin
+///
+pub fn deliver(conn: &impl postgres::GenericConnection, hook: &WebHook, event: &str, body: String) -> Result<Delivery, HecateError> {
+    let id = match hook.id {
+        Some(id) => id,
+        None => { return Err(HecateError::new(500, String::from("Webhook must be saved before delivery"), None)); }
+    };
+
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(err) => { return Err(HecateError::new(500, String::from("Failed to create webhook client"), Some(err.to_string()))); }
+    };
+
+    let mut req = client.post(hook.url.as_str())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Hecate-Event", event);
+
+    match hook.secret {
+        None => (),
+        Some(ref secret) => {
+            req = req.header("X-Hecate-Signature", format!("sha256={}", sign(conn, &body, secret)?));
+        }
+    };
+
+    let start = Instant::now();
+
+    let (status, error) = match req.body(body).send() {
+        Ok(res) => (Some(res.status().as_u16()), None),
+        Err(err) => {
+            println!("WARN: Failed to post to webhook {}: {:?}", hook.url, err);
+            (None, Some(err.to_string()))
+        }
+    };
+
+    let elapsed = start.elapsed();
+    let delivery = Delivery {
+        id: id,
+        event: String::from(event),
+        success: status.map(|status| status >= 200 && status < 300).unwrap_or(false),
+        status: status,
+        latency: (elapsed.as_secs() * 1000 + elapsed.subsec_millis() as u64) as i64,
+        error: error
+    };
+
+    record(conn, delivery)
+}
+
+///
+/// Record the outcome of a delivery in the delivery log
+///
+fn record(conn: &impl postgres::GenericConnection, delivery: Delivery) -> Result<Delivery, HecateError> {
+    match conn.execute("
+        INSERT INTO webhooks_deliveries (webhook, event, status, latency, error, created)
+            VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                $5,
+                NOW()
+            )
+    ", &[&delivery.id, &delivery.event, &delivery.status.map(|status| status as i32), &delivery.latency, &delivery.error]) {
+        Ok(_) => Ok(delivery),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Record an event as undelivered to a webhook as a blocking webhook of a higher
+/// priority failed to receive it
+///
+fn block(conn: &impl postgres::GenericConnection, hook: &WebHook, event: &str, blocker: i64) -> Result<Delivery, HecateError> {
+    let id = match hook.id {
+        Some(id) => id,
+        None => { return Err(HecateError::new(500, String::from("Webhook must be saved before delivery"), None)); }
+    };
+
+    record(conn, Delivery {
+        id: id,
+        event: String::from(event),
+        success: false,
+        status: None,
+        latency: 0,
+        error: Some(format!("Blocked by a failed delivery to webhook {}", blocker))
+    })
+}
+
+///
+/// Serialize the body of an event, stamping it with the current schema version
+///
+pub fn payload(mut body: serde_json::Value) -> String {
+    body["schema_version"] = json!(SCHEMA_VERSION);
+
+    body.to_string()
+}
+
+///
+/// Send a ping event to a webhook to confirm that it is reachable
+///
+pub fn ping(conn: &impl postgres::GenericConnection, id: i64) -> Result<Delivery, HecateError> {
+    let hook = get(conn, id)?;
+
+    let body = payload(json!({
+        "id": id,
+        "type": "webhook.ping"
+    }));
+
+    deliver(conn, &hook, "webhook.ping", body)
+}
+
+///
+/// Build the events for a finalized delta
+///
+/// By default a single `delta.finalize` event summarizes the number of features
+/// affected by each action. Webhooks in `feature` mode are instead sent a
+/// `delta.feature` event for every feature in the delta
+///
+fn delta_events(conn: &impl postgres::GenericConnection, id: &i64, feature_mode: bool) -> Result<Vec<(&'static str, String)>, HecateError> {
+    let actions = delta::actions(conn, id)?;
+
+    if feature_mode {
+        return Ok(actions.iter().map(|(feature, action)| ("delta.feature", payload(json!({
+            "id": id,
+            "type": "delta.feature",
+            "feature": feature,
+            "action": action
+        })))).collect());
+    }
+
+    let mut counts = json!({
+        "create": 0,
+        "modify": 0,
+        "delete": 0,
+        "restore": 0
+    });
+
+    for (_, action) in actions.iter() {
+        counts[action] = json!(counts[action].as_i64().unwrap_or(0) + 1);
+    }
+
+    Ok(vec![("delta.finalize", payload(json!({
+        "id": id,
+        "type": "delta.finalize",
+        "counts": counts,
+        "total": actions.len()
+    })))])
+}
+
 pub fn send(conn: &impl postgres::GenericConnection, task: &worker::TaskType) -> Result<(), HecateError> {
     let action = match task {
         worker::TaskType::Delta(_) => Action::Delta,
         worker::TaskType::User(_) => Action::User,
         worker::TaskType::Style(_) => Action::Style,
-        worker::TaskType::Meta => Action::Meta
+        worker::TaskType::Meta => Action::Meta,
+        worker::TaskType::Analyze => { return Ok(()); }
     };
 
-    for hook in list(conn, action)? {
-        let client = reqwest::Client::new();
+    //Delta events are only built once for each mode however many webhooks are subscribed
+    let mut delta_cache: Vec<(bool, Vec<(&'static str, String)>)> = Vec::new();
+
+    //The blocking webhook whose failed delivery stops delivery to lower priority webhooks
+    let mut blocked: Option<(i64, i64)> = None;
 
-        let body = match task {
+    for hook in list(conn, action)? {
+        let events = match task {
             worker::TaskType::Delta(delta) => {
-                json!({
-                    "id": delta,
-                    "type": "delta"
-                }).to_string()
-            },
-            worker::TaskType::User(user) => {
-                json!({
-                    "id": user,
-                    "type": "user"
-                }).to_string()
-            },
-            worker::TaskType::Style(style) => {
-                    json!({
-                        "id": style,
-                        "type": "style"
-                    }).to_string()
-            },
-            worker::TaskType::Meta => {
-                json!({
-                    "id": null,
-                    "type": "meta"
-                }).to_string()
-            }
-        };
+                let feature_mode = hook.is_feature_mode();
 
-        match client.post(hook.url.as_str())
-            .body(body)
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .send()
-        {
-            Ok(res) => {
-                println!("{:#?}", res);
-                ()
+                match delta_cache.iter().find(|cached| cached.0 == feature_mode) {
+                    Some(cached) => cached.1.clone(),
+                    None => {
+                        let events = delta_events(conn, delta, feature_mode)?;
+                        delta_cache.push((feature_mode, events.clone()));
+                        events
+                    }
+                }
             },
-            Err(err) => {
-                println!("WARN: Failed to post to webhook {}: {:?}", hook.url, err);
-            }
+            worker::TaskType::User(user) => vec![("user", payload(json!({
+                "id": user,
+                "type": "user"
+            })))],
+            worker::TaskType::Style(style) => vec![("style", payload(json!({
+                "id": style,
+                "type": "style"
+            })))],
+            worker::TaskType::Meta => vec![("meta", payload(json!({
+                "id": null,
+                "type": "meta"
+            })))],
+            worker::TaskType::Analyze => Vec::new()
         };
+
+        for (event, body) in events {
+            let delivered = match blocked {
+                Some((blocker, priority)) if hook.priority() < priority => block(conn, &hook, event, blocker).map(|_| false),
+                _ => deliver(conn, &hook, event, body).map(|delivery| delivery.success)
+            };
+
+            //A failed delivery is recorded in the log and only prevents delivery to other webhooks if it is blocking
+            match delivered {
+                Ok(true) => (),
+                Ok(false) => {
+                    if hook.is_blocking() && blocked.is_none() {
+                        blocked = Some((hook.id.unwrap_or(0), hook.priority()));
+                    }
+                },
+                Err(err) => {
+                    println!("WARN: Failed to record delivery to webhook {}: {}", hook.url, err.to_string());
+                }
+            };
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_schema_version() {
+        for body in vec![
+            json!({ "id": 1, "type": "webhook.ping" }),
+            json!({ "id": 1, "type": "delta.feature", "feature": 2, "action": "create" }),
+            json!({ "id": 1, "type": "delta.finalize", "counts": {}, "total": 0 }),
+            json!({ "id": 1, "type": "user" }),
+            json!({ "id": 1, "type": "style" }),
+            json!({ "id": null, "type": "meta" })
+        ] {
+            let emitted: serde_json::Value = serde_json::from_str(&payload(body.clone())).unwrap();
+
+            assert_eq!(emitted["schema_version"], json!(SCHEMA_VERSION));
+            assert_eq!(emitted["type"], body["type"]);
+            assert_eq!(emitted["id"], body["id"]);
+        }
+    }
+
+    #[test]
+    fn payload_ping() {
+        assert_eq!(payload(json!({
+            "id": 1,
+            "type": "webhook.ping"
+        })), r#"{"id":1,"schema_version":1,"type":"webhook.ping"}"#);
+    }
+}
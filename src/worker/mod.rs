@@ -1,14 +1,18 @@
 use crossbeam;
 use postgres;
 use std::thread;
-use crate::{delta, mvt, webhooks};
+use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex};
+use crate::{delta, mvt, options, webhooks};
+use crate::tenant::Scope;
 
 #[derive(Debug,PartialEq)]
 pub enum TaskType {
     Delta(i64),
     User(String),
     Style(i64),
-    Meta
+    Meta,
+    Analyze
 }
 
 #[derive(Debug,PartialEq)]
@@ -24,20 +28,64 @@ impl Task {
     }
 }
 
+///
+/// Fan out the ids of finalized deltas to any number of live subscribers
+///
+#[derive(Clone)]
+pub struct Broadcast {
+    subscribers: Arc<Mutex<Vec<crossbeam::Sender<i64>>>>
+}
+
+impl Broadcast {
+    pub fn new() -> Self {
+        Broadcast {
+            subscribers: Arc::new(Mutex::new(Vec::new()))
+        }
+    }
+
+    pub fn subscribe(&self) -> crossbeam::Receiver<i64> {
+        let (tx, rx) = crossbeam::channel::unbounded();
+
+        match self.subscribers.lock() {
+            Ok(mut subscribers) => subscribers.push(tx),
+            Err(_) => println!("WARN: Failed to subscribe to delta broadcast")
+        };
+
+        rx
+    }
+
+    ///
+    /// Send a delta id to every subscriber, dropping any subscriber
+    /// whose receiving end has since been closed
+    ///
+    pub fn publish(&self, delta_id: i64) {
+        match self.subscribers.lock() {
+            Ok(mut subscribers) => {
+                subscribers.retain(|subscriber| subscriber.send(delta_id).is_ok());
+            },
+            Err(_) => println!("WARN: Failed to publish to delta broadcast")
+        };
+    }
+}
+
 pub struct Worker {
-    sender: crossbeam::Sender<Task>
+    sender: crossbeam::Sender<Task>,
+    broadcast: Broadcast
 }
 
 impl Worker {
-    pub fn new(database: String) -> Self {
+    pub fn new(database: String, schema: options::Database, tiles: options::Tiles, flights: mvt::Flights) -> Self {
         let (tx, rx) = crossbeam::channel::unbounded();
+        let broadcast = Broadcast::new();
 
+        let worker_broadcast = broadcast.clone();
         thread::Builder::new().name(String::from("Hecate Daemon")).spawn(move || {
-            worker(rx, database);
+            worker(rx, database, schema, worker_broadcast, tiles, flights);
         }).unwrap();
 
         Worker {
-            sender: tx
+            sender: tx,
+            broadcast: broadcast
         }
     }
 
@@ -46,44 +94,100 @@ impl Worker {
             println!("WARN: Failed to write task to queue");
         }
     }
+
+    ///
+    /// Obtain a receiver that will be sent the id of each delta as it is finalized
+    ///
+    pub fn subscribe(&self) -> crossbeam::Receiver<i64> {
+        self.broadcast.subscribe()
+    }
 }
 
+///
+/// How often batched deltas are checked for whether their window has passed
+///
+const BATCH_INTERVAL: Duration = Duration::from_secs(1);
+
 ///
 /// Main logic for web worker
 ///
-fn worker(rx: crossbeam::Receiver<Task>, database: String) {
+fn worker(rx: crossbeam::Receiver<Task>, database: String, schema: options::Database, broadcast: Broadcast, tiles: options::Tiles, flights: mvt::Flights) {
     let conn = postgres::Connection::connect(format!("postgres://{}", database), postgres::TlsMode::None).unwrap();
+    schema.search_path(&conn).unwrap();
+
+    let mut closed = Instant::now();
 
     loop {
-        let task = match rx.recv() {
+        //Batched deltas are finalized once their window has passed, their events are only sent then
+        if closed.elapsed() >= BATCH_INTERVAL {
+            match delta::batch::close(&conn) {
+                Ok(batches) => for delta_id in batches {
+                    run(&conn, Task::new(TaskType::Delta(delta_id)), &broadcast, &tiles, &flights);
+                },
+                Err(err) => println!("Daemon: Failed to close batches: {}", err.to_string())
+            };
+
+            closed = Instant::now();
+        }
+
+        let task = match rx.recv_timeout(BATCH_INTERVAL) {
             Ok(task) => task,
+            Err(crossbeam::channel::RecvTimeoutError::Timeout) => continue,
             Err(err) => {
                 println!("Daemon: Failed to obtain task: {}", err);
                 continue;
             }
         };
 
-        match webhooks::send(&conn, &task.job) {
-            Err(err) => println!("HecateError: {:?}", &err.to_string()),
-            _ => ()
-        };
+        run(&conn, task, &broadcast, &tiles, &flights);
+    }
+}
 
-        match task.job {
-            TaskType::Delta(delta_id) => {
-                let tiles = delta::tiles(&conn, &delta_id, 14, 17).unwrap();
+fn run(conn: &postgres::Connection, task: Task, broadcast: &Broadcast, tiles: &options::Tiles, flights: &mvt::Flights) {
+    //Refresh planner statistics after a bulk import, this is not an event webhooks subscribe to
+    if task.job == TaskType::Analyze {
+        if let Err(err) = conn.execute("ANALYZE geo", &[]) {
+            println!("Daemon: Failed to analyze features: {}", err);
+        }
 
-                if tiles.len() == 0 {
-                    continue;
+        return;
+    }
+
+    match task.job {
+        TaskType::Delta(delta_id) => broadcast.publish(delta_id),
+        _ => ()
+    };
+
+    match webhooks::send(conn, &task.job) {
+        Err(err) => println!("HecateError: {:?}", &err.to_string()),
+        _ => ()
+    };
+
+    match task.job {
+        TaskType::Delta(delta_id) => {
+            let affected = delta::tiles(conn, &delta_id, 14, 17).unwrap();
+
+            if affected.len() == 0 {
+                return;
+            }
+
+            //Each tenant has its own tiles, only those of the tenants the delta changed are stale
+            let tenants = match delta::tenants(conn, &delta_id) {
+                Ok(tenants) => tenants,
+                Err(err) => {
+                    println!("Daemon: Failed to obtain delta tenants: {}", err.to_string());
+                    return;
                 }
+            };
 
-                for tile in tiles {
-                    if mvt::get(&conn, tile.2, tile.0 as u32, tile.1 as u32, true).is_err() {
+            for tenant in tenants {
+                for tile in &affected {
+                    if mvt::get(conn, flights, tile.2, tile.0 as u32, tile.1 as u32, true, tiles, &Scope::Tenant(tenant)).is_err() {
                         println!("Daemon: Failed to generate tile: {:?}", tile);
                     }
                 }
-            },
-            _ => ()
-        }
+            }
+        },
+        _ => ()
     }
 }
-
@@ -0,0 +1,143 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn access_level() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Username (nick)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=nick&password=yeaheh&email=nick@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Username (henry)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=henry&password=yeaheh&email=henry@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Username (jane)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=jane&password=yeaheh&email=jane@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Username (ed)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ed&password=yeaheh&email=ed@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Set access levels
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("UPDATE users SET access = 'user' WHERE username = 'nick'", &[]).unwrap();
+            conn.execute("UPDATE users SET access = 'admin' WHERE username = 'henry'", &[]).unwrap();
+            conn.execute("UPDATE users SET access = 'Admin' WHERE username = 'jane'", &[]).unwrap();
+            conn.execute("UPDATE users SET access = 'superadmin' WHERE username = 'ed'", &[]).unwrap();
+        }
+
+        for user in vec!["ingalls", "nick"] { //NULL & user access - user endpoints allowed, admin endpoints denied
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/info")
+                .basic_auth(user, Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let mut resp = client.get("http://localhost:8000/api/webhooks")
+                .basic_auth(user, Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "{\"code\":401,\"reason\":\"You must be logged in to access this resource\",\"status\":\"Unauthorized\"}");
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //admin access - user & admin endpoints allowed
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/info")
+                .basic_auth("henry", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let mut resp = client.get("http://localhost:8000/api/webhooks")
+                .basic_auth("henry", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "[]");
+            assert!(resp.status().is_success());
+        }
+
+        for user in vec!["jane", "ed"] { //Unrecognized access - surfaced as an error rather than a denial
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/webhooks")
+                .basic_auth(user, Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "{\"code\":500,\"reason\":\"Auth Config Error: Unrecognized user access level\",\"status\":\"Internal Server Error\"}");
+            assert_eq!(resp.status().as_u16(), 500);
+
+            let resp = client.get("http://localhost:8000/api/user/info")
+                .basic_auth(user, Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 500);
+
+            //Public endpoints do not depend on the access level
+            let resp = client.get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1")
+                .basic_auth(user, Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn admin_network() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        { //Admin networks
+            let mut server = Command::new("cargo").args(&[
+                "run",
+                "--",
+                "--config", env::current_dir().unwrap().join("tests/fixtures/config.admin.json").to_str().unwrap()
+            ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            { //Create Username (ingalls)
+                let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+                assert_eq!(resp.text().unwrap(), "true");
+                assert!(resp.status().is_success());
+            }
+
+            { //Promote ingalls to admin
+                let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+                conn.execute("UPDATE users SET access = 'admin' WHERE username = 'ingalls'", &[]).unwrap();
+            }
+
+            { //Admin from an allowed network
+                let client = reqwest::Client::new();
+                let resp = client.get("http://localhost:8000/api/webhooks")
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .header("X-Forwarded-For", "192.0.2.1")
+                    .send()
+                    .unwrap();
+                assert!(resp.status().is_success());
+            }
+
+            { //Admin from a denied address within an allowed network
+                let client = reqwest::Client::new();
+                let resp = client.get("http://localhost:8000/api/webhooks")
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .header("X-Forwarded-For", "192.0.2.66")
+                    .send()
+                    .unwrap();
+                assert_eq!(resp.status().as_u16(), 403);
+            }
+
+            { //Admin from a network that is not allowed
+                let client = reqwest::Client::new();
+                let mut resp = client.get("http://localhost:8000/api/webhooks")
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .header("X-Forwarded-For", "203.0.113.9")
+                    .send()
+                    .unwrap();
+                assert_eq!(resp.status().as_u16(), 403);
+
+                let json_body: serde_json::value::Value = resp.json().unwrap();
+                assert_eq!(json_body, json!({
+                    "code": 403,
+                    "reason": "Admin operations are not permitted from this network",
+                    "status": "Forbidden"
+                }));
+            }
+
+            { //Non admin endpoints are unaffected
+                let client = reqwest::Client::new();
+                let resp = client.get("http://localhost:8000/api/user/info")
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .header("X-Forwarded-For", "203.0.113.9")
+                    .send()
+                    .unwrap();
+                assert!(resp.status().is_success());
+            }
+
+            server.kill().unwrap();
+        }
+    }
+}
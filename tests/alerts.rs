@@ -0,0 +1,144 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    ///
+    /// Accept every request sent to the alert target, passing the body
+    /// of each back to the test
+    ///
+    fn serve() -> mpsc::Receiver<String> {
+        let listener = TcpListener::bind("127.0.0.1:8011").unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+
+                let mut req: Vec<u8> = Vec::new();
+                let mut buf = [0; 1024];
+                let body = loop {
+                    let size = stream.read(&mut buf).unwrap();
+                    req.extend_from_slice(&buf[..size]);
+
+                    let req_str = String::from_utf8_lossy(&req).to_string();
+                    if let Some(end) = req_str.find("\r\n\r\n") {
+                        let length: usize = req_str.lines()
+                            .find(|line| line.to_lowercase().starts_with("content-length:"))
+                            .map(|line| line[15..].trim().parse().unwrap())
+                            .unwrap_or(0);
+
+                        if req.len() >= end + 4 + length {
+                            break String::from(&req_str[end + 4..]);
+                        }
+                    }
+
+                    if size == 0 {
+                        break String::new();
+                    }
+                };
+
+                stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+                tx.send(body).unwrap();
+            }
+        });
+
+        rx
+    }
+
+    fn meta(request_id: &str) -> u16 {
+        let client = reqwest::Client::new();
+        let resp = client.get("http://localhost:8000/api/meta")
+            .header("X-Request-Id", request_id)
+            .send()
+            .unwrap();
+
+        resp.status().as_u16()
+    }
+
+    #[test]
+    fn alerts() {
+        let alerts = serve();
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.alerts.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Successful requests are not alerted on
+            assert_eq!(meta("ok"), 200);
+            assert!(alerts.recv_timeout(Duration::from_secs(2)).is_err());
+        }
+
+        { //Break the meta table so that listing it fails with a 500
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("DROP TABLE meta", &[]).unwrap();
+        }
+
+        { //A server error sends a single alert
+            assert_eq!(meta("first"), 500);
+
+            let alert: serde_json::value::Value = serde_json::from_str(&alerts.recv_timeout(Duration::from_secs(5)).unwrap()).unwrap();
+            assert_eq!(alert["type"], json!("error"));
+            assert_eq!(alert["code"], json!(500));
+            assert_eq!(alert["status"], json!("Internal Server Error"));
+            assert_eq!(alert["reason"], json!("Database Error"));
+            assert_eq!(alert["route"], json!("GET /api/meta"));
+            assert_eq!(alert["request_id"], json!("first"));
+            assert_eq!(alert["suppressed"], json!(0));
+            assert!(alert["detail"].as_str().unwrap().contains("meta"));
+        }
+
+        { //A flood of the same error is deduplicated
+            for i in 0..20 {
+                assert_eq!(meta(&format!("flood-{}", i)), 500);
+            }
+
+            assert!(alerts.recv_timeout(Duration::from_secs(2)).is_err());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn attribution() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.attribution.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Attribution").unwrap(), "Hecate contributors, ODbL");
+        }
+
+        { //Feature Query
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Attribution").unwrap(), "Hecate contributors, ODbL");
+            assert_eq!(resp.headers().get("Link").unwrap(), r#"<https://opendatacommons.org/licenses/odbl/>; rel="license""#);
+        }
+
+        { //Feature
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Attribution").unwrap(), "Hecate contributors, ODbL");
+        }
+
+        { //Delta FeatureCollection carries the attribution
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/1").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Attribution").unwrap(), "Hecate contributors, ODbL");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["features"]["type"], json!("FeatureCollection"));
+            assert_eq!(json_body["features"]["attribution"], json!("Hecate contributors, ODbL"));
+            assert_eq!(json_body["features"]["license"], json!("https://opendatacommons.org/licenses/odbl/"));
+        }
+
+        { //Feature version delta FeatureCollection carries the attribution
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/version/1/delta").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["features"]["attribution"], json!("Hecate contributors, ODbL"));
+        }
+
+        { //Non data endpoints do not carry the attribution
+            let resp = reqwest::get("http://localhost:8000/api/users").unwrap();
+            assert!(resp.status().is_success());
+            assert!(resp.headers().get("X-Hecate-Attribution").is_none());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn auth_bound() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--auth", env::current_dir().unwrap().join("tests/fixtures/auth.bound.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Username (nick)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=nick&password=yeaheh&email=nick@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Make ingalls an admin & create the downtown bound
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE username = 'ingalls';
+            ", &[]).unwrap();
+
+            conn.execute("
+                INSERT INTO bounds (name, geom, props) VALUES (
+                    'downtown',
+                    ST_SetSRID(ST_GeomFromGeoJSON('{ \"type\": \"MultiPolygon\", \"coordinates\": [ [ [ [ 0, 0 ], [ 1, 0 ], [ 1, 1 ], [ 0, 1 ], [ 0, 0 ] ] ] ] }'), 4326),
+                    '{}'::JSONB
+                );
+            ", &[]).unwrap();
+        }
+
+        { //Create Point in bound - nick
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0.5, 0.5 ] }
+                }"#)
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point out of bound - nick
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["message"], "Feature must be within bound 'downtown'");
+        }
+
+        { //Modify Point out of bound - nick
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Moving a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+        }
+
+        { //Create Features with one out of bound - nick
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Creating Points",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "124" },
+                        "geometry": { "type": "Point", "coordinates": [ 0.25, 0.25 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "125" },
+                        "geometry": { "type": "Point", "coordinates": [ -1, -1 ] }
+                    }]
+                }"#)
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+        }
+
+        { //Only the in bound feature was created
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT count(*) FROM geo
+            ", &[]).unwrap();
+            let count: i64 = res.get(0).get(0);
+            assert_eq!(count, 1);
+        }
+
+        { //Create Point out of bound - admin bypasses the bound
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let outside: i64 = {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT id FROM geo WHERE ST_Equals(geom, ST_SetSRID(ST_MakePoint(2, 2), 4326))
+            ", &[]).unwrap();
+            res.get(0).get(0)
+        };
+
+        { //Modify out of bound Point into the bound - nick
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(json!({
+                    "id": outside,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Moving a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0.5, 0.5 ] }
+                }).to_string())
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["message"], "Feature must be within bound 'downtown'");
+        }
+
+        { //Delete out of bound Point - nick
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(json!({
+                    "id": outside,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "delete",
+                    "message": "Deleting a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }).to_string())
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["message"], "Feature must be within bound 'downtown'");
+        }
+
+        { //Out of bound Point is unchanged
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT version, ST_AsText(geom) FROM geo WHERE id = $1
+            ", &[&outside]).unwrap();
+            assert_eq!(res.len(), 1);
+
+            let version: i64 = res.get(0).get(0);
+            let geom: String = res.get(0).get(1);
+            assert_eq!(version, 1);
+            assert_eq!(geom, "POINT(2 2)");
+        }
+
+        server.kill().unwrap();
+    }
+}
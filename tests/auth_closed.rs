@@ -172,6 +172,7 @@ mod test {
                 },
                 "user": {
                     "info": "self",
+                    "update": "self",
                     "list": "user",
                     "create": "public",
                     "create_session": "self"
@@ -193,7 +194,8 @@ mod test {
                     "force": "user",
                     "create": "user",
                     "get": "user",
-                    "history": "user"
+                    "history": "user",
+                    "purge": "user"
                 },  
                 "bounds": {
                     "list": "user",
@@ -211,7 +213,13 @@ mod test {
                 },
                 "auth": {
                     "get": "public"
-                }
+                },
+                "index": {
+                    "list": "user",
+                    "create": "user",
+                    "delete": "user"
+                },
+                "require_auth": null
             }));
             assert!(resp.status().is_success());
         }
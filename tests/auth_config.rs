@@ -0,0 +1,148 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn get_config() -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.get("http://localhost:8000/api/auth/config")
+            .basic_auth("ingalls", Some("yeaheh"))
+            .send()
+            .unwrap()
+    }
+
+    fn set_config(body: String) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.put("http://localhost:8000/api/auth/config")
+            .body(body)
+            .basic_auth("ingalls", Some("yeaheh"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .unwrap()
+    }
+
+    #[test]
+    fn auth_config() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Admin Only
+            assert_eq!(get_config().status().as_u16(), 401);
+            assert_eq!(set_config(String::from("{}")).status().as_u16(), 401);
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        let mut config: serde_json::value::Value = get_config().json().unwrap();
+
+        { //Config matches the public auth endpoint
+            let mut resp = reqwest::get("http://localhost:8000/api/auth").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body, config);
+            assert_eq!(config["stats"]["get"], json!("public"));
+        }
+
+        { //Round trip an updated config
+            let original = config.clone();
+
+            config["stats"]["get"] = json!("admin");
+
+            let mut resp = set_config(config.to_string());
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, config);
+
+            let json_body: serde_json::value::Value = get_config().json().unwrap();
+            assert_eq!(json_body, config);
+
+            //New rules apply without a restart
+            let resp = reqwest::get("http://localhost:8000/api/data/stats").unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+
+            let mut resp = set_config(original.to_string());
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, original);
+
+            let resp = reqwest::get("http://localhost:8000/api/data/stats").unwrap();
+            assert_ne!(resp.status().as_u16(), 401);
+
+            config = original;
+        }
+
+        { //Invalid configs are rejected and leave the current config in place
+            let mut invalid = config.clone();
+            invalid["stats"]["get"] = json!("everyone");
+
+            let mut resp = set_config(invalid.to_string());
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Auth Config Error: 'stats::get' must be one of 'public', 'admin', 'user', or null",
+                "status": "Bad Request"
+            }));
+
+            let mut resp = set_config(String::from("{ \"stats\": \"public\" }"));
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Invalid Auth Config JSON"));
+
+            let json_body: serde_json::value::Value = get_config().json().unwrap();
+            assert_eq!(json_body, config);
+        }
+
+        server.kill().unwrap();
+    }
+}
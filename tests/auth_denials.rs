@@ -0,0 +1,162 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json::value::Value;
+
+    #[test]
+    fn auth_denials() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "wilder"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //A user requesting an admin endpoint is denied
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/auth/config?secret=value")
+                .basic_auth("wilder", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Creating a feature without logging in is denied
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Successful requests are not recorded
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1").unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        let denials = |query: &str| {
+            let client = reqwest::Client::new();
+            let mut resp = client.get(&*format!("http://localhost:8000/api/auth/denials{}", query))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut json: Value = resp.json().unwrap();
+
+            //Remove timestamps from the response
+            let list = if json.is_array() {
+                json.as_array_mut().unwrap()
+            } else {
+                json["data"].as_array_mut().unwrap()
+            };
+
+            for denial in list {
+                assert!(denial["created"].is_string());
+                denial.as_object_mut().unwrap().remove("created");
+            }
+
+            json
+        };
+
+        {
+            assert_eq!(denials(""), json!([{
+                "id": 2,
+                "uid": null,
+                "username": null,
+                "scope": "user",
+                "method": "POST",
+                "uri": "/api/data/feature",
+                "status": 401
+            }, {
+                "id": 1,
+                "uid": 2,
+                "username": "wilder",
+                "scope": "admin",
+                "method": "GET",
+                "uri": "/api/auth/config",
+                "status": 401
+            }]));
+        }
+
+        { //Paginated
+            assert_eq!(denials("?limit=1&envelope=true")["meta"]["next"], json!(2));
+
+            let page = denials("?limit=1&offset=2");
+            assert_eq!(page.as_array().unwrap().len(), 1);
+            assert_eq!(page[0]["id"], json!(1));
+        }
+
+        { //The feed is admin only
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/auth/denials")
+                .basic_auth("wilder", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+
+            assert_eq!(denials("?limit=1")[0]["uri"], json!("/api/auth/denials"));
+        }
+
+        server.kill().unwrap();
+    }
+}
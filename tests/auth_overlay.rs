@@ -0,0 +1,119 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json::value::Value;
+
+    #[test]
+    fn auth_overlay() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--auth-overlay", env::current_dir().unwrap().join("tests/fixtures/auth.overlay.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "wilder"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        let create = |username: &str| {
+            let client = reqwest::Client::new();
+            client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth(username, Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap()
+        };
+
+        { //The overlaid behaviour requires an admin
+            let mut resp = create("wilder");
+            assert_eq!(resp.text().unwrap(), "{\"code\":401,\"reason\":\"You must be logged in to access this resource\",\"status\":\"Unauthorized\"}");
+
+            let mut resp = create("ingalls");
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Other behaviours keep their defaults
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let resp = reqwest::get("http://localhost:8000/api/schema").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/auth/config")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            let config: Value = resp.json().unwrap();
+            assert_eq!(config["feature"], json!({
+                "force": "none",
+                "create": "admin",
+                "get": "public",
+                "history": "public",
+                "purge": "admin"
+            }));
+            assert_eq!(config["server"], json!("public"));
+            assert_eq!(config["require_auth"], json!(false));
+        }
+
+        server.kill().unwrap();
+    }
+}
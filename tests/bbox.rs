@@ -0,0 +1,155 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn query(params: &[(&str, &str)]) -> Vec<String> {
+        let client = reqwest::Client::new();
+        let mut resp = client.get("http://localhost:8000/api/data/features")
+            .query(params)
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        let body = resp.text().unwrap();
+        let mut names: Vec<String> = body.trim_end_matches('\u{4}').lines().map(|line| {
+            let feat: serde_json::value::Value = serde_json::from_str(line).unwrap();
+            String::from(feat["properties"]["name"].as_str().unwrap())
+        }).collect();
+
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn bbox() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Basic Creation",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "east" },
+                        "geometry": { "type": "Point", "coordinates": [ 175, 0 ] }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "west" },
+                        "geometry": { "type": "Point", "coordinates": [ -175, 0 ] }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "null island" },
+                        "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Normal BBOX
+            assert_eq!(query(&[("bbox", "-10,-10,10,10")]), vec!["null island"]);
+            assert_eq!(query(&[("bbox", "170,-10,180,10")]), vec!["east"]);
+        }
+
+        { //Antimeridian BBOX returns features on both sides
+            assert_eq!(query(&[("bbox", "170,-10,-170,10")]), vec!["east", "west"]);
+            assert_eq!(query(&[("filter", "INTERSECTS(170, -10, -170, 10)")]), vec!["east", "west"]);
+            assert_eq!(query(&[("bbox", "170,-10,-170,10"), ("prop", "name=west")]), vec!["west"]);
+
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/features/count")
+                .query(&[("bbox", "170,-10,-170,10")])
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({ "count": 2 }));
+        }
+
+        { //Out of range BBOX
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=170,-10,190,10").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "BBOX maxX value must be a number between -180 and 180",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Malformed BBOX
+            for bbox in vec!["1,2,3", "a,b,c,d", "1,2,3,4,5"] {
+                let mut resp = reqwest::get(&*format!("http://localhost:8000/api/data/features?bbox={}", bbox)).unwrap();
+                assert_eq!(resp.status().as_u16(), 400);
+
+                let json_body: serde_json::value::Value = resp.json().unwrap();
+                assert_eq!(json_body, json!({
+                    "code": 400,
+                    "reason": "Invalid BBOX",
+                    "status": "Bad Request"
+                }));
+            }
+        }
+
+        server.kill().unwrap();
+    }
+}
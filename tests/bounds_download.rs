@@ -0,0 +1,183 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate rusqlite;
+extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn bounds_download() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        for (name, coords) in &[("Inside", "-77.01210021972656, 38.925763232374514"), ("Inside", "-77.05, 38.9"), ("Outside", "-76.5, 39.5")] {
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point {name} of Bounds",
+                    "properties": {{ "name": "{name}" }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {coords} ] }}
+                }}"#, name = name, coords = coords))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Set DC Bounds
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/bounds/dc")
+                .body(r#"{
+                    "type": "Feature",
+                    "properties": {},
+                    "geometry": { "type": "MultiPolygon", "coordinates": [ [ [ [ -77.13363, 38.83542 ], [ -76.96403, 38.83542 ], [ -76.96403, 38.97489 ], [ -77.13363, 38.97489 ], [ -77.13363, 38.83542 ] ] ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Download requires clone::get
+            let resp = reqwest::get("http://localhost:8000/api/data/bounds/dc/download?format=gpkg").unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Unknown bound
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/bounds/nowhere/download?format=gpkg")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Invalid format & srid
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/bounds/dc/download?format=shp")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), r#"{"code":400,"reason":"Invalid Format - must be one of 'geojson' or 'gpkg'","status":"Bad Request"}"#);
+
+            let resp = client.get("http://localhost:8000/api/data/bounds/dc/download?srid=1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Download GeoJSON
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/bounds/dc/download?srid=3857")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("Content-Disposition").unwrap(), "attachment; filename=\"dc.geojson\"");
+
+            let body = resp.text().unwrap();
+            let lines: Vec<&str> = body.trim_end_matches('\u{0004}').lines().collect();
+            assert_eq!(lines.len(), 2);
+
+            //Web mercator coordinates are in metres
+            let feat: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+            assert!(feat["geometry"]["coordinates"][0].as_f64().unwrap() < -8000000.0);
+        }
+
+        { //Download GeoPackage
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/bounds/dc/download?format=gpkg&srid=3857")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("Content-Type").unwrap(), "application/geopackage+sqlite3");
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.copy_to(&mut body).unwrap();
+
+            let path = env::temp_dir().join("hecate-bounds-download.gpkg");
+            File::create(&path).unwrap().write_all(&body).unwrap();
+
+            let gpkg = rusqlite::Connection::open(&path).unwrap();
+
+            let count: i64 = gpkg.query_row("SELECT count(*) FROM features", rusqlite::NO_PARAMS, |row| row.get(0)).unwrap();
+            assert_eq!(count, 2);
+
+            let outside: i64 = gpkg.query_row("SELECT count(*) FROM features WHERE properties LIKE '%Outside%'", rusqlite::NO_PARAMS, |row| row.get(0)).unwrap();
+            assert_eq!(outside, 0);
+
+            let srid: i64 = gpkg.query_row("SELECT srs_id FROM gpkg_geometry_columns WHERE table_name = 'features'", rusqlite::NO_PARAMS, |row| row.get(0)).unwrap();
+            assert_eq!(srid, 3857);
+
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        server.kill().unwrap();
+    }
+}
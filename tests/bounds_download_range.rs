@@ -0,0 +1,244 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn bounds_download_range() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        for (name, coords) in &[("Inside", "-77.01210021972656, 38.925763232374514"), ("Inside", "-77.05, 38.9"), ("Outside", "-76.5, 39.5")] {
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point {name} of Bounds",
+                    "properties": {{ "name": "{name}" }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {coords} ] }}
+                }}"#, name = name, coords = coords))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Set DC Bounds
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/bounds/dc")
+                .body(r#"{
+                    "type": "Feature",
+                    "properties": {},
+                    "geometry": { "type": "MultiPolygon", "coordinates": [ [ [ [ -77.13363, 38.83542 ], [ -76.96403, 38.83542 ], [ -76.96403, 38.97489 ], [ -77.13363, 38.97489 ], [ -77.13363, 38.83542 ] ] ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let download = |query: &str, range: Option<&str>| -> (u16, reqwest::header::HeaderMap, Vec<u8>) {
+            let client = reqwest::Client::new();
+            let mut req = client.get(&*format!("http://localhost:8000/api/data/bounds/dc/download{}", query))
+                .basic_auth("ingalls", Some("yeaheh"));
+
+            if let Some(range) = range {
+                req = req.header(reqwest::header::RANGE, range);
+            }
+
+            let mut resp = req.send().unwrap();
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.copy_to(&mut body).unwrap();
+
+            (resp.status().as_u16(), resp.headers().clone(), body)
+        };
+
+        let resume = |query: &str, range: &str, if_range: &str| -> (u16, reqwest::header::HeaderMap, Vec<u8>) {
+            let client = reqwest::Client::new();
+            let mut resp = client.get(&*format!("http://localhost:8000/api/data/bounds/dc/download{}", query))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::RANGE, range)
+                .header(reqwest::header::IF_RANGE, if_range)
+                .send()
+                .unwrap();
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.copy_to(&mut body).unwrap();
+
+            (resp.status().as_u16(), resp.headers().clone(), body)
+        };
+
+        for query in &["", "?format=gpkg"] { //Resume a download across two requests
+            let (status, headers, full) = download(query, None);
+            assert_eq!(status, 200);
+            let etag = String::from(headers.get("ETag").unwrap().to_str().unwrap());
+
+            let (status, headers, mut joined) = download(query, Some("bytes=0-19"));
+            assert_eq!(status, 206);
+            assert_eq!(headers.get("ETag").unwrap().to_str().unwrap(), etag);
+
+            let (status, headers, rest) = resume(query, "bytes=20-", &*etag);
+            assert_eq!(status, 206);
+            assert_eq!(headers.get("ETag").unwrap().to_str().unwrap(), etag);
+            assert_eq!(headers.get("Content-Range").unwrap().to_str().unwrap(), format!("bytes 20-{}/{}", full.len() - 1, full.len()));
+
+            joined.extend(rest);
+            assert_eq!(joined, full);
+
+            //A validator of an earlier export returns the whole file
+            let (status, headers, body) = resume(query, "bytes=20-", "\"0123456789abcdef\"");
+            assert_eq!(status, 200);
+            assert!(headers.get("Content-Range").is_none());
+            assert_eq!(body, full);
+        }
+
+        { //GeoJSON byte ranges
+            let (status, headers, full) = download("", None);
+            assert_eq!(status, 200);
+            assert_eq!(headers.get("Accept-Ranges").unwrap(), "bytes");
+            assert!(full.len() > 50);
+
+            let (status, headers, body) = download("", Some("bytes=10-49"));
+            assert_eq!(status, 206);
+            assert_eq!(headers.get("Accept-Ranges").unwrap(), "bytes");
+            assert_eq!(headers.get("Content-Range").unwrap().to_str().unwrap(), format!("bytes 10-49/{}", full.len()));
+            assert_eq!(headers.get("Content-Length").unwrap().to_str().unwrap(), "40");
+            assert_eq!(body, full[10..50].to_vec());
+
+            //Resume from an offset
+            let (status, headers, body) = download("", Some("bytes=50-"));
+            assert_eq!(status, 206);
+            assert_eq!(headers.get("Content-Range").unwrap().to_str().unwrap(), format!("bytes 50-{}/{}", full.len() - 1, full.len()));
+            assert_eq!(body, full[50..].to_vec());
+
+            let (status, _, body) = download("", Some("bytes=-20"));
+            assert_eq!(status, 206);
+            assert_eq!(body, full[full.len() - 20..].to_vec());
+
+            //Several ranges are not supported and return the whole file
+            let (status, _, body) = download("", Some("bytes=0-9,20-29"));
+            assert_eq!(status, 200);
+            assert_eq!(body, full);
+        }
+
+        { //GeoPackage byte ranges
+            let (status, headers, full) = download("?format=gpkg", None);
+            assert_eq!(status, 200);
+            assert_eq!(headers.get("Accept-Ranges").unwrap(), "bytes");
+
+            let (status, headers, body) = download("?format=gpkg", Some("bytes=0-15"));
+            assert_eq!(status, 206);
+            assert_eq!(headers.get("Content-Type").unwrap(), "application/geopackage+sqlite3");
+            assert_eq!(headers.get("Content-Range").unwrap().to_str().unwrap(), format!("bytes 0-15/{}", full.len()));
+            assert_eq!(body, b"SQLite format 3\0".to_vec());
+
+            let (status, headers, body) = download("?format=gpkg", Some(&*format!("bytes={}-", full.len())));
+            assert_eq!(status, 416);
+            assert_eq!(headers.get("Content-Range").unwrap().to_str().unwrap(), format!("bytes */{}", full.len()));
+            assert_eq!(String::from_utf8(body).unwrap(), r#"{"code":416,"reason":"Range Not Satisfiable","status":"Range Not Satisfiable"}"#);
+        }
+
+        { //The validator changes with the data
+            let (_, headers, _) = download("", None);
+            let etag = String::from(headers.get("ETag").unwrap().to_str().unwrap());
+
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point Inside of Bounds",
+                    "properties": { "name": "Inside" },
+                    "geometry": { "type": "Point", "coordinates": [ -77.1, 38.9 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+
+            let (status, headers, full) = resume("", "bytes=20-", &*etag);
+            assert_eq!(status, 200);
+            assert_ne!(headers.get("ETag").unwrap().to_str().unwrap(), etag);
+            assert_eq!(full, download("", None).2);
+        }
+
+        { //The validator changes with an edit that does not create a new delta, ie: one appended to an open batch
+            let (_, headers, _) = download("", None);
+            let etag = String::from(headers.get("ETag").unwrap().to_str().unwrap());
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE geo
+                    SET
+                        version = version + 1,
+                        props = '{ \"name\": \"Renamed\" }'::JSONB
+                    WHERE id = 1
+            ", &[]).unwrap();
+
+            let (status, headers, full) = resume("", "bytes=20-", &*etag);
+            assert_eq!(status, 200);
+            assert_ne!(headers.get("ETag").unwrap().to_str().unwrap(), etag);
+            assert_eq!(full, download("", None).2);
+        }
+
+        server.kill().unwrap();
+    }
+}
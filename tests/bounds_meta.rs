@@ -0,0 +1,239 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn bounds_meta() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create Point Inside of Bounds
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point Inside of Bounds",
+                    "properties": { "indc": true },
+                    "geometry": { "type": "Point", "coordinates": [ -77.01210021972656,38.925763232374514 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Set DC Bounds
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/bounds/dc")
+                .body(r#"{
+                    "type": "Feature",
+                    "properties": {},
+                    "geometry": { "type": "MultiPolygon", "coordinates": [ [ [ [ -77.13363, 38.83542 ], [ -76.96403, 38.83542 ], [ -76.96403, 38.97489 ], [ -77.13363, 38.97489 ], [ -77.13363, 38.83542 ] ] ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Set small DC Bounds
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/bounds/dc_small")
+                .body(r#"{
+                    "type": "Feature",
+                    "properties": {},
+                    "geometry": { "type": "MultiPolygon", "coordinates": [ [ [ [ -77.02, 38.92 ], [ -77.00, 38.92 ], [ -77.00, 38.93 ], [ -77.02, 38.93 ], [ -77.02, 38.92 ] ] ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Set MD Bounds
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/bounds/md")
+                .body(r#"{
+                    "type": "Feature",
+                    "properties": {},
+                    "geometry": { "type": "MultiPolygon", "coordinates": [ [ [ [ -79.48, 37.91 ], [ -75.04, 37.91 ], [ -75.04, 39.72 ], [ -79.48, 39.72 ], [ -79.48, 37.91 ] ] ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //List Bounds w/ Meta
+            let mut resp = reqwest::get("http://localhost:8000/api/data/bounds?meta=true").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let list = json_body.as_array().unwrap();
+
+            assert_eq!(list.len(), 3);
+            assert_eq!(list[0]["name"], json!("dc"));
+            assert_eq!(list[1]["name"], json!("dc_small"));
+            assert_eq!(list[2]["name"], json!("md"));
+
+            for bound in list {
+                assert!(bound["area"].as_f64().unwrap() > 0.0);
+                assert_eq!(bound["features"], json!(1));
+                assert!(bound["counted"].is_string());
+            }
+
+            assert!(list[0]["area"].as_f64().unwrap() > list[1]["area"].as_f64().unwrap());
+        }
+
+        { //List Bounds w/ Meta & Prefix Filter
+            let mut resp = reqwest::get("http://localhost:8000/api/data/bounds?meta=true&filter=dc").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let names: Vec<serde_json::Value> = json_body.as_array().unwrap().iter().map(|bound| bound["name"].clone()).collect();
+
+            assert_eq!(names, vec![json!("dc"), json!("dc_small")]);
+        }
+
+        { //List Bounds sorted by Area
+            let mut resp = reqwest::get("http://localhost:8000/api/data/bounds?sort=area").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!(["md", "dc", "dc_small"]));
+        }
+
+        { //List Bounds sorted by Area w/ Prefix Filter & Limit
+            let mut resp = reqwest::get("http://localhost:8000/api/data/bounds?sort=area&filter=dc&limit=1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!(["dc"]));
+        }
+
+        { //List Bounds w/ Invalid Sort
+            let mut resp = reqwest::get("http://localhost:8000/api/data/bounds?sort=size").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Sort - must be one of 'name' or 'area'",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Create Point Outside of DC Bounds
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point Outside of DC Bounds",
+                    "properties": { "indc": false },
+                    "geometry": { "type": "Point", "coordinates": [ -76.61, 39.29 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Regenerate Stats & Cached Counts
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/stats/regen")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Cached Counts are refreshed
+            let mut resp = reqwest::get("http://localhost:8000/api/data/bounds?meta=true&sort=area").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let counts: Vec<(serde_json::Value, serde_json::Value)> = json_body.as_array().unwrap().iter().map(|bound| {
+                (bound["name"].clone(), bound["features"].clone())
+            }).collect();
+
+            assert_eq!(counts, vec![
+                (json!("md"), json!(2)),
+                (json!("dc"), json!(1)),
+                (json!("dc_small"), json!(1))
+            ]);
+        }
+
+        server.kill().unwrap();
+    }
+}
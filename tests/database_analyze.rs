@@ -0,0 +1,115 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn database_analyze() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.database.analyze.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        //A new connection is used for each check as statistics are cached for the length of a transaction
+        let last_analyze = || -> bool {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let rows = conn.query("
+                SELECT
+                    last_analyze IS NOT NULL
+                FROM
+                    pg_stat_user_tables
+                WHERE
+                    relname = 'geo'
+            ", &[]).unwrap();
+
+            rows.get(0).get(0)
+        };
+
+        let upload = |count: i64| {
+            let features: Vec<serde_json::Value> = (0..count).map(|i| json!({
+                "type": "Feature",
+                "action": "create",
+                "properties": { "number": i },
+                "geometry": { "type": "Point", "coordinates": [ i, 0 ] }
+            })).collect();
+
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(json!({
+                    "type": "FeatureCollection",
+                    "message": "Import Points",
+                    "features": features
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        };
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Import below the threshold does not analyze
+            upload(4);
+            thread::sleep(Duration::from_secs(2));
+
+            assert!(!last_analyze());
+        }
+
+        { //Import at the threshold analyzes once committed
+            upload(5);
+            thread::sleep(Duration::from_secs(2));
+
+            assert!(last_analyze());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use std::io::BufReader;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::sync::mpsc;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn delta_batch() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.batch.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let (tx, rx) = mpsc::channel();
+
+        { //Open Stream
+            let resp = reqwest::Client::builder()
+                .timeout(None)
+                .build()
+                .unwrap()
+                .get("http://localhost:8000/api/delta/stream")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            thread::spawn(move || {
+                for line in BufReader::new(resp).lines() {
+                    if tx.send(line.unwrap()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "1" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point within the window
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "id": 1,
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Modifying a Point",
+                    "properties": { "number": "2" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point again within the window
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "id": 1,
+                    "version": 2,
+                    "action": "modify",
+                    "message": "Modifying a Point again",
+                    "properties": { "number": "3" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Three edits share a single delta
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT id, affected, jsonb_array_length(features->'features')::BIGINT FROM deltas ORDER BY id
+            ", &[]).unwrap();
+            let deltas: Vec<(i64, Vec<i64>, i64)> = res.iter().map(|row| (row.get(0), row.get(1), row.get(2))).collect();
+            assert_eq!(deltas, vec![(1, vec![1, 1, 1], 3)]);
+        }
+
+        { //An open batch is not finalized
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("SELECT finalized FROM deltas WHERE id = 1", &[]).unwrap();
+            let finalized: bool = res.get(0).get(0);
+            assert!(!finalized);
+        }
+
+        thread::sleep(Duration::from_secs(3));
+
+        { //The batch is finalized once the window has passed
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("SELECT finalized FROM deltas WHERE id = 1", &[]).unwrap();
+            let finalized: bool = res.get(0).get(0);
+            assert!(finalized);
+        }
+
+        { //Delta joins the messages of its edits
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["props"]["message"], "Creating a Point; Modifying a Point; Modifying a Point again");
+        }
+
+        { //Every edit is kept in the history of the feature
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json: serde_json::Value = resp.json().unwrap();
+            let mut numbers: Vec<String> = json.as_array().unwrap().iter().map(|entry| {
+                assert_eq!(entry["id"], 1);
+                String::from(entry["feat"]["properties"]["number"].as_str().unwrap())
+            }).collect();
+            numbers.sort();
+            assert_eq!(numbers, vec!["1", "2", "3"]);
+        }
+
+        { //Modify Point after the window
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "id": 1,
+                    "version": 3,
+                    "action": "modify",
+                    "message": "Modifying a Point later",
+                    "properties": { "number": "4" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Edit after the window starts a new delta
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT id, affected, jsonb_array_length(features->'features')::BIGINT FROM deltas ORDER BY id
+            ", &[]).unwrap();
+            let deltas: Vec<(i64, Vec<i64>, i64)> = res.iter().map(|row| (row.get(0), row.get(1), row.get(2))).collect();
+            assert_eq!(deltas, vec![(1, vec![1, 1, 1], 3), (2, vec![1], 1)]);
+        }
+
+        { //Invalid batch_window is rejected
+            let client = reqwest::Client::new();
+            let resp = client.patch("http://localhost:8000/api/user/1")
+                .body(r#"{ "meta": { "batch_window": "never" } }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Disable batching for the user
+            let client = reqwest::Client::new();
+            let resp = client.patch("http://localhost:8000/api/user/1")
+                .body(r#"{ "meta": { "batch_window": 0 } }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point without batching
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "id": 1,
+                    "version": 4,
+                    "action": "modify",
+                    "message": "Modifying a Point unbatched",
+                    "properties": { "number": "5" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point again without batching
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "id": 1,
+                    "version": 5,
+                    "action": "modify",
+                    "message": "Modifying a Point unbatched again",
+                    "properties": { "number": "6" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Each edit of the user has its own delta
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT id, affected, jsonb_array_length(features->'features')::BIGINT FROM deltas ORDER BY id
+            ", &[]).unwrap();
+            let deltas: Vec<(i64, Vec<i64>, i64)> = res.iter().map(|row| (row.get(0), row.get(1), row.get(2))).collect();
+            assert_eq!(deltas, vec![(1, vec![1, 1, 1], 3), (2, vec![1], 1), (3, vec![1], 1), (4, vec![1], 1)]);
+        }
+
+        { //Each delta is sent to the stream once, a batch only when it closes
+            let mut ids: Vec<String> = Vec::new();
+            while ids.len() < 4 {
+                let line = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+                if line.starts_with("id: ") {
+                    ids.push(line);
+                }
+            }
+
+            while let Ok(line) = rx.recv_timeout(Duration::from_secs(3)) {
+                assert!(!line.starts_with("id: "), "not ok - {} was sent again", line);
+            }
+
+            ids.sort();
+            assert_eq!(ids, vec!["id: 1", "id: 2", "id: 3", "id: 4"]);
+        }
+
+        server.kill().unwrap();
+    }
+}
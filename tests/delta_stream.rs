@@ -0,0 +1,114 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::io::BufReader;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::sync::mpsc;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn delta_stream() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let (tx, rx) = mpsc::channel();
+
+        { //Open Stream
+            let resp = reqwest::Client::builder()
+                .timeout(None)
+                .build()
+                .unwrap()
+                .get("http://localhost:8000/api/delta/stream")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get(reqwest::header::CONTENT_TYPE).unwrap(), "text/event-stream");
+
+            thread::spawn(move || {
+                for line in BufReader::new(resp).lines() {
+                    if tx.send(line.unwrap()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Streamed Creation",
+                    "properties": {
+                        "shop": true
+                    },
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [ 1,1 ]
+                    }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Event Arrives
+            assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "id: 1");
+            assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "event: delta");
+
+            let data = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(data.starts_with("data: "));
+            assert!(data.contains(r#""id":1"#));
+            assert!(data.contains(r#""message":"Streamed Creation""#));
+            assert!(data.contains(r#""username":"ingalls""#));
+        }
+
+        server.kill().unwrap();
+    }
+}
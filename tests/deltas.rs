@@ -337,6 +337,34 @@ mod test {
             assert!(resp.status().is_success());
         }
 
+        { //Test fields param
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?limit=2&fields=id,username").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body, json!([{
+                "id": 3,
+                "username": "ingalls"
+            }, {
+                "id": 2,
+                "username": "ingalls"
+            }]));
+        }
+
+        { //Test unknown field
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?fields=id,features").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Field - 'features' must be one of id, uid, username, created, props",
+                "status": "Bad Request"
+            }));
+        }
+
         { //Test Start & End
             let mut resp = reqwest::get("http://localhost:8000/api/deltas").unwrap();
             let json_body: serde_json::value::Value = resp.json().unwrap();
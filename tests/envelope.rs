@@ -0,0 +1,275 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn envelope() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "ingalls2", "ingalls3"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        for i in 0..3 { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {{ "number": {} }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {}, 0 ] }}
+                }}"#, i, i))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Create Style
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/style")
+                .body(r#"{
+                    "name": "Awesome Style",
+                    "style": "I am a style"
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "1");
+        }
+
+        { //Lists are not wrapped by default
+            let mut resp = reqwest::get("http://localhost:8000/api/users").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 3);
+        }
+
+        { //User list envelope
+            let mut resp = reqwest::get("http://localhost:8000/api/users?envelope=true").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"].as_array().unwrap().len(), 3);
+            assert_eq!(json_body["meta"], json!({
+                "total": 3,
+                "limit": 100,
+                "next": null,
+                "prev": null
+            }));
+        }
+
+        { //A full page has no total
+            let mut resp = reqwest::get("http://localhost:8000/api/users?envelope=true&limit=2").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"].as_array().unwrap().len(), 2);
+            assert_eq!(json_body["meta"], json!({
+                "total": null,
+                "limit": 2,
+                "next": null,
+                "prev": null
+            }));
+        }
+
+        { //Style list envelope
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/styles/1?envelope=true")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"].as_array().unwrap().len(), 1);
+            assert_eq!(json_body["data"][0]["name"], json!("Awesome Style"));
+            assert_eq!(json_body["meta"], json!({
+                "total": 1,
+                "limit": null,
+                "next": null,
+                "prev": null
+            }));
+        }
+
+        { //Public style list envelope
+            let mut resp = reqwest::get("http://localhost:8000/api/styles?envelope=true").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "data": [],
+                "meta": {
+                    "total": 0,
+                    "limit": null,
+                    "next": null,
+                    "prev": null
+                }
+            }));
+        }
+
+        { //Bounds list envelope
+            let mut resp = reqwest::get("http://localhost:8000/api/data/bounds?envelope=true").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "data": [],
+                "meta": {
+                    "total": 0,
+                    "limit": null,
+                    "next": null,
+                    "prev": null
+                }
+            }));
+        }
+
+        { //First page of deltas
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?envelope=true&limit=2").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"][0]["id"], json!(3));
+            assert_eq!(json_body["data"][1]["id"], json!(2));
+            assert_eq!(json_body["meta"], json!({
+                "total": null,
+                "limit": 2,
+                "next": 2,
+                "prev": null
+            }));
+        }
+
+        { //Last page of deltas
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?envelope=true&limit=2&offset=2").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"].as_array().unwrap().len(), 1);
+            assert_eq!(json_body["data"][0]["id"], json!(1));
+            assert_eq!(json_body["meta"], json!({
+                "total": null,
+                "limit": 2,
+                "next": null,
+                "prev": 4
+            }));
+        }
+
+        { //Previous cursor returns the first page of deltas
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?limit=2&offset=4").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0]["id"], json!(3));
+            assert_eq!(json_body[1]["id"], json!(2));
+        }
+
+        { //Feature query envelope
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1&envelope=true").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"].as_array().unwrap().len(), 3);
+            assert_eq!(json_body["data"][0]["type"], json!("Feature"));
+            assert_eq!(json_body["meta"], json!({
+                "total": 3,
+                "limit": null,
+                "next": null,
+                "prev": null
+            }));
+        }
+
+        { //First page of ordered features
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1&order=id&limit=2&envelope=true").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "2");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"][0]["id"], json!(1));
+            assert_eq!(json_body["data"][1]["id"], json!(2));
+            assert_eq!(json_body["meta"], json!({
+                "total": null,
+                "limit": 2,
+                "next": 2,
+                "prev": null
+            }));
+        }
+
+        { //Next page of ordered features
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1&order=id&limit=2&after=2&envelope=true").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["data"].as_array().unwrap().len(), 1);
+            assert_eq!(json_body["data"][0]["id"], json!(3));
+            assert_eq!(json_body["meta"], json!({
+                "total": null,
+                "limit": 2,
+                "next": null,
+                "prev": null
+            }));
+        }
+
+        { //Envelope cannot be used with topojson
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1&format=topojson&envelope=true").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "envelope param can only be used with geojson format",
+                "status": "Bad Request"
+            }));
+        }
+
+        server.kill().unwrap();
+    }
+}
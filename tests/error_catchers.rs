@@ -0,0 +1,96 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn error_catchers() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Wrong method on a route returns a 405 listing the allowed methods
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/stats")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 405);
+            assert_eq!(resp.headers().get("Allow").unwrap(), "GET");
+            assert_eq!(resp.headers().get(reqwest::header::CONTENT_TYPE).unwrap(), "application/json");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 405,
+                "status": "Method Not Allowed",
+                "reason": "Method Not Allowed - POST is not supported on this resource",
+                "allowed": [ "GET" ]
+            }));
+        }
+
+        { //Every method of a route with a dynamic segment is listed
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/style/1")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 405);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let mut allowed: Vec<String> = json_body["allowed"].as_array().unwrap().iter().map(|method| String::from(method.as_str().unwrap())).collect();
+            allowed.sort();
+            assert_eq!(allowed, vec!["DELETE", "GET", "PATCH"]);
+        }
+
+        { //Unknown paths return a 404
+            let mut resp = reqwest::get("http://localhost:8000/api/nothing/here").unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 404,
+                "status": "Not Found",
+                "reason": "Resource Not Found"
+            }));
+        }
+
+        server.kill().unwrap();
+    }
+}
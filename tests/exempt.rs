@@ -0,0 +1,135 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn exempt() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--auth", env::current_dir().unwrap().join("tests/fixtures/auth.closed.json").to_str().unwrap(),
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.exempt.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Exempt route skips the auth rules
+            let mut resp = reqwest::get("http://localhost:8000/api/schema").unwrap();
+            assert_eq!(resp.text().unwrap(), "{\"code\":404,\"reason\":\"No schema Validation Enforced\",\"status\":\"Not Found\"}");
+            assert_eq!(resp.headers().get("Access-Control-Allow-Origin").unwrap(), "*");
+        }
+
+        { //Preflight of an exempt route
+            let client = reqwest::Client::new();
+            let resp = client.request(reqwest::Method::OPTIONS, "http://localhost:8000/api/schema")
+                .header("Origin", "https://example.com")
+                .header("Access-Control-Request-Method", "GET")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 204);
+            assert_eq!(resp.headers().get("Access-Control-Allow-Origin").unwrap(), "*");
+            assert_eq!(resp.headers().get("Access-Control-Allow-Methods").unwrap(), "GET");
+        }
+
+        { //Exempt route still requires an admin
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "name": "number", "field": "number" }"#)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "{\"code\":401,\"reason\":\"You must be logged in to access this resource\",\"status\":\"Unauthorized\"}");
+
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "name": "number", "field": "number" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "{\"code\":401,\"reason\":\"You must be logged in to access this resource\",\"status\":\"Unauthorized\"}");
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Data route still enforces the auth rules
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert_eq!(resp.text().unwrap(), "{\"code\":401,\"reason\":\"You must be logged in to access this resource\",\"status\":\"Unauthorized\"}");
+            assert!(resp.headers().get("Access-Control-Allow-Origin").is_none());
+        }
+
+        {
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/feature/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -244,16 +244,9 @@ mod test {
             assert!(resp.status().is_client_error());
         }
 
-        { // Check bbox - minX > maxX 
-            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=107.578125,-30.600094,56.162109,46.377254").unwrap();
-            let json_body: serde_json::value::Value = resp.json().unwrap();
-
-            assert_eq!(json_body, json!({
-                "code": 400,
-                "reason": "BBOX minX value cannot be greater than maxX value",
-                "status": "Bad Request"
-            }));
-            assert!(resp.status().is_client_error());
+        { // Check bbox - minX > maxX crosses the antimeridian
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=107.578125,-30.600094,56.162109,46.377254").unwrap();
+            assert!(resp.status().is_success());
         }
 
         { // Check bbox - minY > maxY
@@ -0,0 +1,150 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use]
+extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_blame() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Username (wilder)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=wilder&password=yeaheh&email=wilder@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //ingalls creates the feature
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create a cafe",
+                    "properties": { "name": "Walnut Grove", "amenity": "cafe" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //wilder renames the feature and adds a cuisine
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Rename the cafe",
+                    "properties": { "name": "Plum Creek", "amenity": "cafe", "cuisine": "coffee_shop" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("wilder", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //ingalls adds opening hours without touching the other properties
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 2,
+                    "action": "modify",
+                    "message": "Add opening hours",
+                    "properties": { "name": "Plum Creek", "amenity": "cafe", "cuisine": "coffee_shop", "opening_hours": "24/7" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Each property maps to the version that set its current value
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/blame").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut json: serde_json::Value = resp.json().unwrap();
+
+            for (_, version) in json.as_object_mut().unwrap().iter_mut() {
+                assert!(version["timestamp"].is_string());
+                version.as_object_mut().unwrap().remove("timestamp");
+            }
+
+            assert_eq!(json, json!({
+                "name": { "delta": 2, "uid": 2, "username": "wilder" },
+                "amenity": { "delta": 1, "uid": 1, "username": "ingalls" },
+                "cuisine": { "delta": 2, "uid": 2, "username": "wilder" },
+                "opening_hours": { "delta": 3, "uid": 1, "username": "ingalls" }
+            }));
+        }
+
+        { //Feature without history
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/2/blame").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
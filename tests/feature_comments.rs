@@ -0,0 +1,207 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn comment(username: &str, id: i64, comment: &str) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.post(&*format!("http://localhost:8000/api/data/feature/{}/comments", id))
+            .basic_auth(username, Some("yeaheh"))
+            .body(json!({ "comment": comment }).to_string())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .unwrap()
+    }
+
+    fn delete(username: &str, comment: i64) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.delete(&*format!("http://localhost:8000/api/data/feature/1/comments/{}", comment))
+            .basic_auth(username, Some("yeaheh"))
+            .send()
+            .unwrap()
+    }
+
+    fn list() -> serde_json::value::Value {
+        let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/comments").unwrap();
+        assert!(resp.status().is_success());
+
+        resp.json().unwrap()
+    }
+
+    #[test]
+    fn feature_comments() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Admin
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create User
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls2&password=yeaheh&email=ingalls2@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create User
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls3&password=yeaheh&email=ingalls3@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "building": true },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Post Comments
+            let mut resp = comment("ingalls2", 1, "Is this building still here?");
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(1));
+            assert_eq!(json_body["uid"], json!(2));
+            assert_eq!(json_body["username"], json!("ingalls2"));
+            assert_eq!(json_body["comment"], json!("Is this building still here?"));
+            assert_eq!(json_body["resolved"], json!(null));
+            assert!(json_body["created"].is_string());
+
+            assert!(comment("ingalls3", 1, "It was demolished last year").status().is_success());
+            assert!(comment("ingalls2", 1, "Thanks, I will remove it").status().is_success());
+        }
+
+        { //Comments cannot be posted anonymously, empty, or on a missing feature
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/1/comments")
+                .body(json!({ "comment": "Anonymous" }).to_string())
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+
+            assert_eq!(comment("ingalls2", 1, " ").status().as_u16(), 400);
+            assert_eq!(comment("ingalls2", 2, "Missing").status().as_u16(), 404);
+        }
+
+        { //Comments are listed in the order they were posted
+            let comments = list();
+            let comments = comments.as_array().unwrap();
+
+            assert_eq!(comments.len(), 3);
+            assert_eq!(comments[0]["comment"], json!("Is this building still here?"));
+            assert_eq!(comments[1]["comment"], json!("It was demolished last year"));
+            assert_eq!(comments[1]["username"], json!("ingalls3"));
+            assert_eq!(comments[2]["comment"], json!("Thanks, I will remove it"));
+
+            //Commenting does not create a new version of the feature
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            let feature: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(feature["version"], json!(1));
+        }
+
+        { //Resolve & Unresolve
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/comments/1/resolve")
+                .basic_auth("ingalls3", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["resolved_by"], json!(3));
+            assert!(json_body["resolved"].is_string());
+
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/comments/1/unresolve")
+                .basic_auth("ingalls2", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["resolved_by"], json!(null));
+            assert_eq!(json_body["resolved"], json!(null));
+        }
+
+        { //Only the author or an admin can delete a comment
+            assert_eq!(delete("ingalls3", 1).status().as_u16(), 403);
+
+            let client = reqwest::Client::new();
+            let resp = client.delete("http://localhost:8000/api/data/feature/1/comments/3")
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+
+            let mut resp = delete("ingalls2", 1);
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+
+            assert!(delete("ingalls", 2).status().is_success());
+            assert_eq!(delete("ingalls", 2).status().as_u16(), 404);
+
+            let comments = list();
+            let comments = comments.as_array().unwrap();
+            assert_eq!(comments.len(), 1);
+            assert_eq!(comments[0]["comment"], json!("Thanks, I will remove it"));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,176 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_geometry() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "key": "shop-1",
+                    "properties": { "shop": "bakery", "name": "Bread" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Move Point
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/data/feature/1/geometry?version=1&message=Move%20Point")
+                .body(r#"{ "type": "Point", "coordinates": [ 1, 1 ] }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Geometry changed, properties are untouched
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "key": "shop-1",
+                "type": "Feature",
+                "version": 2,
+                "geometry": { "type": "Point", "coordinates": [ 1.0, 1.0 ] },
+                "properties": { "shop": "bakery", "name": "Bread" }
+            }));
+        }
+
+        { //Move is recorded in history
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0]["id"], json!(2));
+            assert_eq!(json_body[0]["feat"]["action"], json!("modify"));
+            assert_eq!(json_body[0]["feat"]["properties"], json!({ "shop": "bakery", "name": "Bread" }));
+        }
+
+        { //Stale version is rejected
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/data/feature/1/geometry?version=1&message=Move%20Point")
+                .body(r#"{ "type": "Point", "coordinates": [ 2, 2 ] }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["message"], json!("Modify Version Mismatch"));
+        }
+
+        { //Invalid coordinates are rejected
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/data/feature/1/geometry?version=2&message=Move%20Point")
+                .body(r#"{ "type": "Point", "coordinates": [ 200, 0 ] }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["message"], json!("longitude > 180"));
+        }
+
+        { //Body that is not a geometry is rejected
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/data/feature/1/geometry?version=2&message=Move%20Point")
+                .body(r#"{ "type": "Feature", "properties": {}, "geometry": { "type": "Point", "coordinates": [ 2, 2 ] } }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Body must be valid GeoJSON Geometry"));
+        }
+
+        { //Rejected updates leave the feature unchanged
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(2));
+            assert_eq!(json_body["geometry"], json!({ "type": "Point", "coordinates": [ 1.0, 1.0 ] }));
+        }
+
+        server.kill().unwrap();
+    }
+}
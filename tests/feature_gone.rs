@@ -0,0 +1,136 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_gone() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        for i in 0..2 { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {{ "number": {} }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {}, 0 ] }}
+                }}"#, i, i))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Delete Point 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "delete",
+                    "message": "Delete a point",
+                    "properties": null,
+                    "geometry": null
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Existing feature
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(2));
+        }
+
+        { //Deleted feature is gone
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert_eq!(resp.status().as_u16(), 410);
+            assert_eq!(resp.headers().get("X-Hecate-Delta").unwrap(), "3");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 410,
+                "status": "Gone",
+                "reason": "Feature Deleted",
+                "delta": 3
+            }));
+        }
+
+        { //Deleted feature is gone in any geometry format
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1?geometry=wkt").unwrap();
+            assert_eq!(resp.status().as_u16(), 410);
+        }
+
+        { //Feature that never existed is not found
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/3").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+            assert!(resp.headers().get("X-Hecate-Delta").is_none());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 404,
+                "reason": "Not Found",
+                "status": "Not Found"
+            }));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_related() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Parent & Children
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Create Campus",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "Campus" },
+                        "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "Library", "parent_id": 1 },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "Gym", "parent_id": "1" },
+                        "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "Reading Room", "parent_id": 2 },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "Orphan", "parent_id": 99 },
+                        "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Children of the parent
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/related?key=parent_id").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let ids: Vec<i64> = json_body["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![2, 3]);
+        }
+
+        { //Children of a child
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2/related?key=parent_id").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let ids: Vec<i64> = json_body["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![4]);
+        }
+
+        { //Feature without children
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/4/related?key=parent_id").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let ids: Vec<i64> = json_body["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![]);
+        }
+
+        { //Children are capped by the limit
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/related?key=parent_id&limit=1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let ids: Vec<i64> = json_body["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![2]);
+        }
+
+        { //Parent of a child
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/4/related?key=parent_id&direction=outgoing").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let ids: Vec<i64> = json_body["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![2]);
+        }
+
+        { //Parent of a feature without a parent
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/related?key=parent_id&direction=outgoing").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let ids: Vec<i64> = json_body["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![]);
+        }
+
+        { //Parent that does not exist
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/5/related?key=parent_id&direction=outgoing").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let ids: Vec<i64> = json_body["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![]);
+        }
+
+        { //Key is required
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1/related").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Invalid key
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1/related?key=parent%20id").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Invalid direction
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1/related?key=parent_id&direction=children").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Invalid limit
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1/related?key=parent_id&limit=0").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Missing feature
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/100/related?key=parent_id").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_restore() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create a second, non admin user
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=wilder&password=yeaheh&email=wilder@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "number": 1 },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        for version in vec![1, 2] { //Modify Point twice
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "id": 1,
+                    "version": {},
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Modify Point",
+                    "properties": {{ "number": {} }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {}, {} ] }}
+                }}"#, version, version + 1, version, version))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Restore requires feature::force
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/1/restore?version=1")
+                .body(r#"{ "message": "Revert", "current": 3 }"#)
+                .basic_auth("wilder", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Restore with a stale current version
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/restore?version=1")
+                .body(r#"{ "message": "Revert", "current": 2 }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 409);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 409,
+                "reason": "Version Conflict - feature is at version 3",
+                "status": "Conflict"
+            }));
+        }
+
+        for version in vec![0, 3, 4] { //Restore to a version that is not earlier
+            let client = reqwest::Client::new();
+            let resp = client.post(&*format!("http://localhost:8000/api/data/feature/1/restore?version={}", version))
+                .body(r#"{ "message": "Revert", "current": 3 }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Restore a missing feature
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/100/restore?version=1")
+                .body(r#"{ "message": "Revert", "current": 3 }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Restore to version 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/restore?version=1")
+                .body(r#"{ "message": "Revert", "current": 3 }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": 4,
+                "version": 4
+            }));
+        }
+
+        { //Feature equals version 1 at a new version
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(4));
+            assert_eq!(json_body["properties"], json!({ "number": 1 }));
+            assert_eq!(json_body["geometry"], json!({ "type": "Point", "coordinates": [ 0.0, 0.0 ] }));
+        }
+
+        { //History is preserved with the restore recorded as a modify
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let history = json_body.as_array().unwrap();
+            assert_eq!(history.len(), 4);
+
+            assert_eq!(history[0]["id"], json!(4));
+            assert_eq!(history[0]["feat"]["action"], json!("modify"));
+            assert_eq!(history[0]["feat"]["version"], json!(3));
+            assert_eq!(history[0]["feat"]["restored_from"], json!(1));
+            assert_eq!(history[0]["feat"]["properties"], json!({ "number": 1 }));
+
+            assert_eq!(history[1]["feat"]["properties"], json!({ "number": 3 }));
+            assert_eq!(history[2]["feat"]["properties"], json!({ "number": 2 }));
+            assert_eq!(history[3]["feat"]["properties"], json!({ "number": 1 }));
+        }
+
+        { //The delta is a normal edit
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/4").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["props"], json!({ "message": "Revert" }));
+            assert_eq!(json_body["affected"], json!([1]));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,179 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_split() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create LineString
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Road",
+                    "properties": { "highway": "residential", "name": "Main St" },
+                    "geometry": { "type": "LineString", "coordinates": [ [ 0, 0 ], [ 1, 0 ], [ 2, 0 ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Splitter that misses the feature fails
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/split")
+                .body(r#"{
+                    "message": "Split Road",
+                    "splitter": { "type": "Point", "coordinates": [ 5, 5 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Splitter does not split the feature"));
+        }
+
+        { //Splitting a missing feature fails
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/100/split")
+                .body(r#"{
+                    "message": "Split Road",
+                    "splitter": { "type": "Point", "coordinates": [ 1, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Split LineString in two
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/split")
+                .body(r#"{
+                    "message": "Split Road",
+                    "splitter": { "type": "Point", "coordinates": [ 1, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": 2,
+                "features": [ 2, 3 ]
+            }));
+        }
+
+        { //Original is deleted
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        for (id, coords) in vec![
+            (2, json!([ [ 0.0, 0.0 ], [ 1.0, 0.0 ] ])),
+            (3, json!([ [ 1.0, 0.0 ], [ 2.0, 0.0 ] ]))
+        ] { //Children share the original properties
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/data/feature/{}", id)).unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(1));
+            assert_eq!(json_body["properties"], json!({
+                "highway": "residential",
+                "name": "Main St"
+            }));
+            assert_eq!(json_body["geometry"], json!({
+                "type": "LineString",
+                "coordinates": coords
+            }));
+        }
+
+        { //Lineage is recorded in history
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0]["id"], json!(2));
+            assert_eq!(json_body[0]["feat"]["action"], json!("delete"));
+            assert_eq!(json_body[0]["feat"]["split_into"], json!([ 2, 3 ]));
+
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/3/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0]["id"], json!(2));
+            assert_eq!(json_body[0]["feat"]["action"], json!("create"));
+            assert_eq!(json_body[0]["feat"]["split_from"], json!(1));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn query(param: &str, value: &str) -> Vec<i64> {
+        let client = reqwest::Client::new();
+        let mut resp = client.get("http://localhost:8000/api/data/features")
+            .query(&[(param, value)])
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        let body = resp.text().unwrap();
+        let mut ids: Vec<i64> = body.trim_end_matches('\u{4}').lines().map(|line| {
+            let feature: serde_json::value::Value = serde_json::from_str(line).unwrap();
+            feature["id"].as_i64().unwrap()
+        }).collect();
+
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn feature_tags() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create Features
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Create Shops",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "shop": "bakery" },
+                        "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "shop": "cafe" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Feature starts without tags
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/tags").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([]));
+        }
+
+        { //Tagging requires auth
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/1/tags/needs-review")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Tag Feature, tagging twice is a no-op
+            for _ in 0..2 {
+                let client = reqwest::Client::new();
+                let mut resp = client.post("http://localhost:8000/api/data/feature/1/tags/needs-review")
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .send()
+                    .unwrap();
+
+                assert!(resp.status().is_success());
+
+                let json_body: serde_json::value::Value = resp.json().unwrap();
+                assert_eq!(json_body, json!([ "needs-review" ]));
+            }
+
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/tags/source:survey")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([ "needs-review", "source:survey" ]));
+        }
+
+        { //Invalid tags are rejected
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/tags/Needs%20Review")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Invalid Tag - may only contain a-z, 0-9, _, :, and -"));
+        }
+
+        { //Missing features cannot be tagged
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/100/tags/needs-review")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Tagging does not change the version or create a delta
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(1));
+            assert_eq!(json_body["properties"], json!({ "shop": "bakery" }));
+
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 1);
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let count: i64 = conn.query("SELECT count(*) FROM deltas", &[]).unwrap().get(0).get(0);
+            assert_eq!(count, 1);
+        }
+
+        { //Filter by tag
+            assert_eq!(query("tag", "needs-review"), vec![1]);
+            assert_eq!(query("tag", "unused"), Vec::<i64>::new());
+            assert_eq!(query("filter", "TAGGED('source:survey')"), vec![1]);
+            assert_eq!(query("filter", "NOT TAGGED('needs-review')"), vec![2]);
+            assert_eq!(query("filter", "shop = 'cafe' OR TAGGED('needs-review')"), vec![1, 2]);
+        }
+
+        { //Count by tag
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features/count?tag=needs-review").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({ "count": 1 }));
+        }
+
+        { //Remove Tag
+            let client = reqwest::Client::new();
+            let mut resp = client.delete("http://localhost:8000/api/data/feature/1/tags/needs-review")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([ "source:survey" ]));
+
+            assert_eq!(query("tag", "needs-review"), Vec::<i64>::new());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(1));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_version_delta() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        for coords in vec!["[ 0, 0 ]", "[ 1, 1 ]"] { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {{ "shop": true }},
+                    "geometry": {{ "type": "Point", "coordinates": {} }}
+                }}"#, coords))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Modify Point 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Modify Point",
+                    "properties": { "shop": false },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Version 1 belongs to the create delta
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/version/1/delta").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(1));
+            assert_eq!(json_body["uid"], json!(1));
+            assert_eq!(json_body["username"], json!("ingalls"));
+            assert_eq!(json_body["affected"], json!([ 1 ]));
+            assert_eq!(json_body["props"], json!({ "message": "Create Point" }));
+            assert!(json_body["created"].is_string());
+            assert_eq!(json_body["features"]["features"][0]["action"], json!("create"));
+        }
+
+        { //Version 2 belongs to the modify delta
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/version/2/delta").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(3));
+            assert_eq!(json_body["props"], json!({ "message": "Modify Point" }));
+            assert_eq!(json_body["features"]["features"][0]["properties"], json!({ "shop": false }));
+        }
+
+        { //Version 1 of Point 2 belongs to its own create delta
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2/version/1/delta").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(2));
+        }
+
+        { //Version without a delta
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/version/3/delta").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 404,
+                "reason": "No delta found for the given feature version",
+                "status": "Not Found"
+            }));
+        }
+
+        { //Feature without a delta
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/100/version/1/delta").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn ids(params: &[(&str, &str)]) -> Vec<i64> {
+        let client = reqwest::Client::new();
+        let mut resp = client.get("http://localhost:8000/api/data/features")
+            .query(params)
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        let body = resp.text().unwrap();
+        let mut ids: Vec<i64> = body.trim_end_matches('\u{4}').lines().map(|line| {
+            let feat: serde_json::value::Value = serde_json::from_str(line).unwrap();
+            feat["id"].as_i64().unwrap()
+        }).collect();
+
+        ids.sort();
+        ids
+    }
+
+    fn status(url: &str, params: &[(&str, &str)]) -> (u16, serde_json::value::Value) {
+        let client = reqwest::Client::new();
+        let mut resp = client.get(url)
+            .query(params)
+            .send()
+            .unwrap();
+
+        (resp.status().as_u16(), resp.json().unwrap())
+    }
+
+    #[test]
+    fn features_buffer() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points ~55m, ~222m and ~1.1km east of the origin
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Basic Creation",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "near" },
+                        "geometry": { "type": "Point", "coordinates": [ 0.0005, 0 ] }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "middle" },
+                        "geometry": { "type": "Point", "coordinates": [ 0.002, 0 ] }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "far" },
+                        "geometry": { "type": "Point", "coordinates": [ 0.01, 0 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Point without a buffer only matches at the point
+            assert_eq!(ids(&[("point", "0,0")]), Vec::<i64>::new());
+        }
+
+        { //Buffered point returns the features within the radius
+            assert_eq!(ids(&[("point", "0,0"), ("buffer", "100")]), vec![1]);
+            assert_eq!(ids(&[("point", "0,0"), ("buffer", "300")]), vec![1, 2]);
+            assert_eq!(ids(&[("point", "0,0"), ("buffer", "2000")]), vec![1, 2, 3]);
+        }
+
+        { //Buffered bbox returns the features within the distance of the bbox
+            assert_eq!(ids(&[("bbox", "-0.0001,-0.0001,0.0001,0.0001")]), Vec::<i64>::new());
+            assert_eq!(ids(&[("bbox", "-0.0001,-0.0001,0.0001,0.0001"), ("buffer", "100")]), vec![1]);
+            assert_eq!(ids(&[("bbox", "-0.0001,-0.0001,0.0001,0.0001"), ("buffer", "1000")]), vec![1, 2]);
+        }
+
+        { //Buffered point count
+            assert_eq!(status("http://localhost:8000/api/data/features/count", &[("point", "0,0"), ("buffer", "300")]), (200, json!({
+                "count": 2
+            })));
+        }
+
+        { //Invalid buffers
+            for buffer in &["0", "-100", "50001"] {
+                assert_eq!(status("http://localhost:8000/api/data/features", &[("point", "0,0"), ("buffer", buffer)]), (400, json!({
+                    "code": 400,
+                    "reason": "Buffer must be a number of metres greater than 0 and no greater than 50000",
+                    "status": "Bad Request"
+                })));
+            }
+        }
+
+        { //Buffer cannot be used with a filter
+            assert_eq!(status("http://localhost:8000/api/data/features", &[("prop", "name=near"), ("buffer", "100")]), (400, json!({
+                "code": 400,
+                "reason": "buffer param can only be used with bbox or point params",
+                "status": "Bad Request"
+            })));
+        }
+
+        server.kill().unwrap();
+    }
+}
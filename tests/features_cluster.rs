@@ -0,0 +1,124 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn cluster(query: &str) -> serde_json::value::Value {
+        let mut resp = reqwest::get(&*format!("http://localhost:8000/api/data/cluster?{}", query)).unwrap();
+        assert!(resp.status().is_success());
+
+        resp.json().unwrap()
+    }
+
+    fn counts(clusters: &serde_json::value::Value) -> Vec<i64> {
+        clusters["features"].as_array().unwrap().iter().map(|cluster| cluster["properties"]["count"].as_i64().unwrap()).collect()
+    }
+
+    #[test]
+    fn features_cluster() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create a dense group of 10 points, a group of 5 points, a lone point, and a polygon
+            let mut features: Vec<serde_json::value::Value> = Vec::new();
+
+            for i in 0..10 {
+                features.push(json!({ "type": "Feature", "action": "create", "properties": { "group": "a" }, "geometry": { "type": "Point", "coordinates": [ 0.001 * i as f64, 0.002 * i as f64 ] } }));
+            }
+
+            for i in 0..5 {
+                features.push(json!({ "type": "Feature", "action": "create", "properties": { "group": "b" }, "geometry": { "type": "Point", "coordinates": [ 10.0 + 0.01 * i as f64, 10.0 + 0.01 * i as f64 ] } }));
+            }
+
+            features.push(json!({ "type": "Feature", "action": "create", "properties": { "group": "c" }, "geometry": { "type": "Point", "coordinates": [ -10.0, -10.0 ] } }));
+            features.push(json!({ "type": "Feature", "action": "create", "properties": { "group": "a" }, "geometry": { "type": "Polygon", "coordinates": [ [ [ 0.0, 0.0 ], [ 0.01, 0.0 ], [ 0.01, 0.01 ], [ 0.0, 0.0 ] ] ] } }));
+
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(json!({ "type": "FeatureCollection", "message": "Create Points", "features": features }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Points are aggregated into clusters at a low zoom
+            let clusters = cluster("bbox=-180,-90,180,90&zoom=4");
+
+            assert_eq!(clusters["type"], json!("FeatureCollection"));
+            assert_eq!(counts(&clusters), vec![10, 5, 1]);
+
+            let centroid = &clusters["features"][0]["geometry"];
+            assert_eq!(centroid["type"], json!("Point"));
+            assert!((centroid["coordinates"][0].as_f64().unwrap() - 0.0045).abs() < 0.000001);
+            assert!((centroid["coordinates"][1].as_f64().unwrap() - 0.009).abs() < 0.000001);
+
+            assert_eq!(clusters["features"][2]["geometry"], json!({ "type": "Point", "coordinates": [ -10, -10 ] }));
+        }
+
+        { //Only points within the bbox are clustered
+            assert_eq!(counts(&cluster("bbox=5,5,15,15&zoom=4")), vec![5]);
+            assert_eq!(counts(&cluster("bbox=20,20,30,30&zoom=4")), Vec::<i64>::new());
+        }
+
+        { //Points are separated at a high zoom
+            assert_eq!(counts(&cluster("bbox=-0.001,-0.001,0.01,0.02&zoom=17")), vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+        }
+
+        { //bbox & zoom are required and a large bbox at a high zoom is rejected
+            let resp = reqwest::get("http://localhost:8000/api/data/cluster?zoom=4").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let resp = reqwest::get("http://localhost:8000/api/data/cluster?bbox=-180,-90,180,90&zoom=17").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
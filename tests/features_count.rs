@@ -0,0 +1,179 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn query(params: &[(&str, &str)]) -> usize {
+        let client = reqwest::Client::new();
+        let mut resp = client.get("http://localhost:8000/api/data/features")
+            .query(params)
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        let body = resp.text().unwrap();
+        body.trim_end_matches('\u{4}').lines().count()
+    }
+
+    fn count(params: &[(&str, &str)]) -> serde_json::value::Value {
+        let client = reqwest::Client::new();
+        let mut resp = client.get("http://localhost:8000/api/data/features/count")
+            .query(params)
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        resp.json().unwrap()
+    }
+
+    #[test]
+    fn features_count() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Basic Creation",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": "bakery",
+                            "name": "Main Street Bakery",
+                            "building": { "levels": 1 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 1,1 ]
+                        }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": "cafe",
+                            "name": "O'Malley's",
+                            "building": { "levels": 3 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 10,10 ]
+                        }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": "hardware",
+                            "name": "Main Hardware",
+                            "building": { "levels": 5 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 20,20 ]
+                        }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Count matches Query
+            let cases: Vec<Vec<(&str, &str)>> = vec![
+                vec![("bbox", "-1,-1,25,25")],
+                vec![("bbox", "5,5,15,15")],
+                vec![("point", "1,1")],
+                vec![("prop", "shop=cafe")],
+                vec![("prop", "building.levels=5"), ("bbox", "-1,-1,5,5")],
+                vec![("filter", "name LIKE 'Main%'")],
+                vec![("filter", "building.levels > 1"), ("bbox", "5,5,25,25")],
+                vec![("filter", "shop = 'grocery'")]
+            ];
+
+            for params in cases {
+                let expected = query(&params);
+                assert_eq!(count(&params), json!({ "count": expected }), "{:?}", params);
+            }
+        }
+
+        { //Known Counts
+            assert_eq!(count(&[("bbox", "-1,-1,25,25")]), json!({ "count": 3 }));
+            assert_eq!(count(&[("filter", "name LIKE 'Main%'")]), json!({ "count": 2 }));
+            assert_eq!(count(&[("filter", "shop = 'grocery'")]), json!({ "count": 0 }));
+        }
+
+        { //Invalid Params
+            let client = reqwest::Client::new();
+
+            let resp = client.get("http://localhost:8000/api/data/features/count")
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let resp = client.get("http://localhost:8000/api/data/features/count")
+                .query(&[("bbox", "-1,-1,25,25"), ("point", "1,1")])
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let resp = client.get("http://localhost:8000/api/data/features/count")
+                .query(&[("filter", "shop = 'cafe'; DROP TABLE geo; --")])
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
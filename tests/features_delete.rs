@@ -0,0 +1,272 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn features_delete() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Bad Import",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "source": "bad" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "source": "bad" },
+                        "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "source": "bad" },
+                        "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "source": "good" },
+                        "geometry": { "type": "Point", "coordinates": [ 4, 4 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "source": "bad" },
+                        "geometry": { "type": "Point", "coordinates": [ 5, 5 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Delete Point 3
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 3,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "delete",
+                    "message": "Delete a point",
+                    "properties": { "source": "bad" },
+                    "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Batch Delete - No Auth
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/features/delete")
+                .body(r#"{
+                    "message": "Cleanup",
+                    "features": [{ "id": 1, "version": 1 }]
+                }"#)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Batch Delete - Missing Version
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/delete")
+                .body(r#"{
+                    "message": "Cleanup",
+                    "features": [{ "id": 1 }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), r#"{"code":400,"reason":"Each feature must have a version unless force is set","status":"Bad Request"}"#);
+            assert!(resp.status().is_client_error());
+        }
+
+        { //Batch Delete - All or nothing with an already deleted id
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/delete")
+                .body(r#"{
+                    "message": "Cleanup",
+                    "features": [
+                        { "id": 1, "version": 1 },
+                        { "id": 2, "version": 1 },
+                        { "id": 3, "version": 1 }
+                    ]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json, json!({
+                "code": 400,
+                "status": "Bad Request",
+                "reason": "Batch Delete Failed - no features were deleted",
+                "results": [
+                    { "id": 1, "status": "deleted" },
+                    { "id": 2, "status": "deleted" },
+                    { "id": 3, "status": "failed", "reason": "Feature Not Found" }
+                ]
+            }));
+        }
+
+        { //Nothing was deleted
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT count(*) FROM geo
+            ", &[]).unwrap();
+            let count: i64 = res.get(0).get(0);
+            assert_eq!(count, 4);
+        }
+
+        let delta_id = { //Batch Delete - Best effort with an already deleted id & a version mismatch
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/delete")
+                .body(r#"{
+                    "message": "Cleanup",
+                    "partial": true,
+                    "features": [
+                        { "id": 1, "version": 1 },
+                        { "id": 3, "version": 1 },
+                        { "id": 2, "version": 1 },
+                        { "id": 4, "version": 2 }
+                    ]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["results"], json!([
+                { "id": 1, "status": "deleted" },
+                { "id": 3, "status": "failed", "reason": "Feature Not Found" },
+                { "id": 2, "status": "deleted" },
+                { "id": 4, "status": "failed", "reason": "Delete Version Mismatch" }
+            ]));
+
+            json["delta"].as_i64().unwrap()
+        };
+
+        { //Deletes are recorded in a single delta
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT
+                    props->>'message',
+                    affected,
+                    JSON_Array_Length((features->'features')::JSON)
+                FROM deltas
+                WHERE id = $1
+            ", &[&delta_id]).unwrap();
+            let message: String = res.get(0).get(0);
+            let affected: Vec<i64> = res.get(0).get(1);
+            let features: i32 = res.get(0).get(2);
+            assert_eq!(message, "Cleanup");
+            assert_eq!(affected, vec![1, 2]);
+            assert_eq!(features, 2);
+
+            let res = conn.query("
+                SELECT id FROM geo ORDER BY id
+            ", &[]).unwrap();
+            let ids: Vec<i64> = res.iter().map(|row| row.get(0)).collect();
+            assert_eq!(ids, vec![4, 5]);
+        }
+
+        { //Batch Delete - By filter
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/delete")
+                .body(r#"{
+                    "message": "Cleanup by filter",
+                    "filter": "source = 'bad'"
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["results"], json!([
+                { "id": 5, "status": "deleted" }
+            ]));
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/4").unwrap();
+            assert!(resp.status().is_success());
+
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/5").unwrap();
+            assert!(resp.status().is_client_error());
+        }
+
+        server.kill().unwrap();
+    }
+}
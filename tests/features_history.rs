@@ -0,0 +1,170 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn upload(body: &str) {
+        let client = reqwest::Client::new();
+        let mut resp = client.post("http://localhost:8000/api/data/features")
+            .body(String::from(body))
+            .basic_auth("ingalls", Some("yeaheh"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+        assert_eq!(resp.text().unwrap(), "true");
+    }
+
+    fn history(query: &str, ids: serde_json::value::Value) -> serde_json::value::Value {
+        let client = reqwest::Client::new();
+        let mut resp = client.post(&*format!("http://localhost:8000/api/data/features/history{}", query))
+            .body(json!({ "ids": ids }).to_string())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        resp.json().unwrap()
+    }
+
+    //The (delta, feature, action) of each version in a list
+    fn versions(list: &serde_json::value::Value) -> Vec<(i64, i64, String)> {
+        list.as_array().unwrap().iter().map(|version| (
+            version["id"].as_i64().unwrap(),
+            version["feat"]["id"].as_i64().unwrap(),
+            String::from(version["feat"]["action"].as_str().unwrap())
+        )).collect()
+    }
+
+    #[test]
+    fn features_history() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Delta 1 creates Feature 1, Delta 2 creates Features 2 & 3
+            upload(r#"{ "type": "FeatureCollection", "message": "Create Bakery", "features": [
+                { "type": "Feature", "action": "create", "properties": { "shop": "bakery" }, "geometry": { "type": "Point", "coordinates": [ 0, 0 ] } }
+            ] }"#);
+
+            upload(r#"{ "type": "FeatureCollection", "message": "Create Cafes", "features": [
+                { "type": "Feature", "action": "create", "properties": { "shop": "cafe" }, "geometry": { "type": "Point", "coordinates": [ 1, 1 ] } },
+                { "type": "Feature", "action": "create", "properties": { "shop": "cafe" }, "geometry": { "type": "Point", "coordinates": [ 2, 2 ] } }
+            ] }"#);
+        }
+
+        { //Delta 3 modifies Feature 1, Delta 4 modifies Features 1, 2 & 3
+            upload(r#"{ "type": "FeatureCollection", "message": "Name Bakery", "features": [
+                { "id": 1, "version": 1, "type": "Feature", "action": "modify", "properties": { "shop": "bakery", "name": "Bread" }, "geometry": { "type": "Point", "coordinates": [ 0, 0 ] } }
+            ] }"#);
+
+            upload(r#"{ "type": "FeatureCollection", "message": "Open All", "features": [
+                { "id": 1, "version": 2, "type": "Feature", "action": "modify", "properties": { "shop": "bakery", "name": "Bread", "open": true }, "geometry": { "type": "Point", "coordinates": [ 0, 0 ] } },
+                { "id": 2, "version": 1, "type": "Feature", "action": "modify", "properties": { "shop": "cafe", "open": true }, "geometry": { "type": "Point", "coordinates": [ 1, 1 ] } },
+                { "id": 3, "version": 1, "type": "Feature", "action": "modify", "properties": { "shop": "cafe", "open": true }, "geometry": { "type": "Point", "coordinates": [ 2, 2 ] } }
+            ] }"#);
+        }
+
+        { //Histories of Features 1 & 2 are merged in chronological order
+            let list = history("", json!([ 2, 1 ]));
+
+            assert_eq!(versions(&list), vec![
+                (1, 1, String::from("create")),
+                (2, 2, String::from("create")),
+                (3, 1, String::from("modify")),
+                (4, 1, String::from("modify")),
+                (4, 2, String::from("modify"))
+            ]);
+
+            assert_eq!(list[0]["uid"], json!(1));
+            assert_eq!(list[0]["username"], json!("ingalls"));
+            assert_eq!(list[0]["message"], json!("Create Bakery"));
+            assert_eq!(list[2]["message"], json!("Name Bakery"));
+            assert_eq!(list[2]["feat"]["properties"], json!({ "shop": "bakery", "name": "Bread" }));
+
+            let created: Vec<&str> = list.as_array().unwrap().iter().map(|version| version["created"].as_str().unwrap()).collect();
+            let mut sorted = created.clone();
+            sorted.sort();
+            assert_eq!(created, sorted);
+        }
+
+        { //Pages end on a delta boundary
+            let page = history("?limit=2&envelope=true", json!([ 1, 2 ]));
+            assert_eq!(versions(&page["data"]), vec![(1, 1, String::from("create")), (2, 2, String::from("create"))]);
+            assert_eq!(page["meta"]["next"], json!(2));
+
+            let page = history("?limit=2&after=2&envelope=true", json!([ 1, 2 ]));
+            assert_eq!(versions(&page["data"]), vec![(3, 1, String::from("modify")), (4, 1, String::from("modify")), (4, 2, String::from("modify"))]);
+            assert_eq!(page["meta"]["next"], json!(4));
+
+            let page = history("?limit=2&after=4&envelope=true", json!([ 1, 2 ]));
+            assert_eq!(page["data"], json!([]));
+            assert_eq!(page["meta"]["next"], json!(null));
+        }
+
+        { //Ids are required
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/features/history")
+                .body(r#"{ "ids": [] }"#)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
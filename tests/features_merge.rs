@@ -0,0 +1,279 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn features_merge() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        for (name, props, coords) in vec![
+            ("1", r#"{ "name": "Shop A", "shop": "bakery" }"#, "[ 1, 1 ]"),
+            ("2", r#"{ "name": "Shop B", "opening_hours": "24/7" }"#, "[ 2, 2 ]"),
+            ("3", r#"{ "name": "Cafe A", "amenity": "cafe" }"#, "[ 3, 3 ]"),
+            ("4", r#"{ "name": "Cafe B", "amenity": "cafe" }"#, "[ 4, 4 ]")
+        ] { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point {}",
+                    "properties": {},
+                    "geometry": {{ "type": "Point", "coordinates": {} }}
+                }}"#, name, props, coords))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Merging a feature into itself fails
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/merge")
+                .body(r#"{ "message": "Merge", "a": 1, "b": 1 }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Cannot merge a feature into itself"));
+        }
+
+        { //Merging with an invalid strategy fails
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/merge")
+                .body(r#"{ "message": "Merge", "a": 1, "b": 2, "strategy": "prefer-c" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Invalid Strategy - must be one of 'prefer-a', 'prefer-b' or 'newest'"));
+        }
+
+        { //Merging a missing feature fails
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/features/merge")
+                .body(r#"{ "message": "Merge", "a": 1, "b": 100 }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Merge Point 2 into Point 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/merge")
+                .body(r#"{ "message": "Merge Shops", "a": 1, "b": 2 }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": 5,
+                "id": 1
+            }));
+        }
+
+        { //Point 1 is now a MultiPoint with merged properties
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body["version"], json!(2));
+            assert_eq!(json_body["properties"], json!({
+                "name": "Shop A",
+                "shop": "bakery",
+                "opening_hours": "24/7"
+            }));
+            assert_eq!(json_body["geometry"]["type"], json!("MultiPoint"));
+
+            let coords = json_body["geometry"]["coordinates"].as_array().unwrap();
+            assert_eq!(coords.len(), 2);
+            assert!(coords.contains(&json!([ 1.0, 1.0 ])));
+            assert!(coords.contains(&json!([ 2.0, 2.0 ])));
+        }
+
+        { //Point 2 is deleted
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Merge delta is recorded in the history of both features
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0]["id"], json!(5));
+            assert_eq!(json_body[0]["feat"]["action"], json!("modify"));
+
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0]["id"], json!(5));
+            assert_eq!(json_body[0]["feat"]["action"], json!("delete"));
+            assert_eq!(json_body[0]["feat"]["properties"]["name"], json!("Shop B"));
+        }
+
+        { //Modify Point 3 so it is newer than Point 4
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 3,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Rename Cafe A",
+                    "properties": { "name": "Cafe A Renamed", "amenity": "cafe" },
+                    "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Merge Point 3 into Point 4 keeping the newest properties
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/features/merge")
+                .body(r#"{ "message": "Merge Cafes", "a": 4, "b": 3, "strategy": "newest" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Conflicting name comes from the newer Point 3
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/4").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["properties"], json!({
+                "name": "Cafe A Renamed",
+                "amenity": "cafe"
+            }));
+        }
+
+        { //Create Point 5 to merge with prefer-b
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point 5",
+                    "properties": { "name": "Shop C", "shop": "butcher" },
+                    "geometry": { "type": "Point", "coordinates": [ 5, 5 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Merge Point 5 into Point 1 preferring Point 5
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/features/merge")
+                .body(r#"{ "message": "Merge Shops", "a": 1, "b": 5, "strategy": "prefer-b" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Conflicting properties come from Point 5
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(3));
+            assert_eq!(json_body["properties"], json!({
+                "name": "Shop C",
+                "shop": "butcher",
+                "opening_hours": "24/7"
+            }));
+            assert_eq!(json_body["geometry"]["coordinates"].as_array().unwrap().len(), 3);
+        }
+
+        server.kill().unwrap();
+    }
+}
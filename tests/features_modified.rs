@@ -0,0 +1,197 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use]
+extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn features_modified() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "1" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point 2
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "2" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point 3
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "3" },
+                    "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Backdate the deltas that created the first two points
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("UPDATE deltas SET created = '2000-01-01T00:00:00' WHERE id = 1", &[]).unwrap();
+            conn.execute("UPDATE deltas SET created = '2010-01-01T00:00:00' WHERE id = 2", &[]).unwrap();
+        }
+
+        { //Delete Point 3
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 3,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "delete",
+                    "message": "Delete a point",
+                    "properties": null,
+                    "geometry": null
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Only features modified since 2005 along with deletions
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?modified_since=2005-01-01T00:00:00").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            let features: Vec<serde_json::Value> = body.trim_end_matches('\u{4}').lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+            assert_eq!(features.len(), 2);
+
+            assert_eq!(features[0]["id"], json!(2));
+            assert_eq!(features[0]["version"], json!(1));
+            assert_eq!(features[0]["properties"], json!({ "number": "2" }));
+
+            assert_eq!(features[1], json!({
+                "id": 3,
+                "key": null,
+                "type": "Feature",
+                "action": "delete",
+                "version": 1,
+                "geometry": null,
+                "properties": null
+            }));
+        }
+
+        { //All features modified since 1999
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?modified_since=1999-01-01T00:00:00").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            let features: Vec<serde_json::Value> = body.trim_end_matches('\u{4}').lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+            let ids: Vec<i64> = features.iter().map(|feature| feature["id"].as_i64().unwrap()).collect();
+            assert_eq!(ids, vec![1, 2, 3]);
+        }
+
+        { //Nothing modified in the future
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?modified_since=2100-01-01T00:00:00").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            assert_eq!(body.trim_end_matches('\u{4}').lines().count(), 0);
+        }
+
+        { //Invalid Timestamp
+            let resp = reqwest::get("http://localhost:8000/api/data/features?modified_since=yesterday").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Cannot be combined with a bbox
+            let resp = reqwest::get("http://localhost:8000/api/data/features?modified_since=2005-01-01T00:00:00&bbox=0,0,4,4").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+extern crate reqwest; extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn features_order() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        for i in 1..5 { //Create Points - one delta each
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {{ "number": {} }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {}, 0 ] }}
+                }}"#, i, i))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        for id in vec![2, 1] { //Modify Points - 1 is modified last
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "id": {},
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Modify Point",
+                    "properties": {{ "number": {}, "modified": true }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {}, 0 ] }}
+                }}"#, id, id, id))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        let ids = |query: &str| -> Vec<i64> {
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/data/features?{}", query)).unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            body.trim_end_matches('\u{4}').lines().map(|line| {
+                let feature: serde_json::Value = serde_json::from_str(line).unwrap();
+                feature["id"].as_i64().unwrap()
+            }).collect()
+        };
+
+        { //Order by id
+            assert_eq!(ids("bbox=-1,-1,10,1&order=id"), vec![1, 2, 3, 4]);
+            assert_eq!(ids("bbox=-1,-1,10,1&order=id:asc"), vec![1, 2, 3, 4]);
+            assert_eq!(ids("bbox=-1,-1,10,1&order=id:desc"), vec![4, 3, 2, 1]);
+        }
+
+        { //Order by created
+            assert_eq!(ids("bbox=-1,-1,10,1&order=created"), vec![1, 2, 3, 4]);
+            assert_eq!(ids("bbox=-1,-1,10,1&order=created:desc"), vec![4, 3, 2, 1]);
+        }
+
+        { //Order by modified
+            assert_eq!(ids("bbox=-1,-1,10,1&order=modified"), vec![3, 4, 2, 1]);
+            assert_eq!(ids("bbox=-1,-1,10,1&order=modified:desc"), vec![1, 2, 4, 3]);
+        }
+
+        { //Order a filter query
+            assert_eq!(ids("filter=number%20%3E%201&order=modified"), vec![3, 4, 2]);
+        }
+
+        { //Paginate by id
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&order=id"), vec![1, 2]);
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&after=2"), vec![3, 4]);
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&order=id:desc&after=3"), vec![2, 1]);
+        }
+
+        { //Paginate by modified
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&order=modified"), vec![3, 4]);
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&order=modified&after=4"), vec![2, 1]);
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&order=modified&after=1"), Vec::<i64>::new());
+
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&order=modified:desc"), vec![1, 2]);
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=2&order=modified:desc&after=2"), vec![4, 3]);
+        }
+
+        { //Paginate by created
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=3&order=created:desc"), vec![4, 3, 2]);
+            assert_eq!(ids("bbox=-1,-1,10,1&limit=3&order=created:desc&after=2"), vec![1]);
+        }
+
+        { //Invalid order key
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1&order=name").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Order - must be one of 'id', 'created' or 'modified'",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Invalid order direction
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1&order=id:sideways").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Order cannot be used with point
+            let resp = reqwest::get("http://localhost:8000/api/data/features?point=1,0&order=id").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,238 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn features_replace() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "nick"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create Fixture
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Create Buildings",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "building": "residental", "name": "A" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "building": "residental", "name": "B" },
+                        "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "building": "residental", "name": "C" },
+                        "geometry": { "type": "Point", "coordinates": [ 30, 30 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "building": "residential", "name": "D" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "building": "yes", "name": "residental" },
+                        "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Replace requires feature::force
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/features/replace")
+                .body(r#"{ "message": "Fix typo", "key": "building", "value": "residental", "replacement": "residential" }"#)
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Replacing a value with itself fails
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/replace")
+                .body(r#"{ "message": "Fix typo", "key": "building", "value": "yes", "replacement": "yes" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Invalid Replace - value and replacement must differ"));
+        }
+
+        { //Replace within a bbox
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/replace")
+                .body(r#"{ "message": "Fix typo", "key": "building", "value": "residental", "replacement": "residential", "bbox": "0,0,1.5,1.5" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": 2,
+                "count": 1
+            }));
+        }
+
+        { //Replace matching a filter
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/replace")
+                .body(r#"{ "message": "Fix typo", "key": "building", "value": "residental", "replacement": "residential", "filter": "name = 'C'" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": 3,
+                "count": 1
+            }));
+        }
+
+        { //Replace everywhere else
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/replace")
+                .body(r#"{ "message": "Fix typo", "key": "building", "value": "residental", "replacement": "residential" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": 4,
+                "count": 1
+            }));
+        }
+
+        { //Nothing left to replace
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/replace")
+                .body(r#"{ "message": "Fix typo", "key": "building", "value": "residental", "replacement": "residential" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": null,
+                "count": 0
+            }));
+        }
+
+        { //Only matching features changed
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("
+                SELECT id, version, props FROM geo ORDER BY id
+            ", &[]).unwrap();
+
+            let features: Vec<(i64, i64, serde_json::Value)> = rows.iter().map(|row| (row.get(0), row.get(1), row.get(2))).collect();
+            assert_eq!(features, vec![
+                (1, 2, json!({ "building": "residential", "name": "A" })),
+                (2, 2, json!({ "building": "residential", "name": "B" })),
+                (3, 2, json!({ "building": "residential", "name": "C" })),
+                (4, 1, json!({ "building": "residential", "name": "D" })),
+                (5, 1, json!({ "building": "yes", "name": "residental" }))
+            ]);
+        }
+
+        { //Each replace is a single delta
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("
+                SELECT id, affected FROM deltas ORDER BY id
+            ", &[]).unwrap();
+
+            let deltas: Vec<(i64, Vec<i64>)> = rows.iter().map(|row| (row.get(0), row.get(1))).collect();
+            assert_eq!(deltas, vec![
+                (1, vec![1, 2, 3, 4, 5]),
+                (2, vec![1]),
+                (3, vec![3]),
+                (4, vec![2])
+            ]);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn query(filter: &str) -> Vec<i64> {
+        let client = reqwest::Client::new();
+        let mut resp = client.get("http://localhost:8000/api/data/features")
+            .query(&[("filter", filter)])
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        let body = resp.text().unwrap();
+        let mut ids: Vec<i64> = body.trim_end_matches('\u{4}').lines().map(|line| {
+            let feature: serde_json::value::Value = serde_json::from_str(line).unwrap();
+            feature["id"].as_i64().unwrap()
+        }).collect();
+
+        ids.sort();
+        ids
+    }
+
+    fn error(filter: &str) -> serde_json::value::Value {
+        let client = reqwest::Client::new();
+        let mut resp = client.get("http://localhost:8000/api/data/features")
+            .query(&[("filter", filter)])
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_client_error());
+
+        resp.json().unwrap()
+    }
+
+    #[test]
+    fn filter() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Basic Creation",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": "bakery",
+                            "name": "Main Street Bakery",
+                            "building": { "levels": 1 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 1,1 ]
+                        }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": "cafe",
+                            "name": "O'Malley's",
+                            "building": { "levels": 3 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 10,10 ]
+                        }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": "hardware",
+                            "name": "Main Hardware",
+                            "building": { "levels": 5 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 20,20 ]
+                        }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Equals
+            assert_eq!(query("shop = 'cafe'"), vec![2]);
+            assert_eq!(query("building.levels = 5"), vec![3]);
+            assert_eq!(query("name = 'O''Malley''s'"), vec![2]);
+        }
+
+        { //Not Equals
+            assert_eq!(query("shop != 'cafe'"), vec![1, 3]);
+        }
+
+        { //Less Than / Greater Than
+            assert_eq!(query("building.levels < 3"), vec![1]);
+            assert_eq!(query("building.levels > 2"), vec![2, 3]);
+            assert_eq!(query("building.levels > '2'"), Vec::<i64>::new());
+        }
+
+        { //In
+            assert_eq!(query("shop IN ('bakery', 'hardware')"), vec![1, 3]);
+        }
+
+        { //Like
+            assert_eq!(query("name LIKE 'Main%'"), vec![1, 3]);
+        }
+
+        { //Intersects
+            assert_eq!(query("INTERSECTS(5, 5, 15, 15)"), vec![2]);
+        }
+
+        { //Combined
+            assert_eq!(query("(name LIKE 'Main%' OR shop = 'cafe') AND NOT building.levels > 4"), vec![1, 2]);
+        }
+
+        { //Combined with bbox
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/features")
+                .query(&[("filter", "name LIKE 'Main%'"), ("bbox", "15,15,25,25")])
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            let features: Vec<&str> = body.trim_end_matches('\u{4}').lines().collect();
+            assert_eq!(features.len(), 1);
+
+            let feature: serde_json::value::Value = serde_json::from_str(features[0]).unwrap();
+            assert_eq!(feature["id"], json!(3));
+        }
+
+        { //Malicious input is rejected
+            assert_eq!(error("shop = 'cafe'; DROP TABLE geo; --"), json!({
+                "code": 400,
+                "reason": "Invalid Filter - unexpected character ';'",
+                "status": "Bad Request"
+            }));
+
+            assert_eq!(error("shop = 'cafe' OR 1 = 1"), json!({
+                "code": 400,
+                "reason": "Invalid Filter - expected a property",
+                "status": "Bad Request"
+            }));
+
+            assert_eq!(error("shop') = '' OR props = props"), json!({
+                "code": 400,
+                "reason": "Invalid Filter - expected an operator after 'shop'",
+                "status": "Bad Request"
+            }));
+
+            assert_eq!(error("shop = id"), json!({
+                "code": 400,
+                "reason": "Invalid Filter - expected a string, number, true, false, or null",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Injection attempts inside values are only ever compared as data
+            assert_eq!(query("name = 'x''); DROP TABLE geo; --'"), Vec::<i64>::new());
+            assert_eq!(query("shop = 'cafe'"), vec![2]);
+        }
+
+        server.kill().unwrap();
+    }
+}
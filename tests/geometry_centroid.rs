@@ -0,0 +1,123 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn geometry_centroid() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create a U shaped polygon whose centroid lies outside of it
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Polygon",
+                    "properties": { "building": true },
+                    "geometry": { "type": "Polygon", "coordinates": [[ [0,0], [3,0], [3,3], [2,3], [2,1], [1,1], [1,3], [0,3], [0,0] ]] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        let within = |geometry: &serde_json::value::Value| -> bool {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let rows = conn.query("
+                SELECT ST_Intersects(geom, ST_SetSRID(ST_GeomFromGeoJSON($1), 4326)) FROM geo WHERE id = 1
+            ", &[&geometry.to_string()]).unwrap();
+
+            rows.get(0).get(0)
+        };
+
+        { //Centroid returns a point
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?geometry=centroid").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["properties"], json!({ "building": true }));
+            assert_eq!(json_body["geometry"]["type"], "Point");
+            assert_eq!(json_body["geometry"]["coordinates"][0], 1.5);
+            assert!(!within(&json_body["geometry"]));
+        }
+
+        { //Point on surface returns a point within the polygon
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?geometry=point_on_surface").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["geometry"]["type"], "Point");
+            assert!(within(&json_body["geometry"]));
+        }
+
+        { //Feature queries return points
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,4,4&geometry=point_on_surface").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            let feats: Vec<serde_json::value::Value> = body.trim_end_matches('\u{4}').lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+            assert_eq!(feats.len(), 1);
+            assert_eq!(feats[0]["geometry"]["type"], "Point");
+            assert!(within(&feats[0]["geometry"]));
+        }
+
+        { //Full geometry remains the default
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["geometry"]["type"], "Polygon");
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn geometry_grid() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.geometry.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point with finer precision than the grid
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 1.234567890123456, 2.987654321098765 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Stored Point is snapped to the grid
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT ST_AsGeoJSON(geom)::TEXT FROM geo WHERE id = 1
+            ", &[]).unwrap();
+            let geom: String = res.get(0).get(0);
+            assert_eq!(geom, r#"{"type":"Point","coordinates":[1.235,2.988]}"#);
+        }
+
+        { //Modify Point to a LineString with finer precision than the grid
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Modify a point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "LineString", "coordinates": [ [ -1.00049, 1.00051 ], [ 3.1415926535, -2.7182818284 ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Stored LineString is snapped to the grid
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT ST_AsGeoJSON(geom)::TEXT, version FROM geo WHERE id = 1
+            ", &[]).unwrap();
+            let geom: String = res.get(0).get(0);
+            let version: i64 = res.get(0).get(1);
+            assert_eq!(geom, r#"{"type":"LineString","coordinates":[[-1,1.001],[3.142,-2.718]]}"#);
+            assert_eq!(version, 2);
+        }
+
+        { //Create Polygon that collapses on the grid
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a tiny Polygon",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Polygon", "coordinates": [ [ [ 0.0001, 0.0001 ], [ 0.0002, 0.0001 ], [ 0.0002, 0.0002 ], [ 0.0001, 0.0001 ] ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["message"], "Geometry collapsed when snapped to the storage grid");
+        }
+
+        server.kill().unwrap();
+    }
+}
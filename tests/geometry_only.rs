@@ -0,0 +1,130 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn features(query: &str) -> Vec<serde_json::value::Value> {
+        let mut resp = reqwest::get(&*format!("http://localhost:8000/api/data/features?{}", query)).unwrap();
+        assert!(resp.status().is_success());
+
+        let body = resp.text().unwrap();
+        body.trim_end_matches('\u{4}').lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn geometry_only() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Features
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Create Features",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "building": true, "name": "Town Hall" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "building": true, "name": "Library" },
+                        "geometry": { "type": "LineString", "coordinates": [ [ 2, 2 ], [ 3, 3 ] ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Default is unchanged
+            let feats = features("bbox=0,0,4,4&order=id");
+            assert_eq!(feats.len(), 2);
+            assert_eq!(feats[0]["properties"], json!({ "building": true, "name": "Town Hall" }));
+        }
+
+        { //BBOX query omits properties but keeps the geometry intact
+            let feats = features("bbox=0,0,4,4&order=id&geometry_only=true");
+            assert_eq!(feats, vec![json!({
+                "id": 1,
+                "key": null,
+                "type": "Feature",
+                "version": 1,
+                "geometry": { "type": "Point", "coordinates": [ 1, 1 ] },
+                "properties": {}
+            }), json!({
+                "id": 2,
+                "key": null,
+                "type": "Feature",
+                "version": 1,
+                "geometry": { "type": "LineString", "coordinates": [ [ 2, 2 ], [ 3, 3 ] ] },
+                "properties": {}
+            })]);
+        }
+
+        { //Filter query omits properties
+            let feats = features("filter=name%20%3D%20%27Library%27&geometry_only=true");
+            assert_eq!(feats.len(), 1);
+            assert_eq!(feats[0]["id"], json!(2));
+            assert_eq!(feats[0]["properties"], json!({}));
+        }
+
+        { //Can be combined with other geometry formats
+            let feats = features("point=1,1&geometry_only=true&geometry=wkt");
+            assert_eq!(feats.len(), 1);
+            assert_eq!(feats[0]["wkt"], json!("POINT(1 1)"));
+            assert_eq!(feats[0]["properties"], json!({}));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use]
+extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn geometry_wkt() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Get Point as GeoJSON by default
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["geometry"], json!({ "type": "Point", "coordinates": [ 1.0, 1.0 ] }));
+            assert_eq!(json.get("wkt"), None);
+        }
+
+        { //Get Point as WKT
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?geometry=wkt").unwrap();
+            assert!(resp.status().is_success());
+
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["wkt"], "POINT(1 1)");
+            assert_eq!(json["geometry"], serde_json::Value::Null);
+            assert_eq!(json["properties"], json!({ "number": "123" }));
+        }
+
+        { //Get Point as EWKT
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?geometry=ewkt").unwrap();
+            assert!(resp.status().is_success());
+
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["ewkt"], "SRID=4326;POINT(1 1)");
+        }
+
+        { //Get Point with an invalid geometry format
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?geometry=wkb").unwrap();
+            assert!(resp.status().is_client_error());
+
+            let json: serde_json::Value = resp.json().unwrap();
+            assert_eq!(json["reason"], "Invalid Geometry - must be one of 'geojson', 'wkt', 'ewkt', 'centroid' or 'point_on_surface'");
+        }
+
+        { //Query Points as WKT
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=0,0,2,2&geometry=wkt").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            let line = body.lines().next().unwrap();
+
+            let json: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(json["wkt"], "POINT(1 1)");
+            assert_eq!(json["geometry"], serde_json::Value::Null);
+        }
+
+        { //WKT cannot be combined with TopoJSON
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=0,0,2,2&geometry=wkt&format=topojson").unwrap();
+            assert!(resp.status().is_client_error());
+        }
+
+        server.kill().unwrap();
+    }
+}
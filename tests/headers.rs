@@ -0,0 +1,82 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn headers() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.headers.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Plaintext request omits HSTS
+            let resp = reqwest::get("http://localhost:8000/api").unwrap();
+            assert!(resp.status().is_success());
+
+            assert!(resp.headers().get("Strict-Transport-Security").is_none());
+            assert_eq!(resp.headers().get("X-Content-Type-Options").unwrap(), "nosniff");
+            assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "DENY");
+        }
+
+        { //Request forwarded from TLS proxy includes HSTS
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api")
+                .header("X-Forwarded-Proto", "https")
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            assert_eq!(resp.headers().get("Strict-Transport-Security").unwrap(), "max-age=31536000; includeSubDomains");
+            assert_eq!(resp.headers().get("X-Content-Type-Options").unwrap(), "nosniff");
+            assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "DENY");
+        }
+
+        { //Error responses are covered as well
+            let resp = reqwest::get("http://localhost:8000/api/style/100").unwrap();
+            assert!(resp.status().is_client_error());
+
+            assert_eq!(resp.headers().get("X-Content-Type-Options").unwrap(), "nosniff");
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+extern crate reqwest; extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn idempotency() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "wilder"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let header = |resp: &reqwest::Response, name: &str| -> String {
+            String::from(resp.headers().get(name).unwrap().to_str().unwrap())
+        };
+
+        for replayed in vec!["false", "true"] { //Create Point - repeated with the same key
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("Idempotency-Key", "single")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(header(&resp, "X-Hecate-Delta"), "1");
+            assert_eq!(header(&resp, "X-Hecate-Features"), "1");
+            assert_eq!(header(&resp, "Idempotent-Replayed"), replayed);
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        for replayed in vec!["false", "true"] { //Create Points - repeated with the same key
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Creating Points",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "124" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "125" },
+                        "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("Idempotency-Key", "bulk")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(header(&resp, "X-Hecate-Delta"), "2");
+            assert_eq!(header(&resp, "X-Hecate-Features"), "2,3");
+            assert_eq!(header(&resp, "Idempotent-Replayed"), replayed);
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Keys are scoped to the user
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "126" },
+                    "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                }"#)
+                .basic_auth("wilder", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("Idempotency-Key", "single")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(header(&resp, "X-Hecate-Delta"), "3");
+            assert_eq!(header(&resp, "X-Hecate-Features"), "4");
+            assert_eq!(header(&resp, "Idempotent-Replayed"), "false");
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Without a key no headers are returned
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "127" },
+                    "geometry": { "type": "Point", "coordinates": [ 4, 4 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert!(resp.headers().get("X-Hecate-Delta").is_none());
+        }
+
+        { //Invalid Key
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "128" },
+                    "geometry": { "type": "Point", "coordinates": [ 5, 5 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("Idempotency-Key", "a".repeat(256))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Nothing was created by the repeated requests
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("SELECT id FROM geo ORDER BY id", &[]).unwrap();
+            let ids: Vec<i64> = rows.iter().map(|row| row.get(0)).collect();
+            assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+
+            let rows = conn.query("SELECT count(*) FROM deltas", &[]).unwrap();
+            let deltas: i64 = rows.get(0).get(0);
+            assert_eq!(deltas, 4);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn impersonate(id: i64) -> String {
+        let client = reqwest::Client::new();
+        let mut resp = client.post(&*format!("http://localhost:8000/api/user/{}/impersonate", id))
+            .basic_auth("ingalls", Some("yeaheh"))
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        let json_body: serde_json::value::Value = resp.json().unwrap();
+        assert_eq!(json_body["uid"], json!(id));
+
+        String::from(json_body["token"].as_str().unwrap())
+    }
+
+    #[test]
+    fn impersonate() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Admin
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create User
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls2&password=yeaheh&email=ingalls2@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Only admins can impersonate
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/user/1/impersonate")
+                .basic_auth("ingalls2", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Impersonating a missing user
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/user/100/impersonate")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        let token = impersonate(2);
+
+        { //Acts as the target user
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::COOKIE, format!("session={}", token))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(2));
+            assert_eq!(json_body["username"], json!("ingalls2"));
+        }
+
+        { //Cannot perform admin operations
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/user/2/admin")
+                .header(reqwest::header::COOKIE, format!("session={}", token))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 403,
+                "reason": "Impersonated sessions cannot perform admin operations",
+                "status": "Forbidden"
+            }));
+        }
+
+        { //Cannot exchange the token for a full session
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/session")
+                .header(reqwest::header::COOKIE, format!("session={}", token))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+        }
+
+        { //Impersonating an admin does not grant admin
+            let admin_token = impersonate(1);
+
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/2")
+                .header(reqwest::header::COOKIE, format!("session={}", admin_token))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+        }
+
+        { //Impersonation is audited
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("
+                SELECT access FROM users WHERE id = 2
+            ", &[]).unwrap();
+            let access: Option<String> = rows.get(0).get(0);
+            assert_eq!(access, None);
+
+            let rows = conn.query("
+                SELECT
+                    uid,
+                    impersonator,
+                    action,
+                    detail
+                FROM
+                    audit
+                ORDER BY
+                    id
+            ", &[]).unwrap();
+
+            let audit: Vec<(i64, i64, String, serde_json::value::Value)> = rows.iter().map(|row| {
+                (row.get(0), row.get(1), row.get(2), row.get(3))
+            }).collect();
+
+            assert_eq!(audit, vec![
+                (2, 1, String::from("impersonate"), json!({})),
+                (2, 1, String::from("impersonated"), json!({
+                    "method": "GET",
+                    "uri": "/api/user/info",
+                    "status": 200,
+                    "ip": "127.0.0.1"
+                })),
+                (2, 1, String::from("impersonated"), json!({
+                    "method": "PUT",
+                    "uri": "/api/user/2/admin",
+                    "status": 403,
+                    "ip": "127.0.0.1"
+                })),
+                (2, 1, String::from("impersonated"), json!({
+                    "method": "GET",
+                    "uri": "/api/user/session",
+                    "status": 403,
+                    "ip": "127.0.0.1"
+                })),
+                (1, 1, String::from("impersonate"), json!({})),
+                (1, 1, String::from("impersonated"), json!({
+                    "method": "GET",
+                    "uri": "/api/user/2",
+                    "status": 403,
+                    "ip": "127.0.0.1"
+                }))
+            ]);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,281 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn explain(query: &str) -> String {
+        let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+        //The table is far too small for the planner to choose an index on its own
+        conn.execute("SET enable_seqscan = off", &[]).unwrap();
+
+        let mut plan = String::new();
+        for row in conn.query(&*format!("EXPLAIN {}", query), &[]).unwrap().iter() {
+            let line: String = row.get(0);
+            plan.push_str(&line);
+            plan.push('\n');
+        }
+
+        plan
+    }
+
+    #[test]
+    fn index() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Basic Creation",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "building": { "levels": 3 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 1,1 ]
+                        }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "building": { "levels": 4 }
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 1.1,1.1 ]
+                        }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Create Index - Not Admin
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "property": "building.levels", "kind": "expression" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create Index - Invalid Property
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "property": "building'); DROP TABLE geo; --", "kind": "expression" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Property - keys may only contain a-z, 0-9, _, :, and -",
+                "status": "Bad Request"
+            }));
+            assert!(resp.status().is_client_error());
+        }
+
+        { //Create Index - Invalid Kind
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "property": "building.levels", "kind": "hash" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+        }
+
+        let filter = "SELECT id FROM geo WHERE (props #> '{building,levels}'::TEXT[]) = '3'::JSONB";
+
+        { //No index is used before creation
+            assert!(!explain(filter).contains("geo_props_idx_"));
+        }
+
+        { //Create Index
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "property": "building.levels", "kind": "expression" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "property": "building.levels",
+                "kind": "expression"
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Index - Duplicate
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "property": "building.levels", "kind": "expression" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+        }
+
+        { //List Indexes
+            let mut resp = reqwest::get("http://localhost:8000/api/indexes").unwrap();
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([{
+                "id": 1,
+                "property": "building.levels",
+                "kind": "expression"
+            }]));
+            assert!(resp.status().is_success());
+        }
+
+        { //Filtered query uses the index
+            assert!(explain(filter).contains("geo_props_idx_1"));
+        }
+
+        { //Filtered query returns only matching features
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?prop=building.levels%3D3").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            let features: Vec<&str> = body.trim_end_matches('\u{4}').lines().collect();
+            assert_eq!(features.len(), 1);
+
+            let feature: serde_json::value::Value = serde_json::from_str(features[0]).unwrap();
+            assert_eq!(feature["id"], json!(1));
+        }
+
+        { //Create GIN Index
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/index")
+                .body(r#"{ "property": "building", "kind": "gin" }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 2,
+                "property": "building",
+                "kind": "gin"
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        { //GIN indexed filters use containment
+            let plan = explain(r#"SELECT id FROM geo WHERE (props #> '{building}'::TEXT[]) @> '{"levels":4}'::JSONB AND (props #> '{building}'::TEXT[]) = '{"levels":4}'::JSONB"#);
+            assert!(plan.contains("geo_props_idx_2"));
+
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?prop=building%3D%7B%22levels%22%3A4%7D").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            let features: Vec<&str> = body.trim_end_matches('\u{4}').lines().collect();
+            assert_eq!(features.len(), 1);
+
+            let feature: serde_json::value::Value = serde_json::from_str(features[0]).unwrap();
+            assert_eq!(feature["id"], json!(2));
+        }
+
+        { //Delete Index
+            let client = reqwest::Client::new();
+            let mut resp = client.delete("http://localhost:8000/api/index/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Delete Index - Not Found
+            let client = reqwest::Client::new();
+            let resp = client.delete("http://localhost:8000/api/index/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Dropped index is no longer used
+            assert!(!explain(filter).contains("geo_props_idx_1"));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn input_srid() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        let coords = |id: i64| -> (f64, f64) {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("SELECT ST_X(geom), ST_Y(geom), ST_SRID(geom) FROM geo WHERE id = $1", &[&id]).unwrap();
+            assert_eq!(res.len(), 1);
+
+            let srid: i32 = res.get(0).get(2);
+            assert_eq!(srid, 4326);
+
+            (res.get(0).get(0), res.get(0).get(1))
+        };
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create a 3857 point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature?input_srid=3857")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {},
+                    "geometry": { "type": "Point", "coordinates": [ 1113194.9079327357, 1118889.9748579597 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+
+            let (x, y) = coords(1);
+            assert!((x - 10.0).abs() < 0.000001);
+            assert!((y - 10.0).abs() < 0.000001);
+        }
+
+        { //The delta records the reprojected geometry
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let coords = json_body["features"]["features"][0]["geometry"]["coordinates"].as_array().unwrap();
+            assert!((coords[0].as_f64().unwrap() - 10.0).abs() < 0.000001);
+            assert!((coords[1].as_f64().unwrap() - 10.0).abs() < 0.000001);
+        }
+
+        { //Create 3857 points in bulk
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features?input_srid=3857")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Create Points",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {},
+                        "geometry": { "type": "Point", "coordinates": [ -2226389.8158654715, 3503549.843504374 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+
+            let (x, y) = coords(2);
+            assert!((x - -20.0).abs() < 0.000001);
+            assert!((y - 30.0).abs() < 0.000001);
+        }
+
+        { //Coordinates default to 4326
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {},
+                    "geometry": { "type": "Point", "coordinates": [ 1.5, 2.5 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+
+            assert_eq!(coords(3), (1.5, 2.5));
+        }
+
+        { //Unknown SRID
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature?input_srid=999999")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {},
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid SRID - 999999 is not a known spatial reference system",
+                "status": "Bad Request"
+            }));
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("SELECT count(*) FROM geo", &[]).unwrap();
+            let count: i64 = res.get(0).get(0);
+            assert_eq!(count, 3);
+        }
+
+        server.kill().unwrap();
+    }
+}
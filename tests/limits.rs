@@ -0,0 +1,135 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn clone(user: Option<&str>) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        let req = client.get("http://localhost:8000/api/data/clone");
+
+        let req = match user {
+            None => req,
+            Some(user) => req.basic_auth(user, Some("yeaheh"))
+        };
+
+        req.send().unwrap()
+    }
+
+    #[test]
+    fn limits() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+
+            //Enough features that a clone cannot be buffered in full
+            //and remains in flight until the client reads it
+            conn.execute("
+                INSERT INTO geo (key, version, geom, props, deltas)
+                    SELECT
+                        'feature-' || n,
+                        1,
+                        ST_SetSRID(ST_MakePoint(0, 0), 4326),
+                        '{\"name\": \"A feature with a long enough name to pad out the clone response\"}'::JSONB,
+                        '{1}'::BIGINT[]
+                    FROM generate_series(1, 200000) n;
+            ", &[]).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.limits.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Admin
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //N concurrent clones are allowed and the N+1th is rejected
+            let first = clone(None);
+            assert!(first.status().is_success());
+
+            let second = clone(None);
+            assert!(second.status().is_success());
+
+            let mut third = clone(None);
+            assert_eq!(third.status().as_u16(), 503);
+            assert_eq!(third.headers().get("Retry-After").unwrap(), "10");
+
+            let json_body: serde_json::value::Value = third.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 503,
+                "reason": "Too many concurrent requests - please retry later",
+                "status": "Service Unavailable"
+            }));
+
+            //Admins are exempt from the limit
+            let admin = clone(Some("ingalls"));
+            assert!(admin.status().is_success());
+
+            drop(admin);
+            drop(first);
+            drop(second);
+        }
+
+        { //Places are released once the in flight clones finish
+            let mut status = 0;
+            for _ in 0..20 {
+                thread::sleep(Duration::from_millis(500));
+
+                let mut resp = clone(None);
+                status = resp.status().as_u16();
+
+                if status != 503 {
+                    assert_eq!(resp.text().unwrap().trim_end_matches('\u{4}').lines().count(), 200000);
+                    break;
+                }
+            }
+
+            assert_eq!(status, 200);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn list_limit() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.list.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "ingalls2", "ingalls3", "ingalls4"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        for i in 0..4 { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {{ "number": {} }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {}, 0 ] }}
+                }}"#, i, i))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Missing limit uses the default
+            let mut resp = reqwest::get("http://localhost:8000/api/users").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "2");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 2);
+        }
+
+        { //Oversized limit is clamped to the max
+            let mut resp = reqwest::get("http://localhost:8000/api/users?limit=10000000").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "3");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 3);
+        }
+
+        { //Limit within the max is used as is
+            let mut resp = reqwest::get("http://localhost:8000/api/users?limit=1").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "1");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 1);
+        }
+
+        { //Invalid limit
+            let mut resp = reqwest::get("http://localhost:8000/api/users?limit=0").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Limit - must be a positive integer",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Feature query uses the default
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "2");
+
+            let body = resp.text().unwrap();
+            assert_eq!(body.trim_end_matches('\u{4}').lines().count(), 2);
+        }
+
+        { //Feature query limit is clamped to the max
+            let mut resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,10,1&limit=100").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "3");
+
+            let body = resp.text().unwrap();
+            assert_eq!(body.trim_end_matches('\u{4}').lines().count(), 3);
+        }
+
+        { //Delta list uses the default
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "2");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 2);
+        }
+
+        { //Delta list limit is clamped to the max
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?limit=100").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "3");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 3);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn proxy() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        { //Requests forwarded by a trusted proxy
+            let mut server = Command::new("cargo").args(&[
+                "run",
+                "--",
+                "--config", env::current_dir().unwrap().join("tests/fixtures/config.proxy.json").to_str().unwrap()
+            ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            { //Create Username (ingalls)
+                let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+                assert_eq!(resp.text().unwrap(), "true");
+                assert!(resp.status().is_success());
+            }
+
+            { //Forwarded https scheme is honoured
+                let client = reqwest::Client::new();
+                let resp = client.get("http://localhost:8000/api")
+                    .header("X-Forwarded-For", "203.0.113.9")
+                    .header("X-Forwarded-Proto", "https")
+                    .send()
+                    .unwrap();
+                assert!(resp.status().is_success());
+
+                assert_eq!(resp.headers().get("Strict-Transport-Security").unwrap(), "max-age=31536000");
+            }
+
+            { //Session cookie is marked secure
+                let client = reqwest::Client::new();
+                let resp = client.get("http://localhost:8000/api/user/session")
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .header("X-Forwarded-Proto", "https")
+                    .send()
+                    .unwrap();
+                assert!(resp.status().is_success());
+
+                let cookie = resp.headers().get(reqwest::header::SET_COOKIE).unwrap().to_str().unwrap();
+                assert!(cookie.contains("Secure"));
+            }
+
+            server.kill().unwrap();
+            server.wait().unwrap();
+        }
+
+        { //Requests from a source that is not a trusted proxy
+            let mut server = Command::new("cargo").args(&[
+                "run",
+                "--",
+                "--config", env::current_dir().unwrap().join("tests/fixtures/config.proxy.untrusted.json").to_str().unwrap()
+            ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            { //Forwarded https scheme is ignored
+                let client = reqwest::Client::new();
+                let resp = client.get("http://localhost:8000/api")
+                    .header("X-Forwarded-For", "203.0.113.9")
+                    .header("X-Forwarded-Proto", "https")
+                    .send()
+                    .unwrap();
+                assert!(resp.status().is_success());
+
+                assert!(resp.headers().get("Strict-Transport-Security").is_none());
+            }
+
+            { //Session cookie is not marked secure
+                let client = reqwest::Client::new();
+                let resp = client.get("http://localhost:8000/api/user/session")
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .header("X-Forwarded-Proto", "https")
+                    .send()
+                    .unwrap();
+                assert!(resp.status().is_success());
+
+                let cookie = resp.headers().get(reqwest::header::SET_COOKIE).unwrap().to_str().unwrap();
+                assert!(!cookie.contains("Secure"));
+            }
+
+            server.kill().unwrap();
+        }
+    }
+}
@@ -0,0 +1,159 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn upload(user: &str, body: serde_json::value::Value) -> reqwest::Response {
+        let url = match body["type"].as_str() {
+            Some("FeatureCollection") => "http://localhost:8000/api/data/features",
+            _ => "http://localhost:8000/api/data/feature"
+        };
+
+        let client = reqwest::Client::new();
+        client.post(url)
+            .body(body.to_string())
+            .basic_auth(user, Some("yeaheh"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .unwrap()
+    }
+
+    fn point(n: i64) -> serde_json::value::Value {
+        json!({
+            "type": "Feature",
+            "action": "create",
+            "message": "Create a point",
+            "properties": { "number": n },
+            "geometry": { "type": "Point", "coordinates": [ n, n ] }
+        })
+    }
+
+    #[test]
+    fn quota() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.quota.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Users
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=admin&password=yeaheh&email=admin@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 2;
+            ", &[]).unwrap();
+        }
+
+        { //Creates up to the quota are allowed
+            let resp = upload("ingalls", json!({
+                "type": "FeatureCollection",
+                "message": "Two points",
+                "features": [ point(1), point(2) ]
+            }));
+            assert!(resp.status().is_success());
+
+            let resp = upload("ingalls", point(3));
+            assert!(resp.status().is_success());
+        }
+
+        { //Creates over the quota are rejected
+            let mut resp = upload("ingalls", point(4));
+            assert_eq!(resp.status().as_u16(), 429);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 429,
+                "status": "Too Many Requests",
+                "error": "QUOTA_EXCEEDED",
+                "reason": "Quota Exceeded - users can create 3 features every 86400 seconds",
+                "quota": 3,
+                "used": 3
+            }));
+
+            let resp = upload("ingalls", json!({
+                "type": "FeatureCollection",
+                "message": "One more point",
+                "features": [ point(4) ]
+            }));
+            assert_eq!(resp.status().as_u16(), 429);
+        }
+
+        { //Modifications are not counted
+            let resp = upload("ingalls", json!({
+                "id": 1,
+                "type": "Feature",
+                "action": "modify",
+                "version": 1,
+                "message": "Modify a point",
+                "properties": { "number": 10 },
+                "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        { //Admins are exempt
+            for n in 10..15 {
+                let resp = upload("admin", point(n));
+                assert!(resp.status().is_success());
+            }
+        }
+
+        { //The quota resets once creates fall outside of the window
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE deltas SET created = created - INTERVAL '2 days' WHERE uid = 1;
+            ", &[]).unwrap();
+
+            let resp = upload("ingalls", point(4));
+            assert!(resp.status().is_success());
+        }
+
+        server.kill().unwrap();
+    }
+}
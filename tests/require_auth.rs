@@ -0,0 +1,92 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn require_auth() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--auth", env::current_dir().unwrap().join("tests/fixtures/auth.require.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Healthcheck remains open
+            let mut resp = reqwest::get("http://localhost:8000/").unwrap();
+            assert_eq!(resp.text().unwrap(), "Hello World!");
+            assert!(resp.status().is_success());
+        }
+
+        { //Public endpoints require a login
+            let mut resp = reqwest::get("http://localhost:8000/api").unwrap();
+            assert_eq!(resp.text().unwrap(), "{\"code\":401,\"reason\":\"You must be logged in to access this resource\",\"status\":\"Unauthorized\"}");
+            assert_eq!(resp.status().as_u16(), 401);
+
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1").unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Create Username (ingalls)
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                INSERT INTO users (username, password, email, meta)
+                    VALUES ('ingalls', crypt('yeaheh', gen_salt('bf', 10)), 'ingalls@protonmail.com', '{}'::JSONB);
+            ", &[]).unwrap();
+        }
+
+        { //Public endpoints succeed once logged in
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let resp = client.get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn revalidate(query: &str) -> Vec<serde_json::value::Value> {
+        let client = reqwest::Client::new();
+        let mut resp = client.get(&*format!("http://localhost:8000/api/data/features/revalidate{}", query))
+            .basic_auth("ingalls", Some("yeaheh"))
+            .send()
+            .unwrap();
+
+        assert!(resp.status().is_success());
+
+        let body = resp.text().unwrap();
+        assert!(body.ends_with("\u{4}"));
+
+        body.trim_end_matches('\u{4}').lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn revalidate_features() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+
+            //Features written directly so that they bypass import validation
+            conn.execute(r#"
+                INSERT INTO geo (version, geom, props, deltas) VALUES
+                    (1, ST_SetSRID(ST_GeomFromText('POINT(0 0)'), 4326), '{"source": "test", "number": 1, "street": [{ "display": "Main St" }]}', '{1}'),
+                    (1, ST_SetSRID(ST_GeomFromText('POLYGON((10 10, 12 12, 12 10, 10 12, 10 10))'), 4326), '{"source": "test", "number": 2, "street": [{ "display": "Main St" }]}', '{1}'),
+                    (1, ST_SetSRID(ST_GeomFromText('POINT(200 0)'), 4326), '{"source": "test", "number": 3, "street": [{ "display": "Main St" }]}', '{1}'),
+                    (1, ST_SetSRID(ST_GeomFromText('POINT(0.5 0.5)'), 4326), '{"name": "Not an address"}', '{1}');
+            "#, &[]).unwrap();
+
+            conn.execute("
+                INSERT INTO bounds (name, geom) VALUES
+                    ('null_island', ST_Multi(ST_MakeEnvelope(-1, -1, 1, 1, 4326)));
+            ", &[]).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--schema", env::current_dir().unwrap().join("tests/fixtures/source_schema.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--schema", env::current_dir().unwrap().join("tests/fixtures/source_schema.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Admin Only
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/features/revalidate")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //All Features
+            let report = revalidate("");
+
+            assert_eq!(report.len(), 3);
+
+            assert_eq!(report[0]["id"], json!(2));
+            assert_eq!(report[0]["version"], json!(1));
+            assert_eq!(report[0]["reasons"].as_array().unwrap().len(), 1);
+            assert!(report[0]["reasons"][0].as_str().unwrap().starts_with("Invalid Geometry - Self-intersection"));
+
+            assert_eq!(report[1], json!({
+                "id": 3,
+                "key": null,
+                "version": 1,
+                "reasons": [ "longitude > 180" ]
+            }));
+
+            assert_eq!(report[2], json!({
+                "id": 4,
+                "key": null,
+                "version": 1,
+                "reasons": [ "Failed to Match Schema" ]
+            }));
+        }
+
+        { //BBOX
+            let report = revalidate("?bbox=9,9,13,13");
+
+            assert_eq!(report.len(), 1);
+            assert_eq!(report[0]["id"], json!(2));
+        }
+
+        { //Bound
+            let report = revalidate("?bound=null_island");
+
+            assert_eq!(report, vec![json!({
+                "id": 4,
+                "key": null,
+                "version": 1,
+                "reasons": [ "Failed to Match Schema" ]
+            })]);
+
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/features/revalidate?bound=missing")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Nothing is written
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT count(*) FROM geo WHERE version = 1
+            ", &[]).unwrap();
+
+            let count: i64 = res.get(0).get(0);
+            assert_eq!(count, 4);
+        }
+
+        server.kill().unwrap();
+    }
+}
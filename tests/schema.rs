@@ -1,5 +1,6 @@
 extern crate reqwest;
 extern crate postgres;
+#[macro_use] extern crate serde_json;
 
 #[cfg(test)]
 mod test {
@@ -11,6 +12,7 @@ mod test {
     use std::time::Duration;
     use std::thread;
     use reqwest;
+    use serde_json;
 
     #[test]
     fn schema() {
@@ -60,6 +62,25 @@ mod test {
             assert!(resp.status().is_success());
         }
 
+        { //Get Schema in use with metadata
+            let mut resp = reqwest::get("http://localhost:8000/api/schema?meta=true").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["title"], json!("Address source"));
+            assert_eq!(json_body["x-hecate"], json!({
+                "geometry": {
+                    "types": ["Point", "MultiPoint", "LineString", "MultiLineString", "Polygon", "MultiPolygon", "GeometryCollection"],
+                    "srid": 4326
+                },
+                "properties": {
+                    "mode": "reject",
+                    "allow": null,
+                    "deny": null
+                }
+            }));
+        }
+
         { //Create Point Failing Schema Validation
             let client = reqwest::Client::new();
             let mut resp = client.post("http://localhost:8000/api/data/feature")
@@ -0,0 +1,150 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn session_expiry() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.session.expiry.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let session: String = { //Create Session
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/session")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let cookie = resp.headers().get(reqwest::header::SET_COOKIE).unwrap().to_str().unwrap();
+            String::from(cookie.split(';').next().unwrap().trim_start_matches("session="))
+        };
+
+        let api: String = { //Create API Token
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/user/token")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+            assert!(resp.headers().get(reqwest::header::SET_COOKIE).is_none());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["expiry"], json!(7200));
+            String::from(json_body["token"].as_str().unwrap())
+        };
+
+        { //Each token gets the expiry of its kind
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT
+                    token,
+                    name,
+                    kind,
+                    EXTRACT(EPOCH FROM expiry - created)::BIGINT
+                FROM users_tokens
+                ORDER BY created, kind DESC
+            ", &[]).unwrap();
+
+            let tokens: Vec<(String, String, String, i64)> = res.iter().map(|row| (row.get(0), row.get(1), row.get(2), row.get(3))).collect();
+            assert_eq!(tokens, vec![
+                (session.clone(), String::from("Session Token"), String::from("session"), 600),
+                (api.clone(), String::from("API Token"), String::from("api"), 7200)
+            ]);
+        }
+
+        let session_request = || {
+            let client = reqwest::Client::new();
+            client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::COOKIE, format!("session={}", session))
+                .send()
+                .unwrap()
+                .status()
+                .as_u16()
+        };
+
+        let api_request = || {
+            let client = reqwest::Client::new();
+            client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::AUTHORIZATION, format!("Bearer {}", api))
+                .send()
+                .unwrap()
+                .status()
+                .as_u16()
+        };
+
+        { //Both tokens are accepted before they expire
+            assert_eq!(session_request(), 200);
+            assert_eq!(api_request(), 200);
+        }
+
+        { //Expired tokens are only accepted within the grace of their kind
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users_tokens SET expiry = now() - INTERVAL '10 minutes'
+            ", &[]).unwrap();
+
+            assert_eq!(session_request(), 200);
+            assert_eq!(api_request(), 401);
+        }
+
+        { //Session token is rejected once its grace has passed
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users_tokens SET expiry = now() - INTERVAL '2 hours'
+            ", &[]).unwrap();
+
+            assert_eq!(session_request(), 401);
+        }
+
+        server.kill().unwrap();
+    }
+}
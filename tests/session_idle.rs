@@ -0,0 +1,150 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn session_idle() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.session.idle.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "ingalls2"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Set ingalls as admin
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        let session = |username: &str| -> String {
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/session")
+                .basic_auth(username, Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let cookie = resp.headers().get(reqwest::header::SET_COOKIE).unwrap().to_str().unwrap();
+            String::from(cookie.split(';').next().unwrap().trim_start_matches("session="))
+        };
+
+        let request = |token: &String| -> u16 {
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::COOKIE, format!("session={}", token))
+                .send()
+                .unwrap();
+
+            resp.status().as_u16()
+        };
+
+        let active = session("ingalls");
+        let idle = session("ingalls2");
+
+        { //Both tokens were created an hour ago
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users_tokens SET created = now() - INTERVAL '1 hour'
+            ", &[]).unwrap();
+        }
+
+        { //Using a token records its last use
+            assert_eq!(request(&active), 200);
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT last_used IS NOT NULL FROM users_tokens WHERE token = $1
+            ", &[&active]).unwrap();
+            let used: bool = res.get(0).get(0);
+            assert!(used);
+        }
+
+        { //Idle token is reaped, the active token survives
+            thread::sleep(Duration::from_secs(20));
+
+            assert_eq!(request(&idle), 401);
+            assert_eq!(request(&active), 200);
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT count(*) FROM users_tokens WHERE token = $1
+            ", &[&idle]).unwrap();
+            let count: i64 = res.get(0).get(0);
+            assert_eq!(count, 0);
+        }
+
+        { //Active users
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/active?window=60")
+                .header(reqwest::header::COOKIE, format!("session={}", active))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let users = json_body.as_array().unwrap();
+            assert_eq!(users.len(), 1);
+            assert_eq!(users[0]["id"], json!(1));
+            assert_eq!(users[0]["username"], json!("ingalls"));
+            assert!(users[0]["last_used"].is_string());
+        }
+
+        { //Active users requires admin
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/active")
+                .basic_auth("ingalls2", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn session_sliding() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.session.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let token: String = { //Create Session
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/session")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let cookie = resp.headers().get(reqwest::header::SET_COOKIE).unwrap().to_str().unwrap();
+            String::from(cookie.split(';').next().unwrap().trim_start_matches("session="))
+        };
+
+        let request = |token: &String| {
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::COOKIE, format!("session={}", token))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            resp.headers().get(reqwest::header::SET_COOKIE).map(|cookie| String::from(cookie.to_str().unwrap()))
+        };
+
+        let remaining = || {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT EXTRACT(EPOCH FROM expiry - now())::BIGINT FROM users_tokens
+            ", &[]).unwrap();
+            let remaining: i64 = res.get(0).get(0);
+            remaining
+        };
+
+        { //Token far from expiry is not extended
+            assert_eq!(request(&token), None);
+            assert!(remaining() <= 4 * 3600);
+        }
+
+        { //Token near expiry is extended
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users_tokens SET expiry = now() + INTERVAL '10 minutes', created = now() - INTERVAL '4 hours'
+            ", &[]).unwrap();
+
+            let cookie = request(&token).unwrap();
+            assert!(cookie.starts_with(&format!("session={}", token)));
+            assert!(remaining() > 3 * 3600);
+        }
+
+        { //Token is not extended past its max lifetime
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users_tokens SET expiry = now() + INTERVAL '10 minutes', created = now() - INTERVAL '2 days'
+            ", &[]).unwrap();
+
+            assert_eq!(request(&token), None);
+            assert!(remaining() <= 600);
+        }
+
+        { //Extension is capped at the max lifetime
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users_tokens SET expiry = now() + INTERVAL '10 minutes', created = now() - INTERVAL '23 hours'
+            ", &[]).unwrap();
+
+            assert!(request(&token).is_some());
+
+            let remaining = remaining();
+            assert!(remaining > 600 && remaining <= 3600);
+        }
+
+        server.kill().unwrap();
+    }
+}
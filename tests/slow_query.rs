@@ -0,0 +1,112 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use std::io::BufReader;
+    use postgres::{Connection, TlsMode};
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    fn features(request_id: &str) -> u16 {
+        let client = reqwest::Client::new();
+        let resp = client.get("http://localhost:8000/api/data/features")
+            .query(&[("filter", "building.levels > 3")])
+            .header("X-Request-Id", request_id)
+            .send()
+            .unwrap();
+
+        resp.status().as_u16()
+    }
+
+    #[test]
+    fn slow_query() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute(r#"
+                INSERT INTO geo (version, geom, props, deltas) VALUES
+                    (1, ST_SetSRID(ST_MakePoint(0, 0), 4326), '{ "building": { "levels": 5 } }'::JSONB, ARRAY[]::BIGINT[]);
+            "#, &[]).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.timing.slow.json").to_str().unwrap()
+        ]).stdout(Stdio::piped()).spawn().unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let stdout = server.stdout.take().unwrap();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => { tx.send(line).ok(); },
+                    Err(_) => break
+                };
+            }
+        });
+
+        thread::sleep(Duration::from_secs(1));
+
+        { //Fast query is not logged
+            assert_eq!(features("fast-1"), 200);
+        }
+
+        { //Query held up by a lock on the geo table is logged
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let trans = conn.transaction().unwrap();
+            trans.execute("LOCK TABLE geo IN ACCESS EXCLUSIVE MODE", &[]).unwrap();
+
+            let request = thread::spawn(|| features("slow-1"));
+
+            thread::sleep(Duration::from_millis(500));
+            trans.commit().unwrap();
+
+            assert_eq!(request.join().unwrap(), 200);
+        }
+
+        thread::sleep(Duration::from_millis(500));
+
+        let lines: Vec<String> = rx.try_iter().filter(|line| line.starts_with("WARN: Slow Query:")).collect();
+
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("on GET /api/data/features?filter=building.levels"));
+        assert!(lines[0].ends_with("(request slow-1)"));
+
+        server.kill().unwrap();
+    }
+}
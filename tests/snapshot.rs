@@ -0,0 +1,401 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn snapshot() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "nick"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "building": "residental" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Modify Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Fix typo",
+                    "properties": { "building": "residential" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Create Style
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/style")
+                .body(r#"{
+                    "name": "Awesome Style",
+                    "style": "I am a style"
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "1");
+        }
+
+        { //Create Meta
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/meta/layers")
+                .body(r#"{ "value": [ "buildings" ] }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Tag Point
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/1/tags/needs-review")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Comment on Point
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature/1/comments")
+                .body(json!({ "comment": "Is this a house?" }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Snapshot requires admin
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/snapshot")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        let snapshot: String;
+        { //Snapshot
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/snapshot")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            snapshot = resp.text().unwrap();
+
+            let records: Vec<serde_json::Value> = snapshot.trim_end_matches('\u{4}').lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+            let types: Vec<&str> = records.iter().map(|record| record["type"].as_str().unwrap()).collect();
+            assert_eq!(types, vec!["snapshot", "meta", "style", "delta", "delta", "feature", "tag", "comment"]);
+
+            assert_eq!(records[0]["version"], json!(1));
+            assert_eq!(records[5]["id"], json!(1));
+            assert_eq!(records[5]["version"], json!(2));
+            assert_eq!(records[5]["deltas"], json!([1, 2]));
+            assert_eq!(records[5]["properties"], json!({ "building": "residential" }));
+
+            assert_eq!(records[6]["id"], json!(1));
+            assert_eq!(records[6]["tag"], json!("needs-review"));
+
+            assert_eq!(records[7]["id"], json!(1));
+            assert_eq!(records[7]["feature"], json!(1));
+            assert_eq!(records[7]["comment"], json!("Is this a house?"));
+        }
+
+        let history: serde_json::Value;
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+            history = resp.json().unwrap();
+        }
+
+        { //Restoring over a dataset that is not empty fails
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/restore")
+                .body(snapshot.clone())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 409);
+        }
+
+        server.kill().unwrap();
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //A dataset with only tags or comments is not empty
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            for table in vec!["feature_tags", "feature_comments"] {
+                if table == "feature_tags" {
+                    conn.execute("INSERT INTO feature_tags (id, tag, uid, created) VALUES (1, 'orphan', 1, NOW())", &[]).unwrap();
+                } else {
+                    conn.execute("INSERT INTO feature_comments (feature, uid, comment, created) VALUES (1, 1, 'orphan', NOW())", &[]).unwrap();
+                }
+
+                let client = reqwest::Client::new();
+                let resp = client.post("http://localhost:8000/api/data/restore")
+                    .body(snapshot.clone())
+                    .basic_auth("ingalls", Some("yeaheh"))
+                    .send()
+                    .unwrap();
+
+                assert_eq!(resp.status().as_u16(), 409);
+
+                conn.execute(&*format!("DELETE FROM {}", table), &[]).unwrap();
+            }
+        }
+
+        { //Snapshot must start with a header
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/restore")
+                .body(snapshot.lines().skip(1).collect::<Vec<&str>>().join("\n"))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Invalid Snapshot - must start with a snapshot header"));
+        }
+
+        { //Restore into an empty dataset
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/restore")
+                .body(snapshot.clone())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "meta": 1,
+                "styles": 1,
+                "deltas": 2,
+                "features": 1,
+                "tags": 1,
+                "comments": 1
+            }));
+        }
+
+        { //Restored dataset matches the snapshot
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/snapshot")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let restored = resp.text().unwrap();
+            assert_eq!(restored.lines().skip(1).collect::<Vec<&str>>(), snapshot.lines().skip(1).collect::<Vec<&str>>());
+        }
+
+        { //Restored history matches
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/history").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, history);
+        }
+
+        { //Restore can be forced over a dataset that is not empty
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/restore?force=true")
+                .body(snapshot.clone())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut resp = client.get("http://localhost:8000/api/data/snapshot")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let restored = resp.text().unwrap();
+            assert_eq!(restored.lines().skip(1).collect::<Vec<&str>>(), snapshot.lines().skip(1).collect::<Vec<&str>>());
+        }
+
+        { //New comments continue after the restored ids
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/comments")
+                .body(json!({ "comment": "It is a house" }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(2));
+        }
+
+        { //New features continue after the restored ids
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "building": "yes" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["properties"], json!({ "building": "yes" }));
+
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/3").unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn stats_numeric() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        { //Heights of 1, 2, 5 & 10 along with a non-numeric height and a feature without one
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute(r#"
+                INSERT INTO geo (version, geom, props, deltas) VALUES
+                    (1, ST_SetSRID(ST_MakePoint(0, 0), 4326), '{ "height": 1 }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 1), 4326), '{ "height": 2.0 }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 2), 4326), '{ "height": 10 }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 3), 4326), '{ "height": 5 }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 4), 4326), '{ "height": "tall" }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 5), 4326), '{ "name": "low" }'::JSONB, ARRAY[]::BIGINT[])
+            "#, &[]).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/stats/numeric?property=height").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut stats: serde_json::Value = resp.json().unwrap();
+
+            let p99 = stats["percentiles"]["p99"].as_f64().unwrap();
+            assert!((p99 - 9.85).abs() < 0.000001);
+            stats["percentiles"].as_object_mut().unwrap().remove("p99");
+
+            assert_eq!(stats, json!({
+                "property": "height",
+                "count": 4,
+                "ignored": 1,
+                "min": 1,
+                "max": 10,
+                "avg": 4.5,
+                "percentiles": {
+                    "p25": 1.75,
+                    "p50": 3.5,
+                    "p75": 6.25,
+                    "p90": 8.5
+                }
+            }));
+        }
+
+        { //A property without numeric values
+            let mut resp = reqwest::get("http://localhost:8000/api/data/stats/numeric?property=name").unwrap();
+            assert!(resp.status().is_success());
+
+            let stats: serde_json::Value = resp.json().unwrap();
+            assert_eq!(stats["count"], json!(0));
+            assert_eq!(stats["ignored"], json!(1));
+            assert_eq!(stats["min"], json!(null));
+            assert_eq!(stats["percentiles"]["p50"], json!(null));
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/stats/numeric").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+            assert_eq!(resp.text().unwrap(), "{\"code\":400,\"reason\":\"property param required\",\"status\":\"Bad Request\"}");
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn stats_values() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        { //Three distinct building values along with a feature without one
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute(r#"
+                INSERT INTO geo (version, geom, props, deltas) VALUES
+                    (1, ST_SetSRID(ST_MakePoint(0, 0), 4326), '{ "building": "yes" }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 1), 4326), '{ "building": "yes" }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 2), 4326), '{ "building": "yes" }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 3), 4326), '{ "building": "house" }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(10, 10), 4326), '{ "building": "house" }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(10, 11), 4326), '{ "building": "garage" }'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_SetSRID(ST_MakePoint(0, 5), 4326), '{ "name": "low" }'::JSONB, ARRAY[]::BIGINT[])
+            "#, &[]).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Distinct values, most frequent first
+            let mut resp = reqwest::get("http://localhost:8000/api/data/properties/building/values").unwrap();
+            assert!(resp.status().is_success());
+
+            let values: serde_json::Value = resp.json().unwrap();
+            assert_eq!(values, json!({
+                "property": "building",
+                "distinct": 3,
+                "values": [
+                    { "value": "yes", "count": 3 },
+                    { "value": "house", "count": 2 },
+                    { "value": "garage", "count": 1 }
+                ]
+            }));
+        }
+
+        { //Capped by limit
+            let mut resp = reqwest::get("http://localhost:8000/api/data/properties/building/values?limit=2").unwrap();
+            assert!(resp.status().is_success());
+
+            let values: serde_json::Value = resp.json().unwrap();
+            assert_eq!(values, json!({
+                "property": "building",
+                "distinct": 3,
+                "values": [
+                    { "value": "yes", "count": 3 },
+                    { "value": "house", "count": 2 }
+                ]
+            }));
+        }
+
+        { //Scoped to a bbox
+            let mut resp = reqwest::get("http://localhost:8000/api/data/properties/building/values?bbox=-1,-1,1,6").unwrap();
+            assert!(resp.status().is_success());
+
+            let values: serde_json::Value = resp.json().unwrap();
+            assert_eq!(values, json!({
+                "property": "building",
+                "distinct": 2,
+                "values": [
+                    { "value": "yes", "count": 3 },
+                    { "value": "house", "count": 1 }
+                ]
+            }));
+        }
+
+        { //A property no feature has
+            let mut resp = reqwest::get("http://localhost:8000/api/data/properties/shop/values").unwrap();
+            assert!(resp.status().is_success());
+
+            let values: serde_json::Value = resp.json().unwrap();
+            assert_eq!(values, json!({
+                "property": "shop",
+                "distinct": 0,
+                "values": []
+            }));
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/properties/building%20type/values").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/properties/building/values?limit=0").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
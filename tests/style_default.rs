@@ -0,0 +1,186 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn style_default() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "wilder", "boss"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE username = 'boss';
+            ", &[]).unwrap();
+        }
+
+        for name in vec!["Official Style", "Winter Style", "Private Style"] { //Create Styles owned by ingalls
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/style")
+                .body(format!(r#"{{
+                    "name": "{}",
+                    "style": "I am a style"
+                }}"#, name))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        for id in vec![1, 2] { //Mark Styles 1 & 2 as Public
+            let client = reqwest::Client::new();
+            let resp = client.post(&*format!("http://localhost:8000/api/style/{}/public", id))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //No default style
+            let mut resp = reqwest::get("http://localhost:8000/api/style/default").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Default Style Not Set"));
+        }
+
+        { //Only admins can set the default style
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/style/1/default")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+        }
+
+        { //Private styles cannot be the default style
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/style/3/default")
+                .basic_auth("boss", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Default Style Must Be Public"));
+        }
+
+        { //Set default style
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/style/1/default")
+                .basic_auth("boss", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Anyone gets default style
+            let mut resp = reqwest::get("http://localhost:8000/api/style/default").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(1));
+            assert_eq!(json_body["name"], json!("Official Style"));
+        }
+
+        { //Swap default style
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/style/2/default")
+                .basic_auth("boss", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/style/default").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(2));
+            assert_eq!(json_body["name"], json!("Winter Style"));
+        }
+
+        { //Default style is referenced by the server meta
+            let client = reqwest::Client::new();
+            let mut resp = client.delete("http://localhost:8000/api/style/2")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 409);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["references"]["meta"], json!(["style.default"]));
+        }
+
+        { //Unset default style
+            let client = reqwest::Client::new();
+            let resp = client.delete("http://localhost:8000/api/style/default")
+                .basic_auth("boss", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let resp = reqwest::get("http://localhost:8000/api/style/default").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let resp = reqwest::get("http://localhost:8000/api/style/2").unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -268,6 +268,69 @@ mod test {
             assert!(resp.status().is_success());
         }
 
+        { //Reference Style 1 as a server default and as ingalls' default
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                INSERT INTO meta (key, value) VALUES ('default_style', '{ \"style\": 2, \"zoom\": 4 }'::JSONB)
+            ", &[]).unwrap();
+
+            conn.execute("
+                UPDATE users SET meta = '{ \"style\": 2 }'::JSONB WHERE username = 'ingalls'
+            ", &[]).unwrap();
+        }
+
+        { //Delete Referenced Style - refused
+            let client = reqwest::Client::new();
+            let mut resp = client.delete("http://localhost:8000/api/style/2")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), r#"{"code":409,"reason":"Style Is Referenced - pass force=true to remove the references and delete","references":{"meta":["default_style"],"users":["ingalls"]},"status":"Conflict"}"#);
+            assert_eq!(resp.status().as_u16(), 409);
+        }
+
+        { //Referenced Style still exists
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/style/2")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        { //Delete Referenced Style - forced
+            let client = reqwest::Client::new();
+            let mut resp = client.delete("http://localhost:8000/api/style/2?force=true")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //References have been removed
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let res = conn.query("
+                SELECT value::TEXT FROM meta WHERE key = 'default_style'
+            ", &[]).unwrap();
+            let value: String = res.get(0).get(0);
+            assert_eq!(value, r#"{"zoom": 4}"#);
+
+            let res = conn.query("
+                SELECT meta::TEXT FROM users WHERE username = 'ingalls'
+            ", &[]).unwrap();
+            let meta: String = res.get(0).get(0);
+            assert_eq!(meta, "{}");
+
+            let res = conn.query("
+                SELECT count(*) FROM styles WHERE id = 2
+            ", &[]).unwrap();
+            let count: i64 = res.get(0).get(0);
+            assert_eq!(count, 0);
+        }
+
         server.kill().unwrap();
     }
 }
@@ -0,0 +1,191 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn styles_access() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "wilder", "boss"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE username = 'boss';
+            ", &[]).unwrap();
+        }
+
+        for name in vec!["Private Style", "Public Style"] { //Create Styles owned by ingalls
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/style")
+                .body(format!(r#"{{
+                    "name": "{}",
+                    "style": "I am a style"
+                }}"#, name))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Mark Style 2 as Public
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/style/2/public")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Owner gets private style
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/style/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["name"], json!("Private Style"));
+            assert_eq!(json_body["public"], json!(false));
+        }
+
+        { //Non-owner gets private style
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/style/1")
+                .basic_auth("wilder", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 404,
+                "reason": "Style Not Found",
+                "status": "Not Found"
+            }));
+        }
+
+        { //Anonymous gets private style
+            let resp = reqwest::get("http://localhost:8000/api/style/1").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Admin gets private style
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/style/1")
+                .basic_auth("boss", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["name"], json!("Private Style"));
+        }
+
+        { //Anyone gets public style
+            let mut resp = reqwest::get("http://localhost:8000/api/style/2").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["name"], json!("Public Style"));
+
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/style/2")
+                .basic_auth("wilder", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Non-owner lists only public styles
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/styles/1")
+                .basic_auth("wilder", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let names: Vec<serde_json::Value> = json_body.as_array().unwrap().iter().map(|style| style["name"].clone()).collect();
+            assert_eq!(names, vec![json!("Public Style")]);
+        }
+
+        { //Admin lists all styles of a user
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/styles/1")
+                .basic_auth("boss", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let names: Vec<serde_json::Value> = json_body.as_array().unwrap().iter().map(|style| style["name"].clone()).collect();
+            assert_eq!(names, vec![json!("Private Style"), json!("Public Style")]);
+        }
+
+        { //Public list only contains public styles
+            let mut resp = reqwest::get("http://localhost:8000/api/styles").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let names: Vec<serde_json::Value> = json_body.as_array().unwrap().iter().map(|style| style["name"].clone()).collect();
+            assert_eq!(names, vec![json!("Public Style")]);
+        }
+
+        server.kill().unwrap();
+    }
+}
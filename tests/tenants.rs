@@ -0,0 +1,484 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::io::BufReader;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::sync::mpsc;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn tenants() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "nick", "admin"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //ingalls is in tenant 1, nick & admin are in tenant 2
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET tenant = 1 WHERE id = 1;
+            ", &[]).unwrap();
+            conn.execute("
+                UPDATE users SET tenant = 2, access = 'admin' WHERE id = 3;
+            ", &[]).unwrap();
+            conn.execute("
+                UPDATE users SET tenant = 2 WHERE id = 2;
+            ", &[]).unwrap();
+        }
+
+        { //Create a feature in tenant 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "key": "shop-1",
+                    "message": "Create Point",
+                    "properties": { "shop": true },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //The feature is visible within its tenant
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/feature/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Another tenant cannot see the feature
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/feature/1")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 404,
+                "reason": "Not Found",
+                "status": "Not Found"
+            }));
+        }
+
+        { //Another tenant cannot find the feature by key
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/feature?key=shop-1")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Another tenant does not receive the feature in a bbox query
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap().trim_end_matches('\u{4}').lines().count(), 0);
+        }
+
+        { //Another tenant does not see the delta that created the feature
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/deltas")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([]));
+        }
+
+        { //Another tenant cannot modify the feature
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "key": "shop-1",
+                    "message": "Modify Point",
+                    "properties": { "shop": false },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["message"], json!("Feature Not Found"));
+        }
+
+        { //Another tenant cannot delete the feature
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "delete",
+                    "message": "Delete Point",
+                    "properties": null,
+                    "geometry": null
+                }"#)
+                .basic_auth("nick", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Another tenant cannot take over the key of the feature
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "key": "shop-1",
+                    "force": true,
+                    "message": "Create Point",
+                    "properties": { "shop": false },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("admin", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["message"], json!("Duplicate Key Value"));
+        }
+
+        { //Only admins can cross tenants
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/feature/1")
+                .basic_auth("nick", Some("yeaheh"))
+                .header("X-Hecate-Tenant", "*")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 403);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 403,
+                "reason": "Only admins can cross tenants",
+                "status": "Forbidden"
+            }));
+        }
+
+        { //An admin crossing tenants can see & modify the feature
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/feature/1")
+                .basic_auth("admin", Some("yeaheh"))
+                .header("X-Hecate-Tenant", "*")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "key": "shop-1",
+                    "message": "Modify Point",
+                    "properties": { "shop": false },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("admin", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("X-Hecate-Tenant", "*")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //The feature remains in its tenant after an admin edit
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/feature/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(2));
+        }
+
+        { //Clone only returns the features of the tenant
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/clone")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap().trim_end_matches('\u{4}').lines().count(), 1);
+
+            let mut resp = client.get("http://localhost:8000/api/data/clone")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap().trim_end_matches('\u{4}').lines().count(), 0);
+        }
+
+        { //Tiles only contain the features of the tenant
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/1/0/debug")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["features"].as_array().unwrap().len(), 1);
+            assert_eq!(json_body["features"][0]["id"], json!(1));
+
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/1/0/debug")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["features"].as_array().unwrap().len(), 0);
+
+            let mut tile = Vec::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/1/0")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+            resp.copy_to(&mut tile).unwrap();
+            assert!(tile.len() > 0);
+
+            //The tile cached for tenant 1 must not be served to tenant 2
+            let mut tile = Vec::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/1/0")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+            resp.copy_to(&mut tile).unwrap();
+            assert_eq!(tile.len(), 0);
+        }
+
+        { //Create a feature in tenant 2
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "shop": false },
+                    "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                }"#)
+                .basic_auth("admin", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        let not_found = json!({
+            "code": 404,
+            "reason": "Feature Not Found",
+            "status": "Not Found"
+        });
+
+        { //Another tenant cannot merge the feature
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let rows = conn.query("SELECT id FROM geo WHERE tenant = 2", &[]).unwrap();
+            let own: i64 = rows.get(0).get(0);
+
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features/merge")
+                .body(json!({ "message": "Merge Points", "a": own, "b": 1 }).to_string())
+                .basic_auth("admin", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, not_found);
+        }
+
+        { //Another tenant cannot split the feature
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature/1/split")
+                .body(r#"{
+                    "message": "Split Point",
+                    "splitter": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("admin", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, not_found);
+        }
+
+        { //Another tenant cannot restore the feature or learn its version
+            let client = reqwest::Client::new();
+            for current in vec![1, 2] {
+                let mut resp = client.post("http://localhost:8000/api/data/feature/1/restore?version=1")
+                    .body(json!({ "message": "Revert", "current": current }).to_string())
+                    .basic_auth("admin", Some("yeaheh"))
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .send()
+                    .unwrap();
+
+                assert_eq!(resp.status().as_u16(), 404);
+
+                let json_body: serde_json::value::Value = resp.json().unwrap();
+                assert_eq!(json_body, not_found);
+            }
+        }
+
+        { //The feature is unchanged
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/feature/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(2));
+        }
+
+        let (tx, rx) = mpsc::channel();
+
+        { //Open a stream as tenant 2
+            let resp = reqwest::Client::builder()
+                .timeout(None)
+                .build()
+                .unwrap()
+                .get("http://localhost:8000/api/delta/stream")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            thread::spawn(move || {
+                for line in BufReader::new(resp).lines() {
+                    if tx.send(line.unwrap()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        for username in vec!["ingalls", "nick"] { //Create a point in each tenant
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "message": "Create Point",
+                    "properties": { "shop": true },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth(username, Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //The stream only receives the delta of its own tenant
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let rows = conn.query("SELECT MAX(id) FROM deltas WHERE tenant = 2", &[]).unwrap();
+            let delta: i64 = rows.get(0).get(0);
+
+            let mut line = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            while !line.starts_with("id: ") {
+                line = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            }
+            assert_eq!(line, format!("id: {}", delta));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn tiles_cover() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "shop": true },
+                    "geometry": { "type": "Point", "coordinates": [ -77.03, 38.9 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Cover bbox
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/cover?bbox=-77.12,38.79,-76.91,38.99&minzoom=10&maxzoom=10").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([
+                [10, 292, 391],
+                [10, 292, 392],
+                [10, 293, 391],
+                [10, 293, 392]
+            ]));
+        }
+
+        { //Cover bbox across zooms
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/cover?bbox=-180,-90,180,90&maxzoom=1").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([
+                [0, 0, 0],
+                [1, 0, 0],
+                [1, 0, 1],
+                [1, 1, 0],
+                [1, 1, 1]
+            ]));
+        }
+
+        { //Cover feature
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/cover?feature=1&minzoom=14&maxzoom=14").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([
+                [14, 4686, 6267]
+            ]));
+        }
+
+        { //Cover missing feature
+            let resp = reqwest::get("http://localhost:8000/api/tiles/cover?feature=2&maxzoom=14").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Cover too many tiles
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/cover?bbox=-180,-90,180,90&maxzoom=8").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Tile cover of 87381 tiles exceeds the limit of 10000 tiles",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Cover requires a bbox or feature
+            let resp = reqwest::get("http://localhost:8000/api/tiles/cover?maxzoom=8").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Cover requires a maxzoom
+            let resp = reqwest::get("http://localhost:8000/api/tiles/cover?bbox=-1,-1,1,1").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+extern crate protobuf;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+    use protobuf::CodedInputStream;
+    use protobuf::wire_format::WireType;
+
+    ///
+    /// Decode the `id` attribute of every feature in a vector tile
+    ///
+    fn tile_ids(tile: &[u8]) -> Vec<i64> {
+        let mut ids = Vec::new();
+
+        let mut tile = CodedInputStream::from_bytes(tile);
+        while !tile.eof().unwrap() {
+            let (field, wire) = tile.read_tag_unpack().unwrap();
+            if field != 3 { tile.skip_field(wire).unwrap(); continue; } //Tile.layers
+
+            let layer = tile.read_bytes().unwrap();
+            let mut layer = CodedInputStream::from_bytes(&layer);
+
+            let mut keys: Vec<String> = Vec::new();
+            let mut values: Vec<i64> = Vec::new();
+            let mut features: Vec<Vec<u32>> = Vec::new();
+
+            while !layer.eof().unwrap() {
+                match layer.read_tag_unpack().unwrap() {
+                    (2, WireType::WireTypeLengthDelimited) => { //Layer.features
+                        let feature = layer.read_bytes().unwrap();
+                        let mut feature = CodedInputStream::from_bytes(&feature);
+
+                        let mut tags = Vec::new();
+                        while !feature.eof().unwrap() {
+                            match feature.read_tag_unpack().unwrap() {
+                                (2, WireType::WireTypeLengthDelimited) => { //Feature.tags
+                                    let packed = feature.read_bytes().unwrap();
+                                    let mut packed = CodedInputStream::from_bytes(&packed);
+                                    while !packed.eof().unwrap() {
+                                        tags.push(packed.read_raw_varint32().unwrap());
+                                    }
+                                },
+                                (_, wire) => feature.skip_field(wire).unwrap()
+                            }
+                        }
+
+                        features.push(tags);
+                    },
+                    (3, WireType::WireTypeLengthDelimited) => keys.push(layer.read_string().unwrap()), //Layer.keys
+                    (4, WireType::WireTypeLengthDelimited) => { //Layer.values
+                        let value = layer.read_bytes().unwrap();
+                        let mut value = CodedInputStream::from_bytes(&value);
+
+                        let mut int = 0;
+                        while !value.eof().unwrap() {
+                            match value.read_tag_unpack().unwrap() {
+                                (4, _) => int = value.read_int64().unwrap(),
+                                (5, _) => int = value.read_uint64().unwrap() as i64,
+                                (6, _) => int = value.read_sint64().unwrap(),
+                                (_, wire) => value.skip_field(wire).unwrap()
+                            }
+                        }
+
+                        values.push(int);
+                    },
+                    (_, wire) => layer.skip_field(wire).unwrap()
+                }
+            }
+
+            for tags in features {
+                for pair in tags.chunks(2) {
+                    if keys[pair[0] as usize] == "id" {
+                        ids.push(values[pair[1] as usize]);
+                    }
+                }
+            }
+        }
+
+        ids.sort();
+        ids
+    }
+
+
+    #[test]
+    fn tiles_debug() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        let client = reqwest::Client::new();
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        for coordinates in vec!["-97.734375, 56.559482483762245", "-10, 10", "50, 50"] { //Create Points
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {{}},
+                    "geometry": {{ "type": "Point", "coordinates": [ {} ] }}
+                }}"#, coordinates))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        let debug_ids = { //Debug tile in geographic coordinates
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/0/0/debug").send().unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["type"], json!("FeatureCollection"));
+
+            let features = json_body["features"].as_array().unwrap();
+            assert_eq!(features.len(), 2);
+
+            assert_eq!(features[0]["id"], json!(1));
+            assert_eq!(features[0]["properties"], json!({ "id": 1 }));
+            assert_eq!(features[0]["geometry"]["type"], json!("Point"));
+
+            let coords = features[0]["geometry"]["coordinates"].as_array().unwrap();
+            assert!((coords[0].as_f64().unwrap() - -97.734375).abs() < 0.1);
+            assert!((coords[1].as_f64().unwrap() - 56.559482483762245).abs() < 0.1);
+
+            features.iter().map(|feature| feature["id"].as_i64().unwrap()).collect::<Vec<i64>>()
+        };
+
+        { //Debug tile in tile coordinates
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/0/0/debug?coordinates=tile").send().unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let features = json_body["features"].as_array().unwrap();
+            assert_eq!(features.len(), 2);
+
+            for feature in features {
+                for coord in feature["geometry"]["coordinates"].as_array().unwrap() {
+                    let coord = coord.as_f64().unwrap();
+                    assert!(coord >= 0.0 && coord <= 4096.0);
+                }
+            }
+        }
+
+        { //Debug ids match the ids encoded in the tile
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/0/0").send().unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            assert_eq!(tile_ids(&body), debug_ids);
+            assert_eq!(debug_ids, vec![1, 2]);
+        }
+
+        { //Debug does not write to the tile cache
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("SELECT ref FROM tiles WHERE ref = '1/1/0'", &[]).unwrap();
+            assert_eq!(res.len(), 0);
+
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/1/0/debug").send().unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["features"][0]["id"], json!(3));
+
+            let res = conn.query("SELECT ref FROM tiles WHERE ref = '1/1/0'", &[]).unwrap();
+            assert_eq!(res.len(), 0);
+        }
+
+        { //Invalid coordinates
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/0/0/debug?coordinates=pixel").send().unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Coordinates - must be one of 'tile' or 'geographic'",
+                "status": "Bad Request"
+            }));
+        }
+
+        server.kill().unwrap();
+    }
+}
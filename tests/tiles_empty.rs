@@ -0,0 +1,102 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn tiles_empty() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        { //Insert a point in tile 1/0/0, leaving tile 1/1/1 without data
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                INSERT INTO geo (version, geom, props, deltas) VALUES
+                    (1, ST_SetSRID(ST_MakePoint(-97.734375, 56.559482483762245), 4326), '{}'::JSONB, ARRAY[]::BIGINT[])
+            ", &[]).unwrap();
+        }
+
+        let tile = |coords: &str| {
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/tiles/{}", coords)).unwrap();
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            (resp.status().as_u16(), body)
+        };
+
+        { //An empty tile is returned by default
+            let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            let (status, body) = tile("1/1/1");
+            assert_eq!(status, 200);
+            assert_eq!(body.len(), 0);
+
+            let (status, body) = tile("1/0/0");
+            assert_eq!(status, 200);
+            assert!(body.len() > 0);
+
+            server.kill().unwrap();
+            server.wait().unwrap();
+        }
+
+        { //A tile without features is a 204 in no-content mode
+            let mut server = Command::new("cargo").args(&[
+                "run",
+                "--",
+                "--config", env::current_dir().unwrap().join("tests/fixtures/config.tiles.empty.json").to_str().unwrap()
+            ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            let (status, body) = tile("1/1/1");
+            assert_eq!(status, 204);
+            assert_eq!(body.len(), 0);
+
+            //Cached empty tiles are also a 204
+            let (status, _) = tile("1/1/1");
+            assert_eq!(status, 204);
+
+            let (status, body) = tile("1/0/0");
+            assert_eq!(status, 200);
+            assert!(body.len() > 0);
+
+            server.kill().unwrap();
+        }
+    }
+}
@@ -0,0 +1,115 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn tiles_invalid() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        { //Insert a valid point and a self-intersecting polygon in the same tile
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                INSERT INTO geo (version, geom, props, deltas) VALUES
+                    (1, ST_SetSRID(ST_MakePoint(-97.734375, 56.559482483762245), 4326), '{}'::JSONB, ARRAY[]::BIGINT[]),
+                    (1, ST_GeomFromText('POLYGON((-100 50, -90 60, -90 50, -100 60, -100 50))', 4326), '{}'::JSONB, ARRAY[]::BIGINT[])
+            ", &[]).unwrap();
+
+            let res = conn.query("SELECT ST_IsValid(geom) FROM geo ORDER BY id", &[]).unwrap();
+            let valid: Vec<bool> = res.iter().map(|row| row.get(0)).collect();
+            assert_eq!(valid, vec![true, false]);
+        }
+
+        let tile = || {
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/1/0/0").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            body
+        };
+
+        let meta = || {
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/1/0/0/meta").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut json: serde_json::Value = resp.json().unwrap();
+            json.as_object_mut().unwrap().remove("created");
+            json
+        };
+
+        let repaired = { //Invalid geometry is repaired by default
+            let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            let body = tile();
+            assert!(body.len() > 0);
+            assert_eq!(meta(), json!({ "repaired": 1, "skipped": 0, "dropped": 0 }));
+
+            server.kill().unwrap();
+            server.wait().unwrap();
+
+            body
+        };
+
+        { //Invalid geometry is left out of the tile when skipped
+            let mut server = Command::new("cargo").args(&[
+                "run",
+                "--",
+                "--config", env::current_dir().unwrap().join("tests/fixtures/config.tiles.skip.json").to_str().unwrap()
+            ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            let skipped = tile();
+            assert_eq!(meta(), json!({ "repaired": 0, "skipped": 1, "dropped": 0 }));
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("DELETE FROM geo WHERE NOT ST_IsValid(geom)", &[]).unwrap();
+
+            //The skipped tile contains exactly the valid features
+            let valid = tile();
+            assert!(valid.len() > 0);
+            assert_eq!(skipped, valid);
+            assert!(repaired.len() > valid.len());
+
+            server.kill().unwrap();
+        }
+    }
+}
@@ -0,0 +1,133 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn tiles_max_features() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        { //Insert a dense cluster of 50 ranked points and 10 unranked points in the same tile
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                INSERT INTO geo (version, geom, props, deltas)
+                    SELECT
+                        1,
+                        ST_SetSRID(ST_MakePoint(-97.734375 + i * 0.01, 56.559482483762245), 4326),
+                        CASE WHEN i <= 50 THEN jsonb_build_object('rank', i) ELSE '{}'::JSONB END,
+                        ARRAY[]::BIGINT[]
+                    FROM generate_series(1, 60) i
+                    ORDER BY i
+            ", &[]).unwrap();
+        }
+
+        let ids = || {
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/1/0/0/debug").unwrap();
+            assert!(resp.status().is_success());
+
+            let json: serde_json::Value = resp.json().unwrap();
+            json["features"].as_array().unwrap().iter().map(|feat| feat["id"].as_i64().unwrap()).collect::<Vec<i64>>()
+        };
+
+        let meta = || {
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/1/0/0").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/1/0/0/meta").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut json: serde_json::Value = resp.json().unwrap();
+            json.as_object_mut().unwrap().remove("created");
+            json
+        };
+
+        { //Built in limit is applied without dropping features by default
+            let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            assert_eq!(ids().len(), 10);
+            assert_eq!(meta(), json!({ "repaired": 0, "skipped": 0, "dropped": 0 }));
+
+            server.kill().unwrap();
+            server.wait().unwrap();
+        }
+
+        { //Features with the highest priority are kept
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("DELETE FROM tiles", &[]).unwrap();
+
+            let mut server = Command::new("cargo").args(&[
+                "run",
+                "--",
+                "--config", env::current_dir().unwrap().join("tests/fixtures/config.tiles.max.json").to_str().unwrap()
+            ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            assert_eq!(ids(), (26..51).collect::<Vec<i64>>());
+            assert_eq!(meta(), json!({ "repaired": 0, "skipped": 0, "dropped": 35 }));
+
+            server.kill().unwrap();
+            server.wait().unwrap();
+        }
+
+        { //Features are sampled without a priority
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("DELETE FROM tiles", &[]).unwrap();
+
+            let mut server = Command::new("cargo").args(&[
+                "run",
+                "--",
+                "--config", env::current_dir().unwrap().join("tests/fixtures/config.tiles.sample.json").to_str().unwrap()
+            ]).spawn().unwrap();
+            thread::sleep(Duration::from_secs(1));
+
+            let sampled = ids();
+            assert_eq!(sampled.len(), 25);
+            assert_ne!(sampled, (26..51).collect::<Vec<i64>>());
+
+            //The sample is the same each time the tile is generated
+            assert_eq!(ids(), sampled);
+
+            assert_eq!(meta(), json!({ "repaired": 0, "skipped": 0, "dropped": 35 }));
+
+            server.kill().unwrap();
+        }
+    }
+}
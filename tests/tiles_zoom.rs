@@ -0,0 +1,89 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn tiles_zoom() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.tiles.zoom.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        let status = |coords: &str| {
+            reqwest::get(&*format!("http://localhost:8000/api/tiles/{}", coords)).unwrap().status().as_u16()
+        };
+
+        { //Below the minimum zoom
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/1/0/0").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+            assert_eq!(resp.json::<serde_json::Value>().unwrap(), json!({
+                "code": 404,
+                "reason": "Tile Not Found",
+                "status": "Not Found"
+            }));
+        }
+
+        { //Above the maximum zoom
+            assert_eq!(status("15/9372/12535"), 404);
+            assert_eq!(status("22/1199637/1604589"), 404);
+        }
+
+        { //Within the range
+            assert_eq!(status("2/1/1"), 200);
+            assert_eq!(status("14/4686/6267"), 200);
+        }
+
+        { //Meta and debug follow the same range
+            assert_eq!(status("1/0/0/meta"), 404);
+            assert_eq!(status("15/9372/12535/meta"), 404);
+            assert_eq!(status("14/4686/6267/meta"), 200);
+
+            assert_eq!(status("1/0/0/debug"), 404);
+            assert_eq!(status("15/9372/12535/debug"), 404);
+            assert_eq!(status("14/4686/6267/debug"), 200);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn timing() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.timing.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Username (nick)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=nick&password=yeaheh&email=nick@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Make ingalls an admin
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE username = 'ingalls'
+            ", &[]).unwrap();
+        }
+
+        { //Feature query as an admin reports timings
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let timing = resp.headers().get("Server-Timing").unwrap().to_str().unwrap();
+            let metrics: Vec<&str> = timing.split(", ").map(|metric| metric.split(';').next().unwrap()).collect();
+            assert_eq!(metrics, vec!["auth", "db", "serialize", "total"]);
+
+            for metric in timing.split(", ") {
+                assert!(metric.split(';').nth(1).unwrap().starts_with("dur="));
+            }
+        }
+
+        { //Feature query as a user does not report timings
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1")
+                .basic_auth("nick", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+            assert!(resp.headers().get("Server-Timing").is_none());
+        }
+
+        { //Feature query without auth does not report timings
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,1,1").unwrap();
+            assert!(resp.status().is_success());
+            assert!(resp.headers().get("Server-Timing").is_none());
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn token_auth() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username (ingalls)
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let session: String = { //Create Session
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/session")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let cookie = resp.headers().get(reqwest::header::SET_COOKIE).unwrap().to_str().unwrap();
+            String::from(cookie.split(';').next().unwrap().trim_start_matches("session="))
+        };
+
+        let api: String = { //Create API Token
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/user/token")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            String::from(json_body["token"].as_str().unwrap())
+        };
+
+        { //Authenticate with the session cookie
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::COOKIE, format!("session={}", session))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["username"], json!("ingalls"));
+        }
+
+        { //Authenticate with the API token
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::AUTHORIZATION, format!("Bearer {}", api))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["username"], json!("ingalls"));
+        }
+
+        { //An unknown token is rejected
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/info")
+                .header(reqwest::header::AUTHORIZATION, "Bearer not-a-token")
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn topology(url: &str) -> serde_json::value::Value {
+        let mut resp = reqwest::get(url).unwrap();
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get(reqwest::header::CONTENT_TYPE).unwrap(), "application/json");
+
+        resp.json().unwrap()
+    }
+
+    ///
+    /// Count how many arcs consist of exactly the given two points in either direction
+    ///
+    fn count_arc(topology: &serde_json::value::Value, a: serde_json::value::Value, b: serde_json::value::Value) -> usize {
+        topology["arcs"].as_array().unwrap().iter().filter(|arc| {
+            **arc == json!([a, b]) || **arc == json!([b, a])
+        }).count()
+    }
+
+    #[test]
+    fn topojson() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Adjacent Polygons
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Adjacent Boundaries",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "name": "west"
+                        },
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [[[0,0], [1,0], [1,1], [0,1], [0,0]]]
+                        }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "name": "east"
+                        },
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [[[1,0], [2,0], [2,1], [1,1], [1,0]]]
+                        }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //BBOX TopoJSON
+            let topology = topology("http://localhost:8000/api/data/features?bbox=-1,-1,3,3&format=topojson");
+
+            assert_eq!(topology["type"], json!("Topology"));
+            assert_eq!(topology["bbox"], json!([0.0, 0.0, 2.0, 1.0]));
+            assert_eq!(topology["arcs"].as_array().unwrap().len(), 3);
+
+            //The shared edge appears once
+            assert_eq!(count_arc(&topology, json!([1.0, 0.0]), json!([1.0, 1.0])), 1);
+
+            let geoms = topology["objects"]["features"]["geometries"].as_array().unwrap();
+            assert_eq!(geoms.len(), 2);
+
+            let mut names: Vec<&str> = geoms.iter().map(|geom| {
+                assert_eq!(geom["type"], json!("Polygon"));
+                assert_eq!(geom["arcs"].as_array().unwrap().len(), 1);
+                geom["properties"]["name"].as_str().unwrap()
+            }).collect();
+            names.sort();
+            assert_eq!(names, vec!["east", "west"]);
+
+            //Both polygons reference the shared arc, one of them in reverse
+            let mut arcs: Vec<i64> = Vec::new();
+            for geom in geoms {
+                for id in geom["arcs"][0].as_array().unwrap() {
+                    arcs.push(id.as_i64().unwrap());
+                }
+            }
+            assert!(arcs.iter().any(|id| *id < 0 && arcs.contains(&!*id)));
+        }
+
+        { //Quantized TopoJSON
+            let topology = topology("http://localhost:8000/api/data/features?bbox=-1,-1,3,3&format=topojson&quantization=3");
+
+            assert_eq!(topology["transform"], json!({
+                "scale": [1.0, 0.5],
+                "translate": [0.0, 0.0]
+            }));
+            assert_eq!(topology["arcs"].as_array().unwrap().len(), 3);
+
+            //Delta encoded from (1, 0) to (1, 2) or the reverse
+            let shared = topology["arcs"].as_array().unwrap().iter().filter(|arc| {
+                **arc == json!([[1, 0], [0, 2]]) || **arc == json!([[1, 2], [0, -2]])
+            }).count();
+            assert_eq!(shared, 1);
+        }
+
+        { //Clone TopoJSON
+            let topology = topology("http://localhost:8000/api/data/clone?format=topojson");
+
+            assert_eq!(topology["arcs"].as_array().unwrap().len(), 3);
+            assert_eq!(count_arc(&topology, json!([1.0, 0.0]), json!([1.0, 1.0])), 1);
+            assert_eq!(topology["objects"]["features"]["geometries"].as_array().unwrap().len(), 2);
+        }
+
+        { //GeoJSON remains the default
+            let mut resp = reqwest::get("http://localhost:8000/api/data/clone").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            assert!(body.ends_with("\u{4}"));
+            assert_eq!(body.trim_end_matches('\u{4}').lines().count(), 2);
+        }
+
+        { //Invalid Params
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,3,3&format=shapefile").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let resp = reqwest::get("http://localhost:8000/api/data/features?bbox=-1,-1,3,3&format=topojson&quantization=1").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
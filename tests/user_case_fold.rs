@@ -0,0 +1,108 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn create(username: &str, password: &str) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.get("http://localhost:8000/api/user/create")
+            .query(&[("username", username), ("password", password), ("email", &*format!("{}@example.com", username))])
+            .send()
+            .unwrap()
+    }
+
+    fn info(username: &str, password: &str) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.get("http://localhost:8000/api/user/info")
+            .basic_auth(username, Some(password))
+            .send()
+            .unwrap()
+    }
+
+    #[test]
+    fn user_case_fold() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.user.case_fold.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = create("Alice", "yeaheh");
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Usernames that only differ by case collide
+            for username in vec!["alice", "ALICE"] {
+                let mut resp = create(username, "yeaheh");
+                assert_eq!(resp.status().as_u16(), 409);
+
+                let json_body: serde_json::value::Value = resp.json().unwrap();
+                assert_eq!(json_body, json!({
+                    "code": 409,
+                    "status": "Conflict",
+                    "error": "CONFLICT",
+                    "reason": "User/Email Exists"
+                }));
+            }
+        }
+
+        { //Login is case insensitive, the username is displayed as registered
+            for username in vec!["Alice", "alice", "aLiCe"] {
+                let mut resp = info(username, "yeaheh");
+                assert!(resp.status().is_success());
+
+                let json_body: serde_json::value::Value = resp.json().unwrap();
+                assert_eq!(json_body["id"], json!(1));
+                assert_eq!(json_body["username"], json!("Alice"));
+            }
+        }
+
+        { //Password is still case sensitive
+            let resp = info("alice", "YEAHEH");
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,175 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn reset(username: &str, password: &str, id: i64, body: Option<&str>) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        let mut req = client.post(&*format!("http://localhost:8000/api/user/{}/password/reset", id))
+            .basic_auth(username, Some(password));
+
+        if let Some(body) = body {
+            req = req.body(String::from(body)).header(reqwest::header::CONTENT_TYPE, "application/json");
+        }
+
+        req.send().unwrap()
+    }
+
+    fn info(username: &str, password: &str) -> u16 {
+        let client = reqwest::Client::new();
+        let resp = client.get("http://localhost:8000/api/user/info")
+            .basic_auth(username, Some(password))
+            .send()
+            .unwrap();
+
+        resp.status().as_u16()
+    }
+
+    fn change(username: &str, password: &str, new: &str) -> u16 {
+        let client = reqwest::Client::new();
+        let resp = client.post("http://localhost:8000/api/user/password")
+            .basic_auth(username, Some(password))
+            .body(json!({ "password": new }).to_string())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .unwrap();
+
+        resp.status().as_u16()
+    }
+
+    #[test]
+    fn user_password_reset() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Admin
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Create User
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls2&password=yeaheh&email=ingalls2@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create a session for the user
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/session")
+                .basic_auth("ingalls2", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Non-admins cannot reset passwords, including their own
+            let resp = reset("ingalls2", "yeaheh", 1, None);
+            assert_eq!(resp.status().as_u16(), 401);
+
+            let resp = reset("ingalls2", "yeaheh", 2, None);
+            assert_eq!(resp.status().as_u16(), 401);
+
+            assert_eq!(info("ingalls", "yeaheh"), 200);
+        }
+
+        let temporary = { //Admin resets the password to a generated one
+            let mut resp = reset("ingalls", "yeaheh", 2, None);
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(2));
+
+            String::from(json_body["password"].as_str().unwrap())
+        };
+
+        {
+            assert_eq!(temporary.len(), 16);
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let tokens = conn.query("SELECT 1 FROM users_tokens WHERE uid = 2", &[]).unwrap();
+            assert_eq!(tokens.len(), 0);
+
+            let audit = conn.query("SELECT uid, impersonator FROM audit WHERE action = 'password_reset'", &[]).unwrap();
+            assert_eq!(audit.len(), 1);
+            assert_eq!(audit.get(0).get::<_, Option<i64>>(0), Some(2));
+            assert_eq!(audit.get(0).get::<_, Option<i64>>(1), Some(1));
+        }
+
+        { //The old password no longer works and the temporary one must be changed
+            assert_eq!(info("ingalls2", "yeaheh"), 401);
+            assert_eq!(info("ingalls2", &temporary), 403);
+
+            assert_eq!(change("ingalls2", "yeaheh", "n3w-passw0rd"), 401);
+            assert_eq!(change("ingalls2", &temporary, &temporary), 400);
+            assert_eq!(change("ingalls2", &temporary, "n3w-passw0rd"), 200);
+
+            assert_eq!(info("ingalls2", &temporary), 401);
+            assert_eq!(info("ingalls2", "n3w-passw0rd"), 200);
+        }
+
+        { //Admin resets the password of another admin to a given one
+            let mut resp = reset("ingalls", "yeaheh", 1, Some(r#"{ "password": "g1ven-passw0rd" }"#));
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({ "id": 1 }));
+
+            assert_eq!(info("ingalls", "yeaheh"), 401);
+            assert_eq!(info("ingalls", "g1ven-passw0rd"), 403);
+            assert_eq!(change("ingalls", "g1ven-passw0rd", "an0ther-passw0rd"), 200);
+            assert_eq!(info("ingalls", "an0ther-passw0rd"), 200);
+        }
+
+        { //Unknown users cannot be reset
+            let resp = reset("ingalls", "an0ther-passw0rd", 3, None);
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,121 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::env;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn create(username: &str, password: &str) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.get("http://localhost:8000/api/user/create")
+            .query(&[("username", username), ("password", password), ("email", &*format!("{}@example.com", username))])
+            .send()
+            .unwrap()
+    }
+
+    #[test]
+    fn user_policy() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--config", env::current_dir().unwrap().join("tests/fixtures/config.user.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Reserved Username
+            let mut resp = create("admin", "correct horse battery staple");
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Username Reserved",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Reserved Username - Case Insensitive
+            let mut resp = create("ROOT", "correct horse battery staple");
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["reason"], json!("Username Reserved"));
+        }
+
+        { //Invalid Username Charset
+            let mut resp = create("ingalls!", "correct horse battery staple");
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Username - contains characters or a length not allowed by this server",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Weak Password
+            let mut resp = create("ingalls", "yeaheh");
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Weak Password - must have an estimated entropy of at least 40 bits",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Rejected users are not created
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("SELECT count(*) FROM users", &[]).unwrap();
+            let count: i64 = rows.get(0).get(0);
+            assert_eq!(count, 0);
+        }
+
+        { //Valid User
+            let mut resp = create("ingalls", "correct horse battery staple");
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    fn patch(id: i64, body: serde_json::value::Value) -> reqwest::Response {
+        let client = reqwest::Client::new();
+        client.patch(&*format!("http://localhost:8000/api/user/{}", id))
+            .body(body.to_string())
+            .basic_auth("ingalls", Some("yeaheh"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .unwrap()
+    }
+
+    #[test]
+    fn user_profile() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Users
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls2&password=yeaheh&email=ingalls2@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //View own profile
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "username": "ingalls",
+                "email": "ingalls@protonmail.com",
+                "meta": {}
+            }));
+        }
+
+        { //Self update is allowed
+            let mut resp = patch(1, json!({
+                "email": "nick@example.com",
+                "meta": {
+                    "display_name": "Nick"
+                }
+            }));
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "username": "ingalls",
+                "email": "nick@example.com",
+                "meta": {
+                    "display_name": "Nick"
+                }
+            }));
+        }
+
+        { //Omitted fields are unchanged
+            let mut resp = patch(1, json!({
+                "meta": {
+                    "display_name": "Nicholas"
+                }
+            }));
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["email"], json!("nick@example.com"));
+            assert_eq!(json_body["meta"], json!({ "display_name": "Nicholas" }));
+        }
+
+        { //Updating another user requires admin
+            let resp = patch(2, json!({
+                "meta": {
+                    "display_name": "Not Me"
+                }
+            }));
+            assert_eq!(resp.status().as_u16(), 401);
+
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/user/2")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Email validation
+            let mut resp = patch(1, json!({
+                "email": "not an email"
+            }));
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Email",
+                "status": "Bad Request"
+            }));
+
+            let mut resp = patch(1, json!({
+                "email": "ingalls2@protonmail.com"
+            }));
+            assert_eq!(resp.status().as_u16(), 409);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["error"], json!("CONFLICT"));
+            assert_eq!(json_body["reason"], json!("User/Email Exists"));
+        }
+
+        { //Meta validation
+            let resp = patch(1, json!({
+                "meta": "Nick"
+            }));
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let resp = patch(1, json!({
+                "meta": {
+                    "bio": "a".repeat(9000)
+                }
+            }));
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //Admin can update another user
+            let mut resp = patch(2, json!({
+                "meta": {
+                    "display_name": "Support Edit"
+                }
+            }));
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 2,
+                "username": "ingalls2",
+                "email": "ingalls2@protonmail.com",
+                "meta": {
+                    "display_name": "Support Edit"
+                }
+            }));
+        }
+
+        { //Missing user
+            let resp = patch(3, json!({
+                "meta": {}
+            }));
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
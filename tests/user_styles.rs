@@ -0,0 +1,173 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn user_styles() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        for username in vec!["ingalls", "wilder", "boss"] { //Create Usernames
+            let mut resp = reqwest::get(&*format!("http://localhost:8000/api/user/create?username={}&password=yeaheh&email={}@protonmail.com", username, username)).unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE username = 'boss';
+            ", &[]).unwrap();
+        }
+
+        for (username, name) in vec![("ingalls", "Ingalls Private"), ("ingalls", "Ingalls Public"), ("wilder", "Wilder Private")] { //Create Styles
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/style")
+                .body(format!(r#"{{
+                    "name": "{}",
+                    "style": "I am a style"
+                }}"#, name))
+                .basic_auth(username, Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Mark Style 2 as Public
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/style/2/public")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Caller gets their private & public styles
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/styles")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([{
+                "id": 1,
+                "name": "Ingalls Private",
+                "public": false,
+                "uid": 1,
+                "username": "ingalls"
+            }, {
+                "id": 2,
+                "name": "Ingalls Public",
+                "public": true,
+                "uid": 1,
+                "username": "ingalls"
+            }]));
+        }
+
+        { //Another user only gets their own styles
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/styles")
+                .basic_auth("wilder", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([{
+                "id": 3,
+                "name": "Wilder Private",
+                "public": false,
+                "uid": 2,
+                "username": "wilder"
+            }]));
+        }
+
+        { //Admins only get their own styles
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/styles")
+                .basic_auth("boss", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([]));
+        }
+
+        { //Limit
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/user/styles?limit=1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get("X-Hecate-Limit").unwrap(), "1");
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 1);
+            assert_eq!(json_body[0]["id"], json!(1));
+        }
+
+        { //Anonymous
+            let mut resp = reqwest::get("http://localhost:8000/api/user/styles").unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 401,
+                "reason": "You must be logged in to access this resource",
+                "status": "Unauthorized"
+            }));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -71,8 +71,15 @@ mod test {
 
         { //Create Username Duplicate Error
             let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls3@protonmail.com").unwrap();
-            assert_eq!(resp.text().unwrap(), "{\"code\":400,\"reason\":\"User/Email Exists\",\"status\":\"Bad Request\"}");
-            assert!(resp.status().is_client_error());
+            assert_eq!(resp.status().as_u16(), 409);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 409,
+                "status": "Conflict",
+                "error": "CONFLICT",
+                "reason": "User/Email Exists"
+            }));
         }
 
         { //Feature Upload with no auth Fail
@@ -290,6 +297,42 @@ mod test {
             }]));
         }
 
+        { //Test User Listing w/ Fields
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/users?filter=ingalls&limit=2&fields=username")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body, json!([{
+                "username": "ingalls",
+            },{
+                "username": "ingalls2",
+            }]));
+        }
+
+        { //Test User Listing w/ Unknown Field
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/users?fields=id,password")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid Field - 'password' must be one of id, access, username",
+                "status": "Bad Request"
+            }));
+        }
+
         { //Test User Listing w/ Filtering - no match
             let client = reqwest::Client::new();
             let mut resp = client.get("http://localhost:8000/api/users?filter=kp")
@@ -0,0 +1,285 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    ///
+    /// Accept the given number of requests, replying to each with a 200 and
+    /// passing the body of each request back to the test
+    ///
+    fn serve(count: usize) -> (String, mpsc::Receiver<serde_json::Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for _ in 0..count {
+                let (mut stream, _) = listener.accept().unwrap();
+
+                let mut req: Vec<u8> = Vec::new();
+                let mut buf = [0; 1024];
+                let body = loop {
+                    let size = stream.read(&mut buf).unwrap();
+                    req.extend_from_slice(&buf[..size]);
+
+                    let req_str = String::from_utf8_lossy(&req).to_string();
+                    if let Some(end) = req_str.find("\r\n\r\n") {
+                        let length: usize = req_str.lines()
+                            .find(|line| line.to_lowercase().starts_with("content-length:"))
+                            .map(|line| line[15..].trim().parse().unwrap())
+                            .unwrap_or(0);
+
+                        if req.len() >= end + 4 + length {
+                            break String::from(&req_str[end + 4..end + 4 + length]);
+                        }
+                    }
+
+                    if size == 0 {
+                        break String::new();
+                    }
+                };
+
+                stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+                tx.send(serde_json::from_str(&body).unwrap()).unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    #[test]
+    fn webhooks_backfill() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        for i in 0..3 { //Create Points before any webhook exists
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(format!(r#"{{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": {{ "number": {} }},
+                    "geometry": {{ "type": "Point", "coordinates": [ {}, 0 ] }}
+                }}"#, i, i))
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        let (url, rx) = serve(3);
+
+        { //Create Webhook
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "rebuilt",
+                    "url": url,
+                    "actions": ["delta"]
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Backfill requires admin
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/1/backfill?since=1").send().unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Backfill the deltas after delta 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks/1/backfill?since=1")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "webhook": 1,
+                "since": 1,
+                "last": 1,
+                "status": "running",
+                "error": null
+            }));
+        }
+
+        for id in vec![2, 3] { //Deltas are replayed in order
+            let event = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+            assert_eq!(event, json!({
+                "id": id,
+                "schema_version": 1,
+                "type": "delta.finalize",
+                "counts": { "create": 1, "modify": 0, "delete": 0, "restore": 0 },
+                "total": 1
+            }));
+        }
+
+        thread::sleep(Duration::from_secs(1));
+
+        { //Backfill is complete
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/webhooks/1/backfill")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "webhook": 1,
+                "since": 1,
+                "last": 3,
+                "status": "complete",
+                "error": null
+            }));
+        }
+
+        { //Replayed deliveries are logged
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("SELECT count(*) FROM webhooks_deliveries WHERE webhook = 1 AND status = 200", &[]).unwrap();
+            let count: i64 = res.get(0).get(0);
+            assert_eq!(count, 2);
+        }
+
+        { //Simulate a backfill from the start that failed after delivering delta 2
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE webhooks_backfills SET since = 0, last = 2, status = 'failed', error = 'Failed to deliver delta 3: Status 500';
+            ", &[]).unwrap();
+        }
+
+        { //Backfill resumes from the last delivered delta
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks/1/backfill?since=0")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "webhook": 1,
+                "since": 0,
+                "last": 2,
+                "status": "running",
+                "error": null
+            }));
+
+            let event = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+            assert_eq!(event["id"], json!(3));
+            assert_eq!(event["type"], json!("delta.finalize"));
+        }
+
+        { //Create Webhook not subscribed to deltas
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "users",
+                    "url": "http://127.0.0.1:1/hook",
+                    "actions": ["user"]
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Backfill requires a delta webhook
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks/2/backfill?since=0")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Webhook is not subscribed to delta events",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Backfill of an unknown webhook
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/3/backfill?since=0")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
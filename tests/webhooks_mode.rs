@@ -0,0 +1,243 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    ///
+    /// Accept a single request, reply with the given status line and
+    /// pass the raw request back to the test
+    ///
+    fn serve(status: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut req: Vec<u8> = Vec::new();
+            let mut buf = [0; 1024];
+            loop {
+                let size = stream.read(&mut buf).unwrap();
+                req.extend_from_slice(&buf[..size]);
+
+                let req_str = String::from_utf8_lossy(&req).to_string();
+                match req_str.find("\r\n\r\n") {
+                    Some(end) => {
+                        let length: usize = req_str.lines()
+                            .find(|line| line.to_lowercase().starts_with("content-length:"))
+                            .map(|line| line[15..].trim().parse().unwrap())
+                            .unwrap_or(0);
+
+                        if req.len() >= end + 4 + length {
+                            break;
+                        }
+                    },
+                    None => ()
+                };
+
+                if size == 0 {
+                    break;
+                }
+            }
+
+            stream.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).as_bytes()).unwrap();
+            tx.send(String::from_utf8(req).unwrap()).unwrap();
+        });
+
+        (url, rx)
+    }
+
+    #[test]
+    fn webhooks_mode() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        let (url, rx) = serve("200 OK");
+
+        { //Create Webhook - delta mode
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "summary",
+                    "url": url,
+                    "actions": ["delta"],
+                    "mode": "delta"
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "name": "summary",
+                "url": url,
+                "actions": ["delta"],
+                "mode": "delta"
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Webhook - feature mode, delivered to a closed port so only the log is checked
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "features",
+                    "url": "http://127.0.0.1:1/hook",
+                    "actions": ["delta"],
+                    "mode": "feature"
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Webhook - invalid mode
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "invalid",
+                    "url": "http://127.0.0.1:1/hook",
+                    "actions": ["delta"],
+                    "mode": "batch"
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Bulk Import
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Bulk Import",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "1" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "2" },
+                        "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "3" },
+                        "geometry": { "type": "Point", "coordinates": [ 3, 3 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Delta mode receives a single summary event
+            let req = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+            assert!(req.to_lowercase().contains("x-hecate-event: delta.finalize"));
+
+            let body: serde_json::Value = serde_json::from_str(&req[req.find("\r\n\r\n").unwrap() + 4..]).unwrap();
+            assert_eq!(body, json!({
+                "id": 1,
+                "schema_version": 1,
+                "type": "delta.finalize",
+                "counts": {
+                    "create": 3,
+                    "modify": 0,
+                    "delete": 0,
+                    "restore": 0
+                },
+                "total": 3
+            }));
+        }
+
+        thread::sleep(Duration::from_secs(1));
+
+        { //One delivery for the delta mode webhook, one per feature for the feature mode webhook
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("
+                SELECT webhook, event FROM webhooks_deliveries ORDER BY webhook, id
+            ", &[]).unwrap();
+
+            let deliveries: Vec<(i64, String)> = rows.iter().map(|row| (row.get(0), row.get(1))).collect();
+
+            assert_eq!(deliveries, vec![
+                (1, String::from("delta.finalize")),
+                (2, String::from("delta.feature")),
+                (2, String::from("delta.feature")),
+                (2, String::from("delta.feature"))
+            ]);
+        }
+
+        server.kill().unwrap();
+    }
+}
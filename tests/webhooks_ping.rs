@@ -0,0 +1,314 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    ///
+    /// Accept a single request, reply with the given status line and
+    /// pass the raw request back to the test
+    ///
+    fn serve(status: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut req: Vec<u8> = Vec::new();
+            let mut buf = [0; 1024];
+            loop {
+                let size = stream.read(&mut buf).unwrap();
+                req.extend_from_slice(&buf[..size]);
+
+                let req_str = String::from_utf8_lossy(&req).to_string();
+                match req_str.find("\r\n\r\n") {
+                    Some(end) => {
+                        let length: usize = req_str.lines()
+                            .find(|line| line.to_lowercase().starts_with("content-length:"))
+                            .map(|line| line[15..].trim().parse().unwrap())
+                            .unwrap_or(0);
+
+                        if req.len() >= end + 4 + length {
+                            break;
+                        }
+                    },
+                    None => ()
+                };
+
+                if size == 0 {
+                    break;
+                }
+            }
+
+            stream.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).as_bytes()).unwrap();
+            tx.send(String::from_utf8(req).unwrap()).unwrap();
+        });
+
+        (url, rx)
+    }
+
+    #[test]
+    fn webhooks_ping() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        let (ok_url, ok_rx) = serve("200 OK");
+        let (err_url, err_rx) = serve("500 Internal Server Error");
+
+        { //Create Webhook - 200
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "healthy",
+                    "url": ok_url,
+                    "actions": ["delta"],
+                    "secret": "shhh"
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "name": "healthy",
+                "url": ok_url,
+                "actions": ["delta"]
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Webhook - 500
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "broken",
+                    "url": err_url,
+                    "actions": ["delta"]
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Ping - No Auth
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/1/ping")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Ping - 200
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks/1/ping")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            let mut json_body: serde_json::value::Value = resp.json().unwrap();
+            assert!(json_body["latency"].is_i64());
+            json_body.as_object_mut().unwrap().remove("latency");
+
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "event": "webhook.ping",
+                "success": true,
+                "status": 200,
+                "error": null
+            }));
+            assert!(resp.status().is_success());
+
+            let req = ok_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            let body = r#"{"id":1,"schema_version":1,"type":"webhook.ping"}"#;
+            assert!(req.to_lowercase().contains("x-hecate-event: webhook.ping"));
+            assert!(req.ends_with(body));
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let rows = conn.query("SELECT encode(hmac($1::TEXT, 'shhh', 'sha256'), 'hex')", &[&body]).unwrap();
+            let signature: String = rows.get(0).get(0);
+            assert!(req.to_lowercase().contains(&format!("x-hecate-signature: sha256={}", signature)));
+        }
+
+        { //Ping - 500
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks/2/ping")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            let mut json_body: serde_json::value::Value = resp.json().unwrap();
+            json_body.as_object_mut().unwrap().remove("latency");
+
+            assert_eq!(json_body, json!({
+                "id": 2,
+                "event": "webhook.ping",
+                "success": false,
+                "status": 500,
+                "error": null
+            }));
+            assert!(resp.status().is_success());
+
+            let req = err_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(!req.to_lowercase().contains("x-hecate-signature"));
+        }
+
+        { //Ping - Webhook Not Found
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/3/ping")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Deliveries are logged
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("
+                SELECT webhook, event, status FROM webhooks_deliveries ORDER BY id
+            ", &[]).unwrap();
+
+            assert_eq!(rows.len(), 2);
+
+            let webhook: i64 = rows.get(0).get(0);
+            let event: String = rows.get(0).get(1);
+            let status: Option<i32> = rows.get(0).get(2);
+            assert_eq!((webhook, event.as_str(), status), (1, "webhook.ping", Some(200)));
+
+            let webhook: i64 = rows.get(1).get(0);
+            let status: Option<i32> = rows.get(1).get(2);
+            assert_eq!((webhook, status), (2, Some(500)));
+        }
+
+        let (moved_url, moved_rx) = serve("200 OK");
+
+        { //Update Webhook - Omitting the secret keeps it
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/1")
+                .body(json!({
+                    "name": "moved",
+                    "url": moved_url,
+                    "actions": ["delta"]
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Ping - Still signed after the update
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/1/ping")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let req = moved_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            let body = r#"{"id":1,"schema_version":1,"type":"webhook.ping"}"#;
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let rows = conn.query("SELECT encode(hmac($1::TEXT, 'shhh', 'sha256'), 'hex')", &[&body]).unwrap();
+            let signature: String = rows.get(0).get(0);
+            assert!(req.to_lowercase().contains(&format!("x-hecate-signature: sha256={}", signature)));
+        }
+
+        let (cleared_url, cleared_rx) = serve("200 OK");
+
+        { //Update Webhook - An empty secret clears it
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/1")
+                .body(json!({
+                    "name": "moved",
+                    "url": cleared_url,
+                    "actions": ["delta"],
+                    "secret": ""
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Ping - No longer signed
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/1/ping")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let req = cleared_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(!req.to_lowercase().contains("x-hecate-signature"));
+        }
+
+        server.kill().unwrap();
+    }
+}
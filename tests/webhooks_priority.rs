@@ -0,0 +1,325 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    ///
+    /// Accept a single request, reply with the given status line and
+    /// pass the raw request back to the test
+    ///
+    fn serve(status: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut req: Vec<u8> = Vec::new();
+            let mut buf = [0; 1024];
+            loop {
+                let size = stream.read(&mut buf).unwrap();
+                req.extend_from_slice(&buf[..size]);
+
+                let req_str = String::from_utf8_lossy(&req).to_string();
+                match req_str.find("\r\n\r\n") {
+                    Some(end) => {
+                        let length: usize = req_str.lines()
+                            .find(|line| line.to_lowercase().starts_with("content-length:"))
+                            .map(|line| line[15..].trim().parse().unwrap())
+                            .unwrap_or(0);
+
+                        if req.len() >= end + 4 + length {
+                            break;
+                        }
+                    },
+                    None => ()
+                };
+
+                if size == 0 {
+                    break;
+                }
+            }
+
+            stream.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).as_bytes()).unwrap();
+            tx.send(String::from_utf8(req).unwrap()).unwrap();
+        });
+
+        (url, rx)
+    }
+
+    #[test]
+    fn webhooks_priority() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeaheh&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        let (notifier_url, notifier_rx) = serve("200 OK");
+        let (indexer_url, indexer_rx) = serve("200 OK");
+
+        { //Create Webhook - notifier, default priority
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "notifier",
+                    "url": notifier_url,
+                    "actions": ["delta"],
+                    "priority": 0
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(1));
+        }
+
+        { //Create Webhook - indexer, higher priority
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "indexer",
+                    "url": indexer_url,
+                    "actions": ["delta"],
+                    "priority": 10
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(2));
+        }
+
+        { //Webhooks are listed in priority order
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/webhooks")
+                .basic_auth("ingalls", Some("yeaheh"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!([{
+                "id": 2,
+                "name": "indexer",
+                "url": indexer_url,
+                "actions": ["delta"],
+                "priority": 10
+            },{
+                "id": 1,
+                "name": "notifier",
+                "url": notifier_url,
+                "actions": ["delta"],
+                "priority": 0
+            }]));
+        }
+
+        { //Import Delta 1
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "number": "1" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Both webhooks receive the event
+            notifier_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            indexer_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+
+        thread::sleep(Duration::from_secs(1));
+
+        { //The higher priority webhook is delivered to first
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("
+                SELECT webhook, status FROM webhooks_deliveries ORDER BY id
+            ", &[]).unwrap();
+
+            let deliveries: Vec<(i64, Option<i32>)> = rows.iter().map(|row| (row.get(0), row.get(1))).collect();
+
+            assert_eq!(deliveries, vec![
+                (2, Some(200)),
+                (1, Some(200))
+            ]);
+        }
+
+        let (indexer_url, indexer_rx) = serve("200 OK");
+
+        { //Update Webhook - indexer
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/webhooks/2")
+                .body(json!({
+                    "name": "indexer",
+                    "url": indexer_url,
+                    "actions": ["delta"],
+                    "priority": 10
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Webhook - archive, highest priority & not blocking, delivered to a closed port
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "archive",
+                    "url": "http://127.0.0.1:1/hook",
+                    "actions": ["delta"],
+                    "priority": 20
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(3));
+        }
+
+        { //Create Webhook - gate, blocking, delivered to a closed port
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/webhooks")
+                .body(json!({
+                    "name": "gate",
+                    "url": "http://127.0.0.1:1/hook",
+                    "actions": ["delta"],
+                    "priority": 5,
+                    "blocking": true
+                }).to_string())
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(4));
+        }
+
+        { //Import Delta 2
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Create Point",
+                    "properties": { "number": "2" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeaheh"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //The indexer receives the event despite the failed non-blocking archive delivery
+            indexer_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+
+        thread::sleep(Duration::from_secs(1));
+
+        { //The failed gate delivery blocks delivery to the lower priority notifier
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let rows = conn.query("
+                SELECT webhook, status, error FROM webhooks_deliveries WHERE id > 2 ORDER BY id
+            ", &[]).unwrap();
+
+            let deliveries: Vec<(i64, Option<i32>)> = rows.iter().map(|row| (row.get(0), row.get(1))).collect();
+
+            assert_eq!(deliveries, vec![
+                (3, None),
+                (2, Some(200)),
+                (4, None),
+                (1, None)
+            ]);
+
+            let error: Option<String> = rows.get(3).get(2);
+            assert_eq!(error, Some(String::from("Blocked by a failed delivery to webhook 4")));
+        }
+
+        server.kill().unwrap();
+    }
+}